@@ -0,0 +1,308 @@
+//! Cached access decisions
+//!
+//! Masking a viewer's frames needs their access level, and reading it from
+//! the wrapper account on every frame is far too slow. An `AccessCache`
+//! keeps each viewer's level and follows the wrapper's subscription stream,
+//! dropping a viewer's entry as soon as a grant or revocation for them
+//! lands. Entries past their time to live are still served for a while
+//! after, while a background refresh fetches the current level, so frames
+//! never wait on the RPC node for a viewer already seen.
+
+use futures::StreamExt;
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use synchronicity_mask::{VrmData, WrapperAccess};
+
+use crate::client::GlitchGangPrivacyClient;
+use crate::updates::WrapperUpdate;
+
+/// Default time an access level is served without revalidation
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+/// Default time an expired access level is still served while it is refreshed
+pub const DEFAULT_STALE_FOR: Duration = Duration::from_secs(10 * 60);
+
+/// Cached access level of one viewer
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// Access level, `u8::MAX` for the owner
+    access_level: u8,
+    /// When the level was read from the wrapper
+    fetched_at: Instant,
+    /// Whether a background refresh is in flight
+    refreshing: bool,
+}
+
+/// Cache counters since the cache was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessCacheMetrics {
+    /// Lookups answered from a fresh entry
+    pub hits: u64,
+    /// Lookups answered from an expired entry while it was refreshed
+    pub stale_hits: u64,
+    /// Lookups that had to read the wrapper
+    pub misses: u64,
+    /// Entries dropped by wrapper events
+    pub invalidations: u64,
+    /// Background refreshes that stored a new level
+    pub refreshes: u64,
+    /// Background refreshes that failed, leaving the expired entry in place
+    pub refresh_failures: u64,
+}
+
+/// Counters updated from any thread
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+/// State shared by clones of a cache and its background tasks
+struct Shared {
+    /// Client wrappers are read with
+    client: GlitchGangPrivacyClient,
+    /// Time a level is served without revalidation
+    ttl: Duration,
+    /// Time past `ttl` an expired level is still served
+    stale_for: Duration,
+    /// Wrapper account by NFT mint
+    wrappers: RwLock<HashMap<Pubkey, Pubkey>>,
+    /// Access levels by (mint, viewer)
+    entries: RwLock<HashMap<(Pubkey, Pubkey), Entry>>,
+    /// Invalidations per NFT, so reads started before one aren't stored after it
+    generations: RwLock<HashMap<Pubkey, u64>>,
+    /// Cache counters
+    counters: Counters,
+}
+
+/// Cache of viewers' on-chain access levels, invalidated by wrapper events
+///
+/// Clones share the same entries.
+#[derive(Clone)]
+pub struct AccessCache {
+    /// State shared with background tasks
+    shared: Arc<Shared>,
+}
+
+impl AccessCache {
+    /// Create an empty cache reading wrappers with `client`
+    pub fn new(client: &GlitchGangPrivacyClient) -> Self {
+        Self::with_ttl(client, DEFAULT_TTL, DEFAULT_STALE_FOR)
+    }
+    
+    /// Create an empty cache serving levels for `ttl`, then for `stale_for` more while they are refreshed
+    pub fn with_ttl(client: &GlitchGangPrivacyClient, ttl: Duration, stale_for: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                client: client.clone(),
+                ttl,
+                stale_for,
+                wrappers: RwLock::new(HashMap::new()),
+                entries: RwLock::new(HashMap::new()),
+                generations: RwLock::new(HashMap::new()),
+                counters: Counters::default(),
+            }),
+        }
+    }
+    
+    /// Record an NFT's wrapper account, sparing a program account scan on its first lookup
+    pub fn register_wrapper(&self, nft_mint: &Pubkey, wrapper_account: &Pubkey) {
+        self.shared.wrappers.write().unwrap().insert(*nft_mint, *wrapper_account);
+    }
+    
+    /// Get a viewer's access level on an NFT's wrapper
+    ///
+    /// The owner gets `u8::MAX`, which every access policy clears for
+    /// everything.
+    pub async fn access_level(&self, nft_mint: &Pubkey, viewer: &Pubkey) -> Result<u8, String> {
+        let key = (*nft_mint, *viewer);
+        let cached = self.shared.entries.read().unwrap().get(&key).copied();
+        
+        if let Some(entry) = cached {
+            let age = entry.fetched_at.elapsed();
+            if age < self.shared.ttl {
+                self.shared.counters.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(entry.access_level);
+            }
+            if age < self.shared.ttl + self.shared.stale_for {
+                self.shared.counters.stale_hits.fetch_add(1, Ordering::Relaxed);
+                self.revalidate(key);
+                return Ok(entry.access_level);
+            }
+        }
+        
+        self.shared.counters.misses.fetch_add(1, Ordering::Relaxed);
+        self.shared.fetch(key)
+    }
+    
+    /// Mask VRM data for a viewer at their cached access level
+    pub async fn process_vrm_data(&self, vrm_data: &VrmData, viewer: &Pubkey, nft_mint: &Pubkey) -> Result<VrmData, String> {
+        let access_level = self.access_level(nft_mint, viewer).await?;
+        self.shared.client.process_vrm_data_with_access_level(vrm_data, viewer, access_level, nft_mint)
+    }
+    
+    /// Refresh an expired entry in the background, unless a refresh is already in flight
+    fn revalidate(&self, key: (Pubkey, Pubkey)) {
+        match self.shared.entries.write().unwrap().get_mut(&key) {
+            Some(entry) if !entry.refreshing => entry.refreshing = true,
+            _ => return,
+        }
+        
+        let shared = self.shared.clone();
+        tokio::task::spawn_blocking(move || {
+            match shared.fetch(key) {
+                Ok(_) => shared.counters.refreshes.fetch_add(1, Ordering::Relaxed),
+                Err(e) => {
+                    log::warn!("Failed to refresh access of {} to {}: {}", key.1, key.0, e);
+                    if let Some(entry) = shared.entries.write().unwrap().get_mut(&key) {
+                        entry.refreshing = false;
+                    }
+                    shared.counters.refresh_failures.fetch_add(1, Ordering::Relaxed)
+                },
+            };
+        });
+    }
+    
+    /// Apply a wrapper event, dropping the entries it makes stale
+    ///
+    /// Grants and revocations drop the grantee's entry; ownership changes
+    /// drop every entry of the NFT.
+    pub fn apply_update(&self, nft_mint: &Pubkey, update: &WrapperUpdate) {
+        match update {
+            WrapperUpdate::AccessGranted { grantee, .. } | WrapperUpdate::AccessRevoked { grantee } => {
+                self.invalidate(nft_mint, Some(grantee));
+            },
+            WrapperUpdate::OwnerChanged { .. } => self.invalidate(nft_mint, None),
+            WrapperUpdate::ConfigHashChanged { .. } => {},
+        }
+    }
+    
+    /// Drop a viewer's entry on an NFT, or every entry of the NFT
+    pub fn invalidate(&self, nft_mint: &Pubkey, viewer: Option<&Pubkey>) {
+        *self.shared.generations.write().unwrap().entry(*nft_mint).or_default() += 1;
+        
+        let mut entries = self.shared.entries.write().unwrap();
+        let before = entries.len();
+        entries.retain(|(mint, cached_viewer), _| mint != nft_mint || viewer.map_or(false, |viewer| viewer != cached_viewer));
+        
+        self.shared.counters.invalidations.fetch_add((before - entries.len()) as u64, Ordering::Relaxed);
+    }
+    
+    /// Follow an NFT's wrapper, invalidating entries as events arrive
+    ///
+    /// When the subscription ends, events may have been missed, so every
+    /// entry of the NFT is dropped; follow again to resume.
+    pub async fn follow(&self, nft_mint: &Pubkey) -> Result<tokio::task::JoinHandle<()>, String> {
+        let mut updates = self.shared.client.subscribe_wrapper(nft_mint).await?;
+        
+        let cache = self.clone();
+        let nft_mint = *nft_mint;
+        Ok(tokio::spawn(async move {
+            while let Some(update) = updates.next().await {
+                cache.apply_update(&nft_mint, &update);
+            }
+            cache.invalidate(&nft_mint, None);
+        }))
+    }
+    
+    /// Get the cache counters
+    pub fn metrics(&self) -> AccessCacheMetrics {
+        let counters = &self.shared.counters;
+        AccessCacheMetrics {
+            hits: counters.hits.load(Ordering::Relaxed),
+            stale_hits: counters.stale_hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            invalidations: counters.invalidations.load(Ordering::Relaxed),
+            refreshes: counters.refreshes.load(Ordering::Relaxed),
+            refresh_failures: counters.refresh_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Shared {
+    /// Read a viewer's access level from the wrapper and cache it
+    ///
+    /// The level is not cached if the NFT's entries were invalidated during
+    /// the read, since it may predate the event.
+    fn fetch(&self, (nft_mint, viewer): (Pubkey, Pubkey)) -> Result<u8, String> {
+        let generation = self.generation(&nft_mint);
+        
+        let wrapper_account = match self.wrappers.read().unwrap().get(&nft_mint).copied() {
+            Some(wrapper_account) => wrapper_account,
+            None => {
+                let wrapper_account = self.client.find_wrapper(&nft_mint)?;
+                self.wrappers.write().unwrap().insert(nft_mint, wrapper_account);
+                wrapper_account
+            },
+        };
+        
+        let state = self.client.fetch_wrapper_state(&wrapper_account)?;
+        let access_level = if state.owner() == viewer { u8::MAX } else { state.access_level(&viewer) };
+        
+        let mut entries = self.entries.write().unwrap();
+        if self.generation(&nft_mint) == generation {
+            entries.insert((nft_mint, viewer), Entry {
+                access_level,
+                fetched_at: Instant::now(),
+                refreshing: false,
+            });
+        }
+        
+        Ok(access_level)
+    }
+    
+    /// Get the number of invalidations an NFT has seen
+    fn generation(&self, nft_mint: &Pubkey) -> u64 {
+        self.generations.read().unwrap().get(nft_mint).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use crate::test_utils::{MockRpc, wrapper_account};
+    
+    #[tokio::test]
+    async fn test_cache_serves_stale_levels_and_drops_revoked_ones() {
+        let (nft_mint, owner, viewer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let client = MockRpc::new()
+            .with_account_data(&wrapper_account(&nft_mint, &owner, &[(viewer, 3)]))
+            .privacy_client(Keypair::new());
+        
+        let cache = AccessCache::new(&client);
+        cache.register_wrapper(&nft_mint, &Pubkey::new_unique());
+        assert_eq!(cache.access_level(&nft_mint, &viewer).await.unwrap(), 3);
+        assert_eq!(cache.access_level(&nft_mint, &viewer).await.unwrap(), 3);
+        assert_eq!(cache.access_level(&nft_mint, &owner).await.unwrap(), u8::MAX);
+        
+        // Events for other accounts leave the entry alone; a revocation drops it
+        cache.apply_update(&nft_mint, &WrapperUpdate::AccessRevoked { grantee: Pubkey::new_unique() });
+        cache.apply_update(&nft_mint, &WrapperUpdate::AccessRevoked { grantee: viewer });
+        cache.access_level(&nft_mint, &viewer).await.unwrap();
+        assert_eq!(cache.metrics(), AccessCacheMetrics { hits: 1, misses: 3, invalidations: 1, ..Default::default() });
+        
+        // Expired entries are served while a single refresh runs
+        let stale = AccessCache::with_ttl(&client, Duration::ZERO, Duration::from_secs(60));
+        stale.register_wrapper(&nft_mint, &Pubkey::new_unique());
+        stale.access_level(&nft_mint, &viewer).await.unwrap();
+        assert_eq!(stale.access_level(&nft_mint, &viewer).await.unwrap(), 3);
+        while stale.metrics().refreshes == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(stale.metrics().stale_hits, 1);
+        
+        // Ownership changes drop every entry
+        cache.apply_update(&nft_mint, &WrapperUpdate::OwnerChanged { previous: owner, owner: viewer });
+        assert_eq!(cache.metrics().invalidations, 3);
+    }
+}
@@ -0,0 +1,68 @@
+//! Access gate accounts and instructions
+//!
+//! Seeds, account layouts and instruction builders come from the
+//! `access-gate` program crate, taking the program ID the client resolved
+//! from the cluster registry. The wrapped data keys the gate holds for a
+//! wrapper's viewers sit in public account data: the gate only controls when
+//! `ReleaseKey` hands one out, not who can read it, so revoking a viewer
+//! means removing their key and rotating the data key it wraps.
+
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+pub use ::access_gate::id;
+pub use ::access_gate::instruction::remove_key;
+pub use ::access_gate::state::{find_gate_address, find_viewer_key_address, ViewerKey as GateViewerKey, MAX_WRAPPED_KEY_LEN};
+
+/// Decode a viewer key account
+pub fn unpack_viewer_key(data: &[u8]) -> Result<GateViewerKey, String> {
+    GateViewerKey::unpack(data).map_err(|e| format!("Failed to decode gate viewer key: {}", e))
+}
+
+/// Build an instruction storing or replacing the data key wrapped to a viewer
+pub fn store_key(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wrapper: &Pubkey,
+    viewer: &Pubkey,
+    wrapped_key: Vec<u8>,
+) -> Result<Instruction, String> {
+    if wrapped_key.is_empty() || wrapped_key.len() > MAX_WRAPPED_KEY_LEN {
+        return Err(format!("Wrapped key must be 1 to {} bytes", MAX_WRAPPED_KEY_LEN));
+    }
+    
+    Ok(::access_gate::instruction::store_key(program_id, authority, wrapper, viewer, wrapped_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_store_key_checks_the_wrapped_key_length() {
+        let (program_id, authority, wrapper, viewer) = (id(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        
+        let instruction = store_key(&program_id, &authority, &wrapper, &viewer, vec![9; 3]).unwrap();
+        let (gate, _) = find_gate_address(&program_id, &wrapper);
+        assert_eq!(instruction.accounts[2].pubkey, find_viewer_key_address(&program_id, &gate, &viewer).0);
+        assert!(store_key(&program_id, &authority, &wrapper, &viewer, vec![]).is_err());
+        assert!(store_key(&program_id, &authority, &wrapper, &viewer, vec![0; MAX_WRAPPED_KEY_LEN + 1]).is_err());
+    }
+    
+    #[test]
+    fn test_unpack_viewer_key() {
+        let (gate, viewer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![1];
+        data.extend_from_slice(gate.as_ref());
+        data.extend_from_slice(viewer.as_ref());
+        data.extend([2, 0, 0, 0, 7, 7, 0]);
+        data.resize(GateViewerKey::LEN, 0);
+        
+        let key = unpack_viewer_key(&data).unwrap();
+        assert_eq!((key.gate, key.viewer), (gate, viewer));
+        assert_eq!(key.wrapped_key, vec![7, 7]);
+        assert_eq!(key.released_at, None);
+        
+        data[0] = 0;
+        assert!(unpack_viewer_key(&data).is_err());
+    }
+}
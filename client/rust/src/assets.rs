@@ -0,0 +1,234 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageOutputFormat};
+use rand::{Rng, rngs::OsRng};
+use std::io::{Cursor, Read, Write};
+
+use quantum_veil::{encrypt_data, decrypt_data};
+
+/// Plaintext bytes sealed per chunk of an encrypted asset
+pub const ASSET_CHUNK_LEN: usize = 64 * 1024;
+
+/// Bytes of random nonce prefix opening an encrypted asset
+const NONCE_PREFIX_LEN: usize = 7;
+
+/// Poly1305 tag length
+const TAG_LEN: usize = 16;
+
+/// Largest edge of a public preview, in pixels
+const MAX_PREVIEW_DIM: u32 = 512;
+
+/// How a public preview is degraded from the original image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewStyle {
+    /// Gaussian blur with the given sigma, in preview pixels
+    Blur { sigma: f32 },
+    /// Blocks of the given size, in preview pixels
+    Pixelate { block_size: u32 },
+}
+
+impl Default for PreviewStyle {
+    fn default() -> Self {
+        Self::Pixelate { block_size: 16 }
+    }
+}
+
+/// Encrypt a stream chunk by chunk, returning the bytes written
+///
+/// Follows the STREAM construction: every chunk is sealed with ChaCha20Poly1305
+/// under a nonce of `[prefix: 7][counter: u32 BE][last: u8]`, so dropped,
+/// reordered, or truncated chunks fail to decrypt.
+pub fn encrypt_stream(mut reader: impl Read, mut writer: impl Write, key: &[u8; 32]) -> Result<u64, String> {
+    let mut prefix = [0u8; NONCE_PREFIX_LEN];
+    OsRng.fill(&mut prefix);
+    writer.write_all(&prefix)
+        .map_err(|e| format!("Failed to write encrypted asset: {}", e))?;
+    let mut written = NONCE_PREFIX_LEN as u64;
+    
+    let mut current = vec![0u8; ASSET_CHUNK_LEN];
+    let mut next = vec![0u8; ASSET_CHUNK_LEN];
+    let mut len = read_chunk(&mut reader, &mut current)?;
+    let mut counter = 0u32;
+    
+    loop {
+        // Read ahead to learn whether this is the last chunk
+        let next_len = if len == ASSET_CHUNK_LEN { read_chunk(&mut reader, &mut next)? } else { 0 };
+        let last = next_len == 0;
+        
+        let sealed = encrypt_data(&current[..len], key, &chunk_nonce(&prefix, counter, last))?;
+        writer.write_all(&sealed)
+            .map_err(|e| format!("Failed to write encrypted asset: {}", e))?;
+        written += sealed.len() as u64;
+        
+        if last {
+            return Ok(written);
+        }
+        
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        counter = counter.checked_add(1).ok_or("Asset too large to encrypt")?;
+    }
+}
+
+/// Decrypt a stream written by `encrypt_stream`, returning the bytes written
+pub fn decrypt_stream(mut reader: impl Read, mut writer: impl Write, key: &[u8; 32]) -> Result<u64, String> {
+    let mut prefix = [0u8; NONCE_PREFIX_LEN];
+    reader.read_exact(&mut prefix)
+        .map_err(|e| format!("Failed to read encrypted asset: {}", e))?;
+    
+    let mut current = vec![0u8; ASSET_CHUNK_LEN + TAG_LEN];
+    let mut next = vec![0u8; ASSET_CHUNK_LEN + TAG_LEN];
+    let mut len = read_chunk(&mut reader, &mut current)?;
+    let mut counter = 0u32;
+    let mut written = 0u64;
+    
+    loop {
+        if len < TAG_LEN {
+            return Err("Encrypted asset truncated".to_string());
+        }
+        
+        let next_len = if len == current.len() { read_chunk(&mut reader, &mut next)? } else { 0 };
+        let last = next_len == 0;
+        
+        let plaintext = decrypt_data(&current[..len], key, &chunk_nonce(&prefix, counter, last))
+            .map_err(|_| format!("Encrypted asset chunk {} is corrupted or out of place", counter))?;
+        writer.write_all(&plaintext)
+            .map_err(|e| format!("Failed to write decrypted asset: {}", e))?;
+        written += plaintext.len() as u64;
+        
+        if last {
+            return Ok(written);
+        }
+        
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        counter = counter.checked_add(1).ok_or("Encrypted asset too large")?;
+    }
+}
+
+/// Build the nonce of one chunk
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+/// Fill a buffer from a reader, returning fewer bytes only at the end of the stream
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, String> {
+    let mut filled = 0;
+    
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Failed to read asset: {}", e)),
+        }
+    }
+    
+    Ok(filled)
+}
+
+/// Check if bytes hold an image format a preview can be made from
+pub fn is_image(bytes: &[u8]) -> bool {
+    image::guess_format(bytes).is_ok()
+}
+
+/// Make a degraded PNG preview of an image, safe to publish
+///
+/// The preview is scaled down to at most 512 pixels before degrading, so
+/// detail lost to the blur or pixelation cannot be recovered at full size.
+pub fn degraded_preview(image_bytes: &[u8], style: PreviewStyle) -> Result<Vec<u8>, String> {
+    let original = image::load_from_memory(image_bytes)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    
+    // Step 1: Scale down
+    let scaled = if original.width() > MAX_PREVIEW_DIM || original.height() > MAX_PREVIEW_DIM {
+        original.thumbnail(MAX_PREVIEW_DIM, MAX_PREVIEW_DIM)
+    } else {
+        original
+    };
+    
+    // Step 2: Degrade
+    let preview = match style {
+        PreviewStyle::Blur { sigma } => scaled.blur(sigma.max(1.0)),
+        PreviewStyle::Pixelate { block_size } => pixelate(&scaled, block_size.max(2)),
+    };
+    
+    // Step 3: Encode
+    let mut png = Cursor::new(Vec::new());
+    preview.write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+    
+    Ok(png.into_inner())
+}
+
+/// Replace blocks of pixels with their average color
+fn pixelate(image: &DynamicImage, block_size: u32) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    let small_width = (width / block_size).max(1);
+    let small_height = (height / block_size).max(1);
+    
+    image
+        .resize_exact(small_width, small_height, FilterType::Triangle)
+        .resize_exact(width, height, FilterType::Nearest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, Rgb, RgbImage};
+    
+    fn sample_png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| Rgb([(x * 7) as u8, (y * 13) as u8, ((x ^ y) * 3) as u8]));
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image).write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        png.into_inner()
+    }
+    
+    #[test]
+    fn test_stream_round_trip() {
+        let key = [3u8; 32];
+        
+        for len in [0, 10, ASSET_CHUNK_LEN, ASSET_CHUNK_LEN * 2 + 5] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut ciphertext = Vec::new();
+            encrypt_stream(&plaintext[..], &mut ciphertext, &key).unwrap();
+            
+            let mut decrypted = Vec::new();
+            decrypt_stream(&ciphertext[..], &mut decrypted, &key).unwrap();
+            assert_eq!(decrypted, plaintext);
+            
+            assert!(decrypt_stream(&ciphertext[..], &mut Vec::new(), &[4u8; 32]).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_truncated_stream_is_rejected() {
+        let key = [3u8; 32];
+        let plaintext = vec![1u8; ASSET_CHUNK_LEN * 2];
+        let mut ciphertext = Vec::new();
+        encrypt_stream(&plaintext[..], &mut ciphertext, &key).unwrap();
+        
+        // Dropping the last chunk leaves a valid chunk that was not sealed as last
+        let truncated = &ciphertext[..NONCE_PREFIX_LEN + ASSET_CHUNK_LEN + TAG_LEN];
+        assert!(decrypt_stream(truncated, &mut Vec::new(), &key).is_err());
+    }
+    
+    #[test]
+    fn test_preview_is_degraded_and_bounded() {
+        let original = sample_png(1024, 256);
+        assert!(is_image(&original));
+        assert!(!is_image(b"glTF\x02\x00\x00\x00"));
+        
+        for style in [PreviewStyle::Blur { sigma: 4.0 }, PreviewStyle::default()] {
+            let preview = image::load_from_memory(&degraded_preview(&original, style).unwrap()).unwrap();
+            assert_eq!(preview.dimensions(), (512, 128));
+        }
+        
+        // Pixelated blocks are uniform
+        let preview = image::load_from_memory(&degraded_preview(&original, PreviewStyle::Pixelate { block_size: 16 }).unwrap()).unwrap();
+        assert_eq!(preview.get_pixel(0, 0), preview.get_pixel(15, 15));
+    }
+}
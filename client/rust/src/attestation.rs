@@ -0,0 +1,136 @@
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+/// Domain separator for privacy attestations
+const ATTESTATION_DOMAIN: &[u8] = b"quantum-veil/privacy-attestation/v1";
+
+/// Signed statement that an NFT is privacy wrapped under a policy
+///
+/// Issued by an attester that read the wrapper on-chain, so marketplaces
+/// can check an NFT is wrapped by verifying one signature against the
+/// attester key they trust, without reading the wrapper program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyAttestation {
+    /// Wrapper program the wrapper belongs to
+    pub program_id: Pubkey,
+    /// Wrapper account
+    pub wrapper: Pubkey,
+    /// NFT the wrapper protects
+    pub nft_mint: Pubkey,
+    /// Owner of the wrapper when attested
+    pub owner: Pubkey,
+    /// Privacy config hash in force, after collection inheritance
+    pub policy_hash: String,
+    /// When the attestation was issued, as a Unix timestamp
+    pub issued_at: i64,
+    /// When the attestation stops being valid, as a Unix timestamp
+    pub expires_at: i64,
+    /// Key that signed the attestation
+    pub attester: Pubkey,
+    /// Ed25519 signature over the attestation fields
+    pub signature: Signature,
+}
+
+impl PrivacyAttestation {
+    /// Create an unsigned attestation valid for `validity` seconds from `issued_at`
+    pub fn new(
+        program_id: &Pubkey,
+        wrapper: &Pubkey,
+        nft_mint: &Pubkey,
+        owner: &Pubkey,
+        policy_hash: &str,
+        issued_at: i64,
+        validity: i64,
+    ) -> Self {
+        Self {
+            program_id: *program_id,
+            wrapper: *wrapper,
+            nft_mint: *nft_mint,
+            owner: *owner,
+            policy_hash: policy_hash.to_string(),
+            issued_at,
+            expires_at: issued_at.saturating_add(validity),
+            attester: Pubkey::default(),
+            signature: Signature::default(),
+        }
+    }
+    
+    /// Sign the attestation
+    pub fn sign(mut self, attester: &Keypair) -> Self {
+        self.attester = attester.pubkey();
+        self.signature = attester.sign_message(&self.message());
+        self
+    }
+    
+    /// Get the signed message: the domain separator and attestation fields
+    fn message(&self) -> Vec<u8> {
+        let mut message = ATTESTATION_DOMAIN.to_vec();
+        for key in [&self.program_id, &self.wrapper, &self.nft_mint, &self.owner] {
+            message.extend_from_slice(key.as_ref());
+        }
+        message.extend_from_slice(&(self.policy_hash.len() as u32).to_le_bytes());
+        message.extend_from_slice(self.policy_hash.as_bytes());
+        message.extend_from_slice(&self.issued_at.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message.extend_from_slice(self.attester.as_ref());
+        message
+    }
+}
+
+/// Verify an attestation is signed by a trusted attester and not expired at `now`
+pub fn verify_attestation(
+    attestation: &PrivacyAttestation,
+    trusted_attesters: &[Pubkey],
+    now: i64,
+) -> Result<(), String> {
+    if !trusted_attesters.contains(&attestation.attester) {
+        return Err(format!("Untrusted privacy attester: {}", attestation.attester));
+    }
+    
+    if !attestation.signature.verify(attestation.attester.as_ref(), &attestation.message()) {
+        return Err("Invalid privacy attestation signature".to_string());
+    }
+    
+    if now < attestation.issued_at || now >= attestation.expires_at {
+        return Err(format!("Privacy attestation for {} is not valid at {}", attestation.nft_mint, now));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_attestation_verifies_only_as_signed() {
+        let attester = Keypair::new();
+        let trusted = [attester.pubkey()];
+        let attestation = PrivacyAttestation::new(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            "policy-hash",
+            1_700_000_000,
+            3_600,
+        ).sign(&attester);
+        
+        assert!(verify_attestation(&attestation, &trusted, 1_700_000_000).is_ok());
+        
+        // Survives a JSON round trip, as marketplaces receive it
+        let json = serde_json::to_string(&attestation).unwrap();
+        let decoded: PrivacyAttestation = serde_json::from_str(&json).unwrap();
+        assert!(verify_attestation(&decoded, &trusted, 1_700_000_000).is_ok());
+        
+        // Expired, untrusted, or altered attestations are rejected
+        assert!(verify_attestation(&attestation, &trusted, 1_700_003_600).is_err());
+        assert!(verify_attestation(&attestation, &[Pubkey::new_unique()], 1_700_000_000).is_err());
+        
+        let tampered = PrivacyAttestation { policy_hash: "other-policy-hash".to_string(), ..attestation };
+        assert!(verify_attestation(&tampered, &trusted, 1_700_000_000).is_err());
+    }
+}
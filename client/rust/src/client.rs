@@ -1,44 +1,148 @@
+use solana_client::nonblocking::pubsub_client::PubsubClient;
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_account_decoder::UiAccountEncoding;
+use solana_transaction_status::UiTransactionEncoding;
+use spl_associated_token_account::get_associated_token_address;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    hash::Hash,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
     instruction::{AccountMeta, Instruction},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::{str::FromStr, fs};
+use std::ops::Range;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 use base64;
 use ring::{digest, hmac};
+use futures::{Stream, StreamExt};
 use rand::{Rng, rngs::OsRng};
+use sha3::{Digest, Sha3_512};
+use zeroize::Zeroizing;
 
 use crate::models::{
-    GlitchGangMetadata, PrivacyLevel, VrmData, PrivateData, VrmConfig, WrapperInstruction,
-    TimelineType, MetadataFragment
+    GlitchGangMetadata, PrivacyLevel, VrmData, PrivateData, ProtectionReport, VrmConfig, WrapperInstruction,
+    WrapSummary, WrapperState, LevelPreview, ProtectionPreview, MetadataFragment, AccessGrant, AccessEvent, AccessReport,
+    ProtectedAsset, RevealEscrow, RevealTime, ScheduledReveal, TimeLockedAttributes, RecoveryConfig, TimelineType,
+    PrivacyLock, WrapperInspection, CollectionWrapper, CollectionMembership, EffectivePolicy, FreezeConfig, FreezeScope,
+    RevealListing, RevealPurchase, RevealTerms, AccessRequest, WRAPPER_MINT_OFFSET,
+    legacy_agent_pubkey, find_agent_address, find_collection_address, find_freeze_address, find_listing_address,
+    find_lock_address, find_membership_address, find_purchase_address, find_reveal_address, find_recovery_address,
+    find_request_address
 };
+use crate::assets::{self, PreviewStyle};
+use crate::attestation::PrivacyAttestation;
+use crate::cluster::ClusterConfig;
+use crate::dry_run::{DryRunPlan, PlannedAccount, SimulatedTransaction};
+use crate::fpe::{self, FormatPreserving, FpeCipher};
+use crate::nonce::{self, NonceInfo};
+use crate::relayer::Relayer;
+use crate::search::{SearchKey, SEARCH_KEY_LABEL};
+use crate::snapshot::WrapperSnapshot;
+use crate::transcript::DistributionTranscript;
+use crate::watermark;
+use crate::metaplex;
+use crate::access_gate::{self, GateViewerKey};
+use crate::key_wrap::{self, ViewerKey};
+use crate::keystore::{self, KdfParams, KeyBundle, KeyRotation, Keystore, MintKeyRecord};
+use crate::unlock::UnlockProvider;
+use crate::policy::ProtectionPolicy;
+use crate::recovery::{self, KeyShare};
+use crate::registry::{fetch_agent_record, AgentRegistry};
+use crate::sns;
+use crate::stealth::{StealthAnnouncement, StealthKeypair, StealthKeys, StealthMetaAddress};
+use crate::updates::WrapperUpdate;
+use crate::upgrade_authority::{self, UpgradeAuthority};
+use crate::uploader::Uploader;
 
 // Import crate components
-use quantum_veil::{QuantumVeil, EntropySource, PrivacyConfig, SynchronicityMask as QVSyncMask};
-use synchronicity_mask::{SynchronicityMask};
-use timeline_shifter::{TimelineShifter};
+use quantum_veil::{QuantumVeil, EntropySource, KeyProvider, PrivacyConfig, SecretBytes, SynchronicityMask as QVSyncMask, decrypt_data, is_envelope, open_envelope, seal_envelope};
+use synchronicity_mask::{SynchronicityMask, SyncMaskConfig, VrmDataType, AccessPermission, RevocationList};
+use timeline_shifter::{TimelineShifter, DriftScheduler, FragmentManifest, RetrievalStats, derive_timeline_key, timeline_key_id};
+use audit::{AuditEvent, AuditLog};
+use network::{ProxyConfig, RpcPool};
+
+/// Privacy wrapper program ID
+pub(crate) const PROGRAM_ID: &str = "GlchWrapperProgram111111111111111111111111111";
+
+/// Domain label for encryption key identifiers
+const KEY_ID_LABEL: &[u8] = b"quantum-veil/key-id";
+
+/// Domain label for the format-preserving encryption key
+const FPE_KEY_LABEL: &[u8] = b"quantum-veil/fpe/v1";
+
+/// Domain label for the convergence key of deduplicated fragments
+const CONVERGENCE_KEY_LABEL: &[u8] = b"quantum-veil/convergence/v1";
 
 /// Privacy Client for Glitch Gang NFTs
+///
+/// Every method takes `&self`: mutable state sits behind locks and clones
+/// share it, so one client can serve concurrent requests from behind an
+/// `Arc` or as cheap clones.
+#[derive(Clone)]
 pub struct GlitchGangPrivacyClient {
     /// Solana RPC client
-    rpc_client: RpcClient,
+    rpc_client: Arc<RpcClient>,
+    /// HTTP client for metadata fetches
+    http_client: reqwest::Client,
+    /// PubSub websocket URL, if not derived from the RPC URL
+    websocket_url: Option<String>,
     /// Owner's keypair
-    owner_keypair: Keypair,
+    owner_keypair: Arc<Keypair>,
     /// Program ID
     program_id: Pubkey,
+    /// Access gate program ID
+    gate_program_id: Pubkey,
     /// Quantum Veil encryption system
-    quantum_veil: QuantumVeil,
+    quantum_veil: Arc<RwLock<QuantumVeil>>,
     /// Synchronicity Mask for VRM privacy
-    sync_mask: SynchronicityMask,
-    /// Timeline Shifter for metadata fragmentation
-    timeline_shifter: Option<TimelineShifter>,
-    /// Encryption key
-    encryption_key: [u8; 32],
+    sync_mask: Arc<RwLock<SynchronicityMask>>,
+    /// Timeline Shifter for metadata fragmentation, swapped out when reconfigured
+    timeline_shifter: Arc<RwLock<Option<Arc<TimelineShifter>>>>,
+    /// Encryption key, empty while held wrapped by a key provider
+    encryption_key: Arc<RwLock<SecretBytes>>,
+    /// Encryption key wrapped by an external key provider, if configured
+    provided_key: Arc<RwLock<Option<ProvidedKey>>>,
+    /// Audit log shared by the components, if configured
+    audit_log: Option<Arc<AuditLog>>,
+    /// Keys retired by rotation, by NFT mint
+    key_rotations: Arc<RwLock<HashMap<String, Vec<KeyRotation>>>>,
+    /// Entropy of the recovery phrase NFT keys are derived from, if configured
+    mnemonic_entropy: Arc<RwLock<Option<SecretBytes>>>,
+    /// Effects recorded in dry-run mode, if enabled
+    dry_run: Option<Arc<Mutex<DryRunPlan>>>,
+    /// Durable nonce account transactions are built against, if configured
+    durable_nonce: Option<Pubkey>,
+    /// Account paying transaction fees
+    fee_payer: Arc<FeePayer>,
+}
+
+/// Account paying transaction fees
+enum FeePayer {
+    /// The owner pays
+    Owner,
+    /// A separate signer held by the client pays
+    Signer(Keypair),
+    /// A relayer pays and submits the transactions
+    Relayer(Relayer),
+}
+
+/// Encryption key held wrapped by an external key provider
+struct ProvidedKey {
+    /// Provider holding the master key
+    provider: Arc<dyn KeyProvider>,
+    /// Encryption key wrapped by the provider
+    wrapped_key: Vec<u8>,
+    /// Identifier of the unwrapped key
+    key_id: String,
 }
 
 impl GlitchGangPrivacyClient {
@@ -52,43 +156,325 @@ impl GlitchGangPrivacyClient {
             CommitmentConfig::confirmed(),
         );
         
-        let program_id = Pubkey::from_str("GlchWrapperProgram111111111111111111111111111").unwrap();
-        
         let quantum_veil = QuantumVeil::new(solana_rpc);
-        let sync_mask = SynchronicityMask::new(solana_rpc);
+        
+        // Resolve trusted agent names through the on-chain registry
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
+        let sync_mask = SynchronicityMask::new(solana_rpc)
+            .with_agent_resolver(Arc::new(AgentRegistry::new(solana_rpc, program_id)));
+        
+        Self::with_rpc_clients(rpc_client, quantum_veil, sync_mask, owner_keypair)
+    }
+    
+    /// Create a new client whose RPC calls and metadata fetches go through proxies
+    pub fn with_proxy(
+        solana_rpc: &str,
+        owner_keypair: Keypair,
+        proxy: &ProxyConfig,
+    ) -> Result<Self, String> {
+        Self::with_program(solana_rpc, Pubkey::from_str(PROGRAM_ID).unwrap(), owner_keypair, proxy)
+    }
+    
+    /// Create a new client for a cluster's endpoints and program deployment
+    pub fn for_cluster(
+        cluster: &ClusterConfig,
+        owner_keypair: Keypair,
+        proxy: &ProxyConfig,
+    ) -> Result<Self, String> {
+        let mut client = Self::with_program(&cluster.rpc_url, cluster.wrapper_program_id()?, owner_keypair, proxy)?;
+        client.gate_program_id = cluster.gate_program_id()?;
+        Ok(client.with_websocket_url(&cluster.websocket_url()))
+    }
+    
+    /// Create a new proxied client talking to a wrapper program deployment
+    fn with_program(
+        solana_rpc: &str,
+        program_id: Pubkey,
+        owner_keypair: Keypair,
+        proxy: &ProxyConfig,
+    ) -> Result<Self, String> {
+        let rpc_client = proxy.rpc_client(solana_rpc, CommitmentConfig::confirmed())?;
+        let quantum_veil = QuantumVeil::with_rpc_client(proxy.rpc_client(solana_rpc, CommitmentConfig::default())?);
+        
+        let registry = AgentRegistry::with_rpc_client(proxy.rpc_client(solana_rpc, CommitmentConfig::default())?, program_id);
+        let sync_mask = SynchronicityMask::with_rpc_client(proxy.rpc_client(solana_rpc, CommitmentConfig::default())?)
+            .with_agent_resolver(Arc::new(registry));
+        
+        let mut client = Self::with_rpc_clients(rpc_client, quantum_veil, sync_mask, owner_keypair);
+        client.http_client = proxy.http_client()?;
+        client.program_id = program_id;
+        Ok(client)
+    }
+    
+    /// Create a new client whose RPC calls rotate among a pool of endpoints
+    ///
+    /// Metadata fetches go through `proxy`.
+    pub fn with_rpc_pool(
+        pool: &RpcPool,
+        owner_keypair: Keypair,
+        proxy: &ProxyConfig,
+    ) -> Result<Self, String> {
+        let rpc_client = pool.rpc_client(CommitmentConfig::confirmed())?;
+        let quantum_veil = QuantumVeil::with_rpc_client(pool.rpc_client(CommitmentConfig::default())?);
+        
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
+        let registry = AgentRegistry::with_rpc_client(pool.rpc_client(CommitmentConfig::default())?, program_id);
+        let sync_mask = SynchronicityMask::with_rpc_client(pool.rpc_client(CommitmentConfig::default())?)
+            .with_agent_resolver(Arc::new(registry));
+        
+        let mut client = Self::with_rpc_clients(rpc_client, quantum_veil, sync_mask, owner_keypair);
+        client.http_client = proxy.http_client()?;
+        Ok(client)
+    }
+    
+    /// Create a new client from preconfigured RPC-backed components
+    pub fn with_rpc_clients(
+        rpc_client: RpcClient,
+        quantum_veil: QuantumVeil,
+        sync_mask: SynchronicityMask,
+        owner_keypair: Keypair,
+    ) -> Self {
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
         
         // Generate a secure encryption key
-        let mut encryption_key = [0u8; 32];
-        OsRng.fill(&mut encryption_key);
+        let mut encryption_key = vec![0u8; 32];
+        OsRng.fill(&mut encryption_key[..]);
         
         Self {
-            rpc_client,
-            owner_keypair,
+            rpc_client: Arc::new(rpc_client),
+            http_client: reqwest::Client::new(),
+            websocket_url: None,
+            owner_keypair: Arc::new(owner_keypair),
             program_id,
-            quantum_veil,
-            sync_mask,
-            timeline_shifter: None,
-            encryption_key,
+            gate_program_id: access_gate::id(),
+            quantum_veil: Arc::new(RwLock::new(quantum_veil)),
+            sync_mask: Arc::new(RwLock::new(sync_mask)),
+            timeline_shifter: Arc::new(RwLock::new(None)),
+            encryption_key: Arc::new(RwLock::new(SecretBytes::new(encryption_key))),
+            provided_key: Arc::new(RwLock::new(None)),
+            audit_log: None,
+            key_rotations: Arc::new(RwLock::new(HashMap::new())),
+            mnemonic_entropy: Arc::new(RwLock::new(None)),
+            dry_run: None,
+            durable_nonce: None,
+            fee_payer: Arc::new(FeePayer::Owner),
         }
     }
     
     /// Set a specific encryption key
-    pub fn with_encryption_key(mut self, key: [u8; 32]) -> Self {
-        self.encryption_key = key;
+    pub fn with_encryption_key(self, key: [u8; 32]) -> Self {
+        *self.encryption_key.write().unwrap() = SecretBytes::from(&key[..]);
+        *self.provided_key.write().unwrap() = None;
+        self
+    }
+    
+    /// Derive NFT keys from a BIP39 recovery phrase, e.g. one from `keystore::generate_mnemonic`
+    ///
+    /// Wrappers created and keys rotated afterwards use keys derived along
+    /// `m/quantum-veil'/<mint-hash>'/<rotation>'`, recoverable from the phrase
+    /// with `recover_privacy_config`. Keys created before keep working.
+    pub fn with_mnemonic(self, phrase: &str) -> Result<Self, String> {
+        self.set_mnemonic(keystore::mnemonic_entropy(phrase)?)?;
+        Ok(self)
+    }
+    
+    /// Derive NFT keys from mnemonic entropy
+    fn set_mnemonic(&self, entropy: SecretBytes) -> Result<(), String> {
+        self.quantum_veil.write().unwrap().set_hd_master(keystore::hd_master(&entropy)?);
+        *self.mnemonic_entropy.write().unwrap() = Some(entropy);
+        Ok(())
+    }
+    
+    /// Get the recovery phrase NFT keys are derived from, to back up
+    pub fn export_mnemonic(&self) -> Result<Zeroizing<String>, String> {
+        match &*self.mnemonic_entropy.read().unwrap() {
+            Some(entropy) => keystore::mnemonic_phrase(entropy),
+            None => Err("No recovery phrase configured".to_string()),
+        }
+    }
+    
+    /// Rebuild an NFT's privacy config from the recovery phrase
+    ///
+    /// `key_index` is the config's `key_index`, the number of rotations since
+    /// the wrapper was created. Mask settings start at the `Medium` defaults
+    /// unless the client already holds the NFT's mask config.
+    pub fn recover_privacy_config(&self, nft_mint: &Pubkey, key_index: u32) -> Result<PrivacyConfig, String> {
+        let existing = self.sync_mask.read().unwrap().get_config(&nft_mint.to_string()).ok();
+        let mask_config = match existing {
+            Some(mask_config) => mask_config,
+            None => self.sync_mask.write().unwrap().create_config(nft_mint, &self.owner_keypair.pubkey(), PrivacyLevel::Medium),
+        };
+        
+        self.quantum_veil.write().unwrap().recover_config(
+            &self.owner_keypair.pubkey().to_string(),
+            &nft_mint.to_string(),
+            key_index,
+            3600,
+            Self::qv_sync_mask(&mask_config),
+        )
+    }
+    
+    /// Hold keys wrapped by a key provider, e.g. a KMS or HSM, instead of in memory
+    ///
+    /// Pass the wrapped key saved from `wrapped_key` to restore the client's
+    /// encryption key, or `None` to wrap the current one. NFT keys created
+    /// afterwards are wrapped by the provider too.
+    pub fn with_key_provider(self, provider: Arc<dyn KeyProvider>, wrapped_key: Option<Vec<u8>>) -> Result<Self, String> {
+        let wrapped_key = match wrapped_key {
+            Some(wrapped_key) => wrapped_key,
+            None => provider.wrap(self.encryption_key.read().unwrap().expose())?,
+        };
+        
+        // Unwrap once to check the provider holds the key
+        let key = SecretBytes::new(provider.unwrap(&wrapped_key)?);
+        if key.len() != 32 {
+            return Err("Invalid encryption key length".to_string());
+        }
+        
+        rebuild(&self.quantum_veil, QuantumVeil::offline(), |quantum_veil| quantum_veil.with_key_provider(provider.clone()));
+        *self.encryption_key.write().unwrap() = SecretBytes::new(Vec::new());
+        *self.provided_key.write().unwrap() = Some(ProvidedKey {
+            provider,
+            wrapped_key,
+            key_id: key_id_of(key.expose()),
+        });
+        Ok(self)
+    }
+    
+    /// Get the encryption key as wrapped by the key provider, to store for restarts
+    pub fn wrapped_key(&self) -> Option<Vec<u8>> {
+        self.provided_key.read().unwrap().as_ref().map(|provided| provided.wrapped_key.clone())
+    }
+    
+    /// Get the encryption key, unwrapping it through the key provider if configured
+    fn content_key(&self) -> Result<SecretBytes, String> {
+        let key = match &*self.provided_key.read().unwrap() {
+            Some(provided) => SecretBytes::new(provided.provider.unwrap(&provided.wrapped_key)?),
+            None => self.encryption_key.read().unwrap().clone(),
+        };
+        
+        if key.len() != 32 {
+            return Err("Invalid encryption key length".to_string());
+        }
+        Ok(key)
+    }
+    
+    /// Use a PubSub websocket URL other than the one derived from the RPC URL
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = Some(websocket_url.to_string());
         self
     }
     
     /// Set the timeline shifter
-    pub fn with_timeline_shifter(mut self, shifter: TimelineShifter) -> Self {
-        self.timeline_shifter = Some(shifter);
+    pub fn with_timeline_shifter(self, shifter: TimelineShifter) -> Self {
+        *self.timeline_shifter.write().unwrap() = Some(Arc::new(match self.dry_run {
+            Some(_) if !shifter.is_dry_run() => shifter.with_dry_run(),
+            _ => shifter,
+        }));
         self
     }
     
+    /// Simulate transactions and record uploads and fragment writes instead of performing them
+    ///
+    /// Operations still read from the cluster and storage, and return as if
+    /// they had succeeded; `dry_run_plan` lists what they would have done.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = Some(Arc::new(Mutex::new(DryRunPlan::default())));
+        self.configure_timeline_shifter(|shifter| match shifter.is_dry_run() {
+            true => shifter,
+            false => shifter.with_dry_run(),
+        });
+        self
+    }
+    
+    /// Build transactions against a durable nonce instead of a recent blockhash
+    ///
+    /// The owner must be the nonce authority. Each transaction advances the
+    /// nonce, so the next one is built against the new value.
+    pub fn with_durable_nonce(mut self, nonce_account: &Pubkey) -> Self {
+        self.durable_nonce = Some(*nonce_account);
+        self
+    }
+    
+    /// Pay transaction fees from a separate signer instead of the owner
+    pub fn with_fee_payer(mut self, fee_payer: Keypair) -> Self {
+        self.fee_payer = Arc::new(FeePayer::Signer(fee_payer));
+        self
+    }
+    
+    /// Have a relayer pay for and submit transactions
+    ///
+    /// Accounts the owner creates, like the wrapper, are still funded by the
+    /// owner; fees of every transaction are paid by the relayer.
+    pub fn with_relayer(mut self, relayer: Relayer) -> Self {
+        self.fee_payer = Arc::new(FeePayer::Relayer(relayer));
+        self
+    }
+    
+    /// Check if the client is in dry-run mode
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+    
+    /// Get the effects recorded so far in dry-run mode
+    pub fn dry_run_plan(&self) -> Option<DryRunPlan> {
+        let mut plan = self.dry_run.as_ref()?.lock().unwrap().clone();
+        if let Some(shifter) = self.timeline_shifter() {
+            plan.fragment_writes = shifter.planned_writes();
+        }
+        Some(plan)
+    }
+    
+    /// Upload a file, or record the upload in dry-run mode
+    async fn upload_file(&self, uploader: &dyn Uploader, bytes: &[u8], content_type: &str) -> Result<String, String> {
+        match &self.dry_run {
+            Some(plan) => Ok(plan.lock().unwrap().record_upload(bytes, content_type)),
+            None => uploader.upload_file(bytes, content_type).await,
+        }
+    }
+    
+    /// Upload a JSON document, or record the upload in dry-run mode
+    async fn upload_json(&self, uploader: &dyn Uploader, json: &[u8]) -> Result<String, String> {
+        match &self.dry_run {
+            Some(plan) => Ok(plan.lock().unwrap().record_upload(json, "application/json")),
+            None => uploader.upload_json(json).await,
+        }
+    }
+    
+    /// Record decryptions, disclosures, query anomalies and fragment reads in an audit log
+    ///
+    /// Set after `with_timeline_shifter` so the shifter records to it too.
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        rebuild(&self.quantum_veil, QuantumVeil::offline(), |quantum_veil| quantum_veil.with_audit_log(audit_log.clone()));
+        rebuild(&self.sync_mask, SynchronicityMask::offline(), |sync_mask| sync_mask.with_audit_log(audit_log.clone()));
+        self.configure_timeline_shifter(|shifter| shifter.with_audit_log(audit_log.clone()).with_access_log(audit_log.clone()));
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
+    /// Reconfigure the timeline shifter of this client and its clones
+    ///
+    /// If another clone is mid-operation with the current shifter, that
+    /// operation finishes with it and the reconfigured copy takes its place.
+    fn configure_timeline_shifter(&self, configure: impl FnOnce(TimelineShifter) -> TimelineShifter) {
+        let mut slot = self.timeline_shifter.write().unwrap();
+        *slot = slot.take().map(|shifter| {
+            let shifter = Arc::try_unwrap(shifter).unwrap_or_else(|shared| (*shared).clone());
+            Arc::new(configure(shifter))
+        });
+    }
+    
+    /// Get the current timeline shifter, if configured
+    fn timeline_shifter(&self) -> Option<Arc<TimelineShifter>> {
+        self.timeline_shifter.read().unwrap().clone()
+    }
+    
     /// Fetch NFT metadata
     pub async fn fetch_metadata(&self, metadata_uri: &str) -> Result<GlitchGangMetadata, String> {
         log::info!("Fetching metadata from: {}", metadata_uri);
         
-        let response = reqwest::get(metadata_uri)
+        let response = self.http_client.get(metadata_uri)
+            .send()
             .await
             .map_err(|e| format!("Failed to fetch metadata: {}", e))?;
         
@@ -100,7 +486,39 @@ impl GlitchGangPrivacyClient {
         Ok(metadata)
     }
     
+    /// Publish a signed revocation list, returning the URI renderers fetch it from
+    ///
+    /// Publish a new list, with a higher sequence, before the last one expires.
+    pub async fn publish_revocation_list(&self, list: &RevocationList, uploader: &dyn Uploader) -> Result<String, String> {
+        log::info!("Publishing revocation list {} for {}...", list.sequence, list.nft_mint);
+        
+        self.upload_file(uploader, &list.to_bytes(), "application/octet-stream").await
+            .map_err(|e| format!("Failed to publish revocation list: {}", e))
+    }
+    
+    /// Fetch a published revocation list and install it in the mask
+    ///
+    /// Returns the installed list's sequence.
+    pub async fn fetch_revocation_list(&self, uri: &str) -> Result<u64, String> {
+        let response = self.http_client.get(uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to fetch revocation list: {}", e))?;
+        let bytes = response.bytes()
+            .await
+            .map_err(|e| format!("Failed to fetch revocation list: {}", e))?;
+        
+        let list = RevocationList::from_bytes(&bytes)?;
+        let sequence = list.sequence;
+        self.sync_mask.write().unwrap().install_revocation_list(list)?;
+        
+        Ok(sequence)
+    }
+    
     /// Create privacy wrapper for existing NFT
+    ///
+    /// The owner must hold the NFT in their associated token account.
     pub async fn create_wrapper(
         &self,
         nft_mint: &Pubkey,
@@ -116,35 +534,29 @@ impl GlitchGangPrivacyClient {
         ];
         
         // Create synchronicity mask config
-        let sync_mask_config = self.sync_mask.create_config(
+        let sync_mask_config = self.sync_mask.write().unwrap().create_config(
             nft_mint,
             &self.owner_keypair.pubkey(),
             PrivacyLevel::Medium,
         );
         
-        // Create quantum veil config
-        let qv_sync_mask = QVSyncMask {
-            position_noise: 0.5,
-            voice_noise: 0.7,
-            gesture_noise: 0.3,
-            trusted_agents: Vec::new(),
-        };
-        
-        let privacy_config = self.quantum_veil.create_config(
-            &self.owner_keypair.pubkey(),
-            nft_mint,
+        // Create quantum veil config mirroring the mask settings
+        let privacy_config = self.quantum_veil.write().unwrap().create_config(
+            &self.owner_keypair.pubkey().to_string(),
+            &nft_mint.to_string(),
             entropy_sources,
             3600, // Rotate key every hour
-            qv_sync_mask,
-        );
+            Self::qv_sync_mask(&sync_mask_config),
+        )?;
         
         // Get config hash
-        let privacy_config_hash = self.quantum_veil.get_config_hash(&privacy_config);
+        let privacy_config_hash = self.wrapper_config_hash(&privacy_config, &sync_mask_config);
         
         // Create wrapper account
         let wrapper_account = Keypair::new();
         
-        // Prepare instruction
+        // Prepare instruction; the program checks the owner holds the NFT in its token account
+        let token_account = get_associated_token_address(&self.owner_keypair.pubkey(), nft_mint);
         let instruction = Instruction {
             program_id: self.program_id,
             accounts: vec![
@@ -153,6 +565,7 @@ impl GlitchGangPrivacyClient {
                 AccountMeta::new(wrapper_account.pubkey(), true),
                 AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
                 AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false),
+                AccountMeta::new_readonly(token_account, false),
             ],
             data: WrapperInstruction::CreateWrapper {
                 privacy_config_hash: privacy_config_hash.clone(),
@@ -162,57 +575,237 @@ impl GlitchGangPrivacyClient {
         };
         
         // Create and send transaction
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.owner_keypair.pubkey()),
-            &[&self.owner_keypair, &wrapper_account],
-            self.rpc_client.get_latest_blockhash().map_err(|e| e.to_string())?,
-        );
+        let transaction = self.build_transaction(&[instruction], &[&wrapper_account])?;
         
-        let signature = self.rpc_client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| format!("Failed to send transaction: {}", e))?;
+        let signature = self.send_transaction("transaction.create_wrapper", &transaction).await?;
         
         log::info!("Wrapper created! Signature: {}", signature);
         
         Ok(wrapper_account.pubkey())
     }
     
-    /// Apply privacy protections to metadata
+    /// Get an identifier for the current encryption key
+    ///
+    /// Derived one-way from the key, so it can be stored to tell which key a
+    /// piece of metadata needs without revealing the key.
+    pub fn key_id(&self) -> String {
+        match &*self.provided_key.read().unwrap() {
+            Some(provided) => provided.key_id.clone(),
+            None => key_id_of(self.encryption_key.read().unwrap().expose()),
+        }
+    }
+    
+    /// Export every key the client holds, sealed under a passphrase, as keystore JSON
+    ///
+    /// Covers the encryption key, each NFT's privacy config with its rotation
+    /// history, and the recovery phrase if configured, so protected metadata
+    /// stays decryptable after a restart.
+    pub fn export_keys(&self, passphrase: &str) -> Result<String, String> {
+        let configs: Vec<PrivacyConfig> = self.quantum_veil.read().unwrap().configs().cloned().collect();
+        let mut bundle = self.key_bundle(&configs)?;
+        bundle.mnemonic_entropy = self.mnemonic_entropy.read().unwrap().clone();
+        
+        Keystore::seal(&bundle, &self.key_id(), passphrase, KdfParams::default())?.to_json()
+    }
+    
+    /// Collect the encryption key and the key records of the given configs
+    fn key_bundle(&self, configs: &[PrivacyConfig]) -> Result<KeyBundle, String> {
+        if let Some(provided) = &*self.provided_key.read().unwrap() {
+            return Err(format!("Keys held by key provider {} cannot be exported", provided.provider.key_id()));
+        }
+        
+        let quantum_veil = self.quantum_veil.read().unwrap();
+        let key_rotations = self.key_rotations.read().unwrap();
+        let mints = configs.iter()
+            .map(|config| {
+                let key = quantum_veil.data_key(config)?;
+                
+                Ok((config.nft_mint.clone(), MintKeyRecord {
+                    key_id: key_id_of(key.expose()),
+                    config: config.clone(),
+                    rotations: key_rotations.get(&config.nft_mint).cloned().unwrap_or_default(),
+                }))
+            })
+            .collect::<Result<_, String>>()?;
+        
+        Ok(KeyBundle {
+            encryption_key: self.encryption_key.read().unwrap().clone(),
+            mints,
+            mnemonic_entropy: None,
+        })
+    }
+    
+    /// Import keys exported with `export_keys`, replacing the current encryption key
+    ///
+    /// Per-NFT configs in the keystore replace any the client already holds
+    /// for the same mint.
+    pub fn import_keys(&self, keystore_json: &str, passphrase: &str) -> Result<(), String> {
+        let bundle = Keystore::from_json(keystore_json)?.open(passphrase)?;
+        self.restore_keys(bundle)?;
+        
+        log::info!("Imported keys with key id {}", self.key_id());
+        Ok(())
+    }
+    
+    /// Load keys from keystore JSON, unlocking it with a passkey or keychain slot if possible
+    ///
+    /// Falls back to the passphrase when the provider has no slot in the
+    /// keystore or fails, e.g. because the user cancelled the passkey prompt.
+    /// Enroll a provider with `Keystore::add_unlock`.
+    pub fn with_keystore(
+        self,
+        keystore_json: &str,
+        unlock: Option<&dyn UnlockProvider>,
+        passphrase: Option<&str>,
+    ) -> Result<Self, String> {
+        let bundle = Keystore::from_json(keystore_json)?.unlock(unlock, passphrase)?;
+        self.restore_keys(bundle)?;
+        
+        log::info!("Unlocked keystore with key id {}", self.key_id());
+        Ok(self)
+    }
+    
+    /// Replace the encryption key and per-NFT key records with a bundle's
+    fn restore_keys(&self, bundle: KeyBundle) -> Result<(), String> {
+        if bundle.encryption_key.len() != 32 {
+            return Err("Invalid encryption key length".to_string());
+        }
+        *self.encryption_key.write().unwrap() = bundle.encryption_key;
+        *self.provided_key.write().unwrap() = None;
+        if let Some(entropy) = bundle.mnemonic_entropy {
+            self.set_mnemonic(entropy)?;
+        }
+        
+        for (nft_mint, record) in bundle.mints {
+            self.quantum_veil.write().unwrap().update_config(&nft_mint, record.config)?;
+            self.key_rotations.write().unwrap().insert(nft_mint, record.rotations);
+        }
+        
+        Ok(())
+    }
+    
+    /// Export an NFT's wrapper, local configs and fragment manifest as a signed JSON snapshot
+    ///
+    /// The NFT's keys are sealed under `passphrase`; the rest stays readable.
+    /// Pass the manifest the drift scheduler tracks for the NFT, if its
+    /// metadata was fractured.
+    pub fn export_wrapper_state(
+        &self,
+        nft_mint: &Pubkey,
+        manifest: Option<&FragmentManifest>,
+        passphrase: &str,
+    ) -> Result<String, String> {
+        let wrapper_account = self.find_wrapper(nft_mint)?;
+        let wrapper_data = self.rpc_client.get_account_data(&wrapper_account)
+            .map_err(|e| format!("Failed to fetch wrapper {}: {}", wrapper_account, e))?;
+        
+        let config = self.privacy_config(nft_mint)?;
+        let bundle = self.key_bundle(&[config])?;
+        let keys = Keystore::seal(&bundle, &self.key_id(), passphrase, KdfParams::default())?;
+        
+        let exported_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Failed to get current time: {}", e))?
+            .as_secs() as i64;
+        
+        let mut snapshot = WrapperSnapshot::new(
+            &self.program_id,
+            &wrapper_account,
+            nft_mint,
+            &wrapper_data,
+            keys,
+            exported_at,
+        );
+        if let Ok(mask_config) = self.get_mask_config(nft_mint) {
+            snapshot = snapshot.with_mask_config(mask_config);
+        }
+        if let Some(manifest) = manifest {
+            snapshot = snapshot.with_manifest(manifest.clone());
+        }
+        
+        log::info!("Exported wrapper state of NFT {}", nft_mint);
+        snapshot.sign(&self.owner_keypair).to_json()
+    }
+    
+    /// Restore an NFT's privacy setup from a snapshot taken with `export_wrapper_state`
+    ///
+    /// The snapshot must be signed by this client's owner. Like `import_keys`,
+    /// this replaces the encryption key. The returned snapshot carries the
+    /// fragment manifest for the caller to track in its drift scheduler.
+    pub fn import_wrapper_state(&self, snapshot_json: &str, passphrase: &str) -> Result<WrapperSnapshot, String> {
+        let snapshot = WrapperSnapshot::from_json(snapshot_json)?;
+        snapshot.verify()?;
+        
+        if snapshot.owner != self.owner_keypair.pubkey() {
+            return Err(format!("Snapshot belongs to {}, not this client's owner", snapshot.owner));
+        }
+        if snapshot.program_id != self.program_id {
+            return Err(format!("Snapshot is for wrapper program {}, not {}", snapshot.program_id, self.program_id));
+        }
+        
+        let bundle = snapshot.keys.open(passphrase)?;
+        if let Some(mask_config) = &snapshot.mask_config {
+            self.sync_mask.write().unwrap().update_config(&snapshot.nft_mint.to_string(), mask_config.clone())?;
+        }
+        self.restore_keys(bundle)?;
+        
+        log::info!("Imported wrapper state of NFT {} exported at {}", snapshot.nft_mint, snapshot.exported_at);
+        Ok(snapshot)
+    }
+    
+    /// Get the privacy config of an NFT
+    pub fn privacy_config(&self, nft_mint: &Pubkey) -> Result<PrivacyConfig, String> {
+        self.quantum_veil.read().unwrap().get_config(&nft_mint.to_string())
+    }
+    
+    /// Get the rotation history of an NFT's key, oldest first
+    pub fn key_rotations(&self, nft_mint: &Pubkey) -> Vec<KeyRotation> {
+        self.key_rotations.read().unwrap().get(&nft_mint.to_string()).cloned().unwrap_or_default()
+    }
+    
+    /// Apply privacy protections to metadata using the default protection policy
     pub async fn protect_metadata(
-        &mut self,
+        &self,
+        metadata: &GlitchGangMetadata,
+        privacy_level: PrivacyLevel,
+    ) -> Result<ProtectionReport, String> {
+        self.protect_metadata_with_policy(metadata, &ProtectionPolicy::default(), privacy_level).await
+    }
+    
+    /// Apply privacy protections to metadata
+    pub async fn protect_metadata_with_policy(
+        &self,
         metadata: &GlitchGangMetadata,
+        policy: &ProtectionPolicy,
         privacy_level: PrivacyLevel,
-    ) -> Result<GlitchGangMetadata, String> {
+    ) -> Result<ProtectionReport, String> {
         log::info!("Applying privacy protections to metadata...");
         
         let mut protected_metadata = metadata.clone();
         
-        // Select sensitive attributes to protect based on privacy level
-        let sensitive_attributes = match privacy_level {
-            PrivacyLevel::None => Vec::new(),
-            PrivacyLevel::Light => vec!["Secret Code", "Agent Name"],
-            PrivacyLevel::Medium => vec!["Secret Code", "Agent Name", "Mission", "Origin"],
-            PrivacyLevel::Heavy | PrivacyLevel::Complete => {
-                vec!["Secret Code", "Agent Name", "Mission", "Origin", "Accessory", "Symbols"]
-            }
-        };
-        
         // Extract sensitive attributes
-        let mut private_attrs = Vec::new();
-        let mut public_attrs = Vec::new();
+        let (private_attrs, public_attrs) = self.partition(metadata, policy, privacy_level)?;
         
-        for attr in &metadata.attributes {
-            if sensitive_attributes.contains(&attr.trait_type.as_str()) {
-                private_attrs.push(attr.clone());
-            } else {
-                public_attrs.push(attr.clone());
-            }
-        }
+        let generalized_traits: Vec<String> = public_attrs.iter()
+            .filter(|attr| policy.generalizes(&attr.trait_type, privacy_level))
+            .map(|attr| attr.trait_type.clone())
+            .collect();
+        let format_preserved: HashMap<String, FormatPreserving> = public_attrs.iter()
+            .filter_map(|attr| Some((attr.trait_type.clone(), policy.format_of(&attr.trait_type, privacy_level)?.clone())))
+            .collect();
+        let format_preserved_traits = format_preserved.keys().cloned().collect();
         
         // Replace protected attributes with placeholders
         protected_metadata.attributes = public_attrs;
         
+        let encrypted_traits: Vec<String> = private_attrs.iter()
+            .map(|attr| attr.trait_type.clone())
+            .collect();
+        let search_tokens = self.search_key()?.tokens(&private_attrs);
+        let mut fragment_manifest = None;
+        let mut encrypted_attributes = None;
+        let mut timeline_fragments = None;
+        
         // Only encrypt if we have sensitive attributes
         if !private_attrs.is_empty() {
             // Encrypt private attributes
@@ -220,41 +813,252 @@ impl GlitchGangPrivacyClient {
                 .map_err(|e| format!("Failed to serialize private attributes: {}", e))?;
             
             let encrypted = self.encrypt_data(private_json.as_bytes())?;
-            let encrypted_b64 = base64::encode(&encrypted);
+            encrypted_attributes = Some(base64::encode(&encrypted));
+            let content_key = self.content_key()?;
             
             // Fracture metadata if timeline shifter is available
-            let mut timeline_fragments = None;
-            if let Some(shifter) = &mut self.timeline_shifter {
+            if let Some(shifter) = self.timeline_shifter() {
                 let nft_id = metadata.name.clone();
                 
-                // Configure timeline distribution
-                let mut timeline_config = HashMap::new();
-                timeline_config.insert(TimelineType::Primary, 0.3);
-                timeline_config.insert(TimelineType::Identity, 0.15);
-                timeline_config.insert(TimelineType::Activity, 0.15);
-                timeline_config.insert(TimelineType::Social, 0.2);
-                timeline_config.insert(TimelineType::Financial, 0.2);
+                // Fracturing by section splits a JSON object by field, so key the traits by type
+                let fractured = match &policy.sections {
+                    Some(_) => {
+                        let traits: serde_json::Map<String, serde_json::Value> = private_attrs.iter()
+                            .map(|attr| (attr.trait_type.clone(), serde_json::Value::String(attr.value.clone())))
+                            .collect();
+                        serde_json::to_vec(&traits)
+                            .map_err(|e| format!("Failed to serialize private attributes: {}", e))?
+                    },
+                    None => private_json.as_bytes().to_vec(),
+                };
                 
-                let fragments = shifter.fracture_metadata(
+                // Deduplicated fragments converge across everything protected under this key;
+                // the others are bound to this NFT and its owner
+                let mut options = policy.fracture_options().with_owner(&self.owner_keypair.pubkey().to_string());
+                if policy.deduplicate {
+                    options = options.with_convergence_key(self.convergence_key()?);
+                }
+                
+                let manifest = shifter.fracture_metadata(
                     &nft_id,
-                    private_json.as_bytes(),
-                    &self.encryption_key,
-                    timeline_config,
+                    &fractured,
+                    content_key.expose(),
+                    policy.timeline_distribution.clone(),
+                    &options,
                 ).await?;
                 
-                timeline_fragments = Some(fragments);
+                timeline_fragments = Some(manifest.fragment_ids.clone());
+                fragment_manifest = Some(manifest);
             }
-            
-            // Add private data section
+        }
+        
+        if encrypted_attributes.is_some() || !format_preserved.is_empty() {
+            // Add private data section, keeping assets and reveals protected earlier
+            let (protected_assets, timelocked_attributes, numeric_commitments) = protected_metadata.private_data.take()
+                .map(|private_data| (
+                    private_data.protected_assets,
+                    private_data.timelocked_attributes,
+                    private_data.numeric_commitments,
+                ))
+                .unwrap_or_default();
             protected_metadata.private_data = Some(PrivateData {
                 privacy_level: format!("{:?}", privacy_level),
-                encrypted_attributes: Some(encrypted_b64),
+                encrypted_attributes,
                 timeline_fragments,
                 vrm_config: None,
+                protected_assets,
+                timelocked_attributes,
+                numeric_commitments,
+                format_preserved,
+            });
+        }
+        
+        // Publish the manifest hash when fractured, otherwise a hash of the protected metadata
+        let config_hash = match &fragment_manifest {
+            Some(manifest) => manifest.hash(),
+            None => {
+                let metadata_json = serde_json::to_string(&protected_metadata)
+                    .map_err(|e| format!("Failed to serialize protected metadata: {}", e))?;
+                base64::encode(Sha3_512::digest(metadata_json.as_bytes()))
+            }
+        };
+        
+        Ok(ProtectionReport {
+            protected_metadata,
+            encrypted_traits,
+            generalized_traits,
+            format_preserved_traits,
+            search_tokens,
+            fragment_manifest,
+            config_hash,
+            key_id: self.key_id(),
+        })
+    }
+    
+    /// Split metadata attributes under a policy, encrypting in place those it formats
+    fn partition(
+        &self,
+        metadata: &GlitchGangMetadata,
+        policy: &ProtectionPolicy,
+        privacy_level: PrivacyLevel,
+    ) -> Result<(Vec<crate::models::Attribute>, Vec<crate::models::Attribute>), String> {
+        if policy.format_preserving.is_empty() {
+            return Ok(policy.partition(&metadata.attributes, privacy_level));
+        }
+        
+        Ok(policy.partition_with_cipher(&metadata.attributes, privacy_level, &self.fpe_cipher()?, &metadata.name))
+    }
+    
+    /// Get the format-preserving cipher, keyed from the encryption key
+    fn fpe_cipher(&self) -> Result<FpeCipher, String> {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.content_key()?.expose());
+        let fpe_key: [u8; 32] = hmac::sign(&key, FPE_KEY_LABEL).as_ref()
+            .try_into()
+            .map_err(|_| "Invalid format-preserving key length".to_string())?;
+        Ok(FpeCipher::new(&fpe_key))
+    }
+    
+    /// Get the key identical fragments converge under, derived from the encryption key
+    fn convergence_key(&self) -> Result<SecretBytes, String> {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.content_key()?.expose());
+        Ok(SecretBytes::from(hmac::sign(&key, CONVERGENCE_KEY_LABEL).as_ref()))
+    }
+    
+    /// Get the key computing search tokens for hidden attributes, derived from the encryption key
+    ///
+    /// Tokens indexed before the encryption key is replaced no longer match.
+    pub fn search_key(&self) -> Result<SearchKey, String> {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.content_key()?.expose());
+        Ok(SearchKey::new(hmac::sign(&key, SEARCH_KEY_LABEL).as_ref()))
+    }
+    
+    /// Preview what each privacy level would hide, without encrypting or storing anything
+    ///
+    /// Fragment layouts are only planned when a timeline shifter is configured,
+    /// matching `protect_metadata_with_policy`, and are priced at list rates.
+    pub fn protect_preview(
+        &self,
+        metadata: &GlitchGangMetadata,
+        policy: &ProtectionPolicy,
+    ) -> Result<ProtectionPreview, String> {
+        let mut levels = Vec::new();
+        
+        for privacy_level in [
+            PrivacyLevel::None,
+            PrivacyLevel::Light,
+            PrivacyLevel::Medium,
+            PrivacyLevel::Heavy,
+            PrivacyLevel::Complete,
+        ] {
+            let (private_attrs, public_attrs) = self.partition(metadata, policy, privacy_level)?;
+            let hidden_traits: Vec<String> = private_attrs.iter().map(|attr| attr.trait_type.clone()).collect();
+            
+            let private_bytes = if private_attrs.is_empty() {
+                0
+            } else {
+                serde_json::to_vec(&private_attrs)
+                    .map_err(|e| format!("Failed to serialize private attributes: {}", e))?
+                    .len()
+            };
+            
+            // Mirror protection: only non-empty private attributes are fractured
+            let fracture_plan = match self.timeline_shifter() {
+                Some(_) if private_bytes > 0 => Some(TimelineShifter::plan_fracture(
+                    private_bytes,
+                    &policy.timeline_distribution,
+                    &policy.fracture_options(),
+                )?),
+                _ => None,
+            };
+            
+            let decoyed_traits = if fracture_plan.is_some() && policy.decoy_count > 0 {
+                hidden_traits.clone()
+            } else {
+                Vec::new()
+            };
+            
+            levels.push(LevelPreview {
+                privacy_level,
+                hidden_traits,
+                decoyed_traits,
+                visible_traits: public_attrs.iter().map(|attr| attr.trait_type.clone()).collect(),
+                generalized_traits: public_attrs.iter()
+                    .filter(|attr| policy.generalizes(&attr.trait_type, privacy_level))
+                    .map(|attr| attr.trait_type.clone())
+                    .collect(),
+                private_bytes,
+                fracture_plan,
             });
         }
         
-        Ok(protected_metadata)
+        Ok(ProtectionPreview { levels })
+    }
+    
+    /// Wrap an NFT and protect its metadata in one call
+    ///
+    /// Fetches the current metadata, protects and fractures it, uploads the
+    /// protected JSON, creates the wrapper, and publishes the config hash.
+    /// If any step after fracturing fails, the stored fragments are deleted.
+    pub async fn wrap_and_protect(
+        &self,
+        nft_mint: &Pubkey,
+        policy: &ProtectionPolicy,
+        privacy_level: PrivacyLevel,
+        uploader: &dyn Uploader,
+    ) -> Result<WrapSummary, String> {
+        log::info!("Wrapping and protecting NFT: {}", nft_mint);
+        
+        // Step 1: Fetch the current metadata
+        let token_metadata = metaplex::fetch_token_metadata(&self.rpc_client, nft_mint)?;
+        let metadata = self.fetch_metadata(&token_metadata.uri).await?;
+        
+        // Step 2: Protect and fracture
+        let report = self.protect_metadata_with_policy(&metadata, policy, privacy_level).await?;
+        
+        // Step 3: Publish, rolling back the fragments on failure
+        match self.publish_protection(nft_mint, &metadata, &report, uploader).await {
+            Ok((wrapper_account, metadata_uri, signature)) => Ok(WrapSummary {
+                wrapper_account,
+                metadata_uri,
+                update_signature: signature,
+                report,
+            }),
+            Err(e) => {
+                self.rollback_fragments(&report).await;
+                Err(e)
+            }
+        }
+    }
+    
+    /// Upload protected metadata, create the wrapper, and publish the config hash
+    async fn publish_protection(
+        &self,
+        nft_mint: &Pubkey,
+        metadata: &GlitchGangMetadata,
+        report: &ProtectionReport,
+        uploader: &dyn Uploader,
+    ) -> Result<(Pubkey, String, String), String> {
+        let protected_json = serde_json::to_vec(&report.protected_metadata)
+            .map_err(|e| format!("Failed to serialize protected metadata: {}", e))?;
+        let metadata_uri = self.upload_json(uploader, &protected_json).await
+            .map_err(|e| format!("Failed to upload protected metadata: {}", e))?;
+        
+        let wrapper_account = self.create_wrapper(nft_mint, metadata).await?;
+        let signature = self.update_privacy_settings(&wrapper_account, &report.config_hash).await
+            .map_err(|e| format!("Wrapper {} created but config hash not published: {}", wrapper_account, e))?;
+        
+        Ok((wrapper_account, metadata_uri, signature))
+    }
+    
+    /// Delete the fragments stored for a protection that was not published
+    async fn rollback_fragments(&self, report: &ProtectionReport) {
+        let (Some(shifter), Some(manifest)) = (self.timeline_shifter(), &report.fragment_manifest) else {
+            return;
+        };
+        
+        if let Err(e) = shifter.delete_fragments(&manifest.fragment_ids).await {
+            log::error!("Failed to roll back fragments for {}: {}", manifest.nft_mint, e);
+        }
     }
     
     /// Decrypt protected metadata
@@ -264,6 +1068,17 @@ impl GlitchGangPrivacyClient {
         let mut decrypted_metadata = protected_metadata.clone();
         
         if let Some(private_data) = &protected_metadata.private_data {
+            // Decrypt attributes encrypted in place
+            if !private_data.format_preserved.is_empty() {
+                let cipher = self.fpe_cipher()?;
+                for attr in decrypted_metadata.attributes.iter_mut() {
+                    if let Some(format) = private_data.format_preserved.get(&attr.trait_type) {
+                        let tweak = fpe::tweak(&protected_metadata.name, &attr.trait_type);
+                        attr.value = cipher.decrypt(format, &tweak, &attr.value)?;
+                    }
+                }
+            }
+            
             if let Some(encrypted_b64) = &private_data.encrypted_attributes {
                 // Decode base64
                 let encrypted = base64::decode(encrypted_b64)
@@ -276,7 +1091,10 @@ impl GlitchGangPrivacyClient {
                 let private_attrs: Vec<crate::models::Attribute> = serde_json::from_slice(&decrypted)
                     .map_err(|e| format!("Failed to parse private attributes: {}", e))?;
                 
-                // Add private attributes back to metadata
+                // Add private attributes back to metadata, replacing generalized values
+                decrypted_metadata.attributes.retain(|attr| {
+                    !private_attrs.iter().any(|private| private.trait_type == attr.trait_type)
+                });
                 decrypted_metadata.attributes.extend(private_attrs);
             }
         }
@@ -284,21 +1102,18 @@ impl GlitchGangPrivacyClient {
         Ok(decrypted_metadata)
     }
     
-    /// Encrypt data
+    /// Encrypt data into an envelope under the content key
     fn encrypt_data(&self, data: &[u8]) -> Result<Vec<u8>, String> {
-        // Use HMAC as a simple encryption method (in a real system, use ChaCha20Poly1305)
-        let key = hmac::Key::new(hmac::HMAC_SHA256, &self.encryption_key);
-        let tag = hmac::sign(&key, data);
-        
-        // Combine tag and data
-        let mut encrypted = tag.as_ref().to_vec();
-        encrypted.extend_from_slice(data);
-        
-        Ok(encrypted)
+        seal(data, self.content_key()?.expose())
     }
     
     /// Decrypt data
+    ///
+    /// Data from before envelopes is checked against its HMAC tag.
     fn decrypt_data(&self, encrypted: &[u8]) -> Result<Vec<u8>, String> {
+        if is_envelope(encrypted) {
+            return open(encrypted, self.content_key()?.expose());
+        }
         if encrypted.len() < 32 {
             return Err("Encrypted data too short".to_string());
         }
@@ -308,7 +1123,7 @@ impl GlitchGangPrivacyClient {
         let data = &encrypted[32..];
         
         // Verify with HMAC
-        let key = hmac::Key::new(hmac::HMAC_SHA256, &self.encryption_key);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.content_key()?.expose());
         
         match hmac::verify(&key, data, tag) {
             Ok(_) => Ok(data.to_vec()),
@@ -316,29 +1131,180 @@ impl GlitchGangPrivacyClient {
         }
     }
     
-    /// Add VRM privacy settings
-    pub fn add_vrm_privacy(&mut self, metadata: &mut GlitchGangMetadata, model_uri: &str) -> Result<(), String> {
-        log::info!("Adding VRM privacy settings...");
+    /// Encrypt and upload an asset, e.g. the NFT image or VRM file, recording it in the private data
+    ///
+    /// Images get a degraded public preview, which replaces the image in the
+    /// metadata. The asset key is sealed with the owner's encryption key.
+    pub async fn protect_asset(
+        &self,
+        metadata: &mut GlitchGangMetadata,
+        asset: &[u8],
+        content_type: &str,
+        preview_style: Option<PreviewStyle>,
+        uploader: &dyn Uploader,
+    ) -> Result<ProtectedAsset, String> {
+        log::info!("Protecting {} asset of {} bytes...", content_type, asset.len());
         
-        let privacy_settings = HashMap::from([
-            ("position".to_string(), "medium".to_string()),
-            ("voice".to_string(), "high".to_string()),
-            ("gesture".to_string(), "medium".to_string()),
-            ("animation".to_string(), "low".to_string()),
-        ]);
+        // Step 1: Encrypt and upload the asset under a fresh key
+        let mut asset_key = [0u8; 32];
+        OsRng.fill(&mut asset_key);
         
-        let vrm_config = VrmConfig {
-            model_uri: model_uri.to_string(),
-            privacy_settings,
+        let mut encrypted = Vec::with_capacity(asset.len() + asset.len() / assets::ASSET_CHUNK_LEN * 16 + 32);
+        assets::encrypt_stream(asset, &mut encrypted, &asset_key)?;
+        let uri = self.upload_file(uploader, &encrypted, "application/octet-stream").await
+            .map_err(|e| format!("Failed to upload encrypted asset: {}", e))?;
+        
+        // Step 2: Publish a degraded preview of images
+        let preview_uri = match preview_style {
+            Some(style) if assets::is_image(asset) => {
+                let preview = assets::degraded_preview(asset, style)?;
+                Some(self.upload_file(uploader, &preview, "image/png").await
+                    .map_err(|e| format!("Failed to upload asset preview: {}", e))?)
+            },
+            _ => None,
         };
         
-        // Create or update private data section
-        if metadata.private_data.is_none() {
-            metadata.private_data = Some(PrivateData {
+        if let Some(preview_uri) = &preview_uri {
+            for file in metadata.properties.files.iter_mut().filter(|file| file.uri == metadata.image) {
+                file.uri = preview_uri.clone();
+            }
+            metadata.image = preview_uri.clone();
+        }
+        
+        // Step 3: Seal the asset key and record the pointers
+        let sealed_key = seal(&asset_key[..], self.content_key()?.expose())?;
+        
+        let protected_asset = ProtectedAsset {
+            uri,
+            content_type: content_type.to_string(),
+            encrypted_key: base64::encode(&sealed_key),
+            sha256: digest::digest(&digest::SHA256, asset).as_ref().iter().map(|b| format!("{:02x}", b)).collect(),
+            preview_uri,
+            shared_keys: HashMap::new(),
+        };
+        
+        metadata.private_data
+            .get_or_insert_with(|| PrivateData {
+                privacy_level: "Medium".to_string(),
+                encrypted_attributes: None,
+                timeline_fragments: None,
+                vrm_config: None,
+                protected_assets: Vec::new(),
+                timelocked_attributes: Vec::new(),
+                numeric_commitments: Vec::new(),
+                format_preserved: HashMap::new(),
+            })
+            .protected_assets
+            .push(protected_asset.clone());
+        
+        Ok(protected_asset)
+    }
+    
+    /// Decrypt an asset downloaded from a `ProtectedAsset` URI
+    pub fn decrypt_asset(&self, protected_asset: &ProtectedAsset, encrypted: &[u8]) -> Result<Vec<u8>, String> {
+        let asset_key = self.asset_key(protected_asset)?;
+        
+        Self::open_asset(protected_asset, encrypted, &asset_key)
+    }
+    
+    /// Wrap a protected asset's key to a viewer, so they can decrypt it with their own wallet
+    ///
+    /// Viewers publishing an ML-KEM key get a hybrid post-quantum wrap when
+    /// the client is built with the `quantum` feature, and a classical X25519
+    /// wrap otherwise. Sharing again with the same viewer replaces their key.
+    pub fn share_asset(&self, protected_asset: &mut ProtectedAsset, viewer: &ViewerKey) -> Result<(), String> {
+        let asset_key = self.asset_key(protected_asset)?;
+        let wrapped = key_wrap::wrap_key(&asset_key, viewer)?;
+        
+        log::info!("Sharing {} asset with {}", protected_asset.content_type, viewer.account);
+        protected_asset.shared_keys.insert(viewer.account.to_string(), base64::encode(wrapped));
+        Ok(())
+    }
+    
+    /// Get the viewer key to publish so owners can share assets with this wallet
+    pub fn viewer_key(&self) -> ViewerKey {
+        ViewerKey::for_keypair(&self.owner_keypair)
+    }
+    
+    /// Decrypt an asset shared with this wallet through `share_asset`
+    pub fn decrypt_shared_asset(&self, protected_asset: &ProtectedAsset, encrypted: &[u8]) -> Result<Vec<u8>, String> {
+        let viewer = self.owner_keypair.pubkey();
+        let wrapped = protected_asset.shared_keys.get(&viewer.to_string())
+            .ok_or_else(|| format!("Asset is not shared with {}", viewer))?;
+        let wrapped = base64::decode(wrapped)
+            .map_err(|e| format!("Failed to decode shared asset key: {}", e))?;
+        
+        let asset_key = key_wrap::unwrap_key(&wrapped, &self.owner_keypair)?;
+        Self::open_asset(protected_asset, encrypted, &asset_key)
+    }
+    
+    /// Unseal a protected asset's key with the encryption key
+    fn asset_key(&self, protected_asset: &ProtectedAsset) -> Result<Zeroizing<[u8; 32]>, String> {
+        let sealed_key = base64::decode(&protected_asset.encrypted_key)
+            .map_err(|e| format!("Failed to decode asset key: {}", e))?;
+        
+        let asset_key = Zeroizing::new(open(&sealed_key, self.content_key()?.expose())?);
+        let mut key = Zeroizing::new([0u8; 32]);
+        if asset_key.len() != key.len() {
+            return Err("Invalid asset key length".to_string());
+        }
+        key.copy_from_slice(&asset_key);
+        Ok(key)
+    }
+    
+    /// Decrypt an asset with its key and check it against the recorded hash
+    fn open_asset(protected_asset: &ProtectedAsset, encrypted: &[u8], asset_key: &[u8; 32]) -> Result<Vec<u8>, String> {
+        let mut asset = Vec::with_capacity(encrypted.len());
+        assets::decrypt_stream(encrypted, &mut asset, asset_key)?;
+        
+        let sha256: String = digest::digest(&digest::SHA256, &asset).as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        if sha256 != protected_asset.sha256 {
+            return Err("Decrypted asset does not match its recorded hash".to_string());
+        }
+        
+        Ok(asset)
+    }
+    
+    /// Decrypt an asset for a viewer, watermarked with their fingerprint so leaks can be traced
+    pub fn disclose_asset(
+        &self,
+        protected_asset: &ProtectedAsset,
+        encrypted: &[u8],
+        viewer: &Pubkey,
+    ) -> Result<Vec<u8>, String> {
+        let asset = self.decrypt_asset(protected_asset, encrypted)?;
+        log::info!("Disclosing {} asset to {}", protected_asset.content_type, viewer);
+        
+        watermark::watermark_asset(&asset, viewer)
+    }
+    
+    /// Add VRM privacy settings
+    pub fn add_vrm_privacy(&self, metadata: &mut GlitchGangMetadata, model_uri: &str) -> Result<(), String> {
+        log::info!("Adding VRM privacy settings...");
+        
+        let privacy_settings = HashMap::from([
+            ("position".to_string(), "medium".to_string()),
+            ("voice".to_string(), "high".to_string()),
+            ("gesture".to_string(), "medium".to_string()),
+            ("animation".to_string(), "low".to_string()),
+        ]);
+        
+        let vrm_config = VrmConfig {
+            model_uri: model_uri.to_string(),
+            privacy_settings,
+        };
+        
+        // Create or update private data section
+        if metadata.private_data.is_none() {
+            metadata.private_data = Some(PrivateData {
                 privacy_level: "Medium".to_string(),
                 encrypted_attributes: None,
                 timeline_fragments: None,
                 vrm_config: Some(vrm_config),
+                protected_assets: Vec::new(),
+                timelocked_attributes: Vec::new(),
+                numeric_commitments: Vec::new(),
+                format_preserved: HashMap::new(),
             });
         } else {
             metadata.private_data.as_mut().unwrap().vrm_config = Some(vrm_config);
@@ -357,140 +1323,2982 @@ impl GlitchGangPrivacyClient {
         log::info!("Processing VRM data with privacy protections...");
         
         // Apply synchronicity mask
-        self.sync_mask.apply_mask(
+        self.sync_mask.read().unwrap().apply_mask(
             &nft_mint.to_string(),
             vrm_data,
             viewer_id,
         )
     }
     
+    /// Process VRM data at a one-off privacy level, ignoring the stored mask settings
+    ///
+    /// Every VRM data type is masked at `privacy_level`, which is useful for
+    /// previews and demos; no viewer sees through the mask.
+    pub fn process_vrm_data_with_level(
+        &self,
+        vrm_data: &VrmData,
+        privacy_level: PrivacyLevel,
+        nft_mint: &Pubkey,
+    ) -> Result<VrmData, String> {
+        log::info!("Processing VRM data at {:?} privacy...", privacy_level);
+        
+        self.sync_mask.read().unwrap().apply_mask_with_level(&nft_mint.to_string(), vrm_data, privacy_level)
+    }
+    
+    /// Process VRM data for a viewer holding an on-chain access level
+    ///
+    /// Read the level with `access_level` on an `AccessCache` rather than
+    /// from the wrapper on every frame.
+    pub fn process_vrm_data_with_access_level(
+        &self,
+        vrm_data: &VrmData,
+        viewer: &Pubkey,
+        access_level: u8,
+        nft_mint: &Pubkey,
+    ) -> Result<VrmData, String> {
+        self.sync_mask.read().unwrap().apply_mask_with_access_level(
+            &nft_mint.to_string(),
+            vrm_data,
+            &viewer.to_string(),
+            access_level,
+        )
+    }
+    
     /// Grant access to a specific account
     pub async fn grant_access(
         &self,
         wrapper_account: &Pubkey,
-        account_id: &str,
+        grantee: &Pubkey,
         access_level: u8,
     ) -> Result<String, String> {
-        log::info!("Granting access to {} with level {}...", account_id, access_level);
+        log::info!("Granting access to {} with level {}...", grantee, access_level);
         
-        // Prepare instruction
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts: vec![
-                AccountMeta::new(self.owner_keypair.pubkey(), true),
-                AccountMeta::new(*wrapper_account, false),
-            ],
-            data: WrapperInstruction::GrantAccess {
-                account: account_id.to_string(),
+        self.send_owner_instruction(
+            wrapper_account,
+            WrapperInstruction::GrantAccess {
+                grantee: *grantee,
+                level: access_level,
+            },
+        ).await
+    }
+    
+    /// Grant the lowest access level that sees through a privacy level
+    pub async fn grant_privacy_level(
+        &self,
+        wrapper_account: &Pubkey,
+        grantee: &Pubkey,
+        privacy_level: PrivacyLevel,
+    ) -> Result<String, String> {
+        let access_level = self.sync_mask.read().unwrap().access_policy().min_access_level(privacy_level);
+        
+        self.grant_access(wrapper_account, grantee, access_level).await
+    }
+    
+    /// Grant access to a fresh one-time address only the viewer can link to themselves
+    ///
+    /// The ephemeral key rides along as an extra account the program ignores,
+    /// so the viewer can find the grant by scanning the wrapper's history.
+    pub async fn grant_stealth_access(
+        &self,
+        wrapper_account: &Pubkey,
+        meta_address: &StealthMetaAddress,
+        access_level: u8,
+    ) -> Result<(String, StealthAnnouncement), String> {
+        let announcement = meta_address.derive_address()?;
+        log::info!("Granting stealth access to {} with level {}...", announcement.address, access_level);
+        
+        let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+        let mut accounts = vec![
+            AccountMeta::new(self.owner_keypair.pubkey(), true),
+            AccountMeta::new(*wrapper_account, false),
+            AccountMeta::new_readonly(lock_account, false),
+        ];
+        accounts.extend(self.freeze_accounts(wrapper_account)?);
+        accounts.push(AccountMeta::new_readonly(announcement.ephemeral, false));
+        
+        let signature = self.send_instruction(
+            "transaction.grant_access",
+            accounts,
+            WrapperInstruction::GrantAccess {
+                grantee: announcement.address,
                 level: access_level,
+            },
+        ).await?;
+        
+        Ok((signature, announcement))
+    }
+    
+    /// Collect the stealth grants announced in a wrapper's transaction history
+    pub fn stealth_announcements(&self, wrapper_account: &Pubkey) -> Result<Vec<StealthAnnouncement>, String> {
+        let statuses = self.rpc_client.get_signatures_for_address(wrapper_account)
+            .map_err(|e| format!("Failed to fetch history of {}: {}", wrapper_account, e))?;
+        
+        let mut announcements = Vec::new();
+        for status in statuses.into_iter().filter(|status| status.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)
+                .map_err(|e| format!("Invalid signature {}: {}", status.signature, e))?;
+            let transaction = self.rpc_client.get_transaction(&signature, UiTransactionEncoding::Base64)
+                .map_err(|e| format!("Failed to fetch transaction {}: {}", signature, e))?
+                .transaction
+                .transaction
+                .decode()
+                .ok_or_else(|| format!("Failed to decode transaction {}", signature))?;
+            
+            // The ephemeral key follows the lock, and the freeze configs on grants
+            // made since those were added
+            let keys = transaction.message.static_account_keys();
+            for instruction in transaction.message.instructions() {
+                if keys.get(instruction.program_id_index as usize) != Some(&self.program_id)
+                    || !matches!(instruction.accounts.len(), 4 | 7)
+                {
+                    continue;
+                }
+                if let Ok(WrapperInstruction::GrantAccess { grantee, .. }) = WrapperInstruction::try_from_slice(&instruction.data) {
+                    if let Some(ephemeral) = instruction.accounts.last().and_then(|index| keys.get(*index as usize)) {
+                        announcements.push(StealthAnnouncement { address: grantee, ephemeral: *ephemeral });
+                    }
+                }
             }
-            .try_to_vec()
-            .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        }
+        
+        Ok(announcements)
+    }
+    
+    /// Find a viewer's stealth grants still held on a wrapper, with their access levels
+    pub fn find_stealth_grants(
+        &self,
+        wrapper_account: &Pubkey,
+        keys: &StealthKeys,
+    ) -> Result<Vec<(StealthKeypair, u8)>, String> {
+        let state = self.fetch_wrapper_state(wrapper_account)?;
+        let announcements = self.stealth_announcements(wrapper_account)?;
+        
+        Ok(keys.scan(&announcements)
+            .into_iter()
+            .filter_map(|keypair| {
+                let level = state.access_controls.get(&keypair.pubkey()).copied()?;
+                Some((keypair, level))
+            })
+            .collect())
+    }
+    
+    /// Fetch and decode a privacy wrapper account
+    pub fn fetch_wrapper_state(&self, wrapper_account: &Pubkey) -> Result<WrapperState, String> {
+        let data = self.rpc_client.get_account_data(wrapper_account)
+            .map_err(|e| format!("Failed to fetch wrapper {}: {}", wrapper_account, e))?;
+        
+        WrapperState::unpack(&data)
+    }
+    
+    /// Find the wrapper account of an NFT
+    pub fn find_wrapper(&self, nft_mint: &Pubkey) -> Result<Pubkey, String> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &[2])),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(WRAPPER_MINT_OFFSET, nft_mint.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
         };
         
-        // Create and send transaction
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.owner_keypair.pubkey()),
-            &[&self.owner_keypair],
-            self.rpc_client.get_latest_blockhash().map_err(|e| e.to_string())?,
-        );
+        let accounts = self.rpc_client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| format!("Failed to fetch wrappers: {}", e))?;
+        
+        accounts.into_iter()
+            .next()
+            .map(|(address, _)| address)
+            .ok_or_else(|| format!("No wrapper found for NFT {}", nft_mint))
+    }
+    
+    /// Watch an NFT's wrapper for changes made after subscribing
+    ///
+    /// Updates arrive over the RPC node's PubSub websocket, which is not
+    /// proxied. The subscription ends when the stream is dropped and the next
+    /// change arrives, or when the websocket closes.
+    pub async fn subscribe_wrapper(&self, nft_mint: &Pubkey) -> Result<impl Stream<Item = WrapperUpdate>, String> {
+        let wrapper_account = self.find_wrapper(nft_mint)?;
+        let mut previous = self.fetch_wrapper_state(&wrapper_account)?;
         
-        let signature = self.rpc_client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| format!("Failed to send transaction: {}", e))?;
+        let websocket_url = self.websocket_url.clone().unwrap_or_else(|| websocket_url(&self.rpc_client.url()));
+        let pubsub = PubsubClient::new(&websocket_url)
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {}", websocket_url, e))?;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.rpc_client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        };
         
-        Ok(signature.to_string())
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        tokio::spawn(async move {
+            let (mut notifications, unsubscribe) = match pubsub.account_subscribe(&wrapper_account, Some(config)).await {
+                Ok(subscription) => subscription,
+                Err(e) => {
+                    log::error!("Failed to subscribe to wrapper {}: {}", wrapper_account, e);
+                    return;
+                },
+            };
+            
+            while let Some(response) = notifications.next().await {
+                let state = match response.value.data.decode().map(|data| WrapperState::unpack(&data)) {
+                    Some(Ok(state)) => state,
+                    Some(Err(e)) => {
+                        log::warn!("Skipping undecodable update to wrapper {}: {}", wrapper_account, e);
+                        continue;
+                    },
+                    None => continue,
+                };
+                
+                let updates = WrapperUpdate::diff(&previous, &state);
+                previous = state;
+                if updates.into_iter().any(|update| sender.unbounded_send(update).is_err()) {
+                    break;
+                }
+            }
+            
+            drop(notifications);
+            unsubscribe().await;
+        });
+        
+        Ok(receiver)
     }
     
-    /// Revoke access
-    pub async fn revoke_access(
+    /// Fetch a wrapper together with its privacy lock
+    pub fn inspect_wrapper(&self, wrapper_account: &Pubkey) -> Result<WrapperInspection, String> {
+        let state = self.fetch_wrapper_state(wrapper_account)?;
+        
+        // A lock PDA that was never created means the wrapper was never locked
+        let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+        let lock = self.fetch_program_account(&lock_account, "privacy lock")?
+            .map(|data| PrivacyLock::unpack(&data))
+            .transpose()?;
+        
+        Ok(WrapperInspection { state, lock })
+    }
+    
+    /// Fetch and decode a collection wrapper by collection mint
+    pub fn fetch_collection(&self, collection_mint: &Pubkey) -> Result<CollectionWrapper, String> {
+        let (collection_account, _) = find_collection_address(&self.program_id, collection_mint);
+        let data = self.rpc_client.get_account_data(&collection_account)
+            .map_err(|e| format!("Failed to fetch collection wrapper {}: {}", collection_account, e))?;
+        
+        CollectionWrapper::unpack(&data)
+    }
+    
+    /// Resolve the privacy policy in force for a wrapper, following its collection
+    pub fn effective_policy(&self, wrapper_account: &Pubkey) -> Result<EffectivePolicy, String> {
+        let state = self.fetch_wrapper_state(wrapper_account)?;
+        
+        let (membership_account, _) = find_membership_address(&self.program_id, wrapper_account);
+        let membership = self.fetch_program_account(&membership_account, "collection membership")?
+            .map(|data| CollectionMembership::unpack(&data))
+            .transpose()?;
+        
+        // Overriding members don't need their collection fetched
+        let collection = match &membership {
+            Some(membership) if !membership.override_policy => {
+                let data = self.rpc_client.get_account_data(&membership.collection)
+                    .map_err(|e| format!("Failed to fetch collection wrapper {}: {}", membership.collection, e))?;
+                Some(CollectionWrapper::unpack(&data)?)
+            }
+            _ => None,
+        };
+        
+        Ok(EffectivePolicy::resolve(&state, membership.as_ref(), collection.as_ref()))
+    }
+    
+    /// Attest that a wrapper exists under its effective policy, valid for `validity` seconds
+    ///
+    /// Marketplaces check the result with `verify_attestation` against the
+    /// attester key, without reading the wrapper program.
+    pub fn attest_wrapper(
         &self,
         wrapper_account: &Pubkey,
-        account_id: &str,
+        attester: &Keypair,
+        validity: i64,
+    ) -> Result<PrivacyAttestation, String> {
+        let state = self.fetch_wrapper_state(wrapper_account)?;
+        let policy = self.effective_policy(wrapper_account)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Failed to get current time: {}", e))?
+            .as_secs() as i64;
+        
+        Ok(PrivacyAttestation::new(
+            &self.program_id,
+            wrapper_account,
+            &state.original_nft_mint,
+            &state.owner,
+            &policy.policy_hash,
+            now,
+            validity,
+        ).sign(attester))
+    }
+    
+    /// Fetch a program-owned account's data, or `None` if it was never created
+    fn fetch_program_account(&self, address: &Pubkey, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let account = self.rpc_client.get_account_with_commitment(address, self.rpc_client.commitment())
+            .map_err(|e| format!("Failed to fetch {} {}: {}", name, address, e))?
+            .value;
+        
+        Ok(account.filter(|account| account.owner == self.program_id).map(|account| account.data))
+    }
+    
+    /// Create a collection wrapper whose policy member wrappers inherit
+    ///
+    /// The owner keypair must be the update authority of the collection
+    /// mint's metadata.
+    pub async fn create_collection(
+        &self,
+        collection_mint: &Pubkey,
+        default_policy_hash: &str,
+        trusted_agents: Vec<Pubkey>,
     ) -> Result<String, String> {
-        log::info!("Revoking access from {}...", account_id);
+        log::info!("Creating collection wrapper for {}...", collection_mint);
+        
+        let (collection_account, _) = find_collection_address(&self.program_id, collection_mint);
+        self.send_instruction(
+            "transaction.create_collection",
+            vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*collection_mint, false),
+                AccountMeta::new_readonly(metaplex::metadata_address(collection_mint), false),
+                AccountMeta::new(collection_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            WrapperInstruction::CreateCollection {
+                default_policy_hash: default_policy_hash.to_string(),
+                trusted_agents,
+            },
+        ).await
+    }
+    
+    /// Update the policy and trusted agents a collection's members inherit
+    pub async fn update_collection(
+        &self,
+        collection_mint: &Pubkey,
+        default_policy_hash: &str,
+        trusted_agents: Vec<Pubkey>,
+    ) -> Result<String, String> {
+        log::info!("Updating collection wrapper for {}...", collection_mint);
+        
+        let (collection_account, _) = find_collection_address(&self.program_id, collection_mint);
+        self.send_instruction(
+            "transaction.update_collection",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(collection_account, false),
+            ],
+            WrapperInstruction::UpdateCollection {
+                default_policy_hash: default_policy_hash.to_string(),
+                trusted_agents,
+            },
+        ).await
+    }
+    
+    /// Add a wrapper to a collection, co-signed by the collection authority
+    pub async fn join_collection(
+        &self,
+        wrapper_account: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Keypair,
+        override_policy: bool,
+    ) -> Result<String, String> {
+        log::info!("Joining {} to collection {}...", wrapper_account, collection_mint);
+        
+        let (collection_account, _) = find_collection_address(&self.program_id, collection_mint);
+        let (membership_account, _) = find_membership_address(&self.program_id, wrapper_account);
+        let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+        let (joined_freeze, _) = find_freeze_address(&self.program_id, &FreezeScope::Collection(collection_account));
+        
+        // Program and current collection freeze configs, without the membership already passed
+        let [program_freeze, _, collection_freeze] = self.freeze_accounts(wrapper_account)?;
         
-        // Prepare instruction
         let instruction = Instruction {
             program_id: self.program_id,
             accounts: vec![
                 AccountMeta::new(self.owner_keypair.pubkey(), true),
-                AccountMeta::new(*wrapper_account, false),
+                AccountMeta::new_readonly(collection_authority.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new_readonly(collection_account, false),
+                AccountMeta::new(membership_account, false),
+                AccountMeta::new_readonly(lock_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                program_freeze,
+                collection_freeze,
+                AccountMeta::new_readonly(joined_freeze, false),
             ],
-            data: WrapperInstruction::RevokeAccess {
-                account: account_id.to_string(),
-            }
-            .try_to_vec()
-            .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+            data: WrapperInstruction::JoinCollection { override_policy }
+                .try_to_vec()
+                .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
         };
         
-        // Create and send transaction
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.owner_keypair.pubkey()),
-            &[&self.owner_keypair],
-            self.rpc_client.get_latest_blockhash().map_err(|e| e.to_string())?,
-        );
+        let transaction = self.build_transaction(&[instruction], &[collection_authority])?;
         
-        let signature = self.rpc_client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| format!("Failed to send transaction: {}", e))?;
+        let signature = self.send_transaction("transaction.join_collection", &transaction).await?;
         
         Ok(signature.to_string())
     }
     
-    /// Update privacy settings
-    pub async fn update_privacy_settings(
+    /// Choose between a member wrapper's own policy and its collection's default
+    pub async fn set_policy_override(
         &self,
         wrapper_account: &Pubkey,
-        new_privacy_config_hash: &str,
+        override_policy: bool,
     ) -> Result<String, String> {
-        log::info!("Updating privacy settings with new hash: {}", new_privacy_config_hash);
+        log::info!("Setting collection policy override of {} to {}...", wrapper_account, override_policy);
         
-        // Prepare instruction
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts: vec![
-                AccountMeta::new(self.owner_keypair.pubkey(), true),
-                AccountMeta::new(*wrapper_account, false),
+        let (membership_account, _) = find_membership_address(&self.program_id, wrapper_account);
+        let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+        let [program_freeze, _, collection_freeze] = self.freeze_accounts(wrapper_account)?;
+        self.send_instruction(
+            "transaction.set_policy_override",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(membership_account, false),
+                AccountMeta::new_readonly(lock_account, false),
+                program_freeze,
+                collection_freeze,
             ],
-            data: WrapperInstruction::UpdatePrivacy {
-                new_privacy_config_hash: new_privacy_config_hash.to_string(),
-            }
-            .try_to_vec()
-            .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+            WrapperInstruction::SetPolicyOverride { override_policy },
+        ).await
+    }
+    
+    /// Offer viewers timed access to a wrapper for a price
+    ///
+    /// Relisting replaces the terms for new buyers; purchases already made
+    /// keep the royalty cuts they were paid under.
+    pub async fn list_reveal(&self, wrapper_account: &Pubkey, terms: RevealTerms) -> Result<String, String> {
+        log::info!("Listing reveal of {} for {} lamports...", wrapper_account, terms.price);
+        
+        let (listing_account, _) = find_listing_address(&self.program_id, wrapper_account);
+        let mut accounts = vec![
+            AccountMeta::new(self.owner_keypair.pubkey(), true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(listing_account, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ];
+        accounts.extend(self.freeze_accounts(wrapper_account)?);
+        
+        self.send_instruction(
+            "transaction.list_reveal",
+            accounts,
+            WrapperInstruction::ListReveal { terms },
+        ).await
+    }
+    
+    /// Fetch and decode the reveal listing of a wrapper
+    pub fn fetch_listing(&self, wrapper_account: &Pubkey) -> Result<RevealListing, String> {
+        let (listing_account, _) = find_listing_address(&self.program_id, wrapper_account);
+        let data = self.rpc_client.get_account_data(&listing_account)
+            .map_err(|e| format!("Failed to fetch reveal listing {}: {}", listing_account, e))?;
+        
+        RevealListing::unpack(&data)
+    }
+    
+    /// Fetch and decode a buyer's reveal purchase for a wrapper
+    pub fn fetch_purchase(&self, wrapper_account: &Pubkey, buyer: &Pubkey) -> Result<RevealPurchase, String> {
+        let (purchase_account, _) = find_purchase_address(&self.program_id, wrapper_account, buyer);
+        let data = self.rpc_client.get_account_data(&purchase_account)
+            .map_err(|e| format!("Failed to fetch reveal purchase {}: {}", purchase_account, e))?;
+        
+        RevealPurchase::unpack(&data)
+    }
+    
+    /// Fetch every reveal purchase made for a wrapper
+    pub fn wrapper_purchases(&self, wrapper_account: &Pubkey) -> Result<Vec<RevealPurchase>, String> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(RevealPurchase::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(RevealPurchase::WRAPPER_OFFSET, wrapper_account.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
         };
         
-        // Create and send transaction
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.owner_keypair.pubkey()),
-            &[&self.owner_keypair],
-            self.rpc_client.get_latest_blockhash().map_err(|e| e.to_string())?,
-        );
+        let accounts = self.rpc_client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| format!("Failed to fetch reveal purchases: {}", e))?;
+        
+        accounts.into_iter()
+            .map(|(_, account)| RevealPurchase::unpack(&account.data))
+            .collect()
+    }
+    
+    /// Pay into escrow for timed access to a wrapper, as the buyer
+    ///
+    /// `terms` are the ones the buyer reviewed, e.g. from `fetch_listing`; the
+    /// payment fails if the owner has relisted under anything else. It emits
+    /// a `RevealPurchased` event the key-delivery service picks up.
+    pub async fn pay_to_reveal(&self, wrapper_account: &Pubkey, terms: &RevealTerms) -> Result<String, String> {
+        log::info!("Paying {} lamports to reveal {}...", terms.price, wrapper_account);
         
-        let signature = self.rpc_client
-            .send_and_confirm_transaction(&transaction)
-            .map_err(|e| format!("Failed to send transaction: {}", e))?;
+        let buyer = self.owner_keypair.pubkey();
+        let (listing_account, _) = find_listing_address(&self.program_id, wrapper_account);
+        let (purchase_account, _) = find_purchase_address(&self.program_id, wrapper_account, &buyer);
+        let mut accounts = vec![
+            AccountMeta::new(buyer, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new_readonly(listing_account, false),
+            AccountMeta::new(purchase_account, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ];
+        accounts.extend(self.freeze_accounts(wrapper_account)?);
         
-        Ok(signature.to_string())
+        self.send_instruction(
+            "transaction.pay_to_reveal",
+            accounts,
+            WrapperInstruction::PayToReveal { terms: terms.clone() },
+        ).await
     }
     
-    /// Save protected metadata to file
-    pub fn save_metadata_to_file(
-        &self, 
-        metadata: &GlitchGangMetadata,
-        filename: &str
-    ) -> Result<(), String> {
-        log::info!("Saving metadata to file: {}", filename);
+    /// Reclaim an unsettled reveal payment once the settlement window has passed, as the buyer
+    pub async fn refund_reveal(&self, wrapper_account: &Pubkey) -> Result<String, String> {
+        log::info!("Reclaiming reveal payment for {}...", wrapper_account);
         
-        let json = serde_json::to_string_pretty(metadata)
-            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let (purchase_account, _) = find_purchase_address(&self.program_id, wrapper_account, &self.owner_keypair.pubkey());
+        self.send_instruction(
+            "transaction.refund_reveal",
+            vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(purchase_account, false),
+            ],
+            WrapperInstruction::RefundReveal,
+        ).await
+    }
+    
+    /// Confirm receiving the key for a reveal purchase, as the buyer
+    ///
+    /// Lets the owner settle right away and gives up the refund.
+    pub async fn confirm_reveal(&self, wrapper_account: &Pubkey) -> Result<String, String> {
+        log::info!("Confirming reveal of {}...", wrapper_account);
         
-        fs::write(filename, json)
-            .map_err(|e| format!("Failed to write file: {}", e))?;
+        let (purchase_account, _) = find_purchase_address(&self.program_id, wrapper_account, &self.owner_keypair.pubkey());
+        self.send_instruction(
+            "transaction.confirm_reveal",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(purchase_account, false),
+            ],
+            WrapperInstruction::ConfirmReveal,
+        ).await
+    }
+    
+    /// Pay a buyer's escrowed payment out to royalty recipients and the owner
+    ///
+    /// Succeeds once the buyer has confirmed the key with `confirm_reveal`,
+    /// or once the settlement window has passed and the buyer hasn't
+    /// reclaimed the payment first.
+    pub async fn settle_reveal(&self, wrapper_account: &Pubkey, buyer: &Pubkey) -> Result<String, String> {
+        log::info!("Settling reveal payment from {}...", buyer);
         
-        Ok(())
+        let purchase = self.fetch_purchase(wrapper_account, buyer)?;
+        let (purchase_account, _) = find_purchase_address(&self.program_id, wrapper_account, buyer);
+        
+        let mut accounts = vec![
+            AccountMeta::new(self.owner_keypair.pubkey(), true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(purchase_account, false),
+        ];
+        accounts.extend(purchase.royalty_shares.iter().map(|share| AccountMeta::new(share.recipient, false)));
+        
+        self.send_instruction("transaction.settle_reveal", accounts, WrapperInstruction::SettleReveal).await
+    }
+    
+    /// Get the privacy level a viewer is cleared for by a wrapper's on-chain grants
+    pub fn viewer_level(&self, wrapper_account: &Pubkey, viewer: &Pubkey) -> Result<PrivacyLevel, String> {
+        let wrapper_state = self.fetch_wrapper_state(wrapper_account)?;
+        
+        Ok(self.sync_mask.read().unwrap().access_policy().level_for_viewer(&wrapper_state, viewer))
+    }
+    
+    /// Revoke access
+    ///
+    /// If the access gate holds a key for the grantee, it's removed in the
+    /// same transaction. Gate keys sit in public account data, so the grantee
+    /// may have read theirs without `ReleaseKey`: the NFT's key is then
+    /// rotated with `rekey_gate`, which stores the new key for the viewers
+    /// that remain. The retired key stays in the NFT's config, so data
+    /// encrypted before still decrypts for the owner; anything the grantee
+    /// decrypted before stays with them too.
+    pub async fn revoke_access(
+        &self,
+        wrapper_account: &Pubkey,
+        grantee: &Pubkey,
+    ) -> Result<String, String> {
+        log::info!("Revoking access from {}...", grantee);
+        
+        let (operation, revoke) = self.owner_instruction(
+            wrapper_account,
+            WrapperInstruction::RevokeAccess { grantee: *grantee },
+        )?;
+        let mut instructions = vec![revoke];
+        
+        let gated = self.fetch_gate_key(wrapper_account, grantee)?.is_some();
+        if gated {
+            instructions.push(access_gate::remove_key(&self.gate_program_id, &self.owner_keypair.pubkey(), wrapper_account, grantee));
+        }
+        
+        let transaction = self.build_transaction(&instructions, &[])?;
+        let signature = self.send_transaction(operation, &transaction).await?;
+        
+        if gated {
+            let nft_mint = self.fetch_wrapper_state(wrapper_account)?.original_nft_mint;
+            self.rekey_gate(wrapper_account, &nft_mint).await
+                .map_err(|e| format!("Revoked access from {} but failed to re-key: {}", grantee, e))?;
+        }
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Fetch the key the access gate holds for a viewer of a wrapper, if any
+    pub fn fetch_gate_key(&self, wrapper_account: &Pubkey, viewer: &Pubkey) -> Result<Option<GateViewerKey>, String> {
+        let (gate, _) = access_gate::find_gate_address(&self.gate_program_id, wrapper_account);
+        let (viewer_key, _) = access_gate::find_viewer_key_address(&self.gate_program_id, &gate, viewer);
+        let account = self.rpc_client.get_account_with_commitment(&viewer_key, self.rpc_client.commitment())
+            .map_err(|e| format!("Failed to fetch gate key {}: {}", viewer_key, e))?
+            .value;
+        
+        account
+            .filter(|account| account.owner == self.gate_program_id)
+            .map(|account| access_gate::unpack_viewer_key(&account.data))
+            .transpose()
+    }
+    
+    /// Rotate an NFT's key and store the new one in the access gate for every viewer it holds a key for
+    ///
+    /// The gate only records viewers' wallets, so the new key is wrapped
+    /// classically; share it again with `share_asset` for hybrid wraps.
+    /// Returns the signature publishing the rotated config.
+    pub async fn rekey_gate(&self, wrapper_account: &Pubkey, nft_mint: &Pubkey) -> Result<String, String> {
+        let signature = self.rotate_key(wrapper_account, nft_mint).await?;
+        let data_key = {
+            let quantum_veil = self.quantum_veil.read().unwrap();
+            quantum_veil.data_key(&quantum_veil.get_config(&nft_mint.to_string())?)?
+        };
+        let mut key = Zeroizing::new([0u8; 32]);
+        if data_key.expose().len() != key.len() {
+            return Err("Invalid data key length".to_string());
+        }
+        key.copy_from_slice(data_key.expose());
+        
+        let (gate, _) = access_gate::find_gate_address(&self.gate_program_id, wrapper_account);
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(GateViewerKey::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(GateViewerKey::GATE_OFFSET, gate.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self.rpc_client.get_program_accounts_with_config(&self.gate_program_id, config)
+            .map_err(|e| format!("Failed to fetch gate keys: {}", e))?;
+        
+        for (_, account) in accounts {
+            let viewer = access_gate::unpack_viewer_key(&account.data)?.viewer;
+            let wrapped = key_wrap::wrap_key(&key, &ViewerKey::classical(viewer))?;
+            let instruction = access_gate::store_key(&self.gate_program_id, &self.owner_keypair.pubkey(), wrapper_account, &viewer, wrapped)?;
+            
+            let transaction = self.build_transaction(&[instruction], &[])?;
+            self.send_transaction("transaction.store_gate_key", &transaction).await?;
+        }
+        
+        Ok(signature)
+    }
+    
+    /// Fetch the freeze config of the program or a collection, if one was created
+    pub fn fetch_freeze_config(&self, scope: &FreezeScope) -> Result<Option<FreezeConfig>, String> {
+        let (freeze_account, _) = find_freeze_address(&self.program_id, scope);
+        self.fetch_program_account(&freeze_account, "freeze config")?
+            .map(|data| FreezeConfig::unpack(&data))
+            .transpose()
+    }
+    
+    /// Create the freeze config of the program or a collection
+    ///
+    /// The owner keypair must be the program's upgrade authority for a
+    /// program-wide config, or the collection authority for a collection's.
+    /// `authority` can be a multisig or governance address.
+    pub async fn initialize_freeze(&self, scope: FreezeScope, authority: &Pubkey) -> Result<String, String> {
+        log::info!("Creating {:?} freeze config with authority {}...", scope, authority);
+        
+        let scope_account = match scope {
+            FreezeScope::Program => solana_sdk::bpf_loader_upgradeable::get_program_data_address(&self.program_id),
+            FreezeScope::Collection(collection) => collection,
+        };
+        let (freeze_account, _) = find_freeze_address(&self.program_id, &scope);
+        self.send_instruction(
+            "transaction.initialize_freeze",
+            vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(scope_account, false),
+                AccountMeta::new(freeze_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            WrapperInstruction::InitializeFreeze { scope, authority: *authority },
+        ).await
+    }
+    
+    /// Build a `SetFreeze` instruction for a freeze authority to sign
+    ///
+    /// Returned unsent so a multisig or governance proposal can carry it.
+    pub fn set_freeze_instruction(&self, scope: &FreezeScope, authority: &Pubkey, frozen: bool) -> Result<Instruction, String> {
+        self.freeze_authority_instruction(scope, authority, WrapperInstruction::SetFreeze { frozen })
+    }
+    
+    /// Build a `SetFreezeAuthority` instruction for a freeze authority to sign
+    pub fn set_freeze_authority_instruction(
+        &self,
+        scope: &FreezeScope,
+        authority: &Pubkey,
+        new_authority: &Pubkey,
+    ) -> Result<Instruction, String> {
+        self.freeze_authority_instruction(
+            scope,
+            authority,
+            WrapperInstruction::SetFreezeAuthority { new_authority: *new_authority },
+        )
+    }
+    
+    /// Pause or resume settings changes, as the freeze authority
+    pub async fn set_freeze(&self, scope: &FreezeScope, frozen: bool) -> Result<String, String> {
+        log::info!("Setting {:?} freeze to {}...", scope, frozen);
+        
+        let instruction = self.set_freeze_instruction(scope, &self.owner_keypair.pubkey(), frozen)?;
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        let signature = self.send_transaction("transaction.set_freeze", &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Hand a freeze config to a new authority, as the current one
+    pub async fn set_freeze_authority(&self, scope: &FreezeScope, new_authority: &Pubkey) -> Result<String, String> {
+        log::info!("Handing {:?} freeze config to {}...", scope, new_authority);
+        
+        let instruction = self.set_freeze_authority_instruction(scope, &self.owner_keypair.pubkey(), new_authority)?;
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        let signature = self.send_transaction("transaction.set_freeze_authority", &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Build an instruction signed by a freeze authority against its freeze config
+    fn freeze_authority_instruction(
+        &self,
+        scope: &FreezeScope,
+        authority: &Pubkey,
+        instruction: WrapperInstruction,
+    ) -> Result<Instruction, String> {
+        let (freeze_account, _) = find_freeze_address(&self.program_id, scope);
+        
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(freeze_account, false),
+            ],
+            data: instruction
+                .try_to_vec()
+                .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        })
+    }
+    
+    /// Freeze configs a settings change that widens access must carry
+    ///
+    /// A member wrapper carries its collection's config; any other wrapper
+    /// repeats the program config in that slot.
+    fn freeze_accounts(&self, wrapper_account: &Pubkey) -> Result<[AccountMeta; 3], String> {
+        let (program_freeze, _) = find_freeze_address(&self.program_id, &FreezeScope::Program);
+        let (membership_account, _) = find_membership_address(&self.program_id, wrapper_account);
+        let collection_freeze = match self.fetch_program_account(&membership_account, "collection membership")? {
+            Some(data) => {
+                let collection = CollectionMembership::unpack(&data)?.collection;
+                find_freeze_address(&self.program_id, &FreezeScope::Collection(collection)).0
+            }
+            None => program_freeze,
+        };
+        
+        Ok([
+            AccountMeta::new_readonly(program_freeze, false),
+            AccountMeta::new_readonly(membership_account, false),
+            AccountMeta::new_readonly(collection_freeze, false),
+        ])
+    }
+    
+    /// Freeze privacy settings and access grants
+    ///
+    /// With a duration the lock expires after that many seconds; without one
+    /// it never does. An active lock can be extended but not shortened.
+    pub async fn lock_privacy(
+        &self,
+        wrapper_account: &Pubkey,
+        duration: Option<i64>,
+    ) -> Result<String, String> {
+        log::info!("Locking privacy settings of {}...", wrapper_account);
+        
+        let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+        self.send_instruction(
+            "transaction.lock_privacy",
+            vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(lock_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            WrapperInstruction::LockPrivacy { duration },
+        ).await
+    }
+    
+    /// Ask a wrapper's owner for access, as the requester
+    ///
+    /// The request waits in its own PDA, paid for by the requester and
+    /// refunded when the owner approves or denies it. It can't be changed
+    /// while pending, so asking again fails until the owner has answered.
+    pub async fn request_access(&self, wrapper_account: &Pubkey, access_level: u8) -> Result<String, String> {
+        log::info!("Requesting level {} access to {}...", access_level, wrapper_account);
+        
+        let requester = self.owner_keypair.pubkey();
+        let (request_account, _) = find_request_address(&self.program_id, wrapper_account, &requester);
+        
+        self.send_instruction(
+            "transaction.request_access",
+            vec![
+                AccountMeta::new(requester, true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(request_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            WrapperInstruction::RequestAccess { level: access_level },
+        ).await
+    }
+    
+    /// Approve a pending access request at the level it asks for
+    ///
+    /// `level` is the level the owner reviewed, e.g. from
+    /// `pending_access_requests`; the approval fails if the request asks for
+    /// anything else.
+    pub async fn approve_access(&self, wrapper_account: &Pubkey, requester: &Pubkey, level: u8) -> Result<String, String> {
+        log::info!("Approving level {} access request from {}...", level, requester);
+        
+        self.send_owner_instruction(
+            wrapper_account,
+            WrapperInstruction::ApproveAccess { requester: *requester, level },
+        ).await
+    }
+    
+    /// Deny a pending access request
+    pub async fn deny_access(&self, wrapper_account: &Pubkey, requester: &Pubkey) -> Result<String, String> {
+        log::info!("Denying access request from {}...", requester);
+        
+        self.send_owner_instruction(
+            wrapper_account,
+            WrapperInstruction::DenyAccess { requester: *requester },
+        ).await
+    }
+    
+    /// Fetch the access requests awaiting a decision on a wrapper
+    pub fn pending_access_requests(&self, wrapper_account: &Pubkey) -> Result<Vec<AccessRequest>, String> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(AccessRequest::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(AccessRequest::WRAPPER_OFFSET, wrapper_account.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        
+        let accounts = self.rpc_client.get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| format!("Failed to fetch access requests: {}", e))?;
+        
+        accounts.into_iter()
+            .map(|(_, account)| AccessRequest::unpack(&account.data))
+            .collect()
+    }
+    
+    /// Grant access to an agent by name
+    ///
+    /// `.sol` domains resolve through the Solana Name Service and registered
+    /// names through the agent registry. Other IDs fall back to
+    /// `legacy_agent_pubkey`, matching how migrated wrappers store grants
+    /// made before keys were public keys.
+    pub async fn grant_agent_access(
+        &self,
+        wrapper_account: &Pubkey,
+        agent_id: &str,
+        access_level: u8,
+    ) -> Result<String, String> {
+        let grantee = self.agent_grantee(agent_id)?;
+        log::info!("Resolved {} to {}", agent_id, grantee);
+        self.grant_access(wrapper_account, &grantee, access_level).await
+    }
+    
+    /// Revoke access from an agent by name
+    pub async fn revoke_agent_access(
+        &self,
+        wrapper_account: &Pubkey,
+        agent_id: &str,
+    ) -> Result<String, String> {
+        let grantee = self.agent_grantee(agent_id)?;
+        self.revoke_access(wrapper_account, &grantee).await
+    }
+    
+    /// Map an agent ID to the grantee key used on-chain
+    fn agent_grantee(&self, agent_id: &str) -> Result<Pubkey, String> {
+        match self.resolve_grantee(agent_id) {
+            Ok(key) => Ok(key),
+            // A domain that fails to resolve is an error, not a legacy ID
+            Err(e) if sns::is_sol_domain(agent_id) => Err(e),
+            Err(_) => Ok(legacy_agent_pubkey(agent_id)),
+        }
+    }
+    
+    /// Resolve a grantee given as a public key, `.sol` domain, or registered agent name
+    pub fn resolve_grantee(&self, id: &str) -> Result<Pubkey, String> {
+        if let Ok(key) = Pubkey::from_str(id) {
+            return Ok(key);
+        }
+        
+        if sns::is_sol_domain(id) {
+            return sns::resolve_domain(&self.rpc_client, id);
+        }
+        
+        self.resolve_agent(id)
+    }
+    
+    /// Get a display name for a key: its `.sol` domain if it has one, otherwise base58
+    pub fn display_name(&self, key: &Pubkey) -> String {
+        sns::reverse_lookup(&self.rpc_client, key).unwrap_or_else(|_| key.to_string())
+    }
+    
+    /// Report who accessed an NFT's private data in a period of Unix timestamps
+    ///
+    /// Combines the wrapper's current grants, the audit log, and mask query
+    /// totals. Audit events are empty unless `with_audit_log` was set.
+    pub fn access_report(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        time_range: Range<u64>,
+    ) -> Result<AccessReport, String> {
+        let mint = nft_mint.to_string();
+        
+        // Step 1: Current grants from the wrapper
+        let wrapper_state = self.fetch_wrapper_state(wrapper_account)?;
+        let mut grants: Vec<AccessGrant> = wrapper_state.access_controls.iter()
+            .map(|(grantee, access_level)| AccessGrant {
+                grantee: *grantee,
+                display_name: self.display_name(grantee),
+                access_level: *access_level,
+                privacy_level: self.sync_mask.read().unwrap().access_policy().clearance(*access_level),
+            })
+            .collect();
+        grants.sort_by(|a, b| b.access_level.cmp(&a.access_level).then(a.display_name.cmp(&b.display_name)));
+        
+        let mut report = AccessReport {
+            nft_mint: *nft_mint,
+            from: time_range.start,
+            to: time_range.end,
+            grants,
+            unmasked_views: Vec::new(),
+            decryptions: Vec::new(),
+            anomalies: Vec::new(),
+            query_stats: self.sync_mask.read().unwrap().query_stats(&mint)?,
+            retrievals: RetrievalStats::default(),
+        };
+        
+        // Step 2: Audited events for this NFT in the period
+        let entries = match &self.audit_log {
+            Some(audit_log) => audit_log.entries()?,
+            None => Vec::new(),
+        };
+        
+        for entry in entries.into_iter().filter(|entry| time_range.contains(&entry.timestamp)) {
+            report.retrievals.add(&entry, &mint);
+            let event = |viewer: Option<String>, reason: Option<String>, bytes: Option<usize>| AccessEvent {
+                timestamp: entry.timestamp,
+                viewer,
+                reason,
+                bytes,
+            };
+            
+            match entry.event {
+                AuditEvent::ViewerUnmasked { nft_mint, viewer, reason } if nft_mint == mint => {
+                    report.unmasked_views.push(event(Some(viewer), Some(reason), None));
+                },
+                AuditEvent::Decrypted { nft_mint, bytes } if nft_mint == mint => {
+                    report.decryptions.push(event(None, None, Some(bytes)));
+                },
+                AuditEvent::QueryAnomaly { nft_mint, viewer, reason } if nft_mint == mint => {
+                    report.anomalies.push(event(Some(viewer), Some(reason), None));
+                },
+                _ => {},
+            }
+        }
+        
+        Ok(report)
+    }
+    
+    /// Get the VRM mask configuration of an NFT
+    pub fn get_mask_config(&self, nft_mint: &Pubkey) -> Result<SyncMaskConfig, String> {
+        self.sync_mask.read().unwrap().get_config(&nft_mint.to_string())
+    }
+    
+    /// Set the privacy level of a VRM data type and publish the new config hash
+    pub async fn set_data_type_privacy(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        data_type: VrmDataType,
+        privacy_level: PrivacyLevel,
+    ) -> Result<String, String> {
+        log::info!("Setting {:?} privacy to {:?}...", data_type, privacy_level);
+        
+        self.sync_mask.write().unwrap().update_privacy_setting(&nft_mint.to_string(), data_type, privacy_level)?;
+        self.publish_mask_config(wrapper_account, nft_mint).await
+    }
+    
+    /// Set who may see a VRM data type and publish the new config hash
+    pub async fn set_access_permission(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        data_type: VrmDataType,
+        permission: AccessPermission,
+    ) -> Result<String, String> {
+        log::info!("Setting {:?} access to {:?}...", data_type, permission);
+        
+        self.sync_mask.write().unwrap().update_access_permission(&nft_mint.to_string(), data_type, permission)?;
+        self.publish_mask_config(wrapper_account, nft_mint).await
+    }
+    
+    /// Trust an agent, given as a public key, `.sol` domain, or registered name, to see through the VRM mask
+    ///
+    /// Publishes the new config hash and returns the resolved agent key.
+    pub async fn add_trusted_agent(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        agent_id: &str,
+    ) -> Result<Pubkey, String> {
+        let agent = self.resolve_grantee(agent_id)?;
+        log::info!("Trusting agent {}...", agent);
+        
+        self.sync_mask.write().unwrap().add_trusted_agent(&nft_mint.to_string(), &agent.to_string())?;
+        self.publish_mask_config(wrapper_account, nft_mint).await?;
+        
+        Ok(agent)
+    }
+    
+    /// Stop trusting an agent to see through the VRM mask and publish the new config hash
+    pub async fn remove_trusted_agent(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        agent_id: &str,
+    ) -> Result<Pubkey, String> {
+        let agent = self.resolve_grantee(agent_id)?;
+        log::info!("Removing trusted agent {}...", agent);
+        
+        self.sync_mask.write().unwrap().remove_trusted_agent(&nft_mint.to_string(), &agent.to_string())?;
+        self.publish_mask_config(wrapper_account, nft_mint).await?;
+        
+        Ok(agent)
+    }
+    
+    /// Rotate an NFT's privacy key and VRM noise seed together and publish the new config hash
+    ///
+    /// If guardians hold the key for recovery, the new key is split and
+    /// committed to them again. Fails while a recovery is pending; veto it first.
+    pub async fn rotate_key(&self, wrapper_account: &Pubkey, nft_mint: &Pubkey) -> Result<String, String> {
+        log::info!("Rotating privacy key for NFT: {}", nft_mint);
+        
+        let recovery = self.find_recovery(wrapper_account)?;
+        if recovery.as_ref().map_or(false, |config| config.pending.is_some()) {
+            return Err(format!("Recovery of {} is pending; veto it before rotating", wrapper_account));
+        }
+        
+        // Hold the lock across both steps so concurrent rotations retire each key once
+        let (retired_key, rotated) = {
+            let mut quantum_veil = self.quantum_veil.write().unwrap();
+            let retired_key = quantum_veil.data_key(&quantum_veil.get_config(&nft_mint.to_string())?)?;
+            (retired_key, quantum_veil.rotate_key(&nft_mint.to_string())?)
+        };
+        self.key_rotations.write().unwrap().entry(nft_mint.to_string()).or_default().push(KeyRotation {
+            key_id: key_id_of(retired_key.expose()),
+            rotated_at: rotated.last_rotation,
+        });
+        let seed_epoch = self.sync_mask.write().unwrap().rotate_noise_seed(&nft_mint.to_string())?;
+        log::info!("Noise seed advanced to epoch {}", seed_epoch);
+        
+        let signature = self.publish_mask_config(wrapper_account, nft_mint).await?;
+        
+        // Guardians must recover the key in use, not the one just retired
+        if let Some(config) = recovery {
+            self.share_recovery_key(wrapper_account, nft_mint, &config.guardians, config.threshold, config.challenge_period).await
+                .map_err(|e| format!("Rotated the key but failed to re-share it with guardians: {}", e))?;
+        }
+        
+        Ok(signature)
+    }
+    
+    /// Mirror an NFT's mask config into its quantum veil config and publish the combined hash
+    async fn publish_mask_config(&self, wrapper_account: &Pubkey, nft_mint: &Pubkey) -> Result<String, String> {
+        let mask_config = self.get_mask_config(nft_mint)?;
+        let privacy_config = self.quantum_veil.write().unwrap().update_sync_mask(
+            &nft_mint.to_string(),
+            Self::qv_sync_mask(&mask_config),
+        )?;
+        
+        let config_hash = self.wrapper_config_hash(&privacy_config, &mask_config);
+        
+        self.send_owner_instruction(
+            wrapper_account,
+            WrapperInstruction::UpdatePrivacy {
+                new_privacy_config_hash: config_hash,
+            },
+        ).await
+    }
+    
+    /// Build the quantum veil view of a mask config
+    fn qv_sync_mask(mask_config: &SyncMaskConfig) -> QVSyncMask {
+        let noise = |data_type: VrmDataType| {
+            mask_config.privacy_settings.get(&data_type).map_or(0.0, |level| level.intensity_factor())
+        };
+        
+        QVSyncMask {
+            position_noise: noise(VrmDataType::Position),
+            voice_noise: noise(VrmDataType::Voice),
+            gesture_noise: noise(VrmDataType::Gesture),
+            trusted_agents: mask_config.global_trusted_agents.clone(),
+        }
+    }
+    
+    /// Hash committing to both the quantum veil and mask configs of a wrapper
+    fn wrapper_config_hash(&self, privacy_config: &PrivacyConfig, mask_config: &SyncMaskConfig) -> String {
+        let mut hasher = Sha3_512::new();
+        hasher.update(self.quantum_veil.read().unwrap().get_config_hash(privacy_config).as_bytes());
+        hasher.update(mask_config.hash().as_bytes());
+        
+        base64::encode(hasher.finalize())
+    }
+    
+    /// Resolve a registered agent name to its public key
+    pub fn resolve_agent(&self, name: &str) -> Result<Pubkey, String> {
+        fetch_agent_record(&self.rpc_client, &self.program_id, name).map(|record| record.agent)
+    }
+    
+    /// Register an agent name, with the owner as the record authority
+    pub async fn register_agent(
+        &self,
+        name: &str,
+        agent_keypair: &Keypair,
+        metadata_uri: &str,
+    ) -> Result<Pubkey, String> {
+        log::info!("Registering agent {} as {}...", name, agent_keypair.pubkey());
+        
+        let (agent_record, _) = find_agent_address(&self.program_id, name);
+        
+        // Prepare instruction
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(agent_keypair.pubkey(), true),
+                AccountMeta::new(agent_record, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: WrapperInstruction::RegisterAgent {
+                name: name.to_string(),
+                metadata_uri: metadata_uri.to_string(),
+            }
+            .try_to_vec()
+            .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        };
+        
+        // Create and send transaction
+        let transaction = self.build_transaction(&[instruction], &[agent_keypair])?;
+        
+        let signature = self.send_transaction("transaction.register_agent", &transaction).await?;
+        
+        log::info!("Agent registered! Signature: {}", signature);
+        
+        Ok(agent_record)
+    }
+    
+    /// Hide attributes until a slot or timestamp, e.g. a mission revealed on the mint anniversary
+    ///
+    /// The attributes are encrypted under a fresh data key, which is escrowed
+    /// on-chain sealed with the returned reveal key. Once the reveal time
+    /// passes, whoever holds the reveal key, e.g. the owner or a keeper
+    /// service, publishes it with `claim_reveal`.
+    pub async fn schedule_reveal(
+        &self,
+        wrapper_account: &Pubkey,
+        metadata: &mut GlitchGangMetadata,
+        label: &str,
+        trait_types: &[&str],
+        reveal_time: RevealTime,
+    ) -> Result<ScheduledReveal, String> {
+        log::info!("Scheduling reveal {} at {:?}...", label, reveal_time);
+        
+        // Step 1: Encrypt the attributes under a fresh data key
+        let hidden: Vec<crate::models::Attribute> = metadata.attributes.iter()
+            .filter(|attribute| trait_types.contains(&attribute.trait_type.as_str()))
+            .cloned()
+            .collect();
+        if hidden.is_empty() {
+            return Err(format!("No attributes to reveal for {}", label));
+        }
+        
+        let hidden_json = serde_json::to_vec(&hidden)
+            .map_err(|e| format!("Failed to serialize attributes: {}", e))?;
+        let mut data_key = [0u8; 32];
+        OsRng.fill(&mut data_key);
+        let encrypted_attributes = seal(&hidden_json, &data_key)?;
+        
+        // Step 2: Seal the data key with a reveal key and escrow it
+        let mut reveal_key = [0u8; 32];
+        OsRng.fill(&mut reveal_key);
+        let sealed_key = seal(&data_key, &reveal_key)?;
+        let key_commitment = solana_sdk::hash::hashv(&[&reveal_key]).to_bytes();
+        let (reveal_account, _) = find_reveal_address(&self.program_id, wrapper_account, label);
+        
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(reveal_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: WrapperInstruction::ScheduleReveal {
+                label: label.to_string(),
+                reveal_time,
+                key_commitment,
+                sealed_key,
+            }
+            .try_to_vec()
+            .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        };
+        
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        let signature = self.send_transaction("transaction.schedule_reveal", &transaction).await?;
+        
+        // Step 3: Move the attributes into the private data once escrowed
+        metadata.attributes.retain(|attribute| !trait_types.contains(&attribute.trait_type.as_str()));
+        metadata.private_data
+            .get_or_insert_with(|| PrivateData {
+                privacy_level: "Medium".to_string(),
+                encrypted_attributes: None,
+                timeline_fragments: None,
+                vrm_config: None,
+                protected_assets: Vec::new(),
+                timelocked_attributes: Vec::new(),
+                numeric_commitments: Vec::new(),
+                format_preserved: HashMap::new(),
+            })
+            .timelocked_attributes
+            .push(TimeLockedAttributes {
+                label: label.to_string(),
+                reveal_account,
+                reveal_time,
+                encrypted_attributes: base64::encode(&encrypted_attributes),
+            });
+        
+        Ok(ScheduledReveal {
+            reveal_account,
+            reveal_key,
+            signature: signature.to_string(),
+        })
+    }
+    
+    /// Publish the reveal key of a reveal whose time has passed
+    pub async fn claim_reveal(&self, reveal_account: &Pubkey, reveal_key: [u8; 32]) -> Result<String, String> {
+        log::info!("Claiming reveal {}...", reveal_account);
+        
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(*reveal_account, false),
+            ],
+            data: WrapperInstruction::ClaimReveal { reveal_key }
+                .try_to_vec()
+                .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        };
+        
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        let signature = self.send_transaction("transaction.claim_reveal", &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Fetch and decode a reveal escrow account
+    pub fn fetch_reveal(&self, reveal_account: &Pubkey) -> Result<RevealEscrow, String> {
+        let data = self.rpc_client.get_account_data(reveal_account)
+            .map_err(|e| format!("Failed to fetch reveal {}: {}", reveal_account, e))?;
+        
+        RevealEscrow::unpack(&data)
+    }
+    
+    /// Decrypt the attributes of a claimed reveal
+    ///
+    /// Needs no owner key: anyone can open a reveal once its key is published.
+    pub fn open_reveal(&self, metadata: &GlitchGangMetadata, label: &str) -> Result<Vec<crate::models::Attribute>, String> {
+        let timelocked = metadata.private_data.as_ref()
+            .and_then(|private_data| private_data.timelocked_attributes.iter().find(|t| t.label == label))
+            .ok_or_else(|| format!("No reveal named {}", label))?;
+        
+        let escrow = self.fetch_reveal(&timelocked.reveal_account)?;
+        let reveal_key = escrow.reveal_key
+            .ok_or_else(|| format!("Reveal {} has not been claimed yet", label))?;
+        
+        let data_key: [u8; 32] = open(&escrow.sealed_key, &reveal_key)?
+            .try_into()
+            .map_err(|_| "Invalid reveal data key length".to_string())?;
+        
+        let encrypted = base64::decode(&timelocked.encrypted_attributes)
+            .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        
+        serde_json::from_slice(&open(&encrypted, &data_key)?)
+            .map_err(|e| format!("Failed to parse revealed attributes: {}", e))
+    }
+    
+    /// Split the wrapper NFT's key among guardians who can restore it if the owner loses it
+    ///
+    /// Each guardian gets a share sealed to their account. Once `threshold`
+    /// guardians request recovery to a new owner and `challenge_period`
+    /// seconds pass without a veto, the wrapper moves to the new owner and the
+    /// guardians release their shares to them. `rotate_key` re-shares the new
+    /// key with the same guardians.
+    pub async fn setup_recovery(
+        &self,
+        wrapper_account: &Pubkey,
+        guardians: &[Pubkey],
+        threshold: u8,
+        challenge_period: i64,
+    ) -> Result<String, String> {
+        log::info!("Setting up recovery with {} of {} guardians...", threshold, guardians.len());
+        
+        let nft_mint = self.fetch_wrapper_state(wrapper_account)?.original_nft_mint;
+        self.share_recovery_key(wrapper_account, &nft_mint, guardians, threshold, challenge_period).await
+    }
+    
+    /// Split an NFT's current key among guardians and commit to it on-chain
+    async fn share_recovery_key(
+        &self,
+        wrapper_account: &Pubkey,
+        nft_mint: &Pubkey,
+        guardians: &[Pubkey],
+        threshold: u8,
+        challenge_period: i64,
+    ) -> Result<String, String> {
+        // Step 1: Split the key and seal a share to each guardian
+        let data_key = {
+            let quantum_veil = self.quantum_veil.read().unwrap();
+            quantum_veil.data_key(&quantum_veil.get_config(&nft_mint.to_string())?)?
+        };
+        let shares = recovery::split_secret(&data_key.to_array()?, threshold, guardians.len() as u8)?;
+        let sealed_shares = shares.iter().zip(guardians)
+            .map(|(share, guardian)| recovery::seal_share(share, guardian))
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        // Step 2: Store the sealed shares on-chain
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        self.send_instruction(
+            "transaction.setup_recovery",
+            vec![
+                AccountMeta::new(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(recovery_account, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            WrapperInstruction::SetupRecovery {
+                guardians: guardians.to_vec(),
+                threshold,
+                challenge_period,
+                key_commitment: solana_sdk::hash::hashv(&[data_key.expose()]).to_bytes(),
+                sealed_shares,
+            },
+        ).await
+    }
+    
+    /// Request, as a guardian, that a wrapper be recovered to a new owner
+    ///
+    /// The first request starts the challenge period; requests from other
+    /// guardians for the same new owner approve it.
+    pub async fn request_recovery(&self, wrapper_account: &Pubkey, new_owner: &Pubkey) -> Result<String, String> {
+        log::info!("Requesting recovery of {} to {}...", wrapper_account, new_owner);
+        
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        self.send_instruction(
+            "transaction.request_recovery",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(recovery_account, false),
+            ],
+            WrapperInstruction::RequestRecovery { new_owner: *new_owner },
+        ).await
+    }
+    
+    /// Cancel a pending recovery of a wrapper the owner still controls
+    pub async fn veto_recovery(&self, wrapper_account: &Pubkey) -> Result<String, String> {
+        log::info!("Vetoing recovery of {}...", wrapper_account);
+        
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        self.send_instruction(
+            "transaction.veto_recovery",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new_readonly(*wrapper_account, false),
+                AccountMeta::new(recovery_account, false),
+            ],
+            WrapperInstruction::VetoRecovery,
+        ).await
+    }
+    
+    /// Move a wrapper to the requested owner once the challenge period has passed
+    pub async fn complete_recovery(&self, wrapper_account: &Pubkey) -> Result<String, String> {
+        log::info!("Completing recovery of {}...", wrapper_account);
+        
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        self.send_instruction(
+            "transaction.complete_recovery",
+            vec![
+                AccountMeta::new_readonly(self.owner_keypair.pubkey(), true),
+                AccountMeta::new(*wrapper_account, false),
+                AccountMeta::new(recovery_account, false),
+            ],
+            WrapperInstruction::CompleteRecovery,
+        ).await
+    }
+    
+    /// Fetch and decode the recovery config of a wrapper
+    pub fn fetch_recovery(&self, wrapper_account: &Pubkey) -> Result<RecoveryConfig, String> {
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        let data = self.rpc_client.get_account_data(&recovery_account)
+            .map_err(|e| format!("Failed to fetch recovery config {}: {}", recovery_account, e))?;
+        
+        RecoveryConfig::unpack(&data)
+    }
+    
+    /// Fetch the recovery config of a wrapper, if guardians were set up
+    fn find_recovery(&self, wrapper_account: &Pubkey) -> Result<Option<RecoveryConfig>, String> {
+        let (recovery_account, _) = find_recovery_address(&self.program_id, wrapper_account);
+        let account = self.rpc_client.get_account_with_commitment(&recovery_account, self.rpc_client.commitment())
+            .map_err(|e| format!("Failed to fetch recovery config {}: {}", recovery_account, e))?
+            .value;
+        
+        account
+            .filter(|account| account.owner == self.program_id)
+            .map(|account| RecoveryConfig::unpack(&account.data))
+            .transpose()
+    }
+    
+    /// Release this guardian's share to the owner a wrapper was recovered to
+    ///
+    /// Refuses until the recovery has completed on-chain, and reseals the
+    /// share to the new owner so it can be sent over any channel.
+    pub fn release_recovery_share(&self, wrapper_account: &Pubkey, new_owner: &Pubkey) -> Result<Vec<u8>, String> {
+        let config = self.fetch_recovery(wrapper_account)?;
+        let index = config.guardians.iter()
+            .position(|guardian| *guardian == self.owner_keypair.pubkey())
+            .ok_or("Not a guardian of this wrapper")?;
+        
+        if self.fetch_wrapper_state(wrapper_account)?.owner != *new_owner {
+            return Err(format!("Wrapper {} has not been recovered to {}", wrapper_account, new_owner));
+        }
+        
+        let share = recovery::open_share(&config.sealed_shares[index], &self.owner_keypair)?;
+        recovery::seal_share(&share, new_owner)
+    }
+    
+    /// Restore the wrapper NFT's key from shares guardians released to this owner
+    ///
+    /// The key is checked against the on-chain commitment, so a wrong or
+    /// insufficient set of shares is reported rather than returned.
+    pub fn recover_key(&self, wrapper_account: &Pubkey, released_shares: &[Vec<u8>]) -> Result<[u8; 32], String> {
+        let config = self.fetch_recovery(wrapper_account)?;
+        if released_shares.len() < config.threshold as usize {
+            return Err(format!("Need {} key shares, got {}", config.threshold, released_shares.len()));
+        }
+        
+        let shares = released_shares.iter()
+            .map(|sealed| recovery::open_share(sealed, &self.owner_keypair))
+            .collect::<Result<Vec<KeyShare>, String>>()?;
+        let key = recovery::combine_shares(&shares)?;
+        
+        if solana_sdk::hash::hashv(&[&key]).to_bytes() != config.key_commitment {
+            return Err("Recovered key does not match the commitment".to_string());
+        }
+        
+        Ok(key)
+    }
+    
+    /// Migrate a wrapper created with string access keys to the current layout
+    ///
+    /// Wrappers from the original Borsh layout are grown to hold every
+    /// entry, and the owner pays the extra rent.
+    pub async fn migrate_wrapper(&self, wrapper_account: &Pubkey) -> Result<String, String> {
+        log::info!("Migrating wrapper {}...", wrapper_account);
+        
+        self.send_owner_instruction(wrapper_account, WrapperInstruction::MigrateWrapper).await
+    }
+    
+    /// Resize a wrapper to hold `capacity` access entries
+    ///
+    /// The owner pays rent to grow the wrapper and gets it back on shrinking.
+    /// Every grant must still fit.
+    pub async fn resize_wrapper(&self, wrapper_account: &Pubkey, capacity: u16) -> Result<String, String> {
+        log::info!("Resizing wrapper {} to {} access entries...", wrapper_account, capacity);
+        
+        self.send_owner_instruction(wrapper_account, WrapperInstruction::ResizeWrapper { capacity }).await
+    }
+    
+    /// Send a wrapper instruction signed by the owner
+    async fn send_owner_instruction(
+        &self,
+        wrapper_account: &Pubkey,
+        instruction: WrapperInstruction,
+    ) -> Result<String, String> {
+        let (operation, instruction) = self.owner_instruction(wrapper_account, instruction)?;
+        
+        // Create and send transaction
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        
+        let signature = self.send_transaction(operation, &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Build a wrapper instruction signed by the owner, with the accounts it needs, and its operation name
+    fn owner_instruction(
+        &self,
+        wrapper_account: &Pubkey,
+        instruction: WrapperInstruction,
+    ) -> Result<(&'static str, Instruction), String> {
+        let operation = match &instruction {
+            WrapperInstruction::UpdatePrivacy { .. } => "transaction.update_privacy",
+            WrapperInstruction::GrantAccess { .. } => "transaction.grant_access",
+            WrapperInstruction::RevokeAccess { .. } => "transaction.revoke_access",
+            WrapperInstruction::MigrateWrapper => "transaction.migrate_wrapper",
+            WrapperInstruction::ResizeWrapper { .. } => "transaction.resize_wrapper",
+            WrapperInstruction::ApproveAccess { .. } => "transaction.approve_access",
+            WrapperInstruction::DenyAccess { .. } => "transaction.deny_access",
+            _ => "transaction.wrapper",
+        };
+        
+        // Settings changes carry the privacy lock so the program can enforce it
+        let mut accounts = vec![
+            AccountMeta::new(self.owner_keypair.pubkey(), true),
+            if matches!(instruction, WrapperInstruction::DenyAccess { .. }) {
+                AccountMeta::new_readonly(*wrapper_account, false)
+            } else {
+                AccountMeta::new(*wrapper_account, false)
+            },
+        ];
+        if !matches!(
+            instruction,
+            WrapperInstruction::MigrateWrapper | WrapperInstruction::ResizeWrapper { .. } | WrapperInstruction::DenyAccess { .. }
+        ) {
+            let (lock_account, _) = find_lock_address(&self.program_id, wrapper_account);
+            accounts.push(AccountMeta::new_readonly(lock_account, false));
+        }
+        
+        // Migrating or resizing may grow the wrapper, paid for through the system program
+        if matches!(instruction, WrapperInstruction::MigrateWrapper | WrapperInstruction::ResizeWrapper { .. }) {
+            accounts.push(AccountMeta::new_readonly(solana_sdk::system_program::id(), false));
+        }
+        
+        // Changes that widen access also carry the freeze configs
+        if matches!(
+            instruction,
+            WrapperInstruction::UpdatePrivacy { .. } | WrapperInstruction::GrantAccess { .. } | WrapperInstruction::ApproveAccess { .. }
+        ) {
+            accounts.extend(self.freeze_accounts(wrapper_account)?);
+        }
+        
+        // Answering a request closes its PDA, refunding the requester
+        if let WrapperInstruction::ApproveAccess { requester, .. } | WrapperInstruction::DenyAccess { requester } = &instruction {
+            let (request_account, _) = find_request_address(&self.program_id, wrapper_account, requester);
+            accounts.push(AccountMeta::new(request_account, false));
+            accounts.push(AccountMeta::new(*requester, false));
+        }
+        
+        // Prepare instruction
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction
+                .try_to_vec()
+                .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        };
+        
+        Ok((operation, instruction))
+    }
+    
+    /// Send a wrapper instruction over the given accounts, signed by the owner
+    async fn send_instruction(
+        &self,
+        operation: &'static str,
+        accounts: Vec<AccountMeta>,
+        instruction: WrapperInstruction,
+    ) -> Result<String, String> {
+        let instruction = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction
+                .try_to_vec()
+                .map_err(|e| format!("Failed to serialize instruction: {}", e))?,
+        };
+        
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        
+        let signature = self.send_transaction(operation, &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Build a transaction paid for by the fee payer and signed by the owner plus any extra signers
+    ///
+    /// Uses the durable nonce if one is configured.
+    fn build_transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Transaction, String> {
+        let payer = self.fee_payer();
+        
+        match &self.durable_nonce {
+            Some(nonce_account) => {
+                let nonce = self.fetch_nonce(nonce_account)?;
+                let transaction = nonce::build_with_nonce(instructions, &payer, nonce_account, &nonce);
+                self.sign_transaction(transaction, nonce.blockhash, signers)
+            },
+            None => self.build_recent_transaction(instructions, signers),
+        }
+    }
+    
+    /// Build a transaction against a recent blockhash, even if a durable nonce is configured
+    fn build_recent_transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Transaction, String> {
+        let blockhash = self.rpc_client.get_latest_blockhash().map_err(|e| e.to_string())?;
+        let transaction = Transaction::new_with_payer(instructions, Some(&self.fee_payer()));
+        self.sign_transaction(transaction, blockhash, signers)
+    }
+    
+    /// Sign a transaction as the owner, the fee payer if held locally, and any extra signers
+    fn sign_transaction(
+        &self,
+        mut transaction: Transaction,
+        blockhash: Hash,
+        signers: &[&Keypair],
+    ) -> Result<Transaction, String> {
+        let mut all_signers = vec![self.owner_keypair.as_ref()];
+        if let FeePayer::Signer(fee_payer) = self.fee_payer.as_ref() {
+            all_signers.push(fee_payer);
+        }
+        all_signers.extend_from_slice(signers);
+        
+        // A relayer adds the fee payer's signature when it submits
+        transaction.try_partial_sign(&all_signers, blockhash)
+            .map_err(|e| format!("Failed to sign transaction: {}", e))?;
+        Ok(transaction)
+    }
+    
+    /// Create a durable nonce account with the owner as its authority
+    pub async fn create_nonce_account(&self) -> Result<Pubkey, String> {
+        let nonce_account = Keypair::new();
+        let owner = self.owner_keypair.pubkey();
+        let instructions = nonce::create_nonce_account_instructions(&self.rpc_client, &owner, &nonce_account.pubkey(), &owner)?;
+        
+        // The nonce doesn't exist yet, so this one uses a recent blockhash
+        let transaction = self.build_recent_transaction(&instructions, &[&nonce_account])?;
+        let signature = self.send_transaction("transaction.create_nonce_account", &transaction).await?;
+        
+        log::info!("Nonce account {} created! Signature: {}", nonce_account.pubkey(), signature);
+        
+        Ok(nonce_account.pubkey())
+    }
+    
+    /// Advance a durable nonce, invalidating transactions signed against its current value
+    pub async fn advance_nonce(&self, nonce_account: &Pubkey) -> Result<String, String> {
+        let instruction = solana_sdk::system_instruction::advance_nonce_account(nonce_account, &self.owner_keypair.pubkey());
+        
+        let transaction = self.build_recent_transaction(&[instruction], &[])?;
+        let signature = self.send_transaction("transaction.advance_nonce", &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Fetch the current value and authority of a durable nonce
+    pub fn fetch_nonce(&self, nonce_account: &Pubkey) -> Result<NonceInfo, String> {
+        nonce::fetch_nonce(&self.rpc_client, nonce_account)
+    }
+    
+    /// Send a transaction signed elsewhere, e.g. by a cold wallet against a durable nonce
+    ///
+    /// With a relayer configured, the fee payer's signature may be missing.
+    pub async fn send_signed_transaction(&self, transaction: &Transaction) -> Result<String, String> {
+        if !matches!(*self.fee_payer, FeePayer::Relayer(_)) {
+            transaction.verify().map_err(|e| format!("Invalid transaction signatures: {}", e))?;
+        }
+        
+        let signature = self.send_transaction("transaction.signed", transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Fetch the upgrade authority of the wrapper program
+    pub fn fetch_upgrade_authority(&self) -> Result<UpgradeAuthority, String> {
+        upgrade_authority::fetch_upgrade_authority(&self.rpc_client, &self.program_id)
+    }
+    
+    /// Propose handing the wrapper program's upgrade authority to `new_authority`
+    ///
+    /// The owner keypair must be the current upgrade authority. The returned
+    /// transaction carries its signature and goes to the new authority, which
+    /// accepts it with `accept_upgrade_authority`. Build it against a durable
+    /// nonce if the new authority won't sign within a couple of minutes.
+    pub fn propose_upgrade_authority(&self, new_authority: &Pubkey) -> Result<Transaction, String> {
+        let owner = self.owner_keypair.pubkey();
+        let deployed = self.fetch_upgrade_authority()?;
+        upgrade_authority::check_proposal(&deployed, &owner, new_authority)?;
+        
+        log::info!("Proposing upgrade authority of {} to {}...", self.program_id, new_authority);
+        
+        let instruction = upgrade_authority::transfer_instruction(&self.program_id, &owner, new_authority);
+        self.build_transaction(&[instruction], &[])
+    }
+    
+    /// Accept a proposed upgrade authority transfer as the new authority
+    ///
+    /// The owner keypair co-signs the proposal after checking it only hands
+    /// over this program to itself, then confirms the authority moved.
+    pub async fn accept_upgrade_authority(&self, mut transaction: Transaction) -> Result<String, String> {
+        let owner = self.owner_keypair.pubkey();
+        let current = upgrade_authority::check_acceptance(&transaction, &self.program_id, &owner)?;
+        
+        log::info!("Accepting upgrade authority of {} from {}...", self.program_id, current);
+        
+        let blockhash = transaction.message.recent_blockhash;
+        transaction.try_partial_sign(&[self.owner_keypair.as_ref()], blockhash)
+            .map_err(|e| format!("Failed to sign transaction: {}", e))?;
+        let signature = self.send_signed_transaction(&transaction).await?;
+        
+        if self.fetch_upgrade_authority()?.authority != Some(owner) {
+            return Err(format!("Upgrade authority of {} was not handed over", self.program_id));
+        }
+        
+        Ok(signature)
+    }
+    
+    /// Get the account paying transaction fees
+    pub fn fee_payer(&self) -> Pubkey {
+        match self.fee_payer.as_ref() {
+            FeePayer::Owner => self.owner_keypair.pubkey(),
+            FeePayer::Signer(fee_payer) => fee_payer.pubkey(),
+            FeePayer::Relayer(relayer) => relayer.fee_payer,
+        }
+    }
+    
+    /// Send and confirm a transaction, tracing and recording it as `operation`
+    ///
+    /// In dry-run mode the transaction is simulated instead. With a relayer
+    /// configured, the relayer submits it and the client waits for it to be
+    /// confirmed.
+    async fn send_transaction(&self, operation: &'static str, transaction: &Transaction) -> Result<Signature, String> {
+        if let Some(plan) = &self.dry_run {
+            return self.simulate_transaction(plan, operation, transaction);
+        }
+        
+        if let FeePayer::Relayer(relayer) = self.fee_payer.as_ref() {
+            return telemetry::timed_async(operation, async {
+                let signature = relayer.submit(transaction).await?;
+                self.rpc_client.poll_for_signature(&signature)
+                    .map_err(|e| format!("Failed to confirm relayed transaction {}: {}", signature, e))?;
+                Ok::<_, String>(signature)
+            }).await;
+        }
+        
+        telemetry::timed(operation, || {
+            self.rpc_client
+                .send_and_confirm_transaction(transaction)
+                .map_err(|e| format!("Failed to send transaction: {}", e))
+        })
+    }
+    
+    /// Simulate a transaction and record its effects in a dry-run plan
+    ///
+    /// Writable signers other than the fee payer and owner are the keypair
+    /// accounts the transaction creates.
+    fn simulate_transaction(
+        &self,
+        plan: &Mutex<DryRunPlan>,
+        operation: &'static str,
+        transaction: &Transaction,
+    ) -> Result<Signature, String> {
+        let message = &transaction.message;
+        let created: Vec<Pubkey> = message.account_keys.iter()
+            .enumerate()
+            .filter(|(index, _)| *index > 0 && message.is_signer(*index) && message.is_writable(*index))
+            .map(|(_, address)| *address)
+            .filter(|address| *address != self.owner_keypair.pubkey())
+            .collect();
+        
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            commitment: Some(self.rpc_client.commitment()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: created.iter().map(Pubkey::to_string).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self.rpc_client.simulate_transaction_with_config(transaction, config)
+            .map_err(|e| format!("Failed to simulate transaction: {}", e))?
+            .value;
+        let fee = self.rpc_client.get_fee_for_message(message)
+            .map_err(|e| format!("Failed to get transaction fee: {}", e))?;
+        
+        let error = result.err.map(|e| e.to_string());
+        if let Some(e) = &error {
+            log::warn!("Dry run: {} would fail: {}", operation, e);
+        }
+        
+        let signature = transaction.signatures[0];
+        let accounts = result.accounts.unwrap_or_default();
+        let mut plan = plan.lock().unwrap();
+        plan.accounts_created.extend(created.iter().enumerate().map(|(index, address)| {
+            let account = accounts.get(index).cloned().flatten();
+            PlannedAccount {
+                address: *address,
+                lamports: account.as_ref().map(|account| account.lamports),
+                size: account.and_then(|account| account.data.decode()).map(|data| data.len()),
+            }
+        }));
+        plan.transactions.push(SimulatedTransaction {
+            operation: operation.to_string(),
+            signature: signature.to_string(),
+            fee,
+            units_consumed: result.units_consumed,
+            logs: result.logs.unwrap_or_default(),
+            error,
+        });
+        
+        Ok(signature)
+    }
+    
+    /// Update privacy settings
+    pub async fn update_privacy_settings(
+        &self,
+        wrapper_account: &Pubkey,
+        new_privacy_config_hash: &str,
+    ) -> Result<String, String> {
+        log::info!("Updating privacy settings with new hash: {}", new_privacy_config_hash);
+        
+        self.send_owner_instruction(
+            wrapper_account,
+            WrapperInstruction::UpdatePrivacy {
+                new_privacy_config_hash: new_privacy_config_hash.to_string(),
+            },
+        ).await
+    }
+    
+    /// Point an NFT's metadata account at a new URI, e.g. uploaded protected metadata
+    ///
+    /// Only possible for collections where the owner holds the update authority.
+    pub async fn update_metadata_uri(&self, nft_mint: &Pubkey, new_uri: &str) -> Result<String, String> {
+        log::info!("Updating metadata URI of {} to {}", nft_mint, new_uri);
+        
+        let token_metadata = metaplex::fetch_token_metadata(&self.rpc_client, nft_mint)?;
+        if token_metadata.update_authority != self.owner_keypair.pubkey() {
+            return Err(format!(
+                "Update authority of {} is {}, not the owner",
+                nft_mint, token_metadata.update_authority
+            ));
+        }
+        if !token_metadata.is_mutable {
+            return Err(format!("Metadata of {} is immutable", nft_mint));
+        }
+        
+        let instruction = metaplex::update_metadata_uri(&token_metadata, new_uri)?;
+        let transaction = self.build_transaction(&[instruction], &[])?;
+        
+        let signature = self.send_transaction("transaction.update_metadata_uri", &transaction).await?;
+        
+        Ok(signature.to_string())
+    }
+    
+    /// Find which of a manifest's fragments are no longer in storage
+    pub async fn missing_fragments(&self, manifest: &FragmentManifest) -> Result<Vec<String>, String> {
+        let shifter = self.timeline_shifter().ok_or("Timeline shifter not configured")?;
+        
+        shifter.missing_fragments(&manifest.fragment_ids).await
+    }
+    
+    /// Sign a transcript of where a manifest's fragments were stored
+    pub fn distribution_transcript(&self, manifest: &FragmentManifest) -> Result<DistributionTranscript, String> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Failed to get current time: {}", e))?
+            .as_secs() as i64;
+        
+        Ok(DistributionTranscript::new(manifest, created_at)?.sign(&self.owner_keypair))
+    }
+    
+    /// Sign a distribution transcript and upload it, e.g. to Arweave with an `ArweaveUploader`
+    ///
+    /// Returns the transcript and the URI it was uploaded to.
+    pub async fn publish_distribution_transcript(
+        &self,
+        manifest: &FragmentManifest,
+        uploader: &dyn Uploader,
+    ) -> Result<(DistributionTranscript, String), String> {
+        let transcript = self.distribution_transcript(manifest)?;
+        let uri = self.upload_json(uploader, transcript.to_json()?.as_bytes()).await
+            .map_err(|e| format!("Failed to upload distribution transcript: {}", e))?;
+        
+        log::info!("Published distribution transcript for {} at {}", manifest.nft_mint, uri);
+        Ok((transcript, uri))
+    }
+    
+    /// Check a transcript's signature and that its fragments are still stored as committed
+    ///
+    /// Needs no keys, so anyone with access to the storage backends can
+    /// check an owner's transcript. Pass `owner` to also require the signer.
+    pub async fn verify_distribution(&self, transcript: &DistributionTranscript, owner: Option<&Pubkey>) -> Result<(), String> {
+        let shifter = self.timeline_shifter().ok_or("Timeline shifter not configured")?;
+        
+        transcript.verify()?;
+        if let Some(owner) = owner.filter(|owner| **owner != transcript.owner) {
+            return Err(format!("Transcript was signed by {}, not {}", transcript.owner, owner));
+        }
+        
+        let mismatched = shifter.verify_receipts(&transcript.fragments).await;
+        if !mismatched.is_empty() {
+            return Err(format!("Fragments do not match the transcript: {}", mismatched.join(", ")));
+        }
+        
+        Ok(())
+    }
+    
+    /// Wrap one timeline's fragment key to a viewer, e.g. the Social key for a partner app
+    ///
+    /// The viewer can decrypt that timeline's fragments and no others.
+    /// Returns the wrapped key, base64 encoded, for the viewer to open with
+    /// `open_timeline_key`.
+    pub fn share_timeline_key(&self, manifest: &FragmentManifest, timeline: &TimelineType, viewer: &ViewerKey) -> Result<String, String> {
+        let key_id = manifest.timeline_key_id(timeline)
+            .ok_or_else(|| format!("Manifest for {} has no {} timeline key", manifest.nft_mint, timeline.label()))?;
+        
+        let timeline_key = derive_timeline_key(self.content_key()?.expose(), timeline);
+        if timeline_key_id(timeline_key.expose()) != key_id {
+            return Err(format!("Manifest for {} was not fractured with this client's key", manifest.nft_mint));
+        }
+        
+        let wrapped = key_wrap::wrap_key(&timeline_key.to_array::<32>()?, viewer)?;
+        log::info!("Sharing {} timeline key of {} with {}", timeline.label(), manifest.nft_mint, viewer.account);
+        Ok(base64::encode(wrapped))
+    }
+    
+    /// Unwrap a timeline key shared with this wallet, checking it against the manifest
+    pub fn open_timeline_key(&self, manifest: &FragmentManifest, timeline: &TimelineType, wrapped: &str) -> Result<SecretBytes, String> {
+        let wrapped = base64::decode(wrapped)
+            .map_err(|e| format!("Failed to decode timeline key: {}", e))?;
+        let timeline_key = key_wrap::unwrap_key(&wrapped, &self.owner_keypair)?;
+        
+        if manifest.timeline_key_id(timeline) != Some(timeline_key_id(&timeline_key[..]).as_str()) {
+            return Err(format!("Key is not the {} timeline key of {}", timeline.label(), manifest.nft_mint));
+        }
+        
+        Ok(SecretBytes::from(&timeline_key[..]))
+    }
+    
+    /// Reassemble one timeline's share of fractured metadata, fetching only its fragments
+    ///
+    /// Pass a key opened with `open_timeline_key`, or `None` to derive it
+    /// from this client's own encryption key.
+    pub async fn reassemble_timeline(
+        &self,
+        manifest: &FragmentManifest,
+        timeline: &TimelineType,
+        timeline_key: Option<&SecretBytes>,
+    ) -> Result<Vec<u8>, String> {
+        let shifter = self.timeline_shifter().ok_or("Timeline shifter not configured")?;
+        
+        let timeline_key = match timeline_key {
+            Some(key) => key.clone(),
+            None => derive_timeline_key(self.content_key()?.expose(), timeline),
+        };
+        shifter.reassemble_timeline(manifest, timeline, timeline_key.expose()).await
+    }
+    
+    /// Drop a manifest's fragments from the timeline shifter's cache, leaving them in storage
+    pub fn evict_cached_fragments(&self, manifest: &FragmentManifest) {
+        if let Some(shifter) = self.timeline_shifter() {
+            shifter.evict_cached(&manifest.fragment_ids);
+        }
+    }
+    
+    /// Re-shard fragments that are due for drift and publish the new manifest hashes
+    ///
+    /// Each NFT drifts on its own: its old fragments are only deleted once the
+    /// new hash is confirmed, and a failure leaves it on its current manifest
+    /// without holding up the rest. Returns the publish signature or error per NFT.
+    pub async fn drift_timelines(
+        &self,
+        scheduler: &mut DriftScheduler,
+        wrapper_accounts: &HashMap<String, Pubkey>,
+    ) -> Result<HashMap<String, Result<String, String>>, String> {
+        let shifter = self.timeline_shifter().ok_or("Timeline shifter not configured")?;
+        
+        let mut outcomes = HashMap::new();
+        for nft_mint in scheduler.due() {
+            let outcome = self.drift_timeline(scheduler, &shifter, &nft_mint, wrapper_accounts).await;
+            if let Err(e) = &outcome {
+                log::warn!("Timeline drift failed for NFT {}: {}", nft_mint, e);
+            }
+            outcomes.insert(nft_mint, outcome);
+        }
+        
+        Ok(outcomes)
+    }
+    
+    /// Drift one NFT, publishing its new manifest hash before the old fragments go
+    async fn drift_timeline(
+        &self,
+        scheduler: &mut DriftScheduler,
+        shifter: &TimelineShifter,
+        nft_mint: &str,
+        wrapper_accounts: &HashMap<String, Pubkey>,
+    ) -> Result<String, String> {
+        let wrapper_account = wrapper_accounts.get(nft_mint)
+            .ok_or_else(|| format!("No wrapper account for NFT: {}", nft_mint))?;
+        
+        let result = scheduler.drift(shifter, nft_mint).await?;
+        log::info!("Timeline drift epoch {} for NFT: {}", result.manifest.epoch, nft_mint);
+        
+        // An unpublished drift stays pending and is retried with the same hash
+        let signature = self.update_privacy_settings(wrapper_account, &result.config_hash).await?;
+        scheduler.commit(shifter, nft_mint).await?;
+        
+        Ok(signature)
+    }
+    
+    /// Save protected metadata to file
+    pub fn save_metadata_to_file(
+        &self, 
+        metadata: &GlitchGangMetadata,
+        filename: &str
+    ) -> Result<(), String> {
+        log::info!("Saving metadata to file: {}", filename);
+        
+        let json = serde_json::to_string_pretty(metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        
+        fs::write(filename, json)
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        
+        Ok(())
+    }
+}
+
+/// Get an identifier for a key, derived one-way from it
+fn key_id_of(key: &[u8]) -> String {
+    let digest = solana_sdk::hash::hashv(&[KEY_ID_LABEL, key]);
+    digest.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Rebuild a shared component in place with one of its consuming builders
+fn rebuild<T>(component: &RwLock<T>, placeholder: T, build: impl FnOnce(T) -> T) {
+    let mut component = component.write().unwrap();
+    let current = std::mem::replace(&mut *component, placeholder);
+    *component = build(current);
+}
+
+/// Encrypt into an envelope with a fresh random nonce
+fn seal(plaintext: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+    seal_envelope(plaintext, key, &[])
+}
+
+/// Decrypt data sealed by `seal`, or as `[nonce: 12][ciphertext]` from before envelopes
+fn open(sealed: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+    if is_envelope(sealed) {
+        return open_envelope(sealed, key, &[]);
+    }
+    if sealed.len() < 12 {
+        return Err("Sealed data too short".to_string());
+    }
+    
+    decrypt_data(&sealed[12..], key, &sealed[..12])
+}
+
+/// Derive the PubSub websocket URL of an RPC endpoint, one port up for a local validator
+pub(crate) fn websocket_url(rpc_url: &str) -> String {
+    let websocket_url = match rpc_url.split_once("://") {
+        Some(("https", rest)) => format!("wss://{}", rest),
+        Some(("http", rest)) => format!("ws://{}", rest),
+        _ => rpc_url.to_string(),
+    };
+    
+    websocket_url.replacen(":8899", ":8900", 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fpe::Alphabet;
+    use crate::models::{AgentRecord, Attribute, RecoveryRequest};
+    use crate::test_utils::{
+        MemoryUploader, MockRpc, memory_timeline_shifter, sample_metadata, token_metadata_account, wrapper_account
+    };
+    use proptest::prelude::*;
+    use timeline_shifter::{InMemoryAdapter, StorageAdapter};
+    use quantum_veil::LocalKeyProvider;
+    
+    #[tokio::test]
+    async fn test_protect_decrypt_round_trip() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let metadata = sample_metadata();
+        
+        let report = client.protect_metadata(&metadata, PrivacyLevel::Medium).await.unwrap();
+        assert_eq!(report.protected_metadata.attributes.len(), 2);
+        assert!(!adapter.is_empty());
+        
+        let decrypted = client.decrypt_metadata(&report.protected_metadata).unwrap();
+        assert_eq!(decrypted.attributes.len(), metadata.attributes.len());
+    }
+    
+    #[tokio::test]
+    async fn test_distribution_transcript_verifies_stored_fragments() {
+        let adapter = InMemoryAdapter::new();
+        let owner = Keypair::new();
+        let owner_pubkey = owner.pubkey();
+        let client = MockRpc::new()
+            .privacy_client(owner)
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let uploader = MemoryUploader::new();
+        
+        let report = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        let manifest = report.fragment_manifest.unwrap();
+        let (transcript, _) = client.publish_distribution_transcript(&manifest, &uploader).await.unwrap();
+        
+        // A third party reads the published transcript and checks it against storage
+        let published = DistributionTranscript::from_json(std::str::from_utf8(&uploader.uploads()[0]).unwrap()).unwrap();
+        let verifier = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        verifier.verify_distribution(&published, Some(&owner_pubkey)).await.unwrap();
+        published.verify_manifest(&manifest).unwrap();
+        assert!(verifier.verify_distribution(&published, Some(&Pubkey::new_unique())).await.is_err());
+        
+        let mut forged = transcript.clone();
+        forged.fragments[0].sha256 = "00".repeat(32);
+        assert!(forged.verify().is_err());
+        
+        // Fragments changed after the fact no longer match
+        let mut fragment = adapter.retrieve_fragment(&manifest.fragment_ids[0]).await.unwrap();
+        fragment.data[0] ^= 1;
+        adapter.store_fragment(&fragment).await.unwrap();
+        assert!(verifier.verify_distribution(&published, None).await.unwrap_err().contains(&manifest.fragment_ids[0]));
+    }
+    
+    #[tokio::test]
+    async fn test_single_timeline_key_shared_with_partner() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let partner = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        
+        let report = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        let manifest = report.fragment_manifest.unwrap();
+        let wrapped = client.share_timeline_key(&manifest, &TimelineType::Social, &partner.viewer_key()).unwrap();
+        
+        let social_key = partner.open_timeline_key(&manifest, &TimelineType::Social, &wrapped).unwrap();
+        assert_eq!(manifest.timeline_key_id(&TimelineType::Social), Some(timeline_key_id(social_key.expose()).as_str()));
+        assert_ne!(manifest.timeline_key_id(&TimelineType::Financial), manifest.timeline_key_id(&TimelineType::Social));
+        
+        // Only the partner can open it, and only as the Social key
+        assert!(partner.open_timeline_key(&manifest, &TimelineType::Financial, &wrapped).is_err());
+        assert!(client.open_timeline_key(&manifest, &TimelineType::Social, &wrapped).is_err());
+        
+        // The partner reads the Social timeline, the same bytes the owner sees, but no other
+        let social = partner.reassemble_timeline(&manifest, &TimelineType::Social, Some(&social_key)).await.unwrap();
+        assert!(!social.is_empty());
+        assert_eq!(social, client.reassemble_timeline(&manifest, &TimelineType::Social, None).await.unwrap());
+        assert!(partner.reassemble_timeline(&manifest, &TimelineType::Identity, Some(&social_key)).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_protection_report_describes_protection() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_encryption_key([5u8; 32])
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        
+        let report = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        
+        let mut encrypted_traits = report.encrypted_traits.clone();
+        encrypted_traits.sort();
+        assert_eq!(encrypted_traits, vec!["Agent Name", "Mission", "Origin", "Secret Code"]);
+        
+        // The manifest matches the fragments referenced in the metadata, and its hash is published
+        let manifest = report.fragment_manifest.as_ref().unwrap();
+        let private_data = report.protected_metadata.private_data.as_ref().unwrap();
+        assert_eq!(private_data.timeline_fragments.as_ref(), Some(&manifest.fragment_ids));
+        assert_eq!(report.config_hash, manifest.hash());
+        assert_eq!(report.key_id, client.key_id());
+        
+        // Unprotected metadata has nothing to encrypt or fracture
+        let report = client.protect_metadata(&sample_metadata(), PrivacyLevel::None).await.unwrap();
+        assert!(report.encrypted_traits.is_empty());
+        assert!(report.fragment_manifest.is_none());
+        assert!(!report.config_hash.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_format_preserved_traits_keep_their_format() {
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let policy = ProtectionPolicy::new()
+            .with_format_preserving("Secret Code", PrivacyLevel::Light, FormatPreserving::ff1(Alphabet::alphanumeric()))
+            .with_format_preserving("Background", PrivacyLevel::Light, FormatPreserving::ff3_1(Alphabet::lowercase()))
+            .with_format_preserving("Agent Name", PrivacyLevel::Light, FormatPreserving::ff1(Alphabet::digits()));
+        let metadata = sample_metadata();
+        
+        let report = client.protect_metadata_with_policy(&metadata, &policy, PrivacyLevel::Medium).await.unwrap();
+        let value = |metadata: &GlitchGangMetadata, trait_type: &str| metadata.attributes.iter()
+            .find(|attr| attr.trait_type == trait_type)
+            .map(|attr| attr.value.clone());
+        
+        let code = value(&report.protected_metadata, "Secret Code").unwrap();
+        assert_ne!(code, "GLITCH-8983-ALPHA");
+        assert_eq!(code.len(), "GLITCH-8983-ALPHA".len());
+        assert_eq!(&code[6..7], "-");
+        assert!(value(&report.protected_metadata, "Background").unwrap().starts_with('C'));
+        
+        // A value with nothing to encrypt in place is encrypted outright
+        assert_eq!(value(&report.protected_metadata, "Agent Name"), None);
+        assert_eq!(report.encrypted_traits, vec!["Agent Name"]);
+        let mut format_preserved = report.format_preserved_traits.clone();
+        format_preserved.sort();
+        assert_eq!(format_preserved, vec!["Background", "Secret Code"]);
+        
+        let decrypted = client.decrypt_metadata(&report.protected_metadata).unwrap();
+        for trait_type in ["Secret Code", "Background", "Agent Name"] {
+            assert_eq!(value(&decrypted, trait_type), value(&metadata, trait_type));
+        }
+    }
+    
+    #[test]
+    fn test_protect_preview_matches_protection_without_storing() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let policy = ProtectionPolicy::default().with_decoys(2);
+        
+        let preview = client.protect_preview(&sample_metadata(), &policy).unwrap();
+        assert_eq!(preview.levels.len(), 5);
+        
+        let none = preview.level(PrivacyLevel::None).unwrap();
+        assert!(none.hidden_traits.is_empty());
+        assert!(none.fracture_plan.is_none());
+        
+        let medium = preview.level(PrivacyLevel::Medium).unwrap();
+        assert_eq!(medium.hidden_traits, vec!["Origin", "Mission", "Secret Code", "Agent Name"]);
+        assert_eq!(medium.decoyed_traits, medium.hidden_traits);
+        assert_eq!(medium.visible_traits, vec!["Background", "Accessory"]);
+        let plan = medium.fracture_plan.as_ref().unwrap();
+        assert_eq!(plan.fragments.iter().filter(|f| f.decoy).count(), 2);
+        
+        // Higher levels never reveal what lower levels hide
+        let heavy = preview.level(PrivacyLevel::Heavy).unwrap();
+        assert!(medium.hidden_traits.iter().all(|t| heavy.hidden_traits.contains(t)));
+        assert!(heavy.private_bytes > medium.private_bytes);
+        
+        assert!(adapter.is_empty());
+    }
+    
+    /// Serve sample metadata and point a mock metadata account at it
+    async fn mock_nft(server: &mut mockito::Server, nft_mint: &Pubkey) -> MockRpc {
+        server.mock("GET", "/699.json")
+            .with_body(serde_json::to_string(&sample_metadata()).unwrap())
+            .create_async()
+            .await;
+        
+        let uri = format!("{}/699.json", server.url());
+        MockRpc::new().with_account_data(&token_metadata_account(&Pubkey::new_unique(), nft_mint, &uri))
+    }
+    
+    #[tokio::test]
+    async fn test_wrap_and_protect_runs_full_pipeline() {
+        let mut server = mockito::Server::new_async().await;
+        let nft_mint = Pubkey::new_unique();
+        let adapter = InMemoryAdapter::new();
+        let client = mock_nft(&mut server, &nft_mint).await
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let uploader = MemoryUploader::new();
+        
+        let summary = client.wrap_and_protect(
+            &nft_mint,
+            &ProtectionPolicy::default(),
+            PrivacyLevel::Medium,
+            &uploader,
+        ).await.unwrap();
+        
+        // The uploaded document is the protected metadata
+        assert_eq!(summary.metadata_uri, "memory://0");
+        let uploaded: GlitchGangMetadata = serde_json::from_slice(&uploader.uploads()[0]).unwrap();
+        assert_eq!(uploaded.attributes.len(), summary.report.protected_metadata.attributes.len());
+        assert_eq!(adapter.len(), summary.report.fragment_manifest.as_ref().unwrap().fragment_ids.len());
+    }
+    
+    #[tokio::test]
+    async fn test_wrap_and_protect_rolls_back_fragments_on_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let nft_mint = Pubkey::new_unique();
+        let adapter = InMemoryAdapter::new();
+        let client = mock_nft(&mut server, &nft_mint).await
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        
+        let result = client.wrap_and_protect(
+            &nft_mint,
+            &ProtectionPolicy::default().with_decoys(2),
+            PrivacyLevel::Heavy,
+            &MemoryUploader::failing(),
+        ).await;
+        
+        assert!(result.unwrap_err().contains("upload"));
+        assert!(adapter.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_dry_run_previews_wrap_and_protect() {
+        let mut server = mockito::Server::new_async().await;
+        let nft_mint = Pubkey::new_unique();
+        let adapter = InMemoryAdapter::new();
+        let client = mock_nft(&mut server, &nft_mint).await
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter))
+            .with_dry_run();
+        let uploader = MemoryUploader::new();
+        
+        let summary = client.wrap_and_protect(
+            &nft_mint,
+            &ProtectionPolicy::default(),
+            PrivacyLevel::Medium,
+            &uploader,
+        ).await.unwrap();
+        
+        // Nothing was uploaded or stored
+        assert!(uploader.uploads().is_empty());
+        assert!(adapter.is_empty());
+        
+        let plan = client.dry_run_plan().unwrap();
+        assert_eq!(summary.metadata_uri, plan.uploads[0].uri);
+        assert_eq!(plan.fragment_writes.len(), summary.report.fragment_manifest.as_ref().unwrap().fragment_ids.len());
+        
+        let operations: Vec<&str> = plan.transactions.iter().map(|transaction| transaction.operation.as_str()).collect();
+        assert_eq!(operations, ["transaction.create_wrapper", "transaction.update_privacy"]);
+        assert_eq!(plan.transactions[1].signature, summary.update_signature);
+        assert_eq!(plan.accounts_created.len(), 1);
+        assert_eq!(plan.accounts_created[0].address, summary.wrapper_account);
+        assert!(plan.succeeds());
+    }
+    
+    #[tokio::test]
+    async fn test_update_metadata_uri_requires_update_authority() {
+        let owner = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        
+        let client = MockRpc::new()
+            .with_account_data(&token_metadata_account(&owner.pubkey(), &nft_mint, "https://old"))
+            .privacy_client(owner);
+        assert!(client.update_metadata_uri(&nft_mint, "https://new").await.is_ok());
+        
+        let client = MockRpc::new()
+            .with_account_data(&token_metadata_account(&Pubkey::new_unique(), &nft_mint, "https://old"))
+            .privacy_client(Keypair::new());
+        let err = client.update_metadata_uri(&nft_mint, "https://new").await.unwrap_err();
+        assert!(err.contains("not the owner"));
+    }
+    
+    #[tokio::test]
+    async fn test_wrapper_flow_against_mock_rpc() {
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let nft_mint = Pubkey::new_unique();
+        
+        let wrapper = client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        
+        let grantee = Pubkey::new_unique();
+        client.grant_access(&wrapper, &grantee, 2).await.unwrap();
+        client.revoke_access(&wrapper, &grantee).await.unwrap();
+        
+        client.grant_agent_access(&wrapper, "agent1.glitch.gang", 2).await.unwrap();
+        client.revoke_agent_access(&wrapper, "agent1.glitch.gang").await.unwrap();
+        client.migrate_wrapper(&wrapper).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_data_from_before_a_gated_revocation_still_decrypts() {
+        use solana_client::rpc_request::RpcRequest;
+        
+        let owner = Keypair::new();
+        let (nft_mint, wrapper, grantee) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mint = nft_mint.to_string();
+        
+        // The gate holds a key for the grantee, so revoking rotates the NFT's key
+        let (gate, _) = access_gate::find_gate_address(&access_gate::id(), &wrapper);
+        let (viewer_key, _) = access_gate::find_viewer_key_address(&access_gate::id(), &gate, &grantee);
+        let mut gate_key = vec![1];
+        gate_key.extend_from_slice(gate.as_ref());
+        gate_key.extend_from_slice(grantee.as_ref());
+        gate_key.extend([1, 0, 0, 0, 7, 0]);
+        gate_key.resize(GateViewerKey::LEN, 0);
+        
+        let client = MockRpc::new()
+            .with_account(&wrapper, &Pubkey::from_str(PROGRAM_ID).unwrap(), &wrapper_account(&nft_mint, &owner.pubkey(), &[(grantee, 2)]))
+            .with_account(&viewer_key, &access_gate::id(), &gate_key)
+            .with_response(RpcRequest::GetProgramAccounts, serde_json::json!([]))
+            .privacy_client(owner);
+        client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        
+        let ciphertext = client.quantum_veil.read().unwrap().encrypt(&mint, b"before revocation").unwrap();
+        let retired_key = client.privacy_config(&nft_mint).unwrap().current_key;
+        
+        client.revoke_access(&wrapper, &grantee).await.unwrap();
+        let config = client.privacy_config(&nft_mint).unwrap();
+        assert_ne!(config.current_key, retired_key);
+        assert_eq!(client.key_rotations(&nft_mint).len(), 1);
+        
+        let plaintext = client.quantum_veil.read().unwrap().decrypt(&mint, &ciphertext).unwrap();
+        assert_eq!(plaintext, b"before revocation");
+    }
+    
+    #[tokio::test]
+    async fn test_rotation_reshares_the_recovery_key() {
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
+        let (nft_mint, wrapper) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (recovery_account, _) = find_recovery_address(&program_id, &wrapper);
+        let mut config = RecoveryConfig {
+            is_initialized: true,
+            wrapper,
+            guardians: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            threshold: 2,
+            challenge_period: 86_400,
+            key_commitment: [0; 32],
+            sealed_shares: vec![vec![1], vec![2]],
+            pending: None,
+            rival_votes: Vec::new(),
+        };
+        
+        // Guardians get shares of the new key in the same rotation
+        let client = MockRpc::new()
+            .with_account(&recovery_account, &program_id, &config.try_to_vec().unwrap())
+            .privacy_client(Keypair::new())
+            .with_dry_run();
+        client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        client.rotate_key(&wrapper, &nft_mint).await.unwrap();
+        
+        let plan = client.dry_run_plan().unwrap();
+        let operations: Vec<&str> = plan.transactions.iter().map(|transaction| transaction.operation.as_str()).collect();
+        assert_eq!(operations, ["transaction.create_wrapper", "transaction.update_privacy", "transaction.setup_recovery"]);
+        
+        // A pending recovery would leave guardians with the retired key, so rotation waits for a veto
+        config.pending = Some(RecoveryRequest { new_owner: Pubkey::new_unique(), requested_at: 0, approvals: Vec::new() });
+        let client = MockRpc::new()
+            .with_account(&recovery_account, &program_id, &config.try_to_vec().unwrap())
+            .privacy_client(Keypair::new());
+        client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        let key = client.privacy_config(&nft_mint).unwrap().current_key;
+        
+        assert!(client.rotate_key(&wrapper, &nft_mint).await.unwrap_err().contains("pending"));
+        assert_eq!(client.privacy_config(&nft_mint).unwrap().current_key, key);
+        assert!(client.key_rotations(&nft_mint).is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_mask_config_updates_stay_in_sync() {
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let nft_mint = Pubkey::new_unique();
+        let mint = nft_mint.to_string();
+        let wrapper = client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        
+        let config = client.get_mask_config(&nft_mint).unwrap();
+        assert_eq!(config.privacy_settings[&VrmDataType::Voice], PrivacyLevel::Medium);
+        assert_eq!(client.quantum_veil.read().unwrap().get_config(&mint).unwrap().sync_mask.voice_noise, 0.5);
+        
+        client.set_data_type_privacy(&wrapper, &nft_mint, VrmDataType::Voice, PrivacyLevel::Complete).await.unwrap();
+        assert_eq!(client.get_mask_config(&nft_mint).unwrap().privacy_settings[&VrmDataType::Voice], PrivacyLevel::Complete);
+        assert_eq!(client.quantum_veil.read().unwrap().get_config(&mint).unwrap().sync_mask.voice_noise, 1.0);
+        
+        client.set_access_permission(&wrapper, &nft_mint, VrmDataType::Voice, AccessPermission::OwnerOnly).await.unwrap();
+        assert_eq!(
+            client.get_mask_config(&nft_mint).unwrap().access_permissions[&VrmDataType::Voice],
+            AccessPermission::OwnerOnly
+        );
+        
+        let agent = Pubkey::new_unique();
+        client.add_trusted_agent(&wrapper, &nft_mint, &agent.to_string()).await.unwrap();
+        assert_eq!(client.quantum_veil.read().unwrap().get_config(&mint).unwrap().sync_mask.trusted_agents, vec![agent.to_string()]);
+        
+        client.remove_trusted_agent(&wrapper, &nft_mint, &agent.to_string()).await.unwrap();
+        assert!(client.get_mask_config(&nft_mint).unwrap().global_trusted_agents.is_empty());
+        assert!(client.quantum_veil.read().unwrap().get_config(&mint).unwrap().sync_mask.trusted_agents.is_empty());
+        
+        client.rotate_key(&wrapper, &nft_mint).await.unwrap();
+        assert_eq!(client.get_mask_config(&nft_mint).unwrap().seed_epoch, 1);
+        assert_eq!(client.key_rotations(&nft_mint).len(), 1);
+        
+        // Unwrapped NFTs have no mask to update
+        assert!(client.set_data_type_privacy(&wrapper, &Pubkey::new_unique(), VrmDataType::Voice, PrivacyLevel::Light).await.is_err());
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_client_shared_across_tasks() {
+        fn assert_shareable<T: Send + Sync + Clone + 'static>() {}
+        assert_shareable::<GlitchGangPrivacyClient>();
+        
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        
+        // Clones mutate the same configs, keys and fragment cache concurrently
+        let tasks: Vec<_> = (0..16).map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let nft_mint = Pubkey::new_unique();
+                let wrapper = client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+                for _ in 0..3 {
+                    client.set_data_type_privacy(&wrapper, &nft_mint, VrmDataType::Voice, PrivacyLevel::Complete).await.unwrap();
+                    client.rotate_key(&wrapper, &nft_mint).await.unwrap();
+                    let report = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+                    client.decrypt_metadata(&report.protected_metadata).unwrap();
+                }
+                nft_mint
+            })
+        }).collect();
+        
+        for task in tasks {
+            let nft_mint = task.await.unwrap();
+            assert_eq!(client.key_rotations(&nft_mint).len(), 3);
+            assert_eq!(client.get_mask_config(&nft_mint).unwrap().seed_epoch, 3);
+            assert_eq!(client.privacy_config(&nft_mint).unwrap().sync_mask.voice_noise, 1.0);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_builders_reconfigure_cloned_clients() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        let clone = client.clone();
+        let in_flight = client.timeline_shifter().unwrap();
+        
+        // Reconfiguring while a clone and an operation hold the shifter swaps it for both clones
+        let client = client.with_dry_run();
+        assert!(client.timeline_shifter().unwrap().is_dry_run());
+        assert!(clone.timeline_shifter().unwrap().is_dry_run());
+        assert!(!in_flight.is_dry_run());
+        
+        client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        assert!(adapter.is_empty());
+        assert!(!client.dry_run_plan().unwrap().fragment_writes.is_empty());
+    }
+    
+    #[test]
+    fn test_process_vrm_data_with_level_masks_each_level() {
+        use crate::models::{PositionData, RotationData};
+        
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let nft_mint = Pubkey::new_unique();
+        let vrm_data = VrmData {
+            position: PositionData { x: 1.0, y: 2.0, z: 3.0 },
+            rotation: RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 },
+            scale: None,
+            bounding_box: None,
+            voice: None,
+            gestures: Vec::new(),
+            interactions: Vec::new(),
+            animations: HashMap::new(),
+            custom_data: HashMap::new(),
+        };
+        
+        let unmasked = client.process_vrm_data_with_level(&vrm_data, PrivacyLevel::None, &nft_mint).unwrap();
+        assert_eq!((unmasked.position.x, unmasked.position.y, unmasked.position.z), (1.0, 2.0, 3.0));
+        
+        for level in [PrivacyLevel::Light, PrivacyLevel::Medium, PrivacyLevel::Heavy, PrivacyLevel::Complete] {
+            let masked = client.process_vrm_data_with_level(&vrm_data, level, &nft_mint).unwrap();
+            assert_ne!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0), "{:?}", level);
+            
+            // The same level masks the same way every time
+            let again = client.process_vrm_data_with_level(&vrm_data, level, &nft_mint).unwrap();
+            assert_eq!(masked.position.x, again.position.x);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_protect_asset_round_trip() {
+        use image::{DynamicImage, ImageOutputFormat, RgbImage};
+        
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let uploader = MemoryUploader::new();
+        let mut metadata = sample_metadata();
+        let original_image = metadata.image.clone();
+        
+        let mut png = std::io::Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(RgbImage::new(64, 64)).write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        let png = png.into_inner();
+        
+        let protected = client.protect_asset(&mut metadata, &png, "image/png", Some(PreviewStyle::default()), &uploader)
+            .await
+            .unwrap();
+        
+        // The encrypted asset and the preview were uploaded, and the preview is now public
+        let uploads = uploader.uploads();
+        assert_eq!(uploads.len(), 2);
+        assert_ne!(uploads[0], png);
+        assert_eq!(metadata.image, protected.preview_uri.clone().unwrap());
+        assert_ne!(metadata.image, original_image);
+        assert_eq!(metadata.private_data.as_ref().unwrap().protected_assets, vec![protected.clone()]);
+        
+        assert_eq!(client.decrypt_asset(&protected, &uploads[0]).unwrap(), png);
+        
+        // Disclosed copies name their viewer
+        let viewer = Pubkey::new_unique();
+        let disclosed = client.disclose_asset(&protected, &uploads[0], &viewer).unwrap();
+        assert_eq!(watermark::identify_viewer(&disclosed, &[Pubkey::new_unique(), viewer]), Some(viewer));
+        
+        // Other keys cannot unwrap the asset key
+        let other = MockRpc::new().privacy_client(Keypair::new());
+        assert!(other.decrypt_asset(&protected, &uploads[0]).is_err());
+        
+        // Shared copies open with the viewer's own wallet
+        let mut shared = protected.clone();
+        client.share_asset(&mut shared, &other.viewer_key()).unwrap();
+        assert_eq!(other.decrypt_shared_asset(&shared, &uploads[0]).unwrap(), png);
+        assert!(MockRpc::new().privacy_client(Keypair::new()).decrypt_shared_asset(&shared, &uploads[0]).is_err());
+        
+        // Non-images get no preview
+        let vrm = client.protect_asset(&mut metadata, b"glTF model", "model/gltf-binary", Some(PreviewStyle::default()), &uploader)
+            .await
+            .unwrap();
+        assert!(vrm.preview_uri.is_none());
+        assert_eq!(metadata.private_data.unwrap().protected_assets.len(), 2);
+    }
+    
+    #[test]
+    fn test_access_report_combines_grants_and_audit_log() {
+        use audit::MemorySink;
+        
+        let owner = Keypair::new();
+        let viewer = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let mint = nft_mint.to_string();
+        let data = wrapper_account(&nft_mint, &owner.pubkey(), &[(viewer, 3)]);
+        
+        let audit_log = Arc::new(AuditLog::new(Arc::new(MemorySink::new())));
+        let client = MockRpc::new().with_account_data(&data).privacy_client(owner)
+            .with_audit_log(audit_log.clone());
+        
+        audit_log.record(AuditEvent::ViewerUnmasked {
+            nft_mint: mint.clone(),
+            viewer: viewer.to_string(),
+            reason: "access_token".to_string(),
+        }).unwrap();
+        audit_log.record(AuditEvent::Decrypted { nft_mint: mint.clone(), bytes: 42 }).unwrap();
+        audit_log.record(AuditEvent::Decrypted { nft_mint: Pubkey::new_unique().to_string(), bytes: 7 }).unwrap();
+        audit_log.record(AuditEvent::FragmentRetrieved {
+            fragment_id: "fragment".to_string(),
+            nft_mint: Some(mint.clone()),
+            requester: Some(viewer.to_string()),
+            latency_ms: 12,
+            found: true,
+        }).unwrap();
+        
+        let report = client.access_report(&Pubkey::new_unique(), &nft_mint, 0..u64::MAX).unwrap();
+        assert_eq!(report.grants.len(), 1);
+        assert_eq!((report.grants[0].grantee, report.grants[0].privacy_level), (viewer, PrivacyLevel::Heavy));
+        assert_eq!(report.unmasked_views[0].viewer, Some(viewer.to_string()));
+        assert_eq!(report.decryptions.iter().map(|d| d.bytes).collect::<Vec<_>>(), vec![Some(42)]);
+        assert!(report.anomalies.is_empty());
+        assert_eq!((report.retrievals.retrievals, report.retrievals.max_latency_ms), (1, 12));
+        assert_eq!(report.retrievals.requesters[&viewer.to_string()], 1);
+        
+        // Events outside the period are left out
+        let report = client.access_report(&Pubkey::new_unique(), &nft_mint, 0..1).unwrap();
+        assert!(report.unmasked_views.is_empty() && report.decryptions.is_empty());
+    }
+    
+    #[test]
+    fn test_viewer_level_follows_on_chain_grants() {
+        let owner = Keypair::new();
+        let viewer = Pubkey::new_unique();
+        let wrapper = Pubkey::new_unique();
+        let data = wrapper_account(&Pubkey::new_unique(), &owner.pubkey(), &[(viewer, 3)]);
+        
+        let state = WrapperState::unpack(&data).unwrap();
+        assert_eq!(state.privacy_config_hash, "config-hash");
+        assert_eq!(state.access_controls.get(&viewer), Some(&3));
+        assert!(WrapperState::unpack(&data[..data.len() - 1]).is_err());
+        
+        let owner_key = owner.pubkey();
+        let client = MockRpc::new().with_account_data(&data).privacy_client(owner);
+        assert_eq!(client.viewer_level(&wrapper, &viewer).unwrap(), PrivacyLevel::Heavy);
+        assert_eq!(client.viewer_level(&wrapper, &owner_key).unwrap(), PrivacyLevel::Complete);
+        assert_eq!(client.viewer_level(&wrapper, &Pubkey::new_unique()).unwrap(), PrivacyLevel::None);
+    }
+    
+    #[tokio::test]
+    async fn test_timelocked_attributes_open_once_claimed() {
+        let wrapper = Pubkey::new_unique();
+        let mut metadata = sample_metadata();
+        
+        let scheduled = MockRpc::new().privacy_client(Keypair::new())
+            .schedule_reveal(&wrapper, &mut metadata, "mission", &["Mission"], RevealTime::Slot(1_000))
+            .await
+            .unwrap();
+        
+        assert!(metadata.attributes.iter().all(|a| a.trait_type != "Mission"));
+        let timelocked = metadata.private_data.as_mut().unwrap().timelocked_attributes.first_mut().unwrap();
+        assert_eq!(timelocked.reveal_account, find_reveal_address(&Pubkey::from_str(PROGRAM_ID).unwrap(), &wrapper, "mission").0);
+        
+        // Stand in for the escrow the program holds, with a data key we know
+        let data_key = [7u8; 32];
+        let hidden = vec![Attribute { trait_type: "Mission".to_string(), value: "Find the signal".to_string() }];
+        timelocked.encrypted_attributes = base64::encode(seal(&serde_json::to_vec(&hidden).unwrap(), &data_key).unwrap());
+        let mut escrow = RevealEscrow {
+            is_initialized: true,
+            wrapper,
+            label: "mission".to_string(),
+            reveal_time: RevealTime::Slot(1_000),
+            key_commitment: solana_sdk::hash::hashv(&[&scheduled.reveal_key]).to_bytes(),
+            sealed_key: seal(&data_key, &scheduled.reveal_key).unwrap(),
+            reveal_key: None,
+        };
+        
+        let locked = MockRpc::new().with_account_data(&escrow.try_to_vec().unwrap()).privacy_client(Keypair::new());
+        assert!(locked.open_reveal(&metadata, "mission").unwrap_err().contains("not been claimed"));
+        
+        escrow.reveal_key = Some(scheduled.reveal_key);
+        let claimed = MockRpc::new().with_account_data(&escrow.try_to_vec().unwrap()).privacy_client(Keypair::new());
+        assert_eq!(sorted(&claimed.open_reveal(&metadata, "mission").unwrap()), sorted(&hidden));
+        assert!(claimed.open_reveal(&metadata, "origin").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_exported_keys_survive_a_restart() {
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let nft_mint = Pubkey::new_unique();
+        let wrapper = client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        client.rotate_key(&wrapper, &nft_mint).await.unwrap();
+        
+        let protected = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        let sealed = client.quantum_veil.read().unwrap().encrypt(&nft_mint.to_string(), b"per-mint secret").unwrap();
+        let keystore = client.export_keys("correct horse").unwrap();
+        
+        // A fresh client has a different key until it imports the keystore
+        let restarted = MockRpc::new().privacy_client(Keypair::new());
+        assert_ne!(restarted.key_id(), client.key_id());
+        assert!(restarted.import_keys(&keystore, "wrong").is_err());
+        
+        restarted.import_keys(&keystore, "correct horse").unwrap();
+        assert_eq!(restarted.key_id(), client.key_id());
+        assert_eq!(restarted.key_rotations(&nft_mint), client.key_rotations(&nft_mint));
+        assert_eq!(restarted.quantum_veil.read().unwrap().decrypt(&nft_mint.to_string(), &sealed).unwrap(), b"per-mint secret");
+        assert!(restarted.decrypt_metadata(&protected.protected_metadata).is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_recovery_phrase_recovers_nft_keys() {
+        let phrase = keystore::generate_mnemonic();
+        let client = MockRpc::new().privacy_client(Keypair::new()).with_mnemonic(&phrase).unwrap();
+        let nft_mint = Pubkey::new_unique();
+        let wrapper = client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        client.rotate_key(&wrapper, &nft_mint).await.unwrap();
+        
+        assert_eq!(client.privacy_config(&nft_mint).unwrap().key_index, Some(1));
+        let sealed = client.quantum_veil.read().unwrap().encrypt(&nft_mint.to_string(), b"per-mint secret").unwrap();
+        
+        // The phrase alone recovers the rotated key
+        let recovered = MockRpc::new().privacy_client(Keypair::new()).with_mnemonic(&phrase).unwrap();
+        recovered.recover_privacy_config(&nft_mint, 1).unwrap();
+        assert_eq!(recovered.quantum_veil.read().unwrap().decrypt(&nft_mint.to_string(), &sealed).unwrap(), b"per-mint secret");
+        
+        // The phrase travels with exported keys
+        let restarted = MockRpc::new().privacy_client(Keypair::new());
+        assert!(restarted.export_mnemonic().is_err());
+        restarted.import_keys(&client.export_keys("correct horse").unwrap(), "correct horse").unwrap();
+        assert_eq!(restarted.export_mnemonic().unwrap(), phrase);
+        
+        assert!(MockRpc::new().privacy_client(Keypair::new()).with_mnemonic("glitch gang forever").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_key_provider_holds_keys_wrapped() {
+        let provider: Arc<dyn KeyProvider> = Arc::new(LocalKeyProvider::new([5u8; 32]));
+        let client = MockRpc::new().privacy_client(Keypair::new());
+        let key_id = client.key_id();
+        
+        let client = client.with_key_provider(provider.clone(), None).unwrap();
+        assert!(client.encryption_key.read().unwrap().is_empty());
+        assert_eq!(client.key_id(), key_id);
+        assert!(client.export_keys("correct horse").unwrap_err().contains("cannot be exported"));
+        
+        let nft_mint = Pubkey::new_unique();
+        client.create_wrapper(&nft_mint, &sample_metadata()).await.unwrap();
+        assert!(client.quantum_veil.read().unwrap().get_config(&nft_mint.to_string()).unwrap().key_wrapped_by.is_some());
+        let protected = client.protect_metadata(&sample_metadata(), PrivacyLevel::Medium).await.unwrap();
+        
+        // A restarted client needs only the wrapped key and the provider
+        let wrapped_key = client.wrapped_key().unwrap();
+        let restarted = MockRpc::new().privacy_client(Keypair::new())
+            .with_key_provider(provider, Some(wrapped_key.clone()))
+            .unwrap();
+        assert_eq!(restarted.key_id(), key_id);
+        assert!(restarted.decrypt_metadata(&protected.protected_metadata).is_ok());
+        
+        let other: Arc<dyn KeyProvider> = Arc::new(LocalKeyProvider::new([6u8; 32]));
+        assert!(MockRpc::new().privacy_client(Keypair::new()).with_key_provider(other, Some(wrapped_key)).is_err());
+    }
+    
+    #[test]
+    fn test_recover_key_from_released_shares() {
+        let key = [9u8; 32];
+        let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+        let shares = recovery::split_secret(&key, 2, 3).unwrap();
+        let config = RecoveryConfig {
+            is_initialized: true,
+            wrapper: Pubkey::new_unique(),
+            guardians: guardians.iter().map(|g| g.pubkey()).collect(),
+            threshold: 2,
+            challenge_period: 86_400,
+            key_commitment: solana_sdk::hash::hashv(&[&key]).to_bytes(),
+            sealed_shares: shares.iter().zip(&guardians)
+                .map(|(share, guardian)| recovery::seal_share(share, &guardian.pubkey()).unwrap())
+                .collect(),
+            pending: None,
+            rival_votes: Vec::new(),
+        };
+        let new_owner = Keypair::new();
+        let client = MockRpc::new().with_account_data(&config.try_to_vec().unwrap())
+            .privacy_client(Keypair::from_bytes(&new_owner.to_bytes()).unwrap());
+        
+        // Guardians open their shares and reseal them to the new owner
+        let released: Vec<Vec<u8>> = guardians.iter().zip(&config.sealed_shares)
+            .map(|(guardian, sealed)| {
+                let share = recovery::open_share(sealed, guardian).unwrap();
+                recovery::seal_share(&share, &new_owner.pubkey()).unwrap()
+            })
+            .collect();
+        
+        assert_eq!(client.recover_key(&config.wrapper, &released[1..]).unwrap(), key);
+        assert!(client.recover_key(&config.wrapper, &released[..1]).unwrap_err().contains("Need 2"));
+        
+        // Shares of another key fail the commitment check
+        let forged: Vec<Vec<u8>> = recovery::split_secret(&[1u8; 32], 2, 2).unwrap().iter()
+            .map(|share| recovery::seal_share(share, &new_owner.pubkey()).unwrap())
+            .collect();
+        assert!(client.recover_key(&config.wrapper, &forged).unwrap_err().contains("commitment"));
+    }
+    
+    #[tokio::test]
+    async fn test_register_and_resolve_agent_against_mock_rpc() {
+        let agent = Keypair::new();
+        let record = AgentRecord {
+            is_initialized: true,
+            name: "agent1.glitch.gang".to_string(),
+            agent: agent.pubkey(),
+            authority: Pubkey::new_unique(),
+            metadata_uri: "https://glitch.gang/agents/1.json".to_string(),
+            registered_at: 1_700_000_000,
+        };
+        let client = MockRpc::new()
+            .with_account_data(&record.try_to_vec().unwrap())
+            .privacy_client(Keypair::new());
+        
+        client.register_agent("agent1.glitch.gang", &agent, &record.metadata_uri).await.unwrap();
+        
+        assert_eq!(client.resolve_agent("agent1.glitch.gang").unwrap(), agent.pubkey());
+        assert_eq!(client.agent_grantee("agent1.glitch.gang").unwrap(), agent.pubkey());
+        
+        // A record stored under a different name does not resolve
+        assert!(client.resolve_agent("agent2.glitch.gang").is_err());
+        assert_eq!(client.agent_grantee("agent2.glitch.gang").unwrap(), legacy_agent_pubkey("agent2.glitch.gang"));
+    }
+    
+    fn arb_attribute() -> impl Strategy<Value = Attribute> {
+        let trait_types = prop::sample::select(vec![
+            "Background", "Hood", "Mask", "Origin", "Mission", "Secret Code", "Agent Name",
+            "Accessory", "Symbols",
+        ]);
+        
+        (trait_types, ".{0,64}").prop_map(|(trait_type, value)| Attribute {
+            trait_type: trait_type.to_string(),
+            value,
+        })
+    }
+    
+    fn arb_privacy_level() -> impl Strategy<Value = PrivacyLevel> {
+        prop::sample::select(vec![
+            PrivacyLevel::None,
+            PrivacyLevel::Light,
+            PrivacyLevel::Medium,
+            PrivacyLevel::Heavy,
+            PrivacyLevel::Complete,
+        ])
+    }
+    
+    fn sorted(attributes: &[Attribute]) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = attributes.iter()
+            .map(|a| (a.trait_type.clone(), a.value.clone()))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+    
+    proptest! {
+        #[test]
+        fn prop_protect_decrypt_is_identity(
+            attributes in prop::collection::vec(arb_attribute(), 0..16),
+            level in arb_privacy_level(),
+            key in any::<[u8; 32]>(),
+        ) {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let adapter = InMemoryAdapter::new();
+            let client = MockRpc::new()
+                .privacy_client(Keypair::new())
+                .with_encryption_key(key)
+                .with_timeline_shifter(memory_timeline_shifter(&adapter));
+            
+            let mut metadata = sample_metadata();
+            metadata.attributes = attributes;
+            
+            let report = runtime.block_on(client.protect_metadata(&metadata, level)).unwrap();
+            let decrypted = client.decrypt_metadata(&report.protected_metadata).unwrap();
+            
+            // Decrypted attributes are appended after public ones, so compare as sets
+            prop_assert_eq!(sorted(&decrypted.attributes), sorted(&metadata.attributes));
+        }
     }
 }
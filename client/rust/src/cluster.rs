@@ -0,0 +1,268 @@
+//! Cluster presets and program ID registry
+//!
+//! RPC endpoints and deployed program IDs for each cluster come from the
+//! registry shipped in `clusters.json`. A registry file named by
+//! `QUANTUM_VEIL_CLUSTERS` overrides entries or adds custom clusters, and
+//! `ClusterConfig` builders override single values.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::client::websocket_url;
+
+/// Environment variable the CLI reads the cluster from
+pub const CLUSTER_ENV_VAR: &str = "QUANTUM_VEIL_CLUSTER";
+
+/// Environment variable naming a registry file overriding the built-in one
+pub const CLUSTER_REGISTRY_ENV_VAR: &str = "QUANTUM_VEIL_CLUSTERS";
+
+/// Registry shipped with the crate
+const BUILTIN_REGISTRY: &str = include_str!("../clusters.json");
+
+/// Solana cluster to connect to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cluster {
+    /// Mainnet beta
+    Mainnet,
+    /// Public devnet
+    Devnet,
+    /// Public testnet
+    Testnet,
+    /// Local test validator
+    Localnet,
+    /// Cluster registered under its own name, or an RPC URL
+    Custom(String),
+}
+
+impl Cluster {
+    /// Read the cluster from `QUANTUM_VEIL_CLUSTER`, defaulting to devnet
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(CLUSTER_ENV_VAR) {
+            Ok(cluster) if !cluster.is_empty() => cluster.parse(),
+            _ => Ok(Cluster::Devnet),
+        }
+    }
+    
+    /// Get the cluster's name in the registry
+    pub fn name(&self) -> &str {
+        match self {
+            Cluster::Mainnet => "mainnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Testnet => "testnet",
+            Cluster::Localnet => "localnet",
+            Cluster::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Cluster {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" => Err("Cluster name is empty".to_string()),
+            "mainnet" | "mainnet-beta" | "m" => Ok(Cluster::Mainnet),
+            "devnet" | "d" => Ok(Cluster::Devnet),
+            "testnet" | "t" => Ok(Cluster::Testnet),
+            "localnet" | "localhost" | "l" => Ok(Cluster::Localnet),
+            _ => Ok(Cluster::Custom(s.to_string())),
+        }
+    }
+}
+
+/// Endpoints and program IDs of a cluster
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    /// JSON RPC endpoint
+    pub rpc_url: String,
+    /// PubSub websocket endpoint, derived from the RPC URL if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_url: Option<String>,
+    /// Privacy wrapper program ID
+    pub wrapper_program: String,
+    /// On-chain fragment storage program ID
+    pub storage_program: String,
+    /// Access gate program ID, the gate crate's own if unset
+    #[serde(default = "default_gate_program")]
+    pub gate_program: String,
+}
+
+/// Program ID the access gate crate declares
+fn default_gate_program() -> String {
+    crate::access_gate::id().to_string()
+}
+
+impl ClusterConfig {
+    /// Use another RPC endpoint
+    pub fn with_rpc_url(mut self, rpc_url: &str) -> Self {
+        self.rpc_url = rpc_url.to_string();
+        self
+    }
+    
+    /// Use another PubSub websocket endpoint
+    pub fn with_websocket_url(mut self, websocket_url: &str) -> Self {
+        self.websocket_url = Some(websocket_url.to_string());
+        self
+    }
+    
+    /// Use another deployment of the privacy wrapper program
+    pub fn with_wrapper_program(mut self, program_id: &Pubkey) -> Self {
+        self.wrapper_program = program_id.to_string();
+        self
+    }
+    
+    /// Use another deployment of the fragment storage program
+    pub fn with_storage_program(mut self, program_id: &Pubkey) -> Self {
+        self.storage_program = program_id.to_string();
+        self
+    }
+    
+    /// Use another deployment of the access gate program
+    pub fn with_gate_program(mut self, program_id: &Pubkey) -> Self {
+        self.gate_program = program_id.to_string();
+        self
+    }
+    
+    /// Get the PubSub websocket endpoint
+    pub fn websocket_url(&self) -> String {
+        self.websocket_url.clone().unwrap_or_else(|| websocket_url(&self.rpc_url))
+    }
+    
+    /// Get the privacy wrapper program ID
+    pub fn wrapper_program_id(&self) -> Result<Pubkey, String> {
+        Pubkey::from_str(&self.wrapper_program)
+            .map_err(|e| format!("Invalid wrapper program ID {}: {}", self.wrapper_program, e))
+    }
+    
+    /// Get the fragment storage program ID
+    pub fn storage_program_id(&self) -> Result<Pubkey, String> {
+        Pubkey::from_str(&self.storage_program)
+            .map_err(|e| format!("Invalid storage program ID {}: {}", self.storage_program, e))
+    }
+    
+    /// Get the access gate program ID
+    pub fn gate_program_id(&self) -> Result<Pubkey, String> {
+        Pubkey::from_str(&self.gate_program)
+            .map_err(|e| format!("Invalid gate program ID {}: {}", self.gate_program, e))
+    }
+}
+
+/// Cluster configurations by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ClusterRegistry {
+    /// Configurations by cluster name
+    clusters: HashMap<String, ClusterConfig>,
+}
+
+impl Default for ClusterRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ClusterRegistry {
+    /// Get the registry shipped with the crate
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_REGISTRY).expect("Built-in cluster registry is valid")
+    }
+    
+    /// Parse a registry from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse cluster registry: {}", e))
+    }
+    
+    /// Load a registry file on top of the built-in one
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read cluster registry: {}", e))?;
+        Ok(Self::builtin().merge(Self::from_json(&json)?))
+    }
+    
+    /// Load the file named by `QUANTUM_VEIL_CLUSTERS`, or the built-in registry if unset
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(CLUSTER_REGISTRY_ENV_VAR) {
+            Ok(path) if !path.is_empty() => Self::load(&path),
+            _ => Ok(Self::builtin()),
+        }
+    }
+    
+    /// Add or replace clusters with those of another registry
+    pub fn merge(mut self, overrides: ClusterRegistry) -> Self {
+        self.clusters.extend(overrides.clusters.into_iter().map(|(name, config)| (name.to_lowercase(), config)));
+        self
+    }
+    
+    /// Add or replace a cluster
+    pub fn with_cluster(mut self, name: &str, config: ClusterConfig) -> Self {
+        self.clusters.insert(name.to_lowercase(), config);
+        self
+    }
+    
+    /// Get a cluster's configuration
+    ///
+    /// A custom cluster that isn't registered but is an RPC URL uses the
+    /// devnet program IDs.
+    pub fn resolve(&self, cluster: &Cluster) -> Result<ClusterConfig, String> {
+        if let Some(config) = self.clusters.get(&cluster.name().to_lowercase()) {
+            return Ok(config.clone());
+        }
+        
+        match cluster {
+            Cluster::Custom(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                let devnet = self.clusters.get(Cluster::Devnet.name())
+                    .ok_or("No devnet cluster registered to take program IDs from")?;
+                Ok(devnet.clone().with_rpc_url(url))
+            },
+            _ => Err(format!("Unknown cluster {}", cluster)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_clusters_resolve_with_overrides() {
+        let registry = ClusterRegistry::builtin();
+        
+        let devnet = registry.resolve(&"devnet".parse().unwrap()).unwrap();
+        assert_eq!(devnet.rpc_url, "https://api.devnet.solana.com");
+        assert_eq!(devnet.websocket_url(), "wss://api.devnet.solana.com");
+        assert_eq!(registry.resolve(&Cluster::Localnet).unwrap().websocket_url(), "ws://127.0.0.1:8900");
+        assert_eq!("mainnet-beta".parse::<Cluster>().unwrap(), Cluster::Mainnet);
+        
+        let program = Pubkey::new_unique();
+        let staging = registry.resolve(&Cluster::Mainnet).unwrap()
+            .with_rpc_url("https://rpc.example.com")
+            .with_wrapper_program(&program);
+        let registry = registry.merge(ClusterRegistry::from_json(&format!(
+            r#"{{"devnet": {{"rpc_url": "https://devnet.example.com", "wrapper_program": "{}", "storage_program": "{}"}}}}"#,
+            program, program,
+        )).unwrap()).with_cluster("Staging", staging);
+        
+        let staging = registry.resolve(&"staging".parse().unwrap()).unwrap();
+        assert_eq!(staging.rpc_url, "https://rpc.example.com");
+        assert_eq!(staging.wrapper_program_id().unwrap(), program);
+        assert_eq!(staging.gate_program_id().unwrap(), crate::access_gate::id());
+        
+        // Registered entries replace built-in ones, and bare URLs borrow devnet's programs
+        let custom = registry.resolve(&Cluster::Custom("https://my-node.example.com".to_string())).unwrap();
+        assert_eq!(custom.rpc_url, "https://my-node.example.com");
+        assert_eq!(custom.storage_program_id().unwrap(), program);
+        
+        // Entries written before the gate was registered fall back to its declared ID
+        assert_eq!(custom.gate_program_id().unwrap(), crate::access_gate::id());
+        assert!(registry.resolve(&Cluster::Custom("unknown".to_string())).is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//! Capability-scoped API keys for the service layer
+//!
+//! Each key carries a capability: the operations it may perform, optionally
+//! limited to specific mints and an expiry. Keys are shown once when issued
+//! and only their hashes are stored.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Serialize, Deserialize};
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::fmt;
+
+use quantum_veil::constant_time_eq;
+
+/// Prefix of every issued API key
+pub const API_KEY_PREFIX: &str = "qv";
+
+/// Operations an API key can be allowed to perform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Operation {
+    /// Read VRM data with the viewer's mask applied
+    ReadMaskedVrm,
+    /// Read unmasked VRM data
+    ReadVrm,
+    /// Read public metadata
+    ReadMetadata,
+    /// Decrypt protected metadata
+    DecryptMetadata,
+    /// Grant and revoke wrapper access
+    ManageAccess,
+    /// Issue, list, and revoke API keys
+    Admin,
+}
+
+/// What an API key is allowed to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capability {
+    /// Allowed operations
+    pub operations: Vec<Operation>,
+    /// Mints the key is limited to, or `None` for any mint
+    pub mints: Option<Vec<Pubkey>>,
+    /// Expiry as a Unix timestamp, or `None` for no expiry
+    pub expires_at: Option<u64>,
+}
+
+impl Capability {
+    /// Create a capability for the given operations on any mint
+    pub fn new(operations: &[Operation]) -> Self {
+        Self {
+            operations: operations.to_vec(),
+            mints: None,
+            expires_at: None,
+        }
+    }
+    
+    /// Capability for marketplaces: read masked VRM data and public metadata only
+    pub fn read_masked_vrm() -> Self {
+        Self::new(&[Operation::ReadMaskedVrm, Operation::ReadMetadata])
+    }
+    
+    /// Limit the capability to specific mints
+    pub fn with_mints(mut self, mints: &[Pubkey]) -> Self {
+        self.mints = Some(mints.to_vec());
+        self
+    }
+    
+    /// Expire the capability at a Unix timestamp
+    pub fn with_expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+    
+    /// Check if the capability allows an operation, optionally on a mint
+    pub fn allows(&self, operation: Operation, mint: Option<&Pubkey>, now: u64) -> bool {
+        if self.expires_at.map_or(false, |expires_at| expires_at <= now) {
+            return false;
+        }
+        
+        if !self.operations.contains(&operation) {
+            return false;
+        }
+        
+        // Mint-scoped keys cannot be used for mint-agnostic operations
+        match (&self.mints, mint) {
+            (None, _) => true,
+            (Some(mints), Some(mint)) => mints.contains(mint),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Stored API key, without the secret
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// Public key ID, embedded in the key
+    pub id: String,
+    /// Human-readable label, e.g. the marketplace name
+    pub label: String,
+    /// SHA-256 of the key secret
+    pub secret_hash: [u8; 32],
+    /// What the key may do
+    pub capability: Capability,
+    /// Issuance timestamp
+    pub created_at: u64,
+    /// Whether the key has been revoked
+    pub revoked: bool,
+}
+
+/// Authentication or authorization failure
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    /// No key, a malformed key, or an unknown or revoked key
+    Unauthenticated(String),
+    /// A valid key without the required capability
+    Forbidden(String),
+}
+
+impl AuthError {
+    /// HTTP status code for the failure
+    pub fn status_code(&self) -> u16 {
+        match self {
+            AuthError::Unauthenticated(_) => 401,
+            AuthError::Forbidden(_) => 403,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Unauthenticated(reason) => write!(f, "Unauthenticated: {}", reason),
+            AuthError::Forbidden(reason) => write!(f, "Forbidden: {}", reason),
+        }
+    }
+}
+
+/// Request to the key administration endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AdminRequest {
+    /// Issue a new key
+    IssueKey { label: String, capability: Capability },
+    /// Revoke a key by ID
+    RevokeKey { id: String },
+    /// List all keys
+    ListKeys,
+}
+
+/// Response from the key administration endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum AdminResponse {
+    /// Newly issued key; the plaintext key is only ever returned here
+    Issued { api_key: String, record: ApiKeyRecord },
+    /// Key revoked
+    Revoked { id: String },
+    /// All stored keys
+    Keys { keys: Vec<ApiKeyRecord> },
+}
+
+/// Store of hashed API keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialStore {
+    /// Key records by ID
+    keys: HashMap<String, ApiKeyRecord>,
+}
+
+impl CredentialStore {
+    /// Create an empty credential store
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Load a credential store from a JSON file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read credential store: {}", e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse credential store: {}", e))
+    }
+    
+    /// Save the credential store to a JSON file
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize credential store: {}", e))?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write credential store: {}", e))
+    }
+    
+    /// Issue a key, returning the plaintext key and its stored record
+    ///
+    /// The plaintext key is not recoverable afterwards.
+    pub fn issue(&mut self, label: &str, capability: Capability) -> (String, ApiKeyRecord) {
+        // Step 1: Generate a key ID and secret
+        let id = hex_id(&rand::random::<[u8; 8]>());
+        let secret = URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+        
+        // Step 2: Store only the hash of the secret
+        let record = ApiKeyRecord {
+            id: id.clone(),
+            label: label.to_string(),
+            secret_hash: hash_secret(&secret),
+            capability,
+            created_at: now(),
+            revoked: false,
+        };
+        self.keys.insert(id.clone(), record.clone());
+        
+        (format!("{}_{}_{}", API_KEY_PREFIX, id, secret), record)
+    }
+    
+    /// Revoke a key by ID
+    pub fn revoke(&mut self, id: &str) -> Result<(), String> {
+        let record = self.keys.get_mut(id)
+            .ok_or_else(|| format!("API key not found: {}", id))?;
+        record.revoked = true;
+        Ok(())
+    }
+    
+    /// List all key records
+    pub fn list(&self) -> Vec<ApiKeyRecord> {
+        let mut keys: Vec<ApiKeyRecord> = self.keys.values().cloned().collect();
+        keys.sort_by_key(|record| record.created_at);
+        keys
+    }
+    
+    /// Look up the record for a plaintext key
+    pub fn authenticate(&self, api_key: &str) -> Result<&ApiKeyRecord, AuthError> {
+        let invalid = || AuthError::Unauthenticated("Invalid API key".to_string());
+        
+        let mut parts = api_key.splitn(3, '_');
+        let (prefix, id, secret) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(prefix), Some(id), Some(secret)) => (prefix, id, secret),
+            _ => return Err(invalid()),
+        };
+        
+        if prefix != API_KEY_PREFIX {
+            return Err(invalid());
+        }
+        
+        let record = self.keys.get(id).ok_or_else(invalid)?;
+        if !constant_time_eq(&record.secret_hash, &hash_secret(secret)) {
+            return Err(invalid());
+        }
+        
+        if record.revoked {
+            return Err(AuthError::Unauthenticated("API key revoked".to_string()));
+        }
+        
+        Ok(record)
+    }
+    
+    /// Check that a key may perform an operation, optionally on a mint
+    pub fn authorize(
+        &self,
+        api_key: &str,
+        operation: Operation,
+        mint: Option<&Pubkey>,
+    ) -> Result<&ApiKeyRecord, AuthError> {
+        let record = self.authenticate(api_key)?;
+        
+        if !record.capability.allows(operation, mint, now()) {
+            return Err(AuthError::Forbidden(format!(
+                "API key {} is not allowed to perform {:?}",
+                record.id, operation
+            )));
+        }
+        
+        Ok(record)
+    }
+    
+    /// Handle a request to the key administration endpoints
+    ///
+    /// The caller's key must carry the `Admin` operation.
+    pub fn handle_admin(
+        &mut self,
+        authorization: Option<&str>,
+        request: AdminRequest,
+    ) -> Result<AdminResponse, AuthError> {
+        RequireCapability::new(Operation::Admin).check(self, authorization, None)?;
+        
+        match request {
+            AdminRequest::IssueKey { label, capability } => {
+                let (api_key, record) = self.issue(&label, capability);
+                Ok(AdminResponse::Issued { api_key, record })
+            }
+            AdminRequest::RevokeKey { id } => {
+                self.revoke(&id).map_err(AuthError::Forbidden)?;
+                Ok(AdminResponse::Revoked { id })
+            }
+            AdminRequest::ListKeys => Ok(AdminResponse::Keys { keys: self.list() }),
+        }
+    }
+}
+
+/// Request guard for service handlers
+///
+/// Framework-agnostic: services pass the raw `Authorization` header and the
+/// mint the request targets, and map an `AuthError` to its status code.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireCapability {
+    /// Operation the guarded route performs
+    operation: Operation,
+}
+
+impl RequireCapability {
+    /// Guard a route performing an operation
+    pub fn new(operation: Operation) -> Self {
+        Self { operation }
+    }
+    
+    /// Check the `Authorization` header of a request
+    pub fn check<'a>(
+        &self,
+        store: &'a CredentialStore,
+        authorization: Option<&str>,
+        mint: Option<&Pubkey>,
+    ) -> Result<&'a ApiKeyRecord, AuthError> {
+        let api_key = authorization
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| AuthError::Unauthenticated("Missing bearer API key".to_string()))?;
+        
+        store.authorize(api_key, self.operation, mint)
+    }
+}
+
+/// Hash a key secret for storage
+fn hash_secret(secret: &str) -> [u8; 32] {
+    hashv(&[secret.as_bytes()]).to_bytes()
+}
+
+/// Format bytes as a lowercase hex ID
+fn hex_id(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Get the current Unix timestamp
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_marketplace_key_is_scoped_to_masked_reads() {
+        let mut store = CredentialStore::new();
+        let mint = Pubkey::new_unique();
+        let (api_key, record) = store.issue("marketplace", Capability::read_masked_vrm().with_mints(&[mint]));
+        assert!(!serde_json::to_string(&store).unwrap().contains(&api_key));
+        
+        let header = format!("Bearer {}", api_key);
+        let guard = RequireCapability::new(Operation::ReadMaskedVrm);
+        assert_eq!(guard.check(&store, Some(&header), Some(&mint)).unwrap().id, record.id);
+        
+        // Other mints, other operations, and missing keys are refused
+        let other_mint = Pubkey::new_unique();
+        assert_eq!(guard.check(&store, Some(&header), Some(&other_mint)).unwrap_err().status_code(), 403);
+        let unmasked = RequireCapability::new(Operation::ReadVrm);
+        assert_eq!(unmasked.check(&store, Some(&header), Some(&mint)).unwrap_err().status_code(), 403);
+        assert_eq!(guard.check(&store, None, Some(&mint)).unwrap_err().status_code(), 401);
+        
+        // Tampered and revoked keys are unauthenticated
+        let tampered = format!("{}x", header);
+        assert_eq!(guard.check(&store, Some(&tampered), Some(&mint)).unwrap_err().status_code(), 401);
+        store.revoke(&record.id).unwrap();
+        assert_eq!(guard.check(&store, Some(&header), Some(&mint)).unwrap_err().status_code(), 401);
+    }
+    
+    #[test]
+    fn test_expired_keys_are_forbidden() {
+        let mut store = CredentialStore::new();
+        let (api_key, _) = store.issue("expired", Capability::read_masked_vrm().with_expiry(now() - 1));
+        
+        let result = store.authorize(&api_key, Operation::ReadMaskedVrm, None);
+        assert!(matches!(result, Err(AuthError::Forbidden(_))));
+    }
+    
+    #[test]
+    fn test_admin_endpoints_require_admin_key() {
+        let mut store = CredentialStore::new();
+        let (admin_key, _) = store.issue("admin", Capability::new(&[Operation::Admin]));
+        let (reader_key, _) = store.issue("reader", Capability::read_masked_vrm());
+        let admin = format!("Bearer {}", admin_key);
+        let reader = format!("Bearer {}", reader_key);
+        
+        let request: AdminRequest = serde_json::from_str(
+            r#"{"action":"issue_key","label":"marketplace","capability":{"operations":["ReadMaskedVrm"],"mints":null,"expires_at":null}}"#,
+        ).unwrap();
+        assert!(store.handle_admin(Some(&reader), request.clone()).is_err());
+        
+        let issued = match store.handle_admin(Some(&admin), request).unwrap() {
+            AdminResponse::Issued { record, .. } => record,
+            response => panic!("Unexpected response: {:?}", response),
+        };
+        
+        store.handle_admin(Some(&admin), AdminRequest::RevokeKey { id: issued.id.clone() }).unwrap();
+        match store.handle_admin(Some(&admin), AdminRequest::ListKeys).unwrap() {
+            AdminResponse::Keys { keys } => {
+                assert_eq!(keys.len(), 3);
+                assert!(keys.iter().any(|record| record.id == issued.id && record.revoked));
+            }
+            response => panic!("Unexpected response: {:?}", response),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Local cluster harness for end-to-end tests and examples
+//!
+//! Starts a throwaway `solana-test-validator` with the privacy wrapper
+//! program preloaded at its program ID, funds a payer, and hands back a
+//! client pointed at it. The validator and its ledger are removed when the
+//! `TestContext` is dropped.
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::{GlitchGangPrivacyClient, PROGRAM_ID};
+use crate::cluster::{Cluster, ClusterConfig, ClusterRegistry};
+
+/// Environment variable overriding the validator binary
+pub const VALIDATOR_BIN_ENV_VAR: &str = "SOLANA_TEST_VALIDATOR";
+
+/// Environment variable overriding the wrapper program build
+pub const WRAPPER_SO_ENV_VAR: &str = "PRIVACY_WRAPPER_SO";
+
+/// How long to wait for the validator to answer RPC calls
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often to poll the validator while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Configuration of a local cluster
+#[derive(Debug, Clone)]
+pub struct LocalCluster {
+    /// Programs to preload, by program ID
+    programs: Vec<(Pubkey, PathBuf)>,
+    /// RPC port; the PubSub websocket listens one above
+    rpc_port: u16,
+    /// Lamports airdropped to the payer
+    airdrop: u64,
+}
+
+/// Running local cluster with a funded payer and a client for it
+pub struct TestContext {
+    /// Validator process
+    validator: Child,
+    /// Ledger directory, removed on drop
+    ledger: PathBuf,
+    /// RPC URL of the validator
+    pub rpc_url: String,
+    /// PubSub websocket URL of the validator
+    pub websocket_url: String,
+    /// Funded payer, also the client's owner
+    pub payer: Keypair,
+    /// RPC client for the validator
+    pub rpc_client: RpcClient,
+    /// Privacy client owned by the payer
+    pub client: GlitchGangPrivacyClient,
+}
+
+impl Default for LocalCluster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalCluster {
+    /// Configure a cluster with the privacy wrapper program preloaded
+    ///
+    /// The program is read from `PRIVACY_WRAPPER_SO`, or from the workspace's
+    /// `target/deploy` after `cargo build-sbf`.
+    pub fn new() -> Self {
+        let wrapper_so = std::env::var(WRAPPER_SO_ENV_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/privacy_wrapper.so")));
+        
+        Self {
+            programs: vec![(Pubkey::from_str(PROGRAM_ID).unwrap(), wrapper_so)],
+            rpc_port: 8899,
+            airdrop: 10 * LAMPORTS_PER_SOL,
+        }
+    }
+    
+    /// Preload another program, e.g. the fragment storage program `SolanaAdapter` talks to
+    pub fn with_program(mut self, program_id: Pubkey, so_path: impl Into<PathBuf>) -> Self {
+        self.programs.push((program_id, so_path.into()));
+        self
+    }
+    
+    /// Listen on another RPC port, e.g. to run clusters side by side
+    pub fn with_rpc_port(mut self, rpc_port: u16) -> Self {
+        self.rpc_port = rpc_port;
+        self
+    }
+    
+    /// Fund the payer with a different amount
+    pub fn with_airdrop(mut self, lamports: u64) -> Self {
+        self.airdrop = lamports;
+        self
+    }
+    
+    /// Build the validator's command line for a ledger directory
+    fn validator_args(&self, ledger: &str) -> Vec<String> {
+        let mut args = vec![
+            "--reset".to_string(),
+            "--quiet".to_string(),
+            "--ledger".to_string(),
+            ledger.to_string(),
+            "--rpc-port".to_string(),
+            self.rpc_port.to_string(),
+        ];
+        for (program_id, so_path) in &self.programs {
+            args.push("--bpf-program".to_string());
+            args.push(program_id.to_string());
+            args.push(so_path.display().to_string());
+        }
+        args
+    }
+    
+    /// Start the validator and wait until the payer is funded
+    pub fn start(self) -> Result<TestContext, String> {
+        for (program_id, so_path) in &self.programs {
+            if !so_path.exists() {
+                return Err(format!("Program {} not built at {}", program_id, so_path.display()));
+            }
+        }
+        
+        let ledger = std::env::temp_dir().join(format!("quantum-veil-ledger-{}-{}", std::process::id(), self.rpc_port));
+        let validator_bin = std::env::var(VALIDATOR_BIN_ENV_VAR).unwrap_or_else(|_| "solana-test-validator".to_string());
+        let validator = Command::new(&validator_bin)
+            .args(self.validator_args(&ledger.display().to_string()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", validator_bin, e))?;
+        
+        let rpc_url = format!("http://127.0.0.1:{}", self.rpc_port);
+        let payer = Keypair::new();
+        let owner = Keypair::from_bytes(&payer.to_bytes()).map_err(|e| e.to_string())?;
+        
+        // From here on dropping the context stops the validator, even if startup fails
+        let context = TestContext {
+            validator,
+            ledger,
+            websocket_url: format!("ws://127.0.0.1:{}", self.rpc_port + 1),
+            rpc_client: RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed()),
+            client: GlitchGangPrivacyClient::new(&rpc_url, owner),
+            rpc_url,
+            payer,
+        };
+        
+        context.wait_until(|rpc_client| rpc_client.get_health().is_ok(), "the validator to start")?;
+        context.airdrop(&context.payer.pubkey(), self.airdrop)?;
+        
+        Ok(context)
+    }
+}
+
+impl TestContext {
+    /// Start a cluster with the default configuration
+    pub fn start() -> Result<Self, String> {
+        LocalCluster::new().start()
+    }
+    
+    /// Get the local cluster's endpoints and program IDs
+    pub fn cluster(&self) -> ClusterConfig {
+        ClusterRegistry::builtin()
+            .resolve(&Cluster::Localnet)
+            .expect("Built-in registry has localnet")
+            .with_rpc_url(&self.rpc_url)
+            .with_websocket_url(&self.websocket_url)
+    }
+    
+    /// Airdrop lamports to an account and wait for them to land
+    pub fn airdrop(&self, account: &Pubkey, lamports: u64) -> Result<(), String> {
+        let signature = self.rpc_client.request_airdrop(account, lamports)
+            .map_err(|e| format!("Failed to airdrop to {}: {}", account, e))?;
+        
+        self.wait_until(
+            |rpc_client| rpc_client.confirm_transaction(&signature).unwrap_or(false),
+            "the airdrop to confirm",
+        )
+    }
+    
+    /// Poll the validator until a condition holds
+    fn wait_until(&self, condition: impl Fn(&RpcClient) -> bool, what: &str) -> Result<(), String> {
+        let started = Instant::now();
+        while !condition(&self.rpc_client) {
+            if started.elapsed() > STARTUP_TIMEOUT {
+                return Err(format!("Timed out waiting for {}", what));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TestContext {
+    fn drop(&mut self) {
+        if let Err(e) = self.validator.kill().and_then(|_| self.validator.wait()) {
+            log::warn!("Failed to stop local validator: {}", e);
+        }
+        let _ = std::fs::remove_dir_all(&self.ledger);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_validator_preloads_configured_programs() {
+        let storage_program = Pubkey::new_unique();
+        let cluster = LocalCluster::new()
+            .with_program(storage_program, "/tmp/fragment_storage.so")
+            .with_rpc_port(18899);
+        
+        let args = cluster.validator_args("/tmp/ledger");
+        assert_eq!(&args[..6], ["--reset", "--quiet", "--ledger", "/tmp/ledger", "--rpc-port", "18899"]);
+        assert_eq!(args.iter().filter(|arg| *arg == "--bpf-program").count(), 2);
+        assert!(args.ends_with(&["--bpf-program".to_string(), storage_program.to_string(), "/tmp/fragment_storage.so".to_string()]));
+        
+        // Missing builds are reported before anything is spawned
+        assert!(cluster.with_program(Pubkey::new_unique(), "/nonexistent/program.so").start().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Dry-run plans
+//!
+//! A client in dry-run mode simulates its transactions instead of sending
+//! them and records uploads and fragment writes instead of performing them,
+//! so operations like `wrap_and_protect` can be previewed on mainnet. The
+//! effects are collected in a `DryRunPlan`.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+
+use timeline_shifter::PlannedWrite;
+
+/// Transaction simulated instead of sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedTransaction {
+    /// Operation the transaction performs, e.g. `transaction.create_wrapper`
+    pub operation: String,
+    /// Signature the transaction would land under
+    pub signature: String,
+    /// Fee in lamports
+    pub fee: u64,
+    /// Compute units consumed, if reported
+    pub units_consumed: Option<u64>,
+    /// Program logs
+    pub logs: Vec<String>,
+    /// Simulation error, if the transaction would fail
+    ///
+    /// Transactions depending on accounts created earlier in the same dry
+    /// run fail simulation, since nothing was sent.
+    pub error: Option<String>,
+}
+
+/// Account a simulated transaction would create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedAccount {
+    /// Account address
+    pub address: Pubkey,
+    /// Lamports deposited for rent, if the simulation reported them
+    pub lamports: Option<u64>,
+    /// Account data size, if the simulation reported it
+    pub size: Option<usize>,
+}
+
+/// Upload skipped by a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedUpload {
+    /// Placeholder URI returned in place of the real one
+    pub uri: String,
+    /// Content type of the upload
+    pub content_type: String,
+    /// Bytes that would have been uploaded
+    pub size: usize,
+}
+
+/// Effects of the operations run in dry-run mode
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DryRunPlan {
+    /// Transactions in the order they would be sent
+    pub transactions: Vec<SimulatedTransaction>,
+    /// Accounts the transactions would create
+    pub accounts_created: Vec<PlannedAccount>,
+    /// Uploads that would be made
+    pub uploads: Vec<PlannedUpload>,
+    /// Fragments that would be stored
+    pub fragment_writes: Vec<PlannedWrite>,
+}
+
+impl DryRunPlan {
+    /// Record an upload, returning the placeholder URI standing in for it
+    pub(crate) fn record_upload(&mut self, bytes: &[u8], content_type: &str) -> String {
+        let uri = format!("dry-run://upload/{}", self.uploads.len());
+        self.uploads.push(PlannedUpload {
+            uri: uri.clone(),
+            content_type: content_type.to_string(),
+            size: bytes.len(),
+        });
+        uri
+    }
+    
+    /// Get the total transaction fees in lamports
+    pub fn total_fees(&self) -> u64 {
+        self.transactions.iter().map(|transaction| transaction.fee).sum()
+    }
+    
+    /// Get the total rent deposits of created accounts in lamports
+    pub fn total_rent(&self) -> u64 {
+        self.accounts_created.iter().filter_map(|account| account.lamports).sum()
+    }
+    
+    /// Get the bytes that would be uploaded and stored as fragments
+    pub fn total_storage_bytes(&self) -> usize {
+        self.uploads.iter().map(|upload| upload.size).sum::<usize>()
+            + self.fragment_writes.iter().map(|write| write.size).sum::<usize>()
+    }
+    
+    /// Check if every simulated transaction would succeed
+    pub fn succeeds(&self) -> bool {
+        self.transactions.iter().all(|transaction| transaction.error.is_none())
+    }
+}
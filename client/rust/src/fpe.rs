@@ -0,0 +1,359 @@
+//! Format-preserving encryption
+//!
+//! Some marketplaces reject metadata whose attribute values change type or
+//! length. FF1 and FF3-1 (NIST SP 800-38G) encrypt a value into another of
+//! the same length over the same alphabet, so a serial number stays a
+//! serial number and can stay in the public attributes. Characters outside
+//! the alphabet, such as separators, are kept in place.
+
+use aes::Aes256;
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_256};
+
+/// Smallest domain NIST allows, radix^n
+const MIN_DOMAIN: u128 = 1_000_000;
+
+/// Largest half domain handled by FF1 here, so arithmetic fits in a u128
+const FF1_MAX_HALF_DOMAIN: u128 = 1 << 120;
+
+/// Largest half domain FF3-1 allows
+const FF3_1_MAX_HALF_DOMAIN: u128 = 1 << 96;
+
+/// FPE algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FpeMode {
+    /// FF1, taking a tweak of any length
+    Ff1,
+    /// FF3-1, faster but limited to shorter values and a 56-bit tweak
+    Ff3_1,
+}
+
+/// Characters a value is encrypted over, in numeral order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Alphabet(String);
+
+impl Alphabet {
+    /// Create an alphabet of at least two distinct characters
+    pub fn new(chars: &str) -> Result<Self, String> {
+        let mut seen = std::collections::HashSet::new();
+        if !chars.chars().all(|c| seen.insert(c)) {
+            return Err(format!("Alphabet has repeated characters: {}", chars));
+        }
+        if seen.len() < 2 {
+            return Err("Alphabet needs at least two characters".to_string());
+        }
+        
+        Ok(Self(chars.to_string()))
+    }
+    
+    /// Decimal digits
+    pub fn digits() -> Self {
+        Self("0123456789".to_string())
+    }
+    
+    /// Lowercase ASCII letters
+    pub fn lowercase() -> Self {
+        Self("abcdefghijklmnopqrstuvwxyz".to_string())
+    }
+    
+    /// Uppercase ASCII letters
+    pub fn uppercase() -> Self {
+        Self("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string())
+    }
+    
+    /// Digits and ASCII letters of both cases
+    pub fn alphanumeric() -> Self {
+        Self("0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz".to_string())
+    }
+    
+    /// Get the number of characters
+    pub fn radix(&self) -> u128 {
+        self.0.chars().count() as u128
+    }
+    
+    /// Get the numeral of a character, if it is in the alphabet
+    fn numeral(&self, c: char) -> Option<u128> {
+        self.0.chars().position(|a| a == c).map(|i| i as u128)
+    }
+    
+    /// Get the character of a numeral
+    fn char(&self, numeral: u128) -> char {
+        self.0.chars().nth(numeral as usize).unwrap()
+    }
+}
+
+/// How a trait value is encrypted in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatPreserving {
+    /// Algorithm
+    pub mode: FpeMode,
+    /// Characters encrypted; others are left in place
+    pub alphabet: Alphabet,
+}
+
+impl FormatPreserving {
+    /// Encrypt with FF1 over an alphabet
+    pub fn ff1(alphabet: Alphabet) -> Self {
+        Self { mode: FpeMode::Ff1, alphabet }
+    }
+    
+    /// Encrypt with FF3-1 over an alphabet
+    pub fn ff3_1(alphabet: Alphabet) -> Self {
+        Self { mode: FpeMode::Ff3_1, alphabet }
+    }
+}
+
+/// FF1 and FF3-1 over AES-256
+#[derive(Clone)]
+pub struct FpeCipher {
+    /// AES keyed for FF1
+    ff1: Aes256,
+    /// AES keyed with the byte-reversed key, as FF3-1 specifies
+    ff3_1: Aes256,
+}
+
+impl FpeCipher {
+    /// Create a cipher from a 256-bit key
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut reversed = *key;
+        reversed.reverse();
+        
+        Self {
+            ff1: Aes256::new(GenericArray::from_slice(key)),
+            ff3_1: Aes256::new(GenericArray::from_slice(&reversed)),
+        }
+    }
+    
+    /// Encrypt a value, keeping its length and the position of characters outside the alphabet
+    ///
+    /// The same value and tweak always encrypt the same way, so tweaks should
+    /// differ between NFTs and traits.
+    pub fn encrypt(&self, format: &FormatPreserving, tweak: &[u8], value: &str) -> Result<String, String> {
+        self.transform(format, tweak, value, true)
+    }
+    
+    /// Decrypt a value encrypted with `encrypt`
+    pub fn decrypt(&self, format: &FormatPreserving, tweak: &[u8], value: &str) -> Result<String, String> {
+        self.transform(format, tweak, value, false)
+    }
+    
+    /// Run the cipher over the characters of a value that are in the alphabet
+    fn transform(&self, format: &FormatPreserving, tweak: &[u8], value: &str, encrypt: bool) -> Result<String, String> {
+        let radix = format.alphabet.radix();
+        if radix < 2 {
+            return Err("Alphabet needs at least two characters".to_string());
+        }
+        
+        let mut chars: Vec<char> = value.chars().collect();
+        let (positions, numerals): (Vec<usize>, Vec<u128>) = chars.iter()
+            .enumerate()
+            .filter_map(|(i, c)| format.alphabet.numeral(*c).map(|numeral| (i, numeral)))
+            .unzip();
+        
+        let output = match format.mode {
+            FpeMode::Ff1 => self.ff1(radix, tweak, &numerals, encrypt)?,
+            FpeMode::Ff3_1 => self.ff3_1(radix, tweak, &numerals, encrypt)?,
+        };
+        
+        for (i, numeral) in positions.into_iter().zip(output) {
+            chars[i] = format.alphabet.char(numeral);
+        }
+        Ok(chars.into_iter().collect())
+    }
+    
+    /// FF1 (SP 800-38G section 6.2)
+    fn ff1(&self, radix: u128, tweak: &[u8], x: &[u128], encrypt: bool) -> Result<Vec<u128>, String> {
+        let n = x.len();
+        check_domain(radix, n, n.div_ceil(2), FF1_MAX_HALF_DOMAIN)?;
+        
+        let u = n / 2;
+        let v = n - u;
+        let b = bit_len(radix.pow(v as u32) - 1).div_ceil(8);
+        let d = 4 * b.div_ceil(4) + 4;
+        
+        let mut p = vec![1, 2, 1];
+        p.extend_from_slice(&(radix as u32).to_be_bytes()[1..]);
+        p.extend_from_slice(&[10, (u % 256) as u8]);
+        p.extend_from_slice(&(n as u32).to_be_bytes());
+        p.extend_from_slice(&(tweak.len() as u32).to_be_bytes());
+        
+        let (mut a, mut b_half) = (x[..u].to_vec(), x[u..].to_vec());
+        for i in rounds(10, encrypt) {
+            let m = if i % 2 == 0 { u } else { v };
+            let modulus = radix.pow(m as u32);
+            
+            // Encryption feeds B to the round function, decryption A
+            let mut q = tweak.to_vec();
+            q.resize(tweak.len() + (16 - (tweak.len() + b + 1) % 16) % 16, 0);
+            q.push(i);
+            q.extend_from_slice(&num(if encrypt { &b_half } else { &a }, radix).to_be_bytes()[16 - b..]);
+            
+            // R = PRF(P || Q), a CBC-MAC, extended to d bytes by encrypting R ^ [j]
+            let mut r = [0u8; 16];
+            for block in p.iter().chain(&q).copied().collect::<Vec<u8>>().chunks(16) {
+                r.iter_mut().zip(block).for_each(|(r, b)| *r ^= b);
+                r = self.encrypt_block(&self.ff1, r);
+            }
+            let mut s = r.to_vec();
+            for j in 1..d.div_ceil(16) {
+                let mut block = r;
+                block.iter_mut().zip((j as u128).to_be_bytes()).for_each(|(r, j)| *r ^= j);
+                s.extend_from_slice(&self.encrypt_block(&self.ff1, block));
+            }
+            s.truncate(d);
+            let y = s.iter().fold(0u128, |acc, byte| (acc * 256 + *byte as u128) % modulus);
+            
+            if encrypt {
+                let c = (num(&a, radix) + y) % modulus;
+                a = std::mem::replace(&mut b_half, str_m(c, radix, m));
+            } else {
+                let c = (num(&b_half, radix) + modulus - y) % modulus;
+                b_half = std::mem::replace(&mut a, str_m(c, radix, m));
+            }
+        }
+        
+        a.extend(b_half);
+        Ok(a)
+    }
+    
+    /// FF3-1 (SP 800-38G Rev. 1 section 6.3)
+    fn ff3_1(&self, radix: u128, tweak: &[u8], x: &[u128], encrypt: bool) -> Result<Vec<u128>, String> {
+        let n = x.len();
+        let u = n.div_ceil(2);
+        let v = n - u;
+        check_domain(radix, n, u, FF3_1_MAX_HALF_DOMAIN)?;
+        
+        // FF3-1 takes a 56-bit tweak, so longer tweaks are hashed down to one
+        let t = Sha3_256::digest(tweak);
+        let t_l = [t[0], t[1], t[2], t[3] & 0xf0];
+        let t_r = [t[4], t[5], t[6], (t[3] & 0x0f) << 4];
+        
+        let (mut a, mut b) = (x[..u].to_vec(), x[u..].to_vec());
+        for i in rounds(8, encrypt) {
+            let (m, w) = if i % 2 == 0 { (u, t_r) } else { (v, t_l) };
+            let modulus = radix.pow(m as u32);
+            
+            let mut p = [0u8; 16];
+            p[..4].copy_from_slice(&w);
+            p[3] ^= i;
+            p[4..].copy_from_slice(&num_rev(if encrypt { &b } else { &a }, radix).to_be_bytes()[4..]);
+            
+            // S = REVB(CIPH_REVB(K)(REVB(P)))
+            p.reverse();
+            let mut s = self.encrypt_block(&self.ff3_1, p);
+            s.reverse();
+            let y = u128::from_be_bytes(s) % modulus;
+            
+            if encrypt {
+                let c = (num_rev(&a, radix) + y) % modulus;
+                a = std::mem::replace(&mut b, rev(str_m(c, radix, m)));
+            } else {
+                let c = (num_rev(&b, radix) + modulus - y) % modulus;
+                b = std::mem::replace(&mut a, rev(str_m(c, radix, m)));
+            }
+        }
+        
+        a.extend(b);
+        Ok(a)
+    }
+    
+    /// Encrypt one AES block
+    fn encrypt_block(&self, aes: &Aes256, block: [u8; 16]) -> [u8; 16] {
+        let mut block = GenericArray::from(block);
+        aes.encrypt_block(&mut block);
+        block.into()
+    }
+}
+
+/// Get the tweak for a trait of an NFT, so equal values encrypt differently across NFTs and traits
+pub fn tweak(nft_id: &str, trait_type: &str) -> Vec<u8> {
+    [nft_id.as_bytes(), b"/", trait_type.as_bytes()].concat()
+}
+
+/// Check a value's length fits the cipher's domain
+fn check_domain(radix: u128, n: usize, half: usize, max_half_domain: u128) -> Result<(), String> {
+    if n < 2 || radix.checked_pow(n as u32).map_or(false, |domain| domain < MIN_DOMAIN) {
+        return Err(format!("Value has too few characters for format-preserving encryption: {}", n));
+    }
+    if radix.checked_pow(half as u32).map_or(true, |domain| domain > max_half_domain) {
+        return Err(format!("Value has too many characters for format-preserving encryption: {}", n));
+    }
+    
+    Ok(())
+}
+
+/// Get the round numbers in the order they are applied
+fn rounds(count: u8, encrypt: bool) -> Vec<u8> {
+    if encrypt { (0..count).collect() } else { (0..count).rev().collect() }
+}
+
+/// Get the number of bits needed to write a number
+fn bit_len(x: u128) -> usize {
+    (128 - x.leading_zeros()) as usize
+}
+
+/// Read numerals as a number, most significant first
+fn num(x: &[u128], radix: u128) -> u128 {
+    x.iter().fold(0, |acc, numeral| acc * radix + numeral)
+}
+
+/// Read numerals as a number, least significant first
+fn num_rev(x: &[u128], radix: u128) -> u128 {
+    x.iter().rev().fold(0, |acc, numeral| acc * radix + numeral)
+}
+
+/// Write a number as `m` numerals, most significant first
+fn str_m(mut x: u128, radix: u128, m: usize) -> Vec<u128> {
+    let mut numerals = vec![0; m];
+    for numeral in numerals.iter_mut().rev() {
+        *numeral = x % radix;
+        x /= radix;
+    }
+    numerals
+}
+
+/// Reverse numerals
+fn rev(mut x: Vec<u128>) -> Vec<u128> {
+    x.reverse();
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_format_preserving_round_trip() {
+        // NIST SP 800-38G FF1-AES256 samples 7 and 8
+        let key: [u8; 32] = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+            0xef, 0x43, 0x59, 0xd8, 0xd5, 0x80, 0xaa, 0x4f, 0x7f, 0x03, 0x6d, 0x6f, 0x04, 0xfc, 0x6a, 0x94,
+        ];
+        let cipher = FpeCipher::new(&key);
+        let digits = FormatPreserving::ff1(Alphabet::digits());
+        assert_eq!(cipher.encrypt(&digits, &[], "0123456789").unwrap(), "6657667009");
+        assert_eq!(cipher.encrypt(&digits, b"9876543210", "0123456789").unwrap(), "1001623463");
+        assert_eq!(cipher.decrypt(&digits, b"9876543210", "1001623463").unwrap(), "0123456789");
+        
+        // Length, charset and separators are kept, for both modes
+        for format in [
+            FormatPreserving::ff1(Alphabet::alphanumeric()),
+            FormatPreserving::ff3_1(Alphabet::alphanumeric()),
+            FormatPreserving::ff3_1(Alphabet::digits()),
+        ] {
+            let value = "GG-004217-Agent7";
+            let encrypted = cipher.encrypt(&format, b"Glitch #42/Serial", value).unwrap();
+            assert_ne!(encrypted, value);
+            assert_eq!(encrypted.len(), value.len());
+            assert_eq!(encrypted.match_indices('-').collect::<Vec<_>>(), value.match_indices('-').collect::<Vec<_>>());
+            assert_eq!(cipher.decrypt(&format, b"Glitch #42/Serial", &encrypted).unwrap(), value);
+            assert_ne!(cipher.encrypt(&format, b"Glitch #43/Serial", value).unwrap(), encrypted);
+        }
+        
+        // Values too short to encrypt safely are refused
+        assert!(cipher.encrypt(&digits, &[], "42").is_err());
+    }
+}
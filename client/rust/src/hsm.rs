@@ -0,0 +1,115 @@
+//! PKCS#11 key provider for hardware security modules
+//!
+//! Data keys are wrapped with AES key wrap under a secret key that lives on
+//! the token, and messages are signed with HMAC-SHA256 on the token, so no
+//! master key material is ever read into process memory.
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use std::sync::Mutex;
+
+use quantum_veil::KeyProvider;
+
+/// Key provider backed by a PKCS#11 token
+pub struct Pkcs11Provider {
+    /// Loaded PKCS#11 module, kept alive for the session
+    _context: Pkcs11,
+    /// Logged-in session; PKCS#11 sessions are not safe for concurrent use
+    session: Mutex<Session>,
+    /// AES key wrapping data keys
+    wrapping_key: ObjectHandle,
+    /// HMAC key used by `sign`
+    signing_key: Option<ObjectHandle>,
+    /// Label of the wrapping key
+    label: String,
+}
+
+impl Pkcs11Provider {
+    /// Open the first token of a PKCS#11 module and find the wrapping key by label
+    pub fn open(module_path: &str, pin: &str, key_label: &str) -> Result<Self, String> {
+        let context = Pkcs11::new(module_path)
+            .map_err(|e| format!("Failed to load PKCS#11 module {}: {}", module_path, e))?;
+        context.initialize(CInitializeArgs::OsThreads)
+            .map_err(|e| format!("Failed to initialize PKCS#11 module: {}", e))?;
+        
+        let slot = *context.get_slots_with_token()
+            .map_err(|e| format!("Failed to list PKCS#11 slots: {}", e))?
+            .first()
+            .ok_or_else(|| "No PKCS#11 token present".to_string())?;
+        
+        let session = context.open_rw_session(slot)
+            .map_err(|e| format!("Failed to open PKCS#11 session: {}", e))?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_string())))
+            .map_err(|e| format!("Failed to log in to PKCS#11 token: {}", e))?;
+        
+        let wrapping_key = find_secret_key(&session, key_label)?;
+        
+        Ok(Self {
+            _context: context,
+            session: Mutex::new(session),
+            wrapping_key,
+            signing_key: None,
+            label: key_label.to_string(),
+        })
+    }
+    
+    /// Sign with an HMAC key on the token
+    pub fn with_signing_key(mut self, key_label: &str) -> Result<Self, String> {
+        let signing_key = find_secret_key(&self.session()?, key_label)?;
+        self.signing_key = Some(signing_key);
+        Ok(self)
+    }
+    
+    /// Lock the session
+    fn session(&self) -> Result<std::sync::MutexGuard<'_, Session>, String> {
+        self.session.lock().map_err(|_| "PKCS#11 session poisoned".to_string())
+    }
+}
+
+impl KeyProvider for Pkcs11Provider {
+    fn key_id(&self) -> String {
+        format!("pkcs11:{}", self.label)
+    }
+    
+    fn get_key(&self) -> Result<[u8; 32], String> {
+        Err(format!("PKCS#11 key {} cannot be exported", self.label))
+    }
+    
+    fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, String> {
+        self.session()?
+            .encrypt(&Mechanism::AesKeyWrapPad, self.wrapping_key, key)
+            .map_err(|e| format!("Failed to wrap key: {}", e))
+    }
+    
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+        self.session()?
+            .decrypt(&Mechanism::AesKeyWrapPad, self.wrapping_key, wrapped)
+            .map_err(|e| format!("Failed to unwrap key: {}", e))
+    }
+    
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        let signing_key = self.signing_key
+            .ok_or_else(|| "No PKCS#11 signing key configured".to_string())?;
+        
+        self.session()?
+            .sign(&Mechanism::Sha256Hmac, signing_key, message)
+            .map_err(|e| format!("Failed to sign: {}", e))
+    }
+}
+
+/// Find a secret key on the token by label
+fn find_secret_key(session: &Session, label: &str) -> Result<ObjectHandle, String> {
+    session.find_objects(&[
+        Attribute::Class(ObjectClass::SECRET_KEY),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ])
+    .map_err(|e| format!("Failed to search PKCS#11 token: {}", e))?
+    .first()
+    .copied()
+    .ok_or_else(|| format!("No secret key labelled {} on the token", label))
+}
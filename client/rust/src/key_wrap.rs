@@ -0,0 +1,249 @@
+//! Wrapping content keys to viewers
+//!
+//! Sharing a protected asset with a viewer means sealing its key to them.
+//! The classical wrap is an ephemeral X25519 exchange with the viewer's
+//! wallet key. Keys wrapped today can be recorded now and broken later by
+//! a quantum computer, so with the `quantum` feature a viewer that
+//! publishes an ML-KEM-768 key gets a hybrid wrap instead: the key is
+//! sealed under both shared secrets and stays safe while either holds.
+//! Every wrap starts with a version byte, so clients built without the
+//! feature keep reading classical wraps and reject hybrid ones clearly.
+
+use curve25519_dalek::{
+    constants::X25519_BASEPOINT,
+    edwards::CompressedEdwardsY,
+    montgomery::MontgomeryPoint,
+    scalar::Scalar,
+};
+use rand::{Rng, rngs::OsRng};
+use ring::digest;
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_256};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signer}};
+use zeroize::Zeroizing;
+
+use quantum_veil::{encrypt_data, decrypt_data};
+
+use crate::recovery::clamp;
+
+#[cfg(feature = "quantum")]
+use ml_kem::{B32, EncodedSizeUser, KemCore, MlKem768, kem::{Decapsulate, Encapsulate}};
+
+/// ML-KEM-768 decapsulation key
+#[cfg(feature = "quantum")]
+type MlKemSecretKey = <MlKem768 as KemCore>::DecapsulationKey;
+
+/// ML-KEM-768 encapsulation key
+#[cfg(feature = "quantum")]
+type MlKemPublicKey = <MlKem768 as KemCore>::EncapsulationKey;
+
+/// Wrap version sealing under X25519 alone
+pub const WRAP_CLASSICAL: u8 = 1;
+
+/// Wrap version sealing under X25519 and ML-KEM-768
+pub const WRAP_HYBRID: u8 = 2;
+
+/// Domain label for keys sealing a wrapped key
+const WRAP_KEY_LABEL: &[u8] = b"quantum-veil/key-wrap/v1";
+
+/// Domain label for ML-KEM keys derived from a wallet
+#[cfg(feature = "quantum")]
+const ML_KEM_SEED_LABEL: &[u8] = b"quantum-veil/ml-kem-seed/v1";
+
+/// Length of an ML-KEM-768 ciphertext
+#[cfg(feature = "quantum")]
+const ML_KEM_CIPHERTEXT_LEN: usize = 1088;
+
+/// Public keys a viewer publishes to receive wrapped keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewerKey {
+    /// Viewer's wallet, whose ed25519 key doubles as their X25519 key
+    pub account: Pubkey,
+    /// ML-KEM-768 encapsulation key, base64 encoded, if the viewer supports hybrid wraps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ml_kem: Option<String>,
+}
+
+impl ViewerKey {
+    /// Viewer key of a wallet that only supports classical wraps
+    pub fn classical(account: Pubkey) -> Self {
+        Self { account, ml_kem: None }
+    }
+    
+    /// Viewer key of a wallet, with its ML-KEM key when built with the `quantum` feature
+    ///
+    /// The ML-KEM key is derived from the wallet, so the viewer needs
+    /// nothing else to unwrap.
+    pub fn for_keypair(keypair: &Keypair) -> Self {
+        Self {
+            account: keypair.pubkey(),
+            #[cfg(feature = "quantum")]
+            ml_kem: Some(base64::encode(ml_kem_keys(keypair).1.as_bytes())),
+            #[cfg(not(feature = "quantum"))]
+            ml_kem: None,
+        }
+    }
+}
+
+/// Wrap a key to a viewer, hybrid if both sides support it
+///
+/// Classical wraps are `[1][ephemeral key: 32][nonce: 12][ciphertext]`;
+/// hybrid wraps add the ML-KEM ciphertext, as
+/// `[2][ephemeral key: 32][ML-KEM ciphertext: 1088][nonce: 12][ciphertext]`.
+pub fn wrap_key(key: &[u8; 32], viewer: &ViewerKey) -> Result<Vec<u8>, String> {
+    let viewer_point = CompressedEdwardsY(viewer.account.to_bytes())
+        .decompress()
+        .ok_or_else(|| format!("Cannot wrap a key to off-curve account {}", viewer.account))?
+        .to_montgomery();
+    
+    let mut ephemeral = [0u8; 32];
+    OsRng.fill(&mut ephemeral);
+    let ephemeral = Scalar::from_bits(clamp(ephemeral));
+    let ephemeral_public = &X25519_BASEPOINT * &ephemeral;
+    let shared = &viewer_point * &ephemeral;
+    
+    let mut wrapped = Vec::new();
+    let sealing_key = match &viewer.ml_kem {
+        #[cfg(feature = "quantum")]
+        Some(encapsulation_key) => {
+            let encapsulation_key = parse_encapsulation_key(encapsulation_key)?;
+            let (ciphertext, kem_shared) = encapsulation_key.encapsulate(&mut OsRng)
+                .map_err(|_| "Failed to encapsulate to viewer's ML-KEM key".to_string())?;
+            
+            wrapped.push(WRAP_HYBRID);
+            wrapped.extend_from_slice(ephemeral_public.as_bytes());
+            wrapped.extend_from_slice(&ciphertext);
+            derive_sealing_key(WRAP_HYBRID, &shared, &ephemeral_public, &viewer.account, &[kem_shared.as_slice(), ciphertext.as_slice()])
+        },
+        _ => {
+            wrapped.push(WRAP_CLASSICAL);
+            wrapped.extend_from_slice(ephemeral_public.as_bytes());
+            derive_sealing_key(WRAP_CLASSICAL, &shared, &ephemeral_public, &viewer.account, &[])
+        },
+    };
+    
+    let mut nonce = [0u8; 12];
+    OsRng.fill(&mut nonce);
+    wrapped.extend_from_slice(&nonce);
+    wrapped.extend(encrypt_data(key, &*sealing_key, &nonce)?);
+    Ok(wrapped)
+}
+
+/// Unwrap a key wrapped to the keypair's wallet
+pub fn unwrap_key(wrapped: &[u8], keypair: &Keypair) -> Result<Zeroizing<[u8; 32]>, String> {
+    let version = *wrapped.first().ok_or_else(|| "Wrapped key is empty".to_string())?;
+    let header_len = match version {
+        WRAP_CLASSICAL => 33,
+        #[cfg(feature = "quantum")]
+        WRAP_HYBRID => 33 + ML_KEM_CIPHERTEXT_LEN,
+        #[cfg(not(feature = "quantum"))]
+        WRAP_HYBRID => return Err("Key was wrapped with ML-KEM, which needs the quantum feature".to_string()),
+        version => return Err(format!("Unsupported key wrap version {}", version)),
+    };
+    if wrapped.len() < header_len + 12 {
+        return Err("Wrapped key too short".to_string());
+    }
+    
+    // The X25519 secret of an ed25519 key is its clamped expanded seed
+    let expanded = digest::digest(&digest::SHA512, &keypair.to_bytes()[..32]);
+    let secret = Scalar::from_bits(clamp(expanded.as_ref()[..32].try_into().unwrap()));
+    let ephemeral_public = MontgomeryPoint(wrapped[1..33].try_into().unwrap());
+    let shared = &ephemeral_public * &secret;
+    
+    let sealing_key = match version {
+        #[cfg(feature = "quantum")]
+        WRAP_HYBRID => {
+            let ciphertext = &wrapped[33..header_len];
+            let kem_shared = ml_kem_keys(keypair).0
+                .decapsulate(&ciphertext.try_into().map_err(|_| "Invalid ML-KEM ciphertext length".to_string())?)
+                .map_err(|_| "Failed to decapsulate ML-KEM ciphertext".to_string())?;
+            derive_sealing_key(WRAP_HYBRID, &shared, &ephemeral_public, &keypair.pubkey(), &[kem_shared.as_slice(), ciphertext])
+        },
+        _ => derive_sealing_key(WRAP_CLASSICAL, &shared, &ephemeral_public, &keypair.pubkey(), &[]),
+    };
+    
+    let (nonce, ciphertext) = wrapped[header_len..].split_at(12);
+    let key = Zeroizing::new(decrypt_data(ciphertext, &*sealing_key, nonce)
+        .map_err(|_| "Failed to unwrap key".to_string())?);
+    
+    let mut unwrapped = Zeroizing::new([0u8; 32]);
+    if key.len() != unwrapped.len() {
+        return Err("Invalid wrapped key length".to_string());
+    }
+    unwrapped.copy_from_slice(&key);
+    Ok(unwrapped)
+}
+
+/// Derive the key sealing a wrapped key from the shared secrets and public values
+///
+/// Hybrid wraps also bind the ML-KEM shared secret and ciphertext, so the
+/// sealing key is safe while either exchange is.
+fn derive_sealing_key(
+    version: u8,
+    shared: &MontgomeryPoint,
+    ephemeral_public: &MontgomeryPoint,
+    viewer: &Pubkey,
+    kem: &[&[u8]],
+) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(WRAP_KEY_LABEL);
+    hasher.update([version]);
+    hasher.update(shared.as_bytes());
+    hasher.update(ephemeral_public.as_bytes());
+    hasher.update(viewer.as_ref());
+    for part in kem {
+        hasher.update(part);
+    }
+    
+    Zeroizing::new(hasher.finalize().into())
+}
+
+/// Derive a wallet's ML-KEM-768 keys from its secret key
+#[cfg(feature = "quantum")]
+fn ml_kem_keys(keypair: &Keypair) -> (MlKemSecretKey, MlKemPublicKey) {
+    let mut hasher = sha3::Sha3_512::new();
+    hasher.update(ML_KEM_SEED_LABEL);
+    hasher.update(&keypair.to_bytes()[..32]);
+    let seed = Zeroizing::new(hasher.finalize().to_vec());
+    
+    let d = B32::try_from(&seed[..32]).unwrap();
+    let z = B32::try_from(&seed[32..]).unwrap();
+    MlKem768::generate_deterministic(&d, &z)
+}
+
+/// Parse a base64 ML-KEM-768 encapsulation key
+#[cfg(feature = "quantum")]
+fn parse_encapsulation_key(encoded: &str) -> Result<MlKemPublicKey, String> {
+    let bytes = base64::decode(encoded)
+        .map_err(|e| format!("Failed to decode ML-KEM key: {}", e))?;
+    let encoded = bytes.as_slice().try_into()
+        .map_err(|_| format!("Invalid ML-KEM key length: {}", bytes.len()))?;
+    
+    Ok(MlKemPublicKey::from_bytes(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_wrapped_keys_open_only_for_their_viewer() {
+        let viewer = Keypair::new();
+        let key = [9u8; 32];
+        
+        let wrapped = wrap_key(&key, &ViewerKey::classical(viewer.pubkey())).unwrap();
+        assert_eq!(wrapped[0], WRAP_CLASSICAL);
+        assert_eq!(*unwrap_key(&wrapped, &viewer).unwrap(), key);
+        assert!(unwrap_key(&wrapped, &Keypair::new()).is_err());
+        
+        // Hybrid only when the viewer publishes an ML-KEM key and this build supports it
+        let wrapped = wrap_key(&key, &ViewerKey::for_keypair(&viewer)).unwrap();
+        assert_eq!(wrapped[0], if cfg!(feature = "quantum") { WRAP_HYBRID } else { WRAP_CLASSICAL });
+        assert_eq!(*unwrap_key(&wrapped, &viewer).unwrap(), key);
+        assert!(unwrap_key(&wrapped, &Keypair::new()).is_err());
+        
+        let mut unknown = wrapped.clone();
+        unknown[0] = 9;
+        assert!(unwrap_key(&unknown, &viewer).unwrap_err().contains("Unsupported key wrap version"));
+    }
+}
@@ -0,0 +1,334 @@
+//! Passphrase-protected keystore for the client's encryption keys
+//!
+//! Keys are sealed with ChaCha20Poly1305 under a key stretched from the
+//! passphrase with scrypt, and stored as JSON so they survive restarts and
+//! can be moved between machines. Per-NFT keys can instead be derived from
+//! a BIP39 recovery phrase, which the keystore carries alongside them.
+//! Unlock slots let a passkey or the OS keychain open the keystore in place
+//! of the passphrase.
+
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+
+use quantum_veil::{HdMasterKey, LocalKeyProvider, PrivacyConfig, SecretBytes, encrypt_data, decrypt_data};
+
+use crate::unlock::UnlockProvider;
+
+/// Current keystore format version
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Name of the only supported cipher
+const CIPHER: &str = "chacha20poly1305";
+
+/// scrypt cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Log2 of the CPU/memory cost
+    pub log_n: u8,
+    /// Block size
+    pub r: u32,
+    /// Parallelism
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    /// Interactive-login strength, about 32 MiB per derivation
+    fn default() -> Self {
+        Self { log_n: 15, r: 8, p: 1 }
+    }
+}
+
+/// A retired key of an NFT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// Identifier of the retired key
+    pub key_id: String,
+    /// When it was retired, as a Unix timestamp
+    pub rotated_at: u64,
+}
+
+/// Key material and history of one NFT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintKeyRecord {
+    /// Identifier of the current key
+    pub key_id: String,
+    /// Privacy config holding the current key
+    pub config: PrivacyConfig,
+    /// Keys retired by rotation, oldest first
+    #[serde(default)]
+    pub rotations: Vec<KeyRotation>,
+}
+
+/// Everything a keystore protects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBundle {
+    /// Client encryption key, base64 encoded
+    pub encryption_key: SecretBytes,
+    /// Per-NFT key records by mint
+    pub mints: BTreeMap<String, MintKeyRecord>,
+    /// Entropy of the recovery phrase NFT keys are derived from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_entropy: Option<SecretBytes>,
+}
+
+/// Passphrase key wrapped under an unlock provider's key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockSlot {
+    /// Kind of provider, e.g. `passkey`
+    pub kind: String,
+    /// Identifier of the provider opening the slot
+    pub slot_id: String,
+    /// Salt the provider derives its key from, base64 encoded
+    pub salt: String,
+    /// Cipher nonce, base64 encoded
+    pub nonce: String,
+    /// Wrapped passphrase key, base64 encoded
+    pub wrapped_key: String,
+}
+
+/// Encrypted keystore file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    /// Format version
+    pub version: u32,
+    /// Identifier of the client encryption key, readable without the passphrase
+    pub key_id: String,
+    /// Key derivation parameters
+    pub kdf: KdfParams,
+    /// Key derivation salt, base64 encoded
+    pub salt: String,
+    /// Cipher sealing the bundle
+    pub cipher: String,
+    /// Cipher nonce, base64 encoded
+    pub nonce: String,
+    /// Sealed key bundle, base64 encoded
+    pub ciphertext: String,
+    /// Slots opening the keystore without the passphrase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unlock_slots: Vec<UnlockSlot>,
+}
+
+impl Keystore {
+    /// Seal a key bundle under a passphrase
+    pub fn seal(bundle: &KeyBundle, key_id: &str, passphrase: &str, kdf: KdfParams) -> Result<Self, String> {
+        let mut salt = [0u8; 16];
+        OsRng.fill(&mut salt);
+        let mut nonce = [0u8; 12];
+        OsRng.fill(&mut nonce);
+        
+        let plaintext = Zeroizing::new(serde_json::to_vec(bundle)
+            .map_err(|e| format!("Failed to serialize keys: {}", e))?);
+        let ciphertext = encrypt_data(&plaintext, &*derive_key(passphrase, &salt, kdf)?, &nonce)?;
+        
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            key_id: key_id.to_string(),
+            kdf,
+            salt: base64::encode(salt),
+            cipher: CIPHER.to_string(),
+            nonce: base64::encode(nonce),
+            ciphertext: base64::encode(ciphertext),
+            unlock_slots: Vec::new(),
+        })
+    }
+    
+    /// Open the key bundle with the passphrase
+    pub fn open(&self, passphrase: &str) -> Result<KeyBundle, String> {
+        let key = self.passphrase_key(passphrase)?;
+        
+        self.open_with_key(&key)
+            .map_err(|_| "Wrong passphrase or corrupted keystore".to_string())
+    }
+    
+    /// Open the key bundle with an unlock provider enrolled with `add_unlock`
+    pub fn open_with(&self, provider: &dyn UnlockProvider) -> Result<KeyBundle, String> {
+        self.check_format()?;
+        let slot_id = provider.slot_id();
+        let slot = self.unlock_slots.iter()
+            .find(|slot| slot.slot_id == slot_id)
+            .ok_or_else(|| format!("No unlock slot {} in keystore", slot_id))?;
+        
+        let salt = decode(&slot.salt)?;
+        let salt: &[u8; 32] = salt.as_slice().try_into()
+            .map_err(|_| "Invalid unlock slot salt length".to_string())?;
+        let unlock_key = provider.unlock(salt)?;
+        
+        let unwrapped = Zeroizing::new(decrypt_data(&decode(&slot.wrapped_key)?, &*unlock_key, &decode(&slot.nonce)?)
+            .map_err(|_| format!("Unlock slot {} rejected the provider's key", slot_id))?);
+        let mut key = Zeroizing::new([0u8; 32]);
+        if unwrapped.len() != key.len() {
+            return Err("Invalid unlock slot key length".to_string());
+        }
+        key.copy_from_slice(&unwrapped);
+        
+        self.open_with_key(&key)
+            .map_err(|_| format!("Unlock slot {} is stale or corrupted", slot_id))
+    }
+    
+    /// Open the key bundle with an unlock provider, falling back to the passphrase
+    pub fn unlock(&self, provider: Option<&dyn UnlockProvider>, passphrase: Option<&str>) -> Result<KeyBundle, String> {
+        if let Some(provider) = provider {
+            match self.open_with(provider) {
+                Ok(bundle) => return Ok(bundle),
+                Err(e) if passphrase.is_some() => log::warn!("Falling back to passphrase: {}", e),
+                Err(e) => return Err(e),
+            }
+        }
+        
+        match passphrase {
+            Some(passphrase) => self.open(passphrase),
+            None => Err("No unlock provider or passphrase given".to_string()),
+        }
+    }
+    
+    /// Let an unlock provider open the keystore, replacing its previous slot
+    ///
+    /// The passphrase is checked first and keeps working.
+    pub fn add_unlock(&mut self, passphrase: &str, provider: &dyn UnlockProvider) -> Result<(), String> {
+        let key = self.passphrase_key(passphrase)?;
+        self.open_with_key(&key)
+            .map_err(|_| "Wrong passphrase or corrupted keystore".to_string())?;
+        
+        let mut salt = [0u8; 32];
+        OsRng.fill(&mut salt);
+        let mut nonce = [0u8; 12];
+        OsRng.fill(&mut nonce);
+        
+        let unlock_key = provider.enroll(&salt)?;
+        let wrapped_key = encrypt_data(&key[..], &*unlock_key, &nonce)?;
+        
+        let slot_id = provider.slot_id();
+        self.remove_unlock(&slot_id);
+        self.unlock_slots.push(UnlockSlot {
+            kind: provider.kind().to_string(),
+            slot_id,
+            salt: base64::encode(salt),
+            nonce: base64::encode(nonce),
+            wrapped_key: base64::encode(wrapped_key),
+        });
+        
+        Ok(())
+    }
+    
+    /// Drop an unlock slot, returning whether it existed
+    pub fn remove_unlock(&mut self, slot_id: &str) -> bool {
+        let before = self.unlock_slots.len();
+        self.unlock_slots.retain(|slot| slot.slot_id != slot_id);
+        self.unlock_slots.len() != before
+    }
+    
+    /// Check the keystore format is supported
+    fn check_format(&self) -> Result<(), String> {
+        if self.version != KEYSTORE_VERSION || self.cipher != CIPHER {
+            return Err(format!("Unsupported keystore version {} with cipher {}", self.version, self.cipher));
+        }
+        Ok(())
+    }
+    
+    /// Stretch the passphrase into the cipher key
+    fn passphrase_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+        self.check_format()?;
+        derive_key(passphrase, &decode(&self.salt)?, self.kdf)
+    }
+    
+    /// Open the key bundle with the cipher key
+    fn open_with_key(&self, key: &[u8; 32]) -> Result<KeyBundle, String> {
+        let plaintext = Zeroizing::new(decrypt_data(&decode(&self.ciphertext)?, key, &decode(&self.nonce)?)?);
+        
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Failed to parse keys: {}", e))
+    }
+    
+    /// Open the keystore as a key provider whose master key is the client encryption key
+    pub fn open_provider(&self, passphrase: &str) -> Result<LocalKeyProvider, String> {
+        let key = self.open(passphrase)?.encryption_key.to_array::<32>()?;
+        
+        Ok(LocalKeyProvider::new(*key))
+    }
+    
+    /// Serialize the keystore as JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize keystore: {}", e))
+    }
+    
+    /// Parse a keystore from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse keystore: {}", e))
+    }
+}
+
+/// Generate a 24-word BIP39 recovery phrase
+pub fn generate_mnemonic() -> Zeroizing<String> {
+    Zeroizing::new(Mnemonic::new(MnemonicType::Words24, Language::English).into_phrase())
+}
+
+/// Check a BIP39 recovery phrase and get its entropy
+pub fn mnemonic_entropy(phrase: &str) -> Result<SecretBytes, String> {
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+        .map_err(|e| format!("Invalid recovery phrase: {}", e))?;
+    
+    Ok(SecretBytes::from(mnemonic.entropy()))
+}
+
+/// Get the recovery phrase of mnemonic entropy
+pub fn mnemonic_phrase(entropy: &SecretBytes) -> Result<Zeroizing<String>, String> {
+    let mnemonic = Mnemonic::from_entropy(entropy.expose(), Language::English)
+        .map_err(|e| format!("Invalid mnemonic entropy: {}", e))?;
+    
+    Ok(Zeroizing::new(mnemonic.into_phrase()))
+}
+
+/// Derive the HD master key NFT keys are derived from, with no BIP39 passphrase
+pub fn hd_master(entropy: &SecretBytes) -> Result<HdMasterKey, String> {
+    let mnemonic = Mnemonic::from_entropy(entropy.expose(), Language::English)
+        .map_err(|e| format!("Invalid mnemonic entropy: {}", e))?;
+    
+    HdMasterKey::from_seed(Seed::new(&mnemonic, "").as_bytes())
+}
+
+/// Decode a base64 field
+fn decode(field: &str) -> Result<Vec<u8>, String> {
+    base64::decode(field).map_err(|e| format!("Failed to decode base64: {}", e))
+}
+
+/// Stretch a passphrase into a cipher key
+fn derive_key(passphrase: &str, salt: &[u8], kdf: KdfParams) -> Result<Zeroizing<[u8; 32]>, String> {
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+        .map_err(|e| format!("Invalid scrypt parameters: {}", e))?;
+    
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut *key)
+        .map_err(|e| format!("Failed to derive keystore key: {}", e))?;
+    
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Cheap parameters so tests stay fast
+    const TEST_KDF: KdfParams = KdfParams { log_n: 4, r: 8, p: 1 };
+    
+    #[test]
+    fn test_keystore_round_trip_needs_the_passphrase() {
+        let bundle = KeyBundle {
+            encryption_key: SecretBytes::from(&[7u8; 32][..]),
+            mints: BTreeMap::new(),
+            mnemonic_entropy: None,
+        };
+        
+        let keystore = Keystore::seal(&bundle, "0011223344556677", "correct horse", TEST_KDF).unwrap();
+        let parsed = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, keystore);
+        
+        assert_eq!(parsed.open("correct horse").unwrap().encryption_key, bundle.encryption_key);
+        assert!(parsed.open("battery staple").unwrap_err().contains("Wrong passphrase"));
+        
+        // Salts and nonces are fresh on every seal
+        let resealed = Keystore::seal(&bundle, "0011223344556677", "correct horse", TEST_KDF).unwrap();
+        assert_ne!(resealed.ciphertext, keystore.ciphertext);
+    }
+}
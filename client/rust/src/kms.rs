@@ -0,0 +1,286 @@
+//! AWS KMS key provider
+//!
+//! Data keys are wrapped with KMS `Encrypt`/`Decrypt` under a symmetric KMS
+//! key, so the master key never leaves KMS. Requests are signed with
+//! Signature Version 4 using the credentials the provider was built with.
+
+use ring::{digest, hmac};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use network::ProxyConfig;
+use quantum_veil::KeyProvider;
+
+/// Algorithm used when a signing key is configured
+const SIGNING_ALGORITHM: &str = "ECDSA_SHA_256";
+
+/// Key provider backed by AWS KMS
+///
+/// The `KeyProvider` trait is synchronous, so calls made from inside a
+/// Tokio runtime block the current worker and need the multi-threaded runtime.
+pub struct AwsKmsProvider {
+    /// AWS region of the key, e.g. `us-east-1`
+    region: String,
+    /// ARN or alias of the symmetric key wrapping data keys
+    key_arn: String,
+    /// Access key ID
+    access_key_id: String,
+    /// Secret access key
+    secret_access_key: String,
+    /// Session token for temporary credentials
+    session_token: Option<String>,
+    /// ARN of the asymmetric key used by `sign`, if any
+    signing_key_arn: Option<String>,
+    /// Endpoint override, e.g. for VPC endpoints
+    endpoint: Option<String>,
+    /// HTTP client
+    http_client: reqwest::Client,
+}
+
+impl AwsKmsProvider {
+    /// Create a provider for a KMS key with static credentials
+    pub fn new(region: &str, key_arn: &str, access_key_id: &str, secret_access_key: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            key_arn: key_arn.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: None,
+            signing_key_arn: None,
+            endpoint: None,
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Create a provider from `AWS_REGION` and the standard AWS credential variables
+    pub fn from_env(key_arn: &str) -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).map_err(|_| format!("Missing environment variable {}", name));
+        
+        let mut provider = Self::new(&var("AWS_REGION")?, key_arn, &var("AWS_ACCESS_KEY_ID")?, &var("AWS_SECRET_ACCESS_KEY")?);
+        provider.session_token = std::env::var("AWS_SESSION_TOKEN").ok();
+        Ok(provider)
+    }
+    
+    /// Use temporary credentials
+    pub fn with_session_token(mut self, session_token: &str) -> Self {
+        self.session_token = Some(session_token.to_string());
+        self
+    }
+    
+    /// Sign with an asymmetric ECC_NIST_P256 KMS key
+    pub fn with_signing_key(mut self, signing_key_arn: &str) -> Self {
+        self.signing_key_arn = Some(signing_key_arn.to_string());
+        self
+    }
+    
+    /// Send requests to a custom endpoint
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.trim_end_matches('/').to_string());
+        self
+    }
+    
+    /// Send requests through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+    
+    /// Call a KMS action and return its JSON response
+    async fn call(&self, action: &str, body: Value) -> Result<Value, String> {
+        let url = self.endpoint.clone().unwrap_or_else(|| format!("https://kms.{}.amazonaws.com", self.region));
+        let host = url.trim_start_matches("https://").trim_start_matches("http://").to_string();
+        
+        let payload = body.to_string();
+        let amz_date = amz_date(unix_now()?);
+        let target = format!("TrentService.{}", action);
+        
+        // Step 1: Sign the request
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+            ("x-amz-target", target),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = self.authorization(&headers, &payload, &amz_date);
+        
+        // Step 2: Send it
+        let mut request = self.http_client.post(&url).body(payload);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        
+        let response = request.header("authorization", authorization).send().await
+            .map_err(|e| format!("Failed to call KMS {}: {}", action, e))?;
+        let status = response.status();
+        let body: Value = response.json().await
+            .map_err(|e| format!("Failed to parse KMS response: {}", e))?;
+        
+        if !status.is_success() {
+            return Err(format!("KMS {} failed with {}: {}", action, status, body["message"].as_str().or(body["Message"].as_str()).unwrap_or("unknown error")));
+        }
+        
+        Ok(body)
+    }
+    
+    /// Build the SigV4 authorization header for a request
+    fn authorization(&self, headers: &[(&str, String)], payload: &str, amz_date: &str) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/kms/aws4_request", date, self.region);
+        
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers, signed_headers, sha256_hex(payload.as_bytes()),
+        );
+        
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, sha256_hex(canonical_request.as_bytes()),
+        );
+        let signature = to_hex(&hmac_sha256(&signing_key(&self.secret_access_key, date, &self.region, "kms"), string_to_sign.as_bytes()));
+        
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature,
+        )
+    }
+    
+    /// Call a KMS action and decode a base64 field of the response
+    fn call_for(&self, action: &str, body: Value, field: &str) -> Result<Vec<u8>, String> {
+        let response = block_on(self.call(action, body))??;
+        let value = response[field].as_str()
+            .ok_or_else(|| format!("KMS {} response has no {}", action, field))?;
+        
+        base64::decode(value).map_err(|e| format!("Failed to decode base64: {}", e))
+    }
+}
+
+impl KeyProvider for AwsKmsProvider {
+    fn key_id(&self) -> String {
+        self.key_arn.clone()
+    }
+    
+    fn get_key(&self) -> Result<[u8; 32], String> {
+        Err(format!("AWS KMS key {} cannot be exported", self.key_arn))
+    }
+    
+    fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, String> {
+        self.call_for("Encrypt", json!({
+            "KeyId": self.key_arn,
+            "Plaintext": base64::encode(key),
+        }), "CiphertextBlob")
+    }
+    
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+        self.call_for("Decrypt", json!({
+            "KeyId": self.key_arn,
+            "CiphertextBlob": base64::encode(wrapped),
+        }), "Plaintext")
+    }
+    
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        let signing_key_arn = self.signing_key_arn.as_ref()
+            .ok_or_else(|| "No KMS signing key configured".to_string())?;
+        
+        self.call_for("Sign", json!({
+            "KeyId": signing_key_arn,
+            "Message": base64::encode(message),
+            "MessageType": "RAW",
+            "SigningAlgorithm": SIGNING_ALGORITHM,
+        }), "Signature")
+    }
+}
+
+/// Run a future to completion from synchronous code
+fn block_on<F: Future>(future: F) -> Result<F::Output, String> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => Ok(tokio::task::block_in_place(|| handle.block_on(future))),
+        Err(_) => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("Failed to start runtime: {}", e))?;
+            Ok(runtime.block_on(future))
+        }
+    }
+}
+
+/// Derive the SigV4 signing key for a date, region, and service
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// Compute HMAC-SHA256
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), message).as_ref().to_vec()
+}
+
+/// Hash bytes with SHA-256 as lowercase hex
+fn sha256_hex(data: &[u8]) -> String {
+    to_hex(digest::digest(&digest::SHA256, data).as_ref())
+}
+
+/// Format bytes as lowercase hex
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Get the current Unix timestamp
+fn unix_now() -> Result<i64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .map_err(|e| format!("Failed to get current time: {}", e))
+}
+
+/// Format a Unix timestamp as an `x-amz-date`, e.g. `20231114T221320Z`
+fn amz_date(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    
+    // Civil date from days since the epoch, in 400-year eras
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_signing_key_matches_aws_example() {
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(to_hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+    
+    #[test]
+    fn test_amz_date_formats_utc() {
+        assert_eq!(amz_date(0), "19700101T000000Z");
+        assert_eq!(amz_date(1_700_000_000), "20231114T221320Z");
+        assert_eq!(amz_date(951_782_400), "20000229T000000Z");
+        
+        let provider = AwsKmsProvider::new("us-east-1", "arn:aws:kms:us-east-1:111122223333:key/example", "AKID", "secret");
+        assert!(provider.get_key().is_err());
+        assert!(provider.sign(b"message").unwrap_err().contains("No KMS signing key"));
+    }
+}
@@ -1,15 +1,96 @@
+pub mod access_cache;
+pub mod access_gate;
+pub mod assets;
+pub mod attestation;
 pub mod client;
+pub mod cluster;
+pub mod credentials;
+#[cfg(feature = "dev-tools")]
+pub mod dev_tools;
+pub mod dry_run;
+pub mod fpe;
+pub mod key_wrap;
+#[cfg(feature = "pkcs11")]
+pub mod hsm;
+pub mod keystore;
+pub mod kms;
+pub mod metaplex;
 pub mod models;
+pub mod nonce;
+pub mod pinning;
+pub mod pipeline;
+pub mod policy;
+pub mod portfolio;
+#[cfg(feature = "zk")]
+pub mod proofs;
+pub mod range_proofs;
+pub mod recovery;
+pub mod registry;
+pub mod relayer;
+pub mod search;
+pub mod snapshot;
+pub mod sns;
+pub mod stealth;
+pub mod transcript;
+pub mod transforms;
+pub mod unlock;
+pub mod updates;
+pub mod upgrade_authority;
+pub mod uploader;
+pub mod watermark;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 pub use client::GlitchGangPrivacyClient;
+pub use access_cache::AccessCache;
+pub use registry::AgentRegistry;
+pub use dry_run::DryRunPlan;
+pub use nonce::NonceInfo;
+pub use pipeline::ProtectPipeline;
+pub use pinning::{PinManager, PinningService, PinningServiceApi, NotificationDispatcher, PinAlert};
+pub use relayer::Relayer;
+pub use snapshot::WrapperSnapshot;
+pub use transcript::DistributionTranscript;
+pub use cluster::{Cluster, ClusterConfig, ClusterRegistry};
+pub use credentials::{CredentialStore, Capability, Operation};
+pub use policy::{ProtectionPolicy, PolicyAction};
+pub use transforms::{Generalization, DatePrecision};
+pub use fpe::{Alphabet, FormatPreserving};
+pub use portfolio::PortfolioManager;
+pub use assets::PreviewStyle;
+pub use attestation::{PrivacyAttestation, verify_attestation};
+pub use stealth::{StealthKeys, StealthMetaAddress};
+pub use kms::AwsKmsProvider;
+pub use key_wrap::ViewerKey;
+pub use unlock::{UnlockProvider, PasskeyAuthenticator, PasskeyUnlock};
+#[cfg(feature = "keychain")]
+pub use unlock::KeychainUnlock;
+pub use network::{ProxyConfig, RpcPool};
+pub use quantum_veil::{KeyProvider, LocalKeyProvider};
+pub use updates::WrapperUpdate;
+pub use upgrade_authority::UpgradeAuthority;
+#[cfg(feature = "dev-tools")]
+pub use dev_tools::{LocalCluster, TestContext};
+pub use uploader::{Uploader, ArweaveUploader, IpfsUploader, Web3StorageUploader};
 pub use models::{
     GlitchGangMetadata,
     PrivacyLevel,
     VrmData,
     PositionData,
     RotationData,
+    ScaleData,
+    BoundingBox,
     VoiceData,
     GestureData,
+    InteractionEvent,
+    InteractionKind,
+    AccessReport,
+    ProtectedAsset,
+    RevealTime,
+    ScheduledReveal,
+    EntropySource,
+    PrivacyConfig,
+    TimelineType,
 };
 
 /// Project 89: Quantum Veil Privacy System
@@ -0,0 +1,212 @@
+//! Metaplex token metadata accounts
+//!
+//! Decodes the data section of metadata accounts and builds the
+//! `UpdateMetadataAccountV2` instruction used to repoint an NFT's URI.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+/// Metaplex token metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Seed prefix of metadata accounts
+const METADATA_SEED: &[u8] = b"metadata";
+
+/// Instruction discriminator of `UpdateMetadataAccountV2`
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+
+/// Creator entry of a metadata account
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+/// Collection an NFT belongs to
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+/// Usage limits of an NFT
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Uses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+/// Metaplex metadata account, up to the fields every version has
+///
+/// Accounts are zero-filled to a fixed size, so optional fields added in
+/// later versions decode as `None` on older accounts.
+#[derive(BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct TokenMetadata {
+    /// Account type discriminator
+    pub key: u8,
+    /// Account allowed to update the metadata
+    pub update_authority: Pubkey,
+    /// NFT mint address
+    pub mint: Pubkey,
+    /// NFT name
+    pub name: String,
+    /// NFT symbol
+    pub symbol: String,
+    /// URI of the off-chain metadata JSON
+    pub uri: String,
+    /// Royalty in basis points
+    pub seller_fee_basis_points: u16,
+    /// Creators and their royalty shares
+    pub creators: Option<Vec<Creator>>,
+    /// Whether the primary sale has happened
+    pub primary_sale_happened: bool,
+    /// Whether the metadata can still be updated
+    pub is_mutable: bool,
+    /// Edition nonce
+    pub edition_nonce: Option<u8>,
+    /// Token standard
+    pub token_standard: Option<u8>,
+    /// Collection membership
+    pub collection: Option<Collection>,
+    /// Usage limits
+    pub uses: Option<Uses>,
+}
+
+impl TokenMetadata {
+    /// Decode a metadata account
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let mut metadata = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode metadata account: {}", e))?;
+        
+        // Strings are padded with NULs to their maximum size
+        for value in [&mut metadata.name, &mut metadata.symbol, &mut metadata.uri] {
+            let trimmed = value.trim_end_matches('\0').len();
+            value.truncate(trimmed);
+        }
+        
+        Ok(metadata)
+    }
+}
+
+/// Metadata fields written by `UpdateMetadataAccountV2`
+#[derive(BorshSerialize, Debug, Clone, PartialEq)]
+struct DataV2 {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    collection: Option<Collection>,
+    uses: Option<Uses>,
+}
+
+/// Arguments of `UpdateMetadataAccountV2`; `None` leaves a field unchanged
+#[derive(BorshSerialize, Debug, Clone, PartialEq)]
+struct UpdateMetadataAccountArgsV2 {
+    data: Option<DataV2>,
+    update_authority: Option<Pubkey>,
+    primary_sale_happened: Option<bool>,
+    is_mutable: Option<bool>,
+}
+
+/// Derive the metadata account address for a mint
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[METADATA_SEED, TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    ).0
+}
+
+/// Fetch and decode the metadata account of a mint
+pub fn fetch_token_metadata(rpc_client: &RpcClient, mint: &Pubkey) -> Result<TokenMetadata, String> {
+    let data = rpc_client.get_account_data(&metadata_address(mint))
+        .map_err(|e| format!("Failed to fetch metadata account for {}: {}", mint, e))?;
+    
+    TokenMetadata::unpack(&data)
+}
+
+/// Build an instruction pointing a mint's metadata at a new URI
+///
+/// All other metadata fields are written back unchanged.
+pub fn update_metadata_uri(metadata: &TokenMetadata, new_uri: &str) -> Result<Instruction, String> {
+    let args = UpdateMetadataAccountArgsV2 {
+        data: Some(DataV2 {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: new_uri.to_string(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata.creators.clone(),
+            collection: metadata.collection.clone(),
+            uses: metadata.uses.clone(),
+        }),
+        update_authority: None,
+        primary_sale_happened: None,
+        is_mutable: None,
+    };
+    
+    let mut data = vec![UPDATE_METADATA_ACCOUNT_V2];
+    data.extend(args.try_to_vec().map_err(|e| format!("Failed to serialize instruction: {}", e))?);
+    
+    Ok(Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(metadata_address(&metadata.mint), false),
+            AccountMeta::new_readonly(metadata.update_authority, true),
+        ],
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::token_metadata_account;
+    
+    #[test]
+    fn test_unpack_trims_padding() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let data = token_metadata_account(&authority, &mint, "https://example.com/699.json");
+        
+        let metadata = TokenMetadata::unpack(&data).unwrap();
+        assert_eq!(metadata.update_authority, authority);
+        assert_eq!(metadata.mint, mint);
+        assert_eq!(metadata.symbol, "GG");
+        assert_eq!(metadata.uri, "https://example.com/699.json");
+        assert!(metadata.is_mutable);
+        assert!(TokenMetadata::unpack(&data[..100]).is_err());
+    }
+    
+    #[test]
+    fn test_update_uri_keeps_other_fields() {
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let metadata = TokenMetadata::unpack(&token_metadata_account(&authority, &mint, "https://old")).unwrap();
+        
+        let instruction = update_metadata_uri(&metadata, "https://new").unwrap();
+        assert_eq!(instruction.data[0], UPDATE_METADATA_ACCOUNT_V2);
+        assert_eq!(instruction.accounts[0].pubkey, metadata_address(&mint));
+        assert!(instruction.accounts[1].is_signer);
+        
+        // Data section: Some, then the borsh-encoded fields with the new URI
+        let mut expected = vec![UPDATE_METADATA_ACCOUNT_V2, 1];
+        expected.extend(DataV2 {
+            name: "Glitch Gang #699".to_string(),
+            symbol: "GG".to_string(),
+            uri: "https://new".to_string(),
+            seller_fee_basis_points: 500,
+            creators: None,
+            collection: None,
+            uses: None,
+        }.try_to_vec().unwrap());
+        expected.extend([0, 0, 0]);
+        assert_eq!(instruction.data, expected);
+    }
+}
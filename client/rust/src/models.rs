@@ -1,6 +1,16 @@
+use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+
+use crate::fpe::FormatPreserving;
+use crate::search::SearchToken;
+
+use synchronicity_mask::{QueryStats, WrapperAccess};
+use timeline_shifter::{FractureCostEstimate, FragmentManifest, RetrievalStats};
+
+pub use quantum_veil::PrivacyConfig;
+pub use quantum_veil_types::{EntropySource, MetadataFragment, PrivacyLevel, StorageLocation, TimelineType};
 
 /// Glitch Gang NFT metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +52,192 @@ pub struct PrivateData {
     pub encrypted_attributes: Option<String>,
     pub timeline_fragments: Option<Vec<String>>,
     pub vrm_config: Option<VrmConfig>,
+    /// Encrypted assets, e.g. the original image or VRM file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_assets: Vec<ProtectedAsset>,
+    /// Attributes that reveal themselves at a scheduled time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timelocked_attributes: Vec<TimeLockedAttributes>,
+    /// Commitments to hidden numeric traits, for proving bounds on them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub numeric_commitments: Vec<NumericCommitment>,
+    /// Public attributes encrypted in place, by trait type
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub format_preserved: HashMap<String, FormatPreserving>,
+}
+
+/// Pedersen commitment to a hidden numeric trait, e.g. a power level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumericCommitment {
+    /// Trait committed to
+    pub trait_type: String,
+    /// Compressed Ristretto commitment, base64 encoded
+    pub commitment: String,
+}
+
+/// Attributes encrypted under a data key escrowed in a reveal PDA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeLockedAttributes {
+    /// Label naming the reveal, e.g. `mission`
+    pub label: String,
+    /// Reveal escrow account
+    pub reveal_account: Pubkey,
+    /// When the reveal unlocks
+    pub reveal_time: RevealTime,
+    /// Attributes sealed with the data key, base64 encoded as `[nonce: 12][ciphertext]`
+    pub encrypted_attributes: String,
+}
+
+/// Result of scheduling a reveal
+#[derive(Debug, Clone)]
+pub struct ScheduledReveal {
+    /// Reveal escrow account
+    pub reveal_account: Pubkey,
+    /// Key that unlocks the reveal, to hand to whoever claims it on time
+    pub reveal_key: [u8; 32],
+    /// Signature of the scheduling transaction
+    pub signature: String,
+}
+
+/// Pointer to an encrypted asset and its wrapped key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectedAsset {
+    /// URI of the encrypted asset
+    pub uri: String,
+    /// MIME type of the original asset
+    pub content_type: String,
+    /// Asset key sealed with the owner's encryption key, base64 encoded as `[nonce: 12][ciphertext]`
+    pub encrypted_key: String,
+    /// SHA-256 of the original asset, hex encoded
+    pub sha256: String,
+    /// URI of the degraded public preview, for images
+    pub preview_uri: Option<String>,
+    /// Asset key wrapped to each viewer it is shared with, by account, base64 encoded
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub shared_keys: HashMap<String, String>,
+}
+
+/// Result of protecting metadata
+///
+/// Holds everything needed to decrypt the metadata later and to publish its
+/// privacy config hash on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionReport {
+    /// Metadata with sensitive attributes moved into the private data section
+    pub protected_metadata: GlitchGangMetadata,
+    /// Trait types that were encrypted
+    pub encrypted_traits: Vec<String>,
+    /// Encrypted trait types published with a generalized value
+    #[serde(default)]
+    pub generalized_traits: Vec<String>,
+    /// Trait types published encrypted in place
+    #[serde(default)]
+    pub format_preserved_traits: Vec<String>,
+    /// Search tokens of the encrypted attributes, to index with `PortfolioManager::index_protection`
+    #[serde(default)]
+    pub search_tokens: Vec<SearchToken>,
+    /// Where the encrypted attributes were fractured, if a timeline shifter is configured
+    pub fragment_manifest: Option<FragmentManifest>,
+    /// Privacy config hash to publish with `update_privacy_settings`
+    pub config_hash: String,
+    /// Identifier of the encryption key, safe to store alongside the metadata
+    pub key_id: String,
+}
+
+/// What protecting metadata at one privacy level would change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelPreview {
+    /// Privacy level previewed
+    pub privacy_level: PrivacyLevel,
+    /// Trait types that would be encrypted, and removed from the public metadata unless generalized
+    pub hidden_traits: Vec<String>,
+    /// Hidden trait types whose fragments would be mixed with decoys
+    pub decoyed_traits: Vec<String>,
+    /// Trait types that would stay public
+    pub visible_traits: Vec<String>,
+    /// Hidden trait types that would be published with a generalized value
+    #[serde(default)]
+    pub generalized_traits: Vec<String>,
+    /// Size of the attributes to encrypt, in bytes
+    pub private_bytes: usize,
+    /// Estimated fragment layout and list-price cost, if anything would be fractured
+    pub fracture_plan: Option<FractureCostEstimate>,
+}
+
+/// Preview of what each privacy level would hide, from `protect_preview`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionPreview {
+    /// One preview per privacy level, from `None` to `Complete`
+    pub levels: Vec<LevelPreview>,
+}
+
+impl ProtectionPreview {
+    /// Get the preview of a privacy level
+    pub fn level(&self, privacy_level: PrivacyLevel) -> Option<&LevelPreview> {
+        self.levels.iter().find(|preview| preview.privacy_level == privacy_level)
+    }
+}
+
+/// Result of wrapping and protecting an NFT in one call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapSummary {
+    /// Created privacy wrapper account
+    pub wrapper_account: Pubkey,
+    /// URI the protected metadata was uploaded to
+    pub metadata_uri: String,
+    /// Signature of the transaction publishing the config hash
+    pub update_signature: String,
+    /// Details of the protection applied
+    pub report: ProtectionReport,
+}
+
+/// A viewer's current on-chain access grant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessGrant {
+    /// Granted account
+    pub grantee: Pubkey,
+    /// Grantee's `.sol` domain, or base58 key
+    pub display_name: String,
+    /// Access level stored on-chain
+    pub access_level: u8,
+    /// Privacy level the grant sees through
+    pub privacy_level: PrivacyLevel,
+}
+
+/// An audited access event, from the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessEvent {
+    /// Unix timestamp of the event
+    pub timestamp: u64,
+    /// Viewer involved, if the event names one
+    pub viewer: Option<String>,
+    /// Why the event happened, e.g. `owner`, `access_token`, `repeated_probe`
+    pub reason: Option<String>,
+    /// Bytes decrypted, for decryptions
+    pub bytes: Option<usize>,
+}
+
+/// Who accessed an NFT's private data, for an owner dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReport {
+    /// NFT reported on
+    pub nft_mint: Pubkey,
+    /// Start of the reported period, as a Unix timestamp
+    pub from: u64,
+    /// End of the reported period, exclusive
+    pub to: u64,
+    /// Grants currently on the wrapper
+    pub grants: Vec<AccessGrant>,
+    /// Viewers shown unmasked VRM data in the period
+    pub unmasked_views: Vec<AccessEvent>,
+    /// Decryptions of the private data in the period
+    pub decryptions: Vec<AccessEvent>,
+    /// Abusive query patterns detected in the period
+    pub anomalies: Vec<AccessEvent>,
+    /// Per-viewer mask query totals since the client started
+    pub query_stats: Vec<QueryStats>,
+    /// Reads of the NFT's fragments from storage in the period
+    pub retrievals: RetrievalStats,
 }
 
 /// VRM configuration
@@ -68,6 +264,21 @@ pub struct RotationData {
     pub w: f32,
 }
 
+/// VRM scale data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleData {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Axis-aligned bounding box around the avatar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min: PositionData,
+    pub max: PositionData,
+}
+
 /// VRM voice data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceData {
@@ -86,19 +297,45 @@ pub struct GestureData {
     pub joint_rotations: HashMap<String, RotationData>,
 }
 
+/// Kind of interaction between an avatar and a target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InteractionKind {
+    Gaze,
+    Touch,
+    Speak,
+    Gesture,
+    Trade,
+    Custom(String),
+}
+
+/// Interaction of an avatar with another avatar or object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionEvent {
+    pub target_id: String,
+    pub kind: InteractionKind,
+    pub intensity: f32,
+    pub timestamp: u64,
+}
+
 /// Combined VRM data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VrmData {
     pub position: PositionData,
     pub rotation: RotationData,
+    #[serde(default)]
+    pub scale: Option<ScaleData>,
+    #[serde(default)]
+    pub bounding_box: Option<BoundingBox>,
     pub voice: Option<VoiceData>,
     pub gestures: Vec<GestureData>,
+    #[serde(default)]
+    pub interactions: Vec<InteractionEvent>,
     pub animations: HashMap<String, f32>,
     pub custom_data: HashMap<String, serde_json::Value>,
 }
 
 /// Privacy wrapper instruction enum
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 pub enum WrapperInstruction {
     /// Create privacy wrapper for existing NFT
     CreateWrapper {
@@ -115,7 +352,7 @@ pub enum WrapperInstruction {
     /// Grant access to a specific account
     GrantAccess {
         /// Account to grant access to
-        account: String,
+        grantee: Pubkey,
         /// Access level (0-255, where 255 is full access)
         level: u8,
     },
@@ -123,124 +360,843 @@ pub enum WrapperInstruction {
     /// Revoke access
     RevokeAccess {
         /// Account to revoke access from
-        account: String,
-    },
-}
-
-/// Privacy level enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum PrivacyLevel {
-    None = 0,
-    Light = 1,
-    Medium = 2,
-    Heavy = 3,
-    Complete = 4,
-}
-
-/// Timeline types for metadata fragmentation
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum TimelineType {
-    /// Primary timeline (core metadata)
-    Primary,
-    /// Identity timeline (personal info)
-    Identity,
-    /// Activity timeline (actions and behaviors)
-    Activity,
-    /// Social timeline (relationships)
-    Social,
-    /// Financial timeline (transactions)
-    Financial,
-    /// Custom timeline
-    Custom(String),
-}
-
-/// Metadata fragment storage location
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum StorageLocation {
-    /// On-chain Solana storage
-    Onchain {
-        /// Program ID
-        program_id: String,
-        /// Account address
-        account: String,
+        grantee: Pubkey,
+    },
+    
+    /// Migrate a wrapper from an earlier layout to the current layout
+    MigrateWrapper,
+    
+    /// Register an agent name in the agent registry
+    RegisterAgent {
+        /// Agent name
+        name: String,
+        /// URI of off-chain agent metadata
+        metadata_uri: String,
+    },
+    
+    /// Update a registered agent's key and metadata
+    UpdateAgent {
+        /// URI of off-chain agent metadata
+        metadata_uri: String,
+    },
+    
+    /// Escrow a sealed data key that is revealed after a slot or timestamp
+    ScheduleReveal {
+        /// Label naming the reveal
+        label: String,
+        /// When the reveal unlocks
+        reveal_time: RevealTime,
+        /// SHA-256 of the reveal key
+        key_commitment: [u8; 32],
+        /// Data key sealed with the reveal key
+        sealed_key: Vec<u8>,
+    },
+    
+    /// Publish the reveal key of an unlocked reveal
+    ClaimReveal {
+        /// Reveal key matching the escrow commitment
+        reveal_key: [u8; 32],
+    },
+    
+    /// Set the guardians holding shares of the content key
+    SetupRecovery {
+        /// Guardians holding key shares
+        guardians: Vec<Pubkey>,
+        /// Guardian approvals needed to recover
+        threshold: u8,
+        /// Seconds the owner has to veto a request
+        challenge_period: i64,
+        /// SHA-256 of the content key
+        key_commitment: [u8; 32],
+        /// Key shares sealed to each guardian, in guardian order
+        sealed_shares: Vec<Vec<u8>>,
+    },
+    
+    /// Request or approve moving the wrapper to a new owner
+    RequestRecovery {
+        /// Account the wrapper moves to
+        new_owner: Pubkey,
+    },
+    
+    /// Cancel a pending recovery
+    VetoRecovery,
+    
+    /// Move the wrapper to the new owner once the challenge period has passed
+    CompleteRecovery,
+    
+    /// Freeze privacy settings and access grants, optionally for a duration
+    LockPrivacy {
+        /// Seconds the lock lasts, or `None` to lock indefinitely
+        duration: Option<i64>,
+    },
+    
+    /// Create a collection wrapper holding the policy its members inherit
+    CreateCollection {
+        /// Privacy config hash members inherit
+        default_policy_hash: String,
+        /// Agents trusted across the collection
+        trusted_agents: Vec<Pubkey>,
+    },
+    
+    /// Update a collection's default policy and trusted agents
+    UpdateCollection {
+        /// Privacy config hash members inherit
+        default_policy_hash: String,
+        /// Agents trusted across the collection
+        trusted_agents: Vec<Pubkey>,
+    },
+    
+    /// Add a wrapper to a collection, approved by the collection authority
+    JoinCollection {
+        /// Whether the wrapper's own policy replaces the collection default
+        override_policy: bool,
+    },
+    
+    /// Choose between the wrapper's own policy and the collection default
+    SetPolicyOverride {
+        /// Whether the wrapper's own policy replaces the collection default
+        override_policy: bool,
     },
-    /// Arweave permanent storage
-    Arweave {
-        /// Transaction ID
-        transaction_id: String,
+    
+    /// Offer viewers timed access to a wrapper for a price
+    ListReveal {
+        /// Price, access, and royalty terms
+        terms: RevealTerms,
+    },
+    
+    /// Pay into escrow for timed access under a wrapper's listing
+    PayToReveal {
+        /// Terms the buyer agreed to, which the listing must still offer
+        terms: RevealTerms,
+    },
+    
+    /// Pay an escrowed purchase out to royalty recipients and the owner
+    SettleReveal,
+    
+    /// Reclaim an unsettled payment once the settlement window has passed
+    RefundReveal,
+    
+    /// Ask the owner for access to a wrapper
+    RequestAccess {
+        /// Access level requested
+        level: u8,
+    },
+    
+    /// Grant a pending request at the level it asked for
+    ApproveAccess {
+        /// Account whose request is approved
+        requester: Pubkey,
+        /// Level the owner reviewed, which the request must still ask for
+        level: u8,
+    },
+    
+    /// Drop a pending request without granting it
+    DenyAccess {
+        /// Account whose request is denied
+        requester: Pubkey,
+    },
+    
+    /// Fail unless an account holds at least a required access level
+    CheckAccess {
+        /// Lowest access level that passes
+        required_level: u8,
+    },
+    
+    /// Create a freeze config for the program or a collection
+    InitializeFreeze {
+        /// What the config covers
+        scope: FreezeScope,
+        /// Account allowed to freeze and unfreeze
+        authority: Pubkey,
     },
-    /// IPFS decentralized storage
-    Ipfs {
-        /// IPFS CID
-        cid: String,
+    
+    /// Pause or resume settings changes under a freeze config
+    SetFreeze {
+        /// Whether settings changes are paused
+        frozen: bool,
     },
-    /// Shadow realm (custom encrypted storage)
-    ShadowRealm {
-        /// Encrypted access info
-        access_path: String,
+    
+    /// Hand a freeze config to a new authority
+    SetFreezeAuthority {
+        /// Account allowed to freeze and unfreeze from now on
+        new_authority: Pubkey,
+    },
+    
+    /// Resize a wrapper to hold a number of access entries
+    ResizeWrapper {
+        /// Number of access entries the wrapper can hold
+        capacity: u16,
     },
+    
+    /// Confirm receiving the key for an escrowed purchase
+    ConfirmReveal,
 }
 
-/// Metadata fragment with timeline association
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MetadataFragment {
-    /// Fragment ID
-    pub id: String,
-    /// Timeline this fragment belongs to
-    pub timeline: TimelineType,
-    /// Encrypted data content
-    pub data: Vec<u8>,
-    /// Hash linking to other fragments
-    pub links: Vec<String>,
-    /// Timestamp when fragment was created
-    pub timestamp: u64,
-    /// Fragment storage location
-    pub storage_location: StorageLocation,
-}
-
-/// Entropy sources for quantum-grade key generation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum EntropySource {
-    /// Recent Solana blockchain hash
-    BlockchainHash,
-    /// System time-based entropy
-    TimeEntropy,
-    /// Cosmic background radiation simulation
-    CosmicNoise,
-    /// VRM agent interaction data
-    AgentBehavior,
-}
-
-/// Synchronicity mask settings for VRM behavior obfuscation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SyncMaskConfig {
-    /// Level of noise added to position data (0.0 - 1.0)
-    pub position_noise: f32,
-    /// Level of noise added to voice data (0.0 - 1.0)
-    pub voice_noise: f32,
-    /// Level of noise added to gesture data (0.0 - 1.0)
-    pub gesture_noise: f32,
-    /// Trusted agents that can see through the mask
-    pub trusted_agents: Vec<String>,
-}
-
-/// Privacy configuration for a Glitch Gang NFT
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PrivacyConfig {
-    /// Owner's public key
-    pub owner: String,
-    /// NFT mint address
-    pub nft_mint: String,
-    /// Current encryption key (base64 encoded)
-    pub current_key: String,
-    /// Nonce for encryption (base64 encoded)
-    pub current_nonce: String,
-    /// Entropy sources used for key generation
-    pub entropy_sources: Vec<EntropySource>,
-    /// Key rotation frequency in seconds
-    pub key_rotation_frequency: u64,
-    /// Last key rotation timestamp
-    pub last_rotation: u64,
-    /// Synchronicity mask settings
-    pub sync_mask: SyncMaskConfig,
+/// Seed prefix for reveal escrow PDAs
+pub const REVEAL_SEED: &[u8] = b"reveal";
+
+/// When a scheduled reveal unlocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub enum RevealTime {
+    /// Unix timestamp, as reported by the clock sysvar
+    Timestamp(i64),
+    /// Slot number
+    Slot(u64),
+}
+
+/// Time-locked data key, mirroring the on-chain reveal escrow
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RevealEscrow {
+    /// Whether the escrow has been written
+    pub is_initialized: bool,
+    /// Wrapper the reveal belongs to
+    pub wrapper: Pubkey,
+    /// Label naming the reveal
+    pub label: String,
+    /// When the reveal unlocks
+    pub reveal_time: RevealTime,
+    /// SHA-256 of the reveal key
+    pub key_commitment: [u8; 32],
+    /// Data key sealed with the reveal key
+    pub sealed_key: Vec<u8>,
+    /// Reveal key, published once the reveal unlocks
+    pub reveal_key: Option<[u8; 32]>,
+}
+
+impl RevealEscrow {
+    /// Decode reveal escrow account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let escrow = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode reveal escrow: {}", e))?;
+        
+        if !escrow.is_initialized {
+            return Err("Reveal escrow not initialized".to_string());
+        }
+        
+        Ok(escrow)
+    }
+}
+
+/// Seed prefix for recovery PDAs
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+/// Pending request to move a wrapper to a new owner
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RecoveryRequest {
+    /// Account the wrapper moves to once recovery completes
+    pub new_owner: Pubkey,
+    /// When the first guardian requested recovery
+    pub requested_at: i64,
+    /// Guardians that have approved the request
+    pub approvals: Vec<Pubkey>,
+}
+
+/// Guardian asking for a different new owner than the pending request's
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RecoveryVote {
+    /// Guardian casting the vote
+    pub guardian: Pubkey,
+    /// Account the guardian wants the wrapper moved to
+    pub new_owner: Pubkey,
+}
+
+/// Guardian recovery settings, mirroring the on-chain recovery PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RecoveryConfig {
+    /// Whether the config has been written
+    pub is_initialized: bool,
+    /// Wrapper the config protects
+    pub wrapper: Pubkey,
+    /// Guardians holding key shares
+    pub guardians: Vec<Pubkey>,
+    /// Guardian approvals needed to recover
+    pub threshold: u8,
+    /// Seconds the owner has to veto a request
+    pub challenge_period: i64,
+    /// SHA-256 of the content key
+    pub key_commitment: [u8; 32],
+    /// Key shares sealed to each guardian, in guardian order
+    pub sealed_shares: Vec<Vec<u8>>,
+    /// Request awaiting the challenge period, if any
+    pub pending: Option<RecoveryRequest>,
+    /// Guardians backing a different new owner than the pending request's
+    pub rival_votes: Vec<RecoveryVote>,
+}
+
+impl RecoveryConfig {
+    /// Decode recovery account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let config = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode recovery config: {}", e))?;
+        
+        if !config.is_initialized {
+            return Err("Recovery config not initialized".to_string());
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Derive the recovery address for a wrapper
+pub fn find_recovery_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECOVERY_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Seed prefix for privacy lock PDAs
+pub const LOCK_SEED: &[u8] = b"lock";
+
+/// Freeze on a wrapper's privacy settings, mirroring the on-chain lock PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PrivacyLock {
+    /// Whether the lock has been written
+    pub is_initialized: bool,
+    /// Wrapper the lock belongs to
+    pub wrapper: Pubkey,
+    /// When the lock was first set, as a Unix timestamp
+    pub locked_at: i64,
+    /// When the lock expires, or `None` if it never does
+    pub locked_until: Option<i64>,
+}
+
+impl PrivacyLock {
+    /// Decode privacy lock account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let lock = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode privacy lock: {}", e))?;
+        
+        if !lock.is_initialized {
+            return Err("Privacy lock not initialized".to_string());
+        }
+        
+        Ok(lock)
+    }
+    
+    /// Check if the lock is in force at a Unix timestamp
+    pub fn is_active(&self, now: i64) -> bool {
+        match self.locked_until {
+            Some(until) => now < until,
+            None => true,
+        }
+    }
+}
+
+/// Derive the privacy lock address for a wrapper
+pub fn find_lock_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOCK_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Seed prefix for freeze config PDAs
+pub const FREEZE_SEED: &[u8] = b"freeze";
+
+/// What a freeze config covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum FreezeScope {
+    /// Every wrapper in the program
+    Program,
+    /// Wrappers in a collection, by collection wrapper address
+    Collection(Pubkey),
+}
+
+/// Emergency stop on settings changes, mirroring the on-chain freeze config PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct FreezeConfig {
+    /// Whether the config has been written
+    pub is_initialized: bool,
+    /// What the config covers
+    pub scope: FreezeScope,
+    /// Account allowed to freeze, unfreeze, and hand over the config
+    pub authority: Pubkey,
+    /// Whether settings changes are paused
+    pub frozen: bool,
+    /// When the config was last frozen or unfrozen, as a Unix timestamp
+    pub updated_at: i64,
+}
+
+impl FreezeConfig {
+    /// Decode freeze config account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let config = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode freeze config: {}", e))?;
+        
+        if !config.is_initialized {
+            return Err("Freeze config not initialized".to_string());
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Derive the freeze config address for a scope
+pub fn find_freeze_address(program_id: &Pubkey, scope: &FreezeScope) -> (Pubkey, u8) {
+    match scope {
+        FreezeScope::Program => Pubkey::find_program_address(&[FREEZE_SEED], program_id),
+        FreezeScope::Collection(collection) => {
+            Pubkey::find_program_address(&[FREEZE_SEED, collection.as_ref()], program_id)
+        }
+    }
+}
+
+/// Seed prefix for collection wrapper PDAs
+pub const COLLECTION_SEED: &[u8] = b"collection";
+
+/// Seed prefix for collection membership PDAs
+pub const MEMBERSHIP_SEED: &[u8] = b"member";
+
+/// Shared privacy policy for a collection, mirroring the on-chain collection wrapper
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct CollectionWrapper {
+    /// Whether the collection has been written
+    pub is_initialized: bool,
+    /// Collection mint address
+    pub collection_mint: Pubkey,
+    /// Update authority of the collection metadata
+    pub authority: Pubkey,
+    /// Privacy config hash members inherit
+    pub default_policy_hash: String,
+    /// Agents trusted across the collection
+    pub trusted_agents: Vec<Pubkey>,
+    /// Last update timestamp
+    pub last_updated: u64,
+}
+
+impl CollectionWrapper {
+    /// Decode collection wrapper account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let collection = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode collection wrapper: {}", e))?;
+        
+        if !collection.is_initialized {
+            return Err("Collection wrapper not initialized".to_string());
+        }
+        
+        Ok(collection)
+    }
+}
+
+/// Link from a wrapper to its collection, mirroring the on-chain membership PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct CollectionMembership {
+    /// Whether the membership has been written
+    pub is_initialized: bool,
+    /// Member wrapper
+    pub wrapper: Pubkey,
+    /// Collection wrapper PDA the wrapper belongs to
+    pub collection: Pubkey,
+    /// Whether the wrapper's own policy replaces the collection default
+    pub override_policy: bool,
+}
+
+impl CollectionMembership {
+    /// Decode collection membership account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let membership = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode collection membership: {}", e))?;
+        
+        if !membership.is_initialized {
+            return Err("Collection membership not initialized".to_string());
+        }
+        
+        Ok(membership)
+    }
+}
+
+/// Privacy policy in force for a wrapper, after collection inheritance
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectivePolicy {
+    /// Privacy config hash in force
+    pub policy_hash: String,
+    /// Agents trusted through the collection
+    pub trusted_agents: Vec<Pubkey>,
+    /// Collection mint the policy is inherited from, if any
+    pub inherited_from: Option<Pubkey>,
+}
+
+impl EffectivePolicy {
+    /// Resolve a wrapper's policy from its own settings and its collection
+    ///
+    /// Members inherit the collection's default policy and trusted agents
+    /// unless they override, in which case only their own policy applies.
+    pub fn resolve(state: &WrapperState, membership: Option<&CollectionMembership>, collection: Option<&CollectionWrapper>) -> Self {
+        match (membership, collection) {
+            (Some(membership), Some(collection)) if !membership.override_policy => Self {
+                policy_hash: collection.default_policy_hash.clone(),
+                trusted_agents: collection.trusted_agents.clone(),
+                inherited_from: Some(collection.collection_mint),
+            },
+            _ => Self {
+                policy_hash: state.privacy_config_hash.clone(),
+                trusted_agents: Vec::new(),
+                inherited_from: None,
+            },
+        }
+    }
+}
+
+/// Seed prefix for reveal listing PDAs
+pub const LISTING_SEED: &[u8] = b"listing";
+
+/// Seed prefix for reveal purchase PDAs
+pub const PURCHASE_SEED: &[u8] = b"purchase";
+
+/// Seed prefix for access request PDAs
+pub const REQUEST_SEED: &[u8] = b"request";
+
+/// Tag logged ahead of a `RevealPurchased` event
+pub const REVEAL_PURCHASED_EVENT: &[u8] = b"reveal_purchased";
+
+/// Maximum number of royalty recipients on a listing
+pub const MAX_ROYALTY_SHARES: usize = 5;
+
+/// Cut of each reveal payment owed to a royalty recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct RoyaltyShare {
+    /// Account receiving the cut
+    pub recipient: Pubkey,
+    /// Cut in basis points of the payment
+    pub basis_points: u16,
+}
+
+/// Price and access a wrapper owner offers viewers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct RevealTerms {
+    /// Lamports a viewer pays
+    pub price: u64,
+    /// Access level a payment buys
+    pub access_level: u8,
+    /// Seconds the access lasts
+    pub access_duration: i64,
+    /// Seconds the owner has to deliver the key before the buyer can reclaim the payment
+    pub settlement_window: i64,
+    /// Seconds after the settlement window in which only the buyer can act on
+    /// an unconfirmed payment, before the owner can settle it
+    pub dispute_window: i64,
+    /// Royalty cuts, paid out in order; the owner receives the rest
+    pub royalty_shares: Vec<RoyaltyShare>,
+}
+
+/// Reveal offer for a wrapper, mirroring the on-chain listing PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RevealListing {
+    /// Whether the listing has been written
+    pub is_initialized: bool,
+    /// Wrapper being offered
+    pub wrapper: Pubkey,
+    /// Terms viewers pay under
+    pub terms: RevealTerms,
+}
+
+impl RevealListing {
+    /// Decode reveal listing account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let listing = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode reveal listing: {}", e))?;
+        
+        if !listing.is_initialized {
+            return Err("Reveal listing not initialized".to_string());
+        }
+        
+        Ok(listing)
+    }
+}
+
+/// Where a reveal purchase's payment is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub enum PurchaseStatus {
+    /// Held in the purchase account
+    Escrowed,
+    /// Paid out to the owner and royalty recipients
+    Settled,
+    /// Returned to the buyer, ending the access
+    Refunded,
+    /// Held in the purchase account after the buyer confirmed receiving the key
+    Confirmed,
+}
+
+/// Access a viewer asked a wrapper's owner for, mirroring the on-chain request PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AccessRequest {
+    /// Whether the request has been written
+    pub is_initialized: bool,
+    /// Wrapper access is asked for
+    pub wrapper: Pubkey,
+    /// Account asking for access
+    pub requester: Pubkey,
+    /// Access level asked for
+    pub level: u8,
+    /// When the request was last made, as a Unix timestamp
+    pub requested_at: i64,
+}
+
+impl AccessRequest {
+    /// Size of an access request account
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 8;
+    
+    /// Offset of the wrapper in access request account data
+    pub const WRAPPER_OFFSET: usize = 1;
+    
+    /// Decode access request account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let request = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode access request: {}", e))?;
+        
+        if !request.is_initialized {
+            return Err("Access request not initialized".to_string());
+        }
+        
+        Ok(request)
+    }
+}
+
+/// Timed access a viewer paid for, mirroring the on-chain purchase PDA
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RevealPurchase {
+    /// Whether the purchase has been written
+    pub is_initialized: bool,
+    /// Wrapper paid for
+    pub wrapper: Pubkey,
+    /// Viewer who paid
+    pub buyer: Pubkey,
+    /// Lamports paid
+    pub amount: u64,
+    /// Access level bought
+    pub access_level: u8,
+    /// When the payment was made, as a Unix timestamp
+    pub paid_at: i64,
+    /// When the access ends, as a Unix timestamp
+    pub expires_at: i64,
+    /// When the buyer can reclaim an unsettled payment, as a Unix timestamp
+    pub refundable_at: i64,
+    /// When the owner can settle an unconfirmed payment, as a Unix timestamp
+    pub settleable_at: i64,
+    /// Royalty cuts owed from the payment
+    pub royalty_shares: Vec<RoyaltyShare>,
+    /// Where the payment is
+    pub status: PurchaseStatus,
+}
+
+impl RevealPurchase {
+    /// Size of a reveal purchase account
+    pub const LEN: usize = 1 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + (4 + (32 + 2) * MAX_ROYALTY_SHARES) + 1;
+    
+    /// Offset of the wrapper in reveal purchase account data
+    pub const WRAPPER_OFFSET: usize = 1;
+    
+    /// Decode reveal purchase account data
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        let purchase = Self::deserialize(&mut &data[..])
+            .map_err(|e| format!("Failed to decode reveal purchase: {}", e))?;
+        
+        if !purchase.is_initialized {
+            return Err("Reveal purchase not initialized".to_string());
+        }
+        
+        Ok(purchase)
+    }
+    
+    /// Get the access level the purchase grants at a Unix timestamp
+    pub fn level_at(&self, now: i64) -> u8 {
+        if self.status == PurchaseStatus::Refunded || now >= self.expires_at {
+            0
+        } else {
+            self.access_level
+        }
+    }
+}
+
+/// Event logged when a viewer pays to reveal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+pub struct RevealPurchased {
+    /// Wrapper paid for
+    pub wrapper: Pubkey,
+    /// Viewer to deliver the key to
+    pub buyer: Pubkey,
+    /// Purchase account holding the payment
+    pub purchase: Pubkey,
+    /// Access level bought
+    pub access_level: u8,
+    /// When the access ends, as a Unix timestamp
+    pub expires_at: i64,
+    /// Lamports paid
+    pub amount: u64,
+}
+
+impl RevealPurchased {
+    /// Decode the reveal events in a transaction's log messages
+    ///
+    /// Events are logged as `Program data:` lines holding the base64 event
+    /// tag followed by the base64 Borsh-encoded event.
+    pub fn from_logs(logs: &[String]) -> Vec<Self> {
+        logs.iter()
+            .filter_map(|line| line.strip_prefix("Program data: "))
+            .filter_map(|fields| {
+                let mut fields = fields.split_whitespace().map(base64::decode);
+                match (fields.next(), fields.next()) {
+                    (Some(Ok(tag)), Some(Ok(event))) if tag == REVEAL_PURCHASED_EVENT => Self::try_from_slice(&event).ok(),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Derive the reveal listing address for a wrapper
+pub fn find_listing_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LISTING_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Derive the reveal purchase address for a wrapper and buyer
+pub fn find_purchase_address(program_id: &Pubkey, wrapper: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PURCHASE_SEED, wrapper.as_ref(), buyer.as_ref()], program_id)
+}
+
+/// Derive the access request address for a wrapper and requester
+pub fn find_request_address(program_id: &Pubkey, wrapper: &Pubkey, requester: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REQUEST_SEED, wrapper.as_ref(), requester.as_ref()], program_id)
+}
+
+/// Derive the collection wrapper address for a collection mint
+pub fn find_collection_address(program_id: &Pubkey, collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLECTION_SEED, collection_mint.as_ref()], program_id)
+}
+
+/// Derive the collection membership address for a wrapper
+pub fn find_membership_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MEMBERSHIP_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Derive the reveal escrow address for a wrapper and label
+pub fn find_reveal_address(program_id: &Pubkey, wrapper: &Pubkey, label: &str) -> (Pubkey, u8) {
+    let label_hash = hashv(&[label.as_bytes()]);
+    Pubkey::find_program_address(&[REVEAL_SEED, wrapper.as_ref(), label_hash.as_ref()], program_id)
+}
+
+/// Seed prefix for agent record PDAs
+pub const AGENT_SEED: &[u8] = b"agent";
+
+/// Registered agent identity, mirroring the on-chain agent record
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AgentRecord {
+    /// Whether the record has been written
+    pub is_initialized: bool,
+    /// Agent name, e.g. `agent1.glitch.gang`
+    pub name: String,
+    /// Key the agent signs and is granted access with
+    pub agent: Pubkey,
+    /// Account allowed to update the record
+    pub authority: Pubkey,
+    /// URI of off-chain agent metadata
+    pub metadata_uri: String,
+    /// Registration timestamp
+    pub registered_at: u64,
+}
+
+/// Derive the agent record address for a name
+pub fn find_agent_address(program_id: &Pubkey, name: &str) -> (Pubkey, u8) {
+    let name_hash = hashv(&[name.as_bytes()]);
+    Pubkey::find_program_address(&[AGENT_SEED, name_hash.as_ref()], program_id)
+}
+
+/// Seed for deriving grantee keys from legacy string agent IDs
+pub const LEGACY_AGENT_SEED: &[u8] = b"privacy-wrapper/legacy-agent";
+
+/// Map a legacy string agent ID to the grantee key used on-chain
+///
+/// Matches the wrapper program's migration: base58 public keys map to
+/// themselves, any other ID to the SHA-256 of `LEGACY_AGENT_SEED || id`.
+pub fn legacy_agent_pubkey(agent_id: &str) -> Pubkey {
+    agent_id.parse().unwrap_or_else(|_| {
+        Pubkey::new_from_array(hashv(&[LEGACY_AGENT_SEED, agent_id.as_bytes()]).to_bytes())
+    })
+}
+
+/// Size of the wrapper account header
+pub const WRAPPER_HEADER_LEN: usize = 172;
+
+/// Size of a wrapper access entry
+pub const WRAPPER_ENTRY_LEN: usize = 33;
+
+/// Offset of the NFT mint in the wrapper account header
+pub const WRAPPER_MINT_OFFSET: usize = 4;
+
+/// Offset of the owner in the wrapper account header
+pub const WRAPPER_OWNER_OFFSET: usize = 36;
+
+/// Privacy wrapper account, decoded from the current on-chain layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrapperState {
+    /// Original NFT mint address
+    pub original_nft_mint: Pubkey,
+    /// Owner of the NFT
+    pub owner: Pubkey,
+    /// Privacy config hash
+    pub privacy_config_hash: String,
+    /// Access levels granted, by grantee
+    pub access_controls: HashMap<Pubkey, u8>,
+    /// Last update timestamp
+    pub last_updated: u64,
+}
+
+impl WrapperState {
+    /// Decode wrapper account data
+    ///
+    /// Wrappers still on the string-keyed layout must be migrated first.
+    pub fn unpack(data: &[u8]) -> Result<Self, String> {
+        if data.len() < WRAPPER_HEADER_LEN {
+            return Err("Wrapper account too short".to_string());
+        }
+        
+        match data[0] {
+            0 => return Err("Wrapper account not initialized".to_string()),
+            2 => {},
+            version => return Err(format!("Unsupported wrapper version {}, migrate it first", version)),
+        }
+        
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let config_hash_len = (data[1] as usize).min(96);
+        let entry_count = u16::from_le_bytes([data[2], data[3]]) as usize;
+        
+        let slots = data[WRAPPER_HEADER_LEN..]
+            .chunks_exact(WRAPPER_ENTRY_LEN)
+            .map(|entry| (Pubkey::new_from_array(entry[..32].try_into().unwrap()), entry[32]))
+            .collect::<Vec<_>>();
+        if slots.len() < entry_count {
+            return Err("Wrapper account truncated".to_string());
+        }
+        let entries = slots[..entry_count].iter().copied().collect::<HashMap<_, _>>();
+        
+        Ok(Self {
+            original_nft_mint: pubkey_at(4),
+            owner: pubkey_at(36),
+            last_updated: u64::from_le_bytes(data[68..76].try_into().unwrap()),
+            privacy_config_hash: String::from_utf8(data[76..76 + config_hash_len].to_vec())
+                .map_err(|e| format!("Invalid privacy config hash: {}", e))?,
+            access_controls: entries,
+        })
+    }
+}
+
+/// Wrapper state together with its privacy lock
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrapperInspection {
+    /// Decoded wrapper account
+    pub state: WrapperState,
+    /// Privacy lock, if the wrapper has ever been locked
+    pub lock: Option<PrivacyLock>,
+}
+
+impl WrapperInspection {
+    /// Check if privacy settings are frozen at a Unix timestamp
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.lock.as_ref().map_or(false, |lock| lock.is_active(now))
+    }
+}
+
+impl WrapperAccess for WrapperState {
+    fn owner(&self) -> Pubkey {
+        self.owner
+    }
+    
+    fn access_level(&self, viewer: &Pubkey) -> u8 {
+        self.access_controls.get(viewer).copied().unwrap_or(0)
+    }
 }
@@ -0,0 +1,101 @@
+//! Durable nonces
+//!
+//! A transaction signed against a recent blockhash expires after about two
+//! minutes. One built against a durable nonce instead stays valid until the
+//! nonce is advanced, so a cold-wallet owner can sign it hours later. The
+//! transaction's first instruction advances the nonce, which makes it
+//! single-use.
+
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    nonce::State,
+    pubkey::Pubkey,
+    system_instruction,
+    transaction::Transaction,
+};
+
+/// Blockhash and authority stored in a nonce account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceInfo {
+    /// Blockhash transactions built against the nonce use
+    pub blockhash: Hash,
+    /// Account that must sign to advance the nonce
+    pub authority: Pubkey,
+}
+
+/// Fetch the current blockhash and authority of a nonce account
+pub fn fetch_nonce(rpc_client: &RpcClient, nonce_account: &Pubkey) -> Result<NonceInfo, String> {
+    let account = nonce_utils::get_account_with_commitment(rpc_client, nonce_account, rpc_client.commitment())
+        .map_err(|e| format!("Failed to fetch nonce account {}: {}", nonce_account, e))?;
+    let data = nonce_utils::data_from_account(&account)
+        .map_err(|e| format!("Failed to read nonce account {}: {}", nonce_account, e))?;
+    
+    Ok(NonceInfo {
+        blockhash: data.blockhash(),
+        authority: data.authority,
+    })
+}
+
+/// Create the instructions funding and initializing a nonce account
+pub fn create_nonce_account_instructions(
+    rpc_client: &RpcClient,
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Vec<Instruction>, String> {
+    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(State::size())
+        .map_err(|e| format!("Failed to get nonce account rent: {}", e))?;
+    
+    Ok(system_instruction::create_nonce_account(payer, nonce_account, authority, lamports))
+}
+
+/// Build an unsigned transaction against a durable nonce
+///
+/// Signers sign it later with `Transaction::try_sign` or `partial_sign`
+/// against `nonce.blockhash`.
+pub fn build_with_nonce(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce: &NonceInfo,
+) -> Transaction {
+    let message = Message::new_with_nonce(instructions.to_vec(), Some(payer), nonce_account, &nonce.authority);
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.message.recent_blockhash = nonce.blockhash;
+    transaction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+    
+    #[test]
+    fn test_nonce_transaction_signs_later_and_advances_first() {
+        let owner = Keypair::new();
+        let nonce_account = Pubkey::new_unique();
+        let nonce = NonceInfo {
+            blockhash: Hash::new_unique(),
+            authority: owner.pubkey(),
+        };
+        let instruction = system_instruction::transfer(&owner.pubkey(), &Pubkey::new_unique(), 1);
+        
+        let mut transaction = build_with_nonce(&[instruction], &owner.pubkey(), &nonce_account, &nonce);
+        assert!(!transaction.is_signed());
+        assert_eq!(transaction.message.recent_blockhash, nonce.blockhash);
+        
+        // The advance instruction comes first, against the nonce account
+        let advance = &transaction.message.instructions[0];
+        let program = transaction.message.account_keys[advance.program_id_index as usize];
+        assert_eq!(program, solana_sdk::system_program::id());
+        assert_eq!(transaction.message.account_keys[advance.accounts[0] as usize], nonce_account);
+        
+        // Signing offline uses the stored nonce instead of a fresh blockhash
+        transaction.try_sign(&[&owner], nonce.blockhash).unwrap();
+        assert!(transaction.verify().is_ok());
+    }
+}
@@ -0,0 +1,428 @@
+//! Keeping IPFS fragments pinned
+//!
+//! IPFS nodes drop content nobody pins, so a fragment stored on IPFS
+//! disappears unless a pinning service holds it. A `PinManager` tracks the
+//! IPFS fragments of the manifests it is given, makes sure every configured
+//! pinning service has them pinned, and checks on a schedule that they can
+//! still be fetched from a gateway. Fragments that can't be fetched are
+//! reported through a `NotificationDispatcher`, which the host application
+//! connects to whatever reaches its operators.
+
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use network::ProxyConfig;
+use timeline_shifter::FragmentManifest;
+
+use crate::models::StorageLocation;
+
+/// Service keeping content pinned on IPFS
+#[async_trait]
+pub trait PinningService: Send + Sync {
+    /// Name of the service, for logs
+    fn name(&self) -> &str;
+    
+    /// Check if the service has a CID pinned or queued for pinning
+    async fn is_pinned(&self, cid: &str) -> Result<bool, String>;
+    
+    /// Ask the service to pin a CID under a name
+    async fn pin(&self, cid: &str, name: &str) -> Result<(), String>;
+}
+
+/// Pinning service speaking the IPFS Pinning Service API, as Pinata and web3.storage do
+pub struct PinningServiceApi {
+    /// Name of the service, for logs
+    pub name: String,
+    /// API endpoint URL
+    pub endpoint: String,
+    /// Bearer token
+    pub access_token: String,
+    /// HTTP client requests are sent with
+    pub http_client: reqwest::Client,
+}
+
+impl PinningServiceApi {
+    /// Create a client for a Pinning Service API endpoint
+    pub fn new(name: &str, endpoint: &str, access_token: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            access_token: access_token.to_string(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Pin with Pinata, authenticating with a JWT
+    pub fn pinata(jwt: &str) -> Self {
+        Self::new("pinata", "https://api.pinata.cloud/psa", jwt)
+    }
+    
+    /// Pin with web3.storage, authenticating with an API token
+    pub fn web3_storage(api_token: &str) -> Self {
+        Self::new("web3.storage", "https://api.web3.storage", api_token)
+    }
+    
+    /// Send requests through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+}
+
+/// Response of the Pinning Service API `pins` listing
+#[derive(Deserialize)]
+struct PinListResponse {
+    count: u64,
+}
+
+#[async_trait]
+impl PinningService for PinningServiceApi {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    async fn is_pinned(&self, cid: &str) -> Result<bool, String> {
+        let response = self.http_client
+            .get(format!("{}/pins", self.endpoint))
+            .query(&[("cid", cid), ("status", "queued,pinning,pinned")])
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to list pins on {}: {}", self.name, e))?
+            .error_for_status()
+            .map_err(|e| format!("Pin listing rejected by {}: {}", self.name, e))?;
+        
+        let pins: PinListResponse = response.json().await
+            .map_err(|e| format!("Failed to parse pin listing from {}: {}", self.name, e))?;
+        Ok(pins.count > 0)
+    }
+    
+    async fn pin(&self, cid: &str, name: &str) -> Result<(), String> {
+        self.http_client
+            .post(format!("{}/pins", self.endpoint))
+            .bearer_auth(&self.access_token)
+            .json(&serde_json::json!({ "cid": cid, "name": name }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to pin on {}: {}", self.name, e))?
+            .error_for_status()
+            .map_err(|e| format!("Pin rejected by {}: {}", self.name, e))?;
+        
+        Ok(())
+    }
+}
+
+/// Alert that a tracked fragment can no longer be fetched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinAlert {
+    /// NFT the fragment belongs to
+    pub nft_mint: String,
+    /// Fragment ID
+    pub fragment_id: String,
+    /// IPFS CID of the fragment
+    pub cid: String,
+    /// Why the fragment is considered gone
+    pub reason: String,
+    /// When the check failed, as a Unix timestamp
+    pub detected_at: i64,
+}
+
+/// Delivers alerts to operators, e.g. by email, chat or pager
+#[async_trait]
+pub trait NotificationDispatcher: Send + Sync {
+    /// Deliver an alert about a missing fragment
+    async fn dispatch(&self, alert: &PinAlert) -> Result<(), String>;
+}
+
+/// IPFS fragment tracked by a `PinManager`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedFragment {
+    /// NFT the fragment belongs to
+    pub nft_mint: String,
+    /// IPFS CID of the fragment
+    pub cid: String,
+    /// When the fragment was last checked, as a Unix timestamp
+    pub last_checked: Option<i64>,
+    /// Whether the gateway served the fragment at the last check
+    pub available: bool,
+}
+
+/// Outcome of checking every tracked fragment once
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinReport {
+    /// Fragments checked
+    pub checked: usize,
+    /// Pins requested from services that didn't have a fragment pinned
+    pub repinned: usize,
+    /// Alerts raised for fragments the gateway couldn't serve
+    pub alerts: Vec<PinAlert>,
+}
+
+/// Background service keeping tracked IPFS fragments pinned and available
+///
+/// Clones share the tracked fragments.
+#[derive(Clone)]
+pub struct PinManager {
+    /// Gateway availability is checked through
+    gateway: String,
+    /// Services every fragment is kept pinned on
+    services: Vec<Arc<dyn PinningService>>,
+    /// Where alerts are sent, if anywhere
+    dispatcher: Option<Arc<dyn NotificationDispatcher>>,
+    /// Time between checks when spawned
+    interval: Duration,
+    /// HTTP client gateway checks are sent with
+    http_client: reqwest::Client,
+    /// Tracked fragments by fragment ID
+    fragments: Arc<RwLock<BTreeMap<String, TrackedFragment>>>,
+}
+
+impl PinManager {
+    /// Create a manager checking availability through a gateway, e.g. `https://ipfs.io`
+    pub fn new(gateway: &str) -> Self {
+        Self {
+            gateway: gateway.trim_end_matches('/').to_string(),
+            services: Vec::new(),
+            dispatcher: None,
+            interval: Duration::from_secs(6 * 60 * 60),
+            http_client: reqwest::Client::new(),
+            fragments: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+    
+    /// Keep fragments pinned on a service, in addition to any already added
+    pub fn with_service(mut self, service: Arc<dyn PinningService>) -> Self {
+        self.services.push(service);
+        self
+    }
+    
+    /// Send alerts about missing fragments through a dispatcher
+    pub fn with_dispatcher(mut self, dispatcher: Arc<dyn NotificationDispatcher>) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+    
+    /// Check every `interval` when spawned, instead of every six hours
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    
+    /// Send gateway checks through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+    
+    /// Track the IPFS fragments of a manifest, decoys included, returning how many there are
+    ///
+    /// Fragments are found through the manifest's storage receipts.
+    pub fn track_manifest(&self, manifest: &FragmentManifest) -> usize {
+        let mut fragments = self.fragments.write().unwrap();
+        let mut tracked = 0;
+        
+        for receipt in &manifest.receipts {
+            if let StorageLocation::Ipfs { cid } = &receipt.location {
+                fragments.entry(receipt.fragment_id.clone()).or_insert_with(|| TrackedFragment {
+                    nft_mint: manifest.nft_mint.clone(),
+                    cid: cid.clone(),
+                    last_checked: None,
+                    available: true,
+                });
+                tracked += 1;
+            }
+        }
+        
+        tracked
+    }
+    
+    /// Stop tracking a manifest's fragments, e.g. after they drifted to new ones
+    pub fn untrack_manifest(&self, manifest: &FragmentManifest) {
+        let mut fragments = self.fragments.write().unwrap();
+        for id in &manifest.fragment_ids {
+            fragments.remove(id);
+        }
+    }
+    
+    /// Get the tracked fragments by fragment ID
+    pub fn tracked(&self) -> BTreeMap<String, TrackedFragment> {
+        self.fragments.read().unwrap().clone()
+    }
+    
+    /// Re-pin and check every tracked fragment once
+    ///
+    /// Service failures are logged and retried at the next check; only
+    /// fragments the gateway can't serve raise alerts.
+    pub async fn check(&self) -> PinReport {
+        let mut report = PinReport::default();
+        let tracked = self.tracked();
+        
+        for (fragment_id, fragment) in tracked {
+            for service in &self.services {
+                match service.is_pinned(&fragment.cid).await {
+                    Ok(true) => {},
+                    Ok(false) => match service.pin(&fragment.cid, &fragment_id).await {
+                        Ok(()) => {
+                            log::info!("Re-pinned fragment {} on {}", fragment_id, service.name());
+                            report.repinned += 1;
+                        },
+                        Err(e) => log::warn!("Failed to re-pin fragment {}: {}", fragment_id, e),
+                    },
+                    Err(e) => log::warn!("Failed to check pin of fragment {}: {}", fragment_id, e),
+                }
+            }
+            
+            let checked_at = now();
+            let availability = self.fetchable(&fragment.cid).await;
+            if let Some(record) = self.fragments.write().unwrap().get_mut(&fragment_id) {
+                record.last_checked = Some(checked_at);
+                record.available = availability.is_ok();
+            }
+            report.checked += 1;
+            
+            if let Err(reason) = availability {
+                let alert = PinAlert {
+                    nft_mint: fragment.nft_mint.clone(),
+                    fragment_id: fragment_id.clone(),
+                    cid: fragment.cid.clone(),
+                    reason,
+                    detected_at: checked_at,
+                };
+                log::warn!("Fragment {} of {} is unavailable: {}", fragment_id, alert.nft_mint, alert.reason);
+                
+                if let Some(dispatcher) = &self.dispatcher {
+                    if let Err(e) = dispatcher.dispatch(&alert).await {
+                        log::warn!("Failed to dispatch alert for fragment {}: {}", fragment_id, e);
+                    }
+                }
+                report.alerts.push(alert);
+            }
+        }
+        
+        report
+    }
+    
+    /// Check tracked fragments every interval in the background
+    pub fn spawn(&self) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(manager.interval);
+            loop {
+                ticker.tick().await;
+                let report = manager.check().await;
+                log::info!(
+                    "Checked {} pinned fragments: {} re-pinned, {} unavailable",
+                    report.checked,
+                    report.repinned,
+                    report.alerts.len(),
+                );
+            }
+        })
+    }
+    
+    /// Check the gateway can serve a CID
+    async fn fetchable(&self, cid: &str) -> Result<(), String> {
+        self.http_client
+            .head(format!("{}/ipfs/{}", self.gateway, cid))
+            .send()
+            .await
+            .map_err(|e| format!("Gateway unreachable: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Gateway could not serve fragment: {}", e))?;
+        
+        Ok(())
+    }
+}
+
+/// Get the current Unix timestamp
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use std::sync::Mutex;
+    use timeline_shifter::FragmentReceipt;
+    use crate::models::TimelineType;
+    
+    /// Dispatcher collecting alerts
+    #[derive(Default)]
+    struct CollectingDispatcher(Mutex<Vec<PinAlert>>);
+    
+    #[async_trait]
+    impl NotificationDispatcher for CollectingDispatcher {
+        async fn dispatch(&self, alert: &PinAlert) -> Result<(), String> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+    
+    fn receipt(fragment_id: &str, location: StorageLocation) -> FragmentReceipt {
+        FragmentReceipt {
+            fragment_id: fragment_id.to_string(),
+            timeline: TimelineType::Social,
+            sha256: "00".repeat(32),
+            location,
+            receipt: fragment_id.to_string(),
+            stored_at: 0,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_pin_manager_repins_and_alerts_on_missing_fragments() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/pins")
+            .match_query(Matcher::UrlEncoded("cid".into(), "bafyPinned".into()))
+            .match_header("authorization", "Bearer psa-token")
+            .with_body(r#"{"count":1,"results":[]}"#)
+            .create_async()
+            .await;
+        server.mock("GET", "/pins")
+            .match_query(Matcher::UrlEncoded("cid".into(), "bafyGone".into()))
+            .with_body(r#"{"count":0,"results":[]}"#)
+            .create_async()
+            .await;
+        let pin = server.mock("POST", "/pins")
+            .match_body(Matcher::PartialJson(serde_json::json!({ "cid": "bafyGone" })))
+            .with_status(202)
+            .with_body(r#"{"requestid":"1","status":"queued"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server.mock("HEAD", "/ipfs/bafyPinned").create_async().await;
+        server.mock("HEAD", "/ipfs/bafyGone").with_status(504).create_async().await;
+        
+        let mut manifest = FragmentManifest::new("mint", vec!["pinned".into(), "gone".into(), "onchain".into()]);
+        manifest.receipts = vec![
+            receipt("pinned", StorageLocation::Ipfs { cid: "bafyPinned".into() }),
+            receipt("gone", StorageLocation::Ipfs { cid: "bafyGone".into() }),
+            receipt("onchain", StorageLocation::Arweave { transaction_id: "tx".into() }),
+        ];
+        
+        let dispatcher = Arc::new(CollectingDispatcher::default());
+        let manager = PinManager::new(&server.url())
+            .with_service(Arc::new(PinningServiceApi::new("psa", &server.url(), "psa-token")))
+            .with_dispatcher(dispatcher.clone());
+        assert_eq!(manager.track_manifest(&manifest), 2);
+        
+        let report = manager.check().await;
+        pin.assert_async().await;
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.repinned, 1);
+        assert_eq!(report.alerts.len(), 1);
+        assert_eq!(report.alerts[0].fragment_id, "gone");
+        assert_eq!(*dispatcher.0.lock().unwrap(), report.alerts);
+        
+        let tracked = manager.tracked();
+        assert!(tracked["pinned"].available);
+        assert!(!tracked["gone"].available);
+        
+        manager.untrack_manifest(&manifest);
+        assert!(manager.tracked().is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Streamed protection
+//!
+//! Protecting an indexer's archive one document at a time is too slow, and
+//! collecting it first doesn't fit in memory. A `ProtectPipeline` pulls
+//! documents from a stream, protects a bounded number at once and yields
+//! each report as it completes. It only pulls another document when the
+//! consumer takes a report, so a slow consumer slows the pipeline down
+//! instead of piling up reports.
+
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::client::GlitchGangPrivacyClient;
+use crate::models::{GlitchGangMetadata, PrivacyLevel, ProtectionReport};
+use crate::policy::ProtectionPolicy;
+
+/// Default number of documents protected at once
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+/// Outcome of protecting one streamed document
+#[derive(Debug)]
+pub struct StreamedReport {
+    /// Position of the document in the input stream
+    pub index: u64,
+    /// Protection report, or why the document couldn't be read or protected
+    pub result: Result<ProtectionReport, String>,
+}
+
+/// Protects a stream of metadata documents with bounded concurrency
+pub struct ProtectPipeline {
+    /// Client the documents are protected with
+    client: GlitchGangPrivacyClient,
+    /// Privacy level applied to every document
+    privacy_level: PrivacyLevel,
+    /// Policy applied to every document
+    policy: Arc<ProtectionPolicy>,
+    /// Maximum number of documents in flight
+    concurrency: usize,
+    /// Whether reports are yielded in input order
+    ordered: bool,
+    /// Whether fractured fragments stay in the client's fragment cache
+    keep_cached: bool,
+}
+
+impl ProtectPipeline {
+    /// Create a pipeline protecting documents at `privacy_level` with the default policy
+    pub fn new(client: &GlitchGangPrivacyClient, privacy_level: PrivacyLevel) -> Self {
+        Self {
+            client: client.clone(),
+            privacy_level,
+            policy: Arc::new(ProtectionPolicy::default()),
+            concurrency: DEFAULT_CONCURRENCY,
+            ordered: false,
+            keep_cached: false,
+        }
+    }
+    
+    /// Protect documents under a custom policy
+    pub fn with_policy(mut self, policy: ProtectionPolicy) -> Self {
+        self.policy = Arc::new(policy);
+        self
+    }
+    
+    /// Set how many documents are protected at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+    
+    /// Yield reports in input order, holding finished reports behind a slow document
+    pub fn with_ordered(mut self) -> Self {
+        self.ordered = true;
+        self
+    }
+    
+    /// Keep fractured fragments in the client's cache
+    ///
+    /// By default they are evicted once a document is protected, so memory
+    /// stays bounded however many documents pass through.
+    pub fn with_cached_fragments(mut self) -> Self {
+        self.keep_cached = true;
+        self
+    }
+    
+    /// Protect each document from `documents`, yielding reports as they complete
+    ///
+    /// Documents that fail to read or protect yield an error report and the
+    /// pipeline moves on to the next one.
+    pub fn protect<S>(&self, documents: S) -> BoxStream<'static, StreamedReport>
+    where
+        S: Stream<Item = Result<GlitchGangMetadata, String>> + Send + 'static,
+    {
+        let client = self.client.clone();
+        let policy = self.policy.clone();
+        let privacy_level = self.privacy_level;
+        let keep_cached = self.keep_cached;
+        
+        let jobs = documents.enumerate().map(move |(index, document)| {
+            let client = client.clone();
+            let policy = policy.clone();
+            async move {
+                let result = match document {
+                    Ok(metadata) => client.protect_metadata_with_policy(&metadata, &policy, privacy_level).await,
+                    Err(e) => Err(e),
+                };
+                
+                if let Ok(ProtectionReport { fragment_manifest: Some(manifest), .. }) = &result {
+                    if !keep_cached {
+                        client.evict_cached_fragments(manifest);
+                    }
+                }
+                StreamedReport { index: index as u64, result }
+            }
+        });
+        
+        match self.ordered {
+            true => jobs.buffered(self.concurrency).boxed(),
+            false => jobs.buffer_unordered(self.concurrency).boxed(),
+        }
+    }
+}
+
+/// Read metadata documents from JSON lines, e.g. an archive exported by an indexer
+///
+/// Blank lines are skipped. The stream ends after the first read error.
+pub fn json_lines<R>(reader: R) -> impl Stream<Item = Result<GlitchGangMetadata, String>> + Send + 'static
+where
+    R: AsyncBufRead + Unpin + Send + 'static,
+{
+    stream::unfold(Some(reader.lines()), |lines| async move {
+        let mut lines = lines?;
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => {
+                    let document = serde_json::from_str(&line)
+                        .map_err(|e| format!("Failed to parse metadata: {}", e));
+                    return Some((document, Some(lines)));
+                },
+                Ok(None) => return None,
+                Err(e) => return Some((Err(format!("Failed to read metadata: {}", e)), None)),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use timeline_shifter::InMemoryAdapter;
+    use crate::test_utils::{MockRpc, memory_timeline_shifter, sample_metadata};
+    
+    #[tokio::test]
+    async fn test_pipeline_pulls_only_what_it_can_protect() {
+        let adapter = InMemoryAdapter::new();
+        let client = MockRpc::new()
+            .privacy_client(Keypair::new())
+            .with_timeline_shifter(memory_timeline_shifter(&adapter));
+        
+        let mut lines = String::new();
+        for i in 0..40 {
+            let mut metadata = sample_metadata();
+            metadata.name = format!("Glitch Gang #{}", i);
+            lines.push_str(&serde_json::to_string(&metadata).unwrap());
+            lines.push('\n');
+        }
+        lines.push_str("not json\n");
+        
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = pulled.clone();
+        let documents = json_lines(std::io::Cursor::new(lines.into_bytes()))
+            .inspect(move |_| { counter.fetch_add(1, Ordering::SeqCst); });
+        let mut reports = ProtectPipeline::new(&client, PrivacyLevel::Medium)
+            .with_concurrency(4)
+            .with_ordered()
+            .protect(documents);
+        
+        // Backpressure: nothing beyond the in-flight window is read ahead
+        let first = reports.next().await.unwrap();
+        assert_eq!(first.index, 0);
+        assert!(pulled.load(Ordering::SeqCst) <= 4);
+        
+        let rest: Vec<_> = reports.collect().await;
+        assert_eq!(rest.len(), 40);
+        assert!(rest.iter().enumerate().all(|(i, report)| report.index == i as u64 + 1));
+        assert!(rest[..39].iter().all(|report| report.result.is_ok()));
+        assert!(rest[39].result.as_ref().unwrap_err().contains("Failed to parse"));
+        
+        // Fragments stay in storage after leaving the cache
+        let manifest = first.result.unwrap().fragment_manifest.unwrap();
+        assert!(client.missing_fragments(&manifest).await.unwrap().is_empty());
+    }
+}
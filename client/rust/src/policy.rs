@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use crate::fpe::{self, FormatPreserving, FpeCipher};
+use crate::models::{Attribute, PrivacyLevel, TimelineType};
+use crate::transforms::Generalization;
+
+use timeline_shifter::{FractureOptions, SectionMapping};
+
+/// What a policy does with a trait at one privacy level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyAction<'a> {
+    /// Leave the trait public
+    Public,
+    /// Publish a generalized value and encrypt the exact one
+    Generalize(&'a Generalization),
+    /// Encrypt the value in place, keeping its length and charset
+    FormatPreserve(&'a FormatPreserving),
+    /// Remove the trait from the public metadata and encrypt it
+    Encrypt,
+}
+
+/// Which attributes to encrypt at each privacy level, and how to fracture them
+#[derive(Debug, Clone)]
+pub struct ProtectionPolicy {
+    /// Lowest privacy level at which each trait type is encrypted
+    pub trait_levels: HashMap<String, PrivacyLevel>,
+    /// Lowest privacy level at which each trait type is generalized, and how
+    pub generalizations: HashMap<String, (PrivacyLevel, Generalization)>,
+    /// Lowest privacy level at which each trait type is encrypted in place, and how
+    pub format_preserving: HashMap<String, (PrivacyLevel, FormatPreserving)>,
+    /// Share of the encrypted attributes stored in each timeline
+    pub timeline_distribution: HashMap<TimelineType, f32>,
+    /// Number of decoy fragments stored alongside the real ones
+    pub decoy_count: usize,
+    /// Ascending sizes fragments are padded up to, empty for none
+    pub fragment_buckets: Vec<usize>,
+    /// Timeline of each encrypted trait type when fracturing by section rather than byte range
+    pub sections: Option<SectionMapping>,
+    /// Whether identical fragments are encrypted convergently so they can be stored once
+    pub deduplicate: bool,
+}
+
+impl ProtectionPolicy {
+    /// Create a policy that encrypts nothing, with the default timeline distribution
+    pub fn new() -> Self {
+        Self {
+            trait_levels: HashMap::new(),
+            generalizations: HashMap::new(),
+            format_preserving: HashMap::new(),
+            timeline_distribution: HashMap::from([
+                (TimelineType::Primary, 0.3),
+                (TimelineType::Identity, 0.15),
+                (TimelineType::Activity, 0.15),
+                (TimelineType::Social, 0.2),
+                (TimelineType::Financial, 0.2),
+            ]),
+            decoy_count: 0,
+            fragment_buckets: Vec::new(),
+            sections: None,
+            deduplicate: false,
+        }
+    }
+    
+    /// Encrypt a trait type at `level` and above
+    pub fn with_trait(mut self, trait_type: &str, level: PrivacyLevel) -> Self {
+        self.trait_levels.insert(trait_type.to_string(), level);
+        self
+    }
+    
+    /// Generalize a trait type at `level` and above, until it is encrypted
+    pub fn with_generalization(mut self, trait_type: &str, level: PrivacyLevel, generalization: Generalization) -> Self {
+        self.generalizations.insert(trait_type.to_string(), (level, generalization));
+        self
+    }
+    
+    /// Encrypt a trait type in place at `level` and above, until it is encrypted outright
+    ///
+    /// For marketplaces that reject attribute values changing type or length.
+    pub fn with_format_preserving(mut self, trait_type: &str, level: PrivacyLevel, format: FormatPreserving) -> Self {
+        self.format_preserving.insert(trait_type.to_string(), (level, format));
+        self
+    }
+    
+    /// Store decoy fragments alongside the real ones
+    pub fn with_decoys(mut self, decoy_count: usize) -> Self {
+        self.decoy_count = decoy_count;
+        self
+    }
+    
+    /// Pad fragments up to size buckets, e.g. `STANDARD_SIZE_BUCKETS`, so their sizes reveal little
+    pub fn with_fragment_padding(mut self, size_buckets: &[usize]) -> Self {
+        self.fragment_buckets = size_buckets.to_vec();
+        self
+    }
+    
+    /// Fracture encrypted attributes by trait type, storing each on the timeline the mapping gives it
+    ///
+    /// Each timeline then holds a JSON object of its traits, so a partner
+    /// given one timeline key can read those traits and no others.
+    pub fn with_sections(mut self, mapping: SectionMapping) -> Self {
+        self.sections = Some(mapping);
+        self
+    }
+    
+    /// Encrypt fragments convergently, so identical ones across a collection are stored once
+    ///
+    /// Takes effect with a timeline shifter built `with_deduplication`.
+    pub fn with_deduplication(mut self) -> Self {
+        self.deduplicate = true;
+        self
+    }
+    
+    /// Get the options encrypted attributes are fractured with
+    pub fn fracture_options(&self) -> FractureOptions {
+        let options = FractureOptions::with_decoys(self.decoy_count).with_padding(&self.fragment_buckets);
+        match &self.sections {
+            Some(mapping) => options.with_sections(mapping.clone()),
+            None => options,
+        }
+    }
+    
+    /// Check if a trait type is encrypted at a privacy level
+    pub fn protects(&self, trait_type: &str, level: PrivacyLevel) -> bool {
+        if level == PrivacyLevel::None {
+            return false;
+        }
+        
+        self.trait_levels
+            .get(trait_type)
+            .map_or(false, |min_level| level as u8 >= *min_level as u8)
+    }
+    
+    /// Get what happens to a trait type at a privacy level
+    ///
+    /// Encryption takes precedence over format-preserving encryption, which
+    /// takes precedence over generalization.
+    pub fn action(&self, trait_type: &str, level: PrivacyLevel) -> PolicyAction<'_> {
+        if self.protects(trait_type, level) {
+            return PolicyAction::Encrypt;
+        }
+        
+        let applies = |min_level: &PrivacyLevel| level != PrivacyLevel::None && level as u8 >= *min_level as u8;
+        if let Some((_, format)) = self.format_preserving.get(trait_type).filter(|(min_level, _)| applies(min_level)) {
+            return PolicyAction::FormatPreserve(format);
+        }
+        
+        match self.generalizations.get(trait_type) {
+            Some((min_level, generalization)) if applies(min_level) => PolicyAction::Generalize(generalization),
+            _ => PolicyAction::Public,
+        }
+    }
+    
+    /// Check if a trait type is published generalized at a privacy level
+    pub fn generalizes(&self, trait_type: &str, level: PrivacyLevel) -> bool {
+        matches!(self.action(trait_type, level), PolicyAction::Generalize(_))
+    }
+    
+    /// Get how a trait type is encrypted in place at a privacy level, if it is
+    pub fn format_of(&self, trait_type: &str, level: PrivacyLevel) -> Option<&FormatPreserving> {
+        match self.action(trait_type, level) {
+            PolicyAction::FormatPreserve(format) => Some(format),
+            _ => None,
+        }
+    }
+    
+    /// Split attributes into those encrypted at a privacy level and those published
+    ///
+    /// Generalized attributes appear in both: exact in the encrypted list, so
+    /// the owner can recover them, and coarsened in the public one. Values a
+    /// generalization can't handle are only encrypted, as are attributes to
+    /// encrypt in place, which need `partition_with_cipher`.
+    pub fn partition(&self, attributes: &[Attribute], level: PrivacyLevel) -> (Vec<Attribute>, Vec<Attribute>) {
+        self.split(attributes, level, None)
+    }
+    
+    /// Split attributes like `partition`, encrypting in place those the policy formats
+    ///
+    /// Values too short or too long for their cipher are encrypted outright instead.
+    pub fn partition_with_cipher(
+        &self,
+        attributes: &[Attribute],
+        level: PrivacyLevel,
+        cipher: &FpeCipher,
+        nft_id: &str,
+    ) -> (Vec<Attribute>, Vec<Attribute>) {
+        self.split(attributes, level, Some((cipher, nft_id)))
+    }
+    
+    /// Split attributes, with the cipher for in-place encryption if available
+    fn split(
+        &self,
+        attributes: &[Attribute],
+        level: PrivacyLevel,
+        cipher: Option<(&FpeCipher, &str)>,
+    ) -> (Vec<Attribute>, Vec<Attribute>) {
+        let mut private = Vec::new();
+        let mut public = Vec::new();
+        
+        for attr in attributes {
+            match self.action(&attr.trait_type, level) {
+                PolicyAction::Public => public.push(attr.clone()),
+                PolicyAction::Encrypt => private.push(attr.clone()),
+                PolicyAction::Generalize(generalization) => {
+                    if let Some(value) = generalization.apply(&attr.value) {
+                        public.push(Attribute { trait_type: attr.trait_type.clone(), value });
+                    }
+                    private.push(attr.clone());
+                },
+                PolicyAction::FormatPreserve(format) => {
+                    let encrypted = cipher.and_then(|(cipher, nft_id)| {
+                        cipher.encrypt(format, &fpe::tweak(nft_id, &attr.trait_type), &attr.value).ok()
+                    });
+                    match encrypted {
+                        Some(value) => public.push(Attribute { trait_type: attr.trait_type.clone(), value }),
+                        None => private.push(attr.clone()),
+                    }
+                },
+            }
+        }
+        
+        (private, public)
+    }
+}
+
+impl Default for ProtectionPolicy {
+    /// Glitch Gang defaults: identity traits first, then lore, then cosmetics
+    fn default() -> Self {
+        Self::new()
+            .with_trait("Secret Code", PrivacyLevel::Light)
+            .with_trait("Agent Name", PrivacyLevel::Light)
+            .with_trait("Mission", PrivacyLevel::Medium)
+            .with_trait("Origin", PrivacyLevel::Medium)
+            .with_trait("Accessory", PrivacyLevel::Heavy)
+            .with_trait("Symbols", PrivacyLevel::Heavy)
+    }
+}
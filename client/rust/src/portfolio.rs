@@ -0,0 +1,425 @@
+//! Portfolios of wrapped NFTs
+//!
+//! Collectors holding dozens of wrapped NFTs keep them in a portfolio
+//! directory: an `index.json` listing each NFT's wrapper and fragment
+//! manifest, and a signed wrapper snapshot per NFT under `snapshots/`.
+//! Per-NFT configs are only restored into the client from their snapshot
+//! the first time an operation needs them. A `search.json` index of hidden
+//! attribute tokens lets the owner find NFTs by hidden trait.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use timeline_shifter::FragmentManifest;
+
+use crate::client::GlitchGangPrivacyClient;
+use crate::models::{ProtectionReport, PurchaseStatus, RevealPurchase};
+use crate::search::SearchIndex;
+
+/// Current portfolio index format version
+pub const PORTFOLIO_VERSION: u32 = 1;
+
+/// Name of the index file in a portfolio directory
+const INDEX_FILE: &str = "index.json";
+
+/// Name of the hidden attribute search index in a portfolio directory
+const SEARCH_FILE: &str = "search.json";
+
+/// Directory holding per-NFT snapshots in a portfolio directory
+const SNAPSHOT_DIR: &str = "snapshots";
+
+/// One wrapped NFT in a portfolio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioEntry {
+    /// NFT the wrapper protects
+    pub nft_mint: Pubkey,
+    /// Wrapper account
+    pub wrapper: Pubkey,
+    /// Manifest of the NFT's metadata fragments, if it was fractured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<FragmentManifest>,
+}
+
+/// Persisted list of a portfolio's NFTs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioIndex {
+    /// Format version
+    pub version: u32,
+    /// Tracked NFTs by mint
+    pub entries: BTreeMap<String, PortfolioEntry>,
+}
+
+impl Default for PortfolioIndex {
+    fn default() -> Self {
+        Self {
+            version: PORTFOLIO_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+/// Timed access to a portfolio NFT that ends soon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiringGrant {
+    /// NFT the access is to
+    pub nft_mint: Pubkey,
+    /// Wrapper the access was bought on
+    pub wrapper: Pubkey,
+    /// Viewer holding the access
+    pub viewer: Pubkey,
+    /// Access level held until expiry
+    pub access_level: u8,
+    /// When the access ends, as a Unix timestamp
+    pub expires_at: i64,
+}
+
+/// Fragment storage check of one portfolio NFT
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FragmentAudit {
+    /// NFT the fragments belong to
+    pub nft_mint: Pubkey,
+    /// Number of fragments in the manifest, including decoys
+    pub fragment_count: usize,
+    /// Fragments no storage adapter holds any more
+    pub missing: Vec<String>,
+}
+
+impl FragmentAudit {
+    /// Check whether every fragment is still stored
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Manager of many wrapped NFTs sharing one client
+pub struct PortfolioManager {
+    /// Portfolio directory
+    dir: PathBuf,
+    /// Passphrase sealing the per-NFT snapshots
+    passphrase: Zeroizing<String>,
+    /// Tracked NFTs
+    index: PortfolioIndex,
+    /// Search tokens of tracked NFTs' hidden attributes
+    search: SearchIndex,
+    /// Mints whose configs have been restored into the client
+    loaded: HashSet<String>,
+}
+
+impl PortfolioManager {
+    /// Open a portfolio directory, starting an empty portfolio if it has no index
+    pub fn open(dir: impl AsRef<Path>, passphrase: &str) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        let index_path = dir.join(INDEX_FILE);
+        
+        let index = if index_path.exists() {
+            let json = fs::read_to_string(&index_path)
+                .map_err(|e| format!("Failed to read portfolio index: {}", e))?;
+            let index: PortfolioIndex = serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse portfolio index: {}", e))?;
+            
+            if index.version != PORTFOLIO_VERSION {
+                return Err(format!("Unsupported portfolio version {}", index.version));
+            }
+            index
+        } else {
+            PortfolioIndex::default()
+        };
+        
+        let search_path = dir.join(SEARCH_FILE);
+        let search = if search_path.exists() {
+            let json = fs::read_to_string(&search_path)
+                .map_err(|e| format!("Failed to read search index: {}", e))?;
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse search index: {}", e))?
+        } else {
+            SearchIndex::default()
+        };
+        
+        Ok(Self {
+            dir,
+            passphrase: Zeroizing::new(passphrase.to_string()),
+            index,
+            search,
+            loaded: HashSet::new(),
+        })
+    }
+    
+    /// Get the tracked NFTs, ordered by mint
+    pub fn entries(&self) -> impl Iterator<Item = &PortfolioEntry> {
+        self.index.entries.values()
+    }
+    
+    /// Get a tracked NFT
+    pub fn entry(&self, nft_mint: &Pubkey) -> Option<&PortfolioEntry> {
+        self.index.entries.get(&nft_mint.to_string())
+    }
+    
+    /// Start tracking an NFT whose config the client holds, snapshotting its state
+    ///
+    /// Adding an NFT already tracked refreshes its snapshot and manifest.
+    pub fn add(
+        &mut self,
+        client: &GlitchGangPrivacyClient,
+        nft_mint: &Pubkey,
+        manifest: Option<FragmentManifest>,
+    ) -> Result<Pubkey, String> {
+        let wrapper = client.find_wrapper(nft_mint)?;
+        
+        self.index.entries.insert(nft_mint.to_string(), PortfolioEntry {
+            nft_mint: *nft_mint,
+            wrapper,
+            manifest,
+        });
+        self.save_snapshot(client, nft_mint)?;
+        self.save_index()?;
+        self.loaded.insert(nft_mint.to_string());
+        
+        log::info!("Added NFT {} to portfolio", nft_mint);
+        Ok(wrapper)
+    }
+    
+    /// Stop tracking an NFT and delete its snapshot
+    pub fn remove(&mut self, nft_mint: &Pubkey) -> Result<Option<PortfolioEntry>, String> {
+        let entry = self.index.entries.remove(&nft_mint.to_string());
+        if entry.is_some() {
+            self.save_index()?;
+            let snapshot_path = self.snapshot_path(nft_mint);
+            if snapshot_path.exists() {
+                fs::remove_file(&snapshot_path)
+                    .map_err(|e| format!("Failed to delete snapshot of {}: {}", nft_mint, e))?;
+            }
+            self.loaded.remove(&nft_mint.to_string());
+            self.search.remove(nft_mint);
+            self.save_search()?;
+        }
+        
+        Ok(entry)
+    }
+    
+    /// Record a new fragment manifest for an NFT, e.g. after timeline drift
+    pub fn update_manifest(&mut self, nft_mint: &Pubkey, manifest: FragmentManifest) -> Result<(), String> {
+        let entry = self.index.entries.get_mut(&nft_mint.to_string())
+            .ok_or_else(|| format!("NFT {} is not in the portfolio", nft_mint))?;
+        entry.manifest = Some(manifest);
+        
+        self.save_index()
+    }
+    
+    /// Index the hidden attributes of a tracked NFT from its protection report
+    ///
+    /// Replaces what was indexed for the NFT before, so call it after every protection.
+    pub fn index_protection(&mut self, nft_mint: &Pubkey, report: &ProtectionReport) -> Result<(), String> {
+        if self.entry(nft_mint).is_none() {
+            return Err(format!("NFT {} is not in the portfolio", nft_mint));
+        }
+        
+        self.search.insert(nft_mint, &report.search_tokens);
+        self.save_search()
+    }
+    
+    /// Find tracked NFTs hiding a trait type, or a trait with an exact value, ordered by mint
+    ///
+    /// Nothing is decrypted: the client's search key computes the token looked up.
+    pub fn search(
+        &self,
+        client: &GlitchGangPrivacyClient,
+        trait_type: &str,
+        value: Option<&str>,
+    ) -> Result<Vec<Pubkey>, String> {
+        let key = client.search_key()?;
+        let token = match value {
+            Some(value) => key.value_token(trait_type, value),
+            None => key.trait_token(trait_type),
+        };
+        
+        Ok(self.search.lookup(&token))
+    }
+    
+    /// Restore an NFT's configs into the client from its snapshot, if not done yet
+    pub fn load(&mut self, client: &GlitchGangPrivacyClient, nft_mint: &Pubkey) -> Result<(), String> {
+        let mint = nft_mint.to_string();
+        if self.loaded.contains(&mint) {
+            return Ok(());
+        }
+        if !self.index.entries.contains_key(&mint) {
+            return Err(format!("NFT {} is not in the portfolio", nft_mint));
+        }
+        
+        let snapshot_json = fs::read_to_string(self.snapshot_path(nft_mint))
+            .map_err(|e| format!("Failed to read snapshot of {}: {}", nft_mint, e))?;
+        client.import_wrapper_state(&snapshot_json, &self.passphrase)?;
+        
+        self.loaded.insert(mint);
+        Ok(())
+    }
+    
+    /// Rotate the key of every NFT whose rotation is due, re-snapshotting each
+    ///
+    /// Returns the rotated mints with their transaction signatures.
+    pub async fn rotate_due(&mut self, client: &GlitchGangPrivacyClient) -> Result<Vec<(Pubkey, String)>, String> {
+        let entries: Vec<PortfolioEntry> = self.index.entries.values().cloned().collect();
+        
+        let mut rotated = Vec::new();
+        for entry in entries {
+            self.load(client, &entry.nft_mint)?;
+            if !client.privacy_config(&entry.nft_mint)?.needs_rotation() {
+                continue;
+            }
+            
+            let signature = client.rotate_key(&entry.wrapper, &entry.nft_mint).await?;
+            // The old snapshot holds the retired key, so replace it right away
+            self.save_snapshot(client, &entry.nft_mint)?;
+            
+            rotated.push((entry.nft_mint, signature));
+        }
+        
+        Ok(rotated)
+    }
+    
+    /// List timed access to portfolio NFTs ending within `within` seconds of `now`, soonest first
+    pub fn expiring_grants(
+        &self,
+        client: &GlitchGangPrivacyClient,
+        now: i64,
+        within: i64,
+    ) -> Result<Vec<ExpiringGrant>, String> {
+        let mut grants = Vec::new();
+        for entry in self.index.entries.values() {
+            let purchases = client.wrapper_purchases(&entry.wrapper)?;
+            grants.extend(expiring(entry, &purchases, now, within));
+        }
+        
+        grants.sort_by_key(|grant| grant.expires_at);
+        Ok(grants)
+    }
+    
+    /// Check that every fragment of every fractured portfolio NFT is still stored
+    pub async fn audit_fragments(&self, client: &GlitchGangPrivacyClient) -> Result<Vec<FragmentAudit>, String> {
+        let mut audits = Vec::new();
+        for entry in self.index.entries.values() {
+            let Some(manifest) = &entry.manifest else {
+                continue;
+            };
+            
+            audits.push(FragmentAudit {
+                nft_mint: entry.nft_mint,
+                fragment_count: manifest.fragment_ids.len(),
+                missing: client.missing_fragments(manifest).await?,
+            });
+        }
+        
+        Ok(audits)
+    }
+    
+    /// Get the snapshot path of an NFT
+    fn snapshot_path(&self, nft_mint: &Pubkey) -> PathBuf {
+        self.dir.join(SNAPSHOT_DIR).join(format!("{}.json", nft_mint))
+    }
+    
+    /// Export an NFT's current state to its snapshot file
+    fn save_snapshot(&self, client: &GlitchGangPrivacyClient, nft_mint: &Pubkey) -> Result<(), String> {
+        let manifest = self.entry(nft_mint).and_then(|entry| entry.manifest.as_ref());
+        let snapshot_json = client.export_wrapper_state(nft_mint, manifest, &self.passphrase)?;
+        
+        fs::create_dir_all(self.dir.join(SNAPSHOT_DIR))
+            .map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+        fs::write(self.snapshot_path(nft_mint), snapshot_json)
+            .map_err(|e| format!("Failed to write snapshot of {}: {}", nft_mint, e))
+    }
+    
+    /// Write the search index to the portfolio directory
+    fn save_search(&self) -> Result<(), String> {
+        let json = serde_json::to_string(&self.search)
+            .map_err(|e| format!("Failed to serialize search index: {}", e))?;
+        
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create portfolio directory: {}", e))?;
+        fs::write(self.dir.join(SEARCH_FILE), json)
+            .map_err(|e| format!("Failed to write search index: {}", e))
+    }
+    
+    /// Write the index to the portfolio directory
+    fn save_index(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.index)
+            .map_err(|e| format!("Failed to serialize portfolio index: {}", e))?;
+        
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create portfolio directory: {}", e))?;
+        fs::write(self.dir.join(INDEX_FILE), json)
+            .map_err(|e| format!("Failed to write portfolio index: {}", e))
+    }
+}
+
+/// Select purchases on an entry's wrapper whose access ends within the window
+fn expiring(entry: &PortfolioEntry, purchases: &[RevealPurchase], now: i64, within: i64) -> Vec<ExpiringGrant> {
+    purchases.iter()
+        .filter(|purchase| purchase.status != PurchaseStatus::Refunded)
+        .filter(|purchase| purchase.expires_at > now && purchase.expires_at <= now.saturating_add(within))
+        .map(|purchase| ExpiringGrant {
+            nft_mint: entry.nft_mint,
+            wrapper: entry.wrapper,
+            viewer: purchase.buyer,
+            access_level: purchase.access_level,
+            expires_at: purchase.expires_at,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn purchase(wrapper: &Pubkey, expires_at: i64, status: PurchaseStatus) -> RevealPurchase {
+        RevealPurchase {
+            is_initialized: true,
+            wrapper: *wrapper,
+            buyer: Pubkey::new_unique(),
+            amount: 1_000,
+            access_level: 2,
+            paid_at: 0,
+            expires_at,
+            refundable_at: 0,
+            settleable_at: 0,
+            royalty_shares: Vec::new(),
+            status,
+        }
+    }
+    
+    #[test]
+    fn test_index_persists_and_expiring_grants_are_windowed() {
+        let dir = std::env::temp_dir().join(format!("quantum-veil-portfolio-{}", std::process::id()));
+        let entry = PortfolioEntry {
+            nft_mint: Pubkey::new_unique(),
+            wrapper: Pubkey::new_unique(),
+            manifest: Some(FragmentManifest::new("mint", vec!["fragment-1".to_string()])),
+        };
+        
+        let mut portfolio = PortfolioManager::open(&dir, "passphrase").unwrap();
+        assert_eq!(portfolio.entries().count(), 0);
+        portfolio.index.entries.insert(entry.nft_mint.to_string(), entry.clone());
+        portfolio.save_index().unwrap();
+        
+        // Reopening reads the index back, with no configs loaded yet
+        let reopened = PortfolioManager::open(&dir, "passphrase").unwrap();
+        assert_eq!(reopened.entry(&entry.nft_mint), Some(&entry));
+        assert!(reopened.loaded.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+        
+        // Only live access ending inside the window is listed
+        let now = 1_700_000_000;
+        let purchases = [
+            purchase(&entry.wrapper, now + 3_600, PurchaseStatus::Escrowed),
+            purchase(&entry.wrapper, now + 7_200, PurchaseStatus::Settled),
+            purchase(&entry.wrapper, now + 86_400, PurchaseStatus::Settled),
+            purchase(&entry.wrapper, now - 60, PurchaseStatus::Settled),
+            purchase(&entry.wrapper, now + 60, PurchaseStatus::Refunded),
+        ];
+        let grants = expiring(&entry, &purchases, now, 7_200);
+        assert_eq!(grants.iter().map(|grant| grant.expires_at).collect::<Vec<_>>(), vec![now + 3_600, now + 7_200]);
+        assert_eq!(grants[0].viewer, purchases[0].buyer);
+    }
+}
@@ -0,0 +1,108 @@
+//! Commitments to hidden attributes and zero-knowledge proofs about them
+//!
+//! Trait values are mapped to numbers, either directly when numeric or by
+//! their position on an ordered scale such as rarity tiers, then committed
+//! to. The owner keeps the opening and proves predicates like "Rarity is at
+//! least Epic" that anyone can check with `zk::verify_predicate`.
+
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+
+use quantum_veil::SecretBytes;
+use zk::{commit, prove, Predicate, PredicateKeys, PredicateProof};
+
+use crate::models::GlitchGangMetadata;
+
+/// Rarity tiers from lowest to highest
+pub const RARITY_SCALE: &[&str] = &["Common", "Uncommon", "Rare", "Epic", "Legendary"];
+
+/// Published commitment to a hidden attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeCommitment {
+    /// Trait committed to
+    pub trait_type: String,
+    /// Commitment to the trait's numeric value
+    pub commitment: [u8; 32],
+}
+
+/// Secret opening of an attribute commitment, kept by the owner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeOpening {
+    /// Trait committed to
+    pub trait_type: String,
+    /// Numeric value of the trait
+    pub value: u64,
+    /// 32-byte blinding factor hiding the value
+    pub blinding: SecretBytes,
+}
+
+/// Map a trait value to a number, by its position on `scale` or parsed as an integer
+pub fn scale_value(value: &str, scale: &[&str]) -> Result<u64, String> {
+    if let Some(position) = scale.iter().position(|tier| tier.eq_ignore_ascii_case(value)) {
+        return Ok(position as u64);
+    }
+    
+    value.parse::<u64>()
+        .map_err(|_| format!("Attribute value {} is not on the scale or a number", value))
+}
+
+/// Predicate that a trait is at least a tier on a scale, e.g. Rarity ≥ Epic
+pub fn at_least_tier(tier: &str, scale: &[&str]) -> Result<Predicate, String> {
+    Ok(Predicate::at_least(scale_value(tier, scale)?))
+}
+
+/// Commit to a trait of an NFT's metadata under a fresh blinding factor
+pub fn commit_attribute(
+    metadata: &GlitchGangMetadata,
+    trait_type: &str,
+    scale: &[&str],
+) -> Result<(AttributeCommitment, AttributeOpening), String> {
+    let attribute = metadata.attributes.iter()
+        .find(|attribute| attribute.trait_type == trait_type)
+        .ok_or_else(|| format!("Attribute {} not found", trait_type))?;
+    let value = scale_value(&attribute.value, scale)?;
+    
+    let blinding = SecretBytes::new(OsRng.gen::<[u8; 32]>().to_vec());
+    let commitment = commit(value, &blinding.to_array::<32>()?)?;
+    
+    Ok((
+        AttributeCommitment { trait_type: trait_type.to_string(), commitment },
+        AttributeOpening { trait_type: trait_type.to_string(), value, blinding },
+    ))
+}
+
+/// Prove a predicate about a committed attribute without revealing its value
+pub fn prove_attribute(
+    opening: &AttributeOpening,
+    predicate: Predicate,
+    keys: &PredicateKeys,
+) -> Result<PredicateProof, String> {
+    prove(keys, opening.value, &opening.blinding.to_array::<32>()?, predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_metadata;
+    use crate::models::Attribute;
+    use zk::{setup, verify_predicate, PredicateKind};
+    
+    #[test]
+    fn test_prove_rarity_at_least_epic() {
+        let mut metadata = sample_metadata();
+        metadata.attributes.push(Attribute { trait_type: "Rarity".to_string(), value: "Legendary".to_string() });
+        
+        let (commitment, opening) = commit_attribute(&metadata, "Rarity", RARITY_SCALE).unwrap();
+        assert_eq!(opening.value, 4);
+        
+        let keys = setup(PredicateKind::Range).unwrap();
+        let predicate = at_least_tier("Epic", RARITY_SCALE).unwrap();
+        let proof = prove_attribute(&opening, predicate, &keys).unwrap();
+        
+        assert_eq!(proof.commitment, commitment.commitment);
+        assert!(verify_predicate(&keys.verifying_key_bytes().unwrap(), &proof).is_ok());
+        
+        assert!(commit_attribute(&metadata, "Missing", RARITY_SCALE).is_err());
+        assert!(scale_value("Mythic", RARITY_SCALE).is_err());
+    }
+}
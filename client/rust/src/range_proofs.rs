@@ -0,0 +1,214 @@
+//! Pedersen commitments and Bulletproofs range proofs for numeric traits
+//!
+//! A hidden numeric trait is committed to as `v·B + r·B_blinding` and the
+//! commitment stored in the metadata's private data. The owner keeps the
+//! opening and proves bounds such as "power level is at least 9000" with an
+//! aggregated Bulletproof over `v - min` and `max - v`, whose commitments the
+//! verifier derives from the published one, so the value is never revealed.
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek_ng::{ristretto::CompressedRistretto, scalar::Scalar};
+use merlin::Transcript;
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use quantum_veil::SecretBytes;
+
+use crate::models::{GlitchGangMetadata, NumericCommitment, PrivateData};
+
+/// Transcript label binding proofs to this protocol
+const TRANSCRIPT_LABEL: &[u8] = b"quantum-veil/numeric-bounds/v1";
+
+/// Bits each bounded difference is proven to fit in
+const RANGE_BITS: usize = 64;
+
+/// Secret opening of a numeric commitment, kept by the owner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericOpening {
+    /// Trait committed to
+    pub trait_type: String,
+    /// Hidden value
+    pub value: u64,
+    /// Canonical 32-byte blinding scalar
+    pub blinding: SecretBytes,
+}
+
+/// Proof that a committed numeric trait lies within bounds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundProof {
+    /// Trait the proof is about
+    pub trait_type: String,
+    /// Smallest value allowed
+    pub min: u64,
+    /// Largest value allowed
+    pub max: u64,
+    /// Aggregated Bulletproof, base64 encoded
+    pub proof: String,
+}
+
+impl BoundProof {
+    /// Encode the proof as JSON for sharing
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to encode bound proof: {}", e))
+    }
+    
+    /// Decode a proof shared as JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to decode bound proof: {}", e))
+    }
+}
+
+/// Commit to a numeric value under a fresh blinding factor
+pub fn commit_numeric(trait_type: &str, value: u64) -> (NumericCommitment, NumericOpening) {
+    let blinding = Scalar::from_bytes_mod_order(OsRng.gen::<[u8; 32]>());
+    let commitment = PedersenGens::default().commit(Scalar::from(value), blinding).compress();
+    
+    (
+        NumericCommitment {
+            trait_type: trait_type.to_string(),
+            commitment: base64::encode(commitment.as_bytes()),
+        },
+        NumericOpening {
+            trait_type: trait_type.to_string(),
+            value,
+            blinding: SecretBytes::from(&blinding.to_bytes()[..]),
+        },
+    )
+}
+
+/// Move a numeric trait out of the public attributes and into a commitment
+///
+/// Returns the opening, which the owner must keep to prove bounds later.
+pub fn commit_numeric_trait(metadata: &mut GlitchGangMetadata, trait_type: &str) -> Result<NumericOpening, String> {
+    let position = metadata.attributes.iter()
+        .position(|attribute| attribute.trait_type == trait_type)
+        .ok_or_else(|| format!("Attribute {} not found", trait_type))?;
+    let value = metadata.attributes[position].value.parse::<u64>()
+        .map_err(|e| format!("Attribute {} is not numeric: {}", trait_type, e))?;
+    
+    let (commitment, opening) = commit_numeric(trait_type, value);
+    metadata.attributes.remove(position);
+    metadata.private_data
+        .get_or_insert_with(|| PrivateData {
+            privacy_level: "Medium".to_string(),
+            encrypted_attributes: None,
+            timeline_fragments: None,
+            vrm_config: None,
+            protected_assets: Vec::new(),
+            timelocked_attributes: Vec::new(),
+            numeric_commitments: Vec::new(),
+            format_preserved: HashMap::new(),
+        })
+        .numeric_commitments
+        .push(commitment);
+    
+    Ok(opening)
+}
+
+/// Prove the committed value lies in `min..=max` without revealing it
+pub fn prove_bounds(opening: &NumericOpening, min: u64, max: u64) -> Result<BoundProof, String> {
+    if opening.value < min || opening.value > max {
+        return Err(format!("{} is not within {}..={}", opening.trait_type, min, max));
+    }
+    
+    let blinding = Scalar::from_canonical_bytes(*opening.blinding.to_array::<32>()?)
+        .ok_or_else(|| "Invalid blinding scalar".to_string())?;
+    
+    // The upper difference's commitment is max·B minus the published one, so it takes the negated blinding
+    let (proof, _) = RangeProof::prove_multiple(
+        &BulletproofGens::new(RANGE_BITS, 2),
+        &PedersenGens::default(),
+        &mut transcript(&opening.trait_type, min, max),
+        &[opening.value - min, max - opening.value],
+        &[blinding, -blinding],
+        RANGE_BITS,
+    )
+    .map_err(|e| format!("Failed to prove bounds: {:?}", e))?;
+    
+    Ok(BoundProof {
+        trait_type: opening.trait_type.clone(),
+        min,
+        max,
+        proof: base64::encode(proof.to_bytes()),
+    })
+}
+
+/// Verify a bound proof against a published commitment
+pub fn verify_bounds(commitment: &NumericCommitment, proof: &BoundProof) -> Result<(), String> {
+    if commitment.trait_type != proof.trait_type {
+        return Err(format!("Proof is about {}, not {}", proof.trait_type, commitment.trait_type));
+    }
+    if proof.min > proof.max {
+        return Err(format!("Invalid bounds {}..={}", proof.min, proof.max));
+    }
+    
+    let commitment_bytes = base64::decode(&commitment.commitment)
+        .map_err(|e| format!("Failed to decode commitment: {}", e))?;
+    if commitment_bytes.len() != 32 {
+        return Err("Commitment must be 32 bytes".to_string());
+    }
+    let commitment_point = CompressedRistretto::from_slice(&commitment_bytes)
+        .decompress()
+        .ok_or_else(|| "Commitment is not a valid point".to_string())?;
+    
+    let range_proof = base64::decode(&proof.proof)
+        .map_err(|e| format!("Failed to decode bound proof: {}", e))
+        .and_then(|bytes| RangeProof::from_bytes(&bytes).map_err(|e| format!("Failed to decode bound proof: {:?}", e)))?;
+    
+    // Derive the commitments to v - min and max - v from the published one
+    let pc_gens = PedersenGens::default();
+    let lower = commitment_point - pc_gens.B * Scalar::from(proof.min);
+    let upper = pc_gens.B * Scalar::from(proof.max) - commitment_point;
+    
+    range_proof
+        .verify_multiple(
+            &BulletproofGens::new(RANGE_BITS, 2),
+            &pc_gens,
+            &mut transcript(&proof.trait_type, proof.min, proof.max),
+            &[lower.compress(), upper.compress()],
+            RANGE_BITS,
+        )
+        .map_err(|_| format!("Invalid bound proof for {}", proof.trait_type))
+}
+
+/// Start a transcript bound to the trait and bounds being proven
+fn transcript(trait_type: &str, min: u64, max: u64) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+    transcript.append_message(b"trait_type", trait_type.as_bytes());
+    transcript.append_u64(b"min", min);
+    transcript.append_u64(b"max", max);
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attribute;
+    use crate::test_utils::sample_metadata;
+    
+    #[test]
+    fn test_bounds_proven_without_revealing_value() {
+        let mut metadata = sample_metadata();
+        metadata.attributes.push(Attribute { trait_type: "Power Level".to_string(), value: "9001".to_string() });
+        
+        let opening = commit_numeric_trait(&mut metadata, "Power Level").unwrap();
+        assert!(metadata.attributes.iter().all(|attribute| attribute.trait_type != "Power Level"));
+        let commitment = metadata.private_data.as_ref().unwrap().numeric_commitments[0].clone();
+        
+        // Over 9000, shared as JSON
+        let proof = prove_bounds(&opening, 9_000, u64::MAX).unwrap();
+        let shared = BoundProof::from_json(&proof.to_json().unwrap()).unwrap();
+        assert!(verify_bounds(&commitment, &shared).is_ok());
+        assert!(verify_bounds(&commitment, &prove_bounds(&opening, 9_001, 9_001).unwrap()).is_ok());
+        
+        // Bounds the value doesn't meet can't be proven, and proofs don't transfer
+        assert!(prove_bounds(&opening, 9_002, u64::MAX).is_err());
+        let widened = BoundProof { min: 9_500, ..proof.clone() };
+        assert!(verify_bounds(&commitment, &widened).is_err());
+        let (other, _) = commit_numeric("Power Level", 9_001);
+        assert!(verify_bounds(&other, &proof).is_err());
+        
+        assert!(commit_numeric_trait(&mut metadata, "Background").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use curve25519_dalek::{
+    constants::X25519_BASEPOINT,
+    edwards::CompressedEdwardsY,
+    montgomery::MontgomeryPoint,
+    scalar::Scalar,
+};
+use rand::{Rng, rngs::OsRng};
+use ring::digest;
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signer}};
+
+use quantum_veil::{encrypt_data, decrypt_data};
+
+/// Domain label for keys sealing shares to a guardian
+const SHARE_KEY_LABEL: &[u8] = b"quantum-veil/recovery-share";
+
+/// Share of a 32-byte key, as `[x: 1][y: 32]`
+pub type KeyShare = [u8; 33];
+
+/// Split a key into `count` shares, any `threshold` of which recombine it
+///
+/// Shamir's scheme over GF(256), byte by byte: fewer than `threshold`
+/// shares reveal nothing about the key.
+pub fn split_secret(secret: &[u8; 32], threshold: u8, count: u8) -> Result<Vec<KeyShare>, String> {
+    if threshold == 0 || threshold > count {
+        return Err(format!("Invalid threshold {} of {} shares", threshold, count));
+    }
+    
+    let mut shares: Vec<KeyShare> = (1..=count)
+        .map(|x| {
+            let mut share = [0u8; 33];
+            share[0] = x;
+            share
+        })
+        .collect();
+    
+    let mut coefficients = vec![0u8; threshold as usize];
+    for (i, byte) in secret.iter().enumerate() {
+        coefficients[0] = *byte;
+        OsRng.fill(&mut coefficients[1..]);
+        
+        for share in shares.iter_mut() {
+            // Horner's rule from the highest coefficient down
+            share[i + 1] = coefficients.iter().rev().fold(0, |acc, c| gf_mul(acc, share[0]) ^ c);
+        }
+    }
+    
+    Ok(shares)
+}
+
+/// Recombine a key from at least the threshold number of shares
+///
+/// Too few shares yield a wrong key rather than an error, so callers
+/// should check the result against a commitment.
+pub fn combine_shares(shares: &[KeyShare]) -> Result<[u8; 32], String> {
+    if shares.is_empty() {
+        return Err("No key shares to combine".to_string());
+    }
+    
+    let xs: Vec<u8> = shares.iter().map(|share| share[0]).collect();
+    if xs.iter().enumerate().any(|(i, x)| *x == 0 || xs[..i].contains(x)) {
+        return Err("Duplicate or invalid key shares".to_string());
+    }
+    
+    // Lagrange interpolation at zero; subtraction is XOR in GF(256)
+    let mut secret = [0u8; 32];
+    for (j, share) in shares.iter().enumerate() {
+        let basis = xs.iter().enumerate()
+            .filter(|(m, _)| *m != j)
+            .fold(1u8, |acc, (_, x)| gf_mul(acc, gf_mul(*x, gf_inv(x ^ xs[j]))));
+        
+        for (byte, y) in secret.iter_mut().zip(&share[1..]) {
+            *byte ^= gf_mul(basis, *y);
+        }
+    }
+    
+    Ok(secret)
+}
+
+/// Seal a share to a Solana account, as `[ephemeral key: 32][nonce: 12][ciphertext]`
+///
+/// The account's ed25519 key is mapped to X25519 for an ephemeral
+/// Diffie-Hellman exchange, so guardians need nothing beyond their wallet.
+pub fn seal_share(share: &KeyShare, recipient: &Pubkey) -> Result<Vec<u8>, String> {
+    let recipient_point = CompressedEdwardsY(recipient.to_bytes())
+        .decompress()
+        .ok_or_else(|| format!("Cannot seal a share to off-curve account {}", recipient))?
+        .to_montgomery();
+    
+    let mut ephemeral = [0u8; 32];
+    OsRng.fill(&mut ephemeral);
+    let ephemeral = Scalar::from_bits(clamp(ephemeral));
+    let ephemeral_public = &X25519_BASEPOINT * &ephemeral;
+    
+    let key = share_key(&(&recipient_point * &ephemeral), &ephemeral_public, recipient);
+    let mut nonce = [0u8; 12];
+    OsRng.fill(&mut nonce);
+    
+    let mut sealed = ephemeral_public.to_bytes().to_vec();
+    sealed.extend_from_slice(&nonce);
+    sealed.extend(encrypt_data(share, &key, &nonce)?);
+    Ok(sealed)
+}
+
+/// Open a share sealed to the keypair's account
+pub fn open_share(sealed: &[u8], recipient: &Keypair) -> Result<KeyShare, String> {
+    if sealed.len() < 44 {
+        return Err("Sealed share too short".to_string());
+    }
+    
+    // The X25519 secret of an ed25519 key is its clamped expanded seed
+    let expanded = digest::digest(&digest::SHA512, &recipient.to_bytes()[..32]);
+    let secret = Scalar::from_bits(clamp(expanded.as_ref()[..32].try_into().unwrap()));
+    
+    let ephemeral_public = MontgomeryPoint(sealed[..32].try_into().unwrap());
+    let key = share_key(&(&ephemeral_public * &secret), &ephemeral_public, &recipient.pubkey());
+    
+    decrypt_data(&sealed[44..], &key, &sealed[32..44])
+        .map_err(|_| "Failed to open key share".to_string())?
+        .try_into()
+        .map_err(|_| "Invalid key share length".to_string())
+}
+
+/// Derive the key sealing a share from the shared secret and both public keys
+fn share_key(shared: &MontgomeryPoint, ephemeral_public: &MontgomeryPoint, recipient: &Pubkey) -> [u8; 32] {
+    let input = [SHARE_KEY_LABEL, shared.as_bytes(), ephemeral_public.as_bytes(), recipient.as_ref()].concat();
+    digest::digest(&digest::SHA256, &input).as_ref().try_into().unwrap()
+}
+
+/// Clamp scalar bytes as X25519 does
+pub(crate) fn clamp(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    bytes
+}
+
+/// Multiply in GF(256) with the AES polynomial
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    
+    product
+}
+
+/// Invert a nonzero element of GF(256), as `a^254`
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254;
+    
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_any_threshold_of_shares_recombines() {
+        let secret = [42u8; 32];
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        
+        assert_eq!(combine_shares(&shares[..3]).unwrap(), secret);
+        assert_eq!(combine_shares(&[shares[4], shares[1], shares[3]]).unwrap(), secret);
+        assert_eq!(combine_shares(&shares).unwrap(), secret);
+        
+        // Below the threshold the key is not recovered
+        assert_ne!(combine_shares(&shares[..2]).unwrap(), secret);
+        assert!(combine_shares(&[shares[0], shares[0]]).is_err());
+        assert!(split_secret(&secret, 6, 5).is_err());
+    }
+    
+    #[test]
+    fn test_shares_open_only_for_their_guardian() {
+        let guardian = Keypair::new();
+        let share = split_secret(&[7u8; 32], 1, 1).unwrap()[0];
+        
+        let sealed = seal_share(&share, &guardian.pubkey()).unwrap();
+        assert_eq!(open_share(&sealed, &guardian).unwrap(), share);
+        assert!(open_share(&sealed, &Keypair::new()).is_err());
+    }
+}
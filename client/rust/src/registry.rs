@@ -0,0 +1,82 @@
+use borsh::BorshDeserialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::models::{find_agent_address, AgentRecord};
+use crate::sns;
+
+use synchronicity_mask::AgentResolver;
+
+/// Fetch and decode the agent record registered under a name
+pub fn fetch_agent_record(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    name: &str,
+) -> Result<AgentRecord, String> {
+    let (address, _) = find_agent_address(program_id, name);
+    
+    let data = rpc_client.get_account_data(&address)
+        .map_err(|e| format!("Failed to fetch agent record for {}: {}", name, e))?;
+    
+    let record = AgentRecord::deserialize(&mut &data[..])
+        .map_err(|e| format!("Failed to decode agent record: {}", e))?;
+    
+    if !record.is_initialized || record.name != name {
+        return Err(format!("Agent not registered: {}", name));
+    }
+    
+    Ok(record)
+}
+
+/// Agent name resolver backed by the on-chain agent registry
+///
+/// `.sol` domains resolve through the Solana Name Service instead. Resolved
+/// keys are cached, since masks resolve the viewer on every frame.
+pub struct AgentRegistry {
+    /// Solana RPC client
+    rpc_client: RpcClient,
+    /// Wrapper program ID that owns the registry
+    program_id: Pubkey,
+    /// Resolved agent keys by name
+    cache: RwLock<HashMap<String, Pubkey>>,
+}
+
+impl AgentRegistry {
+    /// Create a registry resolver for the given RPC endpoint
+    pub fn new(solana_rpc_url: &str, program_id: Pubkey) -> Self {
+        Self::with_rpc_client(RpcClient::new(solana_rpc_url.to_string()), program_id)
+    }
+    
+    /// Create a registry resolver with a preconfigured RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, program_id: Pubkey) -> Self {
+        Self {
+            rpc_client,
+            program_id,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+    
+    /// Drop a cached resolution, e.g. after the agent rotated its key
+    pub fn invalidate(&self, name: &str) {
+        self.cache.write().unwrap().remove(name);
+    }
+}
+
+impl AgentResolver for AgentRegistry {
+    fn resolve_agent(&self, name: &str) -> Result<Pubkey, String> {
+        if let Some(key) = self.cache.read().unwrap().get(name) {
+            return Ok(*key);
+        }
+        
+        let key = if sns::is_sol_domain(name) {
+            sns::resolve_domain(&self.rpc_client, name)?
+        } else {
+            fetch_agent_record(&self.rpc_client, &self.program_id, name)?.agent
+        };
+        self.cache.write().unwrap().insert(name.to_string(), key);
+        
+        Ok(key)
+    }
+}
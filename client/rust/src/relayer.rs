@@ -0,0 +1,131 @@
+//! Sponsored transactions
+//!
+//! A relayer pays transaction fees so owners without SOL can still manage
+//! their wrappers. The client builds each transaction with the relayer's fee
+//! payer, signs it as the owner and posts the partially-signed transaction to
+//! the relayer, which adds the fee payer's signature, submits it and answers
+//! with the transaction signature.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+use network::ProxyConfig;
+
+/// Relayer submitting transactions it pays the fees of
+pub struct Relayer {
+    /// Endpoint partially-signed transactions are posted to
+    pub endpoint: String,
+    /// Fee payer the relayer signs for
+    pub fee_payer: Pubkey,
+    /// Optional bearer token for the relayer
+    pub auth_token: Option<String>,
+    /// HTTP client transactions are posted with
+    pub http_client: reqwest::Client,
+}
+
+/// Body posted to the relayer
+#[derive(Serialize)]
+struct RelayRequest {
+    /// Base64 of the bincode-serialized, partially-signed transaction
+    transaction: String,
+}
+
+/// Relayer response
+#[derive(Deserialize)]
+struct RelayResponse {
+    /// Signature the transaction landed under
+    signature: String,
+}
+
+impl Relayer {
+    /// Create a relayer paying fees from `fee_payer`
+    pub fn new(endpoint: &str, fee_payer: &Pubkey) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            fee_payer: *fee_payer,
+            auth_token: None,
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Authenticate to the relayer with a bearer token
+    pub fn with_auth_token(mut self, auth_token: &str) -> Self {
+        self.auth_token = Some(auth_token.to_string());
+        self
+    }
+    
+    /// Post transactions through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+    
+    /// Post a partially-signed transaction and wait for its signature
+    ///
+    /// The transaction must name the relayer's fee payer and carry every
+    /// other signature.
+    pub async fn submit(&self, transaction: &Transaction) -> Result<Signature, String> {
+        if transaction.message.account_keys.first() != Some(&self.fee_payer) {
+            return Err(format!("Transaction fee payer is not the relayer's {}", self.fee_payer));
+        }
+        
+        let bytes = bincode::serialize(transaction)
+            .map_err(|e| format!("Failed to serialize transaction: {}", e))?;
+        let body = RelayRequest {
+            transaction: base64::encode(bytes),
+        };
+        
+        let mut request = self.http_client.post(&self.endpoint).json(&body);
+        if let Some(auth_token) = &self.auth_token {
+            request = request.header("Authorization", format!("Bearer {}", auth_token));
+        }
+        
+        let response: RelayResponse = request.send().await
+            .map_err(|e| format!("Failed to reach relayer: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Relayer rejected transaction: {}", e))?
+            .json().await
+            .map_err(|e| format!("Failed to parse relayer response: {}", e))?;
+        
+        Signature::from_str(&response.signature)
+            .map_err(|e| format!("Invalid signature from relayer: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, signature::{Keypair, Signer}, system_instruction};
+    
+    #[tokio::test]
+    async fn test_relayer_receives_partially_signed_transaction() {
+        let owner = Keypair::new();
+        let fee_payer = Pubkey::new_unique();
+        let instruction = system_instruction::transfer(&owner.pubkey(), &Pubkey::new_unique(), 1);
+        
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&fee_payer));
+        transaction.try_partial_sign(&[&owner], Hash::new_unique()).unwrap();
+        assert!(!transaction.is_signed());
+        
+        let encoded = base64::encode(bincode::serialize(&transaction).unwrap());
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/relay")
+            .match_header("authorization", "Bearer relay-token")
+            .match_body(mockito::Matcher::Json(serde_json::json!({ "transaction": encoded })))
+            .with_body(format!(r#"{{"signature":"{}"}}"#, Signature::default()))
+            .create_async()
+            .await;
+        
+        let relayer = Relayer::new(&format!("{}/relay", server.url()), &fee_payer).with_auth_token("relay-token");
+        assert_eq!(relayer.submit(&transaction).await.unwrap(), Signature::default());
+        
+        // Transactions naming another fee payer are refused before posting
+        let other = Relayer::new(&format!("{}/relay", server.url()), &Pubkey::new_unique());
+        assert!(other.submit(&transaction).await.unwrap_err().contains("fee payer"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Searchable index over hidden attributes
+//!
+//! Owners want to ask which of their NFTs hides a trait without decrypting
+//! every one. Protection records an HMAC search token for each hidden trait
+//! type and each hidden trait value, keyed from the owner's encryption key,
+//! and a `SearchIndex` maps tokens to the mints holding them. The index can
+//! sit next to the portfolio without revealing trait names or values: only
+//! the key holder can compute the token to look one up. It does reveal how
+//! many NFTs share each unknown token.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ring::hmac;
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use crate::models::Attribute;
+
+/// Current search index format version
+pub const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Domain label for the search key, derived from the encryption key
+pub(crate) const SEARCH_KEY_LABEL: &[u8] = b"quantum-veil/search/v1";
+
+/// Keyed token standing for a hidden trait type or trait value
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SearchToken(String);
+
+impl SearchToken {
+    /// Get the token as stored in the index
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Key computing search tokens
+pub struct SearchKey {
+    /// HMAC key
+    key: hmac::Key,
+}
+
+impl SearchKey {
+    /// Create a search key from key material
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: hmac::Key::new(hmac::HMAC_SHA256, key) }
+    }
+    
+    /// Get the token matching NFTs that hide a trait type, whatever its value
+    pub fn trait_token(&self, trait_type: &str) -> SearchToken {
+        self.token(&[b"trait", trait_type.as_bytes()])
+    }
+    
+    /// Get the token matching NFTs that hide a trait with an exact value
+    pub fn value_token(&self, trait_type: &str, value: &str) -> SearchToken {
+        self.token(&[b"value", trait_type.as_bytes(), value.as_bytes()])
+    }
+    
+    /// Get the tokens of hidden attributes, a trait token and a value token each
+    pub fn tokens(&self, attributes: &[Attribute]) -> Vec<SearchToken> {
+        attributes.iter()
+            .flat_map(|attr| [self.trait_token(&attr.trait_type), self.value_token(&attr.trait_type, &attr.value)])
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+    
+    /// MAC length-prefixed parts, so part boundaries can't be shifted
+    fn token(&self, parts: &[&[u8]]) -> SearchToken {
+        let mut context = hmac::Context::with_key(&self.key);
+        for part in parts {
+            context.update(&(part.len() as u32).to_le_bytes());
+            context.update(part);
+        }
+        SearchToken(URL_SAFE_NO_PAD.encode(context.sign()))
+    }
+}
+
+/// Inverted index from search tokens to the mints holding them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    /// Format version
+    pub version: u32,
+    /// Mints holding each token
+    pub postings: BTreeMap<SearchToken, BTreeSet<String>>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            version: SEARCH_INDEX_VERSION,
+            postings: BTreeMap::new(),
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Index an NFT's tokens, replacing those indexed for it before
+    pub fn insert(&mut self, nft_mint: &Pubkey, tokens: &[SearchToken]) {
+        self.remove(nft_mint);
+        for token in tokens {
+            self.postings.entry(token.clone()).or_default().insert(nft_mint.to_string());
+        }
+    }
+    
+    /// Drop an NFT from the index
+    pub fn remove(&mut self, nft_mint: &Pubkey) {
+        let mint = nft_mint.to_string();
+        self.postings.retain(|_, mints| {
+            mints.remove(&mint);
+            !mints.is_empty()
+        });
+    }
+    
+    /// Get the NFTs holding a token, ordered by mint
+    pub fn lookup(&self, token: &SearchToken) -> Vec<Pubkey> {
+        self.postings.get(token)
+            .into_iter()
+            .flatten()
+            .filter_map(|mint| Pubkey::from_str(mint).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_index_finds_hidden_traits_by_token() {
+        let key = SearchKey::new(&[7u8; 32]);
+        let attribute = |trait_type: &str, value: &str| Attribute { trait_type: trait_type.to_string(), value: value.to_string() };
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        
+        let mut index = SearchIndex::default();
+        index.insert(&first, &key.tokens(&[attribute("Mission", "Protect entropy"), attribute("Origin", "Void")]));
+        index.insert(&second, &key.tokens(&[attribute("Mission", "Break the loop")]));
+        
+        let mut both = vec![first, second];
+        both.sort_by_key(|mint| mint.to_string());
+        assert_eq!(index.lookup(&key.trait_token("Mission")), both);
+        assert_eq!(index.lookup(&key.value_token("Mission", "Break the loop")), vec![second]);
+        assert_eq!(index.lookup(&key.trait_token("Origin")), vec![first]);
+        
+        // Tokens are keyed, and nothing in the index names a trait
+        assert!(SearchKey::new(&[8u8; 32]).trait_token("Mission") != key.trait_token("Mission"));
+        let json = serde_json::to_string(&index).unwrap();
+        assert!(!json.contains("Mission") && !json.contains("Void"));
+        
+        // Re-indexing replaces an NFT's tokens
+        index.insert(&first, &key.tokens(&[attribute("Origin", "Void")]));
+        assert_eq!(index.lookup(&key.trait_token("Mission")), vec![second]);
+        index.remove(&first);
+        assert!(index.lookup(&key.trait_token("Origin")).is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! Wrapper snapshots
+//!
+//! A snapshot bundles everything needed to manage one NFT's privacy setup
+//! from another machine: the wrapper account as read on-chain, the VRM mask
+//! config, the fragment manifest and the NFT's keys sealed in a keystore.
+//! The owner signs it, so a restored snapshot is known to be theirs and
+//! unmodified. Only the keys are sealed; the rest stays readable so a
+//! snapshot can be inspected before it is restored.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use synchronicity_mask::SyncMaskConfig;
+use timeline_shifter::FragmentManifest;
+
+use crate::keystore::Keystore;
+use crate::models::WrapperState;
+
+/// Current snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Domain separator for snapshot signatures
+const SNAPSHOT_DOMAIN: &[u8] = b"quantum-veil/wrapper-snapshot/v1";
+
+/// Signed backup of one NFT's wrapper and local privacy state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapperSnapshot {
+    /// Format version
+    pub version: u32,
+    /// Wrapper program the wrapper belongs to
+    pub program_id: Pubkey,
+    /// Wrapper account
+    pub wrapper: Pubkey,
+    /// NFT the wrapper protects
+    pub nft_mint: Pubkey,
+    /// When the snapshot was taken, as a Unix timestamp
+    pub exported_at: i64,
+    /// Wrapper account data when the snapshot was taken, base64 encoded
+    pub wrapper_data: String,
+    /// VRM mask config, if the NFT has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask_config: Option<SyncMaskConfig>,
+    /// Manifest of the NFT's metadata fragments, if it was fractured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<FragmentManifest>,
+    /// Client encryption key and the NFT's key record, sealed under the export passphrase
+    pub keys: Keystore,
+    /// Owner that signed the snapshot
+    pub owner: Pubkey,
+    /// Ed25519 signature over the snapshot fields
+    pub signature: Signature,
+}
+
+impl WrapperSnapshot {
+    /// Create an unsigned snapshot of a wrapper's account data and sealed keys
+    pub fn new(
+        program_id: &Pubkey,
+        wrapper: &Pubkey,
+        nft_mint: &Pubkey,
+        wrapper_data: &[u8],
+        keys: Keystore,
+        exported_at: i64,
+    ) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            program_id: *program_id,
+            wrapper: *wrapper,
+            nft_mint: *nft_mint,
+            exported_at,
+            wrapper_data: base64::encode(wrapper_data),
+            mask_config: None,
+            manifest: None,
+            keys,
+            owner: Pubkey::default(),
+            signature: Signature::default(),
+        }
+    }
+    
+    /// Include the NFT's VRM mask config
+    pub fn with_mask_config(mut self, mask_config: SyncMaskConfig) -> Self {
+        self.mask_config = Some(mask_config);
+        self
+    }
+    
+    /// Include the manifest of the NFT's metadata fragments
+    pub fn with_manifest(mut self, manifest: FragmentManifest) -> Self {
+        self.manifest = Some(manifest);
+        self
+    }
+    
+    /// Sign the snapshot as the wrapper owner
+    pub fn sign(mut self, owner: &Keypair) -> Self {
+        self.owner = owner.pubkey();
+        self.signature = owner.sign_message(&self.message());
+        self
+    }
+    
+    /// Decode the wrapper account as it was when the snapshot was taken
+    pub fn wrapper_state(&self) -> Result<WrapperState, String> {
+        let data = base64::decode(&self.wrapper_data)
+            .map_err(|e| format!("Failed to decode wrapper data: {}", e))?;
+        
+        WrapperState::unpack(&data)
+    }
+    
+    /// Check the signature, and that the signer owned the wrapper of this NFT
+    pub fn verify(&self) -> Result<(), String> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {}", self.version));
+        }
+        
+        if !self.signature.verify(self.owner.as_ref(), &self.message()) {
+            return Err("Invalid wrapper snapshot signature".to_string());
+        }
+        
+        let state = self.wrapper_state()?;
+        if state.original_nft_mint != self.nft_mint {
+            return Err(format!("Snapshot wrapper protects {}, not {}", state.original_nft_mint, self.nft_mint));
+        }
+        if state.owner != self.owner {
+            return Err(format!("Snapshot was signed by {}, not the wrapper owner {}", self.owner, state.owner));
+        }
+        
+        Ok(())
+    }
+    
+    /// Serialize the snapshot as JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize snapshot: {}", e))
+    }
+    
+    /// Parse a snapshot from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse snapshot: {}", e))
+    }
+    
+    /// Get the signed message: the domain separator and snapshot fields
+    ///
+    /// The mask config and manifest are committed to by their hashes, which
+    /// don't depend on map ordering, so the message survives a JSON round trip.
+    fn message(&self) -> Vec<u8> {
+        let mut message = SNAPSHOT_DOMAIN.to_vec();
+        let mut push_field = |field: &[u8]| {
+            message.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            message.extend_from_slice(field);
+        };
+        
+        push_field(&self.version.to_le_bytes());
+        for key in [&self.program_id, &self.wrapper, &self.nft_mint, &self.owner] {
+            push_field(key.as_ref());
+        }
+        push_field(&self.exported_at.to_le_bytes());
+        push_field(self.wrapper_data.as_bytes());
+        push_field(self.mask_config.as_ref().map(|config| config.hash()).unwrap_or_default().as_bytes());
+        push_field(self.manifest.as_ref().map(|manifest| manifest.hash()).unwrap_or_default().as_bytes());
+        push_field(serde_json::to_string(&self.keys).unwrap_or_default().as_bytes());
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use quantum_veil::SecretBytes;
+    use crate::keystore::{KdfParams, KeyBundle};
+    use crate::test_utils::wrapper_account;
+    
+    #[test]
+    fn test_snapshot_verifies_only_as_signed_by_owner() {
+        let owner = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        let bundle = KeyBundle {
+            encryption_key: SecretBytes::from(&[7u8; 32][..]),
+            mints: BTreeMap::new(),
+            mnemonic_entropy: None,
+        };
+        let keys = Keystore::seal(&bundle, "0011223344556677", "passphrase", KdfParams { log_n: 4, r: 8, p: 1 }).unwrap();
+        let data = wrapper_account(&nft_mint, &owner.pubkey(), &[(Pubkey::new_unique(), 2)]);
+        
+        let snapshot = WrapperSnapshot::new(&Pubkey::new_unique(), &Pubkey::new_unique(), &nft_mint, &data, keys, 1_700_000_000)
+            .with_manifest(FragmentManifest::new(&nft_mint.to_string(), vec!["fragment-1".to_string()]))
+            .sign(&owner);
+        
+        // Survives a JSON round trip, as moved between machines
+        let restored = WrapperSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+        assert!(restored.verify().is_ok());
+        assert_eq!(restored.wrapper_state().unwrap().access_controls.len(), 1);
+        assert_eq!(restored.keys.open("passphrase").unwrap().encryption_key, bundle.encryption_key);
+        
+        // Altered snapshots, or ones signed by someone other than the owner, are rejected
+        let mut tampered = restored.clone();
+        tampered.manifest.as_mut().unwrap().fragment_ids.push("fragment-2".to_string());
+        assert!(tampered.verify().unwrap_err().contains("signature"));
+        
+        let stranger_signed = restored.sign(&Keypair::new());
+        assert!(stranger_signed.verify().unwrap_err().contains("not the wrapper owner"));
+    }
+}
@@ -0,0 +1,138 @@
+//! Solana Name Service (`.sol` domain) resolution
+//!
+//! Domain accounts are PDAs of the SPL name service program derived from the
+//! hashed name, an optional class, and the parent domain. The owner of a
+//! domain is stored in the name record header.
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::hashv, pubkey, pubkey::Pubkey};
+
+/// SPL name service program
+pub const NAME_PROGRAM_ID: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+
+/// Parent account of all `.sol` domains
+pub const SOL_TLD_AUTHORITY: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+
+/// Class of reverse lookup records
+pub const REVERSE_LOOKUP_CLASS: Pubkey = pubkey!("33m47vH6Eav6jr5Ry86XjhRft2jRBLDnDgPSHoquXi2Z");
+
+/// Prefix hashed with every name
+const HASH_PREFIX: &str = "SPL Name Service";
+
+/// Size of the name record header (parent, owner, class)
+const NAME_RECORD_HEADER_LEN: usize = 96;
+
+/// Check if a name is a `.sol` domain
+pub fn is_sol_domain(name: &str) -> bool {
+    name.len() > ".sol".len() && name.ends_with(".sol")
+}
+
+/// Hash a name the way the name service derives account seeds
+fn hashed_name(name: &str) -> [u8; 32] {
+    hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]).to_bytes()
+}
+
+/// Derive a name account address
+fn name_account_key(hashed_name: &[u8; 32], class: Option<&Pubkey>, parent: Option<&Pubkey>) -> Pubkey {
+    let class = class.copied().unwrap_or_default();
+    let parent = parent.copied().unwrap_or_default();
+    
+    Pubkey::find_program_address(
+        &[hashed_name, class.as_ref(), parent.as_ref()],
+        &NAME_PROGRAM_ID,
+    ).0
+}
+
+/// Derive the account address of a `.sol` domain or subdomain
+///
+/// Accepts `alice.sol` and `sub.alice.sol`; the `.sol` suffix is optional.
+pub fn domain_key(domain: &str) -> Result<Pubkey, String> {
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    let labels: Vec<&str> = name.split('.').collect();
+    
+    match labels.as_slice() {
+        [domain] if !domain.is_empty() => {
+            Ok(name_account_key(&hashed_name(domain), None, Some(&SOL_TLD_AUTHORITY)))
+        }
+        [sub, domain] if !sub.is_empty() && !domain.is_empty() => {
+            let parent = name_account_key(&hashed_name(domain), None, Some(&SOL_TLD_AUTHORITY));
+            // Subdomains are hashed with a leading zero byte
+            Ok(name_account_key(&hashed_name(&format!("\0{}", sub)), None, Some(&parent)))
+        }
+        _ => Err(format!("Invalid .sol domain: {}", domain)),
+    }
+}
+
+/// Resolve a `.sol` domain to the public key that owns it
+pub fn resolve_domain(rpc_client: &RpcClient, domain: &str) -> Result<Pubkey, String> {
+    let key = domain_key(domain)?;
+    
+    let data = rpc_client.get_account_data(&key)
+        .map_err(|e| format!("Failed to resolve {}: {}", domain, e))?;
+    
+    if data.len() < NAME_RECORD_HEADER_LEN {
+        return Err(format!("Invalid name record for {}", domain));
+    }
+    
+    // Header layout: parent name (32), owner (32), class (32)
+    let owner: [u8; 32] = data[32..64].try_into().unwrap();
+    Ok(Pubkey::new_from_array(owner))
+}
+
+/// Derive the reverse lookup record address for a public key
+pub fn reverse_lookup_key(owner: &Pubkey) -> Pubkey {
+    name_account_key(&hashed_name(&owner.to_string()), Some(&REVERSE_LOOKUP_CLASS), None)
+}
+
+/// Look up the `.sol` domain a public key has registered for display
+pub fn reverse_lookup(rpc_client: &RpcClient, owner: &Pubkey) -> Result<String, String> {
+    let data = rpc_client.get_account_data(&reverse_lookup_key(owner))
+        .map_err(|e| format!("No reverse lookup for {}: {}", owner, e))?;
+    
+    // The record body is a length-prefixed name after the header
+    let body = data.get(NAME_RECORD_HEADER_LEN..NAME_RECORD_HEADER_LEN + 4)
+        .ok_or_else(|| format!("Invalid reverse lookup record for {}", owner))?;
+    let len = u32::from_le_bytes(body.try_into().unwrap()) as usize;
+    
+    let start = NAME_RECORD_HEADER_LEN + 4;
+    let name = data.get(start..start + len)
+        .ok_or_else(|| format!("Invalid reverse lookup record for {}", owner))?;
+    let name = String::from_utf8(name.to_vec())
+        .map_err(|e| format!("Invalid reverse lookup name: {}", e))?;
+    
+    Ok(format!("{}.sol", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockRpc;
+    
+    #[test]
+    fn test_domain_key_matches_name_service() {
+        // Published key of the bonfida.sol domain account
+        assert_eq!(
+            domain_key("bonfida.sol").unwrap(),
+            pubkey!("Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb"),
+        );
+        assert_eq!(domain_key("bonfida").unwrap(), domain_key("bonfida.sol").unwrap());
+        assert_ne!(domain_key("dex.bonfida.sol").unwrap(), domain_key("bonfida.sol").unwrap());
+        assert!(domain_key(".sol").is_err());
+        assert!(domain_key("a.b.c.sol").is_err());
+    }
+    
+    #[test]
+    fn test_resolve_and_reverse_lookup_against_mock_rpc() {
+        let owner = Pubkey::new_unique();
+        
+        let mut record = vec![0u8; NAME_RECORD_HEADER_LEN];
+        record[32..64].copy_from_slice(owner.as_ref());
+        let rpc_client = MockRpc::new().with_account_data(&record).rpc_client();
+        assert_eq!(resolve_domain(&rpc_client, "alice.sol").unwrap(), owner);
+        
+        record.extend_from_slice(&5u32.to_le_bytes());
+        record.extend_from_slice(b"alice");
+        let rpc_client = MockRpc::new().with_account_data(&record).rpc_client();
+        assert_eq!(reverse_lookup(&rpc_client, &owner).unwrap(), "alice.sol");
+    }
+}
@@ -0,0 +1,282 @@
+//! Stealth addresses for access grants
+//!
+//! A viewer publishes a meta-address made of a scan key and a spend key.
+//! To grant access, the owner picks an ephemeral key `r`, derives a tweak
+//! from the shared point `r·scan` and grants to the one-time address
+//! `tweak·B + spend`, announcing `r·B` next to the grant. Only the scan
+//! secret recognises the address, only the spend secret signs for it, and
+//! nobody else can link the grant to the viewer or to their other grants.
+
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_POINT,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use ed25519_dalek::{ExpandedSecretKey, PublicKey};
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_512};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{Signer, SignerError},
+};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+/// Domain separator for one-time address tweaks
+const TWEAK_DOMAIN: &[u8] = b"quantum-veil/stealth/tweak/v1";
+
+/// Domain separator for stealth keys derived from a wallet
+const KEY_DOMAIN: &[u8] = b"quantum-veil/stealth/key/v1";
+
+/// Domain separator for one-time signing nonces
+const NONCE_DOMAIN: &[u8] = b"quantum-veil/stealth/nonce/v1";
+
+/// Prefix of encoded meta-addresses
+const META_ADDRESS_PREFIX: &str = "st:";
+
+/// Public keys a viewer publishes to receive stealth grants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthMetaAddress {
+    /// Key one-time addresses are recognised with
+    pub scan_key: Pubkey,
+    /// Key one-time addresses are spent with
+    pub spend_key: Pubkey,
+}
+
+/// One-time address together with the ephemeral key announced for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthAnnouncement {
+    /// One-time address the grant targets
+    pub address: Pubkey,
+    /// Ephemeral public key the viewer scans with
+    pub ephemeral: Pubkey,
+}
+
+/// Viewer's secret scan and spend keys
+pub struct StealthKeys {
+    /// Secret scan scalar
+    scan: Scalar,
+    /// Secret spend scalar
+    spend: Scalar,
+}
+
+/// Keypair of a one-time address, recovered by its viewer
+pub struct StealthKeypair {
+    /// One-time address
+    address: Pubkey,
+    /// Secret scalar for the address
+    secret: Scalar,
+    /// Nonce prefix for deterministic signatures
+    nonce_prefix: [u8; 32],
+}
+
+impl StealthKeys {
+    /// Generate fresh stealth keys
+    pub fn generate() -> Self {
+        Self { scan: random_scalar(), spend: random_scalar() }
+    }
+    
+    /// Derive stealth keys from a wallet, so they need no separate backup
+    pub fn from_keypair(keypair: &Keypair) -> Self {
+        let derive = |label: &[u8]| {
+            let mut hasher = Sha3_512::new();
+            hasher.update(KEY_DOMAIN);
+            hasher.update(label);
+            hasher.update(&keypair.to_bytes()[..32]);
+            Scalar::from_bytes_mod_order_wide(&wide_hash(hasher))
+        };
+        
+        Self { scan: derive(b"scan"), spend: derive(b"spend") }
+    }
+    
+    /// Get the meta-address to publish
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            scan_key: to_pubkey(&(self.scan * ED25519_BASEPOINT_POINT)),
+            spend_key: to_pubkey(&(self.spend * ED25519_BASEPOINT_POINT)),
+        }
+    }
+    
+    /// Recover the keypair of an announced address, if it is ours
+    pub fn recover(&self, announcement: &StealthAnnouncement) -> Option<StealthKeypair> {
+        let ephemeral = decompress(&announcement.ephemeral).ok()?;
+        let tweak = tweak(&(self.scan * ephemeral), &announcement.ephemeral);
+        let secret = tweak + self.spend;
+        
+        if to_pubkey(&(secret * ED25519_BASEPOINT_POINT)) != announcement.address {
+            return None;
+        }
+        
+        Some(StealthKeypair::new(announcement.address, secret))
+    }
+    
+    /// Find the announced addresses that are ours
+    pub fn scan(&self, announcements: &[StealthAnnouncement]) -> Vec<StealthKeypair> {
+        announcements.iter().filter_map(|announcement| self.recover(announcement)).collect()
+    }
+}
+
+impl Drop for StealthKeys {
+    fn drop(&mut self) {
+        self.scan.zeroize();
+        self.spend.zeroize();
+    }
+}
+
+impl StealthMetaAddress {
+    /// Derive a fresh one-time address for the viewer
+    pub fn derive_address(&self) -> Result<StealthAnnouncement, String> {
+        let scan_key = decompress(&self.scan_key)?;
+        let spend_key = decompress(&self.spend_key)?;
+        
+        let mut secret = random_scalar();
+        let ephemeral = to_pubkey(&(secret * ED25519_BASEPOINT_POINT));
+        let tweak = tweak(&(secret * scan_key), &ephemeral);
+        secret.zeroize();
+        
+        Ok(StealthAnnouncement {
+            address: to_pubkey(&(tweak * ED25519_BASEPOINT_POINT + spend_key)),
+            ephemeral,
+        })
+    }
+}
+
+impl fmt::Display for StealthMetaAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}:{}", META_ADDRESS_PREFIX, self.scan_key, self.spend_key)
+    }
+}
+
+impl FromStr for StealthMetaAddress {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scan_key, spend_key) = s.strip_prefix(META_ADDRESS_PREFIX)
+            .and_then(|keys| keys.split_once(':'))
+            .ok_or_else(|| format!("Invalid stealth meta-address: {}", s))?;
+        
+        let meta_address = Self {
+            scan_key: Pubkey::from_str(scan_key).map_err(|e| format!("Invalid scan key: {}", e))?,
+            spend_key: Pubkey::from_str(spend_key).map_err(|e| format!("Invalid spend key: {}", e))?,
+        };
+        decompress(&meta_address.scan_key)?;
+        decompress(&meta_address.spend_key)?;
+        
+        Ok(meta_address)
+    }
+}
+
+impl StealthKeypair {
+    /// Wrap the secret scalar of a one-time address
+    fn new(address: Pubkey, secret: Scalar) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(NONCE_DOMAIN);
+        hasher.update(secret.as_bytes());
+        let mut nonce_prefix = [0u8; 32];
+        nonce_prefix.copy_from_slice(&hasher.finalize()[..32]);
+        
+        Self { address, secret, nonce_prefix }
+    }
+}
+
+impl Signer for StealthKeypair {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.address)
+    }
+    
+    /// Sign as plain ed25519 under the one-time address, from its scalar
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let mut expanded = [0u8; 64];
+        expanded[..32].copy_from_slice(self.secret.as_bytes());
+        expanded[32..].copy_from_slice(&self.nonce_prefix);
+        let secret = ExpandedSecretKey::from_bytes(&expanded)
+            .map_err(|e| SignerError::Custom(format!("Invalid stealth key: {}", e)));
+        expanded.zeroize();
+        
+        let public = PublicKey::from_bytes(self.address.as_ref())
+            .map_err(|e| SignerError::Custom(format!("Invalid stealth address: {}", e)))?;
+        Ok(Signature::new(&secret?.sign(message, &public).to_bytes()))
+    }
+    
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for StealthKeypair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+        self.nonce_prefix.zeroize();
+    }
+}
+
+/// Hash a shared point, cleared of its cofactor, to an address tweak
+fn tweak(shared: &EdwardsPoint, ephemeral: &Pubkey) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.update(TWEAK_DOMAIN);
+    hasher.update(shared.mul_by_cofactor().compress().as_bytes());
+    hasher.update(ephemeral.as_ref());
+    Scalar::from_bytes_mod_order_wide(&wide_hash(hasher))
+}
+
+/// Decode a public key as a point, rejecting small-order ones
+fn decompress(key: &Pubkey) -> Result<EdwardsPoint, String> {
+    CompressedEdwardsY(key.to_bytes())
+        .decompress()
+        .filter(|point| !point.is_small_order())
+        .ok_or_else(|| format!("{} is not a valid stealth key", key))
+}
+
+/// Encode a point as a public key
+fn to_pubkey(point: &EdwardsPoint) -> Pubkey {
+    Pubkey::new_from_array(point.compress().to_bytes())
+}
+
+/// Finish a hash as the 64 bytes wide reductions take
+fn wide_hash(hasher: Sha3_512) -> [u8; 64] {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    wide
+}
+
+/// Draw a uniformly random scalar
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    OsRng.fill(&mut bytes[..]);
+    let scalar = Scalar::from_bytes_mod_order_wide(&bytes);
+    bytes.zeroize();
+    scalar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_only_viewer_finds_and_signs_for_stealth_address() {
+        let viewer = StealthKeys::from_keypair(&Keypair::new());
+        let other = StealthKeys::generate();
+        
+        let meta_address = StealthMetaAddress::from_str(&viewer.meta_address().to_string()).unwrap();
+        let first = meta_address.derive_address().unwrap();
+        let second = meta_address.derive_address().unwrap();
+        let unrelated = other.meta_address().derive_address().unwrap();
+        
+        // One-time addresses don't repeat or reveal the meta-address
+        assert_ne!(first.address, second.address);
+        assert_ne!(first.address, meta_address.spend_key);
+        
+        let found = viewer.scan(&[first, unrelated, second]);
+        assert_eq!(found.iter().map(|keypair| keypair.pubkey()).collect::<Vec<_>>(), vec![first.address, second.address]);
+        assert!(other.scan(&[first, second]).is_empty());
+        
+        // The recovered keypair signs for the one-time address
+        let signature = found[0].sign_message(b"resolve");
+        assert!(signature.verify(first.address.as_ref(), b"resolve"));
+        
+        assert!(StealthMetaAddress::from_str("st:not-a-key").is_err());
+    }
+}
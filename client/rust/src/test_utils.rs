@@ -0,0 +1,237 @@
+//! Hermetic test helpers for the privacy client
+//!
+//! Enabled for this crate's tests and for downstream crates via the
+//! `test-utils` feature.
+
+use solana_client::client_error::Result as ClientResult;
+use solana_client::mock_sender::{MockSender, Mocks};
+use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use async_trait::async_trait;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::client::GlitchGangPrivacyClient;
+use crate::models::{Attribute, File, GlitchGangMetadata, Properties, WRAPPER_HEADER_LEN};
+use crate::uploader::Uploader;
+
+use quantum_veil::QuantumVeil;
+use synchronicity_mask::SynchronicityMask;
+use timeline_shifter::{TimelineShifter, InMemoryAdapter};
+
+/// Mock RPC layer that answers requests without a live cluster
+///
+/// Unconfigured requests get the default successful responses of the
+/// solana-client mock sender; specific responses can be overridden, and
+/// lookups of specific accounts answered with their own data.
+#[derive(Clone, Default)]
+pub struct MockRpc {
+    /// Canned responses by RPC request
+    mocks: Mocks,
+    /// Account lookup responses by address
+    accounts: HashMap<Pubkey, serde_json::Value>,
+}
+
+impl MockRpc {
+    /// Create a new mock RPC layer with default responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Override the response for an RPC request
+    pub fn with_response(mut self, request: RpcRequest, response: serde_json::Value) -> Self {
+        self.mocks.insert(request, response);
+        self
+    }
+    
+    /// Answer account lookups with the given account data
+    pub fn with_account_data(self, data: &[u8]) -> Self {
+        self.with_response(RpcRequest::GetAccountInfo, account_response(&solana_sdk::system_program::id(), data))
+    }
+    
+    /// Answer lookups of one account with its owner and data, ahead of other overrides
+    pub fn with_account(mut self, address: &Pubkey, owner: &Pubkey, data: &[u8]) -> Self {
+        self.accounts.insert(*address, account_response(owner, data));
+        self
+    }
+    
+    /// Create an RPC client backed by this mock
+    pub fn rpc_client(&self) -> RpcClient {
+        let sender = AccountSender {
+            accounts: self.accounts.clone(),
+            mock: MockSender::new_with_mocks("succeeds", self.mocks.clone()),
+        };
+        
+        RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::default()))
+    }
+    
+    /// Create a privacy client whose components all use this mock
+    pub fn privacy_client(&self, owner_keypair: Keypair) -> GlitchGangPrivacyClient {
+        GlitchGangPrivacyClient::with_rpc_clients(
+            self.rpc_client(),
+            QuantumVeil::with_rpc_client(self.rpc_client()),
+            SynchronicityMask::with_rpc_client(self.rpc_client()),
+            owner_keypair,
+        )
+    }
+}
+
+/// Mock sender answering lookups of known accounts by address
+struct AccountSender {
+    /// Account lookup responses by address
+    accounts: HashMap<Pubkey, serde_json::Value>,
+    /// Sender answering every other request
+    mock: MockSender,
+}
+
+#[async_trait]
+impl RpcSender for AccountSender {
+    async fn send(&self, request: RpcRequest, params: serde_json::Value) -> ClientResult<serde_json::Value> {
+        if request == RpcRequest::GetAccountInfo {
+            let address = params[0].as_str().and_then(|address| address.parse::<Pubkey>().ok());
+            if let Some(response) = address.and_then(|address| self.accounts.get(&address)) {
+                return Ok(response.clone());
+            }
+        }
+        
+        self.mock.send(request, params).await
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.mock.get_transport_stats()
+    }
+    
+    fn url(&self) -> String {
+        self.mock.url()
+    }
+}
+
+/// Build a `getAccountInfo` response for an account
+fn account_response(owner: &Pubkey, data: &[u8]) -> serde_json::Value {
+    serde_json::json!({
+        "context": { "slot": 1 },
+        "value": {
+            "data": [base64::encode(data), "base64"],
+            "executable": false,
+            "lamports": 1_000_000,
+            "owner": owner.to_string(),
+            "rentEpoch": 0,
+        },
+    })
+}
+
+/// Uploader that keeps uploaded documents in memory
+///
+/// Clones share the same uploads, so tests can inspect what was published.
+#[derive(Clone, Default)]
+pub struct MemoryUploader {
+    /// Uploaded documents in order
+    uploads: Arc<RwLock<Vec<Vec<u8>>>>,
+    /// Whether uploads should fail
+    fail: bool,
+}
+
+impl MemoryUploader {
+    /// Create an uploader that accepts every upload
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create an uploader that rejects every upload
+    pub fn failing() -> Self {
+        Self { fail: true, ..Self::default() }
+    }
+    
+    /// Get the uploaded documents and files in order
+    pub fn uploads(&self) -> Vec<Vec<u8>> {
+        self.uploads.read().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Uploader for MemoryUploader {
+    async fn upload_file(&self, bytes: &[u8], _content_type: &str) -> Result<String, String> {
+        if self.fail {
+            return Err("Upload rejected".to_string());
+        }
+        
+        let mut uploads = self.uploads.write().unwrap();
+        uploads.push(bytes.to_vec());
+        Ok(format!("memory://{}", uploads.len() - 1))
+    }
+}
+
+/// Encode a Metaplex metadata account pointing at `uri`
+pub fn token_metadata_account(update_authority: &Pubkey, mint: &Pubkey, uri: &str) -> Vec<u8> {
+    // Strings are padded with NULs to their maximum length, as Metaplex stores them
+    let padded = |value: &str, max_len: usize| {
+        let mut bytes = (max_len as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.resize(4 + max_len, 0);
+        bytes
+    };
+    
+    let mut data = vec![4u8]; // MetadataV1 key
+    data.extend_from_slice(update_authority.as_ref());
+    data.extend_from_slice(mint.as_ref());
+    data.extend(padded("Glitch Gang #699", 32));
+    data.extend(padded("GG", 10));
+    data.extend(padded(uri, 200));
+    data.extend(500u16.to_le_bytes()); // Seller fee basis points
+    data.extend([0, 0, 1]); // No creators, primary sale not happened, mutable
+    data.resize(679, 0); // Remaining optional fields unset, as in a fixed-size account
+    data
+}
+
+/// Encode a current-layout privacy wrapper account with the given grants
+pub fn wrapper_account(nft_mint: &Pubkey, owner: &Pubkey, grants: &[(Pubkey, u8)]) -> Vec<u8> {
+    let config_hash = b"config-hash";
+    
+    let mut data = vec![2u8, config_hash.len() as u8]; // Version, config hash length
+    data.extend((grants.len() as u16).to_le_bytes());
+    data.extend_from_slice(nft_mint.as_ref());
+    data.extend_from_slice(owner.as_ref());
+    data.extend(1_700_000_000u64.to_le_bytes()); // Last updated
+    data.extend_from_slice(config_hash);
+    data.resize(WRAPPER_HEADER_LEN, 0);
+    
+    for (grantee, level) in grants {
+        data.extend_from_slice(grantee.as_ref());
+        data.push(*level);
+    }
+    data
+}
+
+/// Create a timeline shifter that stores every timeline in the given adapter
+pub fn memory_timeline_shifter(adapter: &InMemoryAdapter) -> TimelineShifter {
+    TimelineShifter::new(Box::new(adapter.clone()), HashMap::new())
+}
+
+/// Create sample Glitch Gang metadata with both public and sensitive attributes
+pub fn sample_metadata() -> GlitchGangMetadata {
+    let attribute = |trait_type: &str, value: &str| Attribute {
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    };
+    
+    GlitchGangMetadata {
+        name: "Glitch Gang #699 - VertexStream Navigator".to_string(),
+        symbol: "GG".to_string(),
+        description: "A mysterious entity from the Glitch Gang collective".to_string(),
+        attributes: vec![
+            attribute("Background", "Cyber Haze"),
+            attribute("Accessory", "Static Emitter"),
+            attribute("Origin", "Manifested from pure data corruption"),
+            attribute("Mission", "To protect the sanctity of digital entropy"),
+            attribute("Secret Code", "GLITCH-8983-ALPHA"),
+            attribute("Agent Name", "VertexStream Navigator"),
+        ],
+        image: "https://example.com/glitchgang699.png".to_string(),
+        properties: Properties {
+            files: vec![File { uri: "https://example.com/glitchgang699.png".to_string() }],
+        },
+        private_data: None,
+    }
+}
@@ -0,0 +1,128 @@
+//! Signed distribution transcripts
+//!
+//! Fracturing encrypts metadata and scatters it over storage backends, so
+//! nobody but the key holder can read it. A transcript lets anyone check it
+//! hasn't changed since: it lists each fragment's hash, location and storage
+//! receipt, commits to the manifest, and is signed by the owner. Published
+//! somewhere permanent such as Arweave, it lets a third party later confirm
+//! that the fragments reassembled into the metadata are the ones committed.
+
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use timeline_shifter::{FragmentManifest, FragmentReceipt};
+
+/// Current transcript format version
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// Domain separator for transcript signatures
+const TRANSCRIPT_DOMAIN: &[u8] = b"quantum-veil/distribution-transcript/v1";
+
+/// Owner-signed record of where a fracture's fragments were stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionTranscript {
+    /// Format version
+    pub version: u32,
+    /// NFT the fragments belong to, as named in the manifest
+    pub nft_mint: String,
+    /// Manifest epoch the fragments were stored in
+    pub epoch: u64,
+    /// Hash of the manifest, as published on-chain
+    pub manifest_hash: String,
+    /// Commitment and storage receipt of each fragment, decoys included
+    pub fragments: Vec<FragmentReceipt>,
+    /// When the transcript was made, as a Unix timestamp
+    pub created_at: i64,
+    /// Owner that signed the transcript
+    pub owner: Pubkey,
+    /// Ed25519 signature over the transcript fields
+    pub signature: Signature,
+}
+
+impl DistributionTranscript {
+    /// Create an unsigned transcript of a manifest's fragments
+    pub fn new(manifest: &FragmentManifest, created_at: i64) -> Result<Self, String> {
+        if manifest.receipts.is_empty() {
+            return Err(format!("Manifest for {} has no storage receipts", manifest.nft_mint));
+        }
+        
+        Ok(Self {
+            version: TRANSCRIPT_VERSION,
+            nft_mint: manifest.nft_mint.clone(),
+            epoch: manifest.epoch,
+            manifest_hash: manifest.hash(),
+            fragments: manifest.receipts.clone(),
+            created_at,
+            owner: Pubkey::default(),
+            signature: Signature::default(),
+        })
+    }
+    
+    /// Sign the transcript as the owner
+    pub fn sign(mut self, owner: &Keypair) -> Self {
+        self.owner = owner.pubkey();
+        self.signature = owner.sign_message(&self.message());
+        self
+    }
+    
+    /// Check the version and signature
+    pub fn verify(&self) -> Result<(), String> {
+        if self.version != TRANSCRIPT_VERSION {
+            return Err(format!("Unsupported transcript version {}", self.version));
+        }
+        
+        if !self.signature.verify(self.owner.as_ref(), &self.message()) {
+            return Err("Invalid distribution transcript signature".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Check a manifest is the one the transcript commits to
+    pub fn verify_manifest(&self, manifest: &FragmentManifest) -> Result<(), String> {
+        if manifest.hash() != self.manifest_hash {
+            return Err(format!("Manifest for {} does not match the transcript", manifest.nft_mint));
+        }
+        
+        Ok(())
+    }
+    
+    /// Serialize the transcript as JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize transcript: {}", e))
+    }
+    
+    /// Parse a transcript from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse transcript: {}", e))
+    }
+    
+    /// Get the signed message: the domain separator and transcript fields
+    fn message(&self) -> Vec<u8> {
+        let mut message = TRANSCRIPT_DOMAIN.to_vec();
+        let mut push_field = |field: &[u8]| {
+            message.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            message.extend_from_slice(field);
+        };
+        
+        push_field(&self.version.to_le_bytes());
+        push_field(self.nft_mint.as_bytes());
+        push_field(&self.epoch.to_le_bytes());
+        push_field(self.manifest_hash.as_bytes());
+        push_field(&(self.fragments.len() as u32).to_le_bytes());
+        for fragment in &self.fragments {
+            push_field(fragment.fragment_id.as_bytes());
+            push_field(format!("{:?}", fragment.timeline).as_bytes());
+            push_field(fragment.sha256.as_bytes());
+            push_field(serde_json::to_string(&fragment.location).unwrap_or_default().as_bytes());
+            push_field(fragment.receipt.as_bytes());
+            push_field(&fragment.stored_at.to_le_bytes());
+        }
+        push_field(&self.created_at.to_le_bytes());
+        push_field(self.owner.as_ref());
+        message
+    }
+}
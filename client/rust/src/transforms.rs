@@ -0,0 +1,158 @@
+//! Attribute generalization
+//!
+//! Some traits are worth showing approximately rather than hiding: a
+//! collector can see that an agent's power is in the 80s without learning
+//! it is exactly 87. A `Generalization` coarsens a trait value by bucketing
+//! numbers, truncating dates, or rolling categories up to a parent. The
+//! exact value is still encrypted with the other private attributes, so
+//! the owner can recover it.
+
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Precision a date is truncated to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatePrecision {
+    /// Decade, e.g. `2020s`
+    Decade,
+    /// Year, e.g. `2023`
+    Year,
+    /// Month, e.g. `2023-07`
+    Month,
+}
+
+/// How a trait value is coarsened before it is published
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "transform", rename_all = "snake_case")]
+pub enum Generalization {
+    /// Replace a number with the bucket it falls in, e.g. `87` with `80–90`
+    Bucket {
+        /// Width of each bucket
+        width: f64,
+        /// Value buckets are aligned to
+        #[serde(default)]
+        origin: f64,
+    },
+    /// Truncate an ISO 8601 date, e.g. `2023-07-14` to `2023-07`
+    TruncateDate {
+        /// Precision kept
+        precision: DatePrecision,
+    },
+    /// Replace a category with its parent, e.g. `Cyber Katana` with `Blade`
+    Rollup {
+        /// Parent of each known category
+        parents: BTreeMap<String, String>,
+        /// Parent of categories not listed, if they may be published
+        #[serde(default)]
+        other: Option<String>,
+    },
+}
+
+impl Generalization {
+    /// Bucket numbers into ranges `width` wide, aligned to zero
+    pub fn bucket(width: f64) -> Self {
+        Generalization::Bucket { width, origin: 0.0 }
+    }
+    
+    /// Truncate dates to a precision
+    pub fn truncate_date(precision: DatePrecision) -> Self {
+        Generalization::TruncateDate { precision }
+    }
+    
+    /// Roll categories up to parents, withholding unlisted ones
+    pub fn rollup<'a>(parents: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Generalization::Rollup {
+            parents: parents.into_iter().map(|(child, parent)| (child.to_string(), parent.to_string())).collect(),
+            other: None,
+        }
+    }
+    
+    /// Coarsen a value, or `None` if it doesn't fit the transform and must stay private
+    pub fn apply(&self, value: &str) -> Option<String> {
+        match self {
+            Generalization::Bucket { width, origin } => {
+                let number: f64 = value.trim().parse().ok()?;
+                if !number.is_finite() || *width <= 0.0 {
+                    return None;
+                }
+                
+                let lower = origin + ((number - origin) / width).floor() * width;
+                Some(format!("{}–{}", format_number(lower), format_number(lower + width)))
+            },
+            Generalization::TruncateDate { precision } => {
+                let (year, month) = parse_date(value.trim())?;
+                Some(match precision {
+                    DatePrecision::Decade => format!("{}s", year / 10 * 10),
+                    DatePrecision::Year => format!("{:04}", year),
+                    DatePrecision::Month => format!("{:04}-{:02}", year, month?),
+                })
+            },
+            Generalization::Rollup { parents, other } => {
+                parents.get(value).or(other.as_ref()).cloned()
+            },
+        }
+    }
+}
+
+/// Format a bucket bound without float noise, e.g. `0.3` rather than `0.30000000000000004`
+fn format_number(value: f64) -> String {
+    let formatted = format!("{:.6}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+/// Parse the year and, if present, month of an ISO 8601 date
+fn parse_date(value: &str) -> Option<(u32, Option<u32>)> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next().filter(|year| year.len() == 4)?.parse().ok()?;
+    
+    let month = match parts.next() {
+        Some(month) => {
+            let month: u32 = month.get(..2)?.parse().ok()?;
+            (1..=12).contains(&month).then_some(month)?;
+            Some(month)
+        },
+        None => None,
+    };
+    Some((year, month))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Attribute, PrivacyLevel};
+    use crate::policy::ProtectionPolicy;
+    
+    #[test]
+    fn test_generalized_traits_publish_coarse_values() {
+        assert_eq!(Generalization::bucket(10.0).apply("87").as_deref(), Some("80–90"));
+        assert_eq!(Generalization::bucket(0.1).apply("0.25").as_deref(), Some("0.2–0.3"));
+        assert_eq!(Generalization::bucket(10.0).apply("-3").as_deref(), Some("-10–0"));
+        assert_eq!(Generalization::bucket(10.0).apply("high"), None);
+        
+        assert_eq!(Generalization::truncate_date(DatePrecision::Month).apply("2023-07-14T09:30:00Z").as_deref(), Some("2023-07"));
+        assert_eq!(Generalization::truncate_date(DatePrecision::Decade).apply("2023-07-14").as_deref(), Some("2020s"));
+        assert_eq!(Generalization::truncate_date(DatePrecision::Month).apply("2023"), None);
+        
+        let rollup = Generalization::rollup([("Cyber Katana", "Blade"), ("Plasma Dirk", "Blade")]);
+        assert_eq!(rollup.apply("Plasma Dirk").as_deref(), Some("Blade"));
+        assert_eq!(rollup.apply("Railgun"), None);
+        
+        // Exact values stay encrypted; encryption wins at levels where both apply
+        let policy = ProtectionPolicy::new()
+            .with_generalization("Power", PrivacyLevel::Light, Generalization::bucket(10.0))
+            .with_generalization("Weapon", PrivacyLevel::Light, rollup)
+            .with_trait("Power", PrivacyLevel::Heavy);
+        let attributes = [("Power", "87"), ("Weapon", "Railgun"), ("Background", "Void")]
+            .map(|(trait_type, value)| Attribute { trait_type: trait_type.to_string(), value: value.to_string() });
+        
+        let (private, public) = policy.partition(&attributes, PrivacyLevel::Medium);
+        let values = |attributes: &[Attribute]| attributes.iter().map(|attr| format!("{}={}", attr.trait_type, attr.value)).collect::<Vec<_>>();
+        assert_eq!(values(&public), vec!["Power=80–90", "Background=Void"]);
+        assert_eq!(values(&private), vec!["Power=87", "Weapon=Railgun"]);
+        
+        let (_, public) = policy.partition(&attributes, PrivacyLevel::Heavy);
+        assert_eq!(values(&public), vec!["Background=Void"]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Unlocking the keystore without typing the passphrase
+//!
+//! A keystore can carry unlock slots alongside its passphrase. Each slot
+//! holds the passphrase-derived key wrapped under a key-encryption key from
+//! an `UnlockProvider`: a passkey evaluated with the WebAuthn PRF extension,
+//! so a fingerprint or face unlock opens the keystore, or a secret kept in
+//! the OS keychain. The passphrase always still works, so a lost passkey or
+//! keychain never locks the owner out.
+
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Source of a key-encryption key guarding keystore decryption
+pub trait UnlockProvider: Send + Sync {
+    /// Kind of provider, recorded in the slot, e.g. `passkey`
+    fn kind(&self) -> &'static str;
+    
+    /// Identifier of the slot this provider opens, e.g. a passkey credential ID
+    fn slot_id(&self) -> String;
+    
+    /// Get the key-encryption key for a new slot with a random salt
+    ///
+    /// Providers that store their key, like the keychain, create it here.
+    fn enroll(&self, salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String> {
+        self.unlock(salt)
+    }
+    
+    /// Get the key-encryption key of a slot, prompting the user if needed
+    fn unlock(&self, salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String>;
+}
+
+/// Platform authenticator performing WebAuthn assertions, e.g. Windows Hello or Touch ID
+///
+/// Implemented by the host application over its platform's WebAuthn or
+/// CTAP2 API, so the client doesn't depend on one.
+pub trait PasskeyAuthenticator: Send + Sync {
+    /// Assert with a credential and return the PRF extension output for `salt`
+    ///
+    /// The same credential and salt must always give the same output, which
+    /// the PRF (`hmac-secret`) extension guarantees.
+    fn prf(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String>;
+}
+
+/// Unlock provider deriving the key from a passkey with the WebAuthn PRF extension
+pub struct PasskeyUnlock {
+    /// Relying party the passkey is registered with
+    rp_id: String,
+    /// Passkey credential ID
+    credential_id: Vec<u8>,
+    /// Authenticator holding the passkey
+    authenticator: Arc<dyn PasskeyAuthenticator>,
+}
+
+impl PasskeyUnlock {
+    /// Unlock with a registered passkey that supports the PRF extension
+    pub fn new(rp_id: &str, credential_id: &[u8], authenticator: Arc<dyn PasskeyAuthenticator>) -> Self {
+        Self {
+            rp_id: rp_id.to_string(),
+            credential_id: credential_id.to_vec(),
+            authenticator,
+        }
+    }
+}
+
+impl UnlockProvider for PasskeyUnlock {
+    fn kind(&self) -> &'static str {
+        "passkey"
+    }
+    
+    fn slot_id(&self) -> String {
+        format!("passkey:{}:{}", self.rp_id, base64::encode(&self.credential_id))
+    }
+    
+    fn unlock(&self, salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String> {
+        self.authenticator.prf(&self.rp_id, &self.credential_id, salt)
+    }
+}
+
+/// Unlock provider keeping a random key in the OS keychain
+#[cfg(feature = "keychain")]
+pub struct KeychainUnlock {
+    /// Keychain service name
+    service: String,
+    /// Keychain account name
+    account: String,
+}
+
+#[cfg(feature = "keychain")]
+impl KeychainUnlock {
+    /// Keep the key under a keychain service and account, e.g. the app name and key ID
+    pub fn new(service: &str, account: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            account: account.to_string(),
+        }
+    }
+    
+    /// Open the keychain entry
+    fn entry(&self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(&self.service, &self.account).map_err(|e| format!("Failed to open keychain entry: {}", e))
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl UnlockProvider for KeychainUnlock {
+    fn kind(&self) -> &'static str {
+        "keychain"
+    }
+    
+    fn slot_id(&self) -> String {
+        format!("keychain:{}:{}", self.service, self.account)
+    }
+    
+    fn enroll(&self, _salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String> {
+        use rand::{Rng, rngs::OsRng};
+        
+        let mut key = Zeroizing::new([0u8; 32]);
+        OsRng.fill(&mut key[..]);
+        self.entry()?
+            .set_password(&base64::encode(&key[..]))
+            .map_err(|e| format!("Failed to store key in keychain: {}", e))?;
+        
+        Ok(key)
+    }
+    
+    fn unlock(&self, _salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String> {
+        let encoded = Zeroizing::new(self.entry()?
+            .get_password()
+            .map_err(|e| format!("Failed to read key from keychain: {}", e))?);
+        let decoded = Zeroizing::new(base64::decode(&*encoded)
+            .map_err(|e| format!("Failed to decode keychain key: {}", e))?);
+        
+        let mut key = Zeroizing::new([0u8; 32]);
+        if decoded.len() != key.len() {
+            return Err("Invalid keychain key length".to_string());
+        }
+        key.copy_from_slice(&decoded);
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::{KdfParams, KeyBundle, Keystore};
+    use quantum_veil::SecretBytes;
+    use ring::hmac;
+    use std::collections::BTreeMap;
+    
+    /// Authenticator computing the PRF with a fixed secret per credential
+    struct MockAuthenticator;
+    
+    impl PasskeyAuthenticator for MockAuthenticator {
+        fn prf(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<Zeroizing<[u8; 32]>, String> {
+            let secret = [rp_id.as_bytes(), credential_id].concat();
+            let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &secret), salt);
+            
+            let mut output = Zeroizing::new([0u8; 32]);
+            output.copy_from_slice(tag.as_ref());
+            Ok(output)
+        }
+    }
+    
+    #[test]
+    fn test_passkey_unlocks_keystore_with_passphrase_fallback() {
+        let bundle = KeyBundle {
+            encryption_key: SecretBytes::from(&[7u8; 32][..]),
+            mints: BTreeMap::new(),
+            mnemonic_entropy: None,
+        };
+        let mut keystore = Keystore::seal(&bundle, "0011223344556677", "correct horse", KdfParams { log_n: 4, r: 8, p: 1 }).unwrap();
+        let passkey = PasskeyUnlock::new("glitch.gang", b"credential-1", Arc::new(MockAuthenticator));
+        let other = PasskeyUnlock::new("glitch.gang", b"credential-2", Arc::new(MockAuthenticator));
+        
+        assert!(keystore.add_unlock("battery staple", &passkey).is_err());
+        keystore.add_unlock("correct horse", &passkey).unwrap();
+        keystore.add_unlock("correct horse", &passkey).unwrap();
+        assert_eq!(keystore.unlock_slots.len(), 1);
+        
+        let keystore = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+        assert_eq!(keystore.open_with(&passkey).unwrap().encryption_key, bundle.encryption_key);
+        assert_eq!(keystore.open("correct horse").unwrap().encryption_key, bundle.encryption_key);
+        
+        // An unenrolled passkey falls back to the passphrase when one is given
+        assert!(keystore.open_with(&other).unwrap_err().contains("No unlock slot"));
+        assert_eq!(keystore.unlock(Some(&other), Some("correct horse")).unwrap().encryption_key, bundle.encryption_key);
+        assert!(keystore.unlock(Some(&other), None).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Live wrapper account updates
+//!
+//! `GlitchGangPrivacyClient::subscribe_wrapper` watches a wrapper over the
+//! RPC node's PubSub websocket and turns each account change into typed
+//! events by comparing it with the state before.
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::models::WrapperState;
+
+/// Change to a privacy wrapper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WrapperUpdate {
+    /// Ownership moved to a new account
+    OwnerChanged {
+        /// Previous owner
+        previous: Pubkey,
+        /// New owner
+        owner: Pubkey,
+    },
+    /// Privacy config hash changed
+    ConfigHashChanged {
+        /// Previous hash
+        previous: String,
+        /// New hash
+        hash: String,
+    },
+    /// Access was granted, or a grant's level changed
+    AccessGranted {
+        /// Account granted access
+        grantee: Pubkey,
+        /// Access level now held
+        level: u8,
+    },
+    /// A grant was removed
+    AccessRevoked {
+        /// Account that lost access
+        grantee: Pubkey,
+    },
+}
+
+impl WrapperUpdate {
+    /// List the changes from one wrapper state to the next
+    ///
+    /// Grant changes are ordered by grantee, so the same change
+    /// always yields the same events.
+    pub fn diff(previous: &WrapperState, current: &WrapperState) -> Vec<Self> {
+        let mut updates = Vec::new();
+        
+        if previous.owner != current.owner {
+            updates.push(WrapperUpdate::OwnerChanged { previous: previous.owner, owner: current.owner });
+        }
+        if previous.privacy_config_hash != current.privacy_config_hash {
+            updates.push(WrapperUpdate::ConfigHashChanged {
+                previous: previous.privacy_config_hash.clone(),
+                hash: current.privacy_config_hash.clone(),
+            });
+        }
+        
+        let mut grants = Vec::new();
+        for (grantee, level) in &current.access_controls {
+            if previous.access_controls.get(grantee) != Some(level) {
+                grants.push(WrapperUpdate::AccessGranted { grantee: *grantee, level: *level });
+            }
+        }
+        for grantee in previous.access_controls.keys() {
+            if !current.access_controls.contains_key(grantee) {
+                grants.push(WrapperUpdate::AccessRevoked { grantee: *grantee });
+            }
+        }
+        grants.sort_by_key(|update| update.account());
+        updates.extend(grants);
+        
+        updates
+    }
+    
+    /// Get the account a grant event is about
+    fn account(&self) -> Option<Pubkey> {
+        match self {
+            WrapperUpdate::AccessGranted { grantee, .. } | WrapperUpdate::AccessRevoked { grantee } => Some(*grantee),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    
+    #[test]
+    fn test_diff_reports_each_change() {
+        let viewer = Pubkey::new_unique();
+        let agent = Pubkey::new_unique();
+        
+        let previous = WrapperState {
+            original_nft_mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            privacy_config_hash: "hash-1".to_string(),
+            access_controls: HashMap::from([(viewer, 1), (agent, 2)]),
+            last_updated: 1,
+        };
+        assert!(WrapperUpdate::diff(&previous, &previous).is_empty());
+        
+        let current = WrapperState {
+            owner: Pubkey::new_unique(),
+            privacy_config_hash: "hash-2".to_string(),
+            access_controls: HashMap::from([(viewer, 3)]),
+            last_updated: 2,
+            ..previous.clone()
+        };
+        
+        let updates = WrapperUpdate::diff(&previous, &current);
+        assert_eq!(updates[0], WrapperUpdate::OwnerChanged { previous: previous.owner, owner: current.owner });
+        assert_eq!(updates[1], WrapperUpdate::ConfigHashChanged { previous: "hash-1".to_string(), hash: "hash-2".to_string() });
+        assert_eq!(updates.len(), 4);
+        assert!(updates.contains(&WrapperUpdate::AccessGranted { grantee: viewer, level: 3 }));
+        assert!(updates.contains(&WrapperUpdate::AccessRevoked { grantee: agent }));
+    }
+}
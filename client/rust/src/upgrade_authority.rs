@@ -0,0 +1,203 @@
+//! Upgrade authority of the deployed wrapper program
+//!
+//! Whoever holds the program's upgrade authority can replace its code, so it
+//! should be a multisig or governance address rather than a hot key. Handing
+//! it over is done in two steps: the current authority proposes a transfer
+//! by signing the loader's `SetAuthorityChecked` instruction, and the new
+//! authority accepts by co-signing it. The loader refuses the instruction
+//! without both signatures, so a mistyped or uncontrolled address can never
+//! end up holding the program. Nothing here sets the authority to `None`,
+//! which would make the program immutable for good.
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::Instruction,
+    loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+/// Deployment details of an upgradeable program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpgradeAuthority {
+    /// `ProgramData` account holding the program's code
+    pub program_data: Pubkey,
+    /// Slot the program was last deployed or upgraded in
+    pub last_deploy_slot: u64,
+    /// Account allowed to upgrade the program, or `None` if it is immutable
+    pub authority: Option<Pubkey>,
+}
+
+/// Fetch the upgrade authority of a program
+pub fn fetch_upgrade_authority(rpc_client: &RpcClient, program_id: &Pubkey) -> Result<UpgradeAuthority, String> {
+    let program_data = bpf_loader_upgradeable::get_program_data_address(program_id);
+    let account = rpc_client.get_account(&program_data)
+        .map_err(|e| format!("Failed to fetch program data {}: {}", program_data, e))?;
+    
+    if account.owner != bpf_loader_upgradeable::id() {
+        return Err(format!("Program {} is not deployed with the upgradeable loader", program_id));
+    }
+    
+    parse_program_data(&program_data, &account.data)
+}
+
+/// Read the deployment slot and upgrade authority from `ProgramData` account data
+pub fn parse_program_data(program_data: &Pubkey, data: &[u8]) -> Result<UpgradeAuthority, String> {
+    // Only the metadata prefix is state; the program's code follows it
+    let metadata = data.get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or_else(|| format!("Program data {} is too short", program_data))?;
+    
+    match bincode::deserialize(metadata) {
+        Ok(UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }) => Ok(UpgradeAuthority {
+            program_data: *program_data,
+            last_deploy_slot: slot,
+            authority: upgrade_authority_address,
+        }),
+        Ok(_) => Err(format!("Account {} is not program data", program_data)),
+        Err(e) => Err(format!("Failed to parse program data {}: {}", program_data, e)),
+    }
+}
+
+/// Build the instruction handing the upgrade authority to `new_authority`
+///
+/// Both authorities must sign it. A multisig or governance authority signs
+/// by executing it from a proposal, in a transaction the current authority
+/// also signs.
+pub fn transfer_instruction(program_id: &Pubkey, current_authority: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    bpf_loader_upgradeable::set_upgrade_authority_checked(program_id, current_authority, new_authority)
+}
+
+/// Check that `current_authority` can propose handing the program to `new_authority`
+pub fn check_proposal(
+    deployed: &UpgradeAuthority,
+    current_authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<(), String> {
+    match deployed.authority {
+        None => Err("Program is immutable and has no upgrade authority".to_string()),
+        Some(authority) if authority != *current_authority => {
+            Err(format!("{} is not the upgrade authority; {} is", current_authority, authority))
+        }
+        Some(_) if new_authority == current_authority => {
+            Err(format!("{} already holds the upgrade authority", new_authority))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Check that a proposed transaction only hands `program_id` to `new_authority`
+///
+/// Run by the new authority before co-signing, so it signs nothing else.
+/// Returns the authority the transfer is proposed by.
+pub fn check_acceptance(
+    transaction: &Transaction,
+    program_id: &Pubkey,
+    new_authority: &Pubkey,
+) -> Result<Pubkey, String> {
+    let message = &transaction.message;
+    if message.instructions.len() != 1 {
+        return Err("Authority transfer must be the transaction's only instruction".to_string());
+    }
+    let instruction = &message.instructions[0];
+    
+    let account = |index: usize| {
+        instruction.accounts.get(index)
+            .and_then(|&key_index| message.account_keys.get(key_index as usize))
+            .copied()
+            .ok_or_else(|| "Authority transfer is missing accounts".to_string())
+    };
+    
+    if message.account_keys.get(instruction.program_id_index as usize) != Some(&bpf_loader_upgradeable::id()) {
+        return Err("Authority transfer must be an upgradeable loader instruction".to_string());
+    }
+    
+    // The unchecked `SetAuthority` would hand over the program without the new authority's signature
+    if !matches!(bincode::deserialize(&instruction.data), Ok(UpgradeableLoaderInstruction::SetAuthorityChecked)) {
+        return Err("Authority transfer must use SetAuthorityChecked".to_string());
+    }
+    
+    if account(0)? != bpf_loader_upgradeable::get_program_data_address(program_id) {
+        return Err(format!("Authority transfer is not for program {}", program_id));
+    }
+    
+    if account(2)? != *new_authority {
+        return Err(format!("Authority transfer is not to {}", new_authority));
+    }
+    
+    account(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, signature::{Keypair, Signer}};
+    
+    fn program_data(authority: Option<Pubkey>) -> Vec<u8> {
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 42,
+            upgrade_authority_address: authority,
+        };
+        let mut data = bincode::serialize(&state).unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        
+        // The program's code follows the metadata
+        data.extend_from_slice(&[0x7f, b'E', b'L', b'F']);
+        data
+    }
+    
+    #[test]
+    fn test_parse_program_data_reads_authority() {
+        let program_data_address = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        
+        let deployed = parse_program_data(&program_data_address, &program_data(Some(authority))).unwrap();
+        assert_eq!(deployed.authority, Some(authority));
+        assert_eq!(deployed.last_deploy_slot, 42);
+        
+        let immutable = parse_program_data(&program_data_address, &program_data(None)).unwrap();
+        assert_eq!(immutable.authority, None);
+        
+        assert!(parse_program_data(&program_data_address, &[3, 0, 0]).is_err());
+        let buffer = bincode::serialize(&UpgradeableLoaderState::Buffer { authority_address: Some(authority) }).unwrap();
+        assert!(parse_program_data(&program_data_address, &[buffer, vec![0; 64]].concat()).is_err());
+    }
+    
+    #[test]
+    fn test_authority_transfer_needs_both_signatures() {
+        let program_id = Pubkey::new_unique();
+        let current = Keypair::new();
+        let multisig = Keypair::new();
+        let deployed = UpgradeAuthority {
+            program_data: bpf_loader_upgradeable::get_program_data_address(&program_id),
+            last_deploy_slot: 42,
+            authority: Some(current.pubkey()),
+        };
+        
+        // Only the current authority proposes, and only to someone else
+        assert!(check_proposal(&deployed, &current.pubkey(), &multisig.pubkey()).is_ok());
+        assert!(check_proposal(&deployed, &multisig.pubkey(), &current.pubkey()).is_err());
+        assert!(check_proposal(&deployed, &current.pubkey(), &current.pubkey()).is_err());
+        assert!(check_proposal(&UpgradeAuthority { authority: None, ..deployed }, &current.pubkey(), &multisig.pubkey()).is_err());
+        
+        // The proposal carries the current authority's signature, not the new one's
+        let blockhash = Hash::new_unique();
+        let instruction = transfer_instruction(&program_id, &current.pubkey(), &multisig.pubkey());
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&current.pubkey()));
+        transaction.try_partial_sign(&[&current], blockhash).unwrap();
+        assert!(!transaction.is_signed());
+        
+        // The new authority checks what it accepts before co-signing
+        assert_eq!(check_acceptance(&transaction, &program_id, &multisig.pubkey()).unwrap(), current.pubkey());
+        assert!(check_acceptance(&transaction, &program_id, &Pubkey::new_unique()).is_err());
+        assert!(check_acceptance(&transaction, &Pubkey::new_unique(), &multisig.pubkey()).is_err());
+        
+        transaction.try_partial_sign(&[&multisig], blockhash).unwrap();
+        assert!(transaction.is_signed());
+        
+        // The unchecked instruction is refused
+        let unchecked = bpf_loader_upgradeable::set_upgrade_authority(&program_id, &current.pubkey(), Some(&multisig.pubkey()));
+        let transaction = Transaction::new_with_payer(&[unchecked], Some(&current.pubkey()));
+        assert!(check_acceptance(&transaction, &program_id, &multisig.pubkey()).unwrap_err().contains("SetAuthorityChecked"));
+    }
+}
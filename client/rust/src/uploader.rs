@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use network::ProxyConfig;
+
+/// Hosts protected metadata JSON and assets so the NFT URI can point at them
+#[async_trait]
+pub trait Uploader: Send + Sync {
+    /// Upload a file, returning the URI it can be fetched from
+    async fn upload_file(&self, bytes: &[u8], content_type: &str) -> Result<String, String>;
+    
+    /// Upload a JSON document, returning the URI it can be fetched from
+    async fn upload_json(&self, json: &[u8]) -> Result<String, String> {
+        self.upload_file(json, "application/json").await
+    }
+}
+
+/// Uploader adding documents through an IPFS node's HTTP API
+pub struct IpfsUploader {
+    /// IPFS API endpoint URL
+    pub endpoint: String,
+    /// Gateway used to build the returned URI
+    pub gateway: String,
+    /// Optional basic auth token for hosted nodes
+    pub auth_token: Option<String>,
+    /// HTTP client uploads are sent with
+    pub http_client: reqwest::Client,
+}
+
+impl IpfsUploader {
+    /// Create an uploader for an IPFS API endpoint
+    pub fn new(endpoint: &str, gateway: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            gateway: gateway.trim_end_matches('/').to_string(),
+            auth_token: None,
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Create an uploader for Infura's IPFS API
+    pub fn with_infura(project_id: &str, project_secret: &str) -> Self {
+        let auth = format!("{}:{}", project_id, project_secret);
+        
+        Self {
+            auth_token: Some(base64::encode(auth)),
+            ..Self::new("https://ipfs.infura.io:5001", "https://ipfs.io")
+        }
+    }
+    
+    /// Send uploads through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+}
+
+/// Response of the IPFS `add` endpoint
+#[derive(Deserialize)]
+struct IpfsAddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+#[async_trait]
+impl Uploader for IpfsUploader {
+    async fn upload_file(&self, bytes: &[u8], content_type: &str) -> Result<String, String> {
+        let file_name = if content_type == "application/json" { "metadata.json" } else { "asset" };
+        let part = reqwest::multipart::Part::bytes(bytes.to_vec())
+            .file_name(file_name)
+            .mime_str(content_type)
+            .map_err(|e| format!("Failed to build IPFS upload: {}", e))?;
+        
+        let mut request = self.http_client
+            .post(format!("{}/api/v0/add?pin=true", self.endpoint))
+            .multipart(reqwest::multipart::Form::new().part("file", part));
+        if let Some(auth_token) = &self.auth_token {
+            request = request.header("Authorization", format!("Basic {}", auth_token));
+        }
+        
+        let response: IpfsAddResponse = send_json(request).await?;
+        Ok(format!("{}/ipfs/{}", self.gateway, response.hash))
+    }
+}
+
+/// Uploader storing documents with web3.storage
+pub struct Web3StorageUploader {
+    /// web3.storage API endpoint URL
+    pub endpoint: String,
+    /// Gateway used to build the returned URI
+    pub gateway: String,
+    /// API token
+    pub api_token: String,
+    /// HTTP client uploads are sent with
+    pub http_client: reqwest::Client,
+}
+
+impl Web3StorageUploader {
+    /// Create an uploader with a web3.storage API token
+    pub fn new(api_token: &str) -> Self {
+        Self {
+            endpoint: "https://api.web3.storage".to_string(),
+            gateway: "https://w3s.link".to_string(),
+            api_token: api_token.to_string(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Use a different API endpoint, e.g. a compatible self-hosted service
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+    
+    /// Send uploads through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+}
+
+/// Response of the web3.storage `upload` endpoint
+#[derive(Deserialize)]
+struct Web3StorageResponse {
+    cid: String,
+}
+
+#[async_trait]
+impl Uploader for Web3StorageUploader {
+    async fn upload_file(&self, bytes: &[u8], content_type: &str) -> Result<String, String> {
+        let request = self.http_client
+            .post(format!("{}/upload", self.endpoint))
+            .bearer_auth(&self.api_token)
+            .header("Content-Type", content_type)
+            .body(bytes.to_vec());
+        
+        let response: Web3StorageResponse = send_json(request).await?;
+        Ok(format!("{}/ipfs/{}", self.gateway, response.cid))
+    }
+}
+
+/// Uploader posting documents to an Arweave bundler service
+///
+/// The bundler signs and pays for the Arweave transaction, so no Arweave
+/// wallet is needed client-side.
+pub struct ArweaveUploader {
+    /// Bundler upload endpoint URL
+    pub endpoint: String,
+    /// Gateway used to build the returned URI
+    pub gateway: String,
+    /// Optional API key for the bundler
+    pub api_key: Option<String>,
+    /// HTTP client uploads are sent with
+    pub http_client: reqwest::Client,
+}
+
+impl ArweaveUploader {
+    /// Create an uploader for a bundler endpoint
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            gateway: "https://arweave.net".to_string(),
+            api_key: None,
+            http_client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Authenticate with the bundler
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+    
+    /// Send uploads through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+}
+
+/// Response of the bundler upload endpoint
+#[derive(Deserialize)]
+struct ArweaveUploadResponse {
+    id: String,
+}
+
+#[async_trait]
+impl Uploader for ArweaveUploader {
+    async fn upload_file(&self, bytes: &[u8], content_type: &str) -> Result<String, String> {
+        let mut request = self.http_client
+            .post(format!("{}/tx", self.endpoint))
+            .header("Content-Type", content_type)
+            .body(bytes.to_vec());
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        
+        let response: ArweaveUploadResponse = send_json(request).await?;
+        Ok(format!("{}/{}", self.gateway, response.id))
+    }
+}
+
+/// Send an upload request and parse its JSON response
+async fn send_json<T: for<'de> Deserialize<'de>>(request: reqwest::RequestBuilder) -> Result<T, String> {
+    let response = request.send().await
+        .map_err(|e| format!("Failed to upload: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Upload rejected: {}", e))?;
+    
+    response.json().await
+        .map_err(|e| format!("Failed to parse upload response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_uploaders_return_gateway_uris() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/api/v0/add?pin=true")
+            .match_header("authorization", "Basic dXNlcjpwYXNz")
+            .with_body(r#"{"Name":"metadata.json","Hash":"QmHash","Size":"2"}"#)
+            .create_async()
+            .await;
+        server.mock("POST", "/upload")
+            .match_header("authorization", "Bearer w3s-token")
+            .with_body(r#"{"cid":"bafyCid"}"#)
+            .create_async()
+            .await;
+        server.mock("POST", "/tx")
+            .match_body("{}")
+            .with_body(r#"{"id":"arweaveTx"}"#)
+            .create_async()
+            .await;
+        
+        let ipfs = IpfsUploader {
+            auth_token: Some(base64::encode("user:pass")),
+            ..IpfsUploader::new(&server.url(), "https://ipfs.io")
+        };
+        assert_eq!(ipfs.upload_json(b"{}").await.unwrap(), "https://ipfs.io/ipfs/QmHash");
+        
+        let web3 = Web3StorageUploader::new("w3s-token").with_endpoint(&server.url());
+        assert_eq!(web3.upload_json(b"{}").await.unwrap(), "https://w3s.link/ipfs/bafyCid");
+        
+        let arweave = ArweaveUploader::new(&server.url());
+        assert_eq!(arweave.upload_json(b"{}").await.unwrap(), "https://arweave.net/arweaveTx");
+    }
+    
+    #[tokio::test]
+    async fn test_rejected_upload_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("POST", "/tx").with_status(402).create_async().await;
+        
+        let result = ArweaveUploader::new(&server.url()).upload_json(b"{}").await;
+        assert!(result.unwrap_err().contains("rejected"));
+    }
+}
@@ -0,0 +1,248 @@
+use image::{DynamicImage, ImageOutputFormat};
+use ring::digest;
+use solana_sdk::pubkey::Pubkey;
+use std::io::Cursor;
+
+/// Domain label for viewer fingerprints
+const FINGERPRINT_LABEL: &[u8] = b"quantum-veil/watermark";
+
+/// Marker preceding the fingerprint in image watermarks
+const IMAGE_MARKER: u16 = 0x5156;
+
+/// Bits in an image watermark: marker and fingerprint
+const PAYLOAD_BITS: usize = 16 + 64;
+
+/// Key under the glTF `asset.extras` holding a model watermark
+const GLTF_EXTRAS_KEY: &str = "qvWatermark";
+
+/// Magic bytes opening a binary glTF (VRM) file
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+
+/// Chunk type of the JSON chunk in a binary glTF file
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+
+/// Fingerprint of a viewer embedded in the assets disclosed to them
+pub type Fingerprint = [u8; 8];
+
+/// Get the fingerprint of a viewer's public key
+pub fn fingerprint(viewer: &Pubkey) -> Fingerprint {
+    let digest = digest::digest(&digest::SHA256, &[FINGERPRINT_LABEL, viewer.as_ref()].concat());
+    digest.as_ref()[..8].try_into().unwrap()
+}
+
+/// Embed a viewer's fingerprint in an image or VRM file
+///
+/// Images are re-encoded as PNG with the fingerprint repeated through the
+/// blue channel's low bits, so it survives small edits but not lossy
+/// re-encoding. VRM files carry it in the glTF asset extras.
+pub fn watermark_asset(asset: &[u8], viewer: &Pubkey) -> Result<Vec<u8>, String> {
+    let fingerprint = fingerprint(viewer);
+    
+    if asset.starts_with(GLB_MAGIC) {
+        return watermark_glb(asset, &fingerprint);
+    }
+    
+    let image = image::load_from_memory(asset)
+        .map_err(|_| "Unsupported asset type for watermarking".to_string())?;
+    watermark_image(image, &fingerprint)
+}
+
+/// Read the fingerprint embedded in an image or VRM file, if any
+pub fn extract_watermark(asset: &[u8]) -> Option<Fingerprint> {
+    if asset.starts_with(GLB_MAGIC) {
+        let (json, _) = split_glb(asset).ok()?;
+        let hex = json["asset"]["extras"][GLTF_EXTRAS_KEY].as_str()?;
+        return from_hex(hex);
+    }
+    
+    extract_image(&image::load_from_memory(asset).ok()?)
+}
+
+/// Check if an asset carries a viewer's watermark
+pub fn verify_watermark(asset: &[u8], viewer: &Pubkey) -> bool {
+    extract_watermark(asset) == Some(fingerprint(viewer))
+}
+
+/// Find which of the candidate viewers a leaked asset was disclosed to
+pub fn identify_viewer(asset: &[u8], candidates: &[Pubkey]) -> Option<Pubkey> {
+    let found = extract_watermark(asset)?;
+    candidates.iter().find(|viewer| fingerprint(viewer) == found).copied()
+}
+
+/// Get bit `index` of the image payload
+fn payload_bit(fingerprint: &Fingerprint, index: usize) -> u8 {
+    let mut payload = IMAGE_MARKER.to_be_bytes().to_vec();
+    payload.extend_from_slice(fingerprint);
+    (payload[index / 8] >> (7 - index % 8)) & 1
+}
+
+/// Write the payload repeatedly through the blue channel's least significant bits
+fn watermark_image(image: DynamicImage, fingerprint: &Fingerprint) -> Result<Vec<u8>, String> {
+    let mut pixels = image.to_rgba8();
+    if (pixels.width() * pixels.height()) as usize < PAYLOAD_BITS {
+        return Err("Image too small to watermark".to_string());
+    }
+    
+    for (index, pixel) in pixels.pixels_mut().enumerate() {
+        pixel[2] = (pixel[2] & !1) | payload_bit(fingerprint, index % PAYLOAD_BITS);
+    }
+    
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(pixels).write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode watermarked image: {}", e))?;
+    
+    Ok(png.into_inner())
+}
+
+/// Recover the payload by majority vote over every repetition
+fn extract_image(image: &DynamicImage) -> Option<Fingerprint> {
+    let pixels = image.to_rgba8();
+    let mut votes = [0i64; PAYLOAD_BITS];
+    
+    for (index, pixel) in pixels.pixels().enumerate() {
+        votes[index % PAYLOAD_BITS] += if pixel[2] & 1 == 1 { 1 } else { -1 };
+    }
+    
+    let mut payload = [0u8; PAYLOAD_BITS / 8];
+    for (index, vote) in votes.iter().enumerate() {
+        if *vote > 0 {
+            payload[index / 8] |= 1 << (7 - index % 8);
+        }
+    }
+    
+    if u16::from_be_bytes([payload[0], payload[1]]) != IMAGE_MARKER {
+        return None;
+    }
+    
+    payload[2..].try_into().ok()
+}
+
+/// Set the watermark in the asset extras of a binary glTF file
+fn watermark_glb(asset: &[u8], fingerprint: &Fingerprint) -> Result<Vec<u8>, String> {
+    let (mut json, rest) = split_glb(asset)?;
+    
+    let gltf_asset = json.get_mut("asset")
+        .and_then(|value| value.as_object_mut())
+        .ok_or("glTF file has no asset section")?;
+    let extras = gltf_asset.entry("extras").or_insert_with(|| serde_json::json!({}));
+    extras.as_object_mut()
+        .ok_or("glTF asset extras are not an object")?
+        .insert(GLTF_EXTRAS_KEY.to_string(), serde_json::Value::String(to_hex(fingerprint)));
+    
+    let mut json_bytes = serde_json::to_vec(&json)
+        .map_err(|e| format!("Failed to serialize glTF JSON: {}", e))?;
+    while json_bytes.len() % 4 != 0 {
+        json_bytes.push(b' ');
+    }
+    
+    // Rebuild the header and JSON chunk around the untouched binary chunks
+    let total_len = 12 + 8 + json_bytes.len() + rest.len();
+    let mut glb = Vec::with_capacity(total_len);
+    glb.extend_from_slice(&asset[..8]);
+    glb.extend_from_slice(&(total_len as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_bytes.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&json_bytes);
+    glb.extend_from_slice(rest);
+    
+    Ok(glb)
+}
+
+/// Split a binary glTF file into its parsed JSON chunk and the chunks after it
+fn split_glb(asset: &[u8]) -> Result<(serde_json::Value, &[u8]), String> {
+    let read_u32 = |offset: usize| {
+        asset.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| "glTF file truncated".to_string())
+    };
+    
+    let json_len = read_u32(12)? as usize;
+    if read_u32(16)? != GLB_JSON_CHUNK {
+        return Err("glTF file does not start with a JSON chunk".to_string());
+    }
+    
+    let json_bytes = asset.get(20..20 + json_len).ok_or("glTF file truncated")?;
+    let json = serde_json::from_slice(json_bytes)
+        .map_err(|e| format!("Failed to parse glTF JSON: {}", e))?;
+    
+    Ok((json, &asset[20 + json_len..]))
+}
+
+/// Format bytes as lowercase hex
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse a hex fingerprint
+fn from_hex(hex: &str) -> Option<Fingerprint> {
+    if hex.len() != 16 {
+        return None;
+    }
+    
+    let mut fingerprint = [0u8; 8];
+    for (i, byte) in fingerprint.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    
+    Some(fingerprint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    
+    fn sample_png() -> Vec<u8> {
+        let image = RgbImage::from_fn(40, 30, |x, y| Rgb([(x * 5) as u8, (y * 7) as u8, ((x + y) * 3) as u8]));
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image).write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        png.into_inner()
+    }
+    
+    fn sample_glb() -> Vec<u8> {
+        let json = br#"{"asset":{"version":"2.0"}}  "#;
+        let bin = [1u8, 2, 3, 4];
+        let total_len = 12 + 8 + json.len() + 8 + bin.len();
+        
+        let mut glb = b"glTF".to_vec();
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total_len as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+        glb.extend_from_slice(json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&bin);
+        glb
+    }
+    
+    #[test]
+    fn test_image_watermark_identifies_viewer() {
+        let viewer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let original = sample_png();
+        assert_eq!(extract_watermark(&original), None);
+        
+        let marked = watermark_asset(&original, &viewer).unwrap();
+        assert!(verify_watermark(&marked, &viewer));
+        assert!(!verify_watermark(&marked, &other));
+        assert_eq!(identify_viewer(&marked, &[other, viewer]), Some(viewer));
+        
+        // The watermark changes pixels by at most one level
+        let (before, after) = (image::load_from_memory(&original).unwrap().to_rgba8(), image::load_from_memory(&marked).unwrap().to_rgba8());
+        assert!(before.pixels().zip(after.pixels()).all(|(a, b)| (a[2] as i16 - b[2] as i16).abs() <= 1 && a[0] == b[0]));
+    }
+    
+    #[test]
+    fn test_model_watermark_keeps_binary_chunk() {
+        let viewer = Pubkey::new_unique();
+        let original = sample_glb();
+        
+        let marked = watermark_asset(&original, &viewer).unwrap();
+        assert!(verify_watermark(&marked, &viewer));
+        assert!(marked.ends_with(&original[original.len() - 12..]));
+        assert_eq!(u32::from_le_bytes(marked[8..12].try_into().unwrap()) as usize, marked.len());
+        
+        assert!(watermark_asset(b"plain text", &viewer).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use quantum_veil::{decrypt_data, encrypt_data};
+
+const PAYLOAD_SIZES: [usize; 5] = [64, 1024, 16 * 1024, 256 * 1024, 1024 * 1024];
+
+fn bench_chacha20(c: &mut Criterion) {
+    let key = [7u8; 32];
+    let nonce = [3u8; 12];
+    
+    let mut group = c.benchmark_group("chacha20poly1305");
+    
+    for size in PAYLOAD_SIZES {
+        let payload = vec![0xA5u8; size];
+        let ciphertext = encrypt_data(&payload, &key, &nonce).unwrap();
+        
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("encrypt", size), &payload, |b, payload| {
+            b.iter(|| encrypt_data(black_box(payload), &key, &nonce).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", size), &ciphertext, |b, ciphertext| {
+            b.iter(|| decrypt_data(black_box(ciphertext), &key, &nonce).unwrap())
+        });
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_chacha20);
+criterion_main!(benches);
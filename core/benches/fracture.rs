@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::HashMap;
+
+use timeline_shifter::{FractureOptions, InMemoryAdapter, TimelineShifter, TimelineType};
+
+const METADATA_SIZES: [usize; 4] = [1024, 64 * 1024, 1024 * 1024, 10 * 1024 * 1024];
+
+fn memory_shifter(adapter: &InMemoryAdapter) -> TimelineShifter {
+    TimelineShifter::new(Box::new(adapter.clone()), HashMap::new())
+}
+
+fn bench_fracture_reassemble(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let key = [7u8; 32];
+    
+    let mut group = c.benchmark_group("timeline_shifter");
+    group.sample_size(10);
+    
+    for size in METADATA_SIZES {
+        let metadata = vec![0x5Au8; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        
+        group.bench_with_input(BenchmarkId::new("fracture", size), &metadata, |b, metadata| {
+            b.iter(|| {
+                let adapter = InMemoryAdapter::new();
+                let shifter = memory_shifter(&adapter);
+                
+                runtime.block_on(shifter.fracture_metadata(
+                    "mint",
+                    black_box(metadata),
+                    &key,
+                    TimelineType::default_distribution(),
+                    &FractureOptions::default(),
+                )).unwrap()
+            })
+        });
+        
+        // Reassemble through a fresh shifter so every fragment comes from storage
+        let adapter = InMemoryAdapter::new();
+        let manifest = runtime.block_on(memory_shifter(&adapter).fracture_metadata(
+            "mint",
+            &metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        )).unwrap();
+        
+        group.bench_with_input(BenchmarkId::new("reassemble", size), &manifest, |b, manifest| {
+            b.iter(|| {
+                let shifter = memory_shifter(&adapter);
+                runtime.block_on(shifter.reassemble_from_manifest(black_box(manifest), &key)).unwrap()
+            })
+        });
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_fracture_reassemble);
+criterion_main!(benches);
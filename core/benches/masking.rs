@@ -0,0 +1,146 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use synchronicity_mask::{
+    add_joint_noise_batch, add_position_noise, add_position_noise_batch, add_rotation_noise,
+    AccessPermission, GestureData, PositionData, PrivacyLevel, RotationData, SynchronicityMask,
+    VoiceData, VrmData, VrmDataType,
+};
+
+/// Build a frame with a full humanoid joint set and voice spectrum
+fn sample_frame() -> VrmData {
+    let mut joint_rotations = HashMap::new();
+    for i in 0..55 {
+        joint_rotations.insert(format!("joint_{}", i), RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 });
+    }
+    
+    let mut data = VrmData::new();
+    data.position = PositionData { x: 10.5, y: 2.0, z: -3.2 };
+    data.voice = Some(VoiceData {
+        frequency: vec![440.0; 256],
+        amplitude: vec![0.5; 256],
+        pitch: 1.0,
+        timbre: 0.5,
+    });
+    data.add_gesture(GestureData {
+        name: "wave".to_string(),
+        intensity: 0.8,
+        speed: 1.2,
+        joint_rotations,
+    });
+    data
+}
+
+fn setup_mask(level: PrivacyLevel) -> (SynchronicityMask, String, String) {
+    let mut mask = SynchronicityMask::offline();
+    let nft_mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    
+    mask.create_config(&nft_mint, &owner, level);
+    mask.add_trusted_agent(&nft_mint.to_string(), "agent1.glitch.gang").unwrap();
+    mask.update_access_permission(
+        &nft_mint.to_string(),
+        VrmDataType::Voice,
+        AccessPermission::Restricted(vec!["agent2.glitch.gang".to_string()]),
+    ).unwrap();
+    
+    (mask, nft_mint.to_string(), owner.to_string())
+}
+
+fn bench_apply_mask_per_viewer(c: &mut Criterion) {
+    let frame = sample_frame();
+    let mut group = c.benchmark_group("apply_mask_per_viewer");
+    
+    for level in [PrivacyLevel::Light, PrivacyLevel::Heavy, PrivacyLevel::Complete] {
+        let (mask, nft_mint, owner) = setup_mask(level);
+        
+        let viewers: [(&str, Option<&str>); 4] = [
+            ("anonymous", None),
+            ("restricted", Some("agent2.glitch.gang")),
+            ("trusted", Some("agent1.glitch.gang")),
+            ("owner", Some(owner.as_str())),
+        ];
+        
+        for (name, viewer) in viewers {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", level), name),
+                &viewer,
+                |b, viewer| b.iter(|| mask.apply_mask(&nft_mint, black_box(&frame), *viewer).unwrap()),
+            );
+        }
+    }
+    
+    group.finish();
+}
+
+fn bench_batch_masking(c: &mut Criterion) {
+    let (mask, nft_mint, _) = setup_mask(PrivacyLevel::Medium);
+    let mut group = c.benchmark_group("batch_masking");
+    
+    for batch_size in [10usize, 100, 1000] {
+        let frames: Vec<VrmData> = (0..batch_size).map(|_| sample_frame()).collect();
+        
+        group.throughput(Throughput::Elements(batch_size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(batch_size), &frames, |b, frames| {
+            b.iter(|| {
+                frames.iter()
+                    .map(|frame| mask.apply_mask(&nft_mint, black_box(frame), None).unwrap())
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+    
+    group.finish();
+}
+
+/// Compare per-value noise with the vectorized batch path, e.g. for crowds of skeletons
+fn bench_joint_noise(c: &mut Criterion) {
+    let mut group = c.benchmark_group("joint_noise");
+    
+    for joint_count in [55usize, 256, 1024] {
+        let joints: HashMap<String, RotationData> = (0..joint_count)
+            .map(|i| (format!("joint_{}", i), RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }))
+            .collect();
+        let positions = vec![PositionData { x: 1.0, y: 2.0, z: 3.0 }; joint_count];
+        
+        group.throughput(Throughput::Elements(joint_count as u64));
+        group.bench_with_input(BenchmarkId::new("rotation_scalar", joint_count), &joints, |b, joints| {
+            b.iter(|| {
+                let mut joints = joints.clone();
+                for (i, rotation) in joints.values_mut().enumerate() {
+                    add_rotation_noise(rotation, 0.35, black_box(42 + i as u64));
+                }
+                joints
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rotation_batch", joint_count), &joints, |b, joints| {
+            b.iter(|| {
+                let mut joints = joints.clone();
+                add_joint_noise_batch(&mut joints, 0.35, black_box(42));
+                joints
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("position_scalar", joint_count), &positions, |b, positions| {
+            b.iter(|| {
+                let mut positions = positions.clone();
+                for (i, position) in positions.iter_mut().enumerate() {
+                    add_position_noise(position, 0.35, black_box(42 + i as u64));
+                }
+                positions
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("position_batch", joint_count), &positions, |b, positions| {
+            b.iter(|| {
+                let mut positions = positions.clone();
+                add_position_noise_batch(&mut positions, 0.35, black_box(42));
+                positions
+            })
+        });
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_apply_mask_per_viewer, bench_batch_masking, bench_joint_noise);
+criterion_main!(benches);
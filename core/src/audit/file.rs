@@ -0,0 +1,81 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::{AuditEntry, AuditSink};
+
+/// Audit sink appending one JSON entry per line to a file
+pub struct FileSink {
+    /// Path of the log file
+    path: PathBuf,
+    /// Serializes appends from concurrent writers
+    lock: Mutex<()>,
+}
+
+impl FileSink {
+    /// Create a sink for a log file, which is created on first append
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl AuditSink for FileSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        
+        let _guard = self.lock.lock().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log: {}", e))?;
+        
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to append audit entry: {}", e))?;
+        file.sync_data().map_err(|e| format!("Failed to sync audit log: {}", e))
+    }
+    
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to open audit log: {}", e)),
+        };
+        
+        BufReader::new(file)
+            .lines()
+            .map(|line| {
+                let line = line.map_err(|e| format!("Failed to read audit log: {}", e))?;
+                serde_json::from_str(&line).map_err(|e| format!("Failed to parse audit entry: {}", e))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{verify_chain, AuditEvent, AuditLog};
+    use std::sync::Arc;
+    
+    #[test]
+    fn test_file_chain_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("quantum-veil-audit-{}.jsonl", rand::random::<u64>()));
+        let event = AuditEvent::KeyRotated { nft_mint: "mint".to_string() };
+        
+        AuditLog::new(Arc::new(FileSink::new(&path))).record(event.clone()).unwrap();
+        let log = AuditLog::new(Arc::new(FileSink::new(&path)));
+        assert_eq!(log.record(event).unwrap().sequence, 1);
+        log.verify().unwrap();
+        
+        let entries = FileSink::new(&path).entries().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        verify_chain(&entries).unwrap();
+    }
+}
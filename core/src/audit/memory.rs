@@ -0,0 +1,30 @@
+use std::sync::{Arc, RwLock};
+
+use super::{AuditEntry, AuditSink};
+
+/// In-memory audit sink
+///
+/// Clones share the same entries, so tests can inspect what a component logged.
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    /// Appended entries
+    entries: Arc<RwLock<Vec<AuditEntry>>>,
+}
+
+impl MemorySink {
+    /// Create a new, empty in-memory sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AuditSink for MemorySink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        self.entries.write().unwrap().push(entry.clone());
+        Ok(())
+    }
+    
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        Ok(self.entries.read().unwrap().clone())
+    }
+}
@@ -0,0 +1,297 @@
+// Import sub-modules
+pub mod file;
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "onchain")]
+pub mod webhook;
+
+// Re-export sinks
+pub use file::FileSink;
+pub use memory::MemorySink;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+#[cfg(feature = "onchain")]
+pub use webhook::WebhookSink;
+
+use ring::digest;
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Hash that precedes the first entry of every chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Privacy-sensitive operation recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Data was decrypted with an NFT's privacy key
+    Decrypted {
+        /// NFT mint address
+        nft_mint: String,
+        /// Size of the decrypted data in bytes
+        bytes: usize,
+    },
+    /// An NFT's privacy key was rotated
+    KeyRotated {
+        /// NFT mint address
+        nft_mint: String,
+    },
+    /// A viewer was shown unmasked VRM data
+    ViewerUnmasked {
+        /// NFT mint address
+        nft_mint: String,
+        /// Viewer that saw through the mask
+        viewer: String,
+        /// Why the viewer saw unmasked data, e.g. `owner`, `trusted_agent`, `access_token`
+        reason: String,
+    },
+    /// A viewer's mask queries looked abusive, e.g. an attempt to average out noise
+    QueryAnomaly {
+        /// NFT mint address
+        nft_mint: String,
+        /// Viewer that made the queries
+        viewer: String,
+        /// What was detected, e.g. `rate_exceeded`, `repeated_probe`
+        reason: String,
+    },
+    /// Metadata was fractured across timelines
+    MetadataFractured {
+        /// NFT mint address
+        nft_mint: String,
+        /// Number of fragments stored, including decoys
+        fragment_count: usize,
+    },
+    /// Metadata was reassembled and decrypted from fragments
+    MetadataReassembled {
+        /// Fragments that were combined
+        fragment_ids: Vec<String>,
+    },
+    /// A fragment was read from storage
+    FragmentRetrieved {
+        /// Fragment ID
+        fragment_id: String,
+        /// NFT mint address the fragment belongs to, if known
+        nft_mint: Option<String>,
+        /// Who the fragment was fetched for, if known, e.g. a gateway client
+        requester: Option<String>,
+        /// Time the storage backend took, in milliseconds
+        latency_ms: u64,
+        /// Whether the fragment was found
+        found: bool,
+    },
+}
+
+/// Entry in the hash-chained audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the chain, starting at 0
+    pub sequence: u64,
+    /// Unix timestamp of the operation
+    pub timestamp: u64,
+    /// Recorded operation
+    pub event: AuditEvent,
+    /// Hash of the previous entry, or `GENESIS_HASH` for the first
+    pub prev_hash: String,
+    /// Hash of this entry's fields and `prev_hash`
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Compute the hash an entry should carry
+    pub fn compute_hash(sequence: u64, timestamp: u64, event: &AuditEvent, prev_hash: &str) -> String {
+        let event_json = serde_json::to_string(event).unwrap_or_default();
+        
+        let mut hasher = digest::Context::new(&digest::SHA256);
+        hasher.update(prev_hash.as_bytes());
+        hasher.update(&sequence.to_le_bytes());
+        hasher.update(&timestamp.to_le_bytes());
+        hasher.update(event_json.as_bytes());
+        
+        to_hex(hasher.finish().as_ref())
+    }
+}
+
+/// Destination for audit entries
+///
+/// Sinks only append; chaining and verification are handled by `AuditLog`.
+pub trait AuditSink: Send + Sync {
+    /// Append an entry
+    fn append(&self, entry: &AuditEntry) -> Result<(), String>;
+    
+    /// Read back all entries in order
+    fn entries(&self) -> Result<Vec<AuditEntry>, String>;
+}
+
+/// Audit sink writing to several sinks, e.g. a webhook and a local file
+///
+/// Entries are read back from the first sink that supports it.
+pub struct MultiSink {
+    /// Sinks in write order
+    sinks: Vec<Arc<dyn AuditSink>>,
+}
+
+impl MultiSink {
+    /// Create a sink writing to all of `sinks`
+    pub fn new(sinks: Vec<Arc<dyn AuditSink>>) -> Self {
+        Self { sinks }
+    }
+}
+
+impl AuditSink for MultiSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        for sink in &self.sinks {
+            sink.append(entry)?;
+        }
+        Ok(())
+    }
+    
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        self.sinks.iter()
+            .find_map(|sink| sink.entries().ok())
+            .ok_or_else(|| "No readable audit sink".to_string())
+    }
+}
+
+/// Append-only, hash-chained audit log
+pub struct AuditLog {
+    /// Where entries are written
+    sink: Arc<dyn AuditSink>,
+    /// Sequence and hash of the next entry's predecessor
+    head: Mutex<(u64, String)>,
+}
+
+impl AuditLog {
+    /// Open an audit log, continuing the chain already in the sink
+    ///
+    /// Sinks that cannot be read back start a new chain.
+    pub fn new(sink: Arc<dyn AuditSink>) -> Self {
+        let head = match sink.entries().ok().and_then(|entries| entries.last().cloned()) {
+            Some(last) => (last.sequence + 1, last.hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        
+        Self {
+            sink,
+            head: Mutex::new(head),
+        }
+    }
+    
+    /// Record an operation
+    pub fn record(&self, event: AuditEvent) -> Result<AuditEntry, String> {
+        // Hold the head for the whole append so concurrent writers cannot fork the chain
+        let mut head = self.head.lock().unwrap();
+        let (sequence, prev_hash) = head.clone();
+        
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        
+        let entry = AuditEntry {
+            sequence,
+            timestamp,
+            hash: AuditEntry::compute_hash(sequence, timestamp, &event, &prev_hash),
+            event,
+            prev_hash,
+        };
+        
+        self.sink.append(&entry)
+            .map_err(|e| format!("Failed to write audit entry: {}", e))?;
+        *head = (sequence + 1, entry.hash.clone());
+        
+        Ok(entry)
+    }
+    
+    /// Read back all recorded entries in order
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        self.sink.entries()
+    }
+    
+    /// Verify the chain stored in the sink
+    pub fn verify(&self) -> Result<(), String> {
+        verify_chain(&self.sink.entries()?)
+    }
+}
+
+/// Verify that entries form an unbroken, untampered hash chain
+///
+/// Returns an error naming the first entry that does not verify.
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), String> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    
+    for (expected_sequence, entry) in entries.iter().enumerate() {
+        if entry.sequence != expected_sequence as u64 {
+            return Err(format!(
+                "Audit chain broken at entry {}: expected sequence {}",
+                entry.sequence, expected_sequence
+            ));
+        }
+        
+        if entry.prev_hash != prev_hash {
+            return Err(format!("Audit chain broken at entry {}: previous hash mismatch", entry.sequence));
+        }
+        
+        let hash = AuditEntry::compute_hash(entry.sequence, entry.timestamp, &entry.event, &entry.prev_hash);
+        if entry.hash != hash {
+            return Err(format!("Audit entry {} has been tampered with", entry.sequence));
+        }
+        
+        prev_hash = hash;
+    }
+    
+    Ok(())
+}
+
+/// Format bytes as lowercase hex
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn decrypted(bytes: usize) -> AuditEvent {
+        AuditEvent::Decrypted { nft_mint: "mint".to_string(), bytes }
+    }
+    
+    #[test]
+    fn test_chain_verifies_and_detects_tampering() {
+        let sink = Arc::new(MemorySink::new());
+        let log = AuditLog::new(sink.clone());
+        for bytes in 0..5 {
+            log.record(decrypted(bytes)).unwrap();
+        }
+        log.verify().unwrap();
+        
+        let entries = sink.entries().unwrap();
+        
+        // Edited event
+        let mut tampered = entries.clone();
+        tampered[2].event = decrypted(99);
+        assert!(verify_chain(&tampered).unwrap_err().contains("entry 2"));
+        
+        // Dropped entry
+        let mut truncated = entries.clone();
+        truncated.remove(1);
+        assert!(verify_chain(&truncated).is_err());
+        
+        // Rehashed entry still breaks its successor
+        let mut rehashed = entries;
+        rehashed[2].event = decrypted(99);
+        rehashed[2].hash = AuditEntry::compute_hash(2, rehashed[2].timestamp, &rehashed[2].event, &rehashed[2].prev_hash);
+        assert!(verify_chain(&rehashed).unwrap_err().contains("entry 3"));
+    }
+    
+    #[test]
+    fn test_reopened_log_continues_chain() {
+        let sink = Arc::new(MemorySink::new());
+        AuditLog::new(sink.clone()).record(decrypted(1)).unwrap();
+        
+        let entry = AuditLog::new(sink.clone()).record(decrypted(2)).unwrap();
+        assert_eq!(entry.sequence, 1);
+        verify_chain(&sink.entries().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use rusqlite::{params, Connection};
+use std::sync::Mutex;
+
+use super::{AuditEntry, AuditSink};
+
+/// Audit sink storing entries in a SQLite table
+pub struct SqliteSink {
+    /// Database connection
+    connection: Mutex<Connection>,
+}
+
+impl SqliteSink {
+    /// Open a database file, creating the audit table if needed
+    pub fn open(path: &str) -> Result<Self, String> {
+        let connection = Connection::open(path)
+            .map_err(|e| format!("Failed to open audit database: {}", e))?;
+        Self::with_connection(connection)
+    }
+    
+    /// Use an existing connection, creating the audit table if needed
+    pub fn with_connection(connection: Connection) -> Result<Self, String> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                sequence INTEGER PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                prev_hash TEXT NOT NULL,
+                hash TEXT NOT NULL
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create audit table: {}", e))?;
+        
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+impl AuditSink for SqliteSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let event = serde_json::to_string(&entry.event)
+            .map_err(|e| format!("Failed to serialize audit event: {}", e))?;
+        
+        self.connection.lock().unwrap().execute(
+            "INSERT INTO audit_log (sequence, timestamp, event, prev_hash, hash) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![entry.sequence as i64, entry.timestamp as i64, event, entry.prev_hash, entry.hash],
+        ).map_err(|e| format!("Failed to insert audit entry: {}", e))?;
+        
+        Ok(())
+    }
+    
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT sequence, timestamp, event, prev_hash, hash FROM audit_log ORDER BY sequence")
+            .map_err(|e| format!("Failed to query audit log: {}", e))?;
+        
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        }).map_err(|e| format!("Failed to query audit log: {}", e))?;
+        
+        rows.map(|row| {
+            let (sequence, timestamp, event, prev_hash, hash) = row
+                .map_err(|e| format!("Failed to read audit entry: {}", e))?;
+            
+            Ok(AuditEntry {
+                sequence: sequence as u64,
+                timestamp: timestamp as u64,
+                event: serde_json::from_str(&event)
+                    .map_err(|e| format!("Failed to parse audit event: {}", e))?,
+                prev_hash,
+                hash,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditEvent, AuditLog};
+    use std::sync::Arc;
+    
+    #[test]
+    fn test_sqlite_chain_verifies() {
+        let sink = Arc::new(SqliteSink::with_connection(Connection::open_in_memory().unwrap()).unwrap());
+        let log = AuditLog::new(sink.clone());
+        
+        log.record(AuditEvent::KeyRotated { nft_mint: "mint".to_string() }).unwrap();
+        log.record(AuditEvent::Decrypted { nft_mint: "mint".to_string(), bytes: 64 }).unwrap();
+        log.verify().unwrap();
+        
+        // Tampering through SQL is detected
+        sink.connection.lock().unwrap()
+            .execute("UPDATE audit_log SET event = replace(event, '64', '65')", [])
+            .unwrap();
+        assert!(log.verify().is_err());
+    }
+}
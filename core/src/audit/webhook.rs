@@ -0,0 +1,53 @@
+use crate::network::ProxyConfig;
+
+use super::{AuditEntry, AuditSink};
+
+/// Audit sink posting each entry as JSON to a webhook
+///
+/// Posts are sent in the background on the current Tokio runtime. The sink
+/// is write-only, so combine it with a readable sink in a `MultiSink` to
+/// run `verify_chain`.
+pub struct WebhookSink {
+    /// Webhook URL
+    url: String,
+    /// HTTP client
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    /// Create a sink posting to a webhook URL
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+    
+    /// Post entries through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.client = proxy.http_client()?;
+        Ok(self)
+    }
+}
+
+impl AuditSink for WebhookSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|e| format!("Webhook audit sink needs a Tokio runtime: {}", e))?;
+        
+        let request = self.client.post(&self.url).json(entry);
+        let sequence = entry.sequence;
+        runtime.spawn(async move {
+            let result = request.send().await.and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                log::error!("Failed to deliver audit entry {}: {}", sequence, e);
+            }
+        });
+        
+        Ok(())
+    }
+    
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        Err("Webhook audit sink cannot be read back".to_string())
+    }
+}
@@ -0,0 +1,14 @@
+//! Encryption, masking and fragmentation engine of Project 89: Quantum Veil
+//!
+//! Modules needing optional dependencies are compiled only with their
+//! feature: `quantum_veil` and `timeline_shifter` with `crypto`,
+//! `synchronicity_mask` with `masking`, and `network` with `onchain`.
+
+pub mod audit;
+pub mod network;
+pub mod quantum_veil;
+pub mod session;
+pub mod synchronicity_mask;
+pub mod telemetry;
+pub mod timeline_shifter;
+pub mod zk;
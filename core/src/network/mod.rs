@@ -0,0 +1,152 @@
+//! Outbound network configuration
+//!
+//! Routes RPC calls, metadata fetches and storage uploads through SOCKS5 or
+//! HTTP proxies, e.g. Tor, so the user's IP address isn't tied to their NFT
+//! operations. Every component that talks to the network builds its clients
+//! from a `ProxyConfig`, and an `RpcPool` spreads RPC traffic across
+//! providers.
+
+#![cfg(feature = "onchain")]
+
+use reqwest::{Proxy, Url};
+use serde::{Serialize, Deserialize};
+use solana_client::{
+    http_sender::HttpSender,
+    rpc_client::{RpcClient, RpcClientConfig},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashMap;
+
+mod pool;
+
+pub use pool::RpcPool;
+
+/// Environment variable the CLI reads a default proxy from
+pub const PROXY_ENV_VAR: &str = "QUANTUM_VEIL_PROXY";
+
+/// SOCKS port of a local Tor daemon
+const TOR_PROXY_URL: &str = "socks5h://127.0.0.1:9050";
+
+/// Proxies for outbound connections
+///
+/// Use the `socks5h` scheme so host names are resolved by the proxy; with
+/// `socks5` they are resolved locally, leaking lookups to the DNS resolver.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy for hosts without their own entry, or `None` to connect directly
+    pub default: Option<String>,
+    /// Proxies for specific hosts, `None` connecting to that host directly
+    pub endpoints: HashMap<String, Option<String>>,
+}
+
+impl ProxyConfig {
+    /// Connect to every host directly
+    pub fn direct() -> Self {
+        Self::default()
+    }
+    
+    /// Route every connection through a proxy
+    pub fn new(proxy_url: &str) -> Result<Self, String> {
+        Ok(Self {
+            default: Some(validate(proxy_url)?),
+            endpoints: HashMap::new(),
+        })
+    }
+    
+    /// Route every connection through a local Tor daemon
+    pub fn tor() -> Self {
+        Self {
+            default: Some(TOR_PROXY_URL.to_string()),
+            endpoints: HashMap::new(),
+        }
+    }
+    
+    /// Read the default proxy from `QUANTUM_VEIL_PROXY`, connecting directly if unset
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var(PROXY_ENV_VAR) {
+            Ok(proxy_url) if !proxy_url.is_empty() => Self::new(&proxy_url),
+            _ => Ok(Self::direct()),
+        }
+    }
+    
+    /// Route one host through its own proxy, or directly with `None`
+    pub fn with_endpoint(mut self, host: &str, proxy_url: Option<&str>) -> Result<Self, String> {
+        let proxy_url = proxy_url.map(validate).transpose()?;
+        self.endpoints.insert(host.to_lowercase(), proxy_url);
+        Ok(self)
+    }
+    
+    /// Check if no connection is proxied
+    pub fn is_direct(&self) -> bool {
+        self.default.is_none() && self.endpoints.values().all(Option::is_none)
+    }
+    
+    /// Get the proxy a request to a URL goes through, if any
+    pub fn proxy_for(&self, url: &Url) -> Option<Url> {
+        let proxy_url = url.host_str()
+            .and_then(|host| self.endpoints.get(&host.to_lowercase()))
+            .unwrap_or(&self.default);
+        
+        // Entries are validated when added
+        proxy_url.as_deref().and_then(|proxy_url| Url::parse(proxy_url).ok())
+    }
+    
+    /// Build an HTTP client routing each request through its host's proxy
+    pub fn http_client(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder();
+        if !self.is_direct() {
+            let config = self.clone();
+            builder = builder.proxy(Proxy::custom(move |url| config.proxy_for(url)));
+        }
+        
+        builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+    
+    /// Build a Solana RPC client connecting through the endpoint's proxy
+    pub fn rpc_client(&self, rpc_url: &str, commitment: CommitmentConfig) -> Result<RpcClient, String> {
+        if self.is_direct() {
+            return Ok(RpcClient::new_with_commitment(rpc_url.to_string(), commitment));
+        }
+        
+        let sender = HttpSender::new_with_client(rpc_url, self.http_client()?);
+        Ok(RpcClient::new_sender(sender, RpcClientConfig::with_commitment(commitment)))
+    }
+}
+
+/// Check a proxy URL has a supported scheme
+fn validate(proxy_url: &str) -> Result<String, String> {
+    let url = Url::parse(proxy_url).map_err(|e| format!("Invalid proxy URL {}: {}", proxy_url, e))?;
+    
+    match url.scheme() {
+        "socks5h" | "http" | "https" => {},
+        "socks5" => log::warn!("Proxy {} resolves host names locally; use socks5h to keep DNS lookups private", proxy_url),
+        scheme => return Err(format!("Unsupported proxy scheme {}", scheme)),
+    }
+    
+    Ok(proxy_url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_proxy_chosen_per_endpoint() {
+        let config = ProxyConfig::tor()
+            .with_endpoint("ipfs.infura.io", Some("http://10.0.0.1:3128")).unwrap()
+            .with_endpoint("localhost", None).unwrap();
+        
+        let proxy_for = |url: &str| config.proxy_for(&Url::parse(url).unwrap()).map(|proxy| proxy.to_string());
+        assert_eq!(proxy_for("https://api.devnet.solana.com").as_deref(), Some("socks5h://127.0.0.1:9050"));
+        assert_eq!(proxy_for("https://IPFS.infura.io:5001/api/v0/add").as_deref(), Some("http://10.0.0.1:3128/"));
+        assert_eq!(proxy_for("http://localhost:8899"), None);
+        
+        assert!(ProxyConfig::direct().is_direct());
+        assert!(!config.is_direct());
+        assert!(config.http_client().is_ok());
+        assert!(config.rpc_client("https://api.devnet.solana.com", CommitmentConfig::confirmed()).is_ok());
+        
+        assert!(ProxyConfig::new("ftp://127.0.0.1:21").is_err());
+        assert!(ProxyConfig::new("not a url").is_err());
+    }
+}
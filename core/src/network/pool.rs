@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use rand::{Rng, thread_rng};
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    http_sender::HttpSender,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::time::Duration;
+
+use super::ProxyConfig;
+
+/// Solana RPC endpoints requests are spread across
+///
+/// Each request goes to a randomly chosen endpoint, optionally each behind
+/// its own proxy, after a random delay, so no single provider sees the full
+/// activity of a wallet or can line its requests up by timing. Endpoints
+/// should track the same cluster closely: a blockhash fetched from one is
+/// submitted through another.
+#[derive(Debug, Clone, Default)]
+pub struct RpcPool {
+    /// Endpoint URLs with the proxies to reach them through
+    endpoints: Vec<(String, ProxyConfig)>,
+    /// Longest random delay before each request
+    max_jitter: Duration,
+}
+
+impl RpcPool {
+    /// Create an empty pool
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add an endpoint, reached through the given proxies
+    pub fn with_endpoint(mut self, rpc_url: &str, proxy: ProxyConfig) -> Self {
+        self.endpoints.push((rpc_url.to_string(), proxy));
+        self
+    }
+    
+    /// Delay each request by a random time up to `max_jitter`
+    pub fn with_jitter(mut self, max_jitter: Duration) -> Self {
+        self.max_jitter = max_jitter;
+        self
+    }
+    
+    /// Get the number of endpoints in the pool
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+    
+    /// Check if the pool has no endpoints
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+    
+    /// Build an RPC client rotating among the pool's endpoints
+    pub fn rpc_client(&self, commitment: CommitmentConfig) -> Result<RpcClient, String> {
+        if self.endpoints.is_empty() {
+            return Err("RPC pool has no endpoints".to_string());
+        }
+        
+        let senders = self.endpoints.iter()
+            .map(|(rpc_url, proxy)| Ok((rpc_url.clone(), HttpSender::new_with_client(rpc_url, proxy.http_client()?))))
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        let sender = PoolSender { senders, max_jitter: self.max_jitter };
+        Ok(RpcClient::new_sender(sender, RpcClientConfig::with_commitment(commitment)))
+    }
+}
+
+/// RPC sender forwarding each request to a random endpoint
+struct PoolSender {
+    /// Endpoint URLs with their senders
+    senders: Vec<(String, HttpSender)>,
+    /// Longest random delay before each request
+    max_jitter: Duration,
+}
+
+impl PoolSender {
+    /// Pick the endpoint for the next request and how long to wait before sending
+    fn next(&self) -> (&HttpSender, Duration) {
+        let mut rng = thread_rng();
+        let (_, sender) = &self.senders[rng.gen_range(0..self.senders.len())];
+        let delay = self.max_jitter.mul_f64(rng.gen::<f64>());
+        (sender, delay)
+    }
+}
+
+#[async_trait]
+impl RpcSender for PoolSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let (sender, delay) = self.next();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        
+        sender.send(request, params).await
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        let mut stats = RpcTransportStats::default();
+        for (_, sender) in &self.senders {
+            let endpoint_stats = sender.get_transport_stats();
+            stats.request_count += endpoint_stats.request_count;
+            stats.elapsed_time += endpoint_stats.elapsed_time;
+            stats.rate_limited_time += endpoint_stats.rate_limited_time;
+        }
+        stats
+    }
+    
+    /// Get the first endpoint's URL, so clients rebuilt from it stay on the cluster
+    fn url(&self) -> String {
+        self.senders[0].0.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pool_spreads_requests_with_bounded_jitter() {
+        assert!(RpcPool::new().rpc_client(CommitmentConfig::confirmed()).is_err());
+        
+        let pool = RpcPool::new()
+            .with_endpoint("https://api.devnet.solana.com", ProxyConfig::direct())
+            .with_endpoint("https://devnet.helius-rpc.com", ProxyConfig::tor())
+            .with_jitter(Duration::from_millis(250));
+        assert_eq!(pool.len(), 2);
+        
+        let senders = pool.endpoints.iter()
+            .map(|(rpc_url, proxy)| (rpc_url.clone(), HttpSender::new_with_client(rpc_url, proxy.http_client().unwrap())))
+            .collect();
+        let sender = PoolSender { senders, max_jitter: pool.max_jitter };
+        
+        let mut used = std::collections::HashSet::new();
+        for _ in 0..64 {
+            let (chosen, delay) = sender.next();
+            assert!(delay <= Duration::from_millis(250));
+            used.insert(chosen.url());
+        }
+        assert_eq!(used.len(), 2);
+        assert_eq!(sender.url(), "https://api.devnet.solana.com");
+        
+        let client = pool.rpc_client(CommitmentConfig::confirmed()).unwrap();
+        assert_eq!(client.url(), "https://api.devnet.solana.com");
+    }
+}
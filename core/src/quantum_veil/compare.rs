@@ -0,0 +1,38 @@
+//! Constant-time comparisons for access checks
+//!
+//! Threat model: a remote caller who can time how long an access decision
+//! takes, e.g. a viewer probing the mask service or a client presenting API
+//! keys, must not learn how much of a secret or identity they guessed
+//! correctly. Byte-wise `==` returns at the first mismatch, and `contains`
+//! returns at the first match, so both leak through timing.
+//!
+//! The comparisons here touch every byte and every candidate whatever the
+//! outcome. Only lengths and list sizes may leak, which are not secret for
+//! the values compared (keys, hashes, and base58 account ids). Checks inside
+//! the on-chain program are out of scope: their cost in compute units does
+//! not depend on where inputs differ, and callers cannot time them.
+//!
+//! The comparisons are implemented in the no_std `quantum_veil_primitives`
+//! so on-chain programs and constrained targets share them.
+
+pub use quantum_veil_primitives::compare::{constant_time_eq, constant_time_contains};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_constant_time_checks_match_plain_equality() {
+        assert!(constant_time_eq(b"owner-key", b"owner-key"));
+        assert!(!constant_time_eq(b"owner-key", b"owner-kez"));
+        assert!(!constant_time_eq(b"owner-key", b"xwner-key"));
+        assert!(!constant_time_eq(b"owner", b"owner-key"));
+        assert!(constant_time_eq(b"", b""));
+        
+        let agents = vec!["agent-a".to_string(), "agent-b".to_string(), "agent-c".to_string()];
+        for probe in ["agent-a", "agent-c", "agent-", "agent-d", "", "agent-aa"] {
+            assert_eq!(constant_time_contains(&agents, probe.as_bytes()), agents.iter().any(|a| a == probe), "{}", probe);
+        }
+        assert!(!constant_time_contains::<String>(&[], b"agent-a"));
+    }
+}
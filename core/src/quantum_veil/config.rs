@@ -1,18 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Entropy sources for quantum-grade key generation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum EntropySource {
-    /// Recent Solana blockchain hash
-    BlockchainHash,
-    /// System time-based entropy
-    TimeEntropy,
-    /// Cosmic background radiation simulation
-    CosmicNoise,
-    /// VRM agent interaction data
-    AgentBehavior,
-}
+pub use quantum_veil_types::EntropySource;
+
+use super::compare::constant_time_contains;
+use super::secret::SecretBytes;
 
 /// Synchronicity mask settings for VRM behavior obfuscation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +19,22 @@ pub struct SynchronicityMask {
     pub trusted_agents: Vec<String>,
 }
 
+/// Key retired by rotation, kept so data sealed under it still decrypts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiredKey {
+    /// ID of the raw key (base64 encoded), as named in the envelopes it sealed
+    pub key_id: String,
+    /// Retired encryption key, wrapped if `key_wrapped_by` is set
+    pub key: SecretBytes,
+    /// Key provider the key is wrapped by, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_wrapped_by: Option<String>,
+    /// Nonce raw ciphertexts from before envelopes were sealed with
+    pub nonce: SecretBytes,
+    /// When the key was retired, as a Unix timestamp
+    pub retired_at: u64,
+}
+
 /// Privacy configuration for a Glitch Gang NFT
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivacyConfig {
@@ -34,10 +42,19 @@ pub struct PrivacyConfig {
     pub owner: String,
     /// NFT mint address
     pub nft_mint: String,
-    /// Current encryption key (base64 encoded)
-    pub current_key: String,
+    /// Current encryption key (base64 encoded), wrapped if `key_wrapped_by` is set
+    pub current_key: SecretBytes,
+    /// Key provider the current key is wrapped by, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_wrapped_by: Option<String>,
+    /// Derivation index of the current key, if it was derived from an HD master key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_index: Option<u32>,
     /// Nonce for encryption (base64 encoded)
-    pub current_nonce: String,
+    pub current_nonce: SecretBytes,
+    /// Keys retired by rotation, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retired_keys: Vec<RetiredKey>,
     /// Entropy sources used for key generation
     pub entropy_sources: Vec<EntropySource>,
     /// Key rotation frequency in seconds
@@ -77,7 +94,7 @@ impl PrivacyConfig {
     
     /// Check if the given agent is trusted
     pub fn is_agent_trusted(&self, agent_id: &str) -> bool {
-        self.sync_mask.trusted_agents.contains(&agent_id.to_string())
+        constant_time_contains(&self.sync_mask.trusted_agents, agent_id.as_bytes())
     }
     
     /// Add a trusted agent
@@ -0,0 +1,99 @@
+//! Binding ciphertexts to what they belong to
+//!
+//! Ciphertexts are sealed with the NFT, owner, schema version and purpose
+//! they belong to as associated data. The context isn't stored with the
+//! ciphertext: whoever decrypts supplies the context they expect, so an
+//! encrypted attribute transplanted onto another NFT, or moved from one
+//! purpose to another, fails to decrypt instead of being accepted.
+
+use serde::{Serialize, Deserialize};
+
+use super::encryption::{open_envelope, seal_envelope};
+
+/// Version of the context encoding and of the data layouts it vouches for
+pub const CONTEXT_SCHEMA_VERSION: u16 = 1;
+
+/// Domain label starting every encoded context
+const CONTEXT_LABEL: &[u8] = b"quantum-veil/context";
+
+/// What a ciphertext belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionContext {
+    /// NFT mint address, empty for data shared between NFTs
+    pub nft_mint: String,
+    /// Owner address, empty if not known
+    pub owner: String,
+    /// Schema version of the encrypted data
+    pub schema_version: u16,
+    /// What the data is for, e.g. `quantum-veil/data` or `fragment/identity`
+    pub purpose: String,
+}
+
+impl EncryptionContext {
+    /// Create a context at the current schema version
+    pub fn new(nft_mint: &str, owner: &str, purpose: &str) -> Self {
+        Self {
+            nft_mint: nft_mint.to_string(),
+            owner: owner.to_string(),
+            schema_version: CONTEXT_SCHEMA_VERSION,
+            purpose: purpose.to_string(),
+        }
+    }
+    
+    /// Create a context for data shared between NFTs, bound only to its purpose
+    pub fn shared(purpose: &str) -> Self {
+        Self::new("", "", purpose)
+    }
+    
+    /// Get the same context for another purpose
+    pub fn for_purpose(&self, purpose: &str) -> Self {
+        Self { purpose: purpose.to_string(), ..self.clone() }
+    }
+    
+    /// Encode the context as associated data, each field length-prefixed
+    pub fn to_aad(&self) -> Vec<u8> {
+        let mut aad = CONTEXT_LABEL.to_vec();
+        aad.extend_from_slice(&self.schema_version.to_le_bytes());
+        for field in [&self.purpose, &self.nft_mint, &self.owner] {
+            aad.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            aad.extend_from_slice(field.as_bytes());
+        }
+        aad
+    }
+    
+    /// Encrypt data into an envelope bound to this context
+    pub fn seal(&self, plaintext: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+        seal_envelope(plaintext, key, &self.to_aad())
+    }
+    
+    /// Decrypt an envelope, rejecting it unless it was bound to this context
+    pub fn open(&self, sealed: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+        open_envelope(sealed, key, &self.to_aad())
+            .map_err(|e| format!("{} (expected {} for {})", e, self.purpose, self.nft_mint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_ciphertexts_only_open_in_their_context() {
+        let key = [2u8; 32];
+        let context = EncryptionContext::new("mint-a", "owner", "quantum-veil/data");
+        let sealed = context.seal(b"bound attributes", &key).unwrap();
+        assert_eq!(context.open(&sealed, &key).unwrap(), b"bound attributes");
+        
+        // Another NFT, owner, purpose or schema version can't open it
+        let transplanted = EncryptionContext::new("mint-b", "owner", "quantum-veil/data");
+        assert!(transplanted.open(&sealed, &key).unwrap_err().contains("another context"));
+        assert!(EncryptionContext::new("mint-a", "thief", "quantum-veil/data").open(&sealed, &key).is_err());
+        assert!(context.for_purpose("quantum-veil/preview").open(&sealed, &key).is_err());
+        let future = EncryptionContext { schema_version: CONTEXT_SCHEMA_VERSION + 1, ..context.clone() };
+        assert!(future.open(&sealed, &key).is_err());
+        
+        // Fields can't be shifted into one another
+        let shifted = EncryptionContext::new("mint-a", "owner", "quantum-veil/dat");
+        assert_ne!(shifted.to_aad(), EncryptionContext::new("mint-aa", "owner", "quantum-veil/dat").to_aad());
+    }
+}
@@ -1,65 +1,36 @@
-use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
-use chacha20poly1305::aead::{Aead, NewAead};
+//! ChaCha20Poly1305 encryption and key derivation
+//!
+//! Implemented in the no_std `quantum_veil_primitives` so on-chain programs
+//! and constrained targets encrypt the same way. Stored ciphertexts are
+//! wrapped in the primitives' self-describing `Envelope`.
 
-/// Encrypt data using ChaCha20Poly1305
-pub fn encrypt_data(data: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
-    if key.len() != 32 {
-        return Err(format!("Invalid key length: {}, expected 32", key.len()));
-    }
-    
-    if nonce.len() != 12 {
-        return Err(format!("Invalid nonce length: {}, expected 12", nonce.len()));
-    }
-    
-    let cipher_key = Key::from_slice(key);
-    let cipher_nonce = Nonce::from_slice(nonce);
-    
-    let cipher = ChaCha20Poly1305::new(cipher_key);
-    
-    cipher.encrypt(cipher_nonce, data)
-        .map_err(|e| format!("Encryption error: {}", e))
-}
+use rand::{Rng, rngs::OsRng};
 
-/// Decrypt data using ChaCha20Poly1305
-pub fn decrypt_data(ciphertext: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
-    if key.len() != 32 {
-        return Err(format!("Invalid key length: {}, expected 32", key.len()));
-    }
-    
-    if nonce.len() != 12 {
-        return Err(format!("Invalid nonce length: {}, expected 12", nonce.len()));
-    }
-    
-    let cipher_key = Key::from_slice(key);
-    let cipher_nonce = Nonce::from_slice(nonce);
-    
-    let cipher = ChaCha20Poly1305::new(cipher_key);
-    
-    cipher.decrypt(cipher_nonce, ciphertext)
-        .map_err(|e| format!("Decryption error: {}", e))
-}
+pub use quantum_veil_primitives::encryption::{encrypt_data, decrypt_data, derive_key_from_seed};
+pub use quantum_veil_primitives::envelope::{
+    Algorithm, Envelope, ENVELOPE_MAGIC, ENVELOPE_VERSION, blinded_key_id, envelope_overhead, is_envelope, key_id,
+};
 
-/// Create a deterministic key from a seed
-pub fn derive_key_from_seed(seed: &[u8]) -> ([u8; 32], [u8; 12]) {
-    use sha3::{Sha3_512, Digest};
-    
-    let mut hasher = Sha3_512::new();
-    hasher.update(seed);
-    let result = hasher.finalize();
-    
-    // Split the hash result into key and nonce
-    let mut key = [0u8; 32];
+/// Encrypt data into a serialized envelope under a fresh random nonce
+///
+/// The ciphertext is bound to `context` as associated data, which isn't
+/// stored: opening needs the same context.
+pub fn seal_envelope(plaintext: &[u8], key: &[u8], context: &[u8]) -> Result<Vec<u8>, String> {
     let mut nonce = [0u8; 12];
+    OsRng.fill(&mut nonce);
     
-    key.copy_from_slice(&result[0..32]);
-    nonce.copy_from_slice(&result[32..44]);
-    
-    (key, nonce)
+    Ok(Envelope::seal_bound(plaintext, key, &nonce, &[], context)?.to_bytes())
+}
+
+/// Parse a serialized envelope and decrypt it, checking it was bound to `context`
+pub fn open_envelope(sealed: &[u8], key: &[u8], context: &[u8]) -> Result<Vec<u8>, String> {
+    Envelope::parse(sealed)?.open_bound(key, context)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     
     #[test]
     fn test_encrypt_decrypt() {
@@ -73,6 +44,17 @@ mod tests {
         assert_eq!(data, &decrypted[..]);
     }
     
+    #[test]
+    fn test_sealed_envelopes_use_fresh_nonces() {
+        let key = [6u8; 32];
+        let first = seal_envelope(b"same data", &key, b"").unwrap();
+        let second = seal_envelope(b"same data", &key, b"").unwrap();
+        
+        assert_ne!(first, second);
+        assert_eq!(open_envelope(&first, &key, b"").unwrap(), b"same data");
+        assert_eq!(Envelope::parse(&second).unwrap().key_id, key_id(&key));
+    }
+    
     #[test]
     fn test_key_derivation() {
         let seed = b"test seed for key derivation";
@@ -85,4 +67,30 @@ mod tests {
         assert_eq!(key, key2);
         assert_eq!(nonce, nonce2);
     }
+    
+    proptest! {
+        #[test]
+        fn prop_encrypt_decrypt_is_identity(
+            data in proptest::collection::vec(any::<u8>(), 0..8192),
+            key in any::<[u8; 32]>(),
+            nonce in any::<[u8; 12]>(),
+        ) {
+            let encrypted = encrypt_data(&data, &key, &nonce).unwrap();
+            let decrypted = decrypt_data(&encrypted, &key, &nonce).unwrap();
+            
+            prop_assert_eq!(data, decrypted);
+        }
+        
+        #[test]
+        fn prop_wrong_key_fails(
+            data in proptest::collection::vec(any::<u8>(), 0..256),
+            key in any::<[u8; 32]>(),
+            other_key in any::<[u8; 32]>(),
+        ) {
+            prop_assume!(key != other_key);
+            
+            let encrypted = encrypt_data(&data, &key, &[0u8; 12]).unwrap();
+            prop_assert!(decrypt_data(&encrypted, &other_key, &[0u8; 12]).is_err());
+        }
+    }
 }
@@ -1,22 +1,26 @@
-use solana_client::rpc_client::RpcClient;
 use sha3::{Digest, Sha3_512};
 use rand::{Rng, rngs::OsRng};
 use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
 
 use super::config::EntropySource;
+use super::encryption::derive_key_from_seed;
+use super::secret::SecretBytes;
 
 /// Generate a quantum-grade encryption key
-pub fn generate_key(sources: &[EntropySource], rpc_client: &RpcClient) -> (Vec<u8>, Vec<u8>) {
-    let mut hasher = Sha3_512::new();
+///
+/// `BlockchainHash` entropy is taken from `recent_blockhash`, and skipped if
+/// none is given, e.g. when generating keys offline.
+pub fn generate_key(sources: &[EntropySource], recent_blockhash: Option<&[u8]>) -> (SecretBytes, SecretBytes) {
     let mut entropy = Vec::new();
     
     // Gather entropy from selected sources
     for source in sources {
         match source {
             EntropySource::BlockchainHash => {
-                // Get recent Solana blockhash
-                if let Ok(blockhash) = rpc_client.get_latest_blockhash() {
-                    entropy.extend_from_slice(&blockhash.as_ref());
+                // Recent Solana blockhash
+                if let Some(blockhash) = recent_blockhash {
+                    entropy.extend_from_slice(blockhash);
                 }
             },
             EntropySource::TimeEntropy => {
@@ -47,15 +51,15 @@ pub fn generate_key(sources: &[EntropySource], rpc_client: &RpcClient) -> (Vec<u
     let random_bytes: [u8; 32] = rng.gen();
     entropy.extend_from_slice(&random_bytes);
     
-    // Hash the entropy to create the key
-    hasher.update(&entropy);
-    let result = hasher.finalize();
+    // Hash the entropy into the key and nonce
+    let (mut key, mut nonce) = derive_key_from_seed(&entropy);
+    entropy.zeroize();
     
-    // Split the hash result into key and nonce
-    let key = result[0..32].to_vec();
-    let nonce = result[32..44].to_vec();
+    let secrets = (SecretBytes::from(&key[..]), SecretBytes::from(&nonce[..]));
+    key.zeroize();
+    nonce.zeroize();
     
-    (key, nonce)
+    secrets
 }
 
 /// Generate a secure random seed for key derivation
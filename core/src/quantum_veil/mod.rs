@@ -1,52 +1,261 @@
+#![cfg(feature = "crypto")]
+
+mod compare;
 mod config;
+mod context;
 mod encryption;
+mod hd;
 mod key_gen;
+mod provider;
+mod secret;
 
-pub use config::{PrivacyConfig, SynchronicityMask, EntropySource};
-pub use encryption::{encrypt_data, decrypt_data};
+pub use compare::{constant_time_eq, constant_time_contains};
+pub use config::{PrivacyConfig, RetiredKey, SynchronicityMask, EntropySource};
+pub use context::{EncryptionContext, CONTEXT_SCHEMA_VERSION};
+pub use encryption::{
+    encrypt_data, decrypt_data, derive_key_from_seed, seal_envelope, open_envelope,
+    Algorithm, Envelope, ENVELOPE_MAGIC, ENVELOPE_VERSION, blinded_key_id, envelope_overhead, is_envelope, key_id,
+};
+pub use hd::HdMasterKey;
 pub use key_gen::generate_key;
+pub use provider::{KeyProvider, LocalKeyProvider};
+pub use secret::SecretBytes;
 
+#[cfg(feature = "onchain")]
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
+use std::sync::Arc;
 use ring::digest::Context;
-use base64::{encode, decode};
+use base64::encode;
 use sha3::{Sha3_512, Digest};
 
+use crate::audit::{AuditEvent, AuditLog};
+use crate::telemetry::timed;
+
+/// Purpose ciphertexts from `QuantumVeil::encrypt` are bound to
+pub const DATA_PURPOSE: &str = "quantum-veil/data";
+
 /// Quantum Veil encryption manager
 pub struct QuantumVeil {
-    /// RPC client for Solana blockchain interaction
-    rpc_client: RpcClient,
+    /// RPC client for blockhash entropy, if connected
+    #[cfg(feature = "onchain")]
+    rpc_client: Option<RpcClient>,
     /// Cache of privacy configurations by NFT mint
     config_cache: HashMap<String, PrivacyConfig>,
+    /// Audit log for decryptions and key rotations, if configured
+    audit_log: Option<Arc<AuditLog>>,
+    /// Provider wrapping NFT keys, if configured
+    key_provider: Option<Arc<dyn KeyProvider>>,
+    /// Master key NFT keys are derived from, if configured
+    hd_master: Option<HdMasterKey>,
 }
 
 impl QuantumVeil {
     /// Create a new Quantum Veil instance
+    #[cfg(feature = "onchain")]
     pub fn new(solana_rpc_url: &str) -> Self {
+        Self::with_rpc_client(RpcClient::new(solana_rpc_url.to_string()))
+    }
+    
+    /// Create a new Quantum Veil instance with a preconfigured RPC client
+    #[cfg(feature = "onchain")]
+    pub fn with_rpc_client(rpc_client: RpcClient) -> Self {
         Self {
-            rpc_client: RpcClient::new(solana_rpc_url.to_string()),
+            rpc_client: Some(rpc_client),
+            ..Self::offline()
+        }
+    }
+    
+    /// Create a Quantum Veil instance without an RPC connection
+    ///
+    /// Keys are generated without `BlockchainHash` entropy.
+    pub fn offline() -> Self {
+        Self {
+            #[cfg(feature = "onchain")]
+            rpc_client: None,
             config_cache: HashMap::new(),
+            audit_log: None,
+            key_provider: None,
+            hd_master: None,
+        }
+    }
+    
+    /// Record decryptions and key rotations in an audit log
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
+    /// Keep NFT keys wrapped by a key provider, e.g. a KMS or HSM
+    ///
+    /// Configs created or rotated afterwards store their key wrapped, and
+    /// each encryption or decryption unwraps it through the provider.
+    pub fn with_key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+    
+    /// Derive NFT keys from a master key instead of generating them from entropy
+    ///
+    /// Configs created or rotated afterwards record their derivation index,
+    /// so their keys can be recovered from the master seed alone.
+    pub fn with_hd_master(mut self, hd_master: HdMasterKey) -> Self {
+        self.set_hd_master(hd_master);
+        self
+    }
+    
+    /// Derive NFT keys from a master key, for instances already shared
+    pub fn set_hd_master(&mut self, hd_master: HdMasterKey) {
+        self.hd_master = Some(hd_master);
+    }
+    
+    /// Generate a new NFT key, derived at `key_index` if a master key is configured
+    fn new_key(
+        &self,
+        nft_mint: &str,
+        entropy_sources: &[EntropySource],
+        key_index: u32,
+    ) -> (SecretBytes, SecretBytes, Option<u32>) {
+        if let Some(hd_master) = &self.hd_master {
+            let (key, nonce) = hd_master.nft_key(nft_mint, key_index);
+            return (key, nonce, Some(key_index));
+        }
+        
+        let blockhash = self.recent_blockhash(entropy_sources);
+        let (key, nonce) = generate_key(entropy_sources, blockhash.as_deref());
+        (key, nonce, None)
+    }
+    
+    /// Encode a new NFT key for storage, wrapping it if a provider is configured
+    fn store_key(&self, key: &[u8]) -> Result<(SecretBytes, Option<String>), String> {
+        match &self.key_provider {
+            Some(provider) => Ok((SecretBytes::new(provider.wrap(key)?), Some(provider.key_id()))),
+            None => Ok((SecretBytes::from(key), None)),
+        }
+    }
+    
+    /// Get the raw key of a config, unwrapping it through the provider if needed
+    pub fn data_key(&self, config: &PrivacyConfig) -> Result<SecretBytes, String> {
+        self.raw_key(&config.current_key, &config.key_wrapped_by)
+    }
+    
+    /// Unwrap a stored key through the provider it is wrapped by, if any
+    fn raw_key(&self, key: &SecretBytes, wrapped_by: &Option<String>) -> Result<SecretBytes, String> {
+        match (wrapped_by, &self.key_provider) {
+            (None, _) => Ok(key.clone()),
+            (Some(key_id), Some(provider)) if *key_id == provider.key_id() => {
+                provider.unwrap(key.expose()).map(SecretBytes::new)
+            }
+            (Some(key_id), _) => Err(format!("Key is wrapped by {}, which is not configured", key_id)),
+        }
+    }
+    
+    /// Get the raw key an envelope names, the current one or one retired by rotation
+    fn envelope_key(&self, config: &PrivacyConfig, ciphertext: &[u8]) -> Result<SecretBytes, String> {
+        let envelope_key_id = encode(Envelope::parse(ciphertext)?.key_id);
+        
+        match config.retired_keys.iter().rev().find(|retired| retired.key_id == envelope_key_id) {
+            Some(retired) => self.raw_key(&retired.key, &retired.key_wrapped_by),
+            None => self.data_key(config),
         }
     }
     
+    /// Decrypt a raw ciphertext from before envelopes
+    ///
+    /// Raw ciphertexts don't name their key, so the current key is tried
+    /// first and then the retired ones, newest first.
+    fn decrypt_raw(&self, config: &PrivacyConfig, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let key = self.data_key(config)?;
+        let current = encryption::decrypt_data(ciphertext, key.expose(), config.current_nonce.expose());
+        if current.is_ok() {
+            return current;
+        }
+        
+        for retired in config.retired_keys.iter().rev() {
+            let key = self.raw_key(&retired.key, &retired.key_wrapped_by)?;
+            if let Ok(plaintext) = encryption::decrypt_data(ciphertext, key.expose(), retired.nonce.expose()) {
+                return Ok(plaintext);
+            }
+        }
+        
+        current
+    }
+    
+    /// Fetch a recent blockhash if the entropy sources use one and RPC is connected
+    fn recent_blockhash(&self, entropy_sources: &[EntropySource]) -> Option<Vec<u8>> {
+        if !entropy_sources.iter().any(|source| matches!(source, EntropySource::BlockchainHash)) {
+            return None;
+        }
+        
+        #[cfg(feature = "onchain")]
+        if let Some(rpc_client) = &self.rpc_client {
+            return rpc_client.get_latest_blockhash().ok().map(|blockhash| blockhash.as_ref().to_vec());
+        }
+        
+        None
+    }
+    
+    /// Record an event if an audit log is configured
+    fn audit(&self, event: AuditEvent) -> Result<(), String> {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(event)?;
+        }
+        Ok(())
+    }
+    
     /// Create a new privacy configuration for an NFT
     pub fn create_config(
         &mut self,
-        owner: &Pubkey,
-        nft_mint: &Pubkey,
+        owner: &str,
+        nft_mint: &str,
+        entropy_sources: Vec<EntropySource>,
+        key_rotation_frequency: u64,
+        sync_mask: SynchronicityMask,
+    ) -> Result<PrivacyConfig, String> {
+        self.create_config_at(owner, nft_mint, entropy_sources, key_rotation_frequency, sync_mask, 0)
+    }
+    
+    /// Recreate an NFT's config from the master key, with the key derived at `key_index`
+    ///
+    /// The index is the config's `key_index`, which counts rotations since the
+    /// config was created.
+    pub fn recover_config(
+        &mut self,
+        owner: &str,
+        nft_mint: &str,
+        key_index: u32,
+        key_rotation_frequency: u64,
+        sync_mask: SynchronicityMask,
+    ) -> Result<PrivacyConfig, String> {
+        if self.hd_master.is_none() {
+            return Err("Keys can only be recovered with a master key configured".to_string());
+        }
+        
+        self.create_config_at(owner, nft_mint, Vec::new(), key_rotation_frequency, sync_mask, key_index)
+    }
+    
+    /// Create and cache a config whose key is derived at `key_index` if a master key is configured
+    fn create_config_at(
+        &mut self,
+        owner: &str,
+        nft_mint: &str,
         entropy_sources: Vec<EntropySource>,
         key_rotation_frequency: u64,
         sync_mask: SynchronicityMask,
-    ) -> PrivacyConfig {
+        key_index: u32,
+    ) -> Result<PrivacyConfig, String> {
         // Generate initial encryption key
-        let (key, nonce) = generate_key(&entropy_sources, &self.rpc_client);
+        let (key, nonce, key_index) = self.new_key(nft_mint, &entropy_sources, key_index);
+        let (current_key, key_wrapped_by) = self.store_key(key.expose())?;
         
         let config = PrivacyConfig {
             owner: owner.to_string(),
             nft_mint: nft_mint.to_string(),
-            current_key: encode(&key),
-            current_nonce: encode(&nonce),
+            current_key,
+            key_wrapped_by,
+            key_index,
+            current_nonce: nonce,
+            retired_keys: Vec::new(),
             entropy_sources,
             key_rotation_frequency,
             last_rotation: std::time::SystemTime::now()
@@ -59,7 +268,7 @@ impl QuantumVeil {
         // Cache the config
         self.config_cache.insert(nft_mint.to_string(), config.clone());
         
-        config
+        Ok(config)
     }
     
     /// Get privacy configuration by NFT mint
@@ -69,6 +278,11 @@ impl QuantumVeil {
             .ok_or_else(|| format!("No privacy config found for NFT: {}", nft_mint))
     }
     
+    /// Get every cached privacy configuration
+    pub fn configs(&self) -> impl Iterator<Item = &PrivacyConfig> {
+        self.config_cache.values()
+    }
+    
     /// Update privacy configuration
     pub fn update_config(&mut self, nft_mint: &str, config: PrivacyConfig) -> Result<(), String> {
         self.config_cache.insert(nft_mint.to_string(), config);
@@ -87,44 +301,89 @@ impl QuantumVeil {
     
     /// Rotate encryption key based on new entropy
     pub fn rotate_key(&mut self, nft_mint: &str) -> Result<PrivacyConfig, String> {
+        timed("key.rotate", || self.rotate_key_inner(nft_mint))
+    }
+    
+    /// Generate and cache a new key for an NFT, retiring the current one
+    fn rotate_key_inner(&mut self, nft_mint: &str) -> Result<PrivacyConfig, String> {
         let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
         
-        // Generate new key from current entropy sources
-        let (key, nonce) = generate_key(&config.entropy_sources, &self.rpc_client);
-        
-        // Update config with new key
-        let mut updated_config = config.clone();
-        updated_config.current_key = encode(&key);
-        updated_config.current_nonce = encode(&nonce);
-        updated_config.last_rotation = std::time::SystemTime::now()
+        // Generate new key from current entropy sources, or the next derivation index
+        let next_index = config.key_index.map_or(0, |key_index| key_index + 1);
+        let (key, nonce, key_index) = self.new_key(nft_mint, &config.entropy_sources, next_index);
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
         
+        // Keep the current key under its envelope ID so existing data still decrypts
+        let retired = RetiredKey {
+            key_id: encode(key_id(self.data_key(config)?.expose())),
+            key: config.current_key.clone(),
+            key_wrapped_by: config.key_wrapped_by.clone(),
+            nonce: config.current_nonce.clone(),
+            retired_at: now,
+        };
+        
+        // Update config with new key
+        let (current_key, key_wrapped_by) = self.store_key(key.expose())?;
+        let mut updated_config = config.clone();
+        updated_config.retired_keys.push(retired);
+        updated_config.current_key = current_key;
+        updated_config.key_wrapped_by = key_wrapped_by;
+        updated_config.key_index = key_index;
+        updated_config.current_nonce = nonce;
+        updated_config.last_rotation = now;
+        
         // Update cache
         self.config_cache.insert(nft_mint.to_string(), updated_config.clone());
         
+        self.audit(AuditEvent::KeyRotated { nft_mint: nft_mint.to_string() })?;
+        
         Ok(updated_config)
     }
     
-    /// Encrypt data using the current privacy key
+    /// Encrypt data into an envelope using the current privacy key
     pub fn encrypt(&self, nft_mint: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+        self.encrypt_for(nft_mint, DATA_PURPOSE, data)
+    }
+    
+    /// Encrypt data for a purpose, binding it to the NFT, its owner and the purpose
+    ///
+    /// The ciphertext only decrypts with `decrypt_for` and the same purpose,
+    /// while the NFT's config names the same owner.
+    pub fn encrypt_for(&self, nft_mint: &str, purpose: &str, data: &[u8]) -> Result<Vec<u8>, String> {
         let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
         
-        let key_bytes = decode(&config.current_key).map_err(|_| "Invalid key")?;
-        let nonce_bytes = decode(&config.current_nonce).map_err(|_| "Invalid nonce")?;
+        let key = self.data_key(config)?;
         
-        encryption::encrypt_data(data, &key_bytes, &nonce_bytes)
+        EncryptionContext::new(nft_mint, &config.owner, purpose).seal(data, key.expose())
     }
     
-    /// Decrypt data using the current privacy key
+    /// Decrypt data using the privacy key it was encrypted under
+    ///
+    /// Data from before a rotation decrypts with the retired key its envelope
+    /// names. Raw ciphertexts from before envelopes are decrypted with the
+    /// nonce of the key that opens them.
     pub fn decrypt(&self, nft_mint: &str, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        self.decrypt_for(nft_mint, DATA_PURPOSE, ciphertext)
+    }
+    
+    /// Decrypt data encrypted with `encrypt_for`, rejecting it if bound to another NFT, owner or purpose
+    pub fn decrypt_for(&self, nft_mint: &str, purpose: &str, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
         let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
         
-        let key_bytes = decode(&config.current_key).map_err(|_| "Invalid key")?;
-        let nonce_bytes = decode(&config.current_nonce).map_err(|_| "Invalid nonce")?;
+        let plaintext = if is_envelope(ciphertext) {
+            let key = self.envelope_key(config, ciphertext)?;
+            EncryptionContext::new(nft_mint, &config.owner, purpose).open(ciphertext, key.expose())?
+        } else {
+            self.decrypt_raw(config, ciphertext)?
+        };
+        
+        // Only release plaintext once the decryption is on record
+        self.audit(AuditEvent::Decrypted { nft_mint: nft_mint.to_string(), bytes: plaintext.len() })?;
         
-        encryption::decrypt_data(ciphertext, &key_bytes, &nonce_bytes)
+        Ok(plaintext)
     }
     
     /// Update synchronicity mask
@@ -146,3 +405,119 @@ impl QuantumVeil {
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    
+    fn mock_veil() -> QuantumVeil {
+        QuantumVeil::offline()
+    }
+    
+    fn mock_mask() -> SynchronicityMask {
+        SynchronicityMask {
+            position_noise: 0.5,
+            voice_noise: 0.5,
+            gesture_noise: 0.5,
+            trusted_agents: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_rotate_key_changes_key_and_keeps_data_decryptable() {
+        let mut veil = mock_veil();
+        let mint = Pubkey::new_unique();
+        let config = veil.create_config(
+            &Pubkey::new_unique().to_string(),
+            &mint.to_string(),
+            vec![EntropySource::BlockchainHash, EntropySource::CosmicNoise],
+            3600,
+            mock_mask(),
+        ).unwrap();
+        
+        let before = veil.encrypt(&mint.to_string(), b"earlier secret").unwrap();
+        let raw = encrypt_data(b"raw secret", config.current_key.expose(), config.current_nonce.expose()).unwrap();
+        
+        let rotated = veil.rotate_key(&mint.to_string()).unwrap();
+        assert_ne!(config.current_key, rotated.current_key);
+        assert_eq!(rotated.retired_keys.len(), 1);
+        
+        let ciphertext = veil.encrypt(&mint.to_string(), b"rotated secret").unwrap();
+        let plaintext = veil.decrypt(&mint.to_string(), &ciphertext).unwrap();
+        assert_eq!(b"rotated secret", &plaintext[..]);
+        
+        // Data from before the rotation opens with the retired key it names
+        veil.rotate_key(&mint.to_string()).unwrap();
+        assert_eq!(veil.decrypt(&mint.to_string(), &before).unwrap(), b"earlier secret");
+        assert_eq!(veil.decrypt(&mint.to_string(), &ciphertext).unwrap(), b"rotated secret");
+        assert_eq!(veil.decrypt(&mint.to_string(), &raw).unwrap(), b"raw secret");
+    }
+    
+    #[test]
+    fn test_ciphertexts_are_bound_to_their_nft_owner_and_purpose() {
+        let mut veil = mock_veil();
+        let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config = veil.create_config(&Pubkey::new_unique().to_string(), &mint.to_string(), vec![EntropySource::TimeEntropy], 3600, mock_mask()).unwrap();
+        let mint = mint.to_string();
+        
+        let ciphertext = veil.encrypt_for(&mint, "attributes", b"bound secret").unwrap();
+        assert_eq!(veil.decrypt_for(&mint, "attributes", &ciphertext).unwrap(), b"bound secret");
+        assert!(veil.decrypt_for(&mint, "preview", &ciphertext).is_err());
+        assert!(veil.decrypt(&mint, &ciphertext).is_err());
+        
+        // Transplanted onto another NFT under the same key, or after a transfer, it no longer opens
+        veil.update_config(&other_mint.to_string(), config.clone()).unwrap();
+        assert!(veil.decrypt_for(&other_mint.to_string(), "attributes", &ciphertext).unwrap_err().contains("another context"));
+        veil.update_config(&mint, PrivacyConfig { owner: Pubkey::new_unique().to_string(), ..config }).unwrap();
+        assert!(veil.decrypt_for(&mint, "attributes", &ciphertext).is_err());
+    }
+    
+    #[test]
+    fn test_decryptions_and_rotations_are_audited() {
+        use crate::audit::{AuditSink, MemorySink};
+        
+        let sink = Arc::new(MemorySink::new());
+        let mut veil = mock_veil().with_audit_log(Arc::new(AuditLog::new(sink.clone())));
+        let nft_mint = Pubkey::new_unique();
+        let mint = nft_mint.to_string();
+        veil.create_config(
+            &Pubkey::new_unique().to_string(),
+            &mint,
+            vec![EntropySource::BlockchainHash],
+            3600,
+            mock_mask(),
+        ).unwrap();
+        
+        let ciphertext = veil.encrypt(&mint, b"secret").unwrap();
+        veil.decrypt(&mint, &ciphertext).unwrap();
+        veil.rotate_key(&mint).unwrap();
+        
+        let events: Vec<AuditEvent> = sink.entries().unwrap().into_iter().map(|entry| entry.event).collect();
+        assert_eq!(events, vec![
+            AuditEvent::Decrypted { nft_mint: mint.clone(), bytes: 6 },
+            AuditEvent::KeyRotated { nft_mint: mint },
+        ]);
+    }
+    
+    #[test]
+    fn test_keys_stay_wrapped_by_the_provider() {
+        let provider = Arc::new(LocalKeyProvider::new([8u8; 32]));
+        let mut veil = mock_veil().with_key_provider(provider.clone());
+        let mint = Pubkey::new_unique();
+        let config = veil.create_config(&Pubkey::new_unique().to_string(), &mint.to_string(), vec![EntropySource::TimeEntropy], 3600, mock_mask()).unwrap();
+        
+        assert_eq!(config.key_wrapped_by, Some(provider.key_id()));
+        assert_eq!(veil.data_key(&config).unwrap().len(), 32);
+        assert_ne!(config.current_key.len(), 32);
+        assert!(!format!("{:?}", config).contains(&*veil.data_key(&config).unwrap().to_base64()));
+        
+        let ciphertext = veil.encrypt(&mint.to_string(), b"wrapped secret").unwrap();
+        assert_eq!(veil.decrypt(&mint.to_string(), &ciphertext).unwrap(), b"wrapped secret");
+        
+        // Without the provider the stored key is useless
+        let mut bare = mock_veil();
+        bare.update_config(&mint.to_string(), config).unwrap();
+        assert!(bare.decrypt(&mint.to_string(), &ciphertext).unwrap_err().contains("not configured"));
+    }
+}
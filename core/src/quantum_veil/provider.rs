@@ -0,0 +1,93 @@
+use rand::{Rng, rngs::OsRng};
+use ring::hmac;
+
+use super::encryption::{encrypt_data, decrypt_data};
+use super::secret::SecretBytes;
+
+/// Source of the master key that protects data keys
+///
+/// Data keys are only ever stored wrapped by the provider's master key. Providers
+/// backed by a KMS or HSM never release the master key, so `get_key` fails
+/// for them and wrapping, unwrapping, and signing happen on their side.
+pub trait KeyProvider: Send + Sync {
+    /// Get an identifier for the master key, e.g. a KMS key ARN
+    fn key_id(&self) -> String;
+    
+    /// Get the raw master key, if the provider allows exporting it
+    fn get_key(&self) -> Result<[u8; 32], String>;
+    
+    /// Encrypt a data key under the master key
+    fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, String>;
+    
+    /// Decrypt a data key wrapped by `wrap`
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, String>;
+    
+    /// Sign a message with the provider's signing key
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// Key provider holding its master key in process memory
+pub struct LocalKeyProvider {
+    /// Master key
+    key: SecretBytes,
+}
+
+impl LocalKeyProvider {
+    /// Create a provider for a master key
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { key: SecretBytes::from(&key[..]) }
+    }
+}
+
+impl KeyProvider for LocalKeyProvider {
+    fn key_id(&self) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.key.expose());
+        format!("local:{}", digest.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+    
+    fn get_key(&self) -> Result<[u8; 32], String> {
+        Ok(*self.key.to_array()?)
+    }
+    
+    fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, String> {
+        let mut nonce = [0u8; 12];
+        OsRng.fill(&mut nonce);
+        
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend(encrypt_data(key, self.key.expose(), &nonce)?);
+        Ok(wrapped)
+    }
+    
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+        if wrapped.len() < 12 {
+            return Err("Wrapped key too short".to_string());
+        }
+        
+        decrypt_data(&wrapped[12..], self.key.expose(), &wrapped[..12])
+            .map_err(|_| "Failed to unwrap key".to_string())
+    }
+    
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.key.expose());
+        Ok(hmac::sign(&key, message).as_ref().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_local_provider_wraps_and_signs() {
+        let provider = LocalKeyProvider::new([3u8; 32]);
+        let other = LocalKeyProvider::new([4u8; 32]);
+        
+        let wrapped = provider.wrap(b"data key").unwrap();
+        assert_eq!(provider.unwrap(&wrapped).unwrap(), b"data key");
+        assert!(other.unwrap(&wrapped).is_err());
+        
+        assert_eq!(provider.sign(b"message").unwrap(), provider.sign(b"message").unwrap());
+        assert_ne!(provider.sign(b"message").unwrap(), other.sign(b"message").unwrap());
+        assert_ne!(provider.key_id(), other.key_id());
+    }
+}
@@ -0,0 +1,168 @@
+use base64::{encode, decode};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::compare::constant_time_eq;
+
+/// Key material that is wiped from memory when dropped
+///
+/// The bytes live in a fixed heap allocation that is locked into RAM where
+/// the platform allows, so they are never swapped to disk, and zeroed on
+/// drop. `Debug` output is redacted. Serializes as a base64 string.
+pub struct SecretBytes {
+    /// Secret bytes; never reallocated, so no stale copies are left behind
+    bytes: Box<[u8]>,
+    /// Whether the allocation is locked into RAM
+    locked: bool,
+}
+
+impl SecretBytes {
+    /// Take ownership of secret bytes
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let bytes = Zeroizing::new(bytes);
+        let bytes: Box<[u8]> = bytes.as_slice().into();
+        let locked = lock(&bytes);
+        
+        Self { bytes, locked }
+    }
+    
+    /// Decode secret bytes from base64
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        decode(encoded)
+            .map(Self::new)
+            .map_err(|e| format!("Failed to decode base64: {}", e))
+    }
+    
+    /// Encode the secret as base64, wiping the encoding once dropped
+    pub fn to_base64(&self) -> Zeroizing<String> {
+        Zeroizing::new(encode(&self.bytes))
+    }
+    
+    /// Borrow the secret bytes
+    pub fn expose(&self) -> &[u8] {
+        &self.bytes
+    }
+    
+    /// Copy the secret into a fixed-size array, e.g. a 32-byte key
+    pub fn to_array<const N: usize>(&self) -> Result<Zeroizing<[u8; N]>, String> {
+        let array: [u8; N] = self.bytes.as_ref()
+            .try_into()
+            .map_err(|_| format!("Expected a {}-byte secret, got {} bytes", N, self.bytes.len()))?;
+        
+        Ok(Zeroizing::new(array))
+    }
+    
+    /// Get the length of the secret
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    
+    /// Check if the secret is empty
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    
+    /// Check if the secret is locked into RAM
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl From<&[u8]> for SecretBytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes.to_vec())
+    }
+}
+
+impl Clone for SecretBytes {
+    fn clone(&self) -> Self {
+        Self::from(self.expose())
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.bytes, &other.bytes)
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes([REDACTED; {}])", self.bytes.len())
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            unlock(&self.bytes);
+        }
+    }
+}
+
+impl Serialize for SecretBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_base64())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = Zeroizing::new(String::deserialize(deserializer)?);
+        Self::from_base64(&encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Lock a buffer into RAM so it is never swapped out
+#[cfg(unix)]
+fn lock(bytes: &[u8]) -> bool {
+    // mlock of an empty range is pointless and may fail on some platforms
+    // SAFETY: the pointer and length describe a live allocation
+    !bytes.is_empty() && unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.len()) } == 0
+}
+
+/// Release a buffer locked by `lock`
+#[cfg(unix)]
+fn unlock(bytes: &[u8]) {
+    // SAFETY: the pointer and length describe a live allocation
+    unsafe {
+        libc::munlock(bytes.as_ptr().cast(), bytes.len());
+    }
+}
+
+/// Memory locking is not available on this platform
+#[cfg(not(unix))]
+fn lock(_bytes: &[u8]) -> bool {
+    false
+}
+
+/// Memory locking is not available on this platform
+#[cfg(not(unix))]
+fn unlock(_bytes: &[u8]) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_secret_bytes_redact_and_round_trip() {
+        let secret = SecretBytes::new(vec![1, 2, 3, 4]);
+        
+        assert_eq!(format!("{:?}", secret), "SecretBytes([REDACTED; 4])");
+        assert_eq!(secret.expose(), &[1, 2, 3, 4]);
+        assert_eq!(secret.clone(), secret);
+        assert_ne!(SecretBytes::from(&[1u8, 2, 3, 5][..]), secret);
+        
+        // Serializes as the same base64 string keys were stored as before
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json, format!("\"{}\"", encode([1, 2, 3, 4])));
+        assert_eq!(serde_json::from_str::<SecretBytes>(&json).unwrap(), secret);
+        
+        assert_eq!(*secret.to_array::<4>().unwrap(), [1, 2, 3, 4]);
+        assert!(secret.to_array::<32>().is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use super::{SessionStore, DEFAULT_SESSION_TTL};
+
+/// In-memory session store
+///
+/// Expired sessions are hidden immediately and dropped by `evict_expired`.
+pub struct MemorySessionStore<T> {
+    /// Time a session is kept after it was last written
+    ttl: Duration,
+    /// Sessions with their expiry, by (mint, viewer)
+    sessions: RwLock<HashMap<(String, String), (T, Instant)>>,
+}
+
+impl<T> MemorySessionStore<T> {
+    /// Create an empty store keeping sessions for `ttl` after their last write
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+    
+    /// Get the time a session is kept after it was last written
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+    
+    /// Drop sessions expired at `now`, returning how many were dropped
+    pub fn evict_expired_at(&self, now: Instant) -> usize {
+        let mut sessions = self.sessions.write().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, (_, expires)| *expires > now);
+        before - sessions.len()
+    }
+}
+
+impl<T> Default for MemorySessionStore<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_TTL)
+    }
+}
+
+impl<T: Clone + Send + Sync> SessionStore<T> for MemorySessionStore<T> {
+    fn get(&self, nft_mint: &str, viewer: &str) -> Result<Option<T>, String> {
+        let now = Instant::now();
+        Ok(self.sessions.read().unwrap()
+            .get(&(nft_mint.to_string(), viewer.to_string()))
+            .filter(|(_, expires)| *expires > now)
+            .map(|(session, _)| session.clone()))
+    }
+    
+    fn put(&self, nft_mint: &str, viewer: &str, session: &T) -> Result<(), String> {
+        self.sessions.write().unwrap().insert(
+            (nft_mint.to_string(), viewer.to_string()),
+            (session.clone(), Instant::now() + self.ttl),
+        );
+        Ok(())
+    }
+    
+    fn remove(&self, nft_mint: &str, viewer: &str) -> Result<(), String> {
+        self.sessions.write().unwrap().remove(&(nft_mint.to_string(), viewer.to_string()));
+        Ok(())
+    }
+    
+    fn sessions(&self, nft_mint: &str) -> Result<Vec<(String, T)>, String> {
+        let now = Instant::now();
+        Ok(self.sessions.read().unwrap().iter()
+            .filter(|((mint, _), (_, expires))| mint == nft_mint && *expires > now)
+            .map(|((_, viewer), (session, _))| (viewer.clone(), session.clone()))
+            .collect())
+    }
+    
+    fn evict_expired(&self) -> Result<usize, String> {
+        Ok(self.evict_expired_at(Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_sessions_expire_after_their_ttl() {
+        let store = MemorySessionStore::new(Duration::from_secs(60));
+        store.put("mint", "viewer", &1u32).unwrap();
+        store.put("mint", "other", &2u32).unwrap();
+        store.put("other-mint", "viewer", &3u32).unwrap();
+        
+        assert_eq!(store.get("mint", "viewer").unwrap(), Some(1));
+        let mut sessions = store.sessions("mint").unwrap();
+        sessions.sort();
+        assert_eq!(sessions, vec![("other".to_string(), 2), ("viewer".to_string(), 1)]);
+        
+        store.remove("mint", "other").unwrap();
+        assert_eq!(store.get("mint", "other").unwrap(), None);
+        
+        assert_eq!(store.evict_expired_at(Instant::now()), 0);
+        assert_eq!(store.evict_expired_at(Instant::now() + Duration::from_secs(61)), 2);
+        assert!(store.sessions("other-mint").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+//! Viewer session state
+//!
+//! Rate limiting, pseudonyms and disclosure auditing keep state per viewer
+//! of each NFT. A `SessionStore` holds that state by (mint, viewer) and
+//! forgets viewers who have been idle for its time to live, so long-running
+//! servers don't grow without bound. `MemorySessionStore` keeps sessions in
+//! the process; with the `redis` feature, `RedisSessionStore` shares them
+//! between processes and survives restarts.
+
+// Import sub-modules
+pub mod memory;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+// Re-export stores
+pub use memory::MemorySessionStore;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisSessionStore;
+
+use std::time::Duration;
+
+/// Default time a session is kept after it was last written
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Store of per-viewer session state, keyed by (mint, viewer)
+///
+/// Writes replace the whole session and restart its time to live. Stores
+/// don't serialize read-modify-write cycles; callers updating a session
+/// from several threads hold their own lock around them.
+pub trait SessionStore<T>: Send + Sync {
+    /// Get a viewer's session, if it hasn't expired
+    fn get(&self, nft_mint: &str, viewer: &str) -> Result<Option<T>, String>;
+    
+    /// Store a viewer's session
+    fn put(&self, nft_mint: &str, viewer: &str, session: &T) -> Result<(), String>;
+    
+    /// Forget a viewer's session
+    fn remove(&self, nft_mint: &str, viewer: &str) -> Result<(), String>;
+    
+    /// Get the live sessions of every viewer of an NFT, in no particular order
+    fn sessions(&self, nft_mint: &str) -> Result<Vec<(String, T)>, String>;
+    
+    /// Drop expired sessions, returning how many were dropped
+    ///
+    /// Stores that expire sessions on their own return 0.
+    fn evict_expired(&self) -> Result<usize, String> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,103 @@
+use redis::Commands;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{SessionStore, DEFAULT_SESSION_TTL};
+
+/// Default prefix of session keys
+pub const DEFAULT_PREFIX: &str = "quantum-veil:session";
+
+/// Session store in Redis, shared by every process using the same server and prefix
+///
+/// Sessions are stored as JSON under `{prefix}:{mint}:{viewer}` and expire
+/// through Redis key expiry. Concurrent writers in different processes
+/// overwrite each other's sessions; the last write wins.
+pub struct RedisSessionStore {
+    /// Server connection
+    connection: Mutex<redis::Connection>,
+    /// Prefix of session keys
+    prefix: String,
+    /// Time a session is kept after it was last written
+    ttl: Duration,
+}
+
+impl RedisSessionStore {
+    /// Connect to a Redis server, e.g. `redis://127.0.0.1/`
+    pub fn open(url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url)
+            .map_err(|e| format!("Failed to open session store: {}", e))?;
+        let connection = client.get_connection()
+            .map_err(|e| format!("Failed to connect to session store: {}", e))?;
+        
+        Ok(Self {
+            connection: Mutex::new(connection),
+            prefix: DEFAULT_PREFIX.to_string(),
+            ttl: DEFAULT_SESSION_TTL,
+        })
+    }
+    
+    /// Keep sessions for `ttl` after their last write, rounded up to whole seconds
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    
+    /// Store sessions under a different key prefix, e.g. one per deployment
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+    
+    /// Get the key of a viewer's session
+    fn key(&self, nft_mint: &str, viewer: &str) -> String {
+        format!("{}:{}:{}", self.prefix, nft_mint, viewer)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> SessionStore<T> for RedisSessionStore {
+    fn get(&self, nft_mint: &str, viewer: &str) -> Result<Option<T>, String> {
+        let json: Option<String> = self.connection.lock().unwrap()
+            .get(self.key(nft_mint, viewer))
+            .map_err(|e| format!("Failed to read session: {}", e))?;
+        
+        json.map(|json| serde_json::from_str(&json).map_err(|e| format!("Failed to parse session: {}", e)))
+            .transpose()
+    }
+    
+    fn put(&self, nft_mint: &str, viewer: &str, session: &T) -> Result<(), String> {
+        let json = serde_json::to_string(session)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        let seconds = (self.ttl.as_secs() + u64::from(self.ttl.subsec_nanos() > 0)).max(1);
+        
+        self.connection.lock().unwrap()
+            .set_ex(self.key(nft_mint, viewer), json, seconds as usize)
+            .map_err(|e| format!("Failed to write session: {}", e))
+    }
+    
+    fn remove(&self, nft_mint: &str, viewer: &str) -> Result<(), String> {
+        self.connection.lock().unwrap()
+            .del(self.key(nft_mint, viewer))
+            .map_err(|e| format!("Failed to remove session: {}", e))
+    }
+    
+    fn sessions(&self, nft_mint: &str) -> Result<Vec<(String, T)>, String> {
+        let prefix = self.key(nft_mint, "");
+        let keys: Vec<String> = {
+            let mut connection = self.connection.lock().unwrap();
+            let keys = connection.scan_match(format!("{}*", prefix))
+                .map_err(|e| format!("Failed to list sessions: {}", e))?;
+            keys.collect()
+        };
+        
+        // Sessions expiring between the scan and the read are skipped
+        let mut sessions = Vec::with_capacity(keys.len());
+        for key in keys {
+            let viewer = &key[prefix.len()..];
+            if let Some(session) = self.get(nft_mint, viewer)? {
+                sessions.push((viewer.to_string(), session));
+            }
+        }
+        Ok(sessions)
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Serialize, Deserialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::privacy_levels::PrivacyLevel;
+
+/// Privacy levels a viewer can be cleared for, from least to most private
+const MASKED_LEVELS: [PrivacyLevel; 4] = [
+    PrivacyLevel::Light,
+    PrivacyLevel::Medium,
+    PrivacyLevel::Heavy,
+    PrivacyLevel::Complete,
+];
+
+/// On-chain access grants of a privacy wrapper
+pub trait WrapperAccess {
+    /// Owner of the wrapped NFT
+    fn owner(&self) -> Pubkey;
+    
+    /// Access level granted to a viewer, 0 if none
+    fn access_level(&self, viewer: &Pubkey) -> u8;
+}
+
+/// Mapping from on-chain access levels to the privacy levels they see through
+///
+/// A viewer is cleared for a privacy level once their access level reaches
+/// its threshold, and then sees unmasked every data type at or below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessPolicy {
+    /// Minimum access level for `Light`, `Medium`, `Heavy`, and `Complete`
+    thresholds: [u8; 4],
+}
+
+impl AccessPolicy {
+    /// Create a policy from the thresholds for `Light` through `Complete`
+    ///
+    /// Thresholds must be non-decreasing and above 0, since 0 means no grant.
+    pub fn new(thresholds: [u8; 4]) -> Result<Self, String> {
+        if thresholds[0] == 0 {
+            return Err("Access level 0 means no access and cannot clear a privacy level".to_string());
+        }
+        
+        if thresholds.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("Access thresholds must not decrease with privacy level".to_string());
+        }
+        
+        Ok(Self { thresholds })
+    }
+    
+    /// Get the privacy level an access level is cleared for
+    pub fn clearance(&self, access_level: u8) -> PrivacyLevel {
+        MASKED_LEVELS.iter()
+            .zip(self.thresholds)
+            .take_while(|(_, threshold)| access_level >= *threshold)
+            .last()
+            .map_or(PrivacyLevel::None, |(level, _)| *level)
+    }
+    
+    /// Get the lowest access level cleared for a privacy level
+    pub fn min_access_level(&self, level: PrivacyLevel) -> u8 {
+        match level {
+            PrivacyLevel::None => 0,
+            _ => self.thresholds[level as usize - 1],
+        }
+    }
+    
+    /// Check if an access level sees data at a privacy level unmasked
+    pub fn can_view(&self, access_level: u8, level: PrivacyLevel) -> bool {
+        level as u8 <= self.clearance(access_level) as u8
+    }
+    
+    /// Get the privacy level a viewer is cleared for on a wrapper
+    ///
+    /// The owner is cleared for everything.
+    pub fn level_for_viewer(&self, wrapper_state: &impl WrapperAccess, viewer: &Pubkey) -> PrivacyLevel {
+        if wrapper_state.owner() == *viewer {
+            return PrivacyLevel::Complete;
+        }
+        
+        self.clearance(wrapper_state.access_level(viewer))
+    }
+}
+
+impl Default for AccessPolicy {
+    /// Access levels 1-4 map one-to-one onto `Light` through `Complete`
+    fn default() -> Self {
+        Self { thresholds: [1, 2, 3, 4] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    
+    struct Grants {
+        owner: Pubkey,
+        levels: HashMap<Pubkey, u8>,
+    }
+    
+    impl WrapperAccess for Grants {
+        fn owner(&self) -> Pubkey {
+            self.owner
+        }
+        
+        fn access_level(&self, viewer: &Pubkey) -> u8 {
+            self.levels.get(viewer).copied().unwrap_or(0)
+        }
+    }
+    
+    #[test]
+    fn test_default_policy_boundaries() {
+        let policy = AccessPolicy::default();
+        
+        assert_eq!(policy.clearance(0), PrivacyLevel::None);
+        assert_eq!(policy.clearance(1), PrivacyLevel::Light);
+        assert_eq!(policy.clearance(3), PrivacyLevel::Heavy);
+        assert_eq!(policy.clearance(4), PrivacyLevel::Complete);
+        assert_eq!(policy.clearance(u8::MAX), PrivacyLevel::Complete);
+        
+        assert!(policy.can_view(0, PrivacyLevel::None));
+        assert!(!policy.can_view(0, PrivacyLevel::Light));
+        assert!(policy.can_view(2, PrivacyLevel::Medium));
+        assert!(!policy.can_view(2, PrivacyLevel::Heavy));
+    }
+    
+    #[test]
+    fn test_custom_thresholds() {
+        let policy = AccessPolicy::new([10, 50, 50, 200]).unwrap();
+        
+        assert_eq!(policy.clearance(9), PrivacyLevel::None);
+        assert_eq!(policy.clearance(10), PrivacyLevel::Light);
+        assert_eq!(policy.clearance(49), PrivacyLevel::Light);
+        // Equal thresholds clear both levels at once
+        assert_eq!(policy.clearance(50), PrivacyLevel::Heavy);
+        assert_eq!(policy.clearance(199), PrivacyLevel::Heavy);
+        assert_eq!(policy.clearance(200), PrivacyLevel::Complete);
+        
+        for level in MASKED_LEVELS {
+            assert!(policy.clearance(policy.min_access_level(level)) as u8 >= level as u8);
+        }
+        assert_eq!(policy.min_access_level(PrivacyLevel::None), 0);
+        
+        assert!(AccessPolicy::new([0, 1, 2, 3]).is_err());
+        assert!(AccessPolicy::new([1, 3, 2, 4]).is_err());
+    }
+    
+    #[test]
+    fn test_level_for_viewer() {
+        let owner = Pubkey::new_unique();
+        let viewer = Pubkey::new_unique();
+        let grants = Grants { owner, levels: HashMap::from([(viewer, 2)]) };
+        let policy = AccessPolicy::default();
+        
+        assert_eq!(policy.level_for_viewer(&grants, &owner), PrivacyLevel::Complete);
+        assert_eq!(policy.level_for_viewer(&grants, &viewer), PrivacyLevel::Medium);
+        assert_eq!(policy.level_for_viewer(&grants, &Pubkey::new_unique()), PrivacyLevel::None);
+    }
+}
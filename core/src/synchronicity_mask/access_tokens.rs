@@ -0,0 +1,324 @@
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_512};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use zeroize::Zeroize;
+
+use crate::quantum_veil::{constant_time_eq, SecretBytes};
+
+use super::ring::RingSignature;
+
+/// Domain separator for hashing token nonces to points
+const TOKEN_POINT_DOMAIN: &[u8] = b"quantum-veil/blind-token/point/v1";
+
+/// Domain separator for per-level issuer keys
+const ISSUER_KEY_DOMAIN: &[u8] = b"quantum-veil/blind-token/key/v1";
+
+/// Domain separator for contexts member key images are linked under
+const MEMBER_CONTEXT_DOMAIN: &[u8] = b"quantum-veil/blind-token/member/v1";
+
+/// Domain separator for issuance proof challenges
+const PROOF_DOMAIN: &[u8] = b"quantum-veil/blind-token/dleq/v1";
+
+/// Anonymous access tokens for masked VRM streams, privacy pass style
+///
+/// A viewer blinds a random nonce and the issuer evaluates it under its
+/// secret key for an access level, with a proof that it used the published
+/// key for that level. The viewer unblinds the result into a token that only
+/// the issuer can check, and that it cannot link back to the issuance, so
+/// redeeming it reveals neither the viewer's wallet nor which request it came
+/// from. Each token is redeemed once.
+pub struct TokenIssuer {
+    /// NFT the tokens grant access to
+    nft_mint: Pubkey,
+    /// Seed the per-level keys are derived from
+    seed: SecretBytes,
+    /// Nonces of tokens already redeemed
+    spent: Mutex<HashSet<[u8; 32]>>,
+    /// Key images of group members already issued a token, by epoch
+    member_images: Mutex<HashSet<(u64, [u8; 32])>>,
+}
+
+/// Blinded token sent to the issuer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRequest {
+    /// Access level requested
+    pub level: u8,
+    /// Blinded token point
+    pub blinded: [u8; 32],
+}
+
+/// Issuer's evaluation of a blinded token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenResponse {
+    /// Blinded token point under the issuer key
+    pub evaluated: [u8; 32],
+    /// Challenge of the proof that the published key was used
+    pub challenge: [u8; 32],
+    /// Response of the proof that the published key was used
+    pub response: [u8; 32],
+}
+
+/// Viewer's state between requesting and finalizing a token
+pub struct PendingToken {
+    /// NFT the token is for
+    nft_mint: Pubkey,
+    /// Access level requested
+    level: u8,
+    /// Random token nonce
+    nonce: [u8; 32],
+    /// Blinding factor
+    blind: Scalar,
+    /// Blinded token point, as sent
+    blinded: RistrettoPoint,
+}
+
+/// Unblinded token a viewer redeems for access
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindToken {
+    /// NFT the token grants access to
+    pub nft_mint: Pubkey,
+    /// Access level granted
+    pub level: u8,
+    /// Random token nonce
+    pub nonce: [u8; 32],
+    /// Token point under the issuer key
+    pub evaluated: [u8; 32],
+}
+
+/// Access level won by redeeming a token, for masking one stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRedemption {
+    /// NFT the access is for
+    nft_mint: Pubkey,
+    /// Access level granted
+    level: u8,
+}
+
+impl TokenRedemption {
+    /// Get the NFT the access is for
+    pub fn nft_mint(&self) -> &Pubkey {
+        &self.nft_mint
+    }
+    
+    /// Get the access level granted
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+}
+
+impl TokenIssuer {
+    /// Create an issuer for an NFT from a secret seed
+    pub fn new(nft_mint: &Pubkey, seed: SecretBytes) -> Self {
+        Self {
+            nft_mint: *nft_mint,
+            seed,
+            spent: Mutex::new(HashSet::new()),
+            member_images: Mutex::new(HashSet::new()),
+        }
+    }
+    
+    /// Create an issuer with a fresh random seed
+    pub fn generate(nft_mint: &Pubkey) -> Self {
+        Self::new(nft_mint, SecretBytes::new(OsRng.gen::<[u8; 32]>().to_vec()))
+    }
+    
+    /// Derive the secret key for an access level
+    fn level_key(&self, level: u8) -> Scalar {
+        let mut hasher = Sha3_512::new();
+        hasher.update(ISSUER_KEY_DOMAIN);
+        hasher.update(self.seed.expose());
+        hasher.update(self.nft_mint.as_ref());
+        hasher.update([level]);
+        Scalar::from_bytes_mod_order_wide(&wide_hash(hasher))
+    }
+    
+    /// Get the public key for an access level, published for viewers to check issuance against
+    pub fn public_key(&self, level: u8) -> [u8; 32] {
+        (self.level_key(level) * RISTRETTO_BASEPOINT_POINT).compress().to_bytes()
+    }
+    
+    /// Evaluate a blinded token
+    ///
+    /// The issuer learns the level but nothing about the token it will later
+    /// see redeemed; checking the requester is entitled to the level is up to
+    /// the caller.
+    pub fn issue(&self, request: &TokenRequest) -> Result<TokenResponse, String> {
+        let blinded = decompress(&request.blinded)?;
+        let key = self.level_key(request.level);
+        let evaluated = key * blinded;
+        
+        // Prove log_G(public key) == log_blinded(evaluated), so the key can't be swapped per viewer
+        let commitment_scalar = random_scalar();
+        let challenge = proof_challenge(
+            &(key * RISTRETTO_BASEPOINT_POINT),
+            &blinded,
+            &evaluated,
+            &(commitment_scalar * RISTRETTO_BASEPOINT_POINT),
+            &(commitment_scalar * blinded),
+        );
+        let response = commitment_scalar - challenge * key;
+        
+        Ok(TokenResponse {
+            evaluated: evaluated.compress().to_bytes(),
+            challenge: challenge.to_bytes(),
+            response: response.to_bytes(),
+        })
+    }
+    
+    /// Evaluate a blinded token for an anonymous member of an approved group
+    ///
+    /// The request must carry a linkable ring signature over it by one of
+    /// `ring`'s member keys, e.g. a DAO's member list. The issuer learns only
+    /// that some member asked, and each member gets one token per epoch.
+    pub fn issue_to_member(
+        &self,
+        request: &TokenRequest,
+        ring: &[[u8; 32]],
+        signature: &RingSignature,
+        epoch: u64,
+    ) -> Result<TokenResponse, String> {
+        signature.verify(ring, &member_context(&self.nft_mint, epoch), &request.signing_message(&self.nft_mint))?;
+        
+        if !self.member_images.lock().unwrap().insert((epoch, signature.key_image)) {
+            return Err("Group member already issued a token this epoch".to_string());
+        }
+        
+        self.issue(request)
+    }
+    
+    /// Redeem a token once, returning the access it grants
+    pub fn redeem(&self, token: &BlindToken) -> Result<TokenRedemption, String> {
+        if token.nft_mint != self.nft_mint {
+            return Err("Blind token was issued for a different NFT".to_string());
+        }
+        
+        let expected = self.level_key(token.level) * token_point(&token.nft_mint, token.level, &token.nonce);
+        if !constant_time_eq(expected.compress().as_bytes(), &token.evaluated) {
+            return Err("Invalid blind token".to_string());
+        }
+        
+        if !self.spent.lock().unwrap().insert(token.nonce) {
+            return Err("Blind token already redeemed".to_string());
+        }
+        
+        Ok(TokenRedemption { nft_mint: token.nft_mint, level: token.level })
+    }
+}
+
+impl TokenRequest {
+    /// Get the message a group member signs to request this token for an NFT
+    pub fn signing_message(&self, nft_mint: &Pubkey) -> Vec<u8> {
+        let mut message = nft_mint.to_bytes().to_vec();
+        message.push(self.level);
+        message.extend_from_slice(&self.blinded);
+        message
+    }
+}
+
+/// Get the context member key images are linked under for an NFT and epoch
+pub fn member_context(nft_mint: &Pubkey, epoch: u64) -> Vec<u8> {
+    let mut context = MEMBER_CONTEXT_DOMAIN.to_vec();
+    context.extend_from_slice(nft_mint.as_ref());
+    context.extend_from_slice(&epoch.to_le_bytes());
+    context
+}
+
+impl PendingToken {
+    /// Start a token request for an access level on an NFT
+    pub fn new(nft_mint: &Pubkey, level: u8) -> (Self, TokenRequest) {
+        let nonce = OsRng.gen::<[u8; 32]>();
+        let blind = random_scalar();
+        let blinded = blind * token_point(nft_mint, level, &nonce);
+        
+        let request = TokenRequest { level, blinded: blinded.compress().to_bytes() };
+        (Self { nft_mint: *nft_mint, level, nonce, blind, blinded }, request)
+    }
+    
+    /// Check the issuer's proof against its published key and unblind the token
+    pub fn finalize(self, response: &TokenResponse, public_key: &[u8; 32]) -> Result<BlindToken, String> {
+        let public_key = decompress(public_key)?;
+        let evaluated = decompress(&response.evaluated)?;
+        let challenge = canonical_scalar(&response.challenge)?;
+        let proof_response = canonical_scalar(&response.response)?;
+        
+        let expected = proof_challenge(
+            &public_key,
+            &self.blinded,
+            &evaluated,
+            &(proof_response * RISTRETTO_BASEPOINT_POINT + challenge * public_key),
+            &(proof_response * self.blinded + challenge * evaluated),
+        );
+        if expected != challenge {
+            return Err("Blind token was not issued under the published key".to_string());
+        }
+        
+        Ok(BlindToken {
+            nft_mint: self.nft_mint,
+            level: self.level,
+            nonce: self.nonce,
+            evaluated: (self.blind.invert() * evaluated).compress().to_bytes(),
+        })
+    }
+}
+
+/// Hash a token nonce, bound to its NFT and level, to a point
+fn token_point(nft_mint: &Pubkey, level: u8, nonce: &[u8; 32]) -> RistrettoPoint {
+    let mut hasher = Sha3_512::new();
+    hasher.update(TOKEN_POINT_DOMAIN);
+    hasher.update(nft_mint.as_ref());
+    hasher.update([level]);
+    hasher.update(nonce);
+    RistrettoPoint::from_uniform_bytes(&wide_hash(hasher))
+}
+
+/// Hash the proof transcript to a challenge scalar
+fn proof_challenge(
+    public_key: &RistrettoPoint,
+    blinded: &RistrettoPoint,
+    evaluated: &RistrettoPoint,
+    key_commitment: &RistrettoPoint,
+    blinded_commitment: &RistrettoPoint,
+) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.update(PROOF_DOMAIN);
+    for point in [public_key, blinded, evaluated, key_commitment, blinded_commitment] {
+        hasher.update(point.compress().as_bytes());
+    }
+    Scalar::from_bytes_mod_order_wide(&wide_hash(hasher))
+}
+
+/// Finish a hash as the 64 bytes wide reductions take
+pub(super) fn wide_hash(hasher: Sha3_512) -> [u8; 64] {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    wide
+}
+
+/// Draw a uniformly random scalar
+pub(super) fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    OsRng.fill(&mut bytes[..]);
+    let scalar = Scalar::from_bytes_mod_order_wide(&bytes);
+    bytes.zeroize();
+    scalar
+}
+
+/// Decode a scalar, rejecting non-canonical encodings
+pub(super) fn canonical_scalar(bytes: &[u8; 32]) -> Result<Scalar, String> {
+    Scalar::from_canonical_bytes(*bytes).ok_or_else(|| "Invalid scalar encoding".to_string())
+}
+
+/// Decode a compressed Ristretto point
+pub(super) fn decompress(bytes: &[u8; 32]) -> Result<RistrettoPoint, String> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| "Invalid point encoding".to_string())
+}
@@ -0,0 +1,99 @@
+use serde::{Serialize, Deserialize};
+use std::f64::consts::LN_2;
+
+use super::token::AccessToken;
+
+/// Bloom filter of revoked access token IDs
+///
+/// Compact enough to push to every renderer. Lookups never miss a revoked
+/// token but may occasionally reject a valid one, at the configured rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBlacklist {
+    /// Filter bits
+    bits: Vec<u64>,
+    /// Number of bits in the filter
+    num_bits: u64,
+    /// Number of hash functions
+    num_hashes: u32,
+}
+
+impl TokenBlacklist {
+    /// Create a blacklist sized for `expected_revocations` at the given false positive rate
+    pub fn new(expected_revocations: usize, false_positive_rate: f64) -> Self {
+        let n = expected_revocations.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        
+        // Optimal filter size and hash count for n items at rate p
+        let num_bits = ((-n * p.ln()) / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * LN_2).round().max(1.0) as u32;
+        
+        Self {
+            bits: vec![0; ((num_bits + 63) / 64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+    
+    /// Get the bit positions for a token ID using double hashing
+    fn positions(&self, id: &[u8; 32]) -> impl Iterator<Item = u64> + '_ {
+        let h1 = u64::from_le_bytes(id[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(id[8..16].try_into().unwrap()) | 1;
+        
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+    
+    /// Revoke a token
+    pub fn revoke(&mut self, token: &AccessToken) {
+        self.revoke_id(&token.id());
+    }
+    
+    /// Revoke a token by its ID, for issuers that keep IDs rather than tokens
+    pub fn revoke_id(&mut self, id: &[u8; 32]) {
+        let positions: Vec<u64> = self.positions(id).collect();
+        for bit in positions {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+    
+    /// Check if a token may have been revoked
+    pub fn is_revoked(&self, token: &AccessToken) -> bool {
+        self.positions(&token.id())
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+    
+    /// Encode the filter as its bit count, hash count, and little-endian bit words
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.bits.len() * 8);
+        bytes.extend_from_slice(&self.num_bits.to_le_bytes());
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+    
+    /// Decode a filter from `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 12 || (bytes.len() - 12) % 8 != 0 {
+            return Err(format!("Invalid blacklist length: {}", bytes.len()));
+        }
+        
+        let num_bits = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let num_hashes = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        let bits: Vec<u64> = bytes[12..].chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        
+        if num_bits == 0 || num_hashes == 0 || bits.len() as u64 != (num_bits + 63) / 64 {
+            return Err("Blacklist size doesn't match its bit count".to_string());
+        }
+        
+        Ok(Self { bits, num_bits, num_hashes })
+    }
+}
+
+impl Default for TokenBlacklist {
+    fn default() -> Self {
+        Self::new(1024, 0.001)
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use super::vrm_data::PositionData;
+
+/// Number of times the cell size may double while searching for a k-anonymous grid
+const MAX_DOUBLINGS: u32 = 16;
+
+/// Grid cell index along each axis
+type Cell = (i64, i64, i64);
+
+/// Shared grid that masked positions are snapped to
+///
+/// Every cell that holds an avatar holds at least `k`, so a snapped position
+/// only narrows an avatar down to a group of `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CrowdGrid {
+    /// Edge length of a cell
+    pub cell_size: f32,
+    /// Minimum number of avatars sharing each occupied cell
+    pub k: usize,
+}
+
+impl CrowdGrid {
+    /// Snap a position to the center of its cell
+    pub fn blend(&self, position: &PositionData) -> PositionData {
+        let (x, y, z) = cell_of(position, self.cell_size);
+        let center = |index: i64| (index as f32 + 0.5) * self.cell_size;
+        
+        PositionData { x: center(x), y: center(y), z: center(z) }
+    }
+}
+
+/// Aggregates the positions of masked avatars in one space to size their shared grid
+///
+/// Renderers report positions to a coordinator holding the `CrowdBlend`,
+/// which hands every renderer the same `CrowdGrid`.
+#[derive(Debug, Clone)]
+pub struct CrowdBlend {
+    /// Minimum number of avatars sharing each occupied cell
+    k: usize,
+    /// Smallest cell size tried
+    base_cell_size: f32,
+    /// Latest position of each avatar
+    positions: HashMap<String, PositionData>,
+}
+
+impl CrowdBlend {
+    /// Create an aggregator for k-anonymity, starting from `base_cell_size`
+    pub fn new(k: usize, base_cell_size: f32) -> Result<Self, String> {
+        if k < 2 {
+            return Err("k-anonymity needs k of at least 2".to_string());
+        }
+        
+        if base_cell_size.is_nan() || base_cell_size <= 0.0 {
+            return Err(format!("Invalid cell size: {}", base_cell_size));
+        }
+        
+        Ok(Self {
+            k,
+            base_cell_size,
+            positions: HashMap::new(),
+        })
+    }
+    
+    /// Report an avatar's latest position
+    pub fn report(&mut self, avatar_id: &str, position: PositionData) {
+        self.positions.insert(avatar_id.to_string(), position);
+    }
+    
+    /// Remove an avatar that left the space
+    pub fn remove(&mut self, avatar_id: &str) {
+        self.positions.remove(avatar_id);
+    }
+    
+    /// Get the number of avatars in the space
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    
+    /// Check if no avatars are in the space
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    
+    /// Get the finest grid where every occupied cell holds at least k avatars
+    ///
+    /// Cell sizes double from the base size, so each coarser grid merges whole
+    /// cells of the finer one and occupancy only grows.
+    pub fn grid(&self) -> Result<CrowdGrid, String> {
+        if self.positions.len() < self.k {
+            return Err(format!(
+                "Crowd of {} is too small for {}-anonymity",
+                self.positions.len(), self.k
+            ));
+        }
+        
+        let mut cell_size = self.base_cell_size;
+        for _ in 0..=MAX_DOUBLINGS {
+            let mut occupancy: HashMap<Cell, usize> = HashMap::new();
+            for position in self.positions.values() {
+                *occupancy.entry(cell_of(position, cell_size)).or_default() += 1;
+            }
+            
+            if occupancy.values().all(|count| *count >= self.k) {
+                return Ok(CrowdGrid { cell_size, k: self.k });
+            }
+            
+            cell_size *= 2.0;
+        }
+        
+        Err(format!("Crowd is too spread out for {}-anonymity", self.k))
+    }
+}
+
+/// Get the grid cell containing a position
+fn cell_of(position: &PositionData, cell_size: f32) -> Cell {
+    let index = |value: f32| (value / cell_size).floor() as i64;
+    (index(position.x), index(position.y), index(position.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn at(x: f32, z: f32) -> PositionData {
+        PositionData { x, y: 0.0, z }
+    }
+    
+    #[test]
+    fn test_grid_gives_every_avatar_k_companions() {
+        let mut crowd = CrowdBlend::new(3, 1.0).unwrap();
+        let positions = [at(0.2, 0.3), at(0.7, 0.1), at(1.4, 0.9), at(5.5, 5.5), at(6.2, 5.9), at(7.9, 7.1)];
+        for (i, position) in positions.iter().enumerate() {
+            crowd.report(&format!("avatar{}", i), position.clone());
+        }
+        
+        let grid = crowd.grid().unwrap();
+        assert!(grid.cell_size > 1.0);
+        
+        // Count avatars snapped to each point
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for position in &positions {
+            let blended = grid.blend(position);
+            assert!((blended.x - position.x).abs() <= grid.cell_size);
+            *counts.entry(format!("{:?}", (blended.x, blended.y, blended.z))).or_default() += 1;
+        }
+        assert!(counts.values().all(|count| *count >= 3));
+    }
+    
+    #[test]
+    fn test_small_crowds_are_rejected() {
+        let mut crowd = CrowdBlend::new(3, 1.0).unwrap();
+        crowd.report("a", at(0.0, 0.0));
+        crowd.report("b", at(0.0, 0.0));
+        assert!(crowd.grid().is_err());
+        
+        crowd.report("c", at(0.1, 0.1));
+        assert!(crowd.grid().is_ok());
+        
+        crowd.remove("c");
+        assert!(crowd.grid().is_err());
+        
+        assert!(CrowdBlend::new(1, 1.0).is_err());
+        assert!(CrowdBlend::new(3, 0.0).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    hash::hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::vrm_data::VrmData;
+
+/// Domain separator for signed frame messages
+const FRAME_DOMAIN: &[u8] = b"quantum-veil/vrm-frame/v1";
+
+/// How far ahead of the local clock a frame timestamp may be
+const MAX_CLOCK_SKEW_MS: u64 = 5_000;
+
+/// VRM data signed by its publisher, with replay protection
+///
+/// Consumers check the signature against the publishers they trust and pass
+/// frames through a `ReplayGuard` to drop replayed and stale frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VrmFrame {
+    /// NFT the data belongs to
+    pub nft_mint: Pubkey,
+    /// Per-publisher sequence number, increasing with every frame
+    pub sequence: u64,
+    /// Capture time as a Unix timestamp in milliseconds
+    pub timestamp: u64,
+    /// VRM data carried by the frame
+    pub data: VrmData,
+    /// Publisher that signed the frame
+    pub publisher: Pubkey,
+    /// Ed25519 signature over the frame fields
+    pub signature: Signature,
+}
+
+impl VrmFrame {
+    /// Sign a frame captured now
+    pub fn sign(publisher: &Keypair, nft_mint: &Pubkey, sequence: u64, data: VrmData) -> Self {
+        Self::sign_at(publisher, nft_mint, sequence, now_ms(), data)
+    }
+    
+    /// Sign a frame with an explicit capture time, e.g. when re-signing masked data
+    pub fn sign_at(
+        publisher: &Keypair,
+        nft_mint: &Pubkey,
+        sequence: u64,
+        timestamp: u64,
+        data: VrmData,
+    ) -> Self {
+        let mut frame = Self {
+            nft_mint: *nft_mint,
+            sequence,
+            timestamp,
+            data,
+            publisher: publisher.pubkey(),
+            signature: Signature::default(),
+        };
+        frame.signature = publisher.sign_message(&frame.message());
+        frame
+    }
+    
+    /// Get the signed message: the domain separator, frame fields, and data hash
+    ///
+    /// The data is hashed in canonical JSON, with object keys sorted, so the
+    /// hash survives a round trip through any serializer.
+    fn message(&self) -> Vec<u8> {
+        let data_json = serde_json::to_value(&self.data)
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        
+        let mut message = Vec::with_capacity(FRAME_DOMAIN.len() + 32 + 8 + 8 + 32 + 32);
+        message.extend_from_slice(FRAME_DOMAIN);
+        message.extend_from_slice(self.nft_mint.as_ref());
+        message.extend_from_slice(&self.sequence.to_le_bytes());
+        message.extend_from_slice(&self.timestamp.to_le_bytes());
+        message.extend_from_slice(self.publisher.as_ref());
+        message.extend_from_slice(hash(data_json.as_bytes()).as_ref());
+        message
+    }
+    
+    /// Verify the signature and that the publisher is trusted
+    pub fn verify(&self, trusted_publishers: &[Pubkey]) -> Result<(), String> {
+        if !trusted_publishers.contains(&self.publisher) {
+            return Err(format!("Untrusted frame publisher: {}", self.publisher));
+        }
+        
+        if !self.signature.verify(self.publisher.as_ref(), &self.message()) {
+            return Err("Invalid frame signature".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Rejects replayed, reordered, and stale frames
+///
+/// Tracks the last sequence number accepted from each publisher per NFT.
+#[derive(Debug, Clone)]
+pub struct ReplayGuard {
+    /// Oldest frame age accepted
+    max_age: Duration,
+    /// Last accepted sequence by publisher and NFT
+    last_sequence: HashMap<(Pubkey, Pubkey), u64>,
+}
+
+impl ReplayGuard {
+    /// Create a guard accepting frames up to `max_age` old
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            last_sequence: HashMap::new(),
+        }
+    }
+    
+    /// Check a frame's freshness and sequence, recording it if accepted
+    pub fn check(&mut self, frame: &VrmFrame) -> Result<(), String> {
+        let now = now_ms();
+        if frame.timestamp > now + MAX_CLOCK_SKEW_MS {
+            return Err(format!("Frame timestamp {} is in the future", frame.timestamp));
+        }
+        if now.saturating_sub(frame.timestamp) > self.max_age.as_millis() as u64 {
+            return Err(format!("Frame {} is too old", frame.sequence));
+        }
+        
+        let key = (frame.publisher, frame.nft_mint);
+        if let Some(last) = self.last_sequence.get(&key) {
+            if frame.sequence <= *last {
+                return Err(format!("Replayed frame: sequence {} after {}", frame.sequence, last));
+            }
+        }
+        self.last_sequence.insert(key, frame.sequence);
+        
+        Ok(())
+    }
+}
+
+impl Default for ReplayGuard {
+    /// Accept frames up to 30 seconds old
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+/// Get the current Unix timestamp in milliseconds
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synchronicity_mask::PositionData;
+    
+    fn sample_data() -> VrmData {
+        let mut data = VrmData::new();
+        data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        data.add_custom_data("mood", serde_json::json!("glitchy"));
+        data.add_custom_data("aura", serde_json::json!(0.5));
+        data
+    }
+    
+    #[test]
+    fn test_frame_signature_survives_serialization() {
+        let publisher = Keypair::new();
+        let frame = VrmFrame::sign(&publisher, &Pubkey::new_unique(), 1, sample_data());
+        
+        let decoded: VrmFrame = serde_json::from_str(&serde_json::to_string(&frame).unwrap()).unwrap();
+        assert!(decoded.verify(&[publisher.pubkey()]).is_ok());
+        assert!(decoded.verify(&[Pubkey::new_unique()]).is_err());
+        
+        // Tampered data no longer matches the signature
+        let mut tampered = decoded.clone();
+        tampered.data.position.x = 9.0;
+        assert!(tampered.verify(&[publisher.pubkey()]).is_err());
+    }
+    
+    #[test]
+    fn test_replay_guard_rejects_replays_and_stale_frames() {
+        let publisher = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        let mut guard = ReplayGuard::new(Duration::from_secs(10));
+        
+        let first = VrmFrame::sign(&publisher, &nft_mint, 1, sample_data());
+        let second = VrmFrame::sign(&publisher, &nft_mint, 2, sample_data());
+        assert!(guard.check(&first).is_ok());
+        assert!(guard.check(&second).is_ok());
+        assert!(guard.check(&first).is_err());
+        assert!(guard.check(&second).is_err());
+        
+        // Sequences are tracked per NFT
+        assert!(guard.check(&VrmFrame::sign(&publisher, &Pubkey::new_unique(), 1, sample_data())).is_ok());
+        
+        let stale = VrmFrame::sign_at(&publisher, &nft_mint, 3, now_ms() - 60_000, sample_data());
+        assert!(guard.check(&stale).is_err());
+        let future = VrmFrame::sign_at(&publisher, &nft_mint, 4, now_ms() + 60_000, sample_data());
+        assert!(guard.check(&future).is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! Mask parameters for GPU masking
+//!
+//! Game engines would rather add noise in a compute shader than pass every
+//! frame through `apply_mask`. `SynchronicityMask::export_mask_params`
+//! resolves a viewer's access once per seed epoch into a `MaskParams`, a
+//! 32-byte struct laid out for a uniform buffer, and `export_joint_clamps`
+//! packs the bone profile for a storage buffer. Shaders draw noise from the
+//! counter-based hash of `quantum_veil_primitives::simd`, and
+//! `MaskParams::apply` is the reference implementation their output must
+//! match.
+//!
+//! Only the per-frame transform streams are covered: position, rotation,
+//! scale, and joint rotations. Voice and interactions are masked on the CPU.
+
+use quantum_veil_primitives::simd;
+use serde::{Serialize, Deserialize};
+
+use super::privacy_levels::PrivacyLevel;
+use super::skeleton::{from_euler_degrees, to_euler_degrees};
+use super::vrm_data::RotationData;
+
+/// Stream index of the avatar position
+pub const STREAM_POSITION: usize = 0;
+/// Stream index of the avatar rotation
+pub const STREAM_ROTATION: usize = 1;
+/// Stream index of the avatar scale
+pub const STREAM_SCALE: usize = 2;
+/// Stream index of the gesture joint rotations
+pub const STREAM_JOINTS: usize = 3;
+
+/// Leave the stream unchanged
+pub const MODE_NONE: u32 = 0;
+/// Add seeded noise at the stream's intensity
+pub const MODE_NOISE: u32 = 1;
+/// Replace the stream with seeded random values
+pub const MODE_REPLACE: u32 = 2;
+
+/// Per-viewer, per-epoch parameters of a GPU mask
+///
+/// Laid out as `vec2<u32>, u32, u32, vec4<f32>`, which is the same in
+/// std140 and std430.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MaskParams {
+    /// Noise seed of the epoch, as low and high words
+    pub seed: [u32; 2],
+    /// Seed epoch the parameters are valid for
+    pub seed_epoch: u32,
+    /// Two bits of mode per stream, stream 0 in the lowest bits
+    pub modes: u32,
+    /// Noise intensity per stream
+    pub intensity: [f32; 4],
+}
+
+/// Rotation limits of one joint, in radians, padded to `vec4<f32>` pairs
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct JointClamp {
+    /// Lowest X/Y/Z Euler angle, with unused padding
+    pub min: [f32; 4],
+    /// Highest X/Y/Z Euler angle, with unused padding
+    pub max: [f32; 4],
+}
+
+impl JointClamp {
+    /// Limits that leave a joint unconstrained
+    pub const UNCONSTRAINED: Self = Self {
+        min: [-core::f32::consts::PI, -core::f32::consts::PI, -core::f32::consts::PI, 0.0],
+        max: [core::f32::consts::PI, core::f32::consts::PI, core::f32::consts::PI, 0.0],
+    };
+}
+
+/// Transform streams of one frame, as uploaded to the GPU
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PackedFrame {
+    /// Avatar position
+    pub position: [f32; 3],
+    /// Avatar rotation quaternion
+    pub rotation: [f32; 4],
+    /// Avatar scale
+    pub scale: [f32; 3],
+    /// Joint rotation quaternions, in the order of the exported joint clamps
+    pub joints: Vec<[f32; 4]>,
+}
+
+impl MaskParams {
+    /// Size of the packed parameters in bytes
+    pub const SIZE: usize = 32;
+    
+    /// Create parameters leaving every stream unchanged
+    pub fn unmasked(seed: u64, seed_epoch: u32) -> Self {
+        Self {
+            seed: [seed as u32, (seed >> 32) as u32],
+            seed_epoch,
+            ..Self::default()
+        }
+    }
+    
+    /// Mask a stream at a privacy level
+    pub(super) fn set_level(&mut self, stream: usize, level: PrivacyLevel) {
+        let (mode, intensity) = match level {
+            PrivacyLevel::None => (MODE_NONE, 0.0),
+            PrivacyLevel::Light => (MODE_NOISE, 0.1),
+            PrivacyLevel::Medium => (MODE_NOISE, 0.3),
+            PrivacyLevel::Heavy => (MODE_NOISE, 0.7),
+            PrivacyLevel::Complete => (MODE_REPLACE, 0.0),
+        };
+        
+        self.modes = (self.modes & !(0b11 << (2 * stream))) | (mode << (2 * stream));
+        // Joints get half the noise, like gestures masked on the CPU
+        self.intensity[stream] = if stream == STREAM_JOINTS { intensity * 0.5 } else { intensity };
+    }
+    
+    /// Get a stream's mode
+    pub fn mode(&self, stream: usize) -> u32 {
+        (self.modes >> (2 * stream)) & 0b11
+    }
+    
+    /// Get the epoch's noise seed
+    pub fn noise_seed(&self) -> u64 {
+        self.seed[0] as u64 | (self.seed[1] as u64) << 32
+    }
+    
+    /// Get a stream's noise seed, the epoch seed with the stream index in the low word
+    fn stream_seed(&self, stream: usize) -> u64 {
+        self.noise_seed() ^ stream as u64
+    }
+    
+    /// Pack the parameters as little-endian bytes for upload
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let words = [
+            self.seed[0],
+            self.seed[1],
+            self.seed_epoch,
+            self.modes,
+            self.intensity[0].to_bits(),
+            self.intensity[1].to_bits(),
+            self.intensity[2].to_bits(),
+            self.intensity[3].to_bits(),
+        ];
+        
+        let mut bytes = [0u8; Self::SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+    
+    /// Unpack parameters from `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != Self::SIZE {
+            return Err(format!("Mask parameters must be {} bytes, got {}", Self::SIZE, bytes.len()));
+        }
+        
+        let word = |i: usize| u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap());
+        Ok(Self {
+            seed: [word(0), word(1)],
+            seed_epoch: word(2),
+            modes: word(3),
+            intensity: [4, 5, 6, 7].map(|i| f32::from_bits(word(i))),
+        })
+    }
+    
+    /// Mask a frame as a shader given these parameters must
+    ///
+    /// Joints past the end of `clamps` are unconstrained.
+    pub fn apply(&self, frame: &mut PackedFrame, clamps: &[JointClamp]) {
+        let key = |stream: usize| simd::key_of(self.stream_seed(stream));
+        
+        match self.mode(STREAM_POSITION) {
+            MODE_NOISE => simd::position_noise_batch(&mut frame.position, self.intensity[STREAM_POSITION], self.stream_seed(STREAM_POSITION)),
+            MODE_REPLACE => {
+                let key = key(STREAM_POSITION);
+                frame.position = [0, 1, 2].map(|axis| (simd::unit_noise(key, axis) - 0.5) * 200.0);
+            },
+            _ => {},
+        }
+        
+        match self.mode(STREAM_ROTATION) {
+            MODE_NOISE => simd::rotation_noise_batch(
+                std::slice::from_mut(&mut frame.rotation),
+                self.intensity[STREAM_ROTATION],
+                self.stream_seed(STREAM_ROTATION),
+            ),
+            MODE_REPLACE => frame.rotation = random_rotation(key(STREAM_ROTATION), 0),
+            _ => {},
+        }
+        
+        match self.mode(STREAM_SCALE) {
+            MODE_NOISE => {
+                let noise = simd::unit_noise(key(STREAM_SCALE), 0);
+                let factor = 1.0 + (noise - 0.5) * 2.0 * self.intensity[STREAM_SCALE] * 0.5;
+                frame.scale = frame.scale.map(|axis| (axis * factor).max(0.01));
+            },
+            MODE_REPLACE => frame.scale = [1.0; 3],
+            _ => {},
+        }
+        
+        match self.mode(STREAM_JOINTS) {
+            MODE_NOISE => simd::rotation_noise_batch(&mut frame.joints, self.intensity[STREAM_JOINTS], self.stream_seed(STREAM_JOINTS)),
+            MODE_REPLACE => {
+                let key = key(STREAM_JOINTS);
+                for (index, joint) in frame.joints.iter_mut().enumerate() {
+                    *joint = random_rotation(key, index as u32);
+                }
+            },
+            _ => return,
+        }
+        
+        // Keep noisy joints within the body's range of motion
+        for (joint, clamp) in frame.joints.iter_mut().zip(clamps) {
+            let [x, y, z, w] = *joint;
+            let angles = to_euler_degrees(&RotationData { x, y, z, w }).map(f32::to_radians);
+            let clamped = [0, 1, 2].map(|i| angles[i].clamp(clamp.min[i], clamp.max[i]));
+            
+            if clamped != angles {
+                let rotation = from_euler_degrees(clamped.map(f32::to_degrees));
+                *joint = [rotation.x, rotation.y, rotation.z, rotation.w];
+            }
+        }
+    }
+}
+
+/// Draw a seeded random unit quaternion from counters `4 * index` onwards
+fn random_rotation(key: u32, index: u32) -> [f32; 4] {
+    let quaternion = [0, 1, 2, 3].map(|component| simd::unit_noise(key, index * 4 + component) * 2.0 - 1.0);
+    let mag = quaternion.iter().map(|c| c * c).sum::<f32>().sqrt().max(f32::EPSILON);
+    quaternion.map(|c| c / mag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use crate::synchronicity_mask::{add_rotation_noise_batch, BoneProfile, SynchronicityMask};
+    
+    #[test]
+    fn test_exported_params_match_reference_masking() {
+        let mut mask = SynchronicityMask::offline().with_bone_profile(BoneProfile::humanoid());
+        let (nft_mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        mask.create_config(&nft_mint, &owner, PrivacyLevel::Medium);
+        
+        let params = mask.export_mask_params(&nft_mint.to_string(), None, None).unwrap();
+        assert_eq!(MaskParams::from_bytes(&params.to_bytes()).unwrap(), params);
+        assert!((0..4).all(|stream| params.mode(stream) == MODE_NOISE));
+        assert_eq!(params.intensity, [0.3, 0.3, 0.3, 0.15]);
+        
+        // Joint noise matches the CPU batch path under the stream's seed, before clamping
+        let joints = vec![[0.0, 0.0, 0.0, 1.0]; 5];
+        let mut frame = PackedFrame { rotation: [0.0, 0.0, 0.0, 1.0], scale: [1.0; 3], joints: joints.clone(), ..Default::default() };
+        params.apply(&mut frame, &[]);
+        let mut expected: Vec<RotationData> = joints.iter().map(|_| RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }).collect();
+        add_rotation_noise_batch(&mut expected, 0.15, params.noise_seed() ^ STREAM_JOINTS as u64);
+        assert!(frame.joints.iter().zip(&expected).all(|(joint, rotation)| *joint == [rotation.x, rotation.y, rotation.z, rotation.w]));
+        assert!(frame.position.iter().all(|axis| axis.abs() <= 3.0));
+        
+        // Clamps keep joints within the bone profile
+        let clamps = mask.export_joint_clamps(&["leftLowerLeg"]);
+        assert_eq!(clamps[0].min[0], 0.0);
+        let mut frame = PackedFrame { joints: vec![[-0.5, 0.0, 0.0, 0.866]], ..Default::default() };
+        params.apply(&mut frame, &clamps);
+        let [x, y, z, w] = frame.joints[0];
+        assert!(to_euler_degrees(&RotationData { x, y, z, w })[0] >= -0.5);
+        
+        // The owner sees everything, and future epochs can't be exported
+        let owner_params = mask.export_mask_params(&nft_mint.to_string(), Some(&owner.to_string()), None).unwrap();
+        assert_eq!(owner_params.modes, 0);
+        assert!(mask.export_mask_params(&nft_mint.to_string(), None, Some(1)).is_err());
+    }
+}
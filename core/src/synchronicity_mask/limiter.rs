@@ -0,0 +1,293 @@
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::session::{MemorySessionStore, SessionStore};
+use super::session::ViewerSession;
+use super::vrm_data::VrmData;
+
+/// Grid step used to tell near-identical query inputs apart
+const PROBE_QUANTUM: f32 = 0.1;
+
+/// Per-viewer limits on mask queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryBudget {
+    /// Queries a viewer may make in a burst
+    pub burst: u32,
+    /// Queries refilled per second
+    pub refill_per_sec: f32,
+    /// Sliding window for sustained-rate and probe detection
+    pub window: Duration,
+    /// Queries allowed per window before the viewer is flagged and refused
+    pub window_limit: u32,
+    /// Near-identical inputs per window that look like a denoising attack
+    pub probe_threshold: u32,
+}
+
+impl Default for QueryBudget {
+    /// Enough for a 90 fps renderer, with headroom
+    fn default() -> Self {
+        Self {
+            burst: 240,
+            refill_per_sec: 120.0,
+            window: Duration::from_secs(60),
+            window_limit: 120 * 60,
+            probe_threshold: 600,
+        }
+    }
+}
+
+/// Query state of one viewer on one NFT, with times in milliseconds since the Unix epoch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct QueryState {
+    /// Remaining token bucket balance
+    tokens: f32,
+    /// When the bucket was last refilled
+    last_refill: u64,
+    /// Recent queries with a fingerprint of their input
+    recent: VecDeque<(u64, u64)>,
+    /// Anomalies already reported in the current window
+    flagged: BTreeSet<String>,
+    /// Queries allowed so far
+    allowed: u64,
+    /// Queries refused so far
+    refused: u64,
+}
+
+/// Query totals of one viewer on one NFT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryStats {
+    /// Viewer that made the queries, or `anonymous`
+    pub viewer: String,
+    /// Queries allowed
+    pub allowed: u64,
+    /// Queries refused for exceeding the budget
+    pub refused: u64,
+    /// Anomalies currently flagged for the viewer
+    pub flagged: Vec<String>,
+}
+
+/// Outcome of checking one query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCheck {
+    /// Why the query was refused, if it was
+    pub refusal: Option<String>,
+    /// Newly detected anomaly to report, e.g. `rate_exceeded` or `repeated_probe`
+    pub anomaly: Option<&'static str>,
+}
+
+/// Token bucket and sliding window limiter for mask queries
+///
+/// The bucket caps bursts; the window catches sustained querying and
+/// viewers repeatedly probing the same input to average out noise. State
+/// is kept in viewer sessions, so a shared session store shares budgets
+/// between processes.
+pub struct QueryLimiter {
+    /// Limits applied to every viewer
+    budget: QueryBudget,
+    /// Sessions holding each viewer's query state
+    sessions: Arc<dyn SessionStore<ViewerSession>>,
+    /// Wall-clock time matching an instant, for converting `now` to session time
+    clock: (Instant, SystemTime),
+}
+
+impl QueryLimiter {
+    /// Create a limiter with the given budget, keeping state in memory
+    pub fn new(budget: QueryBudget) -> Self {
+        Self {
+            budget,
+            sessions: Arc::new(MemorySessionStore::default()),
+            clock: (Instant::now(), SystemTime::now()),
+        }
+    }
+    
+    /// Keep query state in a session store
+    pub fn with_session_store(mut self, sessions: Arc<dyn SessionStore<ViewerSession>>) -> Self {
+        self.sessions = sessions;
+        self
+    }
+    
+    /// Get the budget applied to every viewer
+    pub fn budget(&self) -> &QueryBudget {
+        &self.budget
+    }
+    
+    /// Convert an instant to milliseconds since the Unix epoch
+    fn millis(&self, now: Instant) -> u64 {
+        let (instant, wall) = self.clock;
+        let wall = match now.checked_duration_since(instant) {
+            Some(elapsed) => wall + elapsed,
+            None => wall - instant.duration_since(now),
+        };
+        wall.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+    }
+    
+    /// Check a query at `now` against the viewer's budget
+    ///
+    /// Each anomaly is reported once per window, whether or not the query is refused.
+    pub fn check(&self, nft_mint: &str, viewer_id: &str, vrm_data: &VrmData, now: Instant) -> Result<QueryCheck, String> {
+        let mut session = self.sessions.get(nft_mint, viewer_id)?.unwrap_or_default();
+        let check = self.check_state(&mut session, viewer_id, vrm_data, self.millis(now));
+        self.sessions.put(nft_mint, viewer_id, &session)?;
+        
+        Ok(check)
+    }
+    
+    /// Check a query at `now`, in session time, against a viewer's query state
+    fn check_state(&self, session: &mut ViewerSession, viewer_id: &str, vrm_data: &VrmData, now: u64) -> QueryCheck {
+        let budget = self.budget;
+        let window = budget.window.as_millis() as u64;
+        let state = session.query.get_or_insert_with(|| QueryState {
+            tokens: budget.burst as f32,
+            last_refill: now,
+            recent: VecDeque::new(),
+            flagged: BTreeSet::new(),
+            allowed: 0,
+            refused: 0,
+        });
+        
+        // Step 1: Slide the window, forgetting old flags once it is clear
+        while state.recent.front().map_or(false, |(at, _)| now.saturating_sub(*at) > window) {
+            state.recent.pop_front();
+        }
+        if state.recent.is_empty() {
+            state.flagged.clear();
+        }
+        
+        // Step 2: Refill and spend from the bucket
+        let elapsed = now.saturating_sub(state.last_refill) as f32 / 1000.0;
+        state.tokens = (state.tokens + elapsed * budget.refill_per_sec).min(budget.burst as f32);
+        state.last_refill = state.last_refill.max(now);
+        
+        if state.recent.len() as u32 >= budget.window_limit {
+            state.refused += 1;
+            return QueryCheck {
+                refusal: Some(format!("Query limit exceeded for viewer {}", viewer_id)),
+                anomaly: state.flagged.insert("rate_exceeded".to_string()).then_some("rate_exceeded"),
+            };
+        }
+        if state.tokens < 1.0 {
+            state.refused += 1;
+            return QueryCheck {
+                refusal: Some(format!("Query rate exceeded for viewer {}", viewer_id)),
+                anomaly: None,
+            };
+        }
+        state.tokens -= 1.0;
+        state.allowed += 1;
+        
+        // Step 3: Look for repeated probing of the same input
+        let fingerprint = fingerprint(vrm_data);
+        let repeats = state.recent.iter().filter(|(_, seen)| *seen == fingerprint).count() as u32;
+        state.recent.push_back((now, fingerprint));
+        
+        let anomaly = if repeats + 1 >= budget.probe_threshold && state.flagged.insert("repeated_probe".to_string()) {
+            Some("repeated_probe")
+        } else {
+            None
+        };
+        
+        QueryCheck { refusal: None, anomaly }
+    }
+    
+    /// Get the query totals of every viewer of an NFT, sorted by viewer
+    pub fn stats(&self, nft_mint: &str) -> Result<Vec<QueryStats>, String> {
+        let mut stats: Vec<QueryStats> = self.sessions.sessions(nft_mint)?.into_iter()
+            .filter_map(|(viewer, session)| {
+                let state = session.query?;
+                Some(QueryStats {
+                    viewer,
+                    allowed: state.allowed,
+                    refused: state.refused,
+                    flagged: state.flagged.into_iter().collect(),
+                })
+            })
+            .collect();
+        
+        stats.sort_by(|a, b| a.viewer.cmp(&b.viewer));
+        Ok(stats)
+    }
+}
+
+/// Fingerprint the masked inputs of a query, quantized so jittered probes collide
+fn fingerprint(vrm_data: &VrmData) -> u64 {
+    let quantize = |value: f32| (value / PROBE_QUANTUM).round() as i64;
+    let position = &vrm_data.position;
+    let rotation = &vrm_data.rotation;
+    
+    let mut bytes = Vec::with_capacity(56);
+    for value in [position.x, position.y, position.z, rotation.x, rotation.y, rotation.z, rotation.w] {
+        bytes.extend_from_slice(&quantize(value).to_le_bytes());
+    }
+    
+    let digest = solana_sdk::hash::hash(&bytes);
+    u64::from_le_bytes(digest.as_ref()[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synchronicity_mask::PositionData;
+    
+    fn budget() -> QueryBudget {
+        QueryBudget {
+            burst: 5,
+            refill_per_sec: 1.0,
+            window: Duration::from_secs(10),
+            window_limit: 8,
+            probe_threshold: 4,
+        }
+    }
+    
+    fn at(x: f32) -> VrmData {
+        let mut data = VrmData::new();
+        data.position = PositionData { x, y: 0.0, z: 0.0 };
+        data
+    }
+    
+    #[test]
+    fn test_token_bucket_refills() {
+        let limiter = QueryLimiter::new(budget());
+        let start = Instant::now();
+        
+        for i in 0..5 {
+            assert!(limiter.check("mint", "viewer", &at(i as f32), start).unwrap().refusal.is_none());
+        }
+        assert!(limiter.check("mint", "viewer", &at(9.0), start).unwrap().refusal.is_some());
+        
+        // Other viewers have their own budget
+        assert!(limiter.check("mint", "other", &at(0.0), start).unwrap().refusal.is_none());
+        
+        assert!(limiter.check("mint", "viewer", &at(9.0), start + Duration::from_secs(2)).unwrap().refusal.is_none());
+        
+        let stats = limiter.stats("mint").unwrap();
+        assert_eq!(stats.iter().map(|s| (s.viewer.as_str(), s.allowed, s.refused)).collect::<Vec<_>>(),
+            vec![("other", 1, 0), ("viewer", 6, 1)]);
+        assert!(limiter.stats("other-mint").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_denoising_patterns_are_flagged() {
+        let limiter = QueryLimiter::new(budget());
+        let start = Instant::now();
+        let mut anomalies = Vec::new();
+        
+        // Jittered copies of the same position, spaced to stay within the bucket
+        for i in 0..8 {
+            let now = start + Duration::from_millis(1100 * i);
+            let check = limiter.check("mint", "viewer", &at(5.0 + i as f32 * 0.001), now).unwrap();
+            assert!(check.refusal.is_none());
+            anomalies.extend(check.anomaly);
+        }
+        assert_eq!(anomalies, vec!["repeated_probe"]);
+        
+        // The window is full, so the next query is refused and flagged once
+        let refused = limiter.check("mint", "viewer", &at(1.0), start + Duration::from_millis(8800)).unwrap();
+        assert!(refused.refusal.is_some());
+        assert_eq!(refused.anomaly, Some("rate_exceeded"));
+        let refused = limiter.check("mint", "viewer", &at(1.0), start + Duration::from_millis(9900)).unwrap();
+        assert!(refused.refusal.is_some());
+        assert_eq!(refused.anomaly, None);
+    }
+}
@@ -1,96 +1,53 @@
-use rand::{Rng, SeedableRng};
-use rand::rngs::StdRng;
+//! Masking of VRM data types
+//!
+//! The noise math lives in the no_std `quantum_veil_primitives::masking`;
+//! these wrappers apply it to the std VRM data types. The `_batch` variants
+//! use the vectorized `quantum_veil_primitives::simd` path for many values
+//! at once.
 
-use super::vrm_data::{PositionData, RotationData, VoiceData, GestureData};
+use quantum_veil_primitives::{masking, simd};
+use solana_sdk::hash::hashv;
+use std::collections::HashMap;
+
+use super::vrm_data::{PositionData, RotationData, ScaleData, BoundingBox, VoiceData, GestureData, InteractionEvent};
 
 /// Add noise to position data
 pub fn add_position_noise(position: &mut PositionData, intensity: f32, seed: u64) {
-    let mut rng = StdRng::seed_from_u64(seed);
-    
-    position.x += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 10.0; // Scale for position
-    position.y += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 10.0;
-    position.z += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 10.0;
+    *position = position_from(masking::position_noise(position_array(position), intensity, seed));
 }
 
 /// Add noise to quaternion rotation data
 pub fn add_rotation_noise(rotation: &mut RotationData, intensity: f32, seed: u64) {
-    let mut rng = StdRng::seed_from_u64(seed);
-    
-    // Add small random rotation
-    let noise_angle = intensity * std::f32::consts::PI * rng.gen::<f32>();
-    let noise_axis_x = rng.gen::<f32>() * 2.0 - 1.0;
-    let noise_axis_y = rng.gen::<f32>() * 2.0 - 1.0;
-    let noise_axis_z = rng.gen::<f32>() * 2.0 - 1.0;
-    
-    // Normalize axis
-    let mag = (noise_axis_x.powi(2) + noise_axis_y.powi(2) + noise_axis_z.powi(2)).sqrt();
-    let noise_axis_x = noise_axis_x / mag;
-    let noise_axis_y = noise_axis_y / mag;
-    let noise_axis_z = noise_axis_z / mag;
-    
-    // Create noise quaternion
-    let sin_half_angle = (noise_angle / 2.0).sin();
-    let cos_half_angle = (noise_angle / 2.0).cos();
-    
-    let noise_quat_x = noise_axis_x * sin_half_angle;
-    let noise_quat_y = noise_axis_y * sin_half_angle;
-    let noise_quat_z = noise_axis_z * sin_half_angle;
-    let noise_quat_w = cos_half_angle;
-    
-    // Apply noise quaternion (quaternion multiplication)
-    let original_x = rotation.x;
-    let original_y = rotation.y;
-    let original_z = rotation.z;
-    let original_w = rotation.w;
-    
-    rotation.x = original_w * noise_quat_x + original_x * noise_quat_w + original_y * noise_quat_z - original_z * noise_quat_y;
-    rotation.y = original_w * noise_quat_y - original_x * noise_quat_z + original_y * noise_quat_w + original_z * noise_quat_x;
-    rotation.z = original_w * noise_quat_z + original_x * noise_quat_y - original_y * noise_quat_x + original_z * noise_quat_w;
-    rotation.w = original_w * noise_quat_w - original_x * noise_quat_x - original_y * noise_quat_y - original_z * noise_quat_z;
-    
-    // Normalize quaternion
-    let mag = (rotation.x.powi(2) + rotation.y.powi(2) + rotation.z.powi(2) + rotation.w.powi(2)).sqrt();
-    rotation.x /= mag;
-    rotation.y /= mag;
-    rotation.z /= mag;
-    rotation.w /= mag;
+    let [x, y, z, w] = masking::rotation_noise([rotation.x, rotation.y, rotation.z, rotation.w], intensity, seed);
+    *rotation = RotationData { x, y, z, w };
+}
+
+/// Add noise to scale data
+pub fn add_scale_noise(scale: &mut ScaleData, intensity: f32, seed: u64) {
+    let [x, y, z] = masking::scale_noise([scale.x, scale.y, scale.z], intensity, seed);
+    *scale = ScaleData { x, y, z };
+}
+
+/// Add noise to a bounding box, only ever growing it so it still contains the avatar
+pub fn add_bounding_box_noise(bounding_box: &mut BoundingBox, intensity: f32, seed: u64) {
+    let (min, max) = masking::bounding_box_noise(
+        position_array(&bounding_box.min),
+        position_array(&bounding_box.max),
+        intensity,
+        seed,
+    );
+    bounding_box.min = position_from(min);
+    bounding_box.max = position_from(max);
 }
 
 /// Add noise to voice data
 pub fn add_voice_noise(voice: &mut VoiceData, intensity: f32, seed: u64) {
-    let mut rng = StdRng::seed_from_u64(seed);
-    
-    // Add noise to frequency components
-    for freq in &mut voice.frequency {
-        *freq += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 100.0;
-        *freq = freq.max(0.0); // Frequencies must be positive
-    }
-    
-    // Add noise to amplitude components
-    for amp in &mut voice.amplitude {
-        *amp += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
-        *amp = amp.max(0.0); // Amplitudes must be positive
-    }
-    
-    // Add noise to pitch and timbre
-    voice.pitch += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 50.0;
-    voice.pitch = voice.pitch.max(0.0); // Pitch must be positive
-    
-    voice.timbre += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
-    voice.timbre = voice.timbre.max(0.0).min(1.0); // Timbre is normalized 0.0-1.0
+    masking::voice_noise(&mut voice.frequency, &mut voice.amplitude, &mut voice.pitch, &mut voice.timbre, intensity, seed);
 }
 
 /// Add noise to gesture data
 pub fn add_gesture_noise(gesture: &mut GestureData, intensity: f32, seed: u64) {
-    let mut rng = StdRng::seed_from_u64(seed);
-    
-    // Add noise to gesture intensity
-    gesture.intensity += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
-    gesture.intensity = gesture.intensity.max(0.0).min(1.0); // Intensity normalized 0.0-1.0
-    
-    // Add noise to gesture speed
-    gesture.speed += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * gesture.speed;
-    gesture.speed = gesture.speed.max(0.1); // Speed must be positive
+    masking::gesture_noise(&mut gesture.intensity, &mut gesture.speed, intensity, seed);
     
     // Add noise to joint rotations
     for (_, rotation) in gesture.joint_rotations.iter_mut() {
@@ -100,34 +57,65 @@ pub fn add_gesture_noise(gesture: &mut GestureData, intensity: f32, seed: u64) {
     }
 }
 
-/// Create privacy-preserving randomized data
-pub fn create_random_position(seed: u64) -> PositionData {
-    let mut rng = StdRng::seed_from_u64(seed);
+/// Add noise to many positions at once
+pub fn add_position_noise_batch(positions: &mut [PositionData], intensity: f32, seed: u64) {
+    let mut components: Vec<f32> = positions.iter().flat_map(position_array).collect();
+    simd::position_noise_batch(&mut components, intensity, seed);
     
-    PositionData {
-        x: rng.gen_range(-10.0..10.0),
-        y: rng.gen_range(-2.0..5.0), // Typical avatar height range
-        z: rng.gen_range(-10.0..10.0),
+    for (position, xyz) in positions.iter_mut().zip(components.chunks_exact(3)) {
+        *position = position_from([xyz[0], xyz[1], xyz[2]]);
     }
 }
 
-/// Create privacy-preserving randomized rotation
-pub fn create_random_rotation(seed: u64) -> RotationData {
-    let mut rng = StdRng::seed_from_u64(seed);
-    
-    // Generate random quaternion components
-    let x = rng.gen_range(-1.0..1.0);
-    let y = rng.gen_range(-1.0..1.0);
-    let z = rng.gen_range(-1.0..1.0);
-    let w = rng.gen_range(-1.0..1.0);
+/// Add noise to many quaternion rotations at once
+pub fn add_rotation_noise_batch(rotations: &mut [RotationData], intensity: f32, seed: u64) {
+    let mut quaternions: Vec<[f32; 4]> = rotations.iter().map(|r| [r.x, r.y, r.z, r.w]).collect();
+    simd::rotation_noise_batch(&mut quaternions, intensity, seed);
     
-    // Normalize quaternion
-    let mag = (x.powi(2) + y.powi(2) + z.powi(2) + w.powi(2)).sqrt();
-    
-    RotationData {
-        x: x / mag,
-        y: y / mag,
-        z: z / mag,
-        w: w / mag,
+    for (rotation, [x, y, z, w]) in rotations.iter_mut().zip(quaternions) {
+        *rotation = RotationData { x, y, z, w };
+    }
+}
+
+/// Add noise to a skeleton's joint rotations at once, in joint name order so the noise is stable
+pub fn add_joint_noise_batch(joint_rotations: &mut HashMap<String, RotationData>, intensity: f32, seed: u64) {
+    let mut joints: Vec<_> = joint_rotations.iter_mut().collect();
+    joints.sort_by(|(a, _), (b, _)| a.cmp(b));
+    
+    let mut rotations: Vec<RotationData> = joints.iter().map(|(_, rotation)| rotation.clone()).collect();
+    add_rotation_noise_batch(&mut rotations, intensity, seed);
+    for ((_, joint), rotation) in joints.into_iter().zip(rotations) {
+        *joint = rotation;
     }
 }
+
+/// Add timing jitter and intensity noise to an interaction event
+pub fn add_interaction_noise(interaction: &mut InteractionEvent, intensity: f32, seed: u64) {
+    masking::interaction_noise(&mut interaction.timestamp, &mut interaction.intensity, intensity, seed);
+}
+
+/// Replace an interaction target with a pseudonym, stable for a given seed
+pub fn pseudonymize_target(target_id: &str, seed: u64) -> String {
+    let digest = hashv(&[&seed.to_le_bytes(), target_id.as_bytes()]);
+    let suffix: String = digest.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("anon-{}", suffix)
+}
+
+/// Create privacy-preserving randomized data
+pub fn create_random_position(seed: u64) -> PositionData {
+    position_from(masking::random_position(seed))
+}
+
+/// Create privacy-preserving randomized rotation
+pub fn create_random_rotation(seed: u64) -> RotationData {
+    let [x, y, z, w] = masking::random_rotation(seed);
+    RotationData { x, y, z, w }
+}
+
+fn position_array(position: &PositionData) -> [f32; 3] {
+    [position.x, position.y, position.z]
+}
+
+fn position_from([x, y, z]: [f32; 3]) -> PositionData {
+    PositionData { x, y, z }
+}
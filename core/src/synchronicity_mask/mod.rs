@@ -1,20 +1,91 @@
+#![cfg(feature = "masking")]
+
 mod privacy_levels;
+mod access_policy;
 mod vrm_data;
 mod masking;
+mod resolver;
+mod token;
+mod access_tokens;
+mod ring;
+mod blacklist;
+mod revocation;
+mod frame;
+mod recorder;
+mod crowd;
+mod skeleton;
+mod pseudonym;
+mod limiter;
+mod proof;
+mod gpu;
+mod scheduler;
+mod session;
 
 pub use privacy_levels::{PrivacyLevel, AccessPermission};
+pub use access_policy::{AccessPolicy, WrapperAccess};
 pub use vrm_data::{
-    VrmDataType, PositionData, RotationData, VoiceData, GestureData, VrmData
+    VrmDataType, PositionData, RotationData, ScaleData, BoundingBox, VoiceData, GestureData,
+    InteractionKind, InteractionEvent, VrmData
 };
 pub use masking::{
-    add_position_noise, add_rotation_noise, add_voice_noise, add_gesture_noise
+    add_position_noise, add_rotation_noise, add_scale_noise, add_bounding_box_noise, add_voice_noise,
+    add_gesture_noise, add_interaction_noise, pseudonymize_target,
+    add_position_noise_batch, add_rotation_noise_batch, add_joint_noise_batch
+};
+pub use resolver::AgentResolver;
+pub use token::{AccessToken, TOKEN_LEN};
+pub use access_tokens::{
+    member_context, BlindToken, PendingToken, TokenIssuer, TokenRedemption, TokenRequest, TokenResponse
+};
+pub use ring::{RingKey, RingSignature};
+pub use blacklist::TokenBlacklist;
+pub use revocation::{RevocationList, RevocationListBuilder, DEFAULT_LIST_VALIDITY_SECS};
+pub use frame::{VrmFrame, ReplayGuard};
+pub use recorder::{SessionRecorder, SessionRecording, SessionHeader};
+pub use crowd::{CrowdBlend, CrowdGrid};
+pub use skeleton::{BoneProfile, JointLimits};
+pub use pseudonym::{PSEUDONYM_KEY, pseudonym, sign_resolve_request};
+pub use limiter::{QueryBudget, QueryLimiter, QueryCheck, QueryStats};
+pub use proof::MaskProof;
+pub use gpu::{
+    MaskParams, JointClamp, PackedFrame, STREAM_POSITION, STREAM_ROTATION, STREAM_SCALE, STREAM_JOINTS,
+    MODE_NONE, MODE_NOISE, MODE_REPLACE
 };
+pub use scheduler::{MaskingScheduler, Interpolation, InterpolationState};
+pub use session::ViewerSession;
 
+#[cfg(feature = "onchain")]
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
+use sha3::{Digest, Sha3_512};
+use ring::hkdf;
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::session::{MemorySessionStore, SessionStore};
+use crate::quantum_veil::{constant_time_eq, constant_time_contains};
+use crate::telemetry::timed;
+
+/// Limiter key for viewers without an ID
+const ANONYMOUS_VIEWER: &str = "anonymous";
+
+/// HKDF salt for per-epoch noise seeds
+const NOISE_SEED_SALT: &[u8] = b"quantum-veil/noise-seed";
+
+/// HKDF output length for a 64-bit noise seed
+struct SeedLen;
+
+impl hkdf::KeyType for SeedLen {
+    fn len(&self) -> usize {
+        8
+    }
+}
 
 /// Synchronicity mask configuration
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -29,26 +100,294 @@ pub struct SyncMaskConfig {
     pub access_permissions: HashMap<VrmDataType, AccessPermission>,
     /// Global trusted agents that can see through all masks
     pub global_trusted_agents: Vec<String>,
-    /// Seed for deterministic noise generation
+    /// Root seed for deterministic noise generation
     pub noise_seed: u64,
+    /// Noise seed epoch, advanced on every seed rotation
+    #[serde(default)]
+    pub seed_epoch: u32,
     /// Synchronization factor for aligned agents (0.0 - 1.0)
     pub sync_factor: f32,
+    /// Services allowed to issue access tokens on the owner's behalf
+    #[serde(default)]
+    pub token_delegates: Vec<String>,
+    /// Publishers, besides the owner, whose signed VRM frames are accepted
+    #[serde(default)]
+    pub frame_publishers: Vec<String>,
+}
+
+impl SyncMaskConfig {
+    /// Get the noise seed for an epoch
+    ///
+    /// Epoch 0 uses the root seed as-is; later epochs are derived from it with
+    /// HKDF, so masked data from any epoch can be reproduced from the config.
+    pub fn epoch_seed(&self, epoch: u32) -> u64 {
+        if epoch == 0 {
+            return self.noise_seed;
+        }
+        
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, NOISE_SEED_SALT).extract(&self.noise_seed.to_le_bytes());
+        let info = epoch.to_le_bytes();
+        let okm = prk.expand(&[&info], SeedLen).expect("8 bytes is a valid HKDF output length");
+        
+        let mut seed = [0u8; 8];
+        okm.fill(&mut seed).expect("8 bytes is a valid HKDF output length");
+        u64::from_le_bytes(seed)
+    }
+    
+    /// Get the noise seed for the current epoch
+    pub fn current_seed(&self) -> u64 {
+        self.epoch_seed(self.seed_epoch)
+    }
+    
+    /// Check if a viewer is a trusted agent or the owner, in constant time
+    pub fn is_trusted_or_owner(&self, viewer: &str) -> bool {
+        constant_time_contains(&self.global_trusted_agents, viewer.as_bytes())
+            | constant_time_eq(viewer.as_bytes(), self.owner.as_bytes())
+    }
+    
+    /// Get mask configuration hash for Solana storage
+    ///
+    /// Map entries are sorted first, so equal configs hash the same regardless
+    /// of insertion order.
+    pub fn hash(&self) -> String {
+        let mut privacy_settings: Vec<String> = self.privacy_settings.iter()
+            .map(|(data_type, level)| format!("{:?}={:?}", data_type, level))
+            .collect();
+        privacy_settings.sort();
+        
+        let mut access_permissions: Vec<String> = self.access_permissions.iter()
+            .map(|(data_type, permission)| format!("{:?}={:?}", data_type, permission))
+            .collect();
+        access_permissions.sort();
+        
+        let canonical = (
+            &self.nft_mint,
+            &self.owner,
+            privacy_settings,
+            access_permissions,
+            &self.global_trusted_agents,
+            self.noise_seed,
+            self.seed_epoch,
+            self.sync_factor,
+            &self.token_delegates,
+            &self.frame_publishers,
+        );
+        
+        let mut hasher = Sha3_512::new();
+        hasher.update(serde_json::to_string(&canonical).unwrap_or_default().as_bytes());
+        let result = hasher.finalize();
+        
+        base64::encode(&result)
+    }
 }
 
 /// Synchronicity Mask manager
 pub struct SynchronicityMask {
-    /// RPC client for Solana blockchain interaction
-    rpc_client: RpcClient,
+    /// RPC client for Solana blockchain interaction, if connected
+    #[cfg(feature = "onchain")]
+    rpc_client: Option<RpcClient>,
     /// Cache of mask configurations by NFT mint
     config_cache: HashMap<String, SyncMaskConfig>,
+    /// Resolver for agent names, if configured
+    agent_resolver: Option<Arc<dyn AgentResolver>>,
+    /// Revoked access tokens
+    revoked_tokens: TokenBlacklist,
+    /// Latest signed revocation list by NFT mint
+    revocation_lists: HashMap<String, RevocationList>,
+    /// Audit log for unmasked disclosures, if configured
+    audit_log: Option<Arc<AuditLog>>,
+    /// Mapping from on-chain access levels to privacy levels
+    access_policy: AccessPolicy,
+    /// Key re-signing masked frames, if configured
+    frame_signer: Option<Arc<Keypair>>,
+    /// Frames already accepted, for replay protection
+    replay_guard: Mutex<ReplayGuard>,
+    /// Joint limits that masked gestures are clamped to, if configured
+    bone_profile: Option<BoneProfile>,
+    /// Per-viewer state: query budgets, issued pseudonyms, and audited disclosures
+    sessions: Arc<dyn SessionStore<ViewerSession>>,
+    /// Held across each read-modify-write of a session
+    session_lock: Mutex<()>,
+    /// Per-viewer budget for masked queries, if configured
+    query_limiter: Option<QueryLimiter>,
 }
 
 impl SynchronicityMask {
     /// Create a new Synchronicity Mask instance
+    #[cfg(feature = "onchain")]
     pub fn new(solana_rpc_url: &str) -> Self {
+        Self::with_rpc_client(RpcClient::new(solana_rpc_url.to_string()))
+    }
+    
+    /// Create a new Synchronicity Mask instance with a preconfigured RPC client
+    #[cfg(feature = "onchain")]
+    pub fn with_rpc_client(rpc_client: RpcClient) -> Self {
         Self {
-            rpc_client: RpcClient::new(solana_rpc_url.to_string()),
+            rpc_client: Some(rpc_client),
+            ..Self::offline()
+        }
+    }
+    
+    /// Create a Synchronicity Mask instance without an RPC connection
+    ///
+    /// Masking works from cached configs and needs no network access.
+    pub fn offline() -> Self {
+        Self {
+            #[cfg(feature = "onchain")]
+            rpc_client: None,
             config_cache: HashMap::new(),
+            agent_resolver: None,
+            revoked_tokens: TokenBlacklist::default(),
+            revocation_lists: HashMap::new(),
+            audit_log: None,
+            access_policy: AccessPolicy::default(),
+            frame_signer: None,
+            replay_guard: Mutex::new(ReplayGuard::default()),
+            bone_profile: None,
+            sessions: Arc::new(MemorySessionStore::default()),
+            session_lock: Mutex::new(()),
+            query_limiter: None,
+        }
+    }
+    
+    /// Set the mapping from access levels, on-chain or in tokens, to privacy levels
+    pub fn with_access_policy(mut self, access_policy: AccessPolicy) -> Self {
+        self.access_policy = access_policy;
+        self
+    }
+    
+    /// Get the mapping from access levels to privacy levels
+    pub fn access_policy(&self) -> &AccessPolicy {
+        &self.access_policy
+    }
+    
+    /// Set the key that re-signs masked frames for downstream consumers
+    pub fn with_frame_signer(mut self, frame_signer: Arc<Keypair>) -> Self {
+        self.frame_signer = Some(frame_signer);
+        self
+    }
+    
+    /// Clamp masked joint rotations to a bone profile so poses stay humanly plausible
+    pub fn with_bone_profile(mut self, bone_profile: BoneProfile) -> Self {
+        self.bone_profile = Some(bone_profile);
+        self
+    }
+    
+    /// Set the replay guard for incoming frames, e.g. to change the accepted frame age
+    pub fn with_replay_guard(mut self, replay_guard: ReplayGuard) -> Self {
+        self.replay_guard = Mutex::new(replay_guard);
+        self
+    }
+    
+    /// Limit how often each viewer may query masked data for an NFT
+    ///
+    /// Viewers who see unmasked data are not limited. Anomalies are recorded
+    /// in the audit log, if configured.
+    pub fn with_query_limiter(mut self, budget: QueryBudget) -> Self {
+        self.query_limiter = Some(QueryLimiter::new(budget).with_session_store(self.sessions.clone()));
+        self
+    }
+    
+    /// Keep per-viewer state in a session store, e.g. one shared by several servers
+    ///
+    /// Viewers idle for longer than the store's time to live start over: their
+    /// query budget is refilled, their disclosures are audited again, and
+    /// their pseudonyms can no longer be resolved.
+    pub fn with_session_store(mut self, sessions: Arc<dyn SessionStore<ViewerSession>>) -> Self {
+        self.query_limiter = self.query_limiter.map(|limiter| limiter.with_session_store(sessions.clone()));
+        self.sessions = sessions;
+        self
+    }
+    
+    /// Drop sessions of viewers idle for longer than the store's time to live
+    pub fn evict_expired_sessions(&self) -> Result<usize, String> {
+        self.sessions.evict_expired()
+    }
+    
+    /// Record which viewers are shown unmasked data in an audit log
+    ///
+    /// Each viewer is recorded once per NFT and reason, not once per frame.
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
+    /// Record that a viewer was shown unmasked data
+    fn audit_disclosure(&self, nft_mint: &str, viewer: &str, reason: &str) -> Result<(), String> {
+        let audit_log = match &self.audit_log {
+            Some(audit_log) => audit_log,
+            None => return Ok(()),
+        };
+        
+        let _guard = self.session_lock.lock().unwrap();
+        let mut session = self.sessions.get(nft_mint, viewer)?.unwrap_or_default();
+        if session.disclosed.contains(reason) {
+            return Ok(());
+        }
+        
+        audit_log.record(AuditEvent::ViewerUnmasked {
+            nft_mint: nft_mint.to_string(),
+            viewer: viewer.to_string(),
+            reason: reason.to_string(),
+        })?;
+        session.disclosed.insert(reason.to_string());
+        
+        self.sessions.put(nft_mint, viewer, &session)
+    }
+    
+    /// Get per-viewer query totals for an NFT, empty if no limiter is configured
+    pub fn query_stats(&self, nft_mint: &str) -> Result<Vec<QueryStats>, String> {
+        match &self.query_limiter {
+            Some(limiter) => limiter.stats(nft_mint),
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    /// Spend a viewer's query budget, refusing queries over it
+    fn check_query_budget(&self, nft_mint: &str, vrm_data: &VrmData, viewer_id: Option<&str>) -> Result<(), String> {
+        let limiter = match &self.query_limiter {
+            Some(limiter) => limiter,
+            None => return Ok(()),
+        };
+        
+        let viewer = viewer_id.unwrap_or(ANONYMOUS_VIEWER);
+        let check = {
+            let _guard = self.session_lock.lock().unwrap();
+            limiter.check(nft_mint, viewer, vrm_data, Instant::now())?
+        };
+        
+        if let (Some(anomaly), Some(audit_log)) = (check.anomaly, &self.audit_log) {
+            audit_log.record(AuditEvent::QueryAnomaly {
+                nft_mint: nft_mint.to_string(),
+                viewer: viewer.to_string(),
+                reason: anomaly.to_string(),
+            })?;
+        }
+        
+        match check.refusal {
+            Some(refusal) => Err(refusal),
+            None => Ok(()),
+        }
+    }
+    
+    /// Set the resolver used to map agent names to public keys
+    pub fn with_agent_resolver(mut self, resolver: Arc<dyn AgentResolver>) -> Self {
+        self.agent_resolver = Some(resolver);
+        self
+    }
+    
+    /// Resolve an agent ID to the canonical form used in mask configs
+    ///
+    /// Public keys are kept as-is. Names are resolved to their registered key
+    /// when a resolver is configured; without one they are used verbatim.
+    pub fn resolve_agent_id(&self, agent_id: &str) -> Result<String, String> {
+        if Pubkey::from_str(agent_id).is_ok() {
+            return Ok(agent_id.to_string());
+        }
+        
+        match &self.agent_resolver {
+            Some(resolver) => resolver.resolve_agent(agent_id).map(|key| key.to_string()),
+            None => Ok(agent_id.to_string()),
         }
     }
     
@@ -59,46 +398,49 @@ impl SynchronicityMask {
         owner: &Pubkey,
         default_privacy_level: PrivacyLevel,
     ) -> SyncMaskConfig {
-        let mut privacy_settings = HashMap::new();
-        privacy_settings.insert(VrmDataType::Position, default_privacy_level);
-        privacy_settings.insert(VrmDataType::Rotation, default_privacy_level);
-        privacy_settings.insert(VrmDataType::Voice, default_privacy_level);
-        privacy_settings.insert(VrmDataType::Gesture, default_privacy_level);
-        privacy_settings.insert(VrmDataType::Animation, default_privacy_level);
-        privacy_settings.insert(VrmDataType::Interaction, default_privacy_level);
+        // Generate a noise seed based on current time
+        let noise_seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        
+        let config = Self::uniform_config(&nft_mint.to_string(), &owner.to_string(), default_privacy_level, noise_seed);
+        
+        // Cache the config
+        self.config_cache.insert(nft_mint.to_string(), config.clone());
         
+        config
+    }
+    
+    /// Build a config applying one privacy level to every data type, visible to all
+    fn uniform_config(nft_mint: &str, owner: &str, level: PrivacyLevel, noise_seed: u64) -> SyncMaskConfig {
+        let mut privacy_settings = HashMap::new();
         let mut access_permissions = HashMap::new();
         for data_type in [
             VrmDataType::Position,
             VrmDataType::Rotation,
+            VrmDataType::Scale,
             VrmDataType::Voice,
             VrmDataType::Gesture,
             VrmDataType::Animation,
             VrmDataType::Interaction,
         ] {
+            privacy_settings.insert(data_type, level);
             access_permissions.insert(data_type, AccessPermission::Public);
         }
         
-        // Generate a noise seed based on current time
-        let noise_seed = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        
-        let config = SyncMaskConfig {
+        SyncMaskConfig {
             nft_mint: nft_mint.to_string(),
             owner: owner.to_string(),
             privacy_settings,
             access_permissions,
             global_trusted_agents: Vec::new(),
             noise_seed,
+            seed_epoch: 0,
             sync_factor: 0.8,
-        };
-        
-        // Cache the config
-        self.config_cache.insert(nft_mint.to_string(), config.clone());
-        
-        config
+            token_delegates: Vec::new(),
+            frame_publishers: Vec::new(),
+        }
     }
     
     /// Get mask configuration by NFT mint
@@ -108,6 +450,16 @@ impl SynchronicityMask {
             .ok_or_else(|| format!("No mask config found for NFT: {}", nft_mint))
     }
     
+    /// Replace the mask configuration of an NFT, e.g. with one restored from a backup
+    pub fn update_config(&mut self, nft_mint: &str, config: SyncMaskConfig) -> Result<(), String> {
+        if config.nft_mint != nft_mint {
+            return Err(format!("Mask config is for NFT {}, not {}", config.nft_mint, nft_mint));
+        }
+        
+        self.config_cache.insert(nft_mint.to_string(), config);
+        Ok(())
+    }
+    
     /// Update privacy settings for a VRM data type
     pub fn update_privacy_setting(
         &mut self,
@@ -137,77 +489,643 @@ impl SynchronicityMask {
         &mut self,
         nft_mint: &str,
         agent_id: &str,
+    ) -> Result<(), String> {
+        let agent_id = self.resolve_agent_id(agent_id)?;
+        let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
+        
+        if !config.global_trusted_agents.contains(&agent_id) {
+            config.global_trusted_agents.push(agent_id);
+        }
+        
+        Ok(())
+    }
+    
+    /// Remove a trusted agent
+    pub fn remove_trusted_agent(
+        &mut self,
+        nft_mint: &str,
+        agent_id: &str,
+    ) -> Result<(), String> {
+        let agent_id = self.resolve_agent_id(agent_id)?;
+        let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
+        
+        config.global_trusted_agents.retain(|id| *id != agent_id);
+        
+        Ok(())
+    }
+    
+    /// Check if an agent is trusted
+    pub fn is_trusted_agent(
+        &self,
+        nft_mint: &str,
+        agent_id: &str,
+    ) -> Result<bool, String> {
+        let agent_id = self.resolve_agent_id(agent_id)?;
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        Ok(constant_time_contains(&config.global_trusted_agents, agent_id.as_bytes()))
+    }
+    
+    /// Allow a service to issue access tokens on the owner's behalf
+    pub fn add_token_delegate(
+        &mut self,
+        nft_mint: &str,
+        delegate: &Pubkey,
+    ) -> Result<(), String> {
+        let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
+        
+        if !config.token_delegates.contains(&delegate.to_string()) {
+            config.token_delegates.push(delegate.to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Accept signed VRM frames from a publisher other than the owner
+    pub fn add_frame_publisher(
+        &mut self,
+        nft_mint: &str,
+        publisher: &Pubkey,
     ) -> Result<(), String> {
         let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
         
-        if !config.global_trusted_agents.contains(&agent_id.to_string()) {
-            config.global_trusted_agents.push(agent_id.to_string());
+        if !config.frame_publishers.contains(&publisher.to_string()) {
+            config.frame_publishers.push(publisher.to_string());
         }
         
-        Ok(())
+        Ok(())
+    }
+    
+    /// Advance to the next noise seed epoch, returning the new epoch
+    ///
+    /// Rotate alongside the NFT's encryption key, or on a separate cadence, so
+    /// noise cannot be averaged away over long observation.
+    pub fn rotate_noise_seed(&mut self, nft_mint: &str) -> Result<u32, String> {
+        let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
+        
+        config.seed_epoch = config.seed_epoch
+            .checked_add(1)
+            .ok_or("Noise seed epochs exhausted")?;
+        
+        Ok(config.seed_epoch)
+    }
+    
+    /// Revoke an access token before it expires
+    pub fn revoke_token(&mut self, token: &AccessToken) {
+        self.revoked_tokens.revoke(token);
+    }
+    
+    /// Replace the revocation blacklist, e.g. with one pushed from the issuing service
+    pub fn set_token_blacklist(&mut self, blacklist: TokenBlacklist) {
+        self.revoked_tokens = blacklist;
+    }
+    
+    /// Install a signed revocation list fetched from the owner's publication point
+    ///
+    /// The list must be signed by the owner or a token delegate and be newer
+    /// than the installed one. From then on, tokens for the NFT are refused
+    /// once the latest list expires.
+    pub fn install_revocation_list(&mut self, list: RevocationList) -> Result<(), String> {
+        let nft_mint = list.nft_mint.to_string();
+        let config = self.config_cache.get(&nft_mint).ok_or("Config not found")?;
+        list.verify(&Self::token_issuers(config))?;
+        
+        if let Some(installed) = self.revocation_lists.get(&nft_mint) {
+            if list.sequence <= installed.sequence {
+                return Err(format!(
+                    "Revocation list {} is not newer than the installed list {}",
+                    list.sequence, installed.sequence
+                ));
+            }
+        }
+        
+        self.revocation_lists.insert(nft_mint, list);
+        Ok(())
+    }
+    
+    /// Get the owner and delegates, the only keys trusted to issue tokens and revocation lists
+    fn token_issuers(config: &SyncMaskConfig) -> Vec<Pubkey> {
+        std::iter::once(&config.owner)
+            .chain(&config.token_delegates)
+            .filter_map(|key| Pubkey::from_str(key).ok())
+            .collect()
+    }
+    
+    /// Apply synchronicity mask to VRM data
+    pub fn apply_mask(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+    ) -> Result<VrmData, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        // Unregistered names fall back to the raw ID, which only matches legacy entries
+        let resolved_viewer = viewer_id.map(|viewer| {
+            self.resolve_agent_id(viewer).unwrap_or_else(|_| viewer.to_string())
+        });
+        
+        timed("mask.apply", || self.mask_with_config(config, vrm_data, resolved_viewer.as_deref()))
+    }
+    
+    /// Apply synchronicity mask to a signed VRM frame
+    ///
+    /// The frame must be signed by the owner or a frame publisher and must not
+    /// be a replay. The masked frame keeps the original sequence and timestamp
+    /// and is re-signed with the frame signer.
+    pub fn apply_mask_to_frame(
+        &self,
+        frame: &VrmFrame,
+        viewer_id: Option<&str>,
+    ) -> Result<VrmFrame, String> {
+        let frame_signer = self.frame_signer.as_ref().ok_or("No frame signer configured")?;
+        let nft_mint = frame.nft_mint.to_string();
+        let config = self.config_cache.get(&nft_mint).ok_or("Config not found")?;
+        
+        // Step 1: Authenticate the publisher
+        let trusted_publishers: Vec<Pubkey> = std::iter::once(&config.owner)
+            .chain(&config.frame_publishers)
+            .filter_map(|publisher| Pubkey::from_str(publisher).ok())
+            .collect();
+        frame.verify(&trusted_publishers)?;
+        
+        // Step 2: Drop replayed and stale frames
+        self.replay_guard.lock().unwrap().check(frame)?;
+        
+        // Step 3: Mask and re-sign
+        let masked_data = self.apply_mask(&nft_mint, &frame.data, viewer_id)?;
+        
+        Ok(VrmFrame::sign_at(frame_signer, &frame.nft_mint, frame.sequence, frame.timestamp, masked_data))
+    }
+    
+    /// Apply synchronicity mask in a shared space, snapping masked positions to a crowd grid
+    ///
+    /// Instead of noise, a masked position becomes the center of its grid cell,
+    /// which it shares with at least `k` avatars. Viewers who see the position
+    /// unmasked still get the exact position.
+    pub fn apply_mask_in_crowd(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+        crowd_grid: &CrowdGrid,
+    ) -> Result<VrmData, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        let resolved_viewer = viewer_id.map(|viewer| {
+            self.resolve_agent_id(viewer).unwrap_or_else(|_| viewer.to_string())
+        });
+        let viewer = resolved_viewer.as_deref();
+        
+        timed("mask.apply_in_crowd", || {
+            let mut masked_data = self.mask_with_config(config, vrm_data, viewer)?;
+            
+            let sees_position = match viewer {
+                Some(viewer) if config.is_trusted_or_owner(viewer) => true,
+                _ => {
+                    self.has_access(config, VrmDataType::Position, viewer)?
+                        && config.privacy_settings.get(&VrmDataType::Position).unwrap_or(&PrivacyLevel::None) == &PrivacyLevel::None
+                },
+            };
+            if !sees_position {
+                masked_data.position = crowd_grid.blend(&vrm_data.position);
+            }
+            
+            Ok(masked_data)
+        })
+    }
+    
+    /// Apply synchronicity mask with the noise seed of a past epoch
+    ///
+    /// Reproduces data masked before later seed rotations, e.g. for playback.
+    pub fn apply_mask_at_epoch(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+        seed_epoch: u32,
+    ) -> Result<VrmData, String> {
+        let mut config = self.config_cache.get(nft_mint).ok_or("Config not found")?.clone();
+        if seed_epoch > config.seed_epoch {
+            return Err(format!("Noise seed epoch {} has not been reached yet", seed_epoch));
+        }
+        config.seed_epoch = seed_epoch;
+        
+        let resolved_viewer = viewer_id.map(|viewer| {
+            self.resolve_agent_id(viewer).unwrap_or_else(|_| viewer.to_string())
+        });
+        
+        timed("mask.apply_at_epoch", || self.mask_with_config(&config, vrm_data, resolved_viewer.as_deref()))
+    }
+    
+    /// Apply one privacy level to all VRM data, ignoring the stored settings
+    ///
+    /// Meant for previews and demos. The stored noise seed is reused when the
+    /// NFT has a config, so the result matches what a viewer at that level
+    /// would see; otherwise the seed is derived from the mint.
+    pub fn apply_mask_with_level(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        level: PrivacyLevel,
+    ) -> Result<VrmData, String> {
+        let config = match self.config_cache.get(nft_mint) {
+            Some(stored) => Self::uniform_config(nft_mint, &stored.owner, level, stored.current_seed()),
+            None => {
+                let digest = solana_sdk::hash::hash(nft_mint.as_bytes());
+                let noise_seed = u64::from_le_bytes(digest.as_ref()[..8].try_into().unwrap());
+                Self::uniform_config(nft_mint, "", level, noise_seed)
+            }
+        };
+        
+        timed("mask.apply_with_level", || self.mask_with_config(&config, vrm_data, None))
+    }
+    
+    /// Apply synchronicity mask for the viewer holding an access token
+    ///
+    /// The token is verified offline against the owner and token delegates.
+    /// Data types the token's level is cleared for under the access policy
+    /// are returned unmasked; the rest are masked as for any other viewer.
+    pub fn apply_mask_with_token(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        token: &AccessToken,
+    ) -> Result<VrmData, String> {
+        timed("mask.apply_with_token", || self.mask_with_token(nft_mint, vrm_data, token))
+    }
+    
+    /// Verify an access token and mask for its viewer
+    fn mask_with_token(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        token: &AccessToken,
+    ) -> Result<VrmData, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        if token.nft_mint.to_string() != config.nft_mint {
+            return Err("Access token was issued for a different NFT".to_string());
+        }
+        
+        token.verify(&Self::token_issuers(config))?;
+        
+        if self.revoked_tokens.is_revoked(token) {
+            return Err("Access token revoked".to_string());
+        }
+        if let Some(list) = self.revocation_lists.get(nft_mint) {
+            if list.is_expired() {
+                return Err("Revocation list expired; install a newer one to accept tokens".to_string());
+            }
+            if list.is_revoked(token) {
+                return Err("Access token revoked".to_string());
+            }
+        }
+        
+        self.audit_disclosure(nft_mint, &token.viewer.to_string(), "access_token")?;
+        
+        let clearance = self.access_policy.clearance(token.level);
+        self.mask_with_clearance(config, vrm_data, &token.viewer.to_string(), clearance)
+    }
+    
+    /// Apply synchronicity mask for an anonymous viewer who redeemed a blind token
+    ///
+    /// The viewer is never identified, so the disclosure is audited and
+    /// budgeted as anonymous.
+    pub fn apply_mask_with_redemption(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        redemption: &TokenRedemption,
+    ) -> Result<VrmData, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        if redemption.nft_mint().to_string() != config.nft_mint {
+            return Err("Blind token was issued for a different NFT".to_string());
+        }
+        
+        let clearance = self.access_policy.clearance(redemption.level());
+        if clearance != PrivacyLevel::None {
+            self.audit_disclosure(nft_mint, ANONYMOUS_VIEWER, "blind_token")?;
+        }
+        
+        timed("mask.apply_with_redemption", || {
+            self.mask_with_clearance(config, vrm_data, ANONYMOUS_VIEWER, clearance)
+        })
+    }
+    
+    /// Apply synchronicity mask for a viewer with an on-chain access level
+    ///
+    /// The caller reads the level from the viewer's wrapper grant; data types
+    /// it is cleared for under the access policy are returned unmasked.
+    pub fn apply_mask_with_access_level(
+        &self,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        viewer_id: &str,
+        access_level: u8,
+    ) -> Result<VrmData, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        
+        let clearance = self.access_policy.clearance(access_level);
+        if clearance != PrivacyLevel::None {
+            self.audit_disclosure(nft_mint, viewer_id, "access_level")?;
+        }
+        
+        timed("mask.apply_with_access_level", || {
+            self.mask_with_clearance(config, vrm_data, viewer_id, clearance)
+        })
+    }
+    
+    /// Mask for a viewer cleared up to a privacy level
+    fn mask_with_clearance(
+        &self,
+        config: &SyncMaskConfig,
+        vrm_data: &VrmData,
+        viewer_id: &str,
+        clearance: PrivacyLevel,
+    ) -> Result<VrmData, String> {
+        // Lift masking for every data type the clearance covers
+        let mut cleared_config = config.clone();
+        for (data_type, level) in cleared_config.privacy_settings.iter_mut() {
+            if *level as u8 <= clearance as u8 {
+                *level = PrivacyLevel::None;
+                cleared_config.access_permissions.insert(*data_type, AccessPermission::Public);
+            }
+        }
+        
+        self.mask_with_config(&cleared_config, vrm_data, Some(viewer_id))
+    }
+    
+    /// Apply masking from a mask configuration for a resolved viewer
+    fn mask_with_config(
+        &self,
+        config: &SyncMaskConfig,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+    ) -> Result<VrmData, String> {
+        // Check if viewer is globally trusted
+        if let Some(viewer) = viewer_id {
+            if constant_time_contains(&config.global_trusted_agents, viewer.as_bytes()) {
+                self.audit_disclosure(&config.nft_mint, viewer, "trusted_agent")?;
+                return Ok(vrm_data.clone());
+            }
+            
+            // Check if viewer is the owner
+            if constant_time_eq(viewer.as_bytes(), config.owner.as_bytes()) {
+                self.audit_disclosure(&config.nft_mint, viewer, "owner")?;
+                return Ok(vrm_data.clone());
+            }
+        }
+        
+        self.check_query_budget(&config.nft_mint, vrm_data, viewer_id)?;
+        
+        let mut masked_data = self.mask_fields(config, vrm_data, viewer_id)?;
+        
+        // Identify the avatar to the viewer by a stable pseudonym
+        if let Some(viewer) = viewer_id {
+            let name = self.issue_pseudonym(config, viewer)?;
+            masked_data.custom_data.insert(PSEUDONYM_KEY.to_string(), serde_json::Value::String(name));
+        }
+        
+        Ok(masked_data)
+    }
+    
+    /// Mask each VRM data type by its privacy setting and the viewer's access
+    fn mask_fields(
+        &self,
+        config: &SyncMaskConfig,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+    ) -> Result<VrmData, String> {
+        // Create a new masked VRM data instance
+        let mut masked_data = vrm_data.clone();
+        
+        // Apply masking based on privacy settings and access permissions
+        self.mask_position(&mut masked_data.position, config, VrmDataType::Position, viewer_id)?;
+        self.mask_rotation(&mut masked_data.rotation, config, VrmDataType::Rotation, viewer_id)?;
+        self.mask_scale(&mut masked_data.scale, &mut masked_data.bounding_box, config, VrmDataType::Scale, viewer_id)?;
+        
+        if let Some(voice) = &mut masked_data.voice {
+            self.mask_voice(voice, config, VrmDataType::Voice, viewer_id)?;
+        }
+        
+        for gesture in &mut masked_data.gestures {
+            self.mask_gesture(gesture, config, VrmDataType::Gesture, viewer_id)?;
+        }
+        
+        self.mask_interactions(&mut masked_data.interactions, config, VrmDataType::Interaction, viewer_id)?;
+        
+        Ok(masked_data)
+    }
+    
+    /// Reproduce what a viewer was shown at a past seed epoch and attest to it
+    ///
+    /// Masking is deterministic in the epoch's noise seed, so the same raw
+    /// data always masks to the same output. The proof is signed with the
+    /// frame signer; nothing is audited or counted against query budgets.
+    pub fn prove_mask(
+        &self,
+        nft_mint: &str,
+        raw_data: &VrmData,
+        viewer_id: Option<&str>,
+        seed_epoch: u32,
+        timestamp: u64,
+    ) -> Result<MaskProof, String> {
+        let frame_signer = self.frame_signer.as_ref().ok_or("No frame signer configured to attest")?;
+        let (config, viewer, masked) = self.reproduce_mask(nft_mint, raw_data, viewer_id, seed_epoch)?;
+        
+        let proof = MaskProof {
+            nft_mint: nft_mint.to_string(),
+            seed_epoch,
+            config_hash: config.hash(),
+            viewer,
+            timestamp,
+            raw_hash: proof::data_hash(raw_data),
+            masked,
+            attester: frame_signer.pubkey(),
+            signature: Signature::default(),
+        };
+        
+        Ok(proof.sign(frame_signer))
     }
     
-    /// Remove a trusted agent
-    pub fn remove_trusted_agent(
-        &mut self,
-        nft_mint: &str,
-        agent_id: &str,
-    ) -> Result<(), String> {
-        let config = self.config_cache.get_mut(nft_mint).ok_or("Config not found")?;
+    /// Check a mask proof against the raw data it claims to be made from
+    ///
+    /// The proof must be signed by the frame signer or the owner, and
+    /// re-masking the raw data under the current config must reproduce it.
+    pub fn verify_mask_proof(&self, proof: &MaskProof, raw_data: &VrmData) -> Result<(), String> {
+        let config = self.config_cache.get(&proof.nft_mint).ok_or("Config not found")?;
         
-        config.global_trusted_agents.retain(|id| id != agent_id);
+        // Step 1: Authenticate the attester
+        let trusted_attesters: Vec<Pubkey> = self.frame_signer.iter()
+            .map(|signer| signer.pubkey())
+            .chain(Pubkey::from_str(&config.owner).ok())
+            .collect();
+        proof.verify(&trusted_attesters)?;
+        
+        // Step 2: Check the raw data is what the proof committed to
+        if proof.raw_hash != proof::data_hash(raw_data) {
+            return Err("Raw data does not match the mask proof".to_string());
+        }
+        
+        // Step 3: Reproduce the masked output
+        let (config, _, masked) = self.reproduce_mask(&proof.nft_mint, raw_data, proof.viewer.as_deref(), proof.seed_epoch)?;
+        if config.hash() != proof.config_hash {
+            return Err("Mask config has changed since the proof was made".to_string());
+        }
+        if proof::data_hash(&masked) != proof::data_hash(&proof.masked) {
+            return Err("Masked data does not match the mask proof".to_string());
+        }
         
         Ok(())
     }
     
-    /// Check if an agent is trusted
-    pub fn is_trusted_agent(
+    /// Mask as `apply_mask_at_epoch` would, without auditing, budgets, or issuing pseudonyms
+    fn reproduce_mask(
         &self,
         nft_mint: &str,
-        agent_id: &str,
-    ) -> Result<bool, String> {
-        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        raw_data: &VrmData,
+        viewer_id: Option<&str>,
+        seed_epoch: u32,
+    ) -> Result<(SyncMaskConfig, Option<String>, VrmData), String> {
+        let mut config = self.config_cache.get(nft_mint).ok_or("Config not found")?.clone();
+        if seed_epoch > config.seed_epoch {
+            return Err(format!("Noise seed epoch {} has not been reached yet", seed_epoch));
+        }
+        config.seed_epoch = seed_epoch;
+        
+        let viewer = viewer_id.map(|viewer| {
+            self.resolve_agent_id(viewer).unwrap_or_else(|_| viewer.to_string())
+        });
+        
+        let masked = match viewer.as_deref() {
+            Some(viewer) if config.is_trusted_or_owner(viewer) => raw_data.clone(),
+            _ => {
+                let mut masked = self.mask_fields(&config, raw_data, viewer.as_deref())?;
+                if let Some(viewer) = viewer.as_deref() {
+                    let name = pseudonym::pseudonym(config.noise_seed, viewer, nft_mint);
+                    masked.custom_data.insert(PSEUDONYM_KEY.to_string(), serde_json::Value::String(name));
+                }
+                masked
+            }
+        };
         
-        Ok(config.global_trusted_agents.contains(&agent_id.to_string()))
+        Ok((config, viewer, masked))
     }
     
-    /// Apply synchronicity mask to VRM data
-    pub fn apply_mask(
+    /// Export a viewer's mask as parameters for a compute shader, instead of masking data
+    ///
+    /// Defaults to the current seed epoch. Parameters leave every stream
+    /// unmasked for trusted agents and the owner; those disclosures are
+    /// audited like `apply_mask`'s.
+    pub fn export_mask_params(
         &self,
         nft_mint: &str,
-        vrm_data: &VrmData,
         viewer_id: Option<&str>,
-    ) -> Result<VrmData, String> {
+        seed_epoch: Option<u32>,
+    ) -> Result<MaskParams, String> {
         let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        let seed_epoch = seed_epoch.unwrap_or(config.seed_epoch);
+        if seed_epoch > config.seed_epoch {
+            return Err(format!("Noise seed epoch {} has not been reached yet", seed_epoch));
+        }
         
-        // Check if viewer is globally trusted
-        if let Some(viewer) = viewer_id {
-            if config.global_trusted_agents.contains(&viewer.to_string()) {
-                return Ok(vrm_data.clone());
+        let mut params = MaskParams::unmasked(config.epoch_seed(seed_epoch), seed_epoch);
+        let viewer = viewer_id.map(|viewer| {
+            self.resolve_agent_id(viewer).unwrap_or_else(|_| viewer.to_string())
+        });
+        if let Some(viewer) = viewer.as_deref() {
+            if constant_time_contains(&config.global_trusted_agents, viewer.as_bytes()) {
+                self.audit_disclosure(nft_mint, viewer, "trusted_agent")?;
+                return Ok(params);
             }
-            
-            // Check if viewer is the owner
-            if viewer == config.owner {
-                return Ok(vrm_data.clone());
+            if constant_time_eq(viewer.as_bytes(), config.owner.as_bytes()) {
+                self.audit_disclosure(nft_mint, viewer, "owner")?;
+                return Ok(params);
             }
         }
         
-        // Create a new masked VRM data instance
-        let mut masked_data = vrm_data.clone();
+        for (stream, data_type) in [
+            (STREAM_POSITION, VrmDataType::Position),
+            (STREAM_ROTATION, VrmDataType::Rotation),
+            (STREAM_SCALE, VrmDataType::Scale),
+            (STREAM_JOINTS, VrmDataType::Gesture),
+        ] {
+            // No access masks the stream completely, as on the CPU
+            let level = match self.has_access(config, data_type.clone(), viewer.as_deref())? {
+                true => *config.privacy_settings.get(&data_type).unwrap_or(&PrivacyLevel::None),
+                false => PrivacyLevel::Complete,
+            };
+            params.set_level(stream, level);
+        }
         
-        // Apply masking based on privacy settings and access permissions
-        self.mask_position(&mut masked_data.position, config, VrmDataType::Position, viewer_id)?;
-        self.mask_rotation(&mut masked_data.rotation, config, VrmDataType::Rotation, viewer_id)?;
+        Ok(params)
+    }
+    
+    /// Export the bone profile's limits for the given joints, in the order the shader indexes them
+    ///
+    /// Joints without limits, or all joints without a bone profile, are unconstrained.
+    pub fn export_joint_clamps(&self, joints: &[&str]) -> Vec<JointClamp> {
+        joints.iter()
+            .map(|joint| match self.bone_profile.as_ref().and_then(|profile| profile.joints.get(*joint)) {
+                Some(limits) => JointClamp {
+                    min: [limits.min[0].to_radians(), limits.min[1].to_radians(), limits.min[2].to_radians(), 0.0],
+                    max: [limits.max[0].to_radians(), limits.max[1].to_radians(), limits.max[2].to_radians(), 0.0],
+                },
+                None => JointClamp::UNCONSTRAINED,
+            })
+            .collect()
+    }
+    
+    /// Get the pseudonym a viewer sees an NFT under, recording it for resolution
+    fn issue_pseudonym(&self, config: &SyncMaskConfig, viewer_id: &str) -> Result<String, String> {
+        let name = pseudonym::pseudonym(config.noise_seed, viewer_id, &config.nft_mint);
         
-        if let Some(voice) = &mut masked_data.voice {
-            self.mask_voice(voice, config, VrmDataType::Voice, viewer_id)?;
+        let _guard = self.session_lock.lock().unwrap();
+        let mut session = self.sessions.get(&config.nft_mint, viewer_id)?.unwrap_or_default();
+        if session.pseudonym.as_deref() != Some(name.as_str()) {
+            session.pseudonym = Some(name.clone());
+            self.sessions.put(&config.nft_mint, viewer_id, &session)?;
         }
         
-        for gesture in &mut masked_data.gestures {
-            self.mask_gesture(gesture, config, VrmDataType::Gesture, viewer_id)?;
+        Ok(name)
+    }
+    
+    /// Get the pseudonym a viewer sees an NFT's avatar under
+    pub fn viewer_pseudonym(&self, nft_mint: &str, viewer_id: &str) -> Result<String, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        let viewer = self.resolve_agent_id(viewer_id).unwrap_or_else(|_| viewer_id.to_string());
+        
+        Ok(pseudonym::pseudonym(config.noise_seed, &viewer, nft_mint))
+    }
+    
+    /// Resolve a pseudonym back to the viewers it was shown to
+    ///
+    /// Restricted to the owner, who signs the request with
+    /// `sign_resolve_request`. Short pseudonyms can collide, so more than one
+    /// viewer may be returned.
+    pub fn resolve_pseudonym(
+        &self,
+        nft_mint: &str,
+        pseudonym: &str,
+        owner_signature: &Signature,
+    ) -> Result<Vec<String>, String> {
+        let config = self.config_cache.get(nft_mint).ok_or("Config not found")?;
+        let owner = Pubkey::from_str(&config.owner)
+            .map_err(|e| format!("Invalid owner key: {}", e))?;
+        
+        if !pseudonym::verify_resolve_request(owner.as_ref(), nft_mint, pseudonym, owner_signature) {
+            return Err("Only the owner can resolve pseudonyms".to_string());
         }
         
-        Ok(masked_data)
+        let mut viewers: Vec<String> = self.sessions.sessions(nft_mint)?.into_iter()
+            .filter(|(_, session)| session.pseudonym.as_deref() == Some(pseudonym))
+            .map(|(viewer, _)| viewer)
+            .collect();
+        viewers.sort();
+        
+        Ok(viewers)
     }
     
     /// Mask position data
@@ -221,7 +1139,7 @@ impl SynchronicityMask {
         // Check access permission
         if !self.has_access(config, data_type, viewer_id)? {
             // No access, completely randomize
-            let mut rng = StdRng::seed_from_u64(config.noise_seed);
+            let mut rng = StdRng::seed_from_u64(config.current_seed());
             position.x = rng.gen_range(-100.0..100.0);
             position.y = rng.gen_range(-100.0..100.0);
             position.z = rng.gen_range(-100.0..100.0);
@@ -237,16 +1155,16 @@ impl SynchronicityMask {
                 // No masking
             },
             PrivacyLevel::Light => {
-                masking::add_position_noise(position, 0.1, config.noise_seed);
+                masking::add_position_noise(position, 0.1, config.current_seed());
             },
             PrivacyLevel::Medium => {
-                masking::add_position_noise(position, 0.3, config.noise_seed);
+                masking::add_position_noise(position, 0.3, config.current_seed());
             },
             PrivacyLevel::Heavy => {
-                masking::add_position_noise(position, 0.7, config.noise_seed);
+                masking::add_position_noise(position, 0.7, config.current_seed());
             },
             PrivacyLevel::Complete => {
-                let mut rng = StdRng::seed_from_u64(config.noise_seed);
+                let mut rng = StdRng::seed_from_u64(config.current_seed());
                 position.x = rng.gen_range(-100.0..100.0);
                 position.y = rng.gen_range(-100.0..100.0);
                 position.z = rng.gen_range(-100.0..100.0);
@@ -267,7 +1185,7 @@ impl SynchronicityMask {
         // Check access permission
         if !self.has_access(config, data_type, viewer_id)? {
             // No access, completely randomize
-            let mut rng = StdRng::seed_from_u64(config.noise_seed);
+            let mut rng = StdRng::seed_from_u64(config.current_seed());
             rotation.x = rng.gen_range(-1.0..1.0);
             rotation.y = rng.gen_range(-1.0..1.0);
             rotation.z = rng.gen_range(-1.0..1.0);
@@ -290,16 +1208,16 @@ impl SynchronicityMask {
                 // No masking
             },
             PrivacyLevel::Light => {
-                masking::add_rotation_noise(rotation, 0.1, config.noise_seed);
+                masking::add_rotation_noise(rotation, 0.1, config.current_seed());
             },
             PrivacyLevel::Medium => {
-                masking::add_rotation_noise(rotation, 0.3, config.noise_seed);
+                masking::add_rotation_noise(rotation, 0.3, config.current_seed());
             },
             PrivacyLevel::Heavy => {
-                masking::add_rotation_noise(rotation, 0.7, config.noise_seed);
+                masking::add_rotation_noise(rotation, 0.7, config.current_seed());
             },
             PrivacyLevel::Complete => {
-                let mut rng = StdRng::seed_from_u64(config.noise_seed);
+                let mut rng = StdRng::seed_from_u64(config.current_seed());
                 rotation.x = rng.gen_range(-1.0..1.0);
                 rotation.y = rng.gen_range(-1.0..1.0);
                 rotation.z = rng.gen_range(-1.0..1.0);
@@ -316,6 +1234,53 @@ impl SynchronicityMask {
         Ok(())
     }
     
+    /// Mask scale and bounding box data
+    ///
+    /// Without access, or at `Complete`, the avatar is reported at natural
+    /// size with no bounding box.
+    fn mask_scale(
+        &self,
+        scale: &mut Option<ScaleData>,
+        bounding_box: &mut Option<BoundingBox>,
+        config: &SyncMaskConfig,
+        data_type: VrmDataType,
+        viewer_id: Option<&str>,
+    ) -> Result<(), String> {
+        if scale.is_none() && bounding_box.is_none() {
+            return Ok(());
+        }
+        
+        // Get privacy level, treating no access as complete masking
+        let level = if self.has_access(config, data_type.clone(), viewer_id)? {
+            *config.privacy_settings.get(&data_type).unwrap_or(&PrivacyLevel::None)
+        } else {
+            PrivacyLevel::Complete
+        };
+        
+        let intensity = match level {
+            PrivacyLevel::None => return Ok(()),
+            PrivacyLevel::Light => 0.1,
+            PrivacyLevel::Medium => 0.3,
+            PrivacyLevel::Heavy => 0.7,
+            PrivacyLevel::Complete => {
+                if scale.is_some() {
+                    *scale = Some(ScaleData { x: 1.0, y: 1.0, z: 1.0 });
+                }
+                *bounding_box = None;
+                return Ok(());
+            },
+        };
+        
+        if let Some(scale) = scale {
+            masking::add_scale_noise(scale, intensity, config.current_seed());
+        }
+        if let Some(bounding_box) = bounding_box {
+            masking::add_bounding_box_noise(bounding_box, intensity, config.current_seed());
+        }
+        
+        Ok(())
+    }
+    
     /// Mask voice data
     fn mask_voice(
         &self,
@@ -343,13 +1308,13 @@ impl SynchronicityMask {
                 // No masking
             },
             PrivacyLevel::Light => {
-                masking::add_voice_noise(voice, 0.1, config.noise_seed);
+                masking::add_voice_noise(voice, 0.1, config.current_seed());
             },
             PrivacyLevel::Medium => {
-                masking::add_voice_noise(voice, 0.3, config.noise_seed);
+                masking::add_voice_noise(voice, 0.3, config.current_seed());
             },
             PrivacyLevel::Heavy => {
-                masking::add_voice_noise(voice, 0.7, config.noise_seed);
+                masking::add_voice_noise(voice, 0.7, config.current_seed());
             },
             PrivacyLevel::Complete => {
                 voice.frequency = vec![0.0; voice.frequency.len()];
@@ -373,7 +1338,7 @@ impl SynchronicityMask {
         // Check access permission
         if !self.has_access(config, data_type, viewer_id)? {
             // No access, completely randomize
-            let mut rng = StdRng::seed_from_u64(config.noise_seed);
+            let mut rng = StdRng::seed_from_u64(config.current_seed());
             gesture.intensity = rng.gen_range(0.0..1.0);
             gesture.speed = rng.gen_range(0.0..2.0);
             return Ok(());
@@ -388,21 +1353,74 @@ impl SynchronicityMask {
                 // No masking
             },
             PrivacyLevel::Light => {
-                masking::add_gesture_noise(gesture, 0.1, config.noise_seed);
+                masking::add_gesture_noise(gesture, 0.1, config.current_seed());
             },
             PrivacyLevel::Medium => {
-                masking::add_gesture_noise(gesture, 0.3, config.noise_seed);
+                masking::add_gesture_noise(gesture, 0.3, config.current_seed());
             },
             PrivacyLevel::Heavy => {
-                masking::add_gesture_noise(gesture, 0.7, config.noise_seed);
+                masking::add_gesture_noise(gesture, 0.7, config.current_seed());
             },
             PrivacyLevel::Complete => {
-                let mut rng = StdRng::seed_from_u64(config.noise_seed);
+                let mut rng = StdRng::seed_from_u64(config.current_seed());
                 gesture.intensity = rng.gen_range(0.0..1.0);
                 gesture.speed = rng.gen_range(0.0..2.0);
             },
         }
         
+        // Keep noisy joint rotations within the body's range of motion
+        if let Some(bone_profile) = &self.bone_profile {
+            bone_profile.clamp_gesture(gesture);
+        }
+        
+        Ok(())
+    }
+    
+    /// Mask interaction events
+    ///
+    /// Higher levels jitter timing more, pseudonymize targets, and drop a
+    /// growing share of events; without access, or at `Complete`, none are kept.
+    fn mask_interactions(
+        &self,
+        interactions: &mut Vec<InteractionEvent>,
+        config: &SyncMaskConfig,
+        data_type: VrmDataType,
+        viewer_id: Option<&str>,
+    ) -> Result<(), String> {
+        if interactions.is_empty() {
+            return Ok(());
+        }
+        
+        // Get privacy level, treating no access as complete masking
+        let level = if self.has_access(config, data_type.clone(), viewer_id)? {
+            *config.privacy_settings.get(&data_type).unwrap_or(&PrivacyLevel::None)
+        } else {
+            PrivacyLevel::Complete
+        };
+        
+        // (timing jitter, pseudonymize targets, share of events dropped)
+        let (intensity, pseudonymize, drop_rate) = match level {
+            PrivacyLevel::None => return Ok(()),
+            PrivacyLevel::Light => (0.1, false, 0.0),
+            PrivacyLevel::Medium => (0.3, true, 0.25),
+            PrivacyLevel::Heavy => (0.7, true, 0.5),
+            PrivacyLevel::Complete => {
+                interactions.clear();
+                return Ok(());
+            },
+        };
+        
+        let seed = config.current_seed();
+        let mut rng = StdRng::seed_from_u64(seed);
+        interactions.retain(|_| rng.gen::<f32>() >= drop_rate);
+        
+        for (i, interaction) in interactions.iter_mut().enumerate() {
+            masking::add_interaction_noise(interaction, intensity, seed.wrapping_add(i as u64));
+            if pseudonymize {
+                interaction.target_id = masking::pseudonymize_target(&interaction.target_id, seed);
+            }
+        }
+        
         Ok(())
     }
     
@@ -420,13 +1438,13 @@ impl SynchronicityMask {
                 },
                 AccessPermission::Restricted(allowed_agents) => {
                     if let Some(viewer) = viewer_id {
-                        return Ok(allowed_agents.contains(&viewer.to_string()));
+                        return Ok(constant_time_contains(allowed_agents, viewer.as_bytes()));
                     }
                     return Ok(false);
                 },
                 AccessPermission::OwnerOnly => {
                     if let Some(viewer) = viewer_id {
-                        return Ok(viewer == config.owner);
+                        return Ok(constant_time_eq(viewer.as_bytes(), config.owner.as_bytes()));
                     }
                     return Ok(false);
                 },
@@ -437,3 +1455,674 @@ impl SynchronicityMask {
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    
+    /// Resolver backed by a fixed name table
+    struct StaticResolver(HashMap<String, Pubkey>);
+    
+    impl AgentResolver for StaticResolver {
+        fn resolve_agent(&self, name: &str) -> Result<Pubkey, String> {
+            self.0.get(name).copied().ok_or_else(|| format!("Agent not registered: {}", name))
+        }
+    }
+    
+    #[test]
+    fn test_trusted_agents_are_resolved_to_keys() {
+        let agent_key = Pubkey::new_unique();
+        let resolver = StaticResolver(HashMap::from([("agent1.glitch.gang".to_string(), agent_key)]));
+        let mut mask = SynchronicityMask::offline()
+            .with_agent_resolver(Arc::new(resolver));
+        
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Complete);
+        let mint = nft_mint.to_string();
+        
+        mask.add_trusted_agent(&mint, "agent1.glitch.gang").unwrap();
+        assert!(mask.is_trusted_agent(&mint, &agent_key.to_string()).unwrap());
+        assert!(mask.add_trusted_agent(&mint, "unknown.glitch.gang").is_err());
+        
+        // Viewing by name or by key both see through the mask
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        for viewer in ["agent1.glitch.gang".to_string(), agent_key.to_string()] {
+            let masked = mask.apply_mask(&mint, &vrm_data, Some(&viewer)).unwrap();
+            assert_eq!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0));
+        }
+        
+        mask.remove_trusted_agent(&mint, "agent1.glitch.gang").unwrap();
+        assert!(!mask.is_trusted_agent(&mint, &agent_key.to_string()).unwrap());
+    }
+    
+    #[test]
+    fn test_near_miss_identities_stay_masked() {
+        let owner = Pubkey::new_unique().to_string();
+        let agent = Pubkey::new_unique().to_string();
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::from_str(&owner).unwrap(), PrivacyLevel::Complete);
+        let mint = nft_mint.to_string();
+        mask.add_trusted_agent(&mint, &agent).unwrap();
+        let config = mask.get_config(&mint).unwrap();
+        
+        assert!(config.is_trusted_or_owner(&owner));
+        assert!(config.is_trusted_or_owner(&agent));
+        
+        // Identities sharing all but the last character, or a prefix, get no access
+        for id in [&owner, &agent] {
+            let near_miss = format!("{}{}", &id[..id.len() - 1], if id.ends_with('1') { '2' } else { '1' });
+            for probe in [near_miss.as_str(), &id[..id.len() - 1], format!("{}1", id).as_str()] {
+                assert!(!config.is_trusted_or_owner(probe));
+                assert!(!mask.is_trusted_agent(&mint, probe).unwrap_or(false));
+                assert!(!AccessPermission::OwnerOnly.has_access(Some(probe), &config.owner));
+            }
+        }
+    }
+    
+    #[test]
+    fn test_blind_token_unmasks_without_identifying_viewer() {
+        use solana_sdk::signature::{Keypair, Signer};
+        
+        let mut mask = SynchronicityMask::offline();
+        let owner = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        
+        // The viewer blinds a request, the issuer evaluates it, and the viewer unblinds it
+        let issuer = TokenIssuer::generate(&nft_mint);
+        let level = PrivacyLevel::Medium as u8;
+        let (pending, request) = PendingToken::new(&nft_mint, level);
+        let response = issuer.issue(&request).unwrap();
+        let token = pending.finalize(&response, &issuer.public_key(level)).unwrap();
+        
+        // What the issuer saw can't be matched to the redeemed token
+        assert_ne!(request.blinded, token.evaluated);
+        assert_ne!(response.evaluated, token.evaluated);
+        
+        let redemption = issuer.redeem(&token).unwrap();
+        let masked = mask.apply_mask_with_redemption(&mint, &vrm_data, &redemption).unwrap();
+        assert_eq!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0));
+        
+        // Tokens are single use and bound to their level
+        assert!(issuer.redeem(&token).is_err());
+        let (pending, request) = PendingToken::new(&nft_mint, level);
+        let response = issuer.issue(&request).unwrap();
+        let token = pending.finalize(&response, &issuer.public_key(level)).unwrap();
+        assert!(issuer.redeem(&BlindToken { level: level + 1, ..token.clone() }).is_err());
+        
+        // Evaluations under a key other than the published one are caught
+        let (pending, request) = PendingToken::new(&nft_mint, level);
+        let response = issuer.issue(&request).unwrap();
+        assert!(pending.finalize(&response, &issuer.public_key(level + 1)).is_err());
+        
+        // Issuers for other NFTs don't accept the token
+        assert!(TokenIssuer::generate(&Pubkey::new_unique()).redeem(&token).is_err());
+    }
+    
+    #[test]
+    fn test_group_member_gets_one_token_per_epoch_anonymously() {
+        let nft_mint = Pubkey::new_unique();
+        let issuer = TokenIssuer::generate(&nft_mint);
+        let members: Vec<RingKey> = (0..4).map(|_| RingKey::generate()).collect();
+        let ring: Vec<[u8; 32]> = members.iter().map(RingKey::public_key).collect();
+        let level = PrivacyLevel::Medium as u8;
+        
+        // Any member can sign for the group; the signature doesn't say which
+        let (pending, request) = PendingToken::new(&nft_mint, level);
+        let context = member_context(&nft_mint, 1);
+        let signature = members[2].sign(&ring, &context, &request.signing_message(&nft_mint)).unwrap();
+        let response = issuer.issue_to_member(&request, &ring, &signature, 1).unwrap();
+        let token = pending.finalize(&response, &issuer.public_key(level)).unwrap();
+        assert!(issuer.redeem(&token).is_ok());
+        
+        // The same member is linked on a second request in the epoch, but not the next one
+        let (_, request) = PendingToken::new(&nft_mint, level);
+        let again = members[2].sign(&ring, &context, &request.signing_message(&nft_mint)).unwrap();
+        assert_eq!(again.key_image, signature.key_image);
+        assert!(issuer.issue_to_member(&request, &ring, &again, 1).is_err());
+        let next_epoch = members[2].sign(&ring, &member_context(&nft_mint, 2), &request.signing_message(&nft_mint)).unwrap();
+        assert!(issuer.issue_to_member(&request, &ring, &next_epoch, 2).is_ok());
+        
+        // Other members are not linked to them
+        let other = members[0].sign(&ring, &context, &request.signing_message(&nft_mint)).unwrap();
+        assert_ne!(other.key_image, signature.key_image);
+        
+        // Outsiders can't sign, and signatures don't carry over to other requests
+        assert!(RingKey::generate().sign(&ring, &context, b"request").is_err());
+        let (_, other_request) = PendingToken::new(&nft_mint, level);
+        assert!(issuer.issue_to_member(&other_request, &ring, &other, 1).is_err());
+        assert!(issuer.issue_to_member(&request, &ring[..3], &other, 1).is_err());
+    }
+    
+    #[test]
+    fn test_access_token_unmasks_covered_levels() {
+        use solana_sdk::signature::{Keypair, Signer};
+        
+        let mut mask = SynchronicityMask::offline();
+        let owner = Keypair::new();
+        let delegate = Keypair::new();
+        let viewer = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        mask.update_privacy_setting(&mint, VrmDataType::Rotation, PrivacyLevel::Complete).unwrap();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        vrm_data.rotation = RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+        
+        // A medium token reveals position but not the completely private rotation
+        let token = AccessToken::issue(&owner, &viewer, &nft_mint, PrivacyLevel::Medium as u8, 60);
+        let token = AccessToken::decode(&token.encode()).unwrap();
+        let masked = mask.apply_mask_with_token(&mint, &vrm_data, &token).unwrap();
+        assert_eq!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0));
+        assert_ne!(masked.rotation.w, 1.0);
+        
+        // Delegates can only issue once authorized
+        let delegated = AccessToken::issue(&delegate, &viewer, &nft_mint, PrivacyLevel::Medium as u8, 60);
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &delegated).is_err());
+        mask.add_token_delegate(&mint, &delegate.pubkey()).unwrap();
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &delegated).is_ok());
+        
+        // Revoked tokens are rejected
+        mask.revoke_token(&delegated);
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &delegated).is_err());
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &token).is_ok());
+    }
+    
+    #[test]
+    fn test_access_level_unmasks_through_policy() {
+        let mut mask = SynchronicityMask::offline()
+            .with_access_policy(AccessPolicy::new([10, 20, 30, 40]).unwrap());
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        let viewer = Pubkey::new_unique().to_string();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        
+        // One below the medium threshold stays masked; the threshold itself unmasks
+        let masked = mask.apply_mask_with_access_level(&mint, &vrm_data, &viewer, 19).unwrap();
+        assert_ne!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0));
+        let masked = mask.apply_mask_with_access_level(&mint, &vrm_data, &viewer, 20).unwrap();
+        assert_eq!((masked.position.x, masked.position.y, masked.position.z), (1.0, 2.0, 3.0));
+    }
+    
+    #[test]
+    fn test_one_off_level_masks_every_data_type() {
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        let mint = nft_mint.to_string();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        vrm_data.voice = Some(VoiceData { frequency: vec![440.0], amplitude: vec![0.5], pitch: 100.0, timbre: 0.5 });
+        vrm_data.gestures.push(GestureData {
+            name: "wave".to_string(),
+            intensity: 0.5,
+            speed: 1.0,
+            joint_rotations: HashMap::new(),
+        });
+        
+        // Works without a stored config, and ignores one when present
+        let unstored = mask.apply_mask_with_level(&mint, &vrm_data, PrivacyLevel::Heavy).unwrap();
+        assert_ne!(unstored.position.x, 1.0);
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Complete);
+        
+        let none = mask.apply_mask_with_level(&mint, &vrm_data, PrivacyLevel::None).unwrap();
+        assert_eq!((none.position.x, none.position.y, none.position.z), (1.0, 2.0, 3.0));
+        assert_eq!(none.voice.as_ref().unwrap().pitch, 100.0);
+        assert_eq!(none.gestures[0].speed, 1.0);
+        
+        // Noise levels stay within 10 units per unit of intensity
+        for (level, intensity) in [(PrivacyLevel::Light, 0.1), (PrivacyLevel::Medium, 0.3), (PrivacyLevel::Heavy, 0.7)] {
+            let masked = mask.apply_mask_with_level(&mint, &vrm_data, level).unwrap();
+            let offset = (masked.position.x - 1.0).abs().max((masked.position.y - 2.0).abs());
+            assert!(offset > 0.0 && offset <= intensity * 10.0, "{:?} offset {}", level, offset);
+            assert_ne!(masked.voice.as_ref().unwrap().pitch, 100.0);
+            assert_ne!(masked.rotation.w, 1.0);
+        }
+        
+        let complete = mask.apply_mask_with_level(&mint, &vrm_data, PrivacyLevel::Complete).unwrap();
+        let voice = complete.voice.unwrap();
+        assert_eq!((voice.pitch, voice.frequency[0]), (0.0, 0.0));
+    }
+    
+    #[test]
+    fn test_unmasked_viewers_are_audited_once() {
+        use crate::audit::{AuditSink, MemorySink};
+        use solana_sdk::signature::{Keypair, Signer};
+        
+        let sink = Arc::new(MemorySink::new());
+        let mut mask = SynchronicityMask::offline()
+            .with_audit_log(Arc::new(AuditLog::new(sink.clone())));
+        let owner = Keypair::new();
+        let viewer = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        let vrm_data = VrmData::new();
+        
+        // Masked viewers are not recorded; repeated frames are recorded once
+        mask.apply_mask(&mint, &vrm_data, Some(&viewer.to_string())).unwrap();
+        for _ in 0..3 {
+            mask.apply_mask(&mint, &vrm_data, Some(&owner.pubkey().to_string())).unwrap();
+        }
+        let token = AccessToken::issue(&owner, &viewer, &nft_mint, 4, 60);
+        mask.apply_mask_with_token(&mint, &vrm_data, &token).unwrap();
+        
+        let reasons: Vec<String> = sink.entries().unwrap().into_iter()
+            .map(|entry| match entry.event {
+                AuditEvent::ViewerUnmasked { reason, .. } => reason,
+                event => panic!("Unexpected event: {:?}", event),
+            })
+            .collect();
+        assert_eq!(reasons, vec!["owner", "access_token"]);
+    }
+    
+    #[test]
+    fn test_query_anomalies_are_audited() {
+        use crate::audit::{AuditSink, MemorySink};
+        use solana_sdk::signature::{Keypair, Signer};
+        use std::time::Duration;
+        
+        let sink = Arc::new(MemorySink::new());
+        let mut mask = SynchronicityMask::offline()
+            .with_audit_log(Arc::new(AuditLog::new(sink.clone())))
+            .with_query_limiter(QueryBudget {
+                burst: 10,
+                refill_per_sec: 0.0,
+                window: Duration::from_secs(60),
+                window_limit: 100,
+                probe_threshold: 3,
+            });
+        let owner = Keypair::new();
+        let viewer = Pubkey::new_unique().to_string();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        let vrm_data = VrmData::new();
+        
+        // The same input queried over and over is flagged once, then the budget runs out
+        for _ in 0..10 {
+            mask.apply_mask(&mint, &vrm_data, Some(&viewer)).unwrap();
+        }
+        assert!(mask.apply_mask(&mint, &vrm_data, Some(&viewer)).is_err());
+        
+        // The owner is not limited
+        mask.apply_mask(&mint, &vrm_data, Some(&owner.pubkey().to_string())).unwrap();
+        
+        let anomalies: Vec<(String, String)> = sink.entries().unwrap().into_iter()
+            .filter_map(|entry| match entry.event {
+                AuditEvent::QueryAnomaly { viewer, reason, .. } => Some((viewer, reason)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(anomalies, vec![(viewer.clone(), "repeated_probe".to_string())]);
+        
+        let stats = mask.query_stats(&mint).unwrap();
+        assert_eq!((stats[0].viewer.as_str(), stats[0].allowed, stats[0].refused), (viewer.as_str(), 10, 1));
+    }
+    
+    #[test]
+    fn test_invalid_access_tokens_are_rejected() {
+        use solana_sdk::signature::{Keypair, Signer};
+        
+        let mut mask = SynchronicityMask::offline();
+        let owner = Keypair::new();
+        let viewer = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        let vrm_data = VrmData::new();
+        
+        // Expired
+        let expired = AccessToken::issue(&owner, &viewer, &nft_mint, 4, 0);
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &expired).is_err());
+        
+        // Tampered level
+        let mut tampered = AccessToken::issue(&owner, &viewer, &nft_mint, 1, 60);
+        tampered.level = 4;
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &tampered).is_err());
+        
+        // Issued for another NFT
+        let other_mint = Pubkey::new_unique();
+        mask.create_config(&other_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let other = AccessToken::issue(&owner, &viewer, &other_mint, 4, 60);
+        assert!(mask.apply_mask_with_token(&mint, &vrm_data, &other).is_err());
+        
+        assert!(AccessToken::from_bytes(&[0u8; TOKEN_LEN - 1]).is_err());
+    }
+    
+    #[test]
+    fn test_token_blacklist_has_no_false_negatives() {
+        use solana_sdk::signature::Keypair;
+        
+        let issuer = Keypair::new();
+        let mut blacklist = TokenBlacklist::new(100, 0.01);
+        let tokens: Vec<AccessToken> = (0..100)
+            .map(|_| AccessToken::issue(&issuer, &Pubkey::new_unique(), &Pubkey::new_unique(), 1, 60))
+            .collect();
+        
+        for token in &tokens {
+            blacklist.revoke(token);
+        }
+        assert!(tokens.iter().all(|token| blacklist.is_revoked(token)));
+        
+        // Survives a round trip to the renderers
+        let blacklist: TokenBlacklist = serde_json::from_str(&serde_json::to_string(&blacklist).unwrap()).unwrap();
+        assert!(tokens.iter().all(|token| blacklist.is_revoked(token)));
+    }
+    
+    #[test]
+    fn test_config_hash_tracks_settings_not_insertion_order() {
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        let config = mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        // Same entries, rebuilt in reverse order
+        let mut reordered = config.clone();
+        let mut settings: Vec<_> = config.privacy_settings.clone().into_iter().collect();
+        settings.reverse();
+        reordered.privacy_settings = settings.into_iter().collect();
+        assert_eq!(config.hash(), reordered.hash());
+        
+        mask.update_privacy_setting(&mint, VrmDataType::Voice, PrivacyLevel::Complete).unwrap();
+        let updated = mask.get_config(&mint).unwrap();
+        assert_ne!(config.hash(), updated.hash());
+        
+        mask.update_access_permission(&mint, VrmDataType::Voice, AccessPermission::OwnerOnly).unwrap();
+        assert_ne!(updated.hash(), mask.get_config(&mint).unwrap().hash());
+    }
+    
+    #[test]
+    fn test_noise_seed_rotation_keeps_past_epochs_reproducible() {
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        
+        let epoch_0 = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        let root_seed = mask.get_config(&mint).unwrap().noise_seed;
+        
+        assert_eq!(mask.rotate_noise_seed(&mint).unwrap(), 1);
+        let config = mask.get_config(&mint).unwrap();
+        assert_eq!(config.noise_seed, root_seed);
+        assert_ne!(config.current_seed(), root_seed);
+        assert_eq!(config.current_seed(), config.epoch_seed(1));
+        
+        // New noise going forward, old noise still reproducible
+        let epoch_1 = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        assert_ne!(epoch_1.position.x, epoch_0.position.x);
+        let replayed = mask.apply_mask_at_epoch(&mint, &vrm_data, None, 0).unwrap();
+        assert_eq!(replayed.position.x, epoch_0.position.x);
+        
+        assert!(mask.apply_mask_at_epoch(&mint, &vrm_data, None, 2).is_err());
+        assert!(mask.rotate_noise_seed("unknown").is_err());
+    }
+    
+    #[test]
+    fn test_mask_proofs_reproduce_what_a_viewer_saw() {
+        let signer = Arc::new(Keypair::new());
+        let mut mask = SynchronicityMask::offline()
+            .with_frame_signer(signer.clone());
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        let mut raw = VrmData::new();
+        raw.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        let seen = mask.apply_mask(&mint, &raw, Some("viewer")).unwrap();
+        mask.rotate_noise_seed(&mint).unwrap();
+        
+        // The proof made after rotation reproduces the epoch 0 output
+        let proof = mask.prove_mask(&mint, &raw, Some("viewer"), 0, 1_700_000_000_000).unwrap();
+        assert_eq!(serde_json::to_value(&proof.masked).unwrap(), serde_json::to_value(&seen).unwrap());
+        assert_eq!(proof.attester, signer.pubkey());
+        
+        let proof: MaskProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+        assert!(mask.verify_mask_proof(&proof, &raw).is_ok());
+        
+        // Different raw data, doctored output, or a forged signature are caught
+        let mut other_raw = raw.clone();
+        other_raw.position.x = 9.0;
+        assert!(mask.verify_mask_proof(&proof, &other_raw).unwrap_err().contains("Raw data"));
+        
+        let mut doctored = proof.clone();
+        doctored.masked.position.x += 1.0;
+        assert!(mask.verify_mask_proof(&doctored, &raw).is_err());
+        assert!(mask.verify_mask_proof(&doctored.sign(&signer), &raw).unwrap_err().contains("Masked data"));
+        
+        let forged = proof.clone().sign(&Keypair::new());
+        assert!(mask.verify_mask_proof(&forged, &raw).unwrap_err().contains("Untrusted"));
+        
+        assert!(mask.prove_mask(&mint, &raw, None, 2, 0).is_err());
+    }
+    
+    #[test]
+    fn test_masked_frames_are_authenticated_and_re_signed() {
+        use solana_sdk::signature::Signer;
+        
+        let owner = Keypair::new();
+        let mask_signer = Arc::new(Keypair::new());
+        let mut mask = SynchronicityMask::offline()
+            .with_frame_signer(mask_signer.clone());
+        
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.position = PositionData { x: 1.0, y: 2.0, z: 3.0 };
+        let frame = VrmFrame::sign(&owner, &nft_mint, 1, vrm_data.clone());
+        
+        let masked = mask.apply_mask_to_frame(&frame, None).unwrap();
+        assert!(masked.verify(&[mask_signer.pubkey()]).is_ok());
+        assert_eq!((masked.sequence, masked.timestamp), (frame.sequence, frame.timestamp));
+        assert_ne!(masked.data.position.x, vrm_data.position.x);
+        
+        // Replays and unknown publishers are rejected
+        assert!(mask.apply_mask_to_frame(&frame, None).is_err());
+        let publisher = Keypair::new();
+        let spoofed = VrmFrame::sign(&publisher, &nft_mint, 2, vrm_data.clone());
+        assert!(mask.apply_mask_to_frame(&spoofed, None).is_err());
+        
+        mask.add_frame_publisher(&nft_mint.to_string(), &publisher.pubkey()).unwrap();
+        let published = VrmFrame::sign(&publisher, &nft_mint, 2, vrm_data);
+        assert!(mask.apply_mask_to_frame(&published, None).is_ok());
+    }
+    
+    #[test]
+    fn test_crowd_blend_replaces_position_noise() {
+        let mut mask = SynchronicityMask::offline();
+        let owner = Pubkey::new_unique().to_string();
+        let mut crowd = CrowdBlend::new(2, 1.0).unwrap();
+        let mut avatars = Vec::new();
+        
+        for (i, x) in [0.2f32, 0.6, 0.9].iter().enumerate() {
+            let nft_mint = Pubkey::new_unique();
+            mask.create_config(&nft_mint, &Pubkey::from_str(&owner).unwrap(), PrivacyLevel::Medium);
+            
+            let mut vrm_data = VrmData::new();
+            vrm_data.position = PositionData { x: *x, y: 0.0, z: 0.0 };
+            crowd.report(&i.to_string(), vrm_data.position.clone());
+            avatars.push((nft_mint.to_string(), vrm_data));
+        }
+        let grid = crowd.grid().unwrap();
+        
+        for (mint, vrm_data) in &avatars {
+            let masked = mask.apply_mask_in_crowd(mint, vrm_data, None, &grid).unwrap();
+            assert_eq!(masked.position.x, 0.5);
+            
+            // The owner sees the exact position
+            let unmasked = mask.apply_mask_in_crowd(mint, vrm_data, Some(&owner), &grid).unwrap();
+            assert_eq!(unmasked.position.x, vrm_data.position.x);
+        }
+    }
+    
+    #[test]
+    fn test_bone_profile_keeps_masked_gestures_plausible() {
+        let profile = BoneProfile::humanoid();
+        let mut mask = SynchronicityMask::offline()
+            .with_bone_profile(profile.clone());
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Heavy);
+        
+        // Straight knees, which noise could bend backwards
+        let knee = RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+        let mut vrm_data = VrmData::new();
+        vrm_data.add_gesture(GestureData {
+            name: "kneel".to_string(),
+            intensity: 0.5,
+            speed: 1.0,
+            joint_rotations: HashMap::from([
+                ("leftLowerLeg".to_string(), knee.clone()),
+                ("rightLowerLeg".to_string(), knee),
+            ]),
+        });
+        
+        let masked = mask.apply_mask(&nft_mint.to_string(), &vrm_data, None).unwrap();
+        assert!(profile.validate_pose(&masked.gestures[0]).is_ok());
+    }
+    
+    #[test]
+    fn test_scale_and_bounding_box_are_masked() {
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Heavy);
+        let mint = nft_mint.to_string();
+        
+        let mut vrm_data = VrmData::new();
+        vrm_data.scale = Some(ScaleData { x: 1.2, y: 1.2, z: 1.2 });
+        vrm_data.bounding_box = Some(BoundingBox {
+            min: PositionData { x: -0.5, y: 0.0, z: -0.5 },
+            max: PositionData { x: 0.5, y: 1.8, z: 0.5 },
+        });
+        
+        let masked = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        let scale = masked.scale.unwrap();
+        assert_ne!(scale.y, 1.2);
+        assert_eq!(scale.x, scale.y);
+        let bounding_box = masked.bounding_box.unwrap();
+        assert!(bounding_box.min.y <= 0.0 && bounding_box.max.y >= 1.8);
+        
+        mask.update_privacy_setting(&mint, VrmDataType::Scale, PrivacyLevel::Complete).unwrap();
+        let masked = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        assert_eq!(masked.scale.unwrap().y, 1.0);
+        assert!(masked.bounding_box.is_none());
+        
+        // Frames without scale data are untouched
+        assert!(mask.apply_mask(&mint, &VrmData::new(), None).unwrap().scale.is_none());
+    }
+    
+    #[test]
+    fn test_interactions_are_masked_by_level() {
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &Pubkey::new_unique(), PrivacyLevel::Light);
+        let mint = nft_mint.to_string();
+        
+        let mut vrm_data = VrmData::new();
+        for i in 0..40 {
+            vrm_data.add_interaction(InteractionEvent {
+                target_id: format!("avatar{}", i % 4),
+                kind: InteractionKind::Gaze,
+                intensity: 0.5,
+                timestamp: 1_700_000_000_000 + i,
+            });
+        }
+        
+        // Light keeps every event and target, with small timing jitter
+        let light = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        assert_eq!(light.interactions.len(), 40);
+        assert_eq!(light.interactions[0].target_id, "avatar0");
+        assert!(light.interactions.iter().zip(&vrm_data.interactions).all(|(masked, raw)| {
+            masked.timestamp.abs_diff(raw.timestamp) <= 100
+        }));
+        
+        // Heavy drops events and pseudonymizes targets consistently
+        mask.update_privacy_setting(&mint, VrmDataType::Interaction, PrivacyLevel::Heavy).unwrap();
+        let heavy = mask.apply_mask(&mint, &vrm_data, None).unwrap();
+        assert!(heavy.interactions.len() < 40);
+        assert!(heavy.interactions.iter().all(|event| event.target_id.starts_with("anon-")));
+        let targets: HashSet<&str> = heavy.interactions.iter().map(|event| event.target_id.as_str()).collect();
+        assert!(targets.len() <= 4);
+        
+        mask.update_privacy_setting(&mint, VrmDataType::Interaction, PrivacyLevel::Complete).unwrap();
+        assert!(mask.apply_mask(&mint, &vrm_data, None).unwrap().interactions.is_empty());
+    }
+    
+    #[test]
+    fn test_viewers_see_pseudonyms_only_the_owner_can_resolve() {
+        use solana_sdk::signature::Signer;
+        
+        let owner = Keypair::new();
+        let mut mask = SynchronicityMask::offline();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        let viewer = Pubkey::new_unique().to_string();
+        let masked = mask.apply_mask(&mint, &VrmData::new(), Some(&viewer)).unwrap();
+        let name = masked.custom_data[PSEUDONYM_KEY].as_str().unwrap().to_string();
+        assert!(name.starts_with("Entity-"));
+        assert_eq!(mask.viewer_pseudonym(&mint, &viewer).unwrap(), name);
+        
+        // Anonymous viewers and the owner get no pseudonym
+        assert!(!mask.apply_mask(&mint, &VrmData::new(), None).unwrap().custom_data.contains_key(PSEUDONYM_KEY));
+        let own_view = mask.apply_mask(&mint, &VrmData::new(), Some(&owner.pubkey().to_string())).unwrap();
+        assert!(!own_view.custom_data.contains_key(PSEUDONYM_KEY));
+        
+        let signature = sign_resolve_request(&owner, &mint, &name);
+        assert_eq!(mask.resolve_pseudonym(&mint, &name, &signature).unwrap(), vec![viewer]);
+        
+        let forged = sign_resolve_request(&Keypair::new(), &mint, &name);
+        assert!(mask.resolve_pseudonym(&mint, &name, &forged).is_err());
+    }
+    
+    #[test]
+    fn test_masks_sharing_a_session_store_share_viewer_state() {
+        use solana_sdk::signature::Signer;
+        use std::time::Duration;
+        
+        let sessions: Arc<MemorySessionStore<ViewerSession>> = Arc::new(MemorySessionStore::new(Duration::from_secs(60)));
+        let budget = QueryBudget { burst: 3, refill_per_sec: 0.0, ..QueryBudget::default() };
+        let owner = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        let mut masks: Vec<SynchronicityMask> = (0..2)
+            .map(|_| SynchronicityMask::offline().with_query_limiter(budget).with_session_store(sessions.clone()))
+            .collect();
+        for mask in &mut masks {
+            mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        }
+        let mint = nft_mint.to_string();
+        let viewer = Pubkey::new_unique().to_string();
+        
+        // Queries on either server spend the same budget
+        for mask in [&masks[0], &masks[1], &masks[0]] {
+            mask.apply_mask(&mint, &VrmData::new(), Some(&viewer)).unwrap();
+        }
+        assert!(masks[1].apply_mask(&mint, &VrmData::new(), Some(&viewer)).is_err());
+        assert_eq!(masks[0].query_stats(&mint).unwrap()[0].allowed, 3);
+        
+        // A pseudonym issued on one server resolves on the other
+        let name = masks[0].viewer_pseudonym(&mint, &viewer).unwrap();
+        let signature = sign_resolve_request(&owner, &mint, &name);
+        assert_eq!(masks[1].resolve_pseudonym(&mint, &name, &signature).unwrap(), vec![viewer.clone()]);
+        
+        // Expired viewers start over
+        assert_eq!(sessions.evict_expired_at(Instant::now() + Duration::from_secs(61)), 1);
+        assert!(masks[1].apply_mask(&mint, &VrmData::new(), Some(&viewer)).is_ok());
+    }
+}
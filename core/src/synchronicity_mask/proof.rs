@@ -0,0 +1,87 @@
+use serde::{Serialize, Deserialize};
+use solana_sdk::{
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use super::vrm_data::VrmData;
+
+/// Domain separator for signed mask proofs
+const PROOF_DOMAIN: &[u8] = b"quantum-veil/mask-proof/v1";
+
+/// Signed attestation of the masked data a viewer was shown
+///
+/// Commits to the raw frame by hash, so the proof can be shared without the
+/// raw data; whoever holds the raw frame can check the masked output was
+/// reproduced from it under the stated config and seed epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskProof {
+    /// NFT the data belongs to
+    pub nft_mint: String,
+    /// Noise seed epoch the data was masked under
+    pub seed_epoch: u32,
+    /// Hash of the mask config at that epoch
+    pub config_hash: String,
+    /// Resolved viewer, or `None` for anonymous viewers
+    pub viewer: Option<String>,
+    /// When the viewer was shown the data, as a Unix timestamp in milliseconds
+    pub timestamp: u64,
+    /// Hash of the raw VRM data
+    pub raw_hash: Hash,
+    /// Masked VRM data the viewer was shown
+    pub masked: VrmData,
+    /// Key that signed the proof
+    pub attester: Pubkey,
+    /// Ed25519 signature over the proof fields
+    pub signature: Signature,
+}
+
+impl MaskProof {
+    /// Sign the proof
+    pub fn sign(mut self, attester: &Keypair) -> Self {
+        self.attester = attester.pubkey();
+        self.signature = attester.sign_message(&self.message());
+        self
+    }
+    
+    /// Get the signed message: the domain separator, proof fields, and data hashes
+    fn message(&self) -> Vec<u8> {
+        let viewer = self.viewer.as_deref().unwrap_or_default();
+        
+        let mut message = PROOF_DOMAIN.to_vec();
+        for field in [self.nft_mint.as_bytes(), self.config_hash.as_bytes(), viewer.as_bytes()] {
+            message.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            message.extend_from_slice(field);
+        }
+        message.push(u8::from(self.viewer.is_some()));
+        message.extend_from_slice(&self.seed_epoch.to_le_bytes());
+        message.extend_from_slice(&self.timestamp.to_le_bytes());
+        message.extend_from_slice(self.raw_hash.as_ref());
+        message.extend_from_slice(data_hash(&self.masked).as_ref());
+        message.extend_from_slice(self.attester.as_ref());
+        message
+    }
+    
+    /// Verify the signature and that the attester is trusted
+    pub fn verify(&self, trusted_attesters: &[Pubkey]) -> Result<(), String> {
+        if !trusted_attesters.contains(&self.attester) {
+            return Err(format!("Untrusted mask proof attester: {}", self.attester));
+        }
+        
+        if !self.signature.verify(self.attester.as_ref(), &self.message()) {
+            return Err("Invalid mask proof signature".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Hash VRM data in canonical JSON, with object keys sorted
+pub fn data_hash(data: &VrmData) -> Hash {
+    let data_json = serde_json::to_value(data)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    
+    hash(data_json.as_bytes())
+}
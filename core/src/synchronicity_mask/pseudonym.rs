@@ -0,0 +1,67 @@
+use ring::hmac;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+
+/// Custom data key carrying the pseudonym in masked output
+pub const PSEUDONYM_KEY: &str = "pseudonym";
+
+/// Domain separator for owner requests to resolve a pseudonym
+const RESOLVE_DOMAIN: &[u8] = b"quantum-veil/resolve-pseudonym/v1";
+
+/// Get the stable pseudonym, e.g. `Entity-4F2A`, a viewer sees an NFT's avatar under
+///
+/// Keyed by the mask's noise seed, so viewers cannot link pseudonyms across
+/// NFTs or to each other without the mask config.
+pub fn pseudonym(noise_seed: u64, viewer_id: &str, nft_mint: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &noise_seed.to_le_bytes());
+    let tag = hmac::sign(&key, &[viewer_id.as_bytes(), &[0], nft_mint.as_bytes()].concat());
+    
+    format!("Entity-{:02X}{:02X}", tag.as_ref()[0], tag.as_ref()[1])
+}
+
+/// Get the message an owner signs to resolve a pseudonym
+fn resolve_message(nft_mint: &str, pseudonym: &str) -> Vec<u8> {
+    [RESOLVE_DOMAIN, nft_mint.as_bytes(), &[0], pseudonym.as_bytes()].concat()
+}
+
+/// Sign a request to resolve a pseudonym back to its viewers
+pub fn sign_resolve_request(owner: &Keypair, nft_mint: &str, pseudonym: &str) -> Signature {
+    owner.sign_message(&resolve_message(nft_mint, pseudonym))
+}
+
+/// Check a resolve request was signed by the owner
+pub fn verify_resolve_request(owner: &[u8], nft_mint: &str, pseudonym: &str, signature: &Signature) -> bool {
+    signature.verify(owner, &resolve_message(nft_mint, pseudonym))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    
+    #[test]
+    fn test_pseudonyms_are_stable_and_scoped() {
+        let mint = Pubkey::new_unique().to_string();
+        let name = pseudonym(42, "viewer", &mint);
+        
+        assert!(name.starts_with("Entity-"));
+        assert_eq!(name.len(), "Entity-4F2A".len());
+        assert_eq!(name, pseudonym(42, "viewer", &mint));
+        
+        // Different seeds and NFTs give unlinkable pseudonyms, almost always
+        let others = [
+            pseudonym(43, "viewer", &mint),
+            pseudonym(42, "viewer", &Pubkey::new_unique().to_string()),
+        ];
+        assert!(others.iter().any(|other| *other != name));
+    }
+    
+    #[test]
+    fn test_resolve_requests_need_the_owner_signature() {
+        let owner = Keypair::new();
+        let signature = sign_resolve_request(&owner, "mint", "Entity-4F2A");
+        
+        assert!(verify_resolve_request(owner.pubkey().as_ref(), "mint", "Entity-4F2A", &signature));
+        assert!(!verify_resolve_request(owner.pubkey().as_ref(), "mint", "Entity-0000", &signature));
+        assert!(!verify_resolve_request(Keypair::new().pubkey().as_ref(), "mint", "Entity-4F2A", &signature));
+    }
+}
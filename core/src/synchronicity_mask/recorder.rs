@@ -0,0 +1,278 @@
+use rand::{Rng, rngs::OsRng};
+use serde::{Serialize, Deserialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::quantum_veil::{encrypt_data, decrypt_data, derive_key_from_seed};
+use super::frame::VrmFrame;
+
+/// Magic bytes opening every session recording
+const SESSION_MAGIC: &[u8; 8] = b"QVSESS01";
+
+/// Domain label for deriving the recording key
+const SESSION_KEY_LABEL: &[u8] = b"quantum-veil/session-recording";
+
+/// Who and what a recording covers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionHeader {
+    /// NFT whose frames were recorded
+    pub nft_mint: String,
+    /// Viewer the frames were masked for, `None` for anonymous viewers
+    pub viewer_id: Option<String>,
+    /// Recording start as a Unix timestamp
+    pub started_at: u64,
+}
+
+/// One encrypted record: the header or a frame, with its position in the file
+#[derive(Serialize, Deserialize)]
+enum SessionRecord {
+    Header(SessionHeader),
+    Frame { index: u64, frame: VrmFrame },
+}
+
+/// Records the masked frames one viewer was shown to an encrypted, append-only file
+///
+/// Each record is sealed separately as `[len: u32][nonce: 12][ciphertext]`,
+/// so a crash loses at most the frame being written. Records carry their
+/// index, so dropped or reordered records are detected on replay.
+pub struct SessionRecorder {
+    /// Recording file, opened for appending only
+    file: File,
+    /// Key derived for this recording format
+    key: [u8; 32],
+    /// Index of the next frame record
+    next_index: u64,
+}
+
+impl SessionRecorder {
+    /// Start a new recording, failing if the file already exists
+    pub fn create(
+        path: impl AsRef<Path>,
+        encryption_key: &[u8],
+        nft_mint: &str,
+        viewer_id: Option<&str>,
+    ) -> Result<Self, String> {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| format!("Failed to create session recording: {}", e))?;
+        
+        file.write_all(SESSION_MAGIC)
+            .map_err(|e| format!("Failed to write session recording: {}", e))?;
+        
+        let mut recorder = Self {
+            file,
+            key: session_key(encryption_key),
+            next_index: 0,
+        };
+        
+        recorder.append(&SessionRecord::Header(SessionHeader {
+            nft_mint: nft_mint.to_string(),
+            viewer_id: viewer_id.map(str::to_string),
+            started_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }))?;
+        
+        Ok(recorder)
+    }
+    
+    /// Append a masked frame
+    pub fn record(&mut self, frame: &VrmFrame) -> Result<(), String> {
+        self.append(&SessionRecord::Frame { index: self.next_index, frame: frame.clone() })?;
+        self.next_index += 1;
+        Ok(())
+    }
+    
+    /// Seal and append one record
+    fn append(&mut self, record: &SessionRecord) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(record)
+            .map_err(|e| format!("Failed to serialize session record: {}", e))?;
+        
+        let mut nonce = [0u8; 12];
+        OsRng.fill(&mut nonce);
+        let ciphertext = encrypt_data(&plaintext, &self.key, &nonce)?;
+        
+        // Write the record in one call so it is never interleaved
+        let mut sealed = Vec::with_capacity(4 + 12 + ciphertext.len());
+        sealed.extend_from_slice(&((12 + ciphertext.len()) as u32).to_le_bytes());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        
+        self.file.write_all(&sealed)
+            .and_then(|_| self.file.flush())
+            .map_err(|e| format!("Failed to write session recording: {}", e))
+    }
+}
+
+/// A decrypted session recording, for replay and export
+#[derive(Debug, Clone)]
+pub struct SessionRecording {
+    /// Who and what the recording covers
+    pub header: SessionHeader,
+    /// Recorded frames in order
+    pub frames: Vec<VrmFrame>,
+}
+
+impl SessionRecording {
+    /// Open and decrypt a recording
+    pub fn open(path: impl AsRef<Path>, encryption_key: &[u8]) -> Result<Self, String> {
+        let mut bytes = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .map_err(|e| format!("Failed to read session recording: {}", e))?;
+        
+        if !bytes.starts_with(SESSION_MAGIC) {
+            return Err("Not a session recording".to_string());
+        }
+        
+        let key = session_key(encryption_key);
+        let mut header = None;
+        let mut frames = Vec::new();
+        let mut offset = SESSION_MAGIC.len();
+        
+        while offset < bytes.len() {
+            // Step 1: Split off the next sealed record
+            let len_bytes = bytes.get(offset..offset + 4).ok_or("Truncated session record")?;
+            let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+            let sealed = bytes.get(offset + 4..offset + 4 + len).ok_or("Truncated session record")?;
+            if sealed.len() < 12 {
+                return Err("Session record too short".to_string());
+            }
+            offset += 4 + len;
+            
+            // Step 2: Decrypt and check its position
+            let plaintext = decrypt_data(&sealed[12..], &key, &sealed[..12])?;
+            let record: SessionRecord = serde_json::from_slice(&plaintext)
+                .map_err(|e| format!("Failed to parse session record: {}", e))?;
+            
+            match record {
+                SessionRecord::Header(session_header) if header.is_none() => header = Some(session_header),
+                SessionRecord::Frame { index, frame } if header.is_some() => {
+                    if index != frames.len() as u64 {
+                        return Err(format!("Session record {} out of order, expected {}", index, frames.len()));
+                    }
+                    frames.push(frame);
+                },
+                _ => return Err("Unexpected session record".to_string()),
+            }
+        }
+        
+        Ok(Self {
+            header: header.ok_or("Session recording has no header")?,
+            frames,
+        })
+    }
+    
+    /// Replay the frames in recorded order
+    pub fn replay(&self) -> impl Iterator<Item = &VrmFrame> {
+        self.frames.iter()
+    }
+    
+    /// Export the recording as JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        let export = serde_json::json!({
+            "header": self.header,
+            "frames": self.frames,
+        });
+        
+        serde_json::to_string_pretty(&export)
+            .map_err(|e| format!("Failed to serialize session recording: {}", e))
+    }
+    
+    /// Export one row per frame as CSV, for analytics tools
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "sequence,timestamp,position_x,position_y,position_z,rotation_x,rotation_y,rotation_z,rotation_w,has_voice,gestures\n"
+        );
+        
+        for frame in &self.frames {
+            let data = &frame.data;
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                frame.sequence,
+                frame.timestamp,
+                data.position.x,
+                data.position.y,
+                data.position.z,
+                data.rotation.x,
+                data.rotation.y,
+                data.rotation.z,
+                data.rotation.w,
+                data.voice.is_some(),
+                data.gestures.len(),
+            ));
+        }
+        
+        csv
+    }
+}
+
+/// Derive the recording key from an encryption key
+fn session_key(encryption_key: &[u8]) -> [u8; 32] {
+    derive_key_from_seed(&[encryption_key, SESSION_KEY_LABEL].concat()).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synchronicity_mask::{PositionData, VrmData};
+    use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+    
+    fn recording_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.qvsess", name, Pubkey::new_unique()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+    
+    #[test]
+    fn test_record_and_replay_session() {
+        let path = recording_path("session");
+        let key = [7u8; 32];
+        let signer = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        
+        let mut recorder = SessionRecorder::create(&path, &key, &nft_mint.to_string(), Some("viewer")).unwrap();
+        for sequence in 1..=3 {
+            let mut data = VrmData::new();
+            data.position = PositionData { x: sequence as f32, y: 0.0, z: 0.0 };
+            recorder.record(&VrmFrame::sign(&signer, &nft_mint, sequence, data)).unwrap();
+        }
+        drop(recorder);
+        
+        let recording = SessionRecording::open(&path, &key).unwrap();
+        assert_eq!(recording.header.viewer_id.as_deref(), Some("viewer"));
+        assert_eq!(recording.replay().map(|frame| frame.sequence).collect::<Vec<_>>(), vec![1, 2, 3]);
+        
+        let csv = recording.to_csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().nth(2).unwrap().starts_with("2,"));
+        assert!(recording.to_json().unwrap().contains("\"viewer\""));
+        
+        // Wrong key, and no overwriting an existing recording
+        assert!(SessionRecording::open(&path, &[8u8; 32]).is_err());
+        assert!(SessionRecorder::create(&path, &key, &nft_mint.to_string(), None).is_err());
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_truncated_recording_is_rejected() {
+        let path = recording_path("truncated");
+        let key = [7u8; 32];
+        let nft_mint = Pubkey::new_unique();
+        
+        let mut recorder = SessionRecorder::create(&path, &key, &nft_mint.to_string(), None).unwrap();
+        recorder.record(&VrmFrame::sign(&Keypair::new(), &nft_mint, 1, VrmData::new())).unwrap();
+        drop(recorder);
+        
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 5]).unwrap();
+        assert!(SessionRecording::open(&path, &key).is_err());
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,10 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// Resolves human-readable agent names to public keys
+///
+/// Implemented by the client against the on-chain agent registry, so the
+/// mask can compare viewers by key instead of by free-form string.
+pub trait AgentResolver: Send + Sync {
+    /// Resolve an agent name to its registered public key
+    fn resolve_agent(&self, name: &str) -> Result<Pubkey, String>;
+}
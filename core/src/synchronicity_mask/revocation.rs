@@ -0,0 +1,224 @@
+//! Signed revocation lists
+//!
+//! Renderers verify access tokens offline, so a revocation only reaches them
+//! if they are told. The owner, or a token delegate, periodically signs a
+//! `RevocationList`, a bloom filter of revoked token IDs, and publishes it on
+//! IPFS or any HTTP host. Renderers install the latest list with
+//! `SynchronicityMask::install_revocation_list`; once one is installed,
+//! tokens are refused if they are in it or if it has expired, so a renderer
+//! cut off from new lists stops honouring tokens instead of honouring
+//! revoked ones.
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use super::blacklist::TokenBlacklist;
+use super::token::{now, AccessToken};
+
+/// Domain separator for signed revocation lists
+const LIST_DOMAIN: &[u8] = b"quantum-veil/revocation-list/v1";
+
+/// Size of the fixed list fields (mint, issuer, sequence, issued, expiry)
+const HEADER_LEN: usize = 32 + 32 + 8 + 8 + 8;
+
+/// Default time a list stays valid, so owners republish daily
+pub const DEFAULT_LIST_VALIDITY_SECS: u64 = 24 * 60 * 60;
+
+/// Signed bloom filter of an NFT's revoked access tokens
+#[derive(Debug, Clone)]
+pub struct RevocationList {
+    /// NFT whose tokens the list revokes
+    pub nft_mint: Pubkey,
+    /// Owner or delegate that signed the list
+    pub issuer: Pubkey,
+    /// Publication number, increasing with every list, so older lists can't be replayed
+    pub sequence: u64,
+    /// Unix timestamp the list was signed at
+    pub issued_at: u64,
+    /// Unix timestamp after which the list, and tokens checked against it, are refused
+    pub expires_at: u64,
+    /// Revoked token IDs
+    pub blacklist: TokenBlacklist,
+    /// Ed25519 signature over the list fields
+    pub signature: Signature,
+}
+
+impl RevocationList {
+    /// Get the signed message: the domain separator followed by the list fields
+    fn message(&self) -> Vec<u8> {
+        let mut message = LIST_DOMAIN.to_vec();
+        message.extend_from_slice(self.nft_mint.as_ref());
+        message.extend_from_slice(self.issuer.as_ref());
+        message.extend_from_slice(&self.sequence.to_le_bytes());
+        message.extend_from_slice(&self.issued_at.to_le_bytes());
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message.extend(self.blacklist.to_bytes());
+        message
+    }
+    
+    /// Verify the signature and issuer
+    ///
+    /// Expiry is checked on each token lookup, not here.
+    pub fn verify(&self, trusted_issuers: &[Pubkey]) -> Result<(), String> {
+        if !trusted_issuers.contains(&self.issuer) {
+            return Err(format!("Untrusted revocation list issuer: {}", self.issuer));
+        }
+        
+        if !self.signature.verify(self.issuer.as_ref(), &self.message()) {
+            return Err("Invalid revocation list signature".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Check if the list is past its expiry
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= now()
+    }
+    
+    /// Check if a token may have been revoked
+    pub fn is_revoked(&self, token: &AccessToken) -> bool {
+        self.blacklist.is_revoked(token)
+    }
+    
+    /// Encode the list for publishing: the list fields followed by the signature
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.message().split_off(LIST_DOMAIN.len());
+        bytes.extend_from_slice(self.signature.as_ref());
+        bytes
+    }
+    
+    /// Decode a list from `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN + 64 {
+            return Err(format!("Invalid revocation list length: {}", bytes.len()));
+        }
+        
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(bytes[offset..offset + 32].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        let signature_at = bytes.len() - 64;
+        
+        Ok(Self {
+            nft_mint: pubkey_at(0),
+            issuer: pubkey_at(32),
+            sequence: u64_at(64),
+            issued_at: u64_at(72),
+            expires_at: u64_at(80),
+            blacklist: TokenBlacklist::from_bytes(&bytes[HEADER_LEN..signature_at])?,
+            signature: Signature::try_from(&bytes[signature_at..])
+                .map_err(|e| format!("Invalid revocation list signature: {}", e))?,
+        })
+    }
+}
+
+/// Owner-side builder for a revocation list
+pub struct RevocationListBuilder {
+    /// NFT whose tokens are revoked
+    nft_mint: Pubkey,
+    /// Publication number of the list
+    sequence: u64,
+    /// Seconds the list stays valid after signing
+    valid_for: u64,
+    /// Revoked token IDs so far
+    blacklist: TokenBlacklist,
+}
+
+impl RevocationListBuilder {
+    /// Start list number `sequence` for an NFT, sized for the default blacklist
+    ///
+    /// Each published list must carry a higher sequence than the last, and
+    /// must list every revoked token that hasn't expired yet.
+    pub fn new(nft_mint: &Pubkey, sequence: u64) -> Self {
+        Self {
+            nft_mint: *nft_mint,
+            sequence,
+            valid_for: DEFAULT_LIST_VALIDITY_SECS,
+            blacklist: TokenBlacklist::default(),
+        }
+    }
+    
+    /// Size the filter for `expected_revocations` at a false positive rate
+    ///
+    /// Resets the filter, so call it before revoking.
+    pub fn with_capacity(mut self, expected_revocations: usize, false_positive_rate: f64) -> Self {
+        self.blacklist = TokenBlacklist::new(expected_revocations, false_positive_rate);
+        self
+    }
+    
+    /// Keep the list valid for `valid_for` seconds after signing
+    pub fn with_validity(mut self, valid_for: u64) -> Self {
+        self.valid_for = valid_for;
+        self
+    }
+    
+    /// Revoke a token
+    pub fn revoke(mut self, token: &AccessToken) -> Self {
+        self.blacklist.revoke(token);
+        self
+    }
+    
+    /// Revoke a token by its ID
+    pub fn revoke_id(mut self, id: &[u8; 32]) -> Self {
+        self.blacklist.revoke_id(id);
+        self
+    }
+    
+    /// Sign the list as the owner or a token delegate
+    pub fn sign(self, issuer: &Keypair) -> RevocationList {
+        let issued_at = now();
+        let mut list = RevocationList {
+            nft_mint: self.nft_mint,
+            issuer: issuer.pubkey(),
+            sequence: self.sequence,
+            issued_at,
+            expires_at: issued_at + self.valid_for,
+            blacklist: self.blacklist,
+            signature: Signature::default(),
+        };
+        list.signature = issuer.sign_message(&list.message());
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synchronicity_mask::{PrivacyLevel, SynchronicityMask, VrmData};
+    
+    #[test]
+    fn test_installed_lists_revoke_tokens_offline() {
+        let mut mask = SynchronicityMask::offline();
+        let owner = Keypair::new();
+        let nft_mint = Pubkey::new_unique();
+        mask.create_config(&nft_mint, &owner.pubkey(), PrivacyLevel::Medium);
+        let mint = nft_mint.to_string();
+        
+        let revoked = AccessToken::issue(&owner, &Pubkey::new_unique(), &nft_mint, 2, 60);
+        let valid = AccessToken::issue(&owner, &Pubkey::new_unique(), &nft_mint, 2, 60);
+        
+        let list = RevocationListBuilder::new(&nft_mint, 1)
+            .with_capacity(10, 0.001)
+            .revoke_id(&revoked.id())
+            .sign(&owner);
+        let list = RevocationList::from_bytes(&list.to_bytes()).unwrap();
+        mask.install_revocation_list(list.clone()).unwrap();
+        
+        assert!(mask.apply_mask_with_token(&mint, &VrmData::new(), &revoked).is_err());
+        assert!(mask.apply_mask_with_token(&mint, &VrmData::new(), &valid).is_ok());
+        
+        // Replayed, forged, and tampered lists are refused
+        assert!(mask.install_revocation_list(list.clone()).is_err());
+        let forged = RevocationListBuilder::new(&nft_mint, 2).sign(&Keypair::new());
+        assert!(mask.install_revocation_list(forged).is_err());
+        let mut tampered = RevocationListBuilder::new(&nft_mint, 2).revoke(&valid).sign(&owner);
+        tampered.blacklist = TokenBlacklist::default();
+        assert!(mask.install_revocation_list(tampered).is_err());
+        
+        // An expired list fails closed
+        let expired = RevocationListBuilder::new(&nft_mint, 3).with_validity(0).sign(&owner);
+        mask.install_revocation_list(expired).unwrap();
+        assert!(mask.apply_mask_with_token(&mint, &VrmData::new(), &valid).unwrap_err().contains("expired"));
+    }
+}
@@ -0,0 +1,173 @@
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+};
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_512};
+use zeroize::Zeroize;
+
+use super::access_tokens::{canonical_scalar, decompress, random_scalar, wide_hash};
+
+/// Domain separator for the linking base point
+const LINK_DOMAIN: &[u8] = b"quantum-veil/ring/link/v1";
+
+/// Domain separator for ring challenges
+const CHALLENGE_DOMAIN: &[u8] = b"quantum-veil/ring/challenge/v1";
+
+/// Member key for signing on behalf of a group
+pub struct RingKey {
+    /// Secret scalar
+    secret: Scalar,
+}
+
+impl RingKey {
+    /// Generate a fresh member key
+    pub fn generate() -> Self {
+        Self { secret: random_scalar() }
+    }
+    
+    /// Get the public key registered in group member lists
+    pub fn public_key(&self) -> [u8; 32] {
+        (self.secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes()
+    }
+    
+    /// Sign a message as some member of `ring`, without revealing which
+    ///
+    /// Signatures by the same key under the same `context` carry the same
+    /// key image, so a verifier can tell a member signed twice, e.g. for one
+    /// token per member per epoch, without learning who they are.
+    pub fn sign(&self, ring: &[[u8; 32]], context: &[u8], message: &[u8]) -> Result<RingSignature, String> {
+        let public_key = self.public_key();
+        let signer = ring.iter()
+            .position(|member| *member == public_key)
+            .ok_or_else(|| "Signing key is not in the ring".to_string())?;
+        let points = decompress_ring(ring)?;
+        
+        let link_base = link_base(context);
+        let key_image = self.secret * link_base;
+        let transcript = Transcript::new(ring, &key_image, context, message);
+        
+        // Close the ring at the signer: start from a random commitment and go around
+        let n = ring.len();
+        let mut challenges = vec![Scalar::zero(); n];
+        let mut responses = vec![Scalar::zero(); n];
+        let mut nonce = random_scalar();
+        challenges[(signer + 1) % n] = transcript.challenge(
+            &(nonce * RISTRETTO_BASEPOINT_POINT),
+            &(nonce * link_base),
+        );
+        
+        let mut i = (signer + 1) % n;
+        while i != signer {
+            responses[i] = random_scalar();
+            challenges[(i + 1) % n] = transcript.challenge(
+                &(responses[i] * RISTRETTO_BASEPOINT_POINT + challenges[i] * points[i]),
+                &(responses[i] * link_base + challenges[i] * key_image),
+            );
+            i = (i + 1) % n;
+        }
+        responses[signer] = nonce - challenges[signer] * self.secret;
+        nonce.zeroize();
+        
+        Ok(RingSignature {
+            challenge: challenges[0].to_bytes(),
+            responses: responses.iter().map(Scalar::to_bytes).collect(),
+            key_image: key_image.compress().to_bytes(),
+        })
+    }
+}
+
+impl Drop for RingKey {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Linkable ring signature (LSAG) proving a group member signed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingSignature {
+    /// Challenge at the first ring position
+    pub challenge: [u8; 32],
+    /// Response at each ring position
+    pub responses: Vec<[u8; 32]>,
+    /// Image of the signing key under the context, the same for every signature by that key
+    pub key_image: [u8; 32],
+}
+
+impl RingSignature {
+    /// Verify the signature was made by a member of `ring` under `context`
+    pub fn verify(&self, ring: &[[u8; 32]], context: &[u8], message: &[u8]) -> Result<(), String> {
+        if ring.is_empty() || self.responses.len() != ring.len() {
+            return Err("Ring signature does not match the ring size".to_string());
+        }
+        
+        let points = decompress_ring(ring)?;
+        let key_image = decompress(&self.key_image)?;
+        let start = canonical_scalar(&self.challenge)?;
+        
+        let link_base = link_base(context);
+        let transcript = Transcript::new(ring, &key_image, context, message);
+        
+        let mut challenge = start;
+        for (point, response) in points.iter().zip(&self.responses) {
+            let response = canonical_scalar(response)?;
+            challenge = transcript.challenge(
+                &(response * RISTRETTO_BASEPOINT_POINT + challenge * point),
+                &(response * link_base + challenge * key_image),
+            );
+        }
+        
+        if challenge != start {
+            return Err("Invalid ring signature".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Everything a ring challenge commits to besides the round's points
+struct Transcript {
+    /// Hasher primed with the ring, key image, context, and message
+    hasher: Sha3_512,
+}
+
+impl Transcript {
+    /// Prime a transcript
+    fn new(ring: &[[u8; 32]], key_image: &RistrettoPoint, context: &[u8], message: &[u8]) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(CHALLENGE_DOMAIN);
+        hasher.update((ring.len() as u32).to_le_bytes());
+        for member in ring {
+            hasher.update(member);
+        }
+        hasher.update(key_image.compress().as_bytes());
+        for field in [context, message] {
+            hasher.update((field.len() as u32).to_le_bytes());
+            hasher.update(field);
+        }
+        
+        Self { hasher }
+    }
+    
+    /// Derive the next challenge from a round's commitments
+    fn challenge(&self, base_commitment: &RistrettoPoint, link_commitment: &RistrettoPoint) -> Scalar {
+        let mut hasher = self.hasher.clone();
+        hasher.update(base_commitment.compress().as_bytes());
+        hasher.update(link_commitment.compress().as_bytes());
+        Scalar::from_bytes_mod_order_wide(&wide_hash(hasher))
+    }
+}
+
+/// Hash a context to the base point key images are taken over
+fn link_base(context: &[u8]) -> RistrettoPoint {
+    let mut hasher = Sha3_512::new();
+    hasher.update(LINK_DOMAIN);
+    hasher.update(context);
+    RistrettoPoint::from_uniform_bytes(&wide_hash(hasher))
+}
+
+/// Decode every member key of a ring
+fn decompress_ring(ring: &[[u8; 32]]) -> Result<Vec<RistrettoPoint>, String> {
+    ring.iter().map(decompress).collect()
+}
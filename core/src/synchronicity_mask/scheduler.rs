@@ -0,0 +1,249 @@
+//! Frame-rate-aware masking
+//!
+//! A 90 Hz VR client doesn't need fresh noise every frame. The scheduler
+//! runs the full mask at a lower rate and, on the frames in between, moves
+//! the raw position and rotation by the noise offsets of the last two mask
+//! computations, blending from one to the other over the interval. Streams
+//! that are replaced rather than noised, and every other data type, hold
+//! their last masked values, so raw data never shows through.
+
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::gpu::{MODE_REPLACE, STREAM_POSITION, STREAM_ROTATION};
+use super::vrm_data::{PositionData, RotationData, VrmData, VrmDataType};
+use super::SynchronicityMask;
+
+/// How a data type is produced between mask computations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Raw data moved by blended noise offsets
+    Blended,
+    /// Last masked value held until the next computation
+    Held,
+}
+
+/// Interpolation state of one data type for one viewer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterpolationState {
+    /// Data type the state is for
+    pub data_type: VrmDataType,
+    /// How frames between computations are produced
+    pub interpolation: Interpolation,
+    /// How far the blend has moved towards the latest offset, from 0.0 to 1.0
+    pub progress: f32,
+    /// Time since the mask was last computed
+    pub since_computed: Duration,
+}
+
+/// Noise offsets and held output of one viewer
+#[derive(Debug, Clone)]
+struct ViewerState {
+    /// When the mask was last computed
+    computed_at: Instant,
+    /// Masked output of the last computation
+    masked: VrmData,
+    /// Position offsets blended from and towards
+    position: ([f32; 3], [f32; 3]),
+    /// Rotation offsets blended from and towards
+    rotation: ([f32; 4], [f32; 4]),
+    /// Whether position and rotation are blended or held
+    blended: [bool; 2],
+}
+
+/// Coalesces mask computations across frames
+///
+/// Keyed by NFT and viewer. Call `mask_frame` once per rendered frame.
+#[derive(Debug)]
+pub struct MaskingScheduler {
+    /// Time between mask computations
+    interval: Duration,
+    /// State by (mint, viewer)
+    viewers: HashMap<(String, String), ViewerState>,
+}
+
+impl MaskingScheduler {
+    /// Create a scheduler recomputing masks `recompute_hz` times a second
+    pub fn new(recompute_hz: f32) -> Self {
+        Self {
+            interval: Duration::from_secs_f32(1.0 / recompute_hz.max(0.01)),
+            viewers: HashMap::new(),
+        }
+    }
+    
+    /// Get the time between mask computations
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+    
+    /// Mask a frame for a viewer, computing the mask only when it is due
+    pub fn mask_frame(
+        &mut self,
+        mask: &SynchronicityMask,
+        nft_mint: &str,
+        vrm_data: &VrmData,
+        viewer_id: Option<&str>,
+        now: Instant,
+    ) -> Result<VrmData, String> {
+        let key = (nft_mint.to_string(), viewer_id.unwrap_or_default().to_string());
+        
+        let due = self.viewers.get(&key)
+            .map_or(true, |state| now.duration_since(state.computed_at) >= self.interval);
+        if due {
+            let masked = mask.apply_mask(nft_mint, vrm_data, viewer_id)?;
+            let params = mask.export_mask_params(nft_mint, viewer_id, None)?;
+            
+            let position = position_offset(&vrm_data.position, &masked.position);
+            let rotation = rotation_offset(&vrm_data.rotation, &masked.rotation);
+            let previous = self.viewers.get(&key);
+            let state = ViewerState {
+                computed_at: now,
+                position: (previous.map_or(position, |state| state.position.1), position),
+                rotation: (previous.map_or(rotation, |state| state.rotation.1), rotation),
+                blended: [STREAM_POSITION, STREAM_ROTATION].map(|stream| params.mode(stream) != MODE_REPLACE),
+                masked: masked.clone(),
+            };
+            self.viewers.insert(key, state);
+            return Ok(masked);
+        }
+        
+        let state = &self.viewers[&key];
+        let t = self.progress(state, now);
+        let mut frame = state.masked.clone();
+        
+        if state.blended[0] {
+            let (from, to) = state.position;
+            let [x, y, z] = [0, 1, 2].map(|i| from[i] + (to[i] - from[i]) * t);
+            frame.position = PositionData {
+                x: vrm_data.position.x + x,
+                y: vrm_data.position.y + y,
+                z: vrm_data.position.z + z,
+            };
+        }
+        if state.blended[1] {
+            let (from, to) = state.rotation;
+            let offset = nlerp(from, to, t);
+            let raw = &vrm_data.rotation;
+            let [x, y, z, w] = multiply(offset, [raw.x, raw.y, raw.z, raw.w]);
+            frame.rotation = RotationData { x, y, z, w };
+        }
+        
+        Ok(frame)
+    }
+    
+    /// Get how a viewer's data type is interpolated at `now`, if the viewer has been masked
+    pub fn interpolation_state(
+        &self,
+        nft_mint: &str,
+        viewer_id: Option<&str>,
+        data_type: VrmDataType,
+        now: Instant,
+    ) -> Option<InterpolationState> {
+        let state = self.viewers.get(&(nft_mint.to_string(), viewer_id.unwrap_or_default().to_string()))?;
+        
+        let blended = match data_type {
+            VrmDataType::Position => state.blended[0],
+            VrmDataType::Rotation => state.blended[1],
+            _ => false,
+        };
+        
+        Some(InterpolationState {
+            data_type,
+            interpolation: if blended { Interpolation::Blended } else { Interpolation::Held },
+            progress: self.progress(state, now),
+            since_computed: now.duration_since(state.computed_at),
+        })
+    }
+    
+    /// Forget viewers not masked for `idle`, returning how many were dropped
+    pub fn evict_idle(&mut self, now: Instant, idle: Duration) -> usize {
+        let before = self.viewers.len();
+        self.viewers.retain(|_, state| now.duration_since(state.computed_at) < idle);
+        before - self.viewers.len()
+    }
+    
+    /// Get how far a viewer's blend has moved towards the latest offset
+    fn progress(&self, state: &ViewerState, now: Instant) -> f32 {
+        (now.duration_since(state.computed_at).as_secs_f32() / self.interval.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Get the offset moving a raw position to its masked value
+fn position_offset(raw: &PositionData, masked: &PositionData) -> [f32; 3] {
+    [masked.x - raw.x, masked.y - raw.y, masked.z - raw.z]
+}
+
+/// Get the rotation taking a raw rotation to its masked value
+fn rotation_offset(raw: &RotationData, masked: &RotationData) -> [f32; 4] {
+    // The conjugate inverts a unit quaternion
+    multiply([masked.x, masked.y, masked.z, masked.w], [-raw.x, -raw.y, -raw.z, raw.w])
+}
+
+/// Multiply `[x, y, z, w]` quaternions, applying `b` first
+fn multiply(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+/// Blend unit quaternions along the shorter arc and renormalize
+fn nlerp(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let dot: f32 = from.iter().zip(&to).map(|(a, b)| a * b).sum();
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+    
+    let blended: [f32; 4] = std::array::from_fn(|i| from[i] + (to[i] * sign - from[i]) * t);
+    let mag = blended.iter().map(|c| c * c).sum::<f32>().sqrt();
+    blended.map(|c| c / mag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use crate::synchronicity_mask::PrivacyLevel;
+    
+    fn at(x: f32) -> VrmData {
+        let mut data = VrmData::new();
+        data.position = PositionData { x, y: 1.0, z: 0.0 };
+        data
+    }
+    
+    #[test]
+    fn test_scheduler_blends_between_computations() {
+        let mut mask = SynchronicityMask::offline();
+        let mint = Pubkey::new_unique();
+        mask.create_config(&mint, &Pubkey::new_unique(), PrivacyLevel::Medium);
+        let nft_mint = mint.to_string();
+        
+        let mut scheduler = MaskingScheduler::new(30.0);
+        let start = Instant::now();
+        let first = scheduler.mask_frame(&mask, &nft_mint, &at(0.0), Some("viewer"), start).unwrap();
+        assert_eq!(first.position.x, mask.apply_mask(&nft_mint, &at(0.0), Some("viewer")).unwrap().position.x);
+        
+        // Between computations the raw motion shows through under the same noise offset
+        let later = start + Duration::from_millis(11);
+        let moved = scheduler.mask_frame(&mask, &nft_mint, &at(2.0), Some("viewer"), later).unwrap();
+        assert!((moved.position.x - (first.position.x + 2.0)).abs() < 1e-4);
+        
+        let state = scheduler.interpolation_state(&nft_mint, Some("viewer"), VrmDataType::Position, later).unwrap();
+        assert_eq!(state.interpolation, Interpolation::Blended);
+        assert!(state.progress > 0.0 && state.progress < 1.0);
+        let voice = scheduler.interpolation_state(&nft_mint, Some("viewer"), VrmDataType::Voice, later).unwrap();
+        assert_eq!(voice.interpolation, Interpolation::Held);
+        
+        // Completely masked positions are held, never blended over raw data
+        mask.update_privacy_setting(&nft_mint, VrmDataType::Position, PrivacyLevel::Complete).unwrap();
+        let recomputed = scheduler.mask_frame(&mask, &nft_mint, &at(0.0), Some("viewer"), start + scheduler.interval()).unwrap();
+        let held = scheduler.mask_frame(&mask, &nft_mint, &at(50.0), Some("viewer"), start + scheduler.interval() + Duration::from_millis(5)).unwrap();
+        assert_eq!(held.position.x, recomputed.position.x);
+        
+        assert_eq!(scheduler.evict_idle(start + Duration::from_secs(60), Duration::from_secs(30)), 1);
+        assert!(scheduler.interpolation_state(&nft_mint, Some("viewer"), VrmDataType::Position, start).is_none());
+    }
+}
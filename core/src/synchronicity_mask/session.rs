@@ -0,0 +1,18 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+
+use super::limiter::QueryState;
+
+/// Mask state kept for one viewer of one NFT
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewerSession {
+    /// Query budget state, once the viewer has been limited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) query: Option<QueryState>,
+    /// Pseudonym the viewer was last shown the avatar under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pseudonym: Option<String>,
+    /// Reasons the viewer was shown unmasked data that are already audited
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub disclosed: BTreeSet<String>,
+}
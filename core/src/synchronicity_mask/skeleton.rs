@@ -0,0 +1,195 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use super::vrm_data::{GestureData, RotationData};
+
+/// Slack allowed when checking poses, in degrees, for float round-off
+const ANGLE_TOLERANCE: f32 = 0.5;
+
+/// Rotation limits of one joint, as X/Y/Z Euler angles in degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct JointLimits {
+    /// Lowest angle around each axis
+    pub min: [f32; 3],
+    /// Highest angle around each axis
+    pub max: [f32; 3],
+}
+
+impl JointLimits {
+    /// Create limits from per-axis `(min, max)` ranges
+    pub const fn new(x: (f32, f32), y: (f32, f32), z: (f32, f32)) -> Self {
+        Self {
+            min: [x.0, y.0, z.0],
+            max: [x.1, y.1, z.1],
+        }
+    }
+}
+
+/// Per-joint rotation limits for a humanoid skeleton, keyed by VRM bone name
+///
+/// Joints without limits are left unconstrained.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BoneProfile {
+    /// Limits by bone name, e.g. `leftLowerArm`
+    pub joints: HashMap<String, JointLimits>,
+}
+
+impl BoneProfile {
+    /// Approximate limits of the human body for the standard VRM humanoid bones
+    pub fn humanoid() -> Self {
+        let mut joints = HashMap::from([
+            ("hips".to_string(), JointLimits::new((-180.0, 180.0), (-180.0, 180.0), (-180.0, 180.0))),
+            ("spine".to_string(), JointLimits::new((-30.0, 45.0), (-30.0, 30.0), (-25.0, 25.0))),
+            ("chest".to_string(), JointLimits::new((-20.0, 30.0), (-25.0, 25.0), (-20.0, 20.0))),
+            ("neck".to_string(), JointLimits::new((-40.0, 50.0), (-60.0, 60.0), (-30.0, 30.0))),
+            ("head".to_string(), JointLimits::new((-40.0, 40.0), (-70.0, 70.0), (-35.0, 35.0))),
+        ]);
+        
+        // Mirrored limbs flip the sign of the Y and Z ranges
+        for (side, sign) in [("left", 1.0f32), ("right", -1.0f32)] {
+            let mirror = |limits: JointLimits| {
+                let axis = |i: usize| {
+                    let (a, b) = (limits.min[i] * sign, limits.max[i] * sign);
+                    (a.min(b), a.max(b))
+                };
+                JointLimits::new((limits.min[0], limits.max[0]), axis(1), axis(2))
+            };
+            
+            for (bone, limits) in [
+                ("UpperArm", JointLimits::new((-90.0, 90.0), (-100.0, 40.0), (-90.0, 80.0))),
+                ("LowerArm", JointLimits::new((-10.0, 10.0), (-150.0, 0.0), (-90.0, 90.0))),
+                ("Hand", JointLimits::new((-70.0, 70.0), (-30.0, 30.0), (-80.0, 80.0))),
+                ("UpperLeg", JointLimits::new((-120.0, 45.0), (-45.0, 45.0), (-30.0, 60.0))),
+                ("LowerLeg", JointLimits::new((0.0, 150.0), (-5.0, 5.0), (-5.0, 5.0))),
+                ("Foot", JointLimits::new((-45.0, 45.0), (-20.0, 20.0), (-30.0, 30.0))),
+            ] {
+                joints.insert(format!("{}{}", side, bone), mirror(limits));
+            }
+        }
+        
+        Self { joints }
+    }
+    
+    /// Load a profile from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse bone profile: {}", e))
+    }
+    
+    /// Clamp a joint rotation into its limits
+    pub fn clamp_joint(&self, joint: &str, rotation: &mut RotationData) {
+        if let Some(limits) = self.joints.get(joint) {
+            let angles = to_euler_degrees(rotation);
+            let clamped = [0, 1, 2].map(|i| angles[i].clamp(limits.min[i], limits.max[i]));
+            
+            if clamped != angles {
+                *rotation = from_euler_degrees(clamped);
+            }
+        }
+    }
+    
+    /// Clamp every joint of a gesture into its limits
+    pub fn clamp_gesture(&self, gesture: &mut GestureData) {
+        for (joint, rotation) in gesture.joint_rotations.iter_mut() {
+            self.clamp_joint(joint, rotation);
+        }
+    }
+    
+    /// Check that every joint of a gesture is within its limits
+    pub fn validate_pose(&self, gesture: &GestureData) -> Result<(), String> {
+        let mut violations: Vec<String> = gesture.joint_rotations.iter()
+            .filter_map(|(joint, rotation)| {
+                let limits = self.joints.get(joint)?;
+                let angles = to_euler_degrees(rotation);
+                
+                (0..3)
+                    .find(|&i| {
+                        angles[i] < limits.min[i] - ANGLE_TOLERANCE || angles[i] > limits.max[i] + ANGLE_TOLERANCE
+                    })
+                    .map(|i| format!("{} {} at {:.1} degrees", joint, ["x", "y", "z"][i], angles[i]))
+            })
+            .collect();
+        
+        if violations.is_empty() {
+            return Ok(());
+        }
+        
+        violations.sort();
+        Err(format!("Impossible pose in {}: {}", gesture.name, violations.join(", ")))
+    }
+}
+
+/// Convert a quaternion to X/Y/Z Euler angles in degrees
+pub(super) fn to_euler_degrees(rotation: &RotationData) -> [f32; 3] {
+    let (x, y, z, w) = (rotation.x, rotation.y, rotation.z, rotation.w);
+    
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    
+    [roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()]
+}
+
+/// Convert X/Y/Z Euler angles in degrees to a quaternion
+pub(super) fn from_euler_degrees(angles: [f32; 3]) -> RotationData {
+    let [roll, pitch, yaw] = angles.map(|angle| angle.to_radians() / 2.0);
+    let (sr, cr) = roll.sin_cos();
+    let (sp, cp) = pitch.sin_cos();
+    let (sy, cy) = yaw.sin_cos();
+    
+    RotationData {
+        x: sr * cp * cy - cr * sp * sy,
+        y: cr * sp * cy + sr * cp * sy,
+        z: cr * cp * sy - sr * sp * cy,
+        w: cr * cp * cy + sr * sp * sy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn gesture_with(joint: &str, angles: [f32; 3]) -> GestureData {
+        GestureData {
+            name: "wave".to_string(),
+            intensity: 0.5,
+            speed: 1.0,
+            joint_rotations: HashMap::from([(joint.to_string(), from_euler_degrees(angles))]),
+        }
+    }
+    
+    #[test]
+    fn test_euler_round_trip() {
+        let angles = [30.0, -20.0, 45.0];
+        let round_trip = to_euler_degrees(&from_euler_degrees(angles));
+        
+        for i in 0..3 {
+            assert!((round_trip[i] - angles[i]).abs() < 0.01);
+        }
+    }
+    
+    #[test]
+    fn test_clamping_fixes_impossible_poses() {
+        let profile = BoneProfile::humanoid();
+        
+        // A knee bent backwards
+        let mut gesture = gesture_with("leftLowerLeg", [-60.0, 0.0, 0.0]);
+        assert!(profile.validate_pose(&gesture).unwrap_err().contains("leftLowerLeg x"));
+        
+        profile.clamp_gesture(&mut gesture);
+        assert!(profile.validate_pose(&gesture).is_ok());
+        
+        // Elbows bend in opposite directions on each side
+        assert!(profile.validate_pose(&gesture_with("leftLowerArm", [0.0, -60.0, 0.0])).is_ok());
+        assert!(profile.validate_pose(&gesture_with("rightLowerArm", [0.0, -60.0, 0.0])).is_err());
+        
+        // Unknown joints are unconstrained
+        assert!(profile.validate_pose(&gesture_with("tail", [170.0, 0.0, 0.0])).is_ok());
+    }
+    
+    #[test]
+    fn test_profile_loads_from_json() {
+        let profile = BoneProfile::from_json(r#"{"joints": {"head": {"min": [-10, -10, -10], "max": [10, 10, 10]}}}"#).unwrap();
+        assert!(profile.validate_pose(&gesture_with("head", [20.0, 0.0, 0.0])).is_err());
+        assert!(BoneProfile::from_json("not json").is_err());
+    }
+}
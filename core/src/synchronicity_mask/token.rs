@@ -0,0 +1,143 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use solana_sdk::{
+    hash::hashv,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+/// Domain separator for signed token messages
+const TOKEN_DOMAIN: &[u8] = b"quantum-veil/access-token/v1";
+
+/// Size of the signed token fields (viewer, mint, level, expiry, nonce, issuer)
+const MESSAGE_LEN: usize = 32 + 32 + 1 + 8 + 8 + 32;
+
+/// Size of an encoded token
+pub const TOKEN_LEN: usize = MESSAGE_LEN + 64;
+
+/// Short-lived, offline-verifiable proof that a viewer holds an access level
+///
+/// Issued and signed by the NFT owner or a delegate service, so renderers
+/// can check access every frame without hitting the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    /// Viewer the token was issued to
+    pub viewer: Pubkey,
+    /// NFT the token grants access to
+    pub nft_mint: Pubkey,
+    /// Access level granted
+    pub level: u8,
+    /// Expiry as a Unix timestamp
+    pub expires_at: u64,
+    /// Random nonce so identical grants get distinct token IDs
+    pub nonce: u64,
+    /// Owner or delegate that signed the token
+    pub issuer: Pubkey,
+    /// Ed25519 signature over the token fields
+    pub signature: Signature,
+}
+
+impl AccessToken {
+    /// Issue a token valid for `ttl_secs` from now
+    pub fn issue(
+        issuer: &Keypair,
+        viewer: &Pubkey,
+        nft_mint: &Pubkey,
+        level: u8,
+        ttl_secs: u64,
+    ) -> Self {
+        let mut token = Self {
+            viewer: *viewer,
+            nft_mint: *nft_mint,
+            level,
+            expires_at: now() + ttl_secs,
+            nonce: rand::random(),
+            issuer: issuer.pubkey(),
+            signature: Signature::default(),
+        };
+        token.signature = issuer.sign_message(&token.message());
+        token
+    }
+    
+    /// Get the signed message: the domain separator followed by the token fields
+    fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(TOKEN_DOMAIN.len() + MESSAGE_LEN);
+        message.extend_from_slice(TOKEN_DOMAIN);
+        message.extend_from_slice(self.viewer.as_ref());
+        message.extend_from_slice(self.nft_mint.as_ref());
+        message.push(self.level);
+        message.extend_from_slice(&self.expires_at.to_le_bytes());
+        message.extend_from_slice(&self.nonce.to_le_bytes());
+        message.extend_from_slice(self.issuer.as_ref());
+        message
+    }
+    
+    /// Get the token ID used for revocation
+    pub fn id(&self) -> [u8; 32] {
+        hashv(&[&self.message(), self.signature.as_ref()]).to_bytes()
+    }
+    
+    /// Verify the signature, issuer, and expiry
+    pub fn verify(&self, trusted_issuers: &[Pubkey]) -> Result<(), String> {
+        if !trusted_issuers.contains(&self.issuer) {
+            return Err(format!("Untrusted token issuer: {}", self.issuer));
+        }
+        
+        if !self.signature.verify(self.issuer.as_ref(), &self.message()) {
+            return Err("Invalid token signature".to_string());
+        }
+        
+        if self.expires_at <= now() {
+            return Err("Access token expired".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Encode the token in its compact binary form
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.message().split_off(TOKEN_DOMAIN.len());
+        bytes.extend_from_slice(self.signature.as_ref());
+        bytes
+    }
+    
+    /// Decode a token from its compact binary form
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != TOKEN_LEN {
+            return Err(format!("Invalid access token length: {}", bytes.len()));
+        }
+        
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(bytes[offset..offset + 32].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        
+        Ok(Self {
+            viewer: pubkey_at(0),
+            nft_mint: pubkey_at(32),
+            level: bytes[64],
+            expires_at: u64_at(65),
+            nonce: u64_at(73),
+            issuer: pubkey_at(81),
+            signature: Signature::try_from(&bytes[MESSAGE_LEN..])
+                .map_err(|e| format!("Invalid token signature: {}", e))?,
+        })
+    }
+    
+    /// Encode the token as URL-safe base64 for headers and query strings
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.to_bytes())
+    }
+    
+    /// Decode a token from URL-safe base64
+    pub fn decode(encoded: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded)
+            .map_err(|e| format!("Failed to decode access token: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Get the current Unix timestamp
+pub(super) fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
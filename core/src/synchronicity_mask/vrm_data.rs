@@ -46,6 +46,26 @@ pub struct RotationData {
     pub w: f32,
 }
 
+/// VRM scale data, 1.0 on every axis at the model's natural size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleData {
+    /// X scale factor
+    pub x: f32,
+    /// Y scale factor
+    pub y: f32,
+    /// Z scale factor
+    pub z: f32,
+}
+
+/// Axis-aligned bounding box around the avatar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundingBox {
+    /// Minimum corner
+    pub min: PositionData,
+    /// Maximum corner
+    pub max: PositionData,
+}
+
 /// VRM voice data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceData {
@@ -72,6 +92,36 @@ pub struct GestureData {
     pub joint_rotations: HashMap<String, RotationData>,
 }
 
+/// Kind of interaction between an avatar and a target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InteractionKind {
+    /// Looking at the target
+    Gaze,
+    /// Touching the target
+    Touch,
+    /// Speaking to the target
+    Speak,
+    /// Gesturing at the target
+    Gesture,
+    /// Exchanging items with the target
+    Trade,
+    /// Custom interaction kind
+    Custom(String),
+}
+
+/// Interaction of an avatar with another avatar or object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionEvent {
+    /// ID of the avatar or object interacted with
+    pub target_id: String,
+    /// Kind of interaction
+    pub kind: InteractionKind,
+    /// Interaction intensity (0.0 - 1.0)
+    pub intensity: f32,
+    /// Time of the interaction as a Unix timestamp in milliseconds
+    pub timestamp: u64,
+}
+
 /// Combined VRM data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VrmData {
@@ -79,10 +129,19 @@ pub struct VrmData {
     pub position: PositionData,
     /// Rotation (quaternion)
     pub rotation: RotationData,
+    /// Scale (optional)
+    #[serde(default)]
+    pub scale: Option<ScaleData>,
+    /// Bounding box (optional)
+    #[serde(default)]
+    pub bounding_box: Option<BoundingBox>,
     /// Voice data (optional)
     pub voice: Option<VoiceData>,
     /// Gesture animations
     pub gestures: Vec<GestureData>,
+    /// Interaction events since the last frame
+    #[serde(default)]
+    pub interactions: Vec<InteractionEvent>,
     /// Animation parameters
     pub animations: HashMap<String, f32>,
     /// Custom data
@@ -95,8 +154,11 @@ impl VrmData {
         Self {
             position: PositionData { x: 0.0, y: 0.0, z: 0.0 },
             rotation: RotationData { x: 0.0, y: 0.0, z: 0.0, w: 1.0 },
+            scale: None,
+            bounding_box: None,
             voice: None,
             gestures: Vec::new(),
+            interactions: Vec::new(),
             animations: HashMap::new(),
             custom_data: HashMap::new(),
         }
@@ -116,6 +178,11 @@ impl VrmData {
         self.gestures.push(gesture);
     }
     
+    /// Add an interaction event
+    pub fn add_interaction(&mut self, interaction: InteractionEvent) {
+        self.interactions.push(interaction);
+    }
+    
     /// Set animation parameter
     pub fn set_animation(&mut self, name: &str, value: f32) {
         self.animations.insert(name.to_string(), value);
@@ -0,0 +1,91 @@
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, Encoder, HistogramVec, IntCounterVec, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Operation counters, labelled by operation and outcome
+fn operations() -> &'static IntCounterVec {
+    static OPERATIONS: OnceLock<IntCounterVec> = OnceLock::new();
+    OPERATIONS.get_or_init(|| {
+        register_int_counter_vec!(
+            "quantum_veil_operations_total",
+            "Operations performed, by outcome",
+            &["operation", "status"]
+        ).unwrap()
+    })
+}
+
+/// Operation latency histograms, labelled by operation
+fn latencies() -> &'static HistogramVec {
+    static LATENCIES: OnceLock<HistogramVec> = OnceLock::new();
+    LATENCIES.get_or_init(|| {
+        register_histogram_vec!(
+            "quantum_veil_operation_duration_seconds",
+            "Operation latency in seconds",
+            &["operation"]
+        ).unwrap()
+    })
+}
+
+/// Record the outcome and latency of an operation
+pub(super) fn record(operation: &str, elapsed: Duration, success: bool) {
+    let status = if success { "ok" } else { "error" };
+    operations().with_label_values(&[operation, status]).inc();
+    latencies().with_label_values(&[operation]).observe(elapsed.as_secs_f64());
+}
+
+/// Render all registered metrics in the Prometheus text format
+pub fn gather() -> String {
+    let mut buffer = Vec::new();
+    let encoder = TextEncoder::new();
+    encoder.encode(&prometheus::gather(), &mut buffer).unwrap_or_default();
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Serve metrics for Prometheus to scrape
+///
+/// Answers every request on `addr` with the current metrics, so it can be
+/// spawned alongside a service without pulling in an HTTP framework.
+pub async fn serve(addr: SocketAddr) -> Result<(), String> {
+    let listener = TcpListener::bind(addr).await
+        .map_err(|e| format!("Failed to bind metrics exporter: {}", e))?;
+    
+    loop {
+        let (mut stream, _) = listener.accept().await
+            .map_err(|e| format!("Failed to accept metrics connection: {}", e))?;
+        
+        tokio::spawn(async move {
+            // The request itself is irrelevant; drain what the client sent
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            
+            let body = gather();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_operations_are_exported() {
+        record("test.exported", Duration::from_millis(5), true);
+        record("test.exported", Duration::from_millis(5), false);
+        
+        let metrics = gather();
+        assert!(metrics.contains(r#"quantum_veil_operations_total{operation="test.exported",status="ok"} 1"#));
+        assert!(metrics.contains(r#"quantum_veil_operations_total{operation="test.exported",status="error"} 1"#));
+        assert!(metrics.contains(r#"quantum_veil_operation_duration_seconds_count{operation="test.exported"} 2"#));
+    }
+}
@@ -0,0 +1,67 @@
+//! Tracing spans and operation metrics
+//!
+//! Operations are wrapped in `tracing` spans. With the `prometheus` feature
+//! each operation also updates a call counter (labelled by outcome, so error
+//! rates can be derived) and a latency histogram; without it, recording
+//! metrics is a no-op.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+#[cfg(feature = "prometheus")]
+mod exporter;
+
+#[cfg(feature = "prometheus")]
+pub use exporter::{gather, serve};
+
+/// Record the outcome and latency of an operation
+pub fn record(operation: &str, elapsed: Duration, success: bool) {
+    #[cfg(feature = "prometheus")]
+    exporter::record(operation, elapsed, success);
+    
+    #[cfg(not(feature = "prometheus"))]
+    let _ = (operation, elapsed, success);
+}
+
+/// Run an operation in a span and record its metrics
+pub fn timed<T, E: std::fmt::Display>(
+    operation: &'static str,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let span = tracing::debug_span!("operation", name = operation);
+    let _guard = span.enter();
+    
+    let started = Instant::now();
+    let result = f();
+    finish(operation, started, &result);
+    
+    result
+}
+
+/// Run an async operation in a span and record its metrics
+pub async fn timed_async<T, E: std::fmt::Display>(
+    operation: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let span = tracing::debug_span!("operation", name = operation);
+    
+    let started = Instant::now();
+    let result = future.instrument(span.clone()).await;
+    span.in_scope(|| finish(operation, started, &result));
+    
+    result
+}
+
+/// Log and record the result of an operation
+fn finish<T, E: std::fmt::Display>(operation: &'static str, started: Instant, result: &Result<T, E>) {
+    let elapsed = started.elapsed();
+    
+    if let Err(e) = result {
+        tracing::warn!(error = %e, elapsed_ms = elapsed.as_millis() as u64, "{} failed", operation);
+    } else {
+        tracing::debug!(elapsed_ms = elapsed.as_millis() as u64, "{} completed", operation);
+    }
+    
+    record(operation, elapsed, result.is_ok());
+}
@@ -0,0 +1,310 @@
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "onchain")]
+use solana_client::rpc_client::RpcClient;
+#[cfg(feature = "onchain")]
+use solana_sdk::commitment_config::CommitmentConfig;
+use quantum_veil_types::TimelineType;
+
+#[cfg(feature = "onchain")]
+use crate::network::ProxyConfig;
+
+use super::storage::StorageLocation;
+
+/// Default IPFS pinning rate (USD per GB per month)
+const DEFAULT_IPFS_USD_PER_GB_MONTH: f64 = 0.15;
+
+/// Bytes in a gigabyte, used for pinning rate conversion
+const BYTES_PER_GB: f64 = 1_073_741_824.0;
+
+/// Default Solana rent rate (lamports per byte-year)
+const DEFAULT_LAMPORTS_PER_BYTE_YEAR: u64 = 3_480;
+
+/// Years of rent an account must hold to be rent-exempt
+const DEFAULT_EXEMPTION_YEARS: u64 = 2;
+
+/// Bytes of account metadata Solana charges rent for on top of the data
+const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// Rent-exempt deposit for an account of `size` bytes under the default rent schedule
+///
+/// Matches `Rent::default().minimum_balance`, so list prices don't need the Solana SDK.
+fn default_minimum_balance(size: usize) -> u64 {
+    (ACCOUNT_STORAGE_OVERHEAD + size as u64) * DEFAULT_LAMPORTS_PER_BYTE_YEAR * DEFAULT_EXEMPTION_YEARS
+}
+
+/// Estimated cost of storing a single fragment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FragmentCostEstimate {
+    /// Timeline the fragment belongs to
+    pub timeline: TimelineType,
+    /// Fragment size in bytes
+    pub size: usize,
+    /// Storage backend the fragment would be written to
+    pub storage_type: String,
+    /// One-time Solana rent deposit in lamports
+    pub lamports: u64,
+    /// One-time Arweave fee in winston
+    pub winston: u64,
+    /// Recurring IPFS pinning cost in USD per month
+    pub monthly_usd: f64,
+    /// Whether the fragment is a decoy
+    #[serde(default)]
+    pub decoy: bool,
+}
+
+impl FragmentCostEstimate {
+    /// Estimate the cost of a fragment from list prices, without network calls
+    ///
+    /// Uses the default Solana rent schedule and IPFS pinning rate. Arweave
+    /// fees need a live quote and are left at zero.
+    pub fn at_list_price(timeline: &TimelineType, location: &StorageLocation, size: usize) -> Self {
+        let mut estimate = Self {
+            timeline: timeline.clone(),
+            size,
+            storage_type: String::new(),
+            lamports: 0,
+            winston: 0,
+            monthly_usd: 0.0,
+            decoy: false,
+        };
+        
+        match location {
+            StorageLocation::Onchain { .. } => {
+                estimate.storage_type = "Onchain".to_string();
+                estimate.lamports = default_minimum_balance(size);
+            },
+            StorageLocation::Arweave { .. } => {
+                estimate.storage_type = "Arweave".to_string();
+            },
+            StorageLocation::Ipfs { .. } => {
+                estimate.storage_type = "IPFS".to_string();
+                estimate.monthly_usd = size as f64 / BYTES_PER_GB * DEFAULT_IPFS_USD_PER_GB_MONTH;
+            },
+            StorageLocation::ShadowRealm { .. } => {
+                estimate.storage_type = "ShadowRealm".to_string();
+            },
+        }
+        
+        estimate
+    }
+}
+
+/// Estimated cost of a complete fracture operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FractureCostEstimate {
+    /// Per-fragment estimates
+    pub fragments: Vec<FragmentCostEstimate>,
+    /// Total Solana rent in lamports
+    pub total_lamports: u64,
+    /// Total Arweave fees in winston
+    pub total_winston: u64,
+    /// Total IPFS pinning cost in USD per month
+    pub total_monthly_usd: f64,
+}
+
+/// Queries live storage pricing for fragment cost estimation
+#[cfg(feature = "onchain")]
+pub struct CostEstimator {
+    /// RPC client for Solana rent queries
+    rpc_client: RpcClient,
+    /// HTTP client for price queries
+    http_client: reqwest::Client,
+    /// Arweave gateway used for price queries
+    arweave_endpoint: String,
+    /// Configured IPFS pinning rate (USD per GB per month)
+    ipfs_usd_per_gb_month: f64,
+}
+
+#[cfg(feature = "onchain")]
+impl CostEstimator {
+    /// Create a new cost estimator
+    pub fn new(solana_rpc_url: &str, arweave_endpoint: &str, ipfs_usd_per_gb_month: f64) -> Self {
+        Self::with_rpc_client(
+            RpcClient::new_with_commitment(solana_rpc_url.to_string(), CommitmentConfig::confirmed()),
+            arweave_endpoint,
+            ipfs_usd_per_gb_month,
+        )
+    }
+    
+    /// Create a new cost estimator with a preconfigured RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, arweave_endpoint: &str, ipfs_usd_per_gb_month: f64) -> Self {
+        Self {
+            rpc_client,
+            http_client: reqwest::Client::new(),
+            arweave_endpoint: arweave_endpoint.trim_end_matches('/').to_string(),
+            ipfs_usd_per_gb_month,
+        }
+    }
+    
+    /// Create a new cost estimator with the default Arweave gateway and pinning rate
+    pub fn default_with_rpc(solana_rpc_url: &str) -> Self {
+        Self::new(solana_rpc_url, "https://arweave.net", DEFAULT_IPFS_USD_PER_GB_MONTH)
+    }
+    
+    /// Send rent and price queries through proxies
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> Result<Self, String> {
+        self.rpc_client = proxy.rpc_client(&self.rpc_client.url(), CommitmentConfig::confirmed())?;
+        self.http_client = proxy.http_client()?;
+        Ok(self)
+    }
+    
+    /// Get the current rent-exempt deposit for an account of the given size
+    pub fn solana_rent(&self, bytes: usize) -> Result<u64, String> {
+        self.rpc_client
+            .get_minimum_balance_for_rent_exemption(bytes)
+            .map_err(|e| format!("Failed to get rent exemption: {}", e))
+    }
+    
+    /// Get the current Arweave fee for storing the given number of bytes
+    pub async fn arweave_price(&self, bytes: usize) -> Result<u64, String> {
+        let url = format!("{}/price/{}", self.arweave_endpoint, bytes);
+        
+        let response = self.http_client.get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to query Arweave price: {}", e))?;
+        
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read Arweave price: {}", e))?;
+        
+        body.trim()
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid Arweave price response: {}", e))
+    }
+    
+    /// Get the monthly IPFS pinning cost for the given number of bytes
+    pub fn ipfs_monthly_cost(&self, bytes: usize) -> f64 {
+        bytes as f64 / BYTES_PER_GB * self.ipfs_usd_per_gb_month
+    }
+    
+    /// Estimate the cost of storing a fragment at the given location
+    pub async fn estimate_fragment(
+        &self,
+        timeline: &TimelineType,
+        location: &StorageLocation,
+        size: usize,
+    ) -> Result<FragmentCostEstimate, String> {
+        let mut estimate = FragmentCostEstimate {
+            timeline: timeline.clone(),
+            size,
+            storage_type: String::new(),
+            lamports: 0,
+            winston: 0,
+            monthly_usd: 0.0,
+            decoy: false,
+        };
+        
+        match location {
+            StorageLocation::Onchain { .. } => {
+                estimate.storage_type = "Onchain".to_string();
+                estimate.lamports = self.solana_rent(size)?;
+            },
+            StorageLocation::Arweave { .. } => {
+                estimate.storage_type = "Arweave".to_string();
+                estimate.winston = self.arweave_price(size).await?;
+            },
+            StorageLocation::Ipfs { .. } => {
+                estimate.storage_type = "IPFS".to_string();
+                estimate.monthly_usd = self.ipfs_monthly_cost(size);
+            },
+            StorageLocation::ShadowRealm { .. } => {
+                // Shadow realm storage is self-hosted and carries no network fee
+                estimate.storage_type = "ShadowRealm".to_string();
+            },
+        }
+        
+        Ok(estimate)
+    }
+}
+
+impl FractureCostEstimate {
+    /// Build a total estimate from per-fragment estimates
+    pub fn from_fragments(fragments: Vec<FragmentCostEstimate>) -> Self {
+        let total_lamports = fragments.iter().map(|f| f.lamports).sum();
+        let total_winston = fragments.iter().map(|f| f.winston).sum();
+        let total_monthly_usd = fragments.iter().map(|f| f.monthly_usd).sum();
+        
+        Self {
+            fragments,
+            total_lamports,
+            total_winston,
+            total_monthly_usd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn onchain() -> StorageLocation {
+        StorageLocation::Onchain { program_id: "program".to_string(), account: "fragment".to_string() }
+    }
+    
+    fn ipfs() -> StorageLocation {
+        StorageLocation::Ipfs { cid: "cid".to_string() }
+    }
+    
+    #[test]
+    fn test_list_price_matches_default_rent() {
+        // 128 bytes of overhead at 3480 lamports per byte-year, held for two years
+        assert_eq!(default_minimum_balance(0), 890_880);
+        assert_eq!(default_minimum_balance(100), 1_586_880);
+        
+        let estimate = FragmentCostEstimate::at_list_price(&TimelineType::Primary, &onchain(), 100);
+        assert_eq!((estimate.storage_type.as_str(), estimate.lamports), ("Onchain", 1_586_880));
+        
+        let estimate = FragmentCostEstimate::at_list_price(&TimelineType::Social, &ipfs(), BYTES_PER_GB as usize);
+        assert_eq!(estimate.monthly_usd, DEFAULT_IPFS_USD_PER_GB_MONTH);
+        assert_eq!(estimate.lamports, 0);
+    }
+    
+    #[cfg(feature = "onchain")]
+    fn mock_estimator(arweave_endpoint: &str, rent: u64) -> CostEstimator {
+        use solana_client::rpc_request::RpcRequest;
+        
+        let mocks = std::collections::HashMap::from([(RpcRequest::GetMinimumBalanceForRentExemption, serde_json::json!(rent))]);
+        CostEstimator::with_rpc_client(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks), arweave_endpoint, 0.5)
+    }
+    
+    #[cfg(feature = "onchain")]
+    #[tokio::test]
+    async fn test_fragments_are_priced_by_location() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/price/300")
+            .with_body("123456\n")
+            .create_async()
+            .await;
+        let estimator = mock_estimator(&format!("{}/", server.url()), 2_000_000);
+        
+        let estimate = estimator.estimate_fragment(&TimelineType::Primary, &onchain(), 300).await.unwrap();
+        assert_eq!((estimate.storage_type.as_str(), estimate.lamports, estimate.winston), ("Onchain", 2_000_000, 0));
+        
+        let arweave = StorageLocation::Arweave { transaction_id: "tx".to_string() };
+        let estimate = estimator.estimate_fragment(&TimelineType::Social, &arweave, 300).await.unwrap();
+        assert_eq!((estimate.storage_type.as_str(), estimate.lamports, estimate.winston), ("Arweave", 0, 123_456));
+        
+        let estimate = estimator.estimate_fragment(&TimelineType::Social, &ipfs(), BYTES_PER_GB as usize * 2).await.unwrap();
+        assert_eq!((estimate.storage_type.as_str(), estimate.monthly_usd), ("IPFS", 1.0));
+        
+        let shadow = StorageLocation::ShadowRealm { access_path: "shadow".to_string() };
+        let estimate = estimator.estimate_fragment(&TimelineType::Financial, &shadow, 300).await.unwrap();
+        assert_eq!((estimate.lamports, estimate.winston, estimate.monthly_usd), (0, 0, 0.0));
+    }
+    
+    #[cfg(feature = "onchain")]
+    #[tokio::test]
+    async fn test_invalid_arweave_price_is_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/price/10")
+            .with_body("not a price")
+            .create_async()
+            .await;
+        let estimator = mock_estimator(&server.url(), 0);
+        
+        let error = estimator.arweave_price(10).await.unwrap_err();
+        assert!(error.contains("Invalid Arweave price"));
+    }
+}
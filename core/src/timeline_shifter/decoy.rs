@@ -0,0 +1,120 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use quantum_veil_types::TimelineType;
+
+use crate::quantum_veil::{Algorithm, Envelope, SecretBytes, ENVELOPE_VERSION};
+
+use super::keys::FRAGMENT_OVERHEAD;
+use super::sections::SectionMapping;
+use super::storage::obfuscated::bucket_len;
+
+/// Standard fragment sizes: 1, 4, 16 and 64 KiB
+pub const STANDARD_SIZE_BUCKETS: [usize; 4] = [1024, 4 * 1024, 16 * 1024, 64 * 1024];
+
+/// Options controlling how metadata is fractured
+#[derive(Debug, Clone, Default)]
+pub struct FractureOptions {
+    /// Number of decoy fragments to store alongside the real ones
+    pub decoy_count: usize,
+    /// Ascending sizes fragments are padded up to, empty for none
+    ///
+    /// Fragments longer than the largest bucket are padded to a multiple of it.
+    pub size_buckets: Vec<usize>,
+    /// Split JSON metadata into per-timeline sections instead of byte ranges, if set
+    pub sections: Option<SectionMapping>,
+    /// Key under which identical fragments encrypt identically and get the same ID, if set
+    pub convergence_key: Option<SecretBytes>,
+    /// Address of the NFT's owner fragments are bound to, if set
+    pub owner: Option<String>,
+}
+
+impl FractureOptions {
+    /// Create options with the given number of decoy fragments
+    pub fn with_decoys(decoy_count: usize) -> Self {
+        Self { decoy_count, ..Self::default() }
+    }
+    
+    /// Pad fragments up to size buckets, e.g. `STANDARD_SIZE_BUCKETS`
+    ///
+    /// Stored sizes then reveal neither the metadata's exact size nor the
+    /// timeline distribution. The true lengths are sealed in the manifest.
+    pub fn with_padding(mut self, size_buckets: &[usize]) -> Self {
+        self.size_buckets = size_buckets.to_vec();
+        self
+    }
+    
+    /// Fracture JSON metadata by field rather than by byte range
+    ///
+    /// Each timeline stores the fields the mapping assigns it as a JSON
+    /// object of its own, so reassembling one timeline yields usable JSON.
+    /// The timeline distribution is ignored.
+    pub fn with_sections(mut self, mapping: SectionMapping) -> Self {
+        self.sections = Some(mapping);
+        self
+    }
+    
+    /// Encrypt fragments convergently and address them by content, for deduplication
+    ///
+    /// Share one convergence key across a collection, e.g. derived from the
+    /// collection's key: fragments holding the same data then have the same
+    /// ID, and a shifter `with_deduplication` stores them once.
+    pub fn with_convergence_key(mut self, convergence_key: SecretBytes) -> Self {
+        self.convergence_key = Some(convergence_key);
+        self
+    }
+    
+    /// Bind fragments and the manifest to the NFT's owner as well as its mint
+    ///
+    /// They then fail to decrypt under a manifest naming another owner.
+    pub fn with_owner(mut self, owner: &str) -> Self {
+        self.owner = Some(owner.to_string());
+        self
+    }
+    
+    /// Get the stored length of a fragment with `len` bytes of data
+    pub fn padded_len(&self, len: usize) -> usize {
+        bucket_len(&self.size_buckets, len)
+    }
+}
+
+/// Generate random decoy payloads that follow the real fragment size distribution
+///
+/// Each decoy picks a random real fragment as a template, takes its timeline,
+/// and jitters its size by up to ±10% so decoys blend into the same adapters
+/// and size range as the real fragments. Decoys are envelopes like sealed
+/// fragments, with a random key ID, nonce and ciphertext, so their headers
+/// don't give them away.
+pub fn generate_decoys<R: Rng>(
+    real_fragments: &[(TimelineType, usize)],
+    count: usize,
+    rng: &mut R,
+) -> Vec<(TimelineType, Vec<u8>)> {
+    let mut decoys = Vec::with_capacity(count);
+    
+    for _ in 0..count {
+        let (timeline, size) = match real_fragments.choose(rng) {
+            Some(template) => template.clone(),
+            None => break,
+        };
+        
+        let jitter = (size / 10).max(1);
+        let size = rng.gen_range(size.saturating_sub(jitter)..=size + jitter).max(FRAGMENT_OVERHEAD);
+        
+        let algorithm = Algorithm::ChaCha20Poly1305;
+        let mut ciphertext = vec![0u8; size - FRAGMENT_OVERHEAD + algorithm.tag_len()];
+        rng.fill(&mut ciphertext[..]);
+        let envelope = Envelope {
+            version: ENVELOPE_VERSION,
+            algorithm,
+            key_id: rng.gen::<[u8; 8]>().to_vec(),
+            nonce: rng.gen::<[u8; 12]>().to_vec(),
+            aad: Vec::new(),
+            ciphertext,
+        };
+        
+        decoys.push((timeline, envelope.to_bytes()));
+    }
+    
+    decoys
+}
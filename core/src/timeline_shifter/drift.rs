@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use quantum_veil_types::TimelineType;
+
+use crate::quantum_veil::SecretBytes;
+
+use super::decoy::FractureOptions;
+use super::manifest::FragmentManifest;
+use super::TimelineShifter;
+
+/// Metadata tracked for periodic re-sharding
+struct DriftEntry {
+    /// Current fragment manifest
+    manifest: FragmentManifest,
+    /// Key used to reassemble and re-fracture the metadata
+    encryption_key: SecretBytes,
+    /// Timeline distribution to use when re-fracturing
+    timeline_config: HashMap<TimelineType, f32>,
+    /// Fracture options to use when re-fracturing
+    options: FractureOptions,
+    /// Last drift timestamp
+    last_drift: u64,
+    /// Re-fractured manifest waiting for its hash to be published
+    pending: Option<FragmentManifest>,
+}
+
+/// Result of re-sharding one NFT's metadata
+#[derive(Debug, Clone)]
+pub struct DriftResult {
+    /// NFT mint address
+    pub nft_mint: String,
+    /// Manifest describing the new fragment set
+    pub manifest: FragmentManifest,
+    /// Manifest hash to publish on-chain
+    pub config_hash: String,
+}
+
+/// Periodically re-fractures stored metadata so long-term observers can't correlate fragments
+pub struct DriftScheduler {
+    /// Drift cadence in seconds
+    interval: u64,
+    /// Tracked metadata by NFT mint
+    entries: HashMap<String, DriftEntry>,
+}
+
+impl DriftScheduler {
+    /// Create a new drift scheduler with the given cadence in seconds
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval,
+            entries: HashMap::new(),
+        }
+    }
+    
+    /// Start tracking an NFT's fragments for drift
+    pub fn track(
+        &mut self,
+        manifest: FragmentManifest,
+        encryption_key: &[u8],
+        timeline_config: HashMap<TimelineType, f32>,
+        options: FractureOptions,
+    ) {
+        let last_drift = manifest.created_at;
+        
+        self.entries.insert(manifest.nft_mint.clone(), DriftEntry {
+            manifest,
+            encryption_key: SecretBytes::from(encryption_key),
+            timeline_config,
+            options,
+            last_drift,
+            pending: None,
+        });
+    }
+    
+    /// Stop tracking an NFT
+    pub fn untrack(&mut self, nft_mint: &str) -> Option<FragmentManifest> {
+        self.entries.remove(nft_mint).map(|entry| entry.manifest)
+    }
+    
+    /// Get the current manifest for an NFT
+    pub fn manifest(&self, nft_mint: &str) -> Option<&FragmentManifest> {
+        self.entries.get(nft_mint).map(|entry| &entry.manifest)
+    }
+    
+    /// Get the NFT mints whose fragments are due for drift
+    pub fn due(&self) -> Vec<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        
+        self.entries.iter()
+            .filter(|(_, entry)| now.saturating_sub(entry.last_drift) >= self.interval)
+            .map(|(mint, _)| mint.clone())
+            .collect()
+    }
+    
+    /// Re-fracture a single NFT's metadata with fresh fragment seeds
+    ///
+    /// The old fragments stay in place until [`DriftScheduler::commit`] is
+    /// called once the new hash is published. A drift that was never committed
+    /// is returned again rather than re-fractured, since its hash may already
+    /// be on-chain.
+    pub async fn drift(
+        &mut self,
+        shifter: &TimelineShifter,
+        nft_mint: &str,
+    ) -> Result<DriftResult, String> {
+        let entry = self.entries.get_mut(nft_mint).ok_or("NFT not tracked for drift")?;
+        
+        if let Some(pending) = &entry.pending {
+            return Ok(DriftResult {
+                nft_mint: nft_mint.to_string(),
+                manifest: pending.clone(),
+                config_hash: pending.hash(),
+            });
+        }
+        
+        log::info!("Drifting timeline fragments for NFT: {}", nft_mint);
+        
+        // Recover the plaintext from the current fragment set
+        let metadata = shifter.reassemble_from_manifest(
+            &entry.manifest,
+            entry.encryption_key.expose(),
+        ).await?;
+        
+        // Scatter it again under new fragment IDs
+        let mut manifest = shifter.fracture_metadata(
+            nft_mint,
+            &metadata,
+            entry.encryption_key.expose(),
+            entry.timeline_config.clone(),
+            &entry.options,
+        ).await?;
+        manifest.epoch = entry.manifest.epoch + 1;
+        
+        let result = DriftResult {
+            nft_mint: nft_mint.to_string(),
+            config_hash: manifest.hash(),
+            manifest: manifest.clone(),
+        };
+        entry.pending = Some(manifest);
+        
+        Ok(result)
+    }
+    
+    /// Switch an NFT to its drifted manifest once the new hash is published
+    ///
+    /// Only then are the old fragments (including decoys) deleted, so the
+    /// manifest on-chain always points at fragments that exist.
+    pub async fn commit(&mut self, shifter: &TimelineShifter, nft_mint: &str) -> Result<(), String> {
+        let entry = self.entries.get_mut(nft_mint).ok_or("NFT not tracked for drift")?;
+        let pending = entry.pending.as_ref().ok_or("No drift pending for NFT")?;
+        
+        shifter.delete_fragments(&entry.manifest.fragment_ids).await?;
+        
+        entry.manifest = pending.clone();
+        entry.last_drift = entry.manifest.created_at;
+        entry.pending = None;
+        
+        Ok(())
+    }
+}
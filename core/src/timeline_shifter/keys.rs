@@ -0,0 +1,121 @@
+use rand::{Rng, rngs::OsRng};
+use ring::{digest, hkdf, hmac};
+use zeroize::Zeroizing;
+
+use crate::quantum_veil::{decrypt_data, envelope_overhead, is_envelope, EncryptionContext, Envelope, SecretBytes};
+use quantum_veil_types::TimelineType;
+
+/// HKDF salt for per-timeline fragment keys
+const TIMELINE_KEY_SALT: &[u8] = b"quantum-veil/timeline-key";
+
+/// Domain label for timeline key IDs
+const KEY_ID_LABEL: &[u8] = b"quantum-veil/timeline-key-id";
+
+/// Domain label for convergent fragment keys
+const CONVERGENT_KEY_LABEL: &[u8] = b"quantum-veil/convergent-fragment-key";
+
+/// Prefix of content-addressed fragment IDs
+pub const CONTENT_ID_PREFIX: &str = "ca-";
+
+/// Bytes a sealed fragment adds to its data: the envelope header and authentication tag
+pub const FRAGMENT_OVERHEAD: usize = envelope_overhead(0);
+
+/// HKDF output length for a 256-bit timeline key
+struct TimelineKeyLen;
+
+impl hkdf::KeyType for TimelineKeyLen {
+    fn len(&self) -> usize {
+        32
+    }
+}
+
+/// Derive the key encrypting one timeline's fragments from the metadata encryption key
+///
+/// A timeline key opens only that timeline's fragments, so it can be handed
+/// to a partner app without exposing the other timelines.
+pub fn derive_timeline_key(encryption_key: &[u8], timeline: &TimelineType) -> SecretBytes {
+    derive_labelled_key(encryption_key, &timeline.label())
+}
+
+/// Derive the key of the timeline with a label, as `derive_timeline_key`
+pub(crate) fn derive_labelled_key(encryption_key: &[u8], label: &str) -> SecretBytes {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, TIMELINE_KEY_SALT).extract(encryption_key);
+    let okm = prk.expand(&[label.as_bytes()], TimelineKeyLen).expect("32 bytes is a valid HKDF output length");
+    
+    let mut key = Zeroizing::new([0u8; 32]);
+    okm.fill(&mut key[..]).expect("32 bytes is a valid HKDF output length");
+    SecretBytes::from(&key[..])
+}
+
+/// Get the public ID of a timeline key, recorded in the manifest
+pub fn timeline_key_id(timeline_key: &[u8]) -> String {
+    let hash = digest::digest(&digest::SHA256, &[KEY_ID_LABEL, timeline_key].concat());
+    hash.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Get the context a fragment of an NFT's timeline is bound to
+pub fn fragment_context(nft_mint: &str, owner: Option<&str>, timeline: &TimelineType) -> EncryptionContext {
+    EncryptionContext::new(nft_mint, owner.unwrap_or_default(), &format!("timeline-shifter/fragment/{}", timeline.label()))
+}
+
+/// Get the context convergent fragments are bound to
+///
+/// Only the timeline: identical fragments are shared between NFTs.
+pub fn convergent_context(timeline: &TimelineType) -> EncryptionContext {
+    EncryptionContext::shared(&format!("timeline-shifter/convergent-fragment/{}", timeline.label()))
+}
+
+/// Encrypt a fragment's data under its timeline key into an envelope bound to `context`
+///
+/// The envelope names the key by an ID blinded with its nonce, so stored
+/// fragments of a timeline share no tag across fragments or drift epochs.
+/// The timeline key's own ID is only recorded in the manifest.
+pub(crate) fn seal_fragment(data: &[u8], timeline_key: &SecretBytes, context: &EncryptionContext) -> Result<Vec<u8>, String> {
+    let nonce: [u8; 12] = OsRng.gen();
+    Ok(Envelope::seal_blinded(data, timeline_key.expose(), &nonce, &[], &context.to_aad())?.to_bytes())
+}
+
+/// Encrypt a fragment convergently, returning the sealed data and the key it was sealed under
+///
+/// The key and nonce depend only on the convergence key, timeline and
+/// data, so identical sections sealed under one convergence key give
+/// identical fragments that can be stored once.
+pub(crate) fn seal_convergent(
+    data: &[u8],
+    timeline: &TimelineType,
+    convergence_key: &SecretBytes,
+) -> Result<(Vec<u8>, SecretBytes), String> {
+    let mut context = hmac::Context::with_key(&hmac::Key::new(hmac::HMAC_SHA256, convergence_key.expose()));
+    context.update(CONVERGENT_KEY_LABEL);
+    context.update(timeline.label().as_bytes());
+    context.update(&[0]);
+    context.update(data);
+    let content_key = SecretBytes::from(context.sign().as_ref());
+    
+    // Each key seals only this data, so a nonce derived from it is never reused
+    let nonce = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, content_key.expose()), b"nonce");
+    let context = convergent_context(timeline).to_aad();
+    let sealed = Envelope::seal_blinded(data, content_key.expose(), &nonce.as_ref()[..12], &[], &context)?;
+    Ok((sealed.to_bytes(), content_key))
+}
+
+/// Get the content-addressed ID of sealed fragment data
+pub fn content_id(sealed: &[u8]) -> String {
+    let hash = digest::digest(&digest::SHA256, sealed);
+    let hex: String = hash.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", CONTENT_ID_PREFIX, hex)
+}
+
+/// Decrypt a fragment sealed with `seal_fragment` or `seal_convergent`, checking its context
+///
+/// Fragments from before envelopes are `[nonce: 12][ciphertext]` and bound to nothing.
+pub(crate) fn open_fragment(sealed: &[u8], timeline_key: &SecretBytes, context: &EncryptionContext) -> Result<Vec<u8>, String> {
+    if is_envelope(sealed) {
+        return context.open(sealed, timeline_key.expose());
+    }
+    if sealed.len() < 12 + 16 {
+        return Err("Sealed fragment too short".to_string());
+    }
+    
+    decrypt_data(&sealed[12..], timeline_key.expose(), &sealed[..12])
+}
@@ -0,0 +1,285 @@
+use ring::digest;
+use serde::{Serialize, Deserialize};
+use sha3::{Sha3_512, Digest};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::quantum_veil::{decrypt_data, derive_key_from_seed, is_envelope, EncryptionContext, SecretBytes};
+use super::keys::{derive_labelled_key, fragment_context};
+use quantum_veil_types::{MetadataFragment, StorageLocation, TimelineType};
+
+/// Domain label for the key protecting the real-fragment index
+const INDEX_KEY_LABEL: &[u8] = b"quantum-veil/manifest-index";
+
+/// Domain label for the key protecting the fragment lengths
+const LENGTHS_KEY_LABEL: &[u8] = b"quantum-veil/manifest-lengths";
+
+/// Domain label for the key protecting a timeline's fragment list
+const TIMELINE_INDEX_KEY_LABEL: &[u8] = b"quantum-veil/manifest-timeline-index";
+
+/// Record of where an NFT's metadata fragments are stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FragmentManifest {
+    /// NFT mint address
+    pub nft_mint: String,
+    /// Address of the NFT's owner when fractured, if given
+    ///
+    /// Fragments and sealed fields are bound to it together with the mint,
+    /// so they don't open as another NFT's or owner's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// All stored fragment IDs, including any decoys
+    pub fragment_ids: Vec<String>,
+    /// Encrypted, base64-encoded list of real fragment IDs in reassembly order
+    ///
+    /// `None` when no decoys were stored and `fragment_ids` is the real set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_index: Option<String>,
+    /// Encrypted, base64-encoded data length of each real fragment before padding
+    ///
+    /// `None` when fragments were stored unpadded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_lengths: Option<String>,
+    /// ID of the key encrypting each timeline's fragments, by timeline label
+    ///
+    /// Empty when all fragments were encrypted together under the metadata key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timeline_keys: BTreeMap<String, String>,
+    /// Encrypted list of each timeline's real fragments, by timeline label
+    ///
+    /// Each list is sealed under its timeline's key, so a holder of one
+    /// timeline key can find and reassemble that timeline alone.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timeline_index: BTreeMap<String, String>,
+    /// Whether each fragment holds a JSON section of the metadata rather than a byte range
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sectioned: bool,
+    /// Number of times the fragments have been re-sharded
+    pub epoch: u64,
+    /// Timestamp when this manifest was created
+    pub created_at: u64,
+    /// What each storage adapter reported for the fragments it stored, in `fragment_ids` order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub receipts: Vec<FragmentReceipt>,
+}
+
+/// A real fragment as listed in its timeline's sealed index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedFragment {
+    /// Fragment ID
+    pub fragment_id: String,
+    /// Length of the fragment's data before padding
+    pub length: usize,
+    /// Key the fragment was convergently encrypted under, if it is content addressed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_key: Option<SecretBytes>,
+}
+
+/// Commitment to one stored fragment and the storage adapter's receipt for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FragmentReceipt {
+    /// Fragment ID
+    pub fragment_id: String,
+    /// Timeline the fragment was stored on
+    pub timeline: TimelineType,
+    /// SHA-256 of the fragment's encrypted data, hex encoded
+    pub sha256: String,
+    /// Where the fragment was stored
+    pub location: StorageLocation,
+    /// Receipt returned by the storage adapter, e.g. a transaction ID or CID
+    pub receipt: String,
+    /// When the fragment was stored, as a Unix timestamp
+    pub stored_at: u64,
+}
+
+impl FragmentReceipt {
+    /// Commit to a fragment as stored, with its adapter's receipt
+    pub fn new(fragment: &MetadataFragment, receipt: String) -> Self {
+        Self {
+            fragment_id: fragment.id.clone(),
+            timeline: fragment.timeline.clone(),
+            sha256: fragment_hash(&fragment.data),
+            location: fragment.storage_location.clone(),
+            receipt,
+            stored_at: fragment.timestamp,
+        }
+    }
+    
+    /// Check a fragment read back from storage is the one committed to
+    pub fn matches(&self, fragment: &MetadataFragment) -> bool {
+        fragment.id == self.fragment_id && fragment_hash(&fragment.data) == self.sha256
+    }
+}
+
+/// Hash fragment data for a receipt
+fn fragment_hash(data: &[u8]) -> String {
+    digest::digest(&digest::SHA256, data).as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl FragmentManifest {
+    /// Create a new manifest for freshly fractured metadata
+    pub fn new(nft_mint: &str, fragment_ids: Vec<String>) -> Self {
+        Self {
+            nft_mint: nft_mint.to_string(),
+            owner: None,
+            fragment_ids,
+            encrypted_index: None,
+            encrypted_lengths: None,
+            timeline_keys: BTreeMap::new(),
+            timeline_index: BTreeMap::new(),
+            sectioned: false,
+            epoch: 0,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            receipts: Vec::new(),
+        }
+    }
+    
+    /// Encrypt the real fragment IDs into the manifest index
+    pub fn seal_index(&mut self, real_ids: &[String], encryption_key: &[u8]) -> Result<(), String> {
+        let index_json = serde_json::to_vec(real_ids)
+            .map_err(|e| format!("Failed to serialize fragment index: {}", e))?;
+        
+        self.encrypted_index = Some(seal(&index_json, encryption_key, &self.field_context(INDEX_KEY_LABEL))?);
+        Ok(())
+    }
+    
+    /// Get the real fragment IDs, decrypting the index if decoys are present
+    pub fn real_fragment_ids(&self, encryption_key: &[u8]) -> Result<Vec<String>, String> {
+        let encrypted_index = match &self.encrypted_index {
+            Some(index) => index,
+            None => return Ok(self.fragment_ids.clone()),
+        };
+        
+        let index_json = open(encrypted_index, encryption_key, &self.field_context(INDEX_KEY_LABEL))
+            .map_err(|e| format!("Failed to open fragment index: {}", e))?;
+        
+        serde_json::from_slice(&index_json)
+            .map_err(|e| format!("Failed to parse fragment index: {}", e))
+    }
+    
+    /// Encrypt the unpadded data lengths of the real fragments into the manifest
+    pub fn seal_lengths(&mut self, lengths: &BTreeMap<String, usize>, encryption_key: &[u8]) -> Result<(), String> {
+        let lengths_json = serde_json::to_vec(lengths)
+            .map_err(|e| format!("Failed to serialize fragment lengths: {}", e))?;
+        
+        self.encrypted_lengths = Some(seal(&lengths_json, encryption_key, &self.field_context(LENGTHS_KEY_LABEL))?);
+        Ok(())
+    }
+    
+    /// Get the unpadded data length of each real fragment, or `None` if fragments weren't padded
+    pub fn fragment_lengths(&self, encryption_key: &[u8]) -> Result<Option<BTreeMap<String, usize>>, String> {
+        let encrypted_lengths = match &self.encrypted_lengths {
+            Some(lengths) => lengths,
+            None => return Ok(None),
+        };
+        
+        let lengths_json = open(encrypted_lengths, encryption_key, &self.field_context(LENGTHS_KEY_LABEL))
+            .map_err(|e| format!("Failed to open fragment lengths: {}", e))?;
+        
+        serde_json::from_slice(&lengths_json)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse fragment lengths: {}", e))
+    }
+    
+    /// Get the ID of a timeline's key, or `None` if the timeline has no key of its own
+    pub fn timeline_key_id(&self, timeline: &TimelineType) -> Option<&str> {
+        self.timeline_keys.get(&timeline.label()).map(String::as_str)
+    }
+    
+    /// Encrypt a timeline's real fragments, in order, under its timeline key
+    pub fn seal_timeline_index(
+        &mut self,
+        timeline: &TimelineType,
+        fragments: &[IndexedFragment],
+        timeline_key: &[u8],
+    ) -> Result<(), String> {
+        let index_json = serde_json::to_vec(fragments)
+            .map_err(|e| format!("Failed to serialize timeline index: {}", e))?;
+        
+        let sealed = seal(&index_json, timeline_key, &self.field_context(TIMELINE_INDEX_KEY_LABEL))?;
+        self.timeline_index.insert(timeline.label(), sealed);
+        Ok(())
+    }
+    
+    /// Get a timeline's real fragments, in order, with its timeline key
+    ///
+    /// Empty if the timeline got no fragments, e.g. for tiny metadata.
+    pub fn timeline_fragments(&self, timeline: &TimelineType, timeline_key: &[u8]) -> Result<Vec<IndexedFragment>, String> {
+        match self.timeline_index.get(&timeline.label()) {
+            Some(encrypted_index) => open_timeline_index(encrypted_index, timeline_key, &self.field_context(TIMELINE_INDEX_KEY_LABEL)),
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    /// Get the content key of each convergently encrypted fragment, by fragment ID
+    pub fn content_keys(&self, encryption_key: &[u8]) -> Result<HashMap<String, SecretBytes>, String> {
+        let mut content_keys = HashMap::new();
+        let context = self.field_context(TIMELINE_INDEX_KEY_LABEL);
+        
+        for (label, encrypted_index) in &self.timeline_index {
+            let timeline_key = derive_labelled_key(encryption_key, label);
+            for fragment in open_timeline_index(encrypted_index, timeline_key.expose(), &context)? {
+                if let Some(content_key) = fragment.content_key {
+                    content_keys.insert(fragment.fragment_id, content_key);
+                }
+            }
+        }
+        
+        Ok(content_keys)
+    }
+    
+    /// Get the context the NFT's fragments on a timeline are bound to
+    pub fn fragment_context(&self, timeline: &TimelineType) -> EncryptionContext {
+        fragment_context(&self.nft_mint, self.owner.as_deref(), timeline)
+    }
+    
+    /// Get the context a sealed field is bound to, named after its key label
+    fn field_context(&self, label: &[u8]) -> EncryptionContext {
+        EncryptionContext::new(&self.nft_mint, self.owner.as_deref().unwrap_or_default(), &String::from_utf8_lossy(label))
+    }
+    
+    /// Get manifest hash for on-chain storage
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha3_512::new();
+        let manifest_json = serde_json::to_string(self).unwrap_or_default();
+        hasher.update(manifest_json.as_bytes());
+        let result = hasher.finalize();
+        
+        base64::encode(&result)
+    }
+}
+
+/// Encrypt a manifest field under a key derived for its context's purpose, as a base64 envelope
+fn seal(plaintext: &[u8], encryption_key: &[u8], context: &EncryptionContext) -> Result<String, String> {
+    let (key, _) = derive_key_from_seed(&[encryption_key, context.purpose.as_bytes()].concat());
+    
+    Ok(base64::encode(context.seal(plaintext, &key)?))
+}
+
+/// Decrypt and parse a timeline's sealed fragment index
+fn open_timeline_index(encrypted_index: &str, timeline_key: &[u8], context: &EncryptionContext) -> Result<Vec<IndexedFragment>, String> {
+    let index_json = open(encrypted_index, timeline_key, context)
+        .map_err(|e| format!("Failed to open timeline index: {}", e))?;
+    
+    serde_json::from_slice(&index_json)
+        .map_err(|e| format!("Failed to parse timeline index: {}", e))
+}
+
+/// Decrypt a manifest field sealed with `seal`, or as `[nonce: 12][ciphertext]` from before envelopes
+fn open(encoded: &str, encryption_key: &[u8], context: &EncryptionContext) -> Result<Vec<u8>, String> {
+    let sealed = base64::decode(encoded)
+        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+    
+    let (key, _) = derive_key_from_seed(&[encryption_key, context.purpose.as_bytes()].concat());
+    if is_envelope(&sealed) {
+        return context.open(&sealed, &key);
+    }
+    if sealed.len() < 12 {
+        return Err("Sealed field too short".to_string());
+    }
+    
+    decrypt_data(&sealed[12..], &key, &sealed[0..12])
+}
@@ -1,44 +1,227 @@
-mod fragment;
-mod storage;
-mod timeline;
+#![cfg(feature = "crypto")]
 
-pub use fragment::MetadataFragment;
-pub use storage::StorageLocation;
-pub use timeline::TimelineType;
+mod cost;
+mod decoy;
+mod drift;
+mod keys;
+mod manifest;
+mod sections;
+mod storage;
 
-use storage::StorageAdapter;
+pub use cost::{FractureCostEstimate, FragmentCostEstimate};
+#[cfg(feature = "onchain")]
+pub use cost::CostEstimator;
+pub use decoy::{FractureOptions, STANDARD_SIZE_BUCKETS};
+pub use drift::{DriftScheduler, DriftResult};
+pub use keys::{content_id, convergent_context, derive_timeline_key, fragment_context, timeline_key_id, CONTENT_ID_PREFIX, FRAGMENT_OVERHEAD};
+pub use manifest::{FragmentManifest, FragmentReceipt, IndexedFragment};
+pub use sections::SectionMapping;
+pub use storage::{
+    as_requester, AccessLogAdapter, RetrievalStats,
+    DedupAdapter, DryRunAdapter, InstrumentedAdapter, ObfuscatedAdapter, PlannedWrite, ObfuscationConfig, StorageAdapter, StorageLocation, ThreatModel,
+};
+pub use quantum_veil_types::{MetadataFragment, TimelineType};
+#[cfg(any(test, feature = "test-utils"))]
+pub use storage::InMemoryAdapter;
 
 use ring::{digest, hmac};
 use futures::future::join_all;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use serde::{Serialize, Deserialize};
 use rand::{Rng, SeedableRng};
-use rand::rngs::StdRng;
+use rand::rngs::{OsRng, StdRng};
+use rand::seq::SliceRandom;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::audit::{AuditEvent, AuditLog};
+use crate::quantum_veil::SecretBytes;
+
+/// How reassembled fragments are decrypted and joined
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Assembly {
+    /// Byte ranges of data encrypted as a whole under the metadata key, from before per-timeline keys
+    Combined,
+    /// Byte ranges each sealed under their timeline's key
+    ByteRanges,
+    /// JSON sections each sealed under their timeline's key
+    Sections,
+}
+
 /// Timeline Shifter for fracturing and retrieving NFT metadata
 pub struct TimelineShifter {
     /// Primary storage adapter
     primary_adapter: Box<dyn StorageAdapter + Send + Sync>,
     /// Map of adapters by timeline type
     adapters: HashMap<TimelineType, Box<dyn StorageAdapter + Send + Sync>>,
-    /// Cache of fragments by ID
-    fragment_cache: HashMap<String, MetadataFragment>,
+    /// Cache of fragments by ID, shared between clones
+    fragment_cache: Arc<RwLock<HashMap<String, MetadataFragment>>>,
+    /// Optional estimator for previewing storage costs
+    #[cfg(feature = "onchain")]
+    cost_estimator: Option<Arc<CostEstimator>>,
+    /// Audit log for fracturing and reassembly, if configured
+    audit_log: Option<Arc<AuditLog>>,
+    /// Padding and timing obfuscation of storage calls, if configured
+    obfuscation: Option<ObfuscationConfig>,
+    /// Fragment writes skipped in dry-run mode, if enabled
+    planned_writes: Option<Arc<Mutex<Vec<PlannedWrite>>>>,
+    /// Uploads of already stored content-addressed fragments skipped, if deduplicating
+    deduplicated: Option<Arc<AtomicUsize>>,
+    /// Audit log fragment retrievals are recorded in, with the NFT of each known fragment, if logging access
+    access_log: Option<(Arc<AuditLog>, Arc<RwLock<HashMap<String, String>>>)>,
 }
 
 impl TimelineShifter {
     /// Create a new Timeline Shifter with provided adapters
+    ///
+    /// Adapters are wrapped so every storage call is traced and measured.
     pub fn new(
         primary_adapter: Box<dyn StorageAdapter + Send + Sync>,
         adapters: HashMap<TimelineType, Box<dyn StorageAdapter + Send + Sync>>,
     ) -> Self {
+        let instrument = |adapter| -> Box<dyn StorageAdapter + Send + Sync> {
+            Box::new(InstrumentedAdapter::new(adapter))
+        };
+        
         Self {
-            primary_adapter,
-            adapters,
-            fragment_cache: HashMap::new(),
+            primary_adapter: instrument(primary_adapter),
+            adapters: adapters.into_iter().map(|(timeline, adapter)| (timeline, instrument(adapter))).collect(),
+            fragment_cache: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(feature = "onchain")]
+            cost_estimator: None,
+            audit_log: None,
+            obfuscation: None,
+            planned_writes: None,
+            deduplicated: None,
+            access_log: None,
+        }
+    }
+    
+    /// Set the cost estimator used by `estimate_fracture_cost`
+    #[cfg(feature = "onchain")]
+    pub fn with_cost_estimator(mut self, estimator: CostEstimator) -> Self {
+        self.cost_estimator = Some(Arc::new(estimator));
+        self
+    }
+    
+    /// Record fracturing and reassembly in an audit log
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
+    /// Pad fragments and disguise storage timing against a threat model
+    ///
+    /// Fragments stored this way can only be read back by a shifter with the
+    /// same padding key.
+    pub fn with_obfuscation(mut self, config: ObfuscationConfig, padding_key: SecretBytes) -> Self {
+        let obfuscate = |adapter| -> Box<dyn StorageAdapter + Send + Sync> {
+            Box::new(ObfuscatedAdapter::new(adapter, config.clone(), padding_key.clone()))
+        };
+        
+        self.primary_adapter = obfuscate(self.primary_adapter);
+        self.adapters = self.adapters.into_iter().map(|(timeline, adapter)| (timeline, obfuscate(adapter))).collect();
+        self.obfuscation = Some(config);
+        self
+    }
+    
+    /// Record fragment writes and deletions instead of performing them
+    ///
+    /// Fragments can still be read from storage. The skipped writes are
+    /// listed by `planned_writes`.
+    pub fn with_dry_run(mut self) -> Self {
+        let writes = Arc::new(Mutex::new(Vec::new()));
+        let dry_run = |adapter| -> Box<dyn StorageAdapter + Send + Sync> {
+            Box::new(DryRunAdapter::new(adapter, writes.clone()))
+        };
+        
+        self.primary_adapter = dry_run(self.primary_adapter);
+        self.adapters = self.adapters.into_iter().map(|(timeline, adapter)| (timeline, dry_run(adapter))).collect();
+        self.planned_writes = Some(writes);
+        self
+    }
+    
+    /// Store each content-addressed fragment only once
+    ///
+    /// Fragments fractured with a convergence key that are already stored
+    /// are referenced instead of uploaded again, and are kept when a
+    /// manifest's fragments are deleted.
+    pub fn with_deduplication(mut self) -> Self {
+        let deduplicated = Arc::new(AtomicUsize::new(0));
+        let dedup = |adapter| -> Box<dyn StorageAdapter + Send + Sync> {
+            Box::new(DedupAdapter::new(adapter, deduplicated.clone()))
+        };
+        
+        self.primary_adapter = dedup(self.primary_adapter);
+        self.adapters = self.adapters.into_iter().map(|(timeline, adapter)| (timeline, dedup(adapter))).collect();
+        self.deduplicated = Some(deduplicated);
+        self
+    }
+    
+    /// Record every fragment read from storage in an audit log
+    ///
+    /// Entries name the fragment's NFT once the shifter has fractured or
+    /// reassembled it from a manifest, and the requester when read within
+    /// `as_requester`. Reads served from the fragment cache aren't logged.
+    pub fn with_access_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        let owners = Arc::new(RwLock::new(HashMap::new()));
+        let log_access = |adapter| -> Box<dyn StorageAdapter + Send + Sync> {
+            Box::new(AccessLogAdapter::new(adapter, audit_log.clone(), owners.clone()))
+        };
+        
+        self.primary_adapter = log_access(self.primary_adapter);
+        self.adapters = self.adapters.into_iter().map(|(timeline, adapter)| (timeline, log_access(adapter))).collect();
+        self.access_log = Some((audit_log, owners));
+        self
+    }
+    
+    /// Get the fragment retrievals of an NFT recorded by `with_access_log`
+    pub fn retrieval_stats(&self, nft_mint: &str) -> Result<RetrievalStats, String> {
+        match &self.access_log {
+            Some((audit_log, _)) => Ok(RetrievalStats::from_entries(&audit_log.entries()?, nft_mint)),
+            None => Ok(RetrievalStats::default()),
         }
     }
     
+    /// Remember which NFT fragments belong to, for access logging
+    fn register_owner(&self, nft_mint: &str, fragment_ids: &[String]) {
+        if let Some((_, owners)) = &self.access_log {
+            owners.write().unwrap()
+                .extend(fragment_ids.iter().map(|id| (id.clone(), nft_mint.to_string())));
+        }
+    }
+    
+    /// Get how many fragment uploads deduplication has skipped
+    pub fn deduplicated_writes(&self) -> usize {
+        self.deduplicated.as_ref().map_or(0, |deduplicated| deduplicated.load(Ordering::Relaxed))
+    }
+    
+    /// Check if writes are recorded rather than performed
+    pub fn is_dry_run(&self) -> bool {
+        self.planned_writes.is_some()
+    }
+    
+    /// Get the fragment writes skipped in dry-run mode, in order
+    pub fn planned_writes(&self) -> Vec<PlannedWrite> {
+        self.planned_writes.as_ref()
+            .map(|writes| writes.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+    
+    /// Get the adapter storing a timeline's fragments
+    fn adapter_for(&self, timeline: &TimelineType) -> &(dyn StorageAdapter + Send + Sync) {
+        self.adapters.get(timeline).unwrap_or(&self.primary_adapter).as_ref()
+    }
+    
+    /// Record an event if an audit log is configured
+    fn audit(&self, event: AuditEvent) -> Result<(), String> {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(event)?;
+        }
+        Ok(())
+    }
+    
     /// Generate a unique fragment ID
     fn generate_fragment_id(&self, timeline: &TimelineType, nft_mint: &str, seed: u64) -> String {
         let mut hasher = digest::Context::new(&digest::SHA256);
@@ -70,86 +253,210 @@ impl TimelineShifter {
         id[0..16].to_string()
     }
     
-    /// Split metadata into fragments across timelines
-    pub async fn fracture_metadata(
-        &mut self,
-        nft_mint: &str,
-        metadata: &[u8],
-        encryption_key: &[u8],
-        timeline_config: HashMap<TimelineType, f32>, // Timeline type -> fragment percentage
-    ) -> Result<Vec<String>, String> {
-        log::info!("Fracturing metadata across timelines...");
-        
-        // Validate timeline config
-        let total_percentage: f32 = timeline_config.values().sum();
-        if (total_percentage - 1.0).abs() > 0.001 {
-            return Err("Timeline configuration percentages must sum to 1.0".to_string());
-        }
-        
-        // Create a deterministic RNG for fragment generation
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let mut rng = StdRng::seed_from_u64(seed);
-        
-        // Encrypt the full metadata first
-        let encrypted_metadata = self.encrypt_data(metadata, encryption_key)?;
-        
-        // Calculate fragment sizes based on percentages
+    /// Calculate fragment sizes for each timeline from percentage distribution
+    fn compute_fragment_sizes(
+        total_bytes: usize,
+        timeline_config: &HashMap<TimelineType, f32>,
+    ) -> HashMap<TimelineType, usize> {
         let mut fragment_sizes: HashMap<TimelineType, usize> = HashMap::new();
-        let total_bytes = encrypted_metadata.len();
         
-        for (timeline, percentage) in &timeline_config {
+        for (timeline, percentage) in timeline_config {
             let size = (total_bytes as f32 * percentage) as usize;
             fragment_sizes.insert(timeline.clone(), size);
         }
         
-        // Adjust sizes to ensure we use all bytes
+        // Adjust sizes to ensure we use all bytes exactly once
         let allocated_bytes: usize = fragment_sizes.values().sum();
-        if allocated_bytes < total_bytes {
+        if allocated_bytes > total_bytes {
+            // Percentages may sum slightly above 1.0, so trim the overshoot from the largest fragment
+            if let Some(largest) = fragment_sizes.values_mut().max() {
+                *largest -= allocated_bytes - total_bytes;
+            }
+        } else if allocated_bytes < total_bytes {
             // Add remaining bytes to primary timeline
             if let Some(primary_size) = fragment_sizes.get_mut(&TimelineType::Primary) {
                 *primary_size += total_bytes - allocated_bytes;
             } else {
                 // If no primary timeline, add to the first one
-                if let Some((first_timeline, first_size)) = fragment_sizes.iter_mut().next() {
+                if let Some((_, first_size)) = fragment_sizes.iter_mut().next() {
                     *first_size += total_bytes - allocated_bytes;
                 }
             }
         }
         
-        // Create fragments
+        fragment_sizes
+    }
+    
+    /// Choose the default storage location for a fragment on a timeline
+    fn default_storage_location(timeline: &TimelineType, fragment_id: &str) -> StorageLocation {
+        match timeline {
+            TimelineType::Primary => StorageLocation::Onchain {
+                program_id: "Glch89PrivacyNFTprogramID111111111111111111111".to_string(),
+                account: format!("fragment_{}", fragment_id),
+            },
+            TimelineType::Financial => StorageLocation::ShadowRealm {
+                access_path: format!("shadow/financial/{}", fragment_id),
+            },
+            _ => StorageLocation::Ipfs {
+                cid: format!("placeholder_cid_{}", fragment_id), // Will be updated after storage
+            },
+        }
+    }
+    
+    /// Estimate the storage cost of fracturing metadata of the given size at live prices
+    ///
+    /// Prices the same layout as `plan_fracture`, decoys and padding included.
+    #[cfg(feature = "onchain")]
+    pub async fn estimate_fracture_cost(
+        &self,
+        data_len: usize,
+        timeline_config: &HashMap<TimelineType, f32>,
+        options: &FractureOptions,
+    ) -> Result<FractureCostEstimate, String> {
+        let estimator = self.cost_estimator.as_ref().ok_or("No cost estimator configured")?;
+        
+        let mut estimates = Vec::new();
+        for (timeline, size, decoy) in Self::fracture_layout(data_len, timeline_config, options)? {
+            let location = Self::default_storage_location(&timeline, "estimate");
+            let estimate = estimator.estimate_fragment(&timeline, &location, size).await?;
+            estimates.push(FragmentCostEstimate { decoy, ..estimate });
+        }
+        
+        Ok(FractureCostEstimate::from_fragments(estimates))
+    }
+    
+    /// Plan the fragment layout of a fracture, priced at list rates
+    ///
+    /// Makes no network calls, so a fracture can be previewed before it is
+    /// committed.
+    pub fn plan_fracture(
+        data_len: usize,
+        timeline_config: &HashMap<TimelineType, f32>,
+        options: &FractureOptions,
+    ) -> Result<FractureCostEstimate, String> {
+        let estimates = Self::fracture_layout(data_len, timeline_config, options)?
+            .into_iter()
+            .map(|(timeline, size, decoy)| {
+                let location = Self::default_storage_location(&timeline, "estimate");
+                FragmentCostEstimate { decoy, ..FragmentCostEstimate::at_list_price(&timeline, &location, size) }
+            })
+            .collect();
+        
+        Ok(FractureCostEstimate::from_fragments(estimates))
+    }
+    
+    /// Lay out the fragments a fracture would store, as (timeline, stored size, decoy)
+    ///
+    /// Decoys are sized like the average real fragment and spread over the
+    /// real fragments' timelines in turn. Semantic fractures are laid out as
+    /// if split by the distribution, since section sizes depend on the
+    /// metadata itself.
+    fn fracture_layout(
+        data_len: usize,
+        timeline_config: &HashMap<TimelineType, f32>,
+        options: &FractureOptions,
+    ) -> Result<Vec<(TimelineType, usize, bool)>, String> {
+        // Validate timeline config
+        let total_percentage: f32 = timeline_config.values().sum();
+        if (total_percentage - 1.0).abs() > 0.001 {
+            return Err("Timeline configuration percentages must sum to 1.0".to_string());
+        }
+        
+        // Each fragment is sealed with its own nonce and authentication tag
+        let mut fragment_sizes: Vec<(TimelineType, usize)> = Self::compute_fragment_sizes(data_len, timeline_config)
+            .into_iter()
+            .filter(|(_, size)| *size > 0)
+            .map(|(timeline, size)| (timeline, size + FRAGMENT_OVERHEAD))
+            .collect();
+        fragment_sizes.sort_by(|a, b| format!("{:?}", a.0).cmp(&format!("{:?}", b.0)));
+        
+        let mut layout: Vec<(TimelineType, usize, bool)> = fragment_sizes.iter()
+            .map(|(timeline, size)| (timeline.clone(), options.padded_len(*size), false))
+            .collect();
+        
+        let sealed_len: usize = fragment_sizes.iter().map(|(_, size)| size).sum();
+        let decoy_size = options.padded_len(sealed_len / fragment_sizes.len().max(1));
+        for (timeline, _) in fragment_sizes.iter().cycle().take(options.decoy_count) {
+            layout.push((timeline.clone(), decoy_size, true));
+        }
+        
+        Ok(layout)
+    }
+    
+    /// Split metadata into fragments across timelines
+    #[tracing::instrument(skip_all, fields(nft_mint = nft_mint, bytes = metadata.len()))]
+    pub async fn fracture_metadata(
+        &self,
+        nft_mint: &str,
+        metadata: &[u8],
+        encryption_key: &[u8],
+        timeline_config: HashMap<TimelineType, f32>, // Timeline type -> fragment percentage
+        options: &FractureOptions,
+    ) -> Result<FragmentManifest, String> {
+        log::info!("Fracturing metadata across timelines...");
+        
+        // Validate timeline config
+        let total_percentage: f32 = timeline_config.values().sum();
+        if (total_percentage - 1.0).abs() > 0.001 {
+            return Err("Timeline configuration percentages must sum to 1.0".to_string());
+        }
+        
+        // Fragment IDs, decoys and their order must be unpredictable: an RNG seeded from
+        // the clock could be replayed from the fragment timestamps to pick out the decoys
+        let mut rng = OsRng;
+        
+        // Split the metadata per timeline: into JSON sections by field, or byte ranges by percentage
+        let (slices, timelines): (Vec<(TimelineType, Vec<u8>)>, Vec<TimelineType>) = match &options.sections {
+            Some(mapping) => (mapping.split(metadata)?, mapping.timelines()),
+            None => {
+                let mut offset = 0;
+                let slices = Self::compute_fragment_sizes(metadata.len(), &timeline_config)
+                    .into_iter()
+                    .filter(|(_, size)| *size > 0)
+                    .map(|(timeline, size)| {
+                        let end = std::cmp::min(offset + size, metadata.len());
+                        let slice = metadata[offset..end].to_vec();
+                        offset = end;
+                        (timeline, slice)
+                    })
+                    .collect();
+                (slices, timeline_config.keys().cloned().collect())
+            },
+        };
+        
+        // Derive a key per timeline, so one timeline key exposes only its own fragments
+        let timeline_keys: HashMap<TimelineType, SecretBytes> = timelines.iter()
+            .map(|timeline| (timeline.clone(), derive_timeline_key(encryption_key, timeline)))
+            .collect();
+        
+        // Create fragments, listing each timeline's real fragments before padding for partial reassembly
         let mut fragments: Vec<MetadataFragment> = Vec::new();
-        let mut offset = 0;
+        let mut timeline_index: HashMap<TimelineType, Vec<IndexedFragment>> = HashMap::new();
         
-        for (timeline, size) in fragment_sizes {
-            if size == 0 {
-                continue;
-            }
-            
-            // Generate unique ID for this fragment
-            let fragment_id = self.generate_fragment_id(&timeline, nft_mint, rng.gen());
-            
-            // Extract data slice for this fragment
-            let end = std::cmp::min(offset + size, encrypted_metadata.len());
-            let data = encrypted_metadata[offset..end].to_vec();
-            offset = end;
-            
-            // Choose storage location based on timeline
-            let storage_location = match timeline {
-                TimelineType::Primary => StorageLocation::Onchain {
-                    program_id: "Glch89PrivacyNFTprogramID111111111111111111111".to_string(),
-                    account: format!("fragment_{}", fragment_id),
-                },
-                TimelineType::Financial => StorageLocation::ShadowRealm {
-                    access_path: format!("shadow/financial/{}", fragment_id),
+        for (timeline, slice) in slices {
+            // Encrypt this fragment's slice of the metadata under its timeline key, or
+            // convergently under a content key and addressed by content
+            let (fragment_id, data, content_key) = match &options.convergence_key {
+                Some(convergence_key) => {
+                    let (data, content_key) = keys::seal_convergent(&slice, &timeline, convergence_key)?;
+                    (keys::content_id(&data), data, Some(content_key))
                 },
-                _ => StorageLocation::Ipfs {
-                    cid: format!("placeholder_cid_{}", fragment_id), // Will be updated after storage
+                None => {
+                    let context = keys::fragment_context(nft_mint, options.owner.as_deref(), &timeline);
+                    let data = keys::seal_fragment(&slice, &timeline_keys[&timeline], &context)?;
+                    (self.generate_fragment_id(&timeline, nft_mint, rng.gen()), data, None)
                 },
             };
             
+            timeline_index.entry(timeline.clone()).or_default().push(IndexedFragment {
+                fragment_id: fragment_id.clone(),
+                length: data.len(),
+                content_key,
+            });
+            
+            // Choose storage location based on timeline
+            let storage_location = Self::default_storage_location(&timeline, &fragment_id);
+            
             let fragment = MetadataFragment {
                 id: fragment_id,
                 timeline: timeline.clone(),
@@ -165,6 +472,54 @@ impl TimelineShifter {
             fragments.push(fragment);
         }
         
+        let real_ids: Vec<String> = fragments.iter().map(|f| f.id.clone()).collect();
+        
+        // Generate decoys shaped like the real fragments and interleave them
+        if options.decoy_count > 0 {
+            let templates: Vec<(TimelineType, usize)> = fragments.iter()
+                .map(|f| (f.timeline.clone(), f.data.len()))
+                .collect();
+            
+            for (timeline, data) in decoy::generate_decoys(&templates, options.decoy_count, &mut rng) {
+                let fragment_id = self.generate_fragment_id(&timeline, nft_mint, rng.gen());
+                let storage_location = Self::default_storage_location(&timeline, &fragment_id);
+                
+                fragments.push(MetadataFragment {
+                    id: fragment_id,
+                    timeline,
+                    data,
+                    links: Vec::new(),
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                    storage_location,
+                });
+            }
+            
+            fragments.shuffle(&mut rng);
+        }
+        
+        // Pad every fragment, decoys included, so stored sizes fall into a few buckets
+        let mut data_lengths = BTreeMap::new();
+        if !options.size_buckets.is_empty() {
+            for fragment in fragments.iter_mut() {
+                if real_ids.contains(&fragment.id) {
+                    data_lengths.insert(fragment.id.clone(), fragment.data.len());
+                }
+                
+                let mut padding = vec![0u8; options.padded_len(fragment.data.len()) - fragment.data.len()];
+                if fragment.id.starts_with(CONTENT_ID_PREFIX) {
+                    // Pad content-addressed fragments deterministically so duplicates stay identical
+                    let seed = digest::digest(&digest::SHA256, &fragment.data);
+                    StdRng::from_seed(seed.as_ref().try_into().unwrap()).fill(&mut padding[..]);
+                } else {
+                    rand::thread_rng().fill(&mut padding[..]);
+                }
+                fragment.data.extend(padding);
+            }
+        }
+        
         // Create links between fragments
         for i in 0..fragments.len() {
             for j in 0..fragments.len() {
@@ -175,61 +530,230 @@ impl TimelineShifter {
         }
         
         // Store fragments using appropriate adapters
-        let mut fragment_ids = Vec::new();
-        let mut store_tasks = Vec::new();
+        let fragment_ids: Vec<String> = fragments.iter().map(|f| f.id.clone()).collect();
+        
+        let mut adapter_receipts: HashMap<String, String> = HashMap::new();
         
-        for fragment in fragments.iter() {
-            fragment_ids.push(fragment.id.clone());
+        if self.obfuscation.as_ref().map_or(false, |config| config.shuffle) {
+            // One at a time in random order, so upload order and bursts don't mirror the fracture
+            let mut upload_order: Vec<&MetadataFragment> = fragments.iter().collect();
+            upload_order.shuffle(&mut rand::thread_rng());
             
-            // Get the appropriate adapter for this timeline
-            let adapter = if let Some(adapter) = self.adapters.get(&fragment.timeline) {
-                adapter
-            } else {
-                &self.primary_adapter
-            };
+            for fragment in upload_order {
+                let receipt = self.adapter_for(&fragment.timeline)
+                    .store_fragment(fragment)
+                    .await
+                    .map_err(|e| format!("Failed to store fragment: {}", e))?;
+                adapter_receipts.insert(fragment.id.clone(), receipt);
+            }
+        } else {
+            // Hand each adapter all of its fragments at once, so adapters that bundle uploads can
+            let mut batches: HashMap<Option<&TimelineType>, Vec<&MetadataFragment>> = HashMap::new();
+            for fragment in &fragments {
+                let adapter_timeline = self.adapters.get_key_value(&fragment.timeline).map(|(timeline, _)| timeline);
+                batches.entry(adapter_timeline).or_default().push(fragment);
+            }
             
-            // Store the fragment
-            let fragment_clone = fragment.clone();
-            let store_task = async move {
-                adapter.store_fragment(&fragment_clone).await
-            };
+            let store_tasks = batches.iter().map(|(adapter_timeline, batch)| {
+                let adapter = match adapter_timeline {
+                    Some(timeline) => self.adapters[*timeline].as_ref(),
+                    None => self.primary_adapter.as_ref(),
+                };
+                adapter.store_fragments(batch)
+            });
             
-            store_tasks.push(store_task);
-        }
-        
-        // Wait for all storage operations to complete
-        let results = join_all(store_tasks).await;
-        
-        // Check for errors
-        for result in results {
-            if let Err(e) = result {
-                return Err(format!("Failed to store fragment: {}", e));
+            // Wait for all storage operations to complete
+            for (batch, result) in batches.values().zip(join_all(store_tasks).await) {
+                let receipts = result.map_err(|e| format!("Failed to store fragment: {}", e))?;
+                if receipts.len() != batch.len() {
+                    return Err(format!("Storage returned {} receipts for {} fragments", receipts.len(), batch.len()));
+                }
+                adapter_receipts.extend(batch.iter().map(|fragment| fragment.id.clone()).zip(receipts));
             }
         }
         
+        let receipts: Vec<FragmentReceipt> = fragments.iter()
+            .map(|fragment| FragmentReceipt::new(fragment, adapter_receipts.remove(&fragment.id).unwrap_or_default()))
+            .collect();
+        
         // Cache fragments
-        for fragment in fragments {
-            self.fragment_cache.insert(fragment.id.clone(), fragment);
+        self.fragment_cache.write().unwrap()
+            .extend(fragments.into_iter().map(|fragment| (fragment.id.clone(), fragment)));
+        
+        // Record which fragments are real under encryption when decoys are present
+        self.register_owner(nft_mint, &fragment_ids);
+        let mut manifest = FragmentManifest::new(nft_mint, fragment_ids);
+        manifest.owner = options.owner.clone();
+        manifest.receipts = receipts;
+        manifest.sectioned = options.sections.is_some();
+        manifest.timeline_keys = timeline_keys.iter()
+            .map(|(timeline, key)| (timeline.label(), timeline_key_id(key.expose())))
+            .collect();
+        for (timeline, entries) in &timeline_index {
+            manifest.seal_timeline_index(timeline, entries, timeline_keys[timeline].expose())?;
+        }
+        if options.decoy_count > 0 {
+            manifest.seal_index(&real_ids, encryption_key)?;
         }
+        if !data_lengths.is_empty() {
+            manifest.seal_lengths(&data_lengths, encryption_key)?;
+        }
+        
+        self.audit(AuditEvent::MetadataFractured {
+            nft_mint: nft_mint.to_string(),
+            fragment_count: manifest.fragment_ids.len(),
+        })?;
         
-        Ok(fragment_ids)
+        Ok(manifest)
+    }
+    
+    /// Reassemble metadata from a manifest, skipping any decoy fragments and stripping padding
+    pub async fn reassemble_from_manifest(
+        &self,
+        manifest: &FragmentManifest,
+        encryption_key: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let real_ids = manifest.real_fragment_ids(encryption_key)?;
+        let lengths = manifest.fragment_lengths(encryption_key)?;
+        self.register_owner(&manifest.nft_mint, &real_ids);
+        let assembly = if manifest.timeline_keys.is_empty() {
+            Assembly::Combined
+        } else if manifest.sectioned {
+            Assembly::Sections
+        } else {
+            Assembly::ByteRanges
+        };
+        let content_keys = if real_ids.iter().any(|id| id.starts_with(CONTENT_ID_PREFIX)) {
+            manifest.content_keys(encryption_key)?
+        } else {
+            HashMap::new()
+        };
+        self.reassemble(&real_ids, encryption_key, lengths.as_ref(), assembly, &content_keys, Some(manifest)).await
     }
     
-    /// Reassemble metadata from fragments
+    /// Reassemble metadata from unpadded fragments encrypted together under the metadata key
+    ///
+    /// Fragments fractured with per-timeline keys or padding need the
+    /// manifest; use `reassemble_from_manifest` for them.
     pub async fn reassemble_metadata(
-        &mut self,
+        &self,
         fragment_ids: &[String],
         encryption_key: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        self.reassemble(fragment_ids, encryption_key, None, Assembly::Combined, &HashMap::new(), None).await
+    }
+    
+    /// Reassemble metadata from fragments, truncating each to its length if given
+    ///
+    /// Fragments with a content key are opened with it rather than their timeline key.
+    /// Fragments under timeline keys must be bound to the NFT and owner of `manifest`.
+    #[tracing::instrument(skip_all, fields(fragments = fragment_ids.len()))]
+    async fn reassemble(
+        &self,
+        fragment_ids: &[String],
+        encryption_key: &[u8],
+        lengths: Option<&BTreeMap<String, usize>>,
+        assembly: Assembly,
+        content_keys: &HashMap<String, SecretBytes>,
+        manifest: Option<&FragmentManifest>,
     ) -> Result<Vec<u8>, String> {
         log::info!("Reassembling metadata from {} fragments...", fragment_ids.len());
         
+        let mut by_id = self.collect_fragments(fragment_ids).await?;
+        
+        // Combine fragment data in manifest order; cached and retrieved
+        // fragments arrive interleaved and timestamps only have second precision
+        let mut timeline_keys: HashMap<TimelineType, SecretBytes> = HashMap::new();
+        let mut pieces = Vec::new();
+        for id in fragment_ids {
+            let fragment = by_id.remove(id).ok_or_else(|| format!("Missing fragment: {}", id))?;
+            let data = match lengths.and_then(|lengths| lengths.get(id)) {
+                Some(len) => fragment.data.get(..*len)
+                    .ok_or_else(|| format!("Fragment {} is shorter than its recorded length", id))?,
+                None => &fragment.data[..],
+            };
+            
+            if assembly == Assembly::Combined {
+                pieces.push(data.to_vec());
+            } else {
+                let (key, context): (&SecretBytes, _) = match (content_keys.get(id), manifest) {
+                    (Some(content_key), _) => (content_key, keys::convergent_context(&fragment.timeline)),
+                    (None, Some(manifest)) => (
+                        timeline_keys.entry(fragment.timeline.clone())
+                            .or_insert_with(|| derive_timeline_key(encryption_key, &fragment.timeline)),
+                        manifest.fragment_context(&fragment.timeline),
+                    ),
+                    (None, None) => return Err(format!("Fragment {} needs its manifest to be decrypted", id)),
+                };
+                pieces.push(keys::open_fragment(data, key, &context)
+                    .map_err(|e| format!("Failed to decrypt fragment {}: {}", id, e))?);
+            }
+        }
+        
+        let decrypted_data = match assembly {
+            Assembly::Combined => self.decrypt_data(&pieces.concat(), encryption_key)?,
+            Assembly::ByteRanges => pieces.concat(),
+            Assembly::Sections => sections::merge_sections(&pieces)?,
+        };
+        
+        self.audit(AuditEvent::MetadataReassembled { fragment_ids: fragment_ids.to_vec() })?;
+        
+        Ok(decrypted_data)
+    }
+    
+    /// Reassemble only one timeline's share of the metadata, with that timeline's key
+    ///
+    /// Fetches just the timeline's real fragments, so a partner app given
+    /// e.g. the Activity key never pulls the Financial fragments.
+    #[tracing::instrument(skip_all, fields(nft_mint = %manifest.nft_mint, timeline = %timeline.label()))]
+    pub async fn reassemble_timeline(
+        &self,
+        manifest: &FragmentManifest,
+        timeline: &TimelineType,
+        timeline_key: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let key_id = manifest.timeline_key_id(timeline)
+            .ok_or_else(|| format!("Manifest for {} has no {} timeline key", manifest.nft_mint, timeline.label()))?;
+        if timeline_key_id(timeline_key) != key_id {
+            return Err(format!("Key is not the {} timeline key of {}", timeline.label(), manifest.nft_mint));
+        }
+        
+        let entries = manifest.timeline_fragments(timeline, timeline_key)?;
+        let fragment_ids: Vec<String> = entries.iter().map(|entry| entry.fragment_id.clone()).collect();
+        self.register_owner(&manifest.nft_mint, &fragment_ids);
+        log::info!("Reassembling {} timeline from {} fragments...", timeline.label(), fragment_ids.len());
+        
+        let mut by_id = self.collect_fragments(&fragment_ids).await?;
+        let timeline_key = SecretBytes::from(timeline_key);
+        
+        let mut data = Vec::new();
+        for entry in &entries {
+            let id = &entry.fragment_id;
+            let fragment = by_id.remove(id).ok_or_else(|| format!("Missing fragment: {}", id))?;
+            let sealed = fragment.data.get(..entry.length)
+                .ok_or_else(|| format!("Fragment {} is shorter than its recorded length", id))?;
+            let (key, context) = match &entry.content_key {
+                Some(content_key) => (content_key, keys::convergent_context(timeline)),
+                None => (&timeline_key, manifest.fragment_context(timeline)),
+            };
+            data.extend(keys::open_fragment(sealed, key, &context)
+                .map_err(|e| format!("Failed to decrypt fragment {}: {}", id, e))?);
+        }
+        
+        self.audit(AuditEvent::MetadataReassembled { fragment_ids })?;
+        
+        Ok(data)
+    }
+    
+    /// Get fragments by ID from the cache, retrieving and caching any that aren't there
+    async fn collect_fragments(&self, fragment_ids: &[String]) -> Result<HashMap<String, MetadataFragment>, String> {
         // Collect fragments
         let mut fragments = Vec::new();
         let mut retrieve_tasks = Vec::new();
         
         for id in fragment_ids {
             // Check cache first
-            if let Some(fragment) = self.fragment_cache.get(id) {
+            if let Some(fragment) = self.fragment_cache.read().unwrap().get(id) {
                 fragments.push(fragment.clone());
                 continue;
             }
@@ -253,7 +777,7 @@ impl TimelineShifter {
             match result {
                 Ok(fragment) => {
                     fragments.push(fragment.clone());
-                    self.fragment_cache.insert(fragment.id.clone(), fragment);
+                    self.fragment_cache.write().unwrap().insert(fragment.id.clone(), fragment);
                 },
                 Err(e) => {
                     return Err(format!("Failed to retrieve fragment: {}", e));
@@ -261,19 +785,88 @@ impl TimelineShifter {
             }
         }
         
-        // Sort fragments by timestamp to ensure correct order
-        fragments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(fragments.into_iter()
+            .map(|fragment| (fragment.id.clone(), fragment))
+            .collect())
+    }
+    
+    /// Delete fragments from storage and the local cache
+    pub async fn delete_fragments(&self, fragment_ids: &[String]) -> Result<(), String> {
+        log::info!("Deleting {} fragments...", fragment_ids.len());
         
-        // Combine fragment data
-        let mut combined_data = Vec::new();
-        for fragment in fragments {
-            combined_data.extend_from_slice(&fragment.data);
+        for id in fragment_ids {
+            // Use the timeline adapter if the fragment is cached, otherwise try all adapters
+            let cached = self.fragment_cache.write().unwrap().remove(id);
+            let adapter = cached
+                .as_ref()
+                .and_then(|fragment| self.adapters.get(&fragment.timeline));
+            
+            match adapter {
+                Some(adapter) => adapter.delete_fragment(id).await?,
+                None => {
+                    for (_, adapter) in &self.adapters {
+                        if let Ok(true) = adapter.fragment_exists(id).await {
+                            adapter.delete_fragment(id).await?;
+                        }
+                    }
+                    self.primary_adapter.delete_fragment(id).await?;
+                },
+            }
         }
         
-        // Decrypt the combined data
-        let decrypted_data = self.decrypt_data(&combined_data, encryption_key)?;
+        Ok(())
+    }
+    
+    /// Drop fragments from the local cache, leaving them in storage
+    ///
+    /// Reassembling them afterwards reads them back from their adapters.
+    pub fn evict_cached(&self, fragment_ids: &[String]) {
+        let mut cache = self.fragment_cache.write().unwrap();
+        for id in fragment_ids {
+            cache.remove(id);
+        }
+    }
+    
+    /// Find which fragments no storage adapter holds any more
+    pub async fn missing_fragments(&self, fragment_ids: &[String]) -> Result<Vec<String>, String> {
+        let mut missing = Vec::new();
         
-        Ok(decrypted_data)
+        for id in fragment_ids {
+            let mut found = self.primary_adapter.fragment_exists(id).await?;
+            for adapter in self.adapters.values() {
+                if found {
+                    break;
+                }
+                found = adapter.fragment_exists(id).await?;
+            }
+            
+            if !found {
+                missing.push(id.clone());
+            }
+        }
+        
+        Ok(missing)
+    }
+    
+    /// Check stored fragments against the receipts they were committed with
+    ///
+    /// Fragments are read from storage, not the cache. Returns the IDs of
+    /// fragments that are missing or no longer match their receipt.
+    pub async fn verify_receipts(&self, receipts: &[FragmentReceipt]) -> Vec<String> {
+        let mut mismatched = Vec::new();
+        
+        for receipt in receipts {
+            match self.retrieve_fragment(&receipt.fragment_id).await {
+                Ok(fragment) if receipt.matches(&fragment) => {},
+                Ok(_) => mismatched.push(receipt.fragment_id.clone()),
+                Err(e) => {
+                    log::warn!("Failed to retrieve fragment {} for verification: {}", receipt.fragment_id, e);
+                    mismatched.push(receipt.fragment_id.clone());
+                },
+            }
+        }
+        
+        mismatched
     }
     
     /// Retrieve a specific fragment by ID
@@ -291,23 +884,9 @@ impl TimelineShifter {
         self.primary_adapter.retrieve_fragment(id).await
     }
     
-    /// Encrypt data using the provided key
-    fn encrypt_data(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
-        // This is a simplified implementation
-        // In a real system, use proper encryption like ChaCha20Poly1305
-        
-        // Use HMAC as a simple encryption method for demonstration
-        let key = hmac::Key::new(hmac::HMAC_SHA256, key);
-        let tag = hmac::sign(&key, data);
-        
-        // Combine tag and data
-        let mut encrypted = tag.as_ref().to_vec();
-        encrypted.extend_from_slice(data);
-        
-        Ok(encrypted)
-    }
-    
-    /// Decrypt data using the provided key
+    /// Decrypt data fractured before per-timeline keys, using the provided key
+    ///
+    /// Such fragments only carry an HMAC tag over the combined data.
     fn decrypt_data(&self, encrypted: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
         // This is a simplified implementation
         // In a real system, use proper decryption like ChaCha20Poly1305
@@ -338,6 +917,601 @@ impl Clone for TimelineShifter {
             primary_adapter: self.primary_adapter.clone_adapter(),
             adapters: self.adapters.iter().map(|(k, v)| (k.clone(), v.clone_adapter())).collect(),
             fragment_cache: self.fragment_cache.clone(),
+            #[cfg(feature = "onchain")]
+            cost_estimator: self.cost_estimator.clone(),
+            audit_log: self.audit_log.clone(),
+            obfuscation: self.obfuscation.clone(),
+            planned_writes: self.planned_writes.clone(),
+            deduplicated: self.deduplicated.clone(),
+            access_log: self.access_log.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::{btree_map, vec};
+    use proptest::prelude::*;
+    
+    fn memory_shifter(adapter: &InMemoryAdapter) -> TimelineShifter {
+        TimelineShifter::new(Box::new(adapter.clone()), HashMap::new())
+    }
+    
+    #[tokio::test]
+    async fn test_fracture_reassemble_round_trip() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [7u8; 32];
+        let metadata = b"{\"trait_type\":\"Secret Code\",\"value\":\"GLITCH-8983-ALPHA\"}";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        ).await.unwrap();
+        
+        assert_eq!(adapter.len(), manifest.fragment_ids.len());
+        
+        // A fresh shifter has an empty cache and must read from storage
+        let reader = memory_shifter(&adapter);
+        let reassembled = reader.reassemble_from_manifest(&manifest, &key).await.unwrap();
+        
+        assert_eq!(metadata, &reassembled[..]);
+    }
+    
+    #[tokio::test]
+    async fn test_stored_fragments_match_their_receipts() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            b"committed metadata",
+            &[6u8; 32],
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(1),
+        ).await.unwrap();
+        
+        let receipt_ids: Vec<String> = manifest.receipts.iter().map(|receipt| receipt.fragment_id.clone()).collect();
+        assert_eq!(receipt_ids, manifest.fragment_ids);
+        assert!(shifter.verify_receipts(&manifest.receipts).await.is_empty());
+        
+        // Altering a stored fragment breaks its receipt
+        let mut fragment = adapter.retrieve_fragment(&manifest.fragment_ids[0]).await.unwrap();
+        fragment.data[0] ^= 1;
+        adapter.store_fragment(&fragment).await.unwrap();
+        assert_eq!(shifter.verify_receipts(&manifest.receipts).await, vec![manifest.fragment_ids[0].clone()]);
+    }
+    
+    #[tokio::test]
+    async fn test_fracture_and_reassembly_are_audited() {
+        use crate::audit::{verify_chain, AuditSink, MemorySink};
+        
+        let adapter = InMemoryAdapter::new();
+        let sink = Arc::new(MemorySink::new());
+        let shifter = memory_shifter(&adapter).with_audit_log(Arc::new(AuditLog::new(sink.clone())));
+        let key = [3u8; 32];
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            b"audited metadata",
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        ).await.unwrap();
+        shifter.reassemble_from_manifest(&manifest, &key).await.unwrap();
+        
+        let entries = sink.entries().unwrap();
+        verify_chain(&entries).unwrap();
+        assert_eq!(entries[0].event, AuditEvent::MetadataFractured {
+            nft_mint: "mint".to_string(),
+            fragment_count: manifest.fragment_ids.len(),
+        });
+        assert_eq!(entries[1].event, AuditEvent::MetadataReassembled { fragment_ids: manifest.fragment_ids.clone() });
+    }
+    
+    #[tokio::test]
+    async fn test_storage_retrievals_are_logged_per_nft() {
+        use crate::audit::{AuditSink, MemorySink};
+        
+        let adapter = InMemoryAdapter::new();
+        let sink = Arc::new(MemorySink::new());
+        let shifter = memory_shifter(&adapter).with_access_log(Arc::new(AuditLog::new(sink.clone())));
+        let key = [5u8; 32];
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            b"metadata fetched through a gateway",
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        ).await.unwrap();
+        
+        // Cached fragments never reach storage
+        shifter.reassemble_from_manifest(&manifest, &key).await.unwrap();
+        assert!(sink.entries().unwrap().is_empty());
+        
+        shifter.evict_cached(&manifest.fragment_ids);
+        as_requester("203.0.113.7", shifter.reassemble_from_manifest(&manifest, &key)).await.unwrap();
+        
+        let retrieved: Vec<&String> = sink.entries().unwrap().iter()
+            .filter_map(|entry| match &entry.event {
+                AuditEvent::FragmentRetrieved { fragment_id, requester, .. } => {
+                    assert_eq!(requester.as_deref(), Some("203.0.113.7"));
+                    manifest.fragment_ids.iter().find(|id| *id == fragment_id)
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(retrieved.len(), manifest.fragment_ids.len());
+        
+        let stats = shifter.retrieval_stats("mint").unwrap();
+        assert_eq!(stats.retrievals, manifest.fragment_ids.len() as u64);
+        assert_eq!(stats.failures, 0);
+        assert_eq!(stats.requesters["203.0.113.7"], stats.retrievals);
+        assert_eq!(shifter.retrieval_stats("other mint").unwrap(), RetrievalStats::default());
+    }
+    
+    #[tokio::test]
+    async fn test_decoys_are_skipped_on_reassembly() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [9u8; 32];
+        let metadata = b"decoy-protected metadata payload";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(3),
+        ).await.unwrap();
+        
+        let real_ids = manifest.real_fragment_ids(&key).unwrap();
+        assert_eq!(manifest.fragment_ids.len(), real_ids.len() + 3);
+        
+        let reader = memory_shifter(&adapter);
+        let reassembled = reader.reassemble_from_manifest(&manifest, &key).await.unwrap();
+        
+        assert_eq!(metadata, &reassembled[..]);
+    }
+    
+    #[tokio::test]
+    async fn test_decoys_differ_between_fractures_in_the_same_second() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [9u8; 32];
+        
+        let mut decoys = Vec::new();
+        for _ in 0..2 {
+            let manifest = shifter.fracture_metadata(
+                "mint",
+                b"decoy-protected metadata payload",
+                &key,
+                TimelineType::default_distribution(),
+                &FractureOptions::with_decoys(3),
+            ).await.unwrap();
+            
+            let real_ids = manifest.real_fragment_ids(&key).unwrap();
+            for id in manifest.fragment_ids.iter().filter(|id| !real_ids.contains(id)) {
+                decoys.push((id.clone(), adapter.retrieve_fragment(id).await.unwrap().data));
+            }
+        }
+        
+        // Neither decoy IDs nor decoy bytes repeat, so they can't be regenerated from a timestamp
+        let (first, second) = decoys.split_at(3);
+        for (id, data) in first {
+            assert!(second.iter().all(|(other_id, other_data)| other_id != id && other_data != data));
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_stored_fragments_share_no_header_tag() {
+        use crate::quantum_veil::{is_envelope, Envelope};
+        
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [9u8; 32];
+        let metadata = b"decoy-protected metadata payload";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(3),
+        ).await.unwrap();
+        
+        // Decoys look like sealed fragments, and no two fragments name the same key
+        let mut key_ids = std::collections::HashSet::new();
+        for id in &manifest.fragment_ids {
+            let data = adapter.retrieve_fragment(id).await.unwrap().data;
+            assert!(is_envelope(&data));
+            assert!(key_ids.insert(Envelope::parse(&data).unwrap().key_id));
+        }
+        
+        let reassembled = memory_shifter(&adapter).reassemble_from_manifest(&manifest, &key).await.unwrap();
+        assert_eq!(metadata, &reassembled[..]);
+    }
+    
+    #[tokio::test]
+    async fn test_drift_keeps_old_fragments_until_committed() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [7u8; 32];
+        let metadata = b"metadata drifting between epochs";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        ).await.unwrap();
+        let old_ids = manifest.fragment_ids.clone();
+        
+        let mut scheduler = DriftScheduler::new(0);
+        scheduler.track(manifest, &key, TimelineType::default_distribution(), FractureOptions::default());
+        
+        // Until the new hash is published, the old fragments still reassemble
+        let result = scheduler.drift(&shifter, "mint").await.unwrap();
+        assert_eq!(result.manifest.epoch, 1);
+        let current = scheduler.manifest("mint").unwrap().clone();
+        assert_eq!(current.fragment_ids, old_ids);
+        assert_eq!(metadata, &shifter.reassemble_from_manifest(&current, &key).await.unwrap()[..]);
+        
+        // A retried drift republishes the same hash instead of re-fracturing
+        assert_eq!(scheduler.drift(&shifter, "mint").await.unwrap().config_hash, result.config_hash);
+        
+        scheduler.commit(&shifter, "mint").await.unwrap();
+        assert_eq!(scheduler.manifest("mint").unwrap().hash(), result.config_hash);
+        for id in &old_ids {
+            assert!(adapter.retrieve_fragment(id).await.is_err());
+        }
+        assert!(scheduler.commit(&shifter, "mint").await.is_err());
+        
+        let reassembled = memory_shifter(&adapter).reassemble_from_manifest(&result.manifest, &key).await.unwrap();
+        assert_eq!(metadata, &reassembled[..]);
+    }
+    
+    #[tokio::test]
+    async fn test_padded_fragments_hide_their_sizes() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [8u8; 32];
+        let metadata = b"metadata padded during fracture";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(2).with_padding(&STANDARD_SIZE_BUCKETS),
+        ).await.unwrap();
+        
+        // Real fragments and decoys alike are stored at the smallest bucket size
+        for id in &manifest.fragment_ids {
+            assert_eq!(adapter.retrieve_fragment(id).await.unwrap().data.len(), 1024);
+        }
+        assert_eq!(manifest.fragment_lengths(&key).unwrap().unwrap().len(), manifest.fragment_ids.len() - 2);
+        assert!(manifest.fragment_lengths(&[1u8; 32]).is_err());
+        
+        let reader = memory_shifter(&adapter);
+        assert_eq!(reader.reassemble_from_manifest(&manifest, &key).await.unwrap(), metadata);
+    }
+    
+    #[tokio::test]
+    async fn test_timeline_keys_open_only_their_own_fragments() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [2u8; 32];
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            b"metadata sealed under per-timeline keys",
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default(),
+        ).await.unwrap();
+        
+        let social_key = derive_timeline_key(&key, &TimelineType::Social);
+        assert_eq!(manifest.timeline_keys.len(), 5);
+        assert_eq!(manifest.timeline_key_id(&TimelineType::Social), Some(timeline_key_id(social_key.expose()).as_str()));
+        
+        // The Social key opens the Social fragment and nothing else
+        for id in &manifest.fragment_ids {
+            let fragment = adapter.retrieve_fragment(id).await.unwrap();
+            let opened = keys::open_fragment(&fragment.data, &social_key, &manifest.fragment_context(&TimelineType::Social));
+            assert_eq!(opened.is_ok(), fragment.timeline == TimelineType::Social);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_reassemble_timeline_fetches_only_its_fragments() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [1u8; 32];
+        let metadata = b"activity and financial history split over timelines";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(2).with_padding(&STANDARD_SIZE_BUCKETS),
+        ).await.unwrap();
+        
+        // A reader with only the Activity key fetches just the Activity fragment
+        let activity_key = derive_timeline_key(&key, &TimelineType::Activity);
+        let reader = memory_shifter(&adapter);
+        let activity = reader.reassemble_timeline(&manifest, &TimelineType::Activity, activity_key.expose()).await.unwrap();
+        
+        assert!(!activity.is_empty());
+        assert!(metadata.windows(activity.len()).any(|window| window == &activity[..]));
+        let cached: Vec<MetadataFragment> = reader.fragment_cache.read().unwrap().values().cloned().collect();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].timeline, TimelineType::Activity);
+        
+        let financial_key = derive_timeline_key(&key, &TimelineType::Financial);
+        assert!(reader.reassemble_timeline(&manifest, &TimelineType::Activity, financial_key.expose()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_fragments_are_bound_to_their_nft_and_owner() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [6u8; 32];
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            b"metadata bound to its NFT and owner",
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(1).with_owner("owner"),
+        ).await.unwrap();
+        assert_eq!(manifest.owner.as_deref(), Some("owner"));
+        assert_eq!(shifter.reassemble_from_manifest(&manifest, &key).await.unwrap(), b"metadata bound to its NFT and owner");
+        
+        // The same fragments listed under another owner or NFT don't decrypt
+        let transferred = FragmentManifest { owner: Some("thief".to_string()), ..manifest.clone() };
+        assert!(shifter.reassemble_from_manifest(&transferred, &key).await.is_err());
+        let transplanted = FragmentManifest { nft_mint: "other-mint".to_string(), ..manifest.clone() };
+        assert!(shifter.reassemble_from_manifest(&transplanted, &key).await.is_err());
+        let activity_key = derive_timeline_key(&key, &TimelineType::Activity);
+        assert!(shifter.reassemble_timeline(&transplanted, &TimelineType::Activity, activity_key.expose()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_semantic_fracture_yields_usable_sections() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter);
+        let key = [4u8; 32];
+        let metadata = serde_json::json!({
+            "name": "Glitch #89",
+            "attributes": [{ "trait_type": "Origin", "value": "Neo-Tokyo" }],
+            "transaction_history": [{ "amount": 89, "to": "glitch.sol" }],
+        });
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            &serde_json::to_vec(&metadata).unwrap(),
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(1).with_sections(SectionMapping::default()),
+        ).await.unwrap();
+        assert_eq!(manifest.fragment_ids.len(), 4);
+        
+        // The Financial timeline alone is JSON holding just the transaction history
+        let financial_key = derive_timeline_key(&key, &TimelineType::Financial);
+        let financial = shifter.reassemble_timeline(&manifest, &TimelineType::Financial, financial_key.expose()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&financial).unwrap(),
+            serde_json::json!({ "transaction_history": metadata["transaction_history"] }),
+        );
+        
+        let reassembled = shifter.reassemble_from_manifest(&manifest, &key).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&reassembled).unwrap(), metadata);
+        
+        assert!(shifter.fracture_metadata(
+            "mint",
+            b"[1, 2, 3]",
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::default().with_sections(SectionMapping::default()),
+        ).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_identical_sections_are_stored_once() {
+        let adapter = InMemoryAdapter::new();
+        let shifter = memory_shifter(&adapter).with_deduplication();
+        let options = FractureOptions::default()
+            .with_sections(SectionMapping::default())
+            .with_convergence_key(SecretBytes::from(&[3u8; 32][..]))
+            .with_padding(&STANDARD_SIZE_BUCKETS);
+        let boilerplate = serde_json::json!([{ "trait_type": "Collection", "value": "Glitch Gang" }]);
+        
+        let mut fractured = Vec::new();
+        for (name, key) in [("Glitch #1", [1u8; 32]), ("Glitch #2", [2u8; 32])] {
+            let metadata = serde_json::json!({ "name": name, "attributes": boilerplate });
+            let manifest = shifter.fracture_metadata(
+                name,
+                &serde_json::to_vec(&metadata).unwrap(),
+                &key,
+                TimelineType::default_distribution(),
+                &options,
+            ).await.unwrap();
+            fractured.push((manifest, key, metadata));
+        }
+        
+        // The shared attributes section is stored once and referenced by both manifests
+        let shared: Vec<&String> = fractured[0].0.fragment_ids.iter()
+            .filter(|id| fractured[1].0.fragment_ids.contains(id))
+            .collect();
+        assert_eq!(shared.len(), 1);
+        assert!(shared[0].starts_with(CONTENT_ID_PREFIX));
+        assert_eq!(shifter.deduplicated_writes(), 1);
+        assert_eq!(adapter.len(), 3);
+        
+        // Deleting one NFT's fragments keeps the shared one for the other
+        shifter.delete_fragments(&fractured[0].0.fragment_ids).await.unwrap();
+        let (manifest, key, metadata) = &fractured[1];
+        let reassembled = memory_shifter(&adapter).reassemble_from_manifest(manifest, key).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&reassembled).unwrap(), *metadata);
+    }
+    
+    #[tokio::test]
+    async fn test_obfuscated_fragments_are_padded_to_buckets() {
+        let adapter = InMemoryAdapter::new();
+        let config = ObfuscationConfig {
+            min_delay: std::time::Duration::ZERO,
+            max_delay: std::time::Duration::from_millis(5),
+            ..ObfuscationConfig::for_threat_model(ThreatModel::CuriousProvider)
+        };
+        let padding_key = SecretBytes::from(&[4u8; 32][..]);
+        let shifter = memory_shifter(&adapter).with_obfuscation(config.clone(), padding_key.clone());
+        let key = [5u8; 32];
+        let metadata = b"padded metadata payload";
+        
+        let manifest = shifter.fracture_metadata(
+            "mint",
+            metadata,
+            &key,
+            TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(2),
+        ).await.unwrap();
+        
+        // Every stored fragment has the same bucketed size, hiding the split
+        for id in adapter.fragment_ids() {
+            assert_eq!(adapter.retrieve_fragment(&id).await.unwrap().data.len(), 256);
+        }
+        assert_eq!(config.padded_len(300), 512);
+        
+        let reader = memory_shifter(&adapter).with_obfuscation(config.clone(), padding_key);
+        assert_eq!(&reader.reassemble_from_manifest(&manifest, &key).await.unwrap()[..], metadata);
+        
+        // Without the padding key the lengths can't be recovered
+        let wrong_key = memory_shifter(&adapter).with_obfuscation(config, SecretBytes::from(&[6u8; 32][..]));
+        assert!(wrong_key.reassemble_from_manifest(&manifest, &key).await.is_err());
+    }
+    
+    #[test]
+    fn test_plan_fracture_matches_layout() {
+        let plan = TimelineShifter::plan_fracture(
+            1000 - 5 * FRAGMENT_OVERHEAD,
+            &TimelineType::default_distribution(),
+            &FractureOptions::with_decoys(2),
+        ).unwrap();
+        
+        let (decoys, real): (Vec<_>, Vec<_>) = plan.fragments.iter().partition(|f| f.decoy);
+        assert_eq!(decoys.len(), 2);
+        assert_eq!(real.iter().map(|f| f.size).sum::<usize>(), 1000);
+        
+        // Only the on-chain primary fragments pay rent
+        let primary = real.iter().find(|f| f.timeline == TimelineType::Primary).unwrap();
+        assert!(primary.lamports > 0);
+        assert!(plan.total_lamports >= primary.lamports);
+        
+        assert!(TimelineShifter::plan_fracture(10, &HashMap::new(), &FractureOptions::default()).is_err());
+    }
+    
+    #[cfg(feature = "onchain")]
+    #[tokio::test]
+    async fn test_live_estimate_counts_decoys_and_padding() {
+        use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+        
+        let mocks = HashMap::from([(RpcRequest::GetMinimumBalanceForRentExemption, serde_json::json!(5_000))]);
+        let estimator = CostEstimator::with_rpc_client(
+            RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+            "http://arweave.invalid",
+            0.15,
+        );
+        let shifter = memory_shifter(&InMemoryAdapter::new()).with_cost_estimator(estimator);
+        let distribution = TimelineType::default_distribution();
+        let options = FractureOptions::with_decoys(3).with_padding(&STANDARD_SIZE_BUCKETS);
+        
+        let estimate = shifter.estimate_fracture_cost(1000, &distribution, &options).await.unwrap();
+        let plan = TimelineShifter::plan_fracture(1000, &distribution, &options).unwrap();
+        
+        // The same fragments as the plan, priced at the live rent
+        let layout = |estimate: &FractureCostEstimate| estimate.fragments.iter()
+            .map(|f| (f.timeline.clone(), f.size, f.decoy))
+            .collect::<Vec<_>>();
+        assert_eq!(layout(&estimate), layout(&plan));
+        assert_eq!(estimate.fragments.iter().filter(|f| f.decoy).count(), 3);
+        assert!(estimate.fragments.iter().all(|f| STANDARD_SIZE_BUCKETS.contains(&f.size)));
+        
+        let onchain = estimate.fragments.iter().filter(|f| f.storage_type == "Onchain").count() as u64;
+        assert!(onchain > 0);
+        assert_eq!(estimate.total_lamports, onchain * 5_000);
+        assert_eq!(estimate.total_monthly_usd, plan.total_monthly_usd);
+    }
+    
+    /// Arbitrary non-empty distribution over the standard timelines
+    fn arb_distribution() -> impl Strategy<Value = HashMap<TimelineType, f32>> {
+        btree_map(0usize..5, 1u32..100, 1..=5).prop_map(|weights| {
+            let total: u32 = weights.values().sum();
+            let timelines = TimelineType::standard_timelines();
+            
+            weights.into_iter()
+                .map(|(i, weight)| (timelines[i].clone(), weight as f32 / total as f32))
+                .collect()
+        })
+    }
+    
+    proptest! {
+        #[test]
+        fn prop_fragment_sizes_cover_every_byte(
+            total in 0usize..100_000,
+            distribution in arb_distribution(),
+            skew in 0.0f32..0.001,
+        ) {
+            // Skew one percentage so the total lands anywhere in the accepted tolerance
+            let mut distribution = distribution;
+            if let Some(percentage) = distribution.values_mut().next() {
+                *percentage += skew;
+            }
+            
+            let sizes = TimelineShifter::compute_fragment_sizes(total, &distribution);
+            prop_assert_eq!(sizes.values().sum::<usize>(), total);
+        }
+        
+        #[test]
+        fn prop_fracture_reassemble_is_identity(
+            metadata in vec(any::<u8>(), 0..4096),
+            key in vec(any::<u8>(), 1..64),
+            distribution in arb_distribution(),
+            decoys in 0usize..4,
+            evict_from_cache in any::<bool>(),
+        ) {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            
+            let reassembled = runtime.block_on(async {
+                let adapter = InMemoryAdapter::new();
+                let shifter = memory_shifter(&adapter);
+                
+                let manifest = shifter.fracture_metadata(
+                    "mint",
+                    &metadata,
+                    &key,
+                    distribution,
+                    &FractureOptions::with_decoys(decoys),
+                ).await.unwrap();
+                
+                // Mix cached and retrieved fragments
+                if evict_from_cache {
+                    for id in manifest.fragment_ids.iter().step_by(2) {
+                        shifter.fragment_cache.write().unwrap().remove(id);
+                    }
+                }
+                
+                shifter.reassemble_from_manifest(&manifest, &key).await.unwrap()
+            });
+            
+            prop_assert_eq!(metadata, reassembled);
         }
     }
 }
@@ -0,0 +1,102 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use quantum_veil_types::TimelineType;
+
+/// Which timeline each top-level metadata field is stored on, for semantic fracturing
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionMapping {
+    /// Timeline of each mapped field
+    pub fields: BTreeMap<String, TimelineType>,
+    /// Timeline of every field not in `fields`
+    pub default_timeline: TimelineType,
+}
+
+impl Default for SectionMapping {
+    /// Attributes on Identity, transaction history on Financial, activity on
+    /// Activity, relationships on Social, and everything else on Primary
+    fn default() -> Self {
+        Self::new(TimelineType::Primary)
+            .with_field("attributes", TimelineType::Identity)
+            .with_field("transactions", TimelineType::Financial)
+            .with_field("transaction_history", TimelineType::Financial)
+            .with_field("activity", TimelineType::Activity)
+            .with_field("interactions", TimelineType::Activity)
+            .with_field("relationships", TimelineType::Social)
+    }
+}
+
+impl SectionMapping {
+    /// Create a mapping storing every field on one timeline until fields are mapped
+    pub fn new(default_timeline: TimelineType) -> Self {
+        Self {
+            fields: BTreeMap::new(),
+            default_timeline,
+        }
+    }
+    
+    /// Store a top-level field on a timeline
+    pub fn with_field(mut self, field: &str, timeline: TimelineType) -> Self {
+        self.fields.insert(field.to_string(), timeline);
+        self
+    }
+    
+    /// Get the timeline a field is stored on
+    pub fn timeline_of(&self, field: &str) -> &TimelineType {
+        self.fields.get(field).unwrap_or(&self.default_timeline)
+    }
+    
+    /// Get every timeline the mapping can store a section on
+    pub fn timelines(&self) -> Vec<TimelineType> {
+        let mut timelines = vec![self.default_timeline.clone()];
+        for timeline in self.fields.values() {
+            if !timelines.contains(timeline) {
+                timelines.push(timeline.clone());
+            }
+        }
+        timelines
+    }
+    
+    /// Split a JSON object into one serialized object per timeline holding that timeline's fields
+    ///
+    /// Each section is valid JSON on its own, so a single timeline can be
+    /// reassembled and used without the others.
+    pub fn split(&self, metadata: &[u8]) -> Result<Vec<(TimelineType, Vec<u8>)>, String> {
+        let object = match serde_json::from_slice(metadata) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => return Err("Semantic fracture needs metadata that is a JSON object".to_string()),
+            Err(e) => return Err(format!("Failed to parse metadata for semantic fracture: {}", e)),
+        };
+        
+        let mut sections: BTreeMap<String, (TimelineType, Map<String, Value>)> = BTreeMap::new();
+        for (field, value) in object {
+            let timeline = self.timeline_of(&field).clone();
+            sections.entry(timeline.label())
+                .or_insert_with(|| (timeline, Map::new()))
+                .1
+                .insert(field, value);
+        }
+        
+        sections.into_values()
+            .map(|(timeline, section)| {
+                let section = serde_json::to_vec(&Value::Object(section))
+                    .map_err(|e| format!("Failed to serialize metadata section: {}", e))?;
+                Ok((timeline, section))
+            })
+            .collect()
+    }
+}
+
+/// Merge reassembled sections back into one JSON object
+pub(crate) fn merge_sections(sections: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    let mut merged = Map::new();
+    for section in sections {
+        match serde_json::from_slice(section) {
+            Ok(Value::Object(fields)) => merged.extend(fields),
+            Ok(_) => return Err("Metadata section is not a JSON object".to_string()),
+            Err(e) => return Err(format!("Failed to parse metadata section: {}", e)),
+        }
+    }
+    
+    serde_json::to_vec(&Value::Object(merged)).map_err(|e| format!("Failed to serialize metadata: {}", e))
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use crate::audit::{AuditEntry, AuditEvent, AuditLog};
+
+use super::{StorageAdapter, MetadataFragment};
+
+tokio::task_local! {
+    /// Identity of whoever the current task fetches fragments for
+    static REQUESTER: String;
+}
+
+/// Fetch fragments on behalf of a requester, e.g. a gateway client's address
+///
+/// Retrievals made while `future` runs are logged with the requester's
+/// identity; ones made by tasks it spawns are not.
+pub async fn as_requester<F: Future>(requester: &str, future: F) -> F::Output {
+    REQUESTER.scope(requester.to_string(), future).await
+}
+
+/// Adapter wrapper recording every fragment retrieval in an audit log
+///
+/// Entries carry the fragment's NFT when it was registered in `owners`,
+/// and the requester when fetched within `as_requester`. Clones share the
+/// same registry.
+pub struct AccessLogAdapter {
+    /// Wrapped adapter
+    inner: Box<dyn StorageAdapter + Send + Sync>,
+    /// Where retrievals are recorded
+    audit_log: Arc<AuditLog>,
+    /// NFT mint of each known fragment, by fragment ID
+    owners: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl AccessLogAdapter {
+    /// Wrap an adapter, recording retrievals in `audit_log`
+    pub fn new(
+        inner: Box<dyn StorageAdapter + Send + Sync>,
+        audit_log: Arc<AuditLog>,
+        owners: Arc<RwLock<HashMap<String, String>>>,
+    ) -> Self {
+        Self { inner, audit_log, owners }
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for AccessLogAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        self.inner.store_fragment(fragment).await
+    }
+    
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        self.inner.store_fragments(fragments).await
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        let started = Instant::now();
+        let result = self.inner.retrieve_fragment(id).await;
+        
+        let event = AuditEvent::FragmentRetrieved {
+            fragment_id: id.to_string(),
+            nft_mint: self.owners.read().unwrap().get(id).cloned(),
+            requester: REQUESTER.try_with(Clone::clone).ok(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            found: result.is_ok(),
+        };
+        // A retrieval shouldn't fail because it couldn't be logged
+        if let Err(e) = self.audit_log.record(event) {
+            log::warn!("Failed to log retrieval of fragment {}: {}", id, e);
+        }
+        
+        result
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        self.inner.fragment_exists(id).await
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        self.inner.delete_fragment(id).await
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(Self::new(self.inner.clone_adapter(), self.audit_log.clone(), self.owners.clone()))
+    }
+}
+
+/// Fragment retrievals of one NFT, summed from audit entries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetrievalStats {
+    /// Retrievals, failed ones included
+    pub retrievals: u64,
+    /// Retrievals that found no fragment
+    pub failures: u64,
+    /// Retrievals by known requester
+    pub requesters: BTreeMap<String, u64>,
+    /// Total latency of all retrievals in milliseconds
+    pub total_latency_ms: u64,
+    /// Slowest retrieval in milliseconds
+    pub max_latency_ms: u64,
+    /// When a fragment was last retrieved, as a Unix timestamp
+    pub last_retrieved: Option<u64>,
+}
+
+impl RetrievalStats {
+    /// Sum the retrievals of an NFT's fragments recorded in audit entries
+    pub fn from_entries(entries: &[AuditEntry], nft_mint: &str) -> Self {
+        let mut stats = Self::default();
+        for entry in entries {
+            stats.add(entry, nft_mint);
+        }
+        stats
+    }
+    
+    /// Count an audit entry if it is a retrieval of one of the NFT's fragments
+    pub fn add(&mut self, entry: &AuditEntry, nft_mint: &str) {
+        let (requester, latency_ms, found) = match &entry.event {
+            AuditEvent::FragmentRetrieved { nft_mint: Some(mint), requester, latency_ms, found, .. } if mint == nft_mint => {
+                (requester, *latency_ms, *found)
+            },
+            _ => return,
+        };
+        
+        self.retrievals += 1;
+        if !found {
+            self.failures += 1;
+        }
+        if let Some(requester) = requester {
+            *self.requesters.entry(requester.clone()).or_default() += 1;
+        }
+        self.total_latency_ms += latency_ms;
+        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
+        self.last_retrieved = self.last_retrieved.max(Some(entry.timestamp));
+    }
+    
+    /// Get the mean retrieval latency in milliseconds
+    pub fn mean_latency_ms(&self) -> f64 {
+        match self.retrievals {
+            0 => 0.0,
+            retrievals => self.total_latency_ms as f64 / retrievals as f64,
+        }
+    }
+}
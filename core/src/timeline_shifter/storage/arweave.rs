@@ -1,5 +1,11 @@
 use async_trait::async_trait;
+use futures::future::join_all;
+use ring::signature::Ed25519KeyPair;
 
+use crate::network::ProxyConfig;
+use crate::quantum_veil::SecretBytes;
+
+use super::bundle::{bundle, DataItem, BUNDLE_TAGS};
 use super::{StorageAdapter, MetadataFragment};
 
 /// Arweave storage adapter
@@ -7,7 +13,11 @@ pub struct ArweaveAdapter {
     /// Arweave endpoint URL
     pub endpoint: String,
     /// Arweave wallet key for transactions
-    pub wallet_key: Vec<u8>,
+    pub wallet_key: SecretBytes,
+    /// Proxies requests to the endpoint go through
+    pub proxy: ProxyConfig,
+    /// Upload fragments stored together as one ANS-104 bundle
+    pub bundling: bool,
 }
 
 impl ArweaveAdapter {
@@ -15,7 +25,9 @@ impl ArweaveAdapter {
     pub fn new(endpoint: &str, wallet_key: Vec<u8>) -> Self {
         Self {
             endpoint: endpoint.to_string(),
-            wallet_key,
+            wallet_key: SecretBytes::new(wallet_key),
+            proxy: ProxyConfig::direct(),
+            bundling: false,
         }
     }
     
@@ -23,9 +35,26 @@ impl ArweaveAdapter {
     pub fn default_with_key(wallet_key: Vec<u8>) -> Self {
         Self {
             endpoint: "https://arweave.net".to_string(),
-            wallet_key,
+            wallet_key: SecretBytes::new(wallet_key),
+            proxy: ProxyConfig::direct(),
+            bundling: false,
         }
     }
+    
+    /// Send requests through proxies
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+    
+    /// Upload the fragments of a fracture as one ANS-104 bundle instead of a transaction each
+    ///
+    /// Items are signed with the wallet key as an Ed25519 seed, and each
+    /// fragment's receipt is its item ID.
+    pub fn with_bundling(mut self) -> Self {
+        self.bundling = true;
+        self
+    }
 }
 
 #[async_trait]
@@ -38,6 +67,36 @@ impl StorageAdapter for ArweaveAdapter {
         Ok(fragment.id.clone())
     }
     
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        if !self.bundling || fragments.len() < 2 {
+            return join_all(fragments.iter().map(|fragment| self.store_fragment(fragment)))
+                .await
+                .into_iter()
+                .collect();
+        }
+        
+        let signer = Ed25519KeyPair::from_seed_unchecked(self.wallet_key.expose())
+            .map_err(|_| "Bundling needs a 32-byte Ed25519 wallet key".to_string())?;
+        
+        let mut items = Vec::with_capacity(fragments.len());
+        for fragment in fragments {
+            let data = bincode::serialize(fragment)
+                .map_err(|e| format!("Failed to serialize fragment: {}", e))?;
+            items.push(DataItem::sign(&data, &signer));
+        }
+        let bundled = bundle(&items);
+        
+        // In a real implementation, this would post the bundle as one transaction tagged with BUNDLE_TAGS
+        log::info!(
+            "Storing {} fragments in Arweave as one bundle of {} bytes ({:?})",
+            items.len(),
+            bundled.len(),
+            BUNDLE_TAGS,
+        );
+        
+        Ok(items.into_iter().map(|item| item.id).collect())
+    }
+    
     async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
         // In a real implementation, this would download from Arweave
         log::info!("Retrieving fragment {} from Arweave", id);
@@ -66,6 +125,8 @@ impl StorageAdapter for ArweaveAdapter {
         Box::new(ArweaveAdapter {
             endpoint: self.endpoint.clone(),
             wallet_key: self.wallet_key.clone(),
+            proxy: self.proxy.clone(),
+            bundling: self.bundling,
         })
     }
 }
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::timeline_shifter::CONTENT_ID_PREFIX;
+
+use super::{StorageAdapter, MetadataFragment};
+
+/// Adapter wrapper storing each content-addressed fragment only once
+///
+/// A content-addressed fragment already in storage is referenced rather
+/// than uploaded again. Such fragments are never deleted, since other
+/// manifests may reference them. Other fragments pass straight through.
+/// Clones share the same count of skipped uploads.
+pub struct DedupAdapter {
+    /// Wrapped adapter
+    inner: Box<dyn StorageAdapter + Send + Sync>,
+    /// Uploads skipped because the fragment was already stored
+    deduplicated: Arc<AtomicUsize>,
+}
+
+impl DedupAdapter {
+    /// Wrap an adapter, counting skipped uploads in `deduplicated`
+    pub fn new(inner: Box<dyn StorageAdapter + Send + Sync>, deduplicated: Arc<AtomicUsize>) -> Self {
+        Self { inner, deduplicated }
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for DedupAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        if fragment.id.starts_with(CONTENT_ID_PREFIX) && self.inner.fragment_exists(&fragment.id).await? {
+            log::debug!("Fragment {} is already stored, referencing it", fragment.id);
+            self.deduplicated.fetch_add(1, Ordering::Relaxed);
+            return Ok(fragment.id.clone());
+        }
+        
+        self.inner.store_fragment(fragment).await
+    }
+    
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        let mut receipts: Vec<Option<String>> = vec![None; fragments.len()];
+        let mut uploads = Vec::new();
+        
+        for (i, fragment) in fragments.iter().enumerate() {
+            if fragment.id.starts_with(CONTENT_ID_PREFIX) && self.inner.fragment_exists(&fragment.id).await? {
+                log::debug!("Fragment {} is already stored, referencing it", fragment.id);
+                self.deduplicated.fetch_add(1, Ordering::Relaxed);
+                receipts[i] = Some(fragment.id.clone());
+            } else {
+                uploads.push((i, *fragment));
+            }
+        }
+        
+        let batch: Vec<&MetadataFragment> = uploads.iter().map(|(_, fragment)| *fragment).collect();
+        for ((i, _), receipt) in uploads.iter().zip(self.inner.store_fragments(&batch).await?) {
+            receipts[*i] = Some(receipt);
+        }
+        
+        Ok(receipts.into_iter().map(Option::unwrap_or_default).collect())
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        self.inner.retrieve_fragment(id).await
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        self.inner.fragment_exists(id).await
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        if id.starts_with(CONTENT_ID_PREFIX) {
+            log::debug!("Keeping shared fragment {}", id);
+            return Ok(());
+        }
+        
+        self.inner.delete_fragment(id).await
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(Self::new(self.inner.clone_adapter(), self.deduplicated.clone()))
+    }
+}
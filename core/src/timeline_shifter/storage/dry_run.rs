@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex};
+
+use crate::timeline_shifter::TimelineType;
+
+use super::{StorageAdapter, MetadataFragment};
+
+/// Fragment write skipped by a dry run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedWrite {
+    /// Fragment that would have been stored
+    pub fragment_id: String,
+    /// Timeline the fragment belongs to
+    pub timeline: TimelineType,
+    /// Bytes that would have been stored
+    pub size: usize,
+}
+
+/// Adapter wrapper that records writes instead of performing them
+///
+/// Reads still reach the wrapped adapter. Clones share the same record.
+pub struct DryRunAdapter {
+    /// Wrapped adapter
+    inner: Box<dyn StorageAdapter + Send + Sync>,
+    /// Skipped writes in order
+    writes: Arc<Mutex<Vec<PlannedWrite>>>,
+}
+
+impl DryRunAdapter {
+    /// Wrap an adapter, recording skipped writes in `writes`
+    pub fn new(inner: Box<dyn StorageAdapter + Send + Sync>, writes: Arc<Mutex<Vec<PlannedWrite>>>) -> Self {
+        Self { inner, writes }
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for DryRunAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        log::info!("Dry run: skipping store of fragment {} ({} bytes)", fragment.id, fragment.data_size());
+        self.writes.lock().unwrap().push(PlannedWrite {
+            fragment_id: fragment.id.clone(),
+            timeline: fragment.timeline.clone(),
+            size: fragment.data_size(),
+        });
+        Ok(fragment.id.clone())
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        self.inner.retrieve_fragment(id).await
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        self.inner.fragment_exists(id).await
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        log::info!("Dry run: skipping delete of fragment {}", id);
+        Ok(())
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(Self::new(self.inner.clone_adapter(), self.writes.clone()))
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+
+use super::{StorageAdapter, MetadataFragment};
+use crate::telemetry::timed_async;
+
+/// Adapter wrapper that traces and records metrics for every storage call
+///
+/// `TimelineShifter` wraps all of its adapters in one.
+pub struct InstrumentedAdapter {
+    /// Wrapped adapter
+    inner: Box<dyn StorageAdapter + Send + Sync>,
+}
+
+impl InstrumentedAdapter {
+    /// Wrap an adapter
+    pub fn new(inner: Box<dyn StorageAdapter + Send + Sync>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for InstrumentedAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        timed_async("storage.store", self.inner.store_fragment(fragment)).await
+    }
+    
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        timed_async("storage.store_batch", self.inner.store_fragments(fragments)).await
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        timed_async("storage.retrieve", self.inner.retrieve_fragment(id)).await
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        timed_async("storage.exists", self.inner.fragment_exists(id)).await
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        timed_async("storage.delete", self.inner.delete_fragment(id)).await
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(Self::new(self.inner.clone_adapter()))
+    }
+}
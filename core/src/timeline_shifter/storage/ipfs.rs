@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 
+use crate::network::ProxyConfig;
+
 use super::{StorageAdapter, MetadataFragment};
 
 /// IPFS storage adapter
@@ -10,6 +12,8 @@ pub struct IpfsAdapter {
     pub auth_token: Option<String>,
     /// Pin data to IPFS
     pub pin: bool,
+    /// Proxies requests to the endpoint go through
+    pub proxy: ProxyConfig,
 }
 
 impl IpfsAdapter {
@@ -19,6 +23,7 @@ impl IpfsAdapter {
             endpoint: endpoint.to_string(),
             auth_token,
             pin,
+            proxy: ProxyConfig::direct(),
         }
     }
     
@@ -28,6 +33,7 @@ impl IpfsAdapter {
             endpoint: "https://ipfs.io".to_string(),
             auth_token: None,
             pin: true,
+            proxy: ProxyConfig::direct(),
         }
     }
     
@@ -40,8 +46,15 @@ impl IpfsAdapter {
             endpoint: "https://ipfs.infura.io:5001".to_string(),
             auth_token,
             pin: true,
+            proxy: ProxyConfig::direct(),
         }
     }
+    
+    /// Send requests through proxies
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
 }
 
 #[async_trait]
@@ -83,6 +96,7 @@ impl StorageAdapter for IpfsAdapter {
             endpoint: self.endpoint.clone(),
             auth_token: self.auth_token.clone(),
             pin: self.pin,
+            proxy: self.proxy.clone(),
         })
     }
 }
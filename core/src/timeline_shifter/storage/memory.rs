@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use super::{StorageAdapter, MetadataFragment};
+
+/// In-memory storage adapter for hermetic tests
+///
+/// Clones share the same backing store, so a fragment stored through one
+/// `TimelineShifter` can be retrieved through another.
+#[derive(Clone, Default)]
+pub struct InMemoryAdapter {
+    /// Stored fragments by ID
+    fragments: Arc<RwLock<HashMap<String, MetadataFragment>>>,
+}
+
+impl InMemoryAdapter {
+    /// Create a new, empty in-memory adapter
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Get the number of stored fragments
+    pub fn len(&self) -> usize {
+        self.fragments.read().unwrap().len()
+    }
+    
+    /// Check if no fragments are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Get the IDs of all stored fragments
+    pub fn fragment_ids(&self) -> Vec<String> {
+        self.fragments.read().unwrap().keys().cloned().collect()
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for InMemoryAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        self.fragments.write().unwrap().insert(fragment.id.clone(), fragment.clone());
+        Ok(fragment.id.clone())
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        self.fragments.read().unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("Fragment not found: {}", id))
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        Ok(self.fragments.read().unwrap().contains_key(id))
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        self.fragments.write().unwrap().remove(id);
+        Ok(())
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
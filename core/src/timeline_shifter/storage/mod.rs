@@ -1,44 +1,41 @@
-use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
+use futures::future::join_all;
 
 // Import sub-modules
+pub mod access_log;
+#[cfg(feature = "storage-arweave")]
 pub mod arweave;
+#[cfg(feature = "storage-arweave")]
+pub mod bundle;
+pub mod dedup;
+pub mod dry_run;
+pub mod instrumented;
+#[cfg(feature = "storage-ipfs")]
 pub mod ipfs;
+pub mod obfuscated;
+#[cfg(feature = "onchain")]
 pub mod solana;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod memory;
 
 // Re-export adapters
+pub use access_log::{as_requester, AccessLogAdapter, RetrievalStats};
+#[cfg(feature = "storage-arweave")]
 pub use arweave::ArweaveAdapter;
+pub use dedup::DedupAdapter;
+pub use dry_run::{DryRunAdapter, PlannedWrite};
+pub use instrumented::InstrumentedAdapter;
+#[cfg(feature = "storage-ipfs")]
 pub use ipfs::IpfsAdapter;
+pub use obfuscated::{ObfuscatedAdapter, ObfuscationConfig, ThreatModel};
+#[cfg(feature = "onchain")]
 pub use solana::SolanaAdapter;
+#[cfg(any(test, feature = "test-utils"))]
+pub use memory::InMemoryAdapter;
 
-use super::fragment::MetadataFragment;
+pub use quantum_veil_types::StorageLocation;
 
-/// Storage location for metadata fragments
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum StorageLocation {
-    /// On-chain Solana storage
-    Onchain {
-        /// Program ID
-        program_id: String,
-        /// Account address
-        account: String,
-    },
-    /// Arweave permanent storage
-    Arweave {
-        /// Transaction ID
-        transaction_id: String,
-    },
-    /// IPFS decentralized storage
-    Ipfs {
-        /// IPFS CID
-        cid: String,
-    },
-    /// Shadow realm (custom encrypted storage)
-    ShadowRealm {
-        /// Encrypted access info
-        access_path: String,
-    },
-}
+use quantum_veil_types::MetadataFragment;
 
 /// Storage adapter trait for different timeline fragment storage solutions
 #[async_trait]
@@ -46,6 +43,17 @@ pub trait StorageAdapter: Send + Sync {
     /// Store a metadata fragment
     async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String>;
     
+    /// Store several metadata fragments at once, returning their receipts in order
+    ///
+    /// Stores them concurrently one by one unless the adapter can upload
+    /// them together, e.g. as an Arweave bundle.
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        join_all(fragments.iter().map(|fragment| self.store_fragment(fragment)))
+            .await
+            .into_iter()
+            .collect()
+    }
+    
     /// Retrieve a metadata fragment
     async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String>;
     
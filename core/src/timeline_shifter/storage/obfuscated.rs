@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use rand::{Rng, thread_rng};
+use ring::hmac;
+use std::time::Duration;
+
+use crate::quantum_veil::SecretBytes;
+
+use super::{StorageAdapter, MetadataFragment};
+
+/// Label for the keyed mask hiding a fragment's true length
+const LENGTH_MASK_LABEL: &[u8] = b"quantum-veil/obfuscation/length";
+
+/// Bytes of the masked length header prepended to padded fragments
+const LENGTH_HEADER_LEN: usize = 4;
+
+/// Who is assumed to be watching storage traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreatModel {
+    /// Storage providers and the network are trusted
+    Trusted,
+    /// A storage provider inspects fragment sizes and upload bursts
+    CuriousProvider,
+    /// An observer correlates sizes and timing across providers and the network
+    GlobalObserver,
+}
+
+/// How fragment sizes and storage timing are disguised
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObfuscationConfig {
+    /// Ascending lengths stored fragments are padded up to, empty for none
+    ///
+    /// Fragments longer than the largest bucket are padded to a multiple of it.
+    pub size_buckets: Vec<usize>,
+    /// Shortest random delay before each storage call
+    pub min_delay: Duration,
+    /// Longest random delay before each storage call
+    pub max_delay: Duration,
+    /// Upload a fracture's fragments one by one in random order
+    pub shuffle: bool,
+}
+
+impl ObfuscationConfig {
+    /// Get the settings suited to a threat model
+    pub fn for_threat_model(threat_model: ThreatModel) -> Self {
+        match threat_model {
+            ThreatModel::Trusted => Self {
+                size_buckets: Vec::new(),
+                min_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+                shuffle: false,
+            },
+            ThreatModel::CuriousProvider => Self {
+                size_buckets: (8..=16).map(|exponent| 1 << exponent).collect(),
+                min_delay: Duration::ZERO,
+                max_delay: Duration::from_secs(2),
+                shuffle: true,
+            },
+            ThreatModel::GlobalObserver => Self {
+                size_buckets: vec![16 * 1024, 64 * 1024],
+                min_delay: Duration::from_secs(5),
+                max_delay: Duration::from_secs(60),
+                shuffle: true,
+            },
+        }
+    }
+    
+    /// Get the stored length of a fragment of `len` bytes, header included
+    pub fn padded_len(&self, len: usize) -> usize {
+        bucket_len(&self.size_buckets, len + LENGTH_HEADER_LEN)
+    }
+    
+    /// Draw a random delay between `min_delay` and `max_delay`
+    fn delay(&self) -> Duration {
+        if self.max_delay <= self.min_delay {
+            return self.min_delay;
+        }
+        self.min_delay + (self.max_delay - self.min_delay).mul_f64(thread_rng().gen::<f64>())
+    }
+}
+
+/// Round a length up to the smallest bucket holding it
+///
+/// Lengths beyond the largest bucket round up to a multiple of it; with no
+/// buckets the length is kept.
+pub(crate) fn bucket_len(size_buckets: &[usize], len: usize) -> usize {
+    match size_buckets.iter().find(|bucket| **bucket >= len) {
+        Some(bucket) => *bucket,
+        None => match size_buckets.last() {
+            Some(largest) => (len + largest - 1) / largest * largest,
+            None => len,
+        },
+    }
+}
+
+/// Adapter wrapper that pads fragments to bucketed sizes and delays storage calls
+///
+/// Padded fragments start with their true length, masked under a key so
+/// the storage provider can't read it; retrieve them through a wrapper with
+/// the same key.
+pub struct ObfuscatedAdapter {
+    /// Wrapped adapter
+    inner: Box<dyn StorageAdapter + Send + Sync>,
+    /// Padding and timing settings
+    config: ObfuscationConfig,
+    /// Key masking fragment lengths
+    padding_key: SecretBytes,
+}
+
+impl ObfuscatedAdapter {
+    /// Wrap an adapter
+    pub fn new(inner: Box<dyn StorageAdapter + Send + Sync>, config: ObfuscationConfig, padding_key: SecretBytes) -> Self {
+        Self { inner, config, padding_key }
+    }
+    
+    /// Sleep for a random delay
+    async fn wait(&self) {
+        let delay = self.config.delay();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    
+    /// Derive the mask hiding a fragment's length
+    fn length_mask(&self, fragment_id: &str) -> u32 {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.padding_key.expose());
+        let tag = hmac::sign(&key, &[LENGTH_MASK_LABEL, fragment_id.as_bytes()].concat());
+        u32::from_le_bytes(tag.as_ref()[..4].try_into().unwrap())
+    }
+    
+    /// Pad fragment data to its bucket behind a masked length header
+    fn pad(&self, fragment: &MetadataFragment) -> Result<Vec<u8>, String> {
+        let len = u32::try_from(fragment.data.len())
+            .map_err(|_| format!("Fragment {} is too large to pad", fragment.id))?;
+        
+        let mut padded = (len ^ self.length_mask(&fragment.id)).to_le_bytes().to_vec();
+        padded.extend_from_slice(&fragment.data);
+        
+        let mut padding = vec![0u8; self.config.padded_len(fragment.data.len()) - padded.len()];
+        thread_rng().fill(&mut padding[..]);
+        padded.extend(padding);
+        Ok(padded)
+    }
+    
+    /// Strip the header and padding from retrieved fragment data
+    fn unpad(&self, fragment: &MetadataFragment) -> Result<Vec<u8>, String> {
+        if fragment.data.len() < LENGTH_HEADER_LEN {
+            return Err(format!("Fragment {} is missing its length header", fragment.id));
+        }
+        
+        let masked = u32::from_le_bytes(fragment.data[..LENGTH_HEADER_LEN].try_into().unwrap());
+        let len = (masked ^ self.length_mask(&fragment.id)) as usize;
+        fragment.data[LENGTH_HEADER_LEN..]
+            .get(..len)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| format!("Fragment {} has an invalid length header", fragment.id))
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for ObfuscatedAdapter {
+    async fn store_fragment(&self, fragment: &MetadataFragment) -> Result<String, String> {
+        let padded = MetadataFragment { data: self.pad(fragment)?, ..fragment.clone() };
+        self.wait().await;
+        self.inner.store_fragment(&padded).await
+    }
+    
+    async fn store_fragments(&self, fragments: &[&MetadataFragment]) -> Result<Vec<String>, String> {
+        let padded = fragments.iter()
+            .map(|fragment| Ok(MetadataFragment { data: self.pad(fragment)?, ..(*fragment).clone() }))
+            .collect::<Result<Vec<_>, String>>()?;
+        self.wait().await;
+        self.inner.store_fragments(&padded.iter().collect::<Vec<_>>()).await
+    }
+    
+    async fn retrieve_fragment(&self, id: &str) -> Result<MetadataFragment, String> {
+        self.wait().await;
+        let mut fragment = self.inner.retrieve_fragment(id).await?;
+        fragment.data = self.unpad(&fragment)?;
+        Ok(fragment)
+    }
+    
+    async fn fragment_exists(&self, id: &str) -> Result<bool, String> {
+        self.inner.fragment_exists(id).await
+    }
+    
+    async fn delete_fragment(&self, id: &str) -> Result<(), String> {
+        self.wait().await;
+        self.inner.delete_fragment(id).await
+    }
+    
+    fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
+        Box::new(Self::new(self.inner.clone_adapter(), self.config.clone(), self.padding_key.clone()))
+    }
+}
@@ -11,6 +11,8 @@ use solana_sdk::{
 use serde::{Serialize, Deserialize};
 use borsh::{BorshSerialize, BorshDeserialize};
 
+use crate::network::ProxyConfig;
+
 use super::{StorageAdapter, MetadataFragment};
 
 /// Solana on-chain storage adapter
@@ -21,6 +23,8 @@ pub struct SolanaAdapter {
     pub program_id: Pubkey,
     /// Payer for transactions (optional)
     pub payer: Option<Keypair>,
+    /// Proxies RPC calls go through
+    pub proxy: ProxyConfig,
 }
 
 /// Fragment storage instruction
@@ -53,9 +57,17 @@ impl SolanaAdapter {
             rpc_client,
             program_id,
             payer,
+            proxy: ProxyConfig::direct(),
         }
     }
     
+    /// Send RPC calls through proxies
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Result<Self, String> {
+        self.rpc_client = proxy.rpc_client(&self.rpc_client.url(), CommitmentConfig::confirmed())?;
+        self.proxy = proxy;
+        Ok(self)
+    }
+    
     /// Derive PDA for fragment storage
     pub fn get_fragment_address(&self, fragment_id: &str) -> (Pubkey, u8) {
         Pubkey::find_program_address(
@@ -174,9 +186,13 @@ impl StorageAdapter for SolanaAdapter {
     
     fn clone_adapter(&self) -> Box<dyn StorageAdapter + Send + Sync> {
         Box::new(SolanaAdapter {
-            rpc_client: RpcClient::new(self.rpc_client.url()),
+            // The proxy was validated when set
+            rpc_client: self.proxy
+                .rpc_client(&self.rpc_client.url(), CommitmentConfig::confirmed())
+                .expect("Failed to rebuild RPC client"),
             program_id: self.program_id,
             payer: self.payer.clone(),
+            proxy: self.proxy.clone(),
         })
     }
 }
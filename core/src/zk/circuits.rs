@@ -0,0 +1,89 @@
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use super::commitment::mimc_commit_gadget;
+
+/// Bits a range difference is decomposed into
+const RANGE_BITS: usize = 32;
+
+/// Proves a committed value equals a public value
+///
+/// Public inputs, in order: commitment, value.
+#[derive(Clone)]
+pub(crate) struct EqualityCircuit {
+    /// Round constants of the commitment
+    pub constants: Vec<Fr>,
+    /// Published commitment
+    pub commitment: Fr,
+    /// Value the commitment opens to
+    pub value: Fr,
+    /// Blinding factor, known only to the prover
+    pub blinding: Option<Fr>,
+}
+
+impl ConstraintSynthesizer<Fr> for EqualityCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let commitment = FpVar::new_input(cs.clone(), || Ok(self.commitment))?;
+        let value = FpVar::new_input(cs.clone(), || Ok(self.value))?;
+        let blinding = FpVar::new_witness(cs, || self.blinding.ok_or(SynthesisError::AssignmentMissing))?;
+        
+        mimc_commit_gadget(&value, &blinding, &self.constants)?.enforce_equal(&commitment)
+    }
+}
+
+/// Proves a committed value lies in a public inclusive range
+///
+/// Public inputs, in order: commitment, min, max.
+#[derive(Clone)]
+pub(crate) struct RangeCircuit {
+    /// Round constants of the commitment
+    pub constants: Vec<Fr>,
+    /// Published commitment
+    pub commitment: Fr,
+    /// Smallest value allowed
+    pub min: u64,
+    /// Largest value allowed
+    pub max: u64,
+    /// Hidden value, known only to the prover
+    pub value: Option<u64>,
+    /// Blinding factor, known only to the prover
+    pub blinding: Option<Fr>,
+}
+
+impl ConstraintSynthesizer<Fr> for RangeCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let commitment = FpVar::new_input(cs.clone(), || Ok(self.commitment))?;
+        let min = FpVar::new_input(cs.clone(), || Ok(Fr::from(self.min)))?;
+        let max = FpVar::new_input(cs.clone(), || Ok(Fr::from(self.max)))?;
+        let value = FpVar::new_witness(cs.clone(), || {
+            self.value.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let blinding = FpVar::new_witness(cs.clone(), || self.blinding.ok_or(SynthesisError::AssignmentMissing))?;
+        
+        mimc_commit_gadget(&value, &blinding, &self.constants)?.enforce_equal(&commitment)?;
+        
+        // Both differences fit in 32 bits only if neither wrapped around the field
+        let below = self.value.map(|value| value.wrapping_sub(self.min));
+        enforce_small(cs.clone(), below, &(&value - &min))?;
+        let above = self.value.map(|value| self.max.wrapping_sub(value));
+        enforce_small(cs, above, &(&max - &value))
+    }
+}
+
+/// Enforce that a field element is below 2^32 by decomposing it into bits
+fn enforce_small(
+    cs: ConstraintSystemRef<Fr>,
+    difference: Option<u64>,
+    element: &FpVar<Fr>,
+) -> Result<(), SynthesisError> {
+    let bits = (0..RANGE_BITS)
+        .map(|i| {
+            Boolean::new_witness(cs.clone(), || {
+                difference.map(|d| (d >> i) & 1 == 1).ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(element)
+}
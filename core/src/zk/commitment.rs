@@ -0,0 +1,91 @@
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::SynthesisError;
+use sha3::{Digest, Sha3_256};
+
+/// Domain separator for the MiMC round constants
+const MIMC_DOMAIN: &[u8] = b"quantum-veil/zk/mimc";
+
+/// MiMC rounds for the x^7 permutation over BN254, ceil(log_7 p)
+const MIMC_ROUNDS: usize = 91;
+
+/// Largest value an attribute can commit to, so range proofs stay 32-bit
+pub const MAX_ATTRIBUTE_VALUE: u64 = u32::MAX as u64;
+
+/// Derive the MiMC round constants from the domain separator
+pub(crate) fn round_constants() -> Vec<Fr> {
+    (0..MIMC_ROUNDS)
+        .map(|round| {
+            let mut hasher = Sha3_256::new();
+            hasher.update(MIMC_DOMAIN);
+            hasher.update((round as u32).to_le_bytes());
+            Fr::from_le_bytes_mod_order(&hasher.finalize())
+        })
+        .collect()
+}
+
+/// Compress a value and blinding factor with MiMC in Miyaguchi-Preneel mode
+///
+/// The blinding factor keys the permutation, so the commitment hides the
+/// value, and feeding both back in keeps it binding.
+pub(crate) fn mimc_commit(value: Fr, blinding: Fr, constants: &[Fr]) -> Fr {
+    let mut state = value;
+    for constant in constants {
+        let t = state + blinding + constant;
+        let t2 = t.square();
+        state = t2.square() * t2 * t;
+    }
+    
+    state + blinding + value
+}
+
+/// In-circuit `mimc_commit`
+pub(crate) fn mimc_commit_gadget(
+    value: &FpVar<Fr>,
+    blinding: &FpVar<Fr>,
+    constants: &[Fr],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut state = value.clone();
+    for constant in constants {
+        let t = &state + blinding + *constant;
+        let t2 = t.square()?;
+        state = t2.square()? * &t2 * &t;
+    }
+    
+    Ok(state + blinding + value)
+}
+
+/// Commit to an attribute value under a 32-byte blinding factor
+///
+/// Returns the commitment as a little-endian field element, ready to publish.
+pub fn commit(value: u64, blinding: &[u8; 32]) -> Result<[u8; 32], String> {
+    if value > MAX_ATTRIBUTE_VALUE {
+        return Err(format!("Attribute value {} above maximum {}", value, MAX_ATTRIBUTE_VALUE));
+    }
+    
+    let commitment = mimc_commit(Fr::from(value), blinding_scalar(blinding), &round_constants());
+    Ok(to_bytes(commitment))
+}
+
+/// Map a blinding factor onto the scalar field
+pub(crate) fn blinding_scalar(blinding: &[u8; 32]) -> Fr {
+    Fr::from_le_bytes_mod_order(blinding)
+}
+
+/// Encode a field element as 32 little-endian bytes
+pub(crate) fn to_bytes(element: Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&element.into_bigint().to_bytes_le());
+    bytes
+}
+
+/// Decode a field element, rejecting non-canonical encodings
+pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Result<Fr, String> {
+    let element = Fr::from_le_bytes_mod_order(bytes);
+    if to_bytes(element) != *bytes {
+        return Err("Commitment is not a canonical field element".to_string());
+    }
+    
+    Ok(element)
+}
@@ -0,0 +1,27 @@
+//! Zero-knowledge proofs of predicates over committed attributes
+//!
+//! An owner publishes a commitment to a hidden attribute value and can then
+//! prove statements such as "Rarity is at least Epic" without revealing the
+//! value. Proofs are Groth16 over BN254, built with arkworks behind the `zk`
+//! feature.
+//!
+//! Groth16 keys come from a per-circuit trusted setup: whoever knows the
+//! setup randomness can forge proofs, so verifiers should only trust keys
+//! from the project's published ceremony. The `verifier` module depends only
+//! on the verifying key and is small enough for others to embed.
+
+#[cfg(feature = "zk")]
+mod circuits;
+#[cfg(feature = "zk")]
+mod commitment;
+#[cfg(feature = "zk")]
+mod prover;
+#[cfg(feature = "zk")]
+pub mod verifier;
+
+#[cfg(feature = "zk")]
+pub use commitment::{commit, MAX_ATTRIBUTE_VALUE};
+#[cfg(feature = "zk")]
+pub use prover::{prove, setup, PredicateKeys};
+#[cfg(feature = "zk")]
+pub use verifier::{verify_predicate, Predicate, PredicateKind, PredicateProof};
@@ -0,0 +1,134 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use rand::rngs::OsRng;
+
+use super::circuits::{EqualityCircuit, RangeCircuit};
+use super::commitment::{blinding_scalar, commit, from_bytes, round_constants};
+use super::verifier::{Predicate, PredicateKind, PredicateProof};
+
+/// Groth16 keys for one predicate kind
+#[derive(Clone)]
+pub struct PredicateKeys {
+    /// Circuit the keys belong to
+    pub kind: PredicateKind,
+    /// Key the prover needs
+    pub proving_key: ProvingKey<Bn254>,
+    /// Key verifiers need
+    pub verifying_key: VerifyingKey<Bn254>,
+}
+
+impl PredicateKeys {
+    /// Encode the verifying key for `verify_predicate`
+    pub fn verifying_key_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        self.verifying_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| format!("Failed to encode verifying key: {}", e))?;
+        
+        Ok(bytes)
+    }
+}
+
+/// Run the trusted setup for a predicate kind
+///
+/// Only for tests and ceremonies: the randomness is discarded, but whoever
+/// runs the setup is trusted not to have kept it.
+pub fn setup(kind: PredicateKind) -> Result<PredicateKeys, String> {
+    let constants = round_constants();
+    let result = match kind {
+        PredicateKind::Equality => Groth16::<Bn254>::setup(
+            EqualityCircuit { constants, commitment: Fr::from(0u64), value: Fr::from(0u64), blinding: None },
+            &mut OsRng,
+        ),
+        PredicateKind::Range => Groth16::<Bn254>::setup(
+            RangeCircuit { constants, commitment: Fr::from(0u64), min: 0, max: 0, value: None, blinding: None },
+            &mut OsRng,
+        ),
+    };
+    
+    let (proving_key, verifying_key) = result.map_err(|e| format!("Failed to run {:?} setup: {}", kind, e))?;
+    Ok(PredicateKeys { kind, proving_key, verifying_key })
+}
+
+/// Prove that the value behind a commitment satisfies a predicate
+pub fn prove(
+    keys: &PredicateKeys,
+    value: u64,
+    blinding: &[u8; 32],
+    predicate: Predicate,
+) -> Result<PredicateProof, String> {
+    if keys.kind != predicate.kind() {
+        return Err(format!("{:?} keys cannot prove a {:?} predicate", keys.kind, predicate.kind()));
+    }
+    if !predicate.holds(value) {
+        return Err("Attribute value does not satisfy the predicate".to_string());
+    }
+    
+    let commitment_bytes = commit(value, blinding)?;
+    let commitment = from_bytes(&commitment_bytes)?;
+    let constants = round_constants();
+    let blinding = Some(blinding_scalar(blinding));
+    
+    let result = match predicate {
+        Predicate::Equals { value } => Groth16::<Bn254>::prove(
+            &keys.proving_key,
+            EqualityCircuit { constants, commitment, value: Fr::from(value), blinding },
+            &mut OsRng,
+        ),
+        Predicate::InRange { min, max } => Groth16::<Bn254>::prove(
+            &keys.proving_key,
+            RangeCircuit { constants, commitment, min, max, value: Some(value), blinding },
+            &mut OsRng,
+        ),
+    };
+    let proof = result.map_err(|e| format!("Failed to prove predicate: {}", e))?;
+    
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_compressed(&mut proof_bytes)
+        .map_err(|e| format!("Failed to encode predicate proof: {}", e))?;
+    
+    Ok(PredicateProof { commitment: commitment_bytes, predicate, proof: proof_bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::verify_predicate;
+    
+    #[test]
+    fn test_range_proof_hides_value_and_checks_predicate() {
+        let keys = setup(PredicateKind::Range).unwrap();
+        let verifying_key = keys.verifying_key_bytes().unwrap();
+        let blinding = [7u8; 32];
+        
+        // Rarity 3 (Epic) is at least 3, without revealing it is exactly 3
+        let proof = prove(&keys, 3, &blinding, Predicate::at_least(3)).unwrap();
+        assert_eq!(proof.commitment, commit(3, &blinding).unwrap());
+        assert!(verify_predicate(&verifying_key, &proof).is_ok());
+        
+        // The proof doesn't carry over to a stronger claim or another commitment
+        let stronger = PredicateProof { predicate: Predicate::at_least(4), ..proof.clone() };
+        assert!(verify_predicate(&verifying_key, &stronger).is_err());
+        let other = PredicateProof { commitment: commit(3, &[8u8; 32]).unwrap(), ..proof };
+        assert!(verify_predicate(&verifying_key, &other).is_err());
+        
+        // False statements can't be proven, nor with the wrong keys
+        assert!(prove(&keys, 2, &blinding, Predicate::at_least(3)).is_err());
+        assert!(prove(&keys, 3, &blinding, Predicate::Equals { value: 3 }).is_err());
+    }
+    
+    #[test]
+    fn test_equality_proof_verifies_only_with_its_key() {
+        let keys = setup(PredicateKind::Equality).unwrap();
+        let blinding = [9u8; 32];
+        
+        let proof = prove(&keys, 42, &blinding, Predicate::Equals { value: 42 }).unwrap();
+        assert!(verify_predicate(&keys.verifying_key_bytes().unwrap(), &proof).is_ok());
+        
+        let range_keys = setup(PredicateKind::Range).unwrap();
+        assert!(verify_predicate(&range_keys.verifying_key_bytes().unwrap(), &proof).is_err());
+    }
+}
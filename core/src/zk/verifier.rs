@@ -0,0 +1,114 @@
+//! Lightweight predicate proof verification
+//!
+//! Needs only the verifying key for the predicate kind, so marketplaces and
+//! other programs can embed it without the prover.
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use ark_snark::SNARK;
+use serde::{Serialize, Deserialize};
+
+use super::commitment::{from_bytes, MAX_ATTRIBUTE_VALUE};
+
+/// Statement proven about a committed attribute value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Predicate {
+    /// The value equals this one
+    Equals {
+        /// Value the attribute equals
+        value: u64,
+    },
+    /// The value lies in this inclusive range
+    InRange {
+        /// Smallest value allowed
+        min: u64,
+        /// Largest value allowed
+        max: u64,
+    },
+}
+
+impl Predicate {
+    /// The value is at least `min`
+    pub fn at_least(min: u64) -> Self {
+        Predicate::InRange { min, max: MAX_ATTRIBUTE_VALUE }
+    }
+    
+    /// The value is at most `max`
+    pub fn at_most(max: u64) -> Self {
+        Predicate::InRange { min: 0, max }
+    }
+    
+    /// Get the circuit that proves the predicate
+    pub fn kind(&self) -> PredicateKind {
+        match self {
+            Predicate::Equals { .. } => PredicateKind::Equality,
+            Predicate::InRange { .. } => PredicateKind::Range,
+        }
+    }
+    
+    /// Check if a value satisfies the predicate
+    pub fn holds(&self, value: u64) -> bool {
+        match *self {
+            Predicate::Equals { value: expected } => value == expected,
+            Predicate::InRange { min, max } => min <= value && value <= max,
+        }
+    }
+    
+    /// Get the public inputs following the commitment
+    pub(crate) fn public_inputs(&self) -> Result<Vec<Fr>, String> {
+        match *self {
+            Predicate::Equals { value } => Ok(vec![Fr::from(value)]),
+            Predicate::InRange { min, max } if min <= max && max <= MAX_ATTRIBUTE_VALUE => {
+                Ok(vec![Fr::from(min), Fr::from(max)])
+            }
+            Predicate::InRange { min, max } => Err(format!("Invalid range {}..={}", min, max)),
+        }
+    }
+}
+
+/// Circuit a predicate is proven with; each has its own keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredicateKind {
+    /// Committed value equals a public value
+    Equality,
+    /// Committed value lies in a public range
+    Range,
+}
+
+/// Proof that a committed attribute satisfies a predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredicateProof {
+    /// Published commitment to the attribute value
+    pub commitment: [u8; 32],
+    /// Statement proven
+    pub predicate: Predicate,
+    /// Compressed Groth16 proof
+    pub proof: Vec<u8>,
+}
+
+/// Verify a predicate proof against the compressed verifying key for its kind
+pub fn verify_predicate(verifying_key: &[u8], proof: &PredicateProof) -> Result<(), String> {
+    let verifying_key = VerifyingKey::<Bn254>::deserialize_compressed(verifying_key)
+        .map_err(|e| format!("Failed to decode verifying key: {}", e))?;
+    let groth16_proof = Proof::<Bn254>::deserialize_compressed(proof.proof.as_slice())
+        .map_err(|e| format!("Failed to decode predicate proof: {}", e))?;
+    
+    let mut inputs = vec![from_bytes(&proof.commitment)?];
+    inputs.extend(proof.predicate.public_inputs()?);
+    
+    // A key for the other circuit has the wrong number of inputs and fails here
+    if verifying_key.gamma_abc_g1.len() != inputs.len() + 1 {
+        return Err(format!("Verifying key does not match a {:?} proof", proof.predicate.kind()));
+    }
+    
+    let valid = Groth16::<Bn254>::verify(&verifying_key, &inputs, &groth16_proof)
+        .map_err(|e| format!("Failed to verify predicate proof: {}", e))?;
+    if !valid {
+        return Err("Invalid predicate proof".to_string());
+    }
+    
+    Ok(())
+}
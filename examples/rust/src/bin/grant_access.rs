@@ -0,0 +1,112 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use std::str::FromStr;
+use std::fs::File;
+use std::io::Read;
+
+use project_89::{GlitchGangPrivacyClient, PrivacyLevel, ProxyConfig, Cluster, ClusterRegistry};
+
+/// Example program to grant a viewer access to a wrapped NFT
+///
+/// The grantee can be a public key, a `.sol` domain, or a registered agent name.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logging
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    
+    println!("\n⧂ PROJECT 89: QUANTUM VEIL ⧂");
+    println!("Grant Access Example");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    
+    // Parse command line arguments
+    let args: Vec<String> = std::env::args().collect();
+    
+    let (keypair_path, wrapper_account, grantee, level) = parse_args(&args)?;
+    
+    // Load keypair
+    let keypair = load_keypair(keypair_path)?;
+    println!("Using wallet: {}", keypair.pubkey());
+    
+    let wrapper_pubkey = Pubkey::from_str(wrapper_account)?;
+    
+    // Resolve the cluster from QUANTUM_VEIL_CLUSTER, defaulting to devnet for testing
+    let cluster = ClusterRegistry::from_env()?.resolve(&Cluster::from_env()?)?;
+    println!("Using cluster: {}", cluster.rpc_url);
+    
+    // Create privacy client, proxied if QUANTUM_VEIL_PROXY is set (e.g. socks5h://127.0.0.1:9050 for Tor)
+    let client = GlitchGangPrivacyClient::for_cluster(
+        &cluster,
+        keypair,
+        &ProxyConfig::from_env()?,
+    )?;
+    
+    // Resolve the grantee and show who it is
+    let grantee_pubkey = client.resolve_grantee(grantee)?;
+    println!("Grantee: {} ({})", client.display_name(&grantee_pubkey), grantee_pubkey);
+    
+    // Numeric levels are granted as-is; named levels go through the access policy
+    let signature = match level.parse::<u8>() {
+        Ok(access_level) => {
+            println!("\nGranting access level {}...", access_level);
+            client.grant_access(&wrapper_pubkey, &grantee_pubkey, access_level).await?
+        },
+        Err(_) => {
+            let privacy_level = parse_privacy_level(level)?;
+            println!("\nGranting access through {:?} privacy...", privacy_level);
+            client.grant_privacy_level(&wrapper_pubkey, &grantee_pubkey, privacy_level).await?
+        },
+    };
+    println!("✓ Access granted: {}", signature);
+    
+    Ok(())
+}
+
+/// Parse command line arguments
+fn parse_args(args: &[String]) -> Result<(&str, &str, &str, &str), Box<dyn std::error::Error>> {
+    if args.len() < 4 {
+        println!("Usage: grant_access <keypair_path> <wrapper_account> <grantee> [level]");
+        println!("  - keypair_path: Path to the wallet keypair file");
+        println!("  - wrapper_account: Address of the privacy wrapper");
+        println!("  - grantee: Public key, .sol domain, or registered agent name");
+        println!("  - level: Access level (0-255) or privacy level to see through: light, medium, heavy, complete (default: 1)");
+        println!("Set QUANTUM_VEIL_PROXY to route network calls through a proxy, e.g. socks5h://127.0.0.1:9050 for Tor");
+        println!("Set QUANTUM_VEIL_CLUSTER to mainnet, devnet, testnet, localnet, a registered name or an RPC URL,");
+        println!("and QUANTUM_VEIL_CLUSTERS to a registry file overriding the built-in endpoints and program IDs");
+        std::process::exit(1);
+    }
+    
+    let level = if args.len() > 4 { args[4].as_str() } else { "1" };
+    
+    Ok((&args[1], &args[2], &args[3], level))
+}
+
+/// Parse a privacy level name
+fn parse_privacy_level(name: &str) -> Result<PrivacyLevel, Box<dyn std::error::Error>> {
+    match name.to_lowercase().as_str() {
+        "light" => Ok(PrivacyLevel::Light),
+        "medium" => Ok(PrivacyLevel::Medium),
+        "heavy" => Ok(PrivacyLevel::Heavy),
+        "complete" => Ok(PrivacyLevel::Complete),
+        _ => Err(format!("Unknown access level: {}", name).into()),
+    }
+}
+
+/// Load keypair from file
+fn load_keypair(keypair_path: &str) -> Result<Keypair, Box<dyn std::error::Error>> {
+    let mut file = File::open(keypair_path)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    
+    let keypair = if keypair_path.ends_with(".json") {
+        // Parse JSON format
+        let keypair_bytes: Vec<u8> = serde_json::from_slice(&bytes)?;
+        Keypair::from_bytes(&keypair_bytes)?
+    } else {
+        // Parse binary format
+        Keypair::from_bytes(&bytes)?
+    };
+    
+    Ok(keypair)
+}
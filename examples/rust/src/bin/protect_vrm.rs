@@ -11,9 +11,13 @@ use project_89::{
     VrmData,
     PositionData,
     RotationData,
+    ScaleData,
     VoiceData,
     GestureData,
     PrivacyLevel,
+    ProxyConfig,
+    Cluster,
+    ClusterRegistry,
 };
 
 /// Example program to demonstrate VRM protection features
@@ -21,11 +25,11 @@ use project_89::{
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
-
+    
     println!("\n⧂ PROJECT 89: QUANTUM VEIL ⧂");
     println!("VRM Privacy Protection Example");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-
+    
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
     
@@ -47,11 +51,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let nft_mint_pubkey = Pubkey::from_str(&nft_mint)?;
     println!("NFT mint: {}", nft_mint_pubkey);
     
-    // Create privacy client
-    let client = GlitchGangPrivacyClient::new(
-        "https://api.devnet.solana.com", // Use devnet for testing
+    // Resolve the cluster from QUANTUM_VEIL_CLUSTER, defaulting to devnet for testing
+    let cluster = ClusterRegistry::from_env()?.resolve(&Cluster::from_env()?)?;
+    println!("Using cluster: {}", cluster.rpc_url);
+    
+    // Create privacy client, proxied if QUANTUM_VEIL_PROXY is set (e.g. socks5h://127.0.0.1:9050 for Tor)
+    let client = GlitchGangPrivacyClient::for_cluster(
+        &cluster,
         keypair,
-    );
+        &ProxyConfig::from_env()?,
+    )?;
     
     // Create example VRM data
     println!("\nCreating example VRM data...");
@@ -100,6 +109,12 @@ fn create_example_vrm_data() -> VrmData {
             z: 0.0,
             w: 0.707,
         },
+        scale: Some(ScaleData {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        }),
+        bounding_box: None,
         voice: Some(VoiceData {
             frequency: vec![440.0, 880.0, 1320.0],
             amplitude: vec![0.8, 0.4, 0.2],
@@ -114,6 +129,7 @@ fn create_example_vrm_data() -> VrmData {
                 joint_rotations: HashMap::new(),
             }
         ],
+        interactions: Vec::new(),
         animations: HashMap::new(),
         custom_data: HashMap::new(),
     }
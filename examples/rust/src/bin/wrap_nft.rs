@@ -13,6 +13,9 @@ use project_89::{
     GlitchGangMetadata,
     PrivacyLevel,
     EntropySource,
+    ProxyConfig,
+    Cluster,
+    ClusterRegistry,
 };
 
 /// Example program to wrap an existing NFT with privacy features
@@ -20,11 +23,11 @@ use project_89::{
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
-
+    
     println!("\n⧂ PROJECT 89: QUANTUM VEIL ⧂");
     println!("NFT Privacy Wrapper Example");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-
+    
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
     
@@ -42,11 +45,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let metadata = load_metadata(metadata_path)?;
     println!("Loaded metadata for: {}", metadata.name);
     
-    // Create privacy client
-    let client = GlitchGangPrivacyClient::new(
-        "https://api.devnet.solana.com", // Use devnet for testing
+    // Resolve the cluster from QUANTUM_VEIL_CLUSTER, defaulting to devnet for testing
+    let cluster = ClusterRegistry::from_env()?.resolve(&Cluster::from_env()?)?;
+    println!("Using cluster: {}", cluster.rpc_url);
+    
+    // Create privacy client, proxied if QUANTUM_VEIL_PROXY is set (e.g. socks5h://127.0.0.1:9050 for Tor)
+    let client = GlitchGangPrivacyClient::for_cluster(
+        &cluster,
         keypair,
-    );
+        &ProxyConfig::from_env()?,
+    )?;
     
     // Create wrapper account
     println!("\nCreating privacy wrapper...");
@@ -55,7 +63,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Apply privacy protections to metadata
     println!("\nApplying privacy protections...");
-    let protected_metadata = client.protect_metadata(&metadata, PrivacyLevel::Medium).await?;
+    let report = client.protect_metadata(&metadata, PrivacyLevel::Medium).await?;
+    println!("✓ Encrypted attributes: {}", report.encrypted_traits.join(", "));
+    
+    // Publish the config hash so the wrapper points at the protected metadata
+    client.update_privacy_settings(&wrapper_account, &report.config_hash).await?;
+    println!("✓ Privacy config hash updated");
     
     // Save protected metadata
     let output_path = format!("protected_{}.json", nft_mint);
@@ -63,7 +76,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\nSaving protected metadata to {}", output_path);
     std::fs::write(
         &output_path,
-        serde_json::to_string_pretty(&protected_metadata)?,
+        serde_json::to_string_pretty(&report.protected_metadata)?,
     )?;
     println!("✓ Protected metadata saved");
     
@@ -73,6 +86,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  - Wrapper account: {}", wrapper_account);
     println!("  - Protected metadata: {}", output_path);
     println!("  - Privacy level: {:?}", PrivacyLevel::Medium);
+    println!("  - Encryption key ID: {}", report.key_id);
     println!("");
     println!("You can now update the NFT metadata URI to point to the protected metadata");
     println!("Or use the protected metadata with your Glitch Gang applications");
@@ -87,6 +101,9 @@ fn parse_args(args: &[String]) -> Result<(&str, &str, &str), Box<dyn std::error:
         println!("  - keypair_path: Path to the wallet keypair file");
         println!("  - nft_mint: Mint address of the NFT to wrap");
         println!("  - metadata_path: Path to the NFT metadata JSON file");
+        println!("Set QUANTUM_VEIL_PROXY to route network calls through a proxy, e.g. socks5h://127.0.0.1:9050 for Tor");
+        println!("Set QUANTUM_VEIL_CLUSTER to mainnet, devnet, testnet, localnet, a registered name or an RPC URL,");
+        println!("and QUANTUM_VEIL_CLUSTERS to a registry file overriding the built-in endpoints and program IDs");
         std::process::exit(1);
     }
     
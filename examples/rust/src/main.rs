@@ -13,8 +13,12 @@ use project_89::{
     VrmData,
     PositionData,
     RotationData,
+    ScaleData,
     VoiceData,
     GestureData,
+    ProxyConfig,
+    Cluster,
+    ClusterRegistry,
 };
 
 /// Project 89: Quantum Veil - Demo CLI
@@ -34,11 +38,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     println!("Using wallet: {}", keypair.pubkey());
     
-    // Create privacy client
-    let client = GlitchGangPrivacyClient::new(
-        "https://api.devnet.solana.com", // Use devnet for testing
+    // Resolve the cluster from QUANTUM_VEIL_CLUSTER, defaulting to devnet for testing
+    let cluster = ClusterRegistry::from_env()?.resolve(&Cluster::from_env()?)?;
+    println!("Using cluster: {}", cluster.rpc_url);
+    
+    // Create privacy client, proxied if QUANTUM_VEIL_PROXY is set (e.g. socks5h://127.0.0.1:9050 for Tor)
+    let client = GlitchGangPrivacyClient::for_cluster(
+        &cluster,
         keypair,
-    );
+        &ProxyConfig::from_env()?,
+    )?;
     
     // Demo NFT details
     let nft_mint_address = "3jKpTiKAAtnJMLcQsNk82ua7crubQ86e8KfTQB9fKDwp";
@@ -154,28 +163,20 @@ async fn demo_privacy_levels(
         println!("\nApplying privacy level: {:?}", level);
         
         // Apply privacy protection
-        let protected_metadata = client.protect_metadata(&metadata, *level).await?;
+        let report = client.protect_metadata(&metadata, *level).await?;
+        let protected_metadata = &report.protected_metadata;
         
         // Report on protected attributes
-        match level {
-            PrivacyLevel::None => {
-                println!("No attributes protected");
-            },
-            PrivacyLevel::Light => {
-                println!("Protected attributes: Secret Code, Agent Name");
-            },
-            PrivacyLevel::Medium => {
-                println!("Protected attributes: Secret Code, Agent Name, Mission, Origin");
-            },
-            PrivacyLevel::Heavy | PrivacyLevel::Complete => {
-                println!("Protected attributes: Secret Code, Agent Name, Mission, Origin, Accessory, Symbols");
-            },
+        if report.encrypted_traits.is_empty() {
+            println!("No attributes protected");
+        } else {
+            println!("Protected attributes: {}", report.encrypted_traits.join(", "));
         }
         
         // Save the protected metadata (only for Medium level)
         if *level == PrivacyLevel::Medium {
             let output_file = "protected_metadata.json";
-            let json = serde_json::to_string_pretty(&protected_metadata)?;
+            let json = serde_json::to_string_pretty(protected_metadata)?;
             fs::write(output_file, json)?;
             println!("Saved Medium privacy level metadata to {}", output_file);
         }
@@ -205,6 +206,12 @@ async fn demo_vrm_privacy(
             z: 0.0,
             w: 0.707,
         },
+        scale: Some(ScaleData {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        }),
+        bounding_box: None,
         voice: Some(VoiceData {
             frequency: vec![440.0, 880.0, 1320.0],
             amplitude: vec![0.8, 0.4, 0.2],
@@ -219,6 +226,7 @@ async fn demo_vrm_privacy(
                 joint_rotations: HashMap::new(),
             }
         ],
+        interactions: Vec::new(),
         animations: HashMap::new(),
         custom_data: HashMap::new(),
     };
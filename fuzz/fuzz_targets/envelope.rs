@@ -0,0 +1,14 @@
+//! Envelopes arrive in retrieved fragments, manifests and metadata
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quantum_veil_primitives::envelope::Envelope;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(envelope) = Envelope::parse(data) {
+        assert_eq!(envelope.to_bytes(), data);
+        assert!(envelope.open(&[7u8; 32]).is_err());
+        assert!(envelope.open(&[]).is_err());
+    }
+});
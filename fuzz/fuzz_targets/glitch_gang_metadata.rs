@@ -0,0 +1,15 @@
+//! Metadata JSON is fetched from each NFT's URI
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use project_89::GlitchGangMetadata;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(metadata) = serde_json::from_slice::<GlitchGangMetadata>(data) {
+        // Compared as values, since maps in private data serialize in any order
+        let encoded = serde_json::to_vec(&metadata).unwrap();
+        let reparsed: GlitchGangMetadata = serde_json::from_slice(&encoded).unwrap();
+        assert_eq!(serde_json::to_value(&reparsed).unwrap(), serde_json::to_value(&metadata).unwrap());
+    }
+});
@@ -0,0 +1,24 @@
+//! Manifests are fetched from wherever an NFT's metadata points
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use timeline_shifter::{FragmentManifest, TimelineType};
+
+fuzz_target!(|data: &[u8]| {
+    let manifest: FragmentManifest = match serde_json::from_slice(data) {
+        Ok(manifest) => manifest,
+        Err(_) => return,
+    };
+    
+    let encoded = serde_json::to_vec(&manifest).unwrap();
+    let reparsed: FragmentManifest = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(reparsed.hash(), manifest.hash());
+    
+    // Sealed fields under the wrong key fail without panicking
+    let key = [3u8; 32];
+    let _ = manifest.real_fragment_ids(&key);
+    let _ = manifest.fragment_lengths(&key);
+    let _ = manifest.content_keys(&key);
+    let _ = manifest.timeline_fragments(&TimelineType::Social, &key);
+});
@@ -0,0 +1,21 @@
+//! Wrapper account data as read back from any account passed to the program
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use privacy_wrapper::state::{migrate_legacy, PrivacyWrapper, PrivacyWrapperView, HEADER_LEN};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(wrapper) = PrivacyWrapper::unpack(data) {
+        // Decoded state packs back into an account of the same size
+        let mut packed = vec![0u8; data.len().max(HEADER_LEN)];
+        if wrapper.pack(&mut packed).is_ok() {
+            assert_eq!(PrivacyWrapper::unpack(&packed).unwrap(), wrapper);
+        }
+    }
+    
+    let mut migrated = data.to_vec();
+    if migrate_legacy(&mut migrated).is_ok() {
+        let _ = PrivacyWrapperView::load(&migrated);
+    }
+});
@@ -0,0 +1,15 @@
+//! Instruction data is whatever a transaction carries
+
+#![no_main]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use libfuzzer_sys::fuzz_target;
+use privacy_wrapper::instruction::WrapperInstruction;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = WrapperInstruction::try_from_slice(data) {
+        // Anything the program accepts must survive a round trip unchanged
+        let encoded = instruction.try_to_vec().unwrap();
+        assert_eq!(WrapperInstruction::try_from_slice(&encoded).unwrap(), instruction);
+    }
+});
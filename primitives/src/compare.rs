@@ -0,0 +1,17 @@
+//! Constant-time comparisons
+//!
+//! See the core crate's `quantum_veil::compare` for the threat model. Only
+//! lengths and list sizes may leak.
+
+use subtle::ConstantTimeEq;
+
+/// Compare two byte strings in time independent of their contents
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Check if any candidate equals a value, comparing against every candidate
+pub fn constant_time_contains<T: AsRef<[u8]>>(candidates: &[T], value: &[u8]) -> bool {
+    // Non-short-circuiting `|` so a match does not end the scan early
+    candidates.iter().fold(false, |found, candidate| found | constant_time_eq(candidate.as_ref(), value))
+}
@@ -0,0 +1,51 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use sha3::{Digest, Sha3_512};
+
+/// Encrypt data using ChaCha20Poly1305
+pub fn encrypt_data(data: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
+    let (cipher, cipher_nonce) = cipher(key, nonce)?;
+    
+    cipher.encrypt(cipher_nonce, data)
+        .map_err(|e| format!("Encryption error: {}", e))
+}
+
+/// Decrypt data using ChaCha20Poly1305
+pub fn decrypt_data(ciphertext: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
+    let (cipher, cipher_nonce) = cipher(key, nonce)?;
+    
+    cipher.decrypt(cipher_nonce, ciphertext)
+        .map_err(|e| format!("Decryption error: {}", e))
+}
+
+/// Create a deterministic key and nonce from a seed
+pub fn derive_key_from_seed(seed: &[u8]) -> ([u8; 32], [u8; 12]) {
+    let mut hasher = Sha3_512::new();
+    hasher.update(seed);
+    let result = hasher.finalize();
+    
+    // Split the hash result into key and nonce
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    
+    key.copy_from_slice(&result[0..32]);
+    nonce.copy_from_slice(&result[32..44]);
+    
+    (key, nonce)
+}
+
+/// Check key and nonce lengths and build the cipher
+pub(crate) fn cipher<'a>(key: &[u8], nonce: &'a [u8]) -> Result<(ChaCha20Poly1305, &'a Nonce), String> {
+    if key.len() != 32 {
+        return Err(format!("Invalid key length: {}, expected 32", key.len()));
+    }
+    
+    if nonce.len() != 12 {
+        return Err(format!("Invalid nonce length: {}, expected 12", nonce.len()));
+    }
+    
+    Ok((ChaCha20Poly1305::new(Key::from_slice(key)), Nonce::from_slice(nonce)))
+}
@@ -0,0 +1,346 @@
+//! Self-describing encrypted envelopes
+//!
+//! Every ciphertext Quantum Veil produces is wrapped in an envelope naming
+//! the format version, the cipher, the key it was sealed under, its nonce
+//! and the associated data it is bound to, so readers can tell what they
+//! hold and refuse what they can't open instead of decrypting garbage.
+//!
+//! Layout, with integers little-endian:
+//!
+//! | Field      | Size                               |
+//! |------------|------------------------------------|
+//! | magic      | 4 (`QVEL`)                         |
+//! | version    | 1                                  |
+//! | algorithm  | 1                                  |
+//! | key ID     | 1-byte length, then the ID         |
+//! | nonce      | fixed by the algorithm             |
+//! | AAD        | 2-byte length, then the AAD        |
+//! | ciphertext | the rest, authentication tag last  |
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chacha20poly1305::aead::{Aead, Payload};
+use sha3::{Digest, Sha3_512};
+
+use crate::compare::constant_time_eq;
+use crate::encryption::cipher;
+
+/// Bytes every envelope starts with
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"QVEL";
+
+/// Envelope format version written by this crate
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Length of the key IDs `key_id` derives
+pub const KEY_ID_LEN: usize = 8;
+
+/// Domain label for deriving key IDs
+const KEY_ID_LABEL: &[u8] = b"quantum-veil/envelope-key-id";
+
+/// Domain label for deriving per-envelope blinded key IDs
+const BLINDED_KEY_ID_LABEL: &[u8] = b"quantum-veil/envelope-blinded-key-id";
+
+/// Cipher an envelope was sealed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Algorithm {
+    /// ChaCha20Poly1305 with a 32-byte key and 12-byte nonce
+    ChaCha20Poly1305 = 1,
+}
+
+impl Algorithm {
+    /// Get the algorithm with a header byte
+    pub fn from_byte(byte: u8) -> Result<Self, String> {
+        match byte {
+            1 => Ok(Self::ChaCha20Poly1305),
+            other => Err(format!("Unknown envelope algorithm: {}", other)),
+        }
+    }
+    
+    /// Get the nonce length the algorithm takes
+    pub const fn nonce_len(self) -> usize {
+        match self {
+            Self::ChaCha20Poly1305 => 12,
+        }
+    }
+    
+    /// Get the length of the authentication tag ending the ciphertext
+    pub const fn tag_len(self) -> usize {
+        match self {
+            Self::ChaCha20Poly1305 => 16,
+        }
+    }
+}
+
+/// Encrypted data with the header describing how to open it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    /// Format version
+    pub version: u8,
+    /// Cipher the data was sealed with
+    pub algorithm: Algorithm,
+    /// ID of the key the data was sealed under
+    pub key_id: Vec<u8>,
+    /// Nonce the data was sealed with
+    pub nonce: Vec<u8>,
+    /// Associated data the ciphertext is bound to, stored in the clear
+    pub aad: Vec<u8>,
+    /// Ciphertext followed by its authentication tag
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Encrypt data with ChaCha20Poly1305, binding it to `aad`
+    ///
+    /// The nonce must never be reused with the same key for different data.
+    pub fn seal(plaintext: &[u8], key: &[u8], nonce: &[u8], aad: &[u8]) -> Result<Self, String> {
+        Self::seal_bound(plaintext, key, nonce, aad, &[])
+    }
+    
+    /// Encrypt data, binding it to the stored `aad` and to a `context` that isn't stored
+    ///
+    /// Opening needs the same context, e.g. the NFT and owner the data
+    /// belongs to, supplied by the reader rather than read from the envelope.
+    pub fn seal_bound(plaintext: &[u8], key: &[u8], nonce: &[u8], aad: &[u8], context: &[u8]) -> Result<Self, String> {
+        let algorithm = Algorithm::ChaCha20Poly1305;
+        if aad.len() > u16::MAX as usize {
+            return Err(format!("Associated data too long: {} bytes", aad.len()));
+        }
+        
+        let (cipher, cipher_nonce) = cipher(key, nonce)?;
+        let ciphertext = cipher.encrypt(cipher_nonce, Payload { msg: plaintext, aad: &[aad, context].concat() })
+            .map_err(|e| format!("Encryption error: {}", e))?;
+        
+        Ok(Self {
+            version: ENVELOPE_VERSION,
+            algorithm,
+            key_id: key_id(key).to_vec(),
+            nonce: nonce.to_vec(),
+            aad: aad.to_vec(),
+            ciphertext,
+        })
+    }
+    
+    /// Encrypt data as `seal_bound`, naming the key by an ID blinded with the nonce
+    ///
+    /// The ID differs for every nonce, so envelopes sealed under one key
+    /// can't be linked by their headers, while a holder of the key can
+    /// still tell it holds the right one.
+    pub fn seal_blinded(plaintext: &[u8], key: &[u8], nonce: &[u8], aad: &[u8], context: &[u8]) -> Result<Self, String> {
+        let mut envelope = Self::seal_bound(plaintext, key, nonce, aad, context)?;
+        envelope.key_id = blinded_key_id(key, nonce).to_vec();
+        Ok(envelope)
+    }
+    
+    /// Decrypt the data, checking it was sealed under `key`
+    pub fn open(&self, key: &[u8]) -> Result<Vec<u8>, String> {
+        self.open_bound(key, &[])
+    }
+    
+    /// Decrypt data sealed with `seal_bound` or `seal_blinded`, checking it was bound to `context`
+    pub fn open_bound(&self, key: &[u8], context: &[u8]) -> Result<Vec<u8>, String> {
+        let named = constant_time_eq(&self.key_id, &key_id(key));
+        let blinded = constant_time_eq(&self.key_id, &blinded_key_id(key, &self.nonce));
+        if !(named | blinded) {
+            return Err("Envelope was sealed under a different key".into());
+        }
+        
+        let (cipher, cipher_nonce) = cipher(key, &self.nonce)?;
+        cipher.decrypt(cipher_nonce, Payload { msg: &self.ciphertext, aad: &[&self.aad[..], context].concat() })
+            .map_err(|_| "Decryption failed: envelope was tampered with or belongs to another context".into())
+    }
+    
+    /// Parse and validate a serialized envelope
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes };
+        
+        if reader.take(ENVELOPE_MAGIC.len())? != ENVELOPE_MAGIC {
+            return Err("Not an envelope: bad magic".into());
+        }
+        let version = reader.take(1)?[0];
+        if version != ENVELOPE_VERSION {
+            return Err(format!("Unsupported envelope version: {}", version));
+        }
+        let algorithm = Algorithm::from_byte(reader.take(1)?[0])?;
+        
+        let key_id_len = reader.take(1)?[0] as usize;
+        let key_id = reader.take(key_id_len)?.to_vec();
+        let nonce = reader.take(algorithm.nonce_len())?.to_vec();
+        let aad_len = u16::from_le_bytes(reader.take(2)?.try_into().unwrap()) as usize;
+        let aad = reader.take(aad_len)?.to_vec();
+        
+        if reader.bytes.len() < algorithm.tag_len() {
+            return Err("Envelope ciphertext is shorter than its tag".into());
+        }
+        
+        Ok(Self { version, algorithm, key_id, nonce, aad, ciphertext: reader.bytes.to_vec() })
+    }
+    
+    /// Serialize the envelope
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(header_len(self.key_id.len(), self.aad.len()) + self.ciphertext.len());
+        bytes.extend_from_slice(&ENVELOPE_MAGIC);
+        bytes.push(self.version);
+        bytes.push(self.algorithm as u8);
+        bytes.push(self.key_id.len() as u8);
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&(self.aad.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.aad);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+}
+
+/// Check if bytes start like an envelope
+///
+/// Data that doesn't is from before envelopes were introduced.
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ENVELOPE_MAGIC)
+}
+
+/// Get how many bytes an envelope adds to its plaintext, for keys identified by `key_id`
+pub const fn envelope_overhead(aad_len: usize) -> usize {
+    header_len(KEY_ID_LEN, aad_len) + Algorithm::ChaCha20Poly1305.tag_len()
+}
+
+/// Derive the ID envelopes name a key by
+///
+/// IDs are one-way, so they identify a key without revealing it.
+pub fn key_id(key: &[u8]) -> [u8; KEY_ID_LEN] {
+    let mut hasher = Sha3_512::new();
+    hasher.update(KEY_ID_LABEL);
+    hasher.update(key);
+    
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&hasher.finalize()[..KEY_ID_LEN]);
+    id
+}
+
+/// Derive the key ID `seal_blinded` names a key by for one nonce
+pub fn blinded_key_id(key: &[u8], nonce: &[u8]) -> [u8; KEY_ID_LEN] {
+    let mut hasher = Sha3_512::new();
+    hasher.update(BLINDED_KEY_ID_LABEL);
+    hasher.update(key);
+    hasher.update(nonce);
+    
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&hasher.finalize()[..KEY_ID_LEN]);
+    id
+}
+
+/// Get the header length of a ChaCha20Poly1305 envelope
+const fn header_len(key_id_len: usize, aad_len: usize) -> usize {
+    ENVELOPE_MAGIC.len() + 3 + key_id_len + Algorithm::ChaCha20Poly1305.nonce_len() + 2 + aad_len
+}
+
+/// Cursor over envelope bytes
+struct Reader<'a> {
+    /// Bytes not yet read
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Read the next `len` bytes
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err("Envelope truncated".into());
+        }
+        
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    
+    #[test]
+    fn test_envelope_describes_and_opens_its_ciphertext() {
+        let key = [4u8; 32];
+        let envelope = Envelope::seal(b"envelope contents", &key, &[9u8; 12], b"context").unwrap();
+        let bytes = envelope.to_bytes();
+        
+        assert!(is_envelope(&bytes));
+        assert_eq!(bytes.len(), b"envelope contents".len() + envelope_overhead(b"context".len()));
+        
+        let parsed = Envelope::parse(&bytes).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!((parsed.algorithm, &parsed.key_id[..], &parsed.aad[..]), (Algorithm::ChaCha20Poly1305, &key_id(&key)[..], &b"context"[..]));
+        assert_eq!(parsed.open(&key).unwrap(), b"envelope contents");
+        
+        // Wrong key, edited associated data, unknown version and algorithm
+        assert!(parsed.open(&[5u8; 32]).unwrap_err().contains("different key"));
+        assert!(parsed.open_bound(&key, b"unexpected context").unwrap_err().contains("another context"));
+        let mut rebound = parsed.clone();
+        rebound.aad = b"elsewhere".to_vec();
+        assert!(rebound.open(&key).is_err());
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert!(Envelope::parse(&future).unwrap_err().contains("version"));
+        let mut unknown = bytes;
+        unknown[5] = 0;
+        assert!(Envelope::parse(&unknown).unwrap_err().contains("algorithm"));
+    }
+    
+    #[test]
+    fn test_blinded_envelopes_are_unlinkable_but_open() {
+        let key = [4u8; 32];
+        let first = Envelope::seal_blinded(b"fragment", &key, &[1u8; 12], &[], b"context").unwrap();
+        let second = Envelope::seal_blinded(b"fragment", &key, &[2u8; 12], &[], b"context").unwrap();
+        
+        // Neither names the key the way plain envelopes do, nor each other
+        assert_ne!(first.key_id, second.key_id);
+        assert_ne!(first.key_id, key_id(&key));
+        assert_eq!(first.to_bytes().len(), Envelope::seal(b"fragment", &key, &[1u8; 12], &[]).unwrap().to_bytes().len());
+        
+        assert_eq!(first.open_bound(&key, b"context").unwrap(), b"fragment");
+        assert!(second.open_bound(&[5u8; 32], b"context").unwrap_err().contains("different key"));
+    }
+    
+    proptest! {
+        #[test]
+        fn prop_parse_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = Envelope::parse(&bytes);
+            
+            let mut prefixed = ENVELOPE_MAGIC.to_vec();
+            prefixed.extend_from_slice(&[ENVELOPE_VERSION, Algorithm::ChaCha20Poly1305 as u8]);
+            prefixed.extend_from_slice(&bytes);
+            if let Ok(envelope) = Envelope::parse(&prefixed) {
+                prop_assert_eq!(envelope.to_bytes(), prefixed);
+            }
+        }
+        
+        #[test]
+        fn prop_sealed_envelopes_round_trip(
+            data in proptest::collection::vec(any::<u8>(), 0..1024),
+            aad in proptest::collection::vec(any::<u8>(), 0..64),
+            key in any::<[u8; 32]>(),
+            nonce in any::<[u8; 12]>(),
+        ) {
+            let bytes = Envelope::seal(&data, &key, &nonce, &aad).unwrap().to_bytes();
+            prop_assert_eq!(Envelope::parse(&bytes).unwrap().open(&key).unwrap(), data);
+        }
+        
+        #[test]
+        fn prop_truncated_or_flipped_envelopes_fail(
+            data in proptest::collection::vec(any::<u8>(), 1..256),
+            cut in any::<prop::sample::Index>(),
+            flip in any::<prop::sample::Index>(),
+        ) {
+            let key = [1u8; 32];
+            let bytes = Envelope::seal(&data, &key, &[2u8; 12], b"aad").unwrap().to_bytes();
+            
+            let truncated = &bytes[..cut.index(bytes.len())];
+            prop_assert!(Envelope::parse(truncated).and_then(|envelope| envelope.open(&key)).is_err());
+            
+            let mut flipped = bytes.clone();
+            flipped[flip.index(bytes.len())] ^= 1;
+            prop_assert!(Envelope::parse(&flipped).and_then(|envelope| envelope.open(&key)).is_err());
+        }
+    }
+}
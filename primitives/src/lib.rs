@@ -0,0 +1,35 @@
+//! no_std building blocks of Quantum Veil
+//!
+//! Encryption, key derivation, constant-time comparison, and the noise math
+//! behind synchronicity masks, with no dependency on `std`, RPC, or HTTP, so
+//! other on-chain programs and constrained targets can use them. Only
+//! `alloc` is required. The core crate wraps these for its std types.
+//!
+//! Features:
+//! - `std` (default): use the platform's float math and std error impls
+//! - `encryption` (default): ChaCha20Poly1305, key derivation, and the
+//!   self-describing envelopes ciphertexts are stored in
+//! - `masking` (default): seeded noise for positions, rotations, and voice,
+//!   with vectorized batch noise in `simd`
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod compare;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+#[cfg(feature = "encryption")]
+pub mod envelope;
+#[cfg(feature = "masking")]
+pub mod masking;
+#[cfg(feature = "masking")]
+pub mod simd;
+#[cfg(feature = "masking")]
+mod math;
+
+pub use compare::{constant_time_eq, constant_time_contains};
+#[cfg(feature = "encryption")]
+pub use encryption::{encrypt_data, decrypt_data, derive_key_from_seed};
+#[cfg(feature = "encryption")]
+pub use envelope::{Envelope, Algorithm, blinded_key_id, envelope_overhead, is_envelope, key_id};
@@ -0,0 +1,165 @@
+//! Seeded noise behind synchronicity masks
+//!
+//! Vectors are `[x, y, z]` and quaternions `[x, y, z, w]`. The same seed
+//! always yields the same noise, so masked data can be reproduced.
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::math::{cos, sin, sqrt};
+
+/// Add noise to a position
+pub fn position_noise(position: [f32; 3], intensity: f32, seed: u64) -> [f32; 3] {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    position.map(|axis| axis + (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 10.0) // Scale for position
+}
+
+/// Add a small random rotation to a quaternion
+pub fn rotation_noise(rotation: [f32; 4], intensity: f32, seed: u64) -> [f32; 4] {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    let noise_angle = intensity * core::f32::consts::PI * rng.gen::<f32>();
+    let axis = [
+        rng.gen::<f32>() * 2.0 - 1.0,
+        rng.gen::<f32>() * 2.0 - 1.0,
+        rng.gen::<f32>() * 2.0 - 1.0,
+    ];
+    
+    // Normalize axis
+    let mag = sqrt(axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]);
+    let [ax, ay, az] = axis.map(|component| component / mag);
+    
+    // Create noise quaternion
+    let sin_half_angle = sin(noise_angle / 2.0);
+    let (nx, ny, nz, nw) = (ax * sin_half_angle, ay * sin_half_angle, az * sin_half_angle, cos(noise_angle / 2.0));
+    
+    // Apply noise quaternion (quaternion multiplication)
+    let [x, y, z, w] = rotation;
+    normalize([
+        w * nx + x * nw + y * nz - z * ny,
+        w * ny - x * nz + y * nw + z * nx,
+        w * nz + x * ny - y * nx + z * nw,
+        w * nw - x * nx - y * ny - z * nz,
+    ])
+}
+
+/// Scale uniformly by a random factor, so proportions are not distorted
+pub fn scale_noise(scale: [f32; 3], intensity: f32, seed: u64) -> [f32; 3] {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    let factor = 1.0 + (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 0.5;
+    scale.map(|axis| (axis * factor).max(0.01))
+}
+
+/// Grow a bounding box by random margins, so it still contains the avatar
+pub fn bounding_box_noise(min: [f32; 3], max: [f32; 3], intensity: f32, seed: u64) -> ([f32; 3], [f32; 3]) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    let min = min.map(|axis| axis - rng.gen::<f32>() * intensity);
+    let max = max.map(|axis| axis + rng.gen::<f32>() * intensity);
+    (min, max)
+}
+
+/// Add noise to voice frequency and amplitude components, pitch, and timbre
+pub fn voice_noise(
+    frequency: &mut [f32],
+    amplitude: &mut [f32],
+    pitch: &mut f32,
+    timbre: &mut f32,
+    intensity: f32,
+    seed: u64,
+) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    for freq in frequency {
+        *freq += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 100.0;
+        *freq = freq.max(0.0); // Frequencies must be positive
+    }
+    
+    for amp in amplitude {
+        *amp += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
+        *amp = amp.max(0.0); // Amplitudes must be positive
+    }
+    
+    *pitch += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * 50.0;
+    *pitch = pitch.max(0.0); // Pitch must be positive
+    
+    *timbre += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
+    *timbre = timbre.clamp(0.0, 1.0); // Timbre is normalized 0.0-1.0
+}
+
+/// Add noise to a gesture's intensity and speed
+pub fn gesture_noise(gesture_intensity: &mut f32, speed: &mut f32, intensity: f32, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    *gesture_intensity += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
+    *gesture_intensity = gesture_intensity.clamp(0.0, 1.0); // Intensity normalized 0.0-1.0
+    
+    *speed += (rng.gen::<f32>() - 0.5) * 2.0 * intensity * *speed;
+    *speed = speed.max(0.1); // Speed must be positive
+}
+
+/// Jitter an interaction timestamp in milliseconds and add noise to its intensity
+pub fn interaction_noise(timestamp: &mut u64, interaction_intensity: &mut f32, intensity: f32, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    // Jitter the timestamp by up to a second at full intensity
+    let jitter_ms = ((rng.gen::<f32>() - 0.5) * 2.0 * intensity * 1000.0) as i64;
+    *timestamp = timestamp.saturating_add_signed(jitter_ms);
+    
+    *interaction_intensity += (rng.gen::<f32>() - 0.5) * 2.0 * intensity;
+    *interaction_intensity = interaction_intensity.clamp(0.0, 1.0); // Intensity normalized 0.0-1.0
+}
+
+/// Create a random position in a typical avatar range
+pub fn random_position(seed: u64) -> [f32; 3] {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    [
+        rng.gen_range(-10.0..10.0),
+        rng.gen_range(-2.0..5.0), // Typical avatar height range
+        rng.gen_range(-10.0..10.0),
+    ]
+}
+
+/// Create a random unit quaternion
+pub fn random_rotation(seed: u64) -> [f32; 4] {
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    normalize([
+        rng.gen_range(-1.0..1.0),
+        rng.gen_range(-1.0..1.0),
+        rng.gen_range(-1.0..1.0),
+        rng.gen_range(-1.0..1.0),
+    ])
+}
+
+/// Scale a quaternion to unit length
+fn normalize(quaternion: [f32; 4]) -> [f32; 4] {
+    let mag = sqrt(quaternion.iter().map(|component| component * component).sum());
+    quaternion.map(|component| component / mag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_noise_is_seeded_and_keeps_rotations_unit() {
+        let rotation = random_rotation(7);
+        assert_eq!(rotation, random_rotation(7));
+        assert_ne!(rotation, random_rotation(8));
+        
+        let noisy = rotation_noise(rotation, 0.7, 42);
+        assert_eq!(noisy, rotation_noise(rotation, 0.7, 42));
+        assert!((noisy.iter().map(|c| c * c).sum::<f32>() - 1.0).abs() < 1e-5);
+        
+        // Bounding boxes only grow
+        let (min, max) = bounding_box_noise([-1.0; 3], [1.0; 3], 0.5, 42);
+        assert!(min.iter().all(|&axis| axis <= -1.0) && max.iter().all(|&axis| axis >= 1.0));
+        
+        // No noise at zero intensity
+        assert_eq!(position_noise([1.0, 2.0, 3.0], 0.0, 42), [1.0, 2.0, 3.0]);
+    }
+}
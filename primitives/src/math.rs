@@ -0,0 +1,44 @@
+//! Float functions missing from `core`
+//!
+//! With `std` these use the platform implementations, so masked output
+//! matches earlier releases bit for bit; without it they fall back to `libm`.
+
+#[cfg(feature = "std")]
+pub fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+
+#[cfg(feature = "std")]
+pub fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+#[cfg(feature = "std")]
+pub fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+#[cfg(feature = "std")]
+pub fn tan(x: f32) -> f32 {
+    x.tan()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn tan(x: f32) -> f32 {
+    libm::tanf(x)
+}
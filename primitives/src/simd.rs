@@ -0,0 +1,180 @@
+//! Vectorized batch noise
+//!
+//! Masking hundreds of joints per frame for many viewers spends most of its
+//! time seeding a ChaCha generator per joint. The batch functions here draw
+//! noise from a counter-based hash instead, so every value's noise is
+//! independent, and work on `LANES` values at a time in loops the compiler
+//! turns into SIMD. On x86 with `std`, AVX2 is detected at runtime; otherwise
+//! the same kernels run with the target's baseline instructions and produce
+//! identical output.
+//!
+//! Batch noise is seeded like the functions in `masking`, but does not
+//! reproduce their values.
+
+use crate::math::{sqrt, tan};
+
+/// Number of values processed together
+pub const LANES: usize = 8;
+
+/// Get the instruction set the batch kernels run with on this machine
+pub fn backend() -> &'static str {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    if std::is_x86_feature_detected!("avx2") {
+        return "avx2";
+    }
+    "portable"
+}
+
+/// Run a kernel with AVX2 if the CPU supports it, otherwise portably
+macro_rules! dispatch {
+    ($kernel:ident($($arg:expr),*)) => {{
+        #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked
+            return unsafe { avx2::$kernel($($arg),*) };
+        }
+        $kernel($($arg),*)
+    }};
+}
+
+/// Add noise to positions given as consecutive `[x, y, z]` components
+///
+/// Each component moves like in `masking::position_noise`, by up to ten
+/// times the intensity.
+pub fn position_noise_batch(components: &mut [f32], intensity: f32, seed: u64) {
+    dispatch!(offset_lanes(components, intensity * 10.0, key_of(seed)))
+}
+
+/// Rotate each quaternion by a small random rotation
+///
+/// The rotation angle grows with the intensity, up to about 120 degrees at
+/// full intensity. Quaternions stay unit length.
+pub fn rotation_noise_batch(rotations: &mut [[f32; 4]], intensity: f32, seed: u64) {
+    let spread = tan(core::f32::consts::FRAC_PI_4 * intensity.clamp(0.0, 1.0));
+    dispatch!(rotate_lanes(rotations, spread, key_of(seed)))
+}
+
+/// Kernels compiled with AVX2 enabled
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+mod avx2 {
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn offset_lanes(values: &mut [f32], scale: f32, key: u32) {
+        super::offset_lanes(values, scale, key)
+    }
+    
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn rotate_lanes(rotations: &mut [[f32; 4]], spread: f32, key: u32) {
+        super::rotate_lanes(rotations, spread, key)
+    }
+}
+
+/// Offset each value by uniform noise in `[-scale, scale)`
+#[inline(always)]
+fn offset_lanes(values: &mut [f32], scale: f32, key: u32) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    let mut counter = 0u32;
+    for chunk in &mut chunks {
+        for (lane, value) in chunk.iter_mut().enumerate() {
+            *value += (unit_noise(key, counter.wrapping_add(lane as u32)) - 0.5) * 2.0 * scale;
+        }
+        counter = counter.wrapping_add(LANES as u32);
+    }
+    
+    for (lane, value) in chunks.into_remainder().iter_mut().enumerate() {
+        *value += (unit_noise(key, counter.wrapping_add(lane as u32)) - 0.5) * 2.0 * scale;
+    }
+}
+
+/// Rotate quaternions `LANES` at a time, padding the last group with identities
+#[inline(always)]
+fn rotate_lanes(rotations: &mut [[f32; 4]], spread: f32, key: u32) {
+    for (group, chunk) in rotations.chunks_mut(LANES).enumerate() {
+        let mut lanes = [[0.0, 0.0, 0.0, 1.0]; LANES];
+        lanes[..chunk.len()].copy_from_slice(chunk);
+        rotate_group(&mut lanes, (group * LANES) as u32, spread, key);
+        chunk.copy_from_slice(&lanes[..chunk.len()]);
+    }
+}
+
+/// Rotate one group of quaternions, laid out by component so each step is one vector operation
+#[inline(always)]
+fn rotate_group(group: &mut [[f32; 4]; LANES], first: u32, spread: f32, key: u32) {
+    let component = |index: usize| -> [f32; LANES] { core::array::from_fn(|lane| group[lane][index]) };
+    let (x, y, z, w) = (component(0), component(1), component(2), component(3));
+    
+    for lane in 0..LANES {
+        // Noise quaternion [v, 1] normalized, with v drawn from a cube around zero
+        let counter = first.wrapping_add(lane as u32).wrapping_mul(3);
+        let [vx, vy, vz] = [0, 1, 2].map(|axis| (unit_noise(key, counter.wrapping_add(axis)) - 0.5) * 2.0 * spread);
+        let inv = 1.0 / sqrt(vx * vx + vy * vy + vz * vz + 1.0);
+        let (nx, ny, nz, nw) = (vx * inv, vy * inv, vz * inv, inv);
+        
+        let (x, y, z, w) = (x[lane], y[lane], z[lane], w[lane]);
+        let rotated = [
+            w * nx + x * nw + y * nz - z * ny,
+            w * ny - x * nz + y * nw + z * nx,
+            w * nz + x * ny - y * nx + z * nw,
+            w * nw - x * nx - y * ny - z * nz,
+        ];
+        let inv = 1.0 / sqrt(rotated.iter().map(|c| c * c).sum());
+        group[lane] = rotated.map(|c| c * inv);
+    }
+}
+
+/// Derive the noise key for a seed
+pub fn key_of(seed: u64) -> u32 {
+    mix((seed ^ (seed >> 32)) as u32)
+}
+
+/// Map a key and counter to uniform noise in `[0, 1)`
+///
+/// Shaders reproducing batch noise implement this and `mix` bit for bit.
+#[inline(always)]
+pub fn unit_noise(key: u32, counter: u32) -> f32 {
+    // 24 bits fit an f32 mantissa exactly; the signed cast converts in one instruction
+    (mix(counter.wrapping_mul(0x9e37_79b9) ^ key) >> 8) as i32 as f32 * (1.0 / 16_777_216.0)
+}
+
+/// Integer hash with good avalanche, built from vectorizable multiplies and shifts
+#[inline(always)]
+fn mix(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_batch_noise_matches_portable_kernels() {
+        let rotations: [[f32; 4]; 13] = core::array::from_fn(|i| crate::masking::random_rotation(i as u64));
+        
+        let mut dispatched = rotations;
+        rotation_noise_batch(&mut dispatched, 0.7, 42);
+        let mut portable = rotations;
+        rotate_lanes(&mut portable, tan(core::f32::consts::FRAC_PI_4 * 0.7), key_of(42));
+        assert_eq!(dispatched, portable);
+        assert!(dispatched.iter().all(|q| (q.iter().map(|c| c * c).sum::<f32>() - 1.0).abs() < 1e-5));
+        assert_ne!(dispatched, rotations);
+        
+        // Each value's noise depends only on its position, not the batch length
+        let mut prefix = [rotations[0], rotations[1], rotations[2]];
+        rotation_noise_batch(&mut prefix, 0.7, 42);
+        assert_eq!(prefix[..], dispatched[..3]);
+        
+        let mut positions = [1.0f32; 3 * 11];
+        position_noise_batch(&mut positions, 0.5, 7);
+        assert!(positions.iter().all(|&axis| (axis - 1.0).abs() <= 5.0));
+        assert!(positions.windows(2).any(|pair| pair[0] != pair[1]));
+        
+        // No noise at zero intensity
+        let mut still = rotations;
+        rotation_noise_batch(&mut still, 0.0, 42);
+        assert!(still.iter().zip(&rotations).all(|(a, b)| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)));
+    }
+}
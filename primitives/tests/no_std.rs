@@ -0,0 +1,22 @@
+//! Checks the crate builds without `std`
+//!
+//! Builds for the host by default, which catches `std` use in this crate.
+//! Set `NO_STD_TARGET` to a target without `std`, e.g.
+//! `thumbv7em-none-eabihf`, to also catch dependencies that need it.
+
+use std::process::Command;
+
+#[test]
+fn test_builds_without_std() {
+    let mut command = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    command
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["build", "--lib", "-p", "quantum-veil-primitives", "--no-default-features", "--features", "encryption,masking"])
+        .args(["--target-dir", concat!(env!("CARGO_TARGET_TMPDIR"), "/no_std")]);
+    if let Ok(target) = std::env::var("NO_STD_TARGET") {
+        command.args(["--target", &target]);
+    }
+    
+    let output = command.output().expect("Failed to run cargo");
+    assert!(output.status.success(), "no_std build failed:\n{}", String::from_utf8_lossy(&output.stderr));
+}
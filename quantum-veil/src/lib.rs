@@ -0,0 +1,42 @@
+//! Project 89: Quantum Veil
+//!
+//! One dependency for the whole privacy system. The client SDK is
+//! re-exported at the root, the core engine under its module names, and the
+//! model types both share under `types`:
+//!
+//! ```ignore
+//! use quantum_veil::{GlitchGangPrivacyClient, PrivacyLevel};
+//! use quantum_veil::timeline_shifter::TimelineShifter;
+//! ```
+
+pub use project_89::*;
+pub use quantum_veil_types as types;
+
+pub use audit;
+pub use network;
+pub use quantum_veil;
+pub use synchronicity_mask;
+pub use telemetry;
+pub use timeline_shifter;
+#[cfg(feature = "zk")]
+pub use zk;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_client_and_core_share_model_types() {
+        // Client and core values are interchangeable without conversion
+        let level: synchronicity_mask::PrivacyLevel = models::PrivacyLevel::Heavy;
+        assert_eq!(level, types::PrivacyLevel::Heavy);
+        assert_eq!(level.intensity_factor(), 0.75);
+        
+        let policy = ProtectionPolicy::default();
+        let distribution: &std::collections::HashMap<timeline_shifter::TimelineType, f32> = &policy.timeline_distribution;
+        assert!(distribution.contains_key(&types::TimelineType::Financial));
+        
+        let sources: Vec<quantum_veil::EntropySource> = vec![EntropySource::BlockchainHash, types::EntropySource::TimeEntropy];
+        assert_eq!(sources.len(), 2);
+    }
+}
@@ -0,0 +1,40 @@
+use solana_program::{
+    program_error::ProgramError,
+    msg,
+};
+use thiserror::Error;
+
+/// Custom error types for the access gate program
+#[derive(Error, Debug, Copy, Clone)]
+pub enum AccessGateError {
+    /// Invalid instruction
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    
+    /// Invalid account data
+    #[error("Invalid account data")]
+    InvalidAccountData,
+    
+    /// Account not initialized
+    #[error("Account not initialized")]
+    AccountNotInitialized,
+    
+    /// Not the owner of the gated wrapper
+    #[error("Not the wrapper owner")]
+    NotWrapperOwner,
+    
+    /// Not the gate authority
+    #[error("Not the gate authority")]
+    NotGateAuthority,
+    
+    /// Wrapped key is empty or too long
+    #[error("Invalid wrapped key")]
+    InvalidWrappedKey,
+}
+
+impl From<AccessGateError> for ProgramError {
+    fn from(e: AccessGateError) -> Self {
+        msg!("{}", e);
+        ProgramError::Custom(e as u32)
+    }
+}
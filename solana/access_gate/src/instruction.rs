@@ -0,0 +1,158 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use privacy_wrapper::marketplace::find_purchase_address;
+
+use crate::state::{find_gate_address, find_viewer_key_address};
+
+/// Instructions for the access gate program
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum GateInstruction {
+    /// Open a gate on a wrapper
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The wrapper owner (fee payer), who becomes the gate authority
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The gate PDA
+    /// 3. `[]` System program
+    CreateGate {
+        /// Access level a viewer needs for their key to be released
+        min_level: u8,
+    },
+    
+    /// Store or replace the data key wrapped to a viewer
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The gate authority (fee payer)
+    /// 1. `[]` The gate PDA
+    /// 2. `[writable]` The viewer key PDA
+    /// 3. `[]` System program
+    StoreKey {
+        /// Viewer the key is wrapped to
+        viewer: Pubkey,
+        /// Data key wrapped to the viewer
+        wrapped_key: Vec<u8>,
+    },
+    
+    /// Release a viewer's wrapped key as return data
+    ///
+    /// Fails with the wrapper program's `InsufficientAccess` error unless the
+    /// viewer holds the gate's level on the wrapper.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The viewer
+    /// 1. `[]` The gate PDA
+    /// 2. `[]` The wrapper account
+    /// 3. `[writable]` The viewer key PDA
+    /// 4. `[]` The privacy wrapper program
+    /// 5. `[]` Optional: the viewer's reveal purchase PDA on the wrapper
+    ///    program, so timed access bought through `PayToReveal` counts
+    ReleaseKey,
+    
+    /// Delete a viewer's key, returning its rent to the authority
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The gate authority
+    /// 1. `[]` The gate PDA
+    /// 2. `[writable]` The viewer key PDA
+    RemoveKey,
+}
+
+/// Create a `CreateGate` instruction
+pub fn create_gate(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    min_level: u8,
+) -> Instruction {
+    let (gate_account, _) = find_gate_address(program_id, wrapper_account);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(gate_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: GateInstruction::CreateGate { min_level }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `StoreKey` instruction
+pub fn store_key(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wrapper_account: &Pubkey,
+    viewer: &Pubkey,
+    wrapped_key: Vec<u8>,
+) -> Instruction {
+    let (gate_account, _) = find_gate_address(program_id, wrapper_account);
+    let (viewer_key_account, _) = find_viewer_key_address(program_id, &gate_account, viewer);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(gate_account, false),
+            AccountMeta::new(viewer_key_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: GateInstruction::StoreKey {
+            viewer: *viewer,
+            wrapped_key,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `ReleaseKey` instruction
+pub fn release_key(
+    program_id: &Pubkey,
+    viewer: &Pubkey,
+    wrapper_program_id: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let (gate_account, _) = find_gate_address(program_id, wrapper_account);
+    let (viewer_key_account, _) = find_viewer_key_address(program_id, &gate_account, viewer);
+    let (purchase_account, _) = find_purchase_address(wrapper_program_id, wrapper_account, viewer);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*viewer, true),
+            AccountMeta::new_readonly(gate_account, false),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(viewer_key_account, false),
+            AccountMeta::new_readonly(*wrapper_program_id, false),
+            AccountMeta::new_readonly(purchase_account, false),
+        ],
+        data: GateInstruction::ReleaseKey.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `RemoveKey` instruction
+pub fn remove_key(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wrapper_account: &Pubkey,
+    viewer: &Pubkey,
+) -> Instruction {
+    let (gate_account, _) = find_gate_address(program_id, wrapper_account);
+    let (viewer_key_account, _) = find_viewer_key_address(program_id, &gate_account, viewer);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(gate_account, false),
+            AccountMeta::new(viewer_key_account, false),
+        ],
+        data: GateInstruction::RemoveKey.try_to_vec().unwrap(),
+    }
+}
@@ -0,0 +1,40 @@
+//! Token-gated decryptor
+//!
+//! Companion program to the privacy wrapper that enforces key delivery
+//! on-chain. A wrapper owner opens a gate with a minimum access level and
+//! stores each viewer's data key, wrapped to that viewer, in a PDA. The gate
+//! releases a key only after checking through CPI that the viewer still
+//! holds the gate's level on the wrapper.
+//!
+//! Account data is public, so a viewer can read their wrapped key without
+//! `ReleaseKey`, even after losing access. Revoking a viewer therefore means
+//! removing their key and rotating the data key for everyone else, as the
+//! client's `revoke_access` does.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+// Export modules
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+// Program ID
+solana_program::declare_id!("AccessGateProgram111111111111111111111111111");
+
+// Program entrypoint, left out when linked into another program
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Process instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    processor::process_instruction(program_id, accounts, instruction_data)
+}
@@ -0,0 +1,305 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use privacy_wrapper::state::PrivacyWrapperView;
+use privacy_wrapper_cpi::{check_access, CheckAccess};
+
+use crate::{
+    error::AccessGateError,
+    instruction::GateInstruction,
+    state::{
+        find_gate_address, find_viewer_key_address, AccessGate, ViewerKey, GATE_SEED, MAX_WRAPPED_KEY_LEN,
+        VIEWER_KEY_SEED,
+    },
+};
+
+/// Program logic entry point
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = GateInstruction::try_from_slice(instruction_data)
+        .map_err(|_| AccessGateError::InvalidInstruction)?;
+    
+    match instruction {
+        GateInstruction::CreateGate { min_level } => {
+            create_gate(program_id, accounts, min_level)
+        }
+        GateInstruction::StoreKey { viewer, wrapped_key } => {
+            store_key(program_id, accounts, viewer, wrapped_key)
+        }
+        GateInstruction::ReleaseKey => {
+            release_key(program_id, accounts)
+        }
+        GateInstruction::RemoveKey => {
+            remove_key(program_id, accounts)
+        }
+    }
+}
+
+/// Open a gate on a wrapper
+pub fn create_gate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let gate_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Only the wrapper owner opens a gate on it
+    if *wrapper_account.owner != privacy_wrapper::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(AccessGateError::NotWrapperOwner.into());
+    }
+    
+    // Verify the gate address matches the wrapper
+    let (expected_gate, bump) = find_gate_address(program_id, wrapper_account.key);
+    if *gate_account.key != expected_gate {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Create the gate account; fails if the wrapper already has one
+    let space = AccessGate::LEN;
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            gate_account.key,
+            rent_lamports,
+            space as u64,
+            program_id,
+        ),
+        &[
+            owner.clone(),
+            gate_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[GATE_SEED, wrapper_account.key.as_ref(), &[bump]]],
+    )?;
+    
+    let gate = AccessGate {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        authority: *owner.key,
+        min_level,
+    };
+    gate.serialize(&mut *gate_account.data.borrow_mut())?;
+    
+    msg!("Gate created");
+    gate_account.key.log();
+    
+    Ok(())
+}
+
+/// Store or replace the data key wrapped to a viewer
+pub fn store_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    viewer: Pubkey,
+    wrapped_key: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let gate_account = next_account_info(account_info_iter)?;
+    let viewer_key_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    check_authority(program_id, authority, gate_account)?;
+    
+    if wrapped_key.is_empty() || wrapped_key.len() > MAX_WRAPPED_KEY_LEN {
+        return Err(AccessGateError::InvalidWrappedKey.into());
+    }
+    
+    // Verify the viewer key address matches the gate and viewer
+    let (expected_key, bump) = find_viewer_key_address(program_id, gate_account.key, &viewer);
+    if *viewer_key_account.key != expected_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // The first key for a viewer creates the account; later ones replace it
+    if viewer_key_account.data_is_empty() {
+        let space = ViewerKey::LEN;
+        let rent_lamports = Rent::get()?.minimum_balance(space);
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                viewer_key_account.key,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                authority.clone(),
+                viewer_key_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[VIEWER_KEY_SEED, gate_account.key.as_ref(), viewer.as_ref(), &[bump]]],
+        )?;
+    }
+    
+    let viewer_key = ViewerKey {
+        is_initialized: true,
+        gate: *gate_account.key,
+        viewer,
+        wrapped_key,
+        released_at: None,
+    };
+    viewer_key.serialize(&mut *viewer_key_account.data.borrow_mut())?;
+    
+    msg!("Viewer key stored");
+    viewer.log();
+    
+    Ok(())
+}
+
+/// Release a viewer's wrapped key as return data
+pub fn release_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let viewer = next_account_info(account_info_iter)?;
+    let gate_account = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let viewer_key_account = next_account_info(account_info_iter)?;
+    let wrapper_program = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter).ok();
+    
+    if !viewer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    if gate_account.owner != program_id || viewer_key_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let gate = AccessGate::unpack(&gate_account.data.borrow())?;
+    if gate.wrapper != *wrapper_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let (expected_key, _) = find_viewer_key_address(program_id, gate_account.key, viewer.key);
+    if *viewer_key_account.key != expected_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // The wrapper program decides whether the viewer still has access,
+    // counting a reveal purchase when one is passed
+    let check = CheckAccess {
+        wrapper_program,
+        wrapper: wrapper_account,
+        viewer,
+        purchase: purchase_account,
+    };
+    check_access(&privacy_wrapper::id(), &check, gate.min_level)?;
+    
+    let mut viewer_key = ViewerKey::unpack(&viewer_key_account.data.borrow())?;
+    viewer_key.released_at = Some(Clock::get()?.unix_timestamp);
+    viewer_key.serialize(&mut *viewer_key_account.data.borrow_mut())?;
+    
+    set_return_data(&viewer_key.wrapped_key);
+    
+    msg!("Viewer key released");
+    viewer.key.log();
+    
+    Ok(())
+}
+
+/// Delete a viewer's key, returning its rent to the authority
+pub fn remove_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let gate_account = next_account_info(account_info_iter)?;
+    let viewer_key_account = next_account_info(account_info_iter)?;
+    
+    check_authority(program_id, authority, gate_account)?;
+    
+    // Verify account ownership
+    if viewer_key_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let viewer_key = ViewerKey::unpack(&viewer_key_account.data.borrow())?;
+    if viewer_key.gate != *gate_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    close_account(viewer_key_account, authority)?;
+    
+    msg!("Viewer key removed");
+    viewer_key.viewer.log();
+    
+    Ok(())
+}
+
+/// Close a program-owned account, sending all its lamports to a recipient
+///
+/// The account is emptied and handed back to the system program, so a key
+/// can be stored for the viewer again, even later in the same transaction.
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let lamports = recipient.lamports()
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::InvalidArgument)?;
+    
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? = lamports;
+    account.realloc(0, false)?;
+    account.assign(&system_program::id());
+    
+    Ok(())
+}
+
+/// Check that the gate authority signed for a gate owned by this program
+fn check_authority(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    gate_account: &AccountInfo,
+) -> ProgramResult {
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    if gate_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    if AccessGate::unpack(&gate_account.data.borrow())?.authority != *authority.key {
+        return Err(AccessGateError::NotGateAuthority.into());
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::AccessGateError;
+
+/// Seed prefix for gate PDAs
+pub const GATE_SEED: &[u8] = b"gate";
+
+/// Seed prefix for viewer key PDAs
+pub const VIEWER_KEY_SEED: &[u8] = b"viewer-key";
+
+/// Maximum length of a wrapped data key in bytes
+pub const MAX_WRAPPED_KEY_LEN: usize = 128;
+
+/// Minimum access level guarding a wrapper's keys, stored in a PDA derived from the wrapper
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AccessGate {
+    /// Whether the gate has been written
+    pub is_initialized: bool,
+    /// Wrapper the gate guards
+    pub wrapper: Pubkey,
+    /// Account storing and removing viewer keys
+    pub authority: Pubkey,
+    /// Access level a viewer needs for their key to be released
+    pub min_level: u8,
+}
+
+impl AccessGate {
+    /// Size of a gate account
+    pub const LEN: usize = 1 + 32 + 32 + 1;
+    
+    /// Deserialize a gate from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let gate = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(AccessGateError::InvalidAccountData))?;
+        
+        if !gate.is_initialized {
+            return Err(AccessGateError::AccountNotInitialized.into());
+        }
+        
+        Ok(gate)
+    }
+}
+
+/// Data key wrapped to one viewer, stored in a PDA derived from the gate and viewer
+///
+/// Account data is public, so the key is only stored wrapped to the viewer's
+/// own key, and the viewer can read it without `ReleaseKey`. What the gate
+/// enforces is the release record: `ReleaseKey` hands the key out only while
+/// the viewer holds the gate's level, and records when it last did. A key
+/// must be removed with `RemoveKey` and the data key rotated once the
+/// viewer's access is revoked.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ViewerKey {
+    /// Whether the key has been written
+    pub is_initialized: bool,
+    /// Gate the key belongs to
+    pub gate: Pubkey,
+    /// Viewer the key is wrapped to
+    pub viewer: Pubkey,
+    /// Data key wrapped to the viewer
+    pub wrapped_key: Vec<u8>,
+    /// When the key was last released, as a Unix timestamp
+    pub released_at: Option<i64>,
+}
+
+impl ViewerKey {
+    /// Size of a viewer key account
+    pub const LEN: usize = 1 + 32 + 32 + (4 + MAX_WRAPPED_KEY_LEN) + (1 + 8);
+    
+    /// Offset of the gate address, for filtering a gate's keys
+    pub const GATE_OFFSET: usize = 1;
+    
+    /// Deserialize a viewer key from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let key = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(AccessGateError::InvalidAccountData))?;
+        
+        if !key.is_initialized {
+            return Err(AccessGateError::AccountNotInitialized.into());
+        }
+        
+        Ok(key)
+    }
+}
+
+/// Derive the gate address for a wrapper
+pub fn find_gate_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GATE_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Derive the viewer key address for a gate and viewer
+pub fn find_viewer_key_address(program_id: &Pubkey, gate: &Pubkey, viewer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VIEWER_KEY_SEED, gate.as_ref(), viewer.as_ref()], program_id)
+}
@@ -0,0 +1,147 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+use crate::state::MAX_CONFIG_HASH_LEN;
+
+/// Seed prefix for collection wrapper PDAs
+pub const COLLECTION_SEED: &[u8] = b"collection";
+
+/// Seed prefix for collection membership PDAs
+pub const MEMBERSHIP_SEED: &[u8] = b"member";
+
+/// Maximum number of trusted agents on a collection
+pub const MAX_TRUSTED_AGENTS: usize = 16;
+
+/// Metaplex token metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Seed prefix for Metaplex metadata accounts
+const METADATA_SEED: &[u8] = b"metadata";
+
+/// Shared privacy policy for every NFT in a collection, stored in a PDA derived from the collection mint
+///
+/// Member wrappers inherit the default policy unless they set their override
+/// flag. A privacy lock on a member freezes only the member's own settings,
+/// so members that must not follow collection updates should override.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct CollectionWrapper {
+    /// Whether the collection has been written
+    pub is_initialized: bool,
+    /// Collection mint address
+    pub collection_mint: Pubkey,
+    /// Update authority of the collection metadata, which manages the collection
+    pub authority: Pubkey,
+    /// Privacy config hash members inherit
+    pub default_policy_hash: String,
+    /// Agents trusted across the collection
+    pub trusted_agents: Vec<Pubkey>,
+    /// Last update timestamp
+    pub last_updated: u64,
+}
+
+impl CollectionWrapper {
+    /// Size of a collection wrapper account
+    pub const LEN: usize = 1 + 32 + 32 + (4 + MAX_CONFIG_HASH_LEN) + (4 + 32 * MAX_TRUSTED_AGENTS) + 8;
+    
+    /// Deserialize a collection wrapper from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let collection = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !collection.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(collection)
+    }
+}
+
+/// Link from a wrapper to the collection it inherits from, stored in a PDA derived from the wrapper
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct CollectionMembership {
+    /// Whether the membership has been written
+    pub is_initialized: bool,
+    /// Member wrapper
+    pub wrapper: Pubkey,
+    /// Collection wrapper PDA the wrapper belongs to
+    pub collection: Pubkey,
+    /// Whether the wrapper's own policy replaces the collection default
+    pub override_policy: bool,
+}
+
+impl CollectionMembership {
+    /// Size of a collection membership account
+    pub const LEN: usize = 1 + 32 + 32 + 1;
+    
+    /// Deserialize a collection membership from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let membership = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !membership.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(membership)
+    }
+}
+
+/// Derive the collection wrapper address for a collection mint
+pub fn find_collection_address(program_id: &Pubkey, collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLECTION_SEED, collection_mint.as_ref()], program_id)
+}
+
+/// Derive the collection membership address for a wrapper
+pub fn find_membership_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MEMBERSHIP_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Derive the Metaplex metadata address for a mint
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[METADATA_SEED, TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Check that a signer is the update authority of a collection mint's metadata
+///
+/// Metadata accounts start with a one-byte key, the update authority, and
+/// the mint, so only that fixed prefix is read.
+pub fn validate_collection_authority(
+    collection_mint: &Pubkey,
+    metadata_account: &AccountInfo,
+    authority: &Pubkey,
+) -> ProgramResult {
+    if *metadata_account.owner != TOKEN_METADATA_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let (expected_metadata, _) = find_metadata_address(collection_mint);
+    if *metadata_account.key != expected_metadata {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let data = metadata_account.data.borrow();
+    let update_authority = data.get(1..33).ok_or(PrivacyWrapperError::InvalidAccountData)?;
+    if update_authority != authority.as_ref() {
+        return Err(PrivacyWrapperError::NotCollectionAuthority.into());
+    }
+    
+    Ok(())
+}
+
+/// Check that a default policy and trusted agents fit their slots
+pub fn validate_collection(default_policy_hash: &str, trusted_agents: &[Pubkey]) -> ProgramResult {
+    if default_policy_hash.len() > MAX_CONFIG_HASH_LEN || trusted_agents.len() > MAX_TRUSTED_AGENTS {
+        return Err(PrivacyWrapperError::ValueTooLong.into());
+    }
+    
+    Ok(())
+}
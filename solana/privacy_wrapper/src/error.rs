@@ -22,6 +22,145 @@ pub enum PrivacyWrapperError {
     /// Account not initialized
     #[error("Account not initialized")]
     AccountNotInitialized,
+    
+    /// Value exceeds its fixed-size slot
+    #[error("Value too long")]
+    ValueTooLong,
+    
+    /// No free access entry slots
+    #[error("Access list full")]
+    AccessListFull,
+    
+    /// Account uses an older layout and must be migrated
+    #[error("Outdated account version")]
+    OutdatedAccountVersion,
+    
+    /// Agent name is empty, too long, or has invalid characters
+    #[error("Invalid agent name")]
+    InvalidAgentName,
+    
+    /// Not the agent record authority
+    #[error("Not the agent authority")]
+    NotAgentAuthority,
+    
+    /// Reveal time has not passed yet
+    #[error("Reveal still locked")]
+    RevealLocked,
+    
+    /// Reveal key does not match the escrow commitment
+    #[error("Invalid reveal key")]
+    InvalidRevealKey,
+    
+    /// Reveal key has already been published
+    #[error("Already revealed")]
+    AlreadyRevealed,
+    
+    /// Guardians, threshold, or shares do not describe a usable recovery
+    #[error("Invalid recovery config")]
+    InvalidRecoveryConfig,
+    
+    /// Signer is not a guardian of the wrapper
+    #[error("Not a guardian")]
+    NotGuardian,
+    
+    /// A recovery is already pending
+    #[error("Recovery already pending")]
+    RecoveryPending,
+    
+    /// No recovery has been requested
+    #[error("No recovery pending")]
+    NoRecoveryPending,
+    
+    /// The owner can still veto the recovery
+    #[error("Challenge period active")]
+    ChallengePeriodActive,
+    
+    /// Fewer guardians than the threshold approved the recovery
+    #[error("Insufficient guardian approvals")]
+    InsufficientApprovals,
+    
+    /// The wrapper's privacy settings are locked
+    #[error("Privacy settings locked")]
+    PrivacySettingsLocked,
+    
+    /// Lock durations must be positive and can't shorten an existing lock
+    #[error("Invalid lock duration")]
+    InvalidLockDuration,
+    
+    /// Signer is not the update authority of the collection
+    #[error("Not collection authority")]
+    NotCollectionAuthority,
+    
+    /// Reveal listing terms are unusable or royalties exceed the payment
+    #[error("Invalid reveal terms")]
+    InvalidRevealTerms,
+    
+    /// The listing no longer offers the terms the buyer agreed to
+    #[error("Reveal terms changed")]
+    RevealTermsChanged,
+    
+    /// The buyer already has a payment in escrow for the wrapper
+    #[error("Purchase already escrowed")]
+    PurchaseEscrowed,
+    
+    /// The purchase payment has already been settled or refunded
+    #[error("Purchase not escrowed")]
+    PurchaseNotEscrowed,
+    
+    /// The owner can still settle the purchase
+    #[error("Settlement window active")]
+    SettlementWindowActive,
+    
+    /// Royalty recipient accounts don't match the purchase's shares
+    #[error("Invalid royalty recipient")]
+    InvalidRoyaltyRecipient,
+    
+    /// No room for another pending access request
+    ///
+    /// No longer returned since each request has its own PDA; kept so later
+    /// error codes don't shift.
+    #[error("Access request queue full")]
+    RequestQueueFull,
+    
+    /// The account has no pending access request
+    #[error("No access request pending")]
+    NoAccessRequest,
+    
+    /// The account's access level is below the required level
+    #[error("Insufficient access level")]
+    InsufficientAccess,
+    
+    /// An account the instruction writes to was passed read-only
+    #[error("Account not writable")]
+    AccountNotWritable,
+    
+    /// The access a previous payment bought has not ended yet
+    #[error("Purchased access still active")]
+    PurchaseStillActive,
+    
+    /// Settings changes are paused program-wide
+    #[error("Program frozen")]
+    ProgramFrozen,
+    
+    /// Settings changes are paused for the wrapper's collection
+    #[error("Collection frozen")]
+    CollectionFrozen,
+    
+    /// The signer is not the authority of the freeze config
+    #[error("Not freeze authority")]
+    NotFreezeAuthority,
+    
+    /// The buyer has not confirmed the key and the settlement or dispute window is still open
+    #[error("Reveal not confirmed")]
+    RevealNotConfirmed,
+    
+    /// The requester already has a request awaiting the owner
+    #[error("Access request already pending")]
+    AccessRequestPending,
+    
+    /// The pending request asks for a different level than the owner approved
+    #[error("Access request level mismatch")]
+    AccessRequestMismatch,
 }
 
 impl From<PrivacyWrapperError> for ProgramError {
@@ -0,0 +1,161 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::collection::{find_membership_address, CollectionMembership};
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for freeze config PDAs
+pub const FREEZE_SEED: &[u8] = b"freeze";
+
+/// Tag of the upgradeable loader's `ProgramData` account state
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// What a freeze config covers
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FreezeScope {
+    /// Every wrapper in the program, managed by the program's upgrade authority
+    Program,
+    /// Wrappers in a collection, by collection wrapper address, managed by the collection authority
+    Collection(Pubkey),
+}
+
+/// Emergency stop on settings changes, stored in a PDA derived from its scope
+///
+/// While frozen, owners can't update privacy settings, grant access, join
+/// collections, switch policies, or list reveals, and buyers can't pay for
+/// one, so a vulnerability can be contained before it is fixed. Revoking
+/// access and denying requests still work, since they only ever narrow access. The
+/// authority only needs to sign, so it can be a multisig or governance PDA.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct FreezeConfig {
+    /// Whether the config has been written
+    pub is_initialized: bool,
+    /// What the config covers
+    pub scope: FreezeScope,
+    /// Account allowed to freeze, unfreeze, and hand over the config
+    pub authority: Pubkey,
+    /// Whether settings changes are paused
+    pub frozen: bool,
+    /// When the config was last frozen or unfrozen, as a Unix timestamp
+    pub updated_at: i64,
+}
+
+impl FreezeConfig {
+    /// Size of a freeze config account
+    pub const LEN: usize = 1 + (1 + 32) + 32 + 1 + 8;
+    
+    /// Deserialize a freeze config from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let config = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !config.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Derive the freeze config address for a scope
+pub fn find_freeze_address(program_id: &Pubkey, scope: &FreezeScope) -> (Pubkey, u8) {
+    match scope {
+        FreezeScope::Program => Pubkey::find_program_address(&[FREEZE_SEED], program_id),
+        FreezeScope::Collection(collection) => {
+            Pubkey::find_program_address(&[FREEZE_SEED, collection.as_ref()], program_id)
+        }
+    }
+}
+
+/// Check that a signer is the upgrade authority of the program
+///
+/// `ProgramData` accounts start with a four-byte state tag, the deployment
+/// slot, and the optional upgrade authority, so only that fixed prefix is read.
+pub fn validate_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_account: &AccountInfo,
+    authority: &Pubkey,
+) -> ProgramResult {
+    if *program_data_account.owner != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    if *program_data_account.key != bpf_loader_upgradeable::get_program_data_address(program_id) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let data = program_data_account.data.borrow();
+    let prefix = data.get(..45).ok_or(PrivacyWrapperError::InvalidAccountData)?;
+    if prefix[..4] != PROGRAM_DATA_TAG.to_le_bytes() {
+        return Err(PrivacyWrapperError::InvalidAccountData.into());
+    }
+    
+    // An immutable program has no upgrade authority to manage it
+    if prefix[12] != 1 || &prefix[13..45] != authority.as_ref() {
+        return Err(PrivacyWrapperError::NotFreezeAuthority.into());
+    }
+    
+    Ok(())
+}
+
+/// Check that neither the program nor the wrapper's collection is frozen
+///
+/// Freeze configs that were never created mean nothing is frozen. The
+/// collection config is only read if the membership PDA shows the wrapper
+/// belongs to a collection.
+pub fn check_not_frozen(
+    program_id: &Pubkey,
+    wrapper: &Pubkey,
+    program_freeze: &AccountInfo,
+    membership_account: &AccountInfo,
+    collection_freeze: &AccountInfo,
+) -> ProgramResult {
+    if is_frozen(program_id, &FreezeScope::Program, program_freeze)? {
+        return Err(PrivacyWrapperError::ProgramFrozen.into());
+    }
+    
+    let (expected_membership, _) = find_membership_address(program_id, wrapper);
+    if *membership_account.key != expected_membership {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if membership_account.data_is_empty() || membership_account.owner != program_id {
+        return Ok(());
+    }
+    
+    let collection = CollectionMembership::unpack(&membership_account.data.borrow())?.collection;
+    check_collection_not_frozen(program_id, &collection, collection_freeze)
+}
+
+/// Check that a collection, by collection wrapper address, is not frozen
+pub fn check_collection_not_frozen(
+    program_id: &Pubkey,
+    collection: &Pubkey,
+    collection_freeze: &AccountInfo,
+) -> ProgramResult {
+    if is_frozen(program_id, &FreezeScope::Collection(*collection), collection_freeze)? {
+        return Err(PrivacyWrapperError::CollectionFrozen.into());
+    }
+    
+    Ok(())
+}
+
+/// Check whether the freeze config of a scope exists and is frozen
+fn is_frozen(program_id: &Pubkey, scope: &FreezeScope, freeze_account: &AccountInfo) -> Result<bool, ProgramError> {
+    let (expected_freeze, _) = find_freeze_address(program_id, scope);
+    if *freeze_account.key != expected_freeze {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if freeze_account.data_is_empty() || freeze_account.owner != program_id {
+        return Ok(false);
+    }
+    
+    Ok(FreezeConfig::unpack(&freeze_account.data.borrow())?.frozen)
+}
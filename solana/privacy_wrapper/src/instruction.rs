@@ -1,4 +1,21 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::collection::{find_collection_address, find_membership_address, find_metadata_address};
+use crate::freeze::{find_freeze_address, FreezeScope};
+use crate::lock::find_lock_address;
+use crate::marketplace::{find_listing_address, find_purchase_address, RevealTerms};
+use crate::recovery::find_recovery_address;
+use crate::registry::find_agent_address;
+use crate::request::find_request_address;
+use crate::reveal::{find_reveal_address, RevealTime};
 
 /// Instructions for the Privacy Wrapper program
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
@@ -11,6 +28,7 @@ pub enum WrapperInstruction {
     /// 2. `[writable]` The new wrapper account
     /// 3. `[]` System program
     /// 4. `[]` Rent sysvar
+    /// 5. `[]` The owner's token account holding the NFT
     CreateWrapper {
         /// Initial privacy config hash
         privacy_config_hash: String,
@@ -21,6 +39,10 @@ pub enum WrapperInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The NFT owner
     /// 1. `[writable]` The wrapper account
+    /// 2. `[]` The privacy lock PDA
+    /// 3. `[]` The program freeze config PDA
+    /// 4. `[]` The collection membership PDA
+    /// 5. `[]` The collection freeze config PDA, or the program one again outside a collection
     UpdatePrivacy {
         /// New privacy config hash
         new_privacy_config_hash: String,
@@ -31,9 +53,13 @@ pub enum WrapperInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The NFT owner
     /// 1. `[writable]` The wrapper account
+    /// 2. `[]` The privacy lock PDA
+    /// 3. `[]` The program freeze config PDA
+    /// 4. `[]` The collection membership PDA
+    /// 5. `[]` The collection freeze config PDA, or the program one again outside a collection
     GrantAccess {
         /// Account to grant access to
-        account: String,
+        grantee: Pubkey,
         /// Access level (0-255, where 255 is full access)
         level: u8,
     },
@@ -43,8 +69,1162 @@ pub enum WrapperInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The NFT owner
     /// 1. `[writable]` The wrapper account
+    /// 2. `[]` The privacy lock PDA
     RevokeAccess {
         /// Account to revoke access from
-        account: String,
+        grantee: Pubkey,
+    },
+    
+    /// Migrate a wrapper from an earlier layout to the current layout
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (pays to grow Borsh-encoded wrappers)
+    /// 1. `[writable]` The wrapper account
+    /// 2. `[]` System program
+    MigrateWrapper,
+    
+    /// Register an agent name in the agent registry
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The record authority (fee payer)
+    /// 1. `[signer]` The agent key being registered
+    /// 2. `[writable]` The agent record PDA
+    /// 3. `[]` System program
+    RegisterAgent {
+        /// Agent name
+        name: String,
+        /// URI of off-chain agent metadata
+        metadata_uri: String,
+    },
+    
+    /// Update a registered agent's key and metadata
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The record authority
+    /// 1. `[signer]` The new agent key
+    /// 2. `[writable]` The agent record PDA
+    UpdateAgent {
+        /// URI of off-chain agent metadata
+        metadata_uri: String,
+    },
+    
+    /// Escrow a sealed data key that is revealed after a slot or timestamp
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The reveal escrow PDA
+    /// 3. `[]` System program
+    ScheduleReveal {
+        /// Label naming the reveal
+        label: String,
+        /// When the reveal unlocks
+        reveal_time: RevealTime,
+        /// SHA-256 of the reveal key
+        key_commitment: [u8; 32],
+        /// Data key sealed with the reveal key
+        sealed_key: Vec<u8>,
+    },
+    
+    /// Publish the reveal key of an unlocked reveal
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Anyone holding the reveal key
+    /// 1. `[writable]` The reveal escrow PDA
+    ClaimReveal {
+        /// Reveal key matching the escrow commitment
+        reveal_key: [u8; 32],
+    },
+    
+    /// Set the guardians holding shares of the content key
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The recovery PDA
+    /// 3. `[]` System program
+    SetupRecovery {
+        /// Guardians holding key shares
+        guardians: Vec<Pubkey>,
+        /// Guardian approvals needed to recover
+        threshold: u8,
+        /// Seconds the owner has to veto a request
+        challenge_period: i64,
+        /// SHA-256 of the content key
+        key_commitment: [u8; 32],
+        /// Key shares sealed to each guardian, in guardian order
+        sealed_shares: Vec<Vec<u8>>,
+    },
+    
+    /// Request or approve moving the wrapper to a new owner
+    /// 
+    /// Each guardian backs one new owner. Asking for a different one than the
+    /// pending request's records a vote, and a threshold of votes replaces
+    /// the request and restarts its challenge period.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` A guardian
+    /// 1. `[writable]` The recovery PDA
+    RequestRecovery {
+        /// Account the wrapper moves to
+        new_owner: Pubkey,
+    },
+    
+    /// Cancel a pending recovery
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The NFT owner
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The recovery PDA
+    VetoRecovery,
+    
+    /// Move the wrapper to the new owner once the challenge period has passed
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Anyone
+    /// 1. `[writable]` The wrapper account
+    /// 2. `[writable]` The recovery PDA
+    CompleteRecovery,
+    
+    /// Freeze privacy settings and access grants, optionally for a duration
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The privacy lock PDA
+    /// 3. `[]` System program
+    LockPrivacy {
+        /// Seconds the lock lasts, or `None` to lock indefinitely
+        duration: Option<i64>,
+    },
+    
+    /// Create a collection wrapper holding the policy its members inherit
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The collection metadata update authority (fee payer)
+    /// 1. `[]` The collection mint
+    /// 2. `[]` The collection mint's Metaplex metadata account
+    /// 3. `[writable]` The collection wrapper PDA
+    /// 4. `[]` System program
+    CreateCollection {
+        /// Privacy config hash members inherit
+        default_policy_hash: String,
+        /// Agents trusted across the collection
+        trusted_agents: Vec<Pubkey>,
+    },
+    
+    /// Update a collection's default policy and trusted agents
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The collection authority
+    /// 1. `[writable]` The collection wrapper PDA
+    UpdateCollection {
+        /// Privacy config hash members inherit
+        default_policy_hash: String,
+        /// Agents trusted across the collection
+        trusted_agents: Vec<Pubkey>,
+    },
+    
+    /// Add a wrapper to a collection, approved by the collection authority
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[signer]` The collection authority
+    /// 2. `[]` The wrapper account
+    /// 3. `[]` The collection wrapper PDA
+    /// 4. `[writable]` The collection membership PDA
+    /// 5. `[]` The privacy lock PDA
+    /// 6. `[]` System program
+    /// 7. `[]` The program freeze config PDA
+    /// 8. `[]` The freeze config PDA of the collection the wrapper is in, or the program one again outside a collection
+    /// 9. `[]` The freeze config PDA of the collection being joined
+    JoinCollection {
+        /// Whether the wrapper's own policy replaces the collection default
+        override_policy: bool,
     },
+    
+    /// Choose between the wrapper's own policy and the collection default
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The NFT owner
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The collection membership PDA
+    /// 3. `[]` The privacy lock PDA
+    /// 4. `[]` The program freeze config PDA
+    /// 5. `[]` The collection freeze config PDA
+    SetPolicyOverride {
+        /// Whether the wrapper's own policy replaces the collection default
+        override_policy: bool,
+    },
+    
+    /// Offer viewers timed access to a wrapper for a price
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The reveal listing PDA
+    /// 3. `[]` System program
+    /// 4. `[]` The program freeze config PDA
+    /// 5. `[]` The collection membership PDA
+    /// 6. `[]` The collection freeze config PDA, or the program one again outside a collection
+    ListReveal {
+        /// Price, access, and royalty terms
+        terms: RevealTerms,
+    },
+    
+    /// Pay into escrow for timed access under a wrapper's listing
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[]` The reveal listing PDA
+    /// 3. `[writable]` The reveal purchase PDA
+    /// 4. `[]` System program
+    /// 5. `[]` The program freeze config PDA
+    /// 6. `[]` The collection membership PDA
+    /// 7. `[]` The collection freeze config PDA, or the program one again outside a collection
+    PayToReveal {
+        /// Terms the buyer agreed to, which the listing must still offer
+        terms: RevealTerms,
+    },
+    
+    /// Pay an escrowed purchase out to royalty recipients and the owner
+    /// 
+    /// Needs the buyer's `ConfirmReveal`, or the settlement and dispute
+    /// windows to have passed without a refund.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The reveal purchase PDA
+    /// 3.. `[writable]` Royalty recipients, in the purchase's share order
+    SettleReveal,
+    
+    /// Reclaim an unsettled payment once the settlement window has passed
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer
+    /// 1. `[writable]` The reveal purchase PDA
+    RefundReveal,
+    
+    /// Ask the owner for access to a wrapper
+    /// 
+    /// The request is stored in its own PDA, paid for by the requester and
+    /// refunded when the owner answers it. A requester has at most one
+    /// pending request per wrapper, which can't be changed once made.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The requester (fee payer)
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The access request PDA
+    /// 3. `[]` System program
+    RequestAccess {
+        /// Access level requested
+        level: u8,
+    },
+    
+    /// Grant a pending request at the level it asked for
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The NFT owner
+    /// 1. `[writable]` The wrapper account
+    /// 2. `[]` The privacy lock PDA
+    /// 3. `[]` The program freeze config PDA
+    /// 4. `[]` The collection membership PDA
+    /// 5. `[]` The collection freeze config PDA, or the program one again outside a collection
+    /// 6. `[writable]` The access request PDA
+    /// 7. `[writable]` The requester, refunded the request's rent
+    ApproveAccess {
+        /// Account whose request is approved
+        requester: Pubkey,
+        /// Level the owner reviewed, which the request must still ask for
+        level: u8,
+    },
+    
+    /// Drop a pending request without granting it
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The NFT owner
+    /// 1. `[]` The wrapper account
+    /// 2. `[writable]` The access request PDA
+    /// 3. `[writable]` The requester, refunded the request's rent
+    DenyAccess {
+        /// Account whose request is denied
+        requester: Pubkey,
+    },
+    
+    /// Fail unless an account holds at least a required access level
+    /// 
+    /// Meant to be invoked by other programs. On success the account's level
+    /// is set as return data.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` The wrapper account
+    /// 1. `[]` The account whose access is checked
+    /// 2. `[]` Optional: the viewer's purchase PDA, to count timed access
+    ///    bought through `PayToReveal`
+    CheckAccess {
+        /// Lowest access level that passes
+        required_level: u8,
+    },
+    
+    /// Create a freeze config for the program or a collection
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The fee payer
+    /// 1. `[signer]` The program upgrade authority, or the collection authority
+    /// 2. `[]` The program's `ProgramData` account, or the collection wrapper PDA
+    /// 3. `[writable]` The freeze config PDA
+    /// 4. `[]` System program
+    InitializeFreeze {
+        /// What the config covers
+        scope: FreezeScope,
+        /// Account allowed to freeze and unfreeze
+        authority: Pubkey,
+    },
+    
+    /// Pause or resume settings changes under a freeze config
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The freeze authority
+    /// 1. `[writable]` The freeze config PDA
+    SetFreeze {
+        /// Whether settings changes are paused
+        frozen: bool,
+    },
+    
+    /// Hand a freeze config to a new authority
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The freeze authority
+    /// 1. `[writable]` The freeze config PDA
+    SetFreezeAuthority {
+        /// Account allowed to freeze and unfreeze from now on
+        new_authority: Pubkey,
+    },
+    
+    /// Resize a wrapper to hold a number of access entries
+    /// 
+    /// Growing charges the owner rent and shrinking refunds it; every grant
+    /// must still fit.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner (fee payer)
+    /// 1. `[writable]` The wrapper account
+    /// 2. `[]` System program
+    ResizeWrapper {
+        /// Number of access entries the wrapper can hold
+        capacity: u16,
+    },
+    
+    /// Confirm receiving the key for an escrowed purchase
+    /// 
+    /// Lets the owner settle before the settlement window passes; the buyer
+    /// can no longer reclaim the payment.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` The buyer
+    /// 1. `[writable]` The reveal purchase PDA
+    ConfirmReveal,
+}
+
+/// Create a `CreateWrapper` instruction
+///
+/// The NFT is expected in the owner's associated token account; pass
+/// another token account by replacing the last account meta.
+pub fn create_wrapper(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    nft_mint: &Pubkey,
+    wrapper_account: &Pubkey,
+    privacy_config_hash: &str,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*nft_mint, false),
+            AccountMeta::new(*wrapper_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(get_associated_token_address(owner, nft_mint), false),
+        ],
+        data: WrapperInstruction::CreateWrapper {
+            privacy_config_hash: privacy_config_hash.to_string(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create an `UpdatePrivacy` instruction
+pub fn update_privacy(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    new_privacy_config_hash: &str,
+) -> Instruction {
+    guarded_instruction(
+        program_id,
+        owner,
+        wrapper_account,
+        WrapperInstruction::UpdatePrivacy {
+            new_privacy_config_hash: new_privacy_config_hash.to_string(),
+        },
+    )
+}
+
+/// Create a `GrantAccess` instruction
+pub fn grant_access(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    grantee: &Pubkey,
+    level: u8,
+) -> Instruction {
+    guarded_instruction(
+        program_id,
+        owner,
+        wrapper_account,
+        WrapperInstruction::GrantAccess {
+            grantee: *grantee,
+            level,
+        },
+    )
+}
+
+/// Create a `RevokeAccess` instruction
+pub fn revoke_access(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    grantee: &Pubkey,
+) -> Instruction {
+    settings_instruction(
+        program_id,
+        owner,
+        wrapper_account,
+        WrapperInstruction::RevokeAccess {
+            grantee: *grantee,
+        },
+    )
+}
+
+/// Create a `MigrateWrapper` instruction
+pub fn migrate_wrapper(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let mut instruction = owner_instruction(program_id, owner, wrapper_account, WrapperInstruction::MigrateWrapper);
+    instruction.accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    instruction
+}
+
+/// Create a `RegisterAgent` instruction
+pub fn register_agent(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    agent: &Pubkey,
+    name: &str,
+    metadata_uri: &str,
+) -> Instruction {
+    let (agent_record, _) = find_agent_address(program_id, name);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*agent, true),
+            AccountMeta::new(agent_record, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::RegisterAgent {
+            name: name.to_string(),
+            metadata_uri: metadata_uri.to_string(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create an `UpdateAgent` instruction
+pub fn update_agent(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_agent: &Pubkey,
+    name: &str,
+    metadata_uri: &str,
+) -> Instruction {
+    let (agent_record, _) = find_agent_address(program_id, name);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_agent, true),
+            AccountMeta::new(agent_record, false),
+        ],
+        data: WrapperInstruction::UpdateAgent {
+            metadata_uri: metadata_uri.to_string(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `ScheduleReveal` instruction
+pub fn schedule_reveal(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    label: &str,
+    reveal_time: RevealTime,
+    key_commitment: [u8; 32],
+    sealed_key: Vec<u8>,
+) -> Instruction {
+    let (reveal_account, _) = find_reveal_address(program_id, wrapper_account, label);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(reveal_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::ScheduleReveal {
+            label: label.to_string(),
+            reveal_time,
+            key_commitment,
+            sealed_key,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `ClaimReveal` instruction
+pub fn claim_reveal(
+    program_id: &Pubkey,
+    claimer: &Pubkey,
+    reveal_account: &Pubkey,
+    reveal_key: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*claimer, true),
+            AccountMeta::new(*reveal_account, false),
+        ],
+        data: WrapperInstruction::ClaimReveal { reveal_key }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `SetupRecovery` instruction
+pub fn setup_recovery(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    guardian_shares: Vec<(Pubkey, Vec<u8>)>,
+    threshold: u8,
+    challenge_period: i64,
+    key_commitment: [u8; 32],
+) -> Instruction {
+    let (recovery_account, _) = find_recovery_address(program_id, wrapper_account);
+    let (guardians, sealed_shares) = guardian_shares.into_iter().unzip();
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(recovery_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::SetupRecovery {
+            guardians,
+            threshold,
+            challenge_period,
+            key_commitment,
+            sealed_shares,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `RequestRecovery` instruction
+pub fn request_recovery(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    wrapper_account: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    let (recovery_account, _) = find_recovery_address(program_id, wrapper_account);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*guardian, true),
+            AccountMeta::new(recovery_account, false),
+        ],
+        data: WrapperInstruction::RequestRecovery { new_owner: *new_owner }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `VetoRecovery` instruction
+pub fn veto_recovery(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let (recovery_account, _) = find_recovery_address(program_id, wrapper_account);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(recovery_account, false),
+        ],
+        data: WrapperInstruction::VetoRecovery.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `CompleteRecovery` instruction
+pub fn complete_recovery(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let (recovery_account, _) = find_recovery_address(program_id, wrapper_account);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(*wrapper_account, false),
+            AccountMeta::new(recovery_account, false),
+        ],
+        data: WrapperInstruction::CompleteRecovery.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `LockPrivacy` instruction
+pub fn lock_privacy(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    duration: Option<i64>,
+) -> Instruction {
+    let (lock_account, _) = find_lock_address(program_id, wrapper_account);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(lock_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::LockPrivacy { duration }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `CreateCollection` instruction
+pub fn create_collection(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    collection_mint: &Pubkey,
+    default_policy_hash: &str,
+    trusted_agents: Vec<Pubkey>,
+) -> Instruction {
+    let (metadata_account, _) = find_metadata_address(collection_mint);
+    let (collection_account, _) = find_collection_address(program_id, collection_mint);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*collection_mint, false),
+            AccountMeta::new_readonly(metadata_account, false),
+            AccountMeta::new(collection_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::CreateCollection {
+            default_policy_hash: default_policy_hash.to_string(),
+            trusted_agents,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create an `UpdateCollection` instruction
+pub fn update_collection(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    collection_mint: &Pubkey,
+    default_policy_hash: &str,
+    trusted_agents: Vec<Pubkey>,
+) -> Instruction {
+    let (collection_account, _) = find_collection_address(program_id, collection_mint);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(collection_account, false),
+        ],
+        data: WrapperInstruction::UpdateCollection {
+            default_policy_hash: default_policy_hash.to_string(),
+            trusted_agents,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `JoinCollection` instruction
+pub fn join_collection(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    authority: &Pubkey,
+    wrapper_account: &Pubkey,
+    collection_mint: &Pubkey,
+    override_policy: bool,
+) -> Instruction {
+    let (collection_account, _) = find_collection_address(program_id, collection_mint);
+    let (membership_account, _) = find_membership_address(program_id, wrapper_account);
+    let (lock_account, _) = find_lock_address(program_id, wrapper_account);
+    let (program_freeze, _) = find_freeze_address(program_id, &FreezeScope::Program);
+    let (joined_freeze, _) = find_freeze_address(program_id, &FreezeScope::Collection(collection_account));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new_readonly(collection_account, false),
+            AccountMeta::new(membership_account, false),
+            AccountMeta::new_readonly(lock_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_freeze, false),
+            AccountMeta::new_readonly(program_freeze, false),
+            AccountMeta::new_readonly(joined_freeze, false),
+        ],
+        data: WrapperInstruction::JoinCollection { override_policy }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `SetPolicyOverride` instruction
+pub fn set_policy_override(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    collection: &Pubkey,
+    override_policy: bool,
+) -> Instruction {
+    let (membership_account, _) = find_membership_address(program_id, wrapper_account);
+    let (lock_account, _) = find_lock_address(program_id, wrapper_account);
+    let (program_freeze, _) = find_freeze_address(program_id, &FreezeScope::Program);
+    let (collection_freeze, _) = find_freeze_address(program_id, &FreezeScope::Collection(*collection));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(membership_account, false),
+            AccountMeta::new_readonly(lock_account, false),
+            AccountMeta::new_readonly(program_freeze, false),
+            AccountMeta::new_readonly(collection_freeze, false),
+        ],
+        data: WrapperInstruction::SetPolicyOverride { override_policy }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `ListReveal` instruction
+pub fn list_reveal(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    terms: RevealTerms,
+) -> Instruction {
+    let (listing_account, _) = find_listing_address(program_id, wrapper_account);
+    
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*wrapper_account, false),
+        AccountMeta::new(listing_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(freeze_accounts(program_id, wrapper_account));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: WrapperInstruction::ListReveal { terms }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `PayToReveal` instruction
+pub fn pay_to_reveal(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    wrapper_account: &Pubkey,
+    terms: RevealTerms,
+) -> Instruction {
+    let (listing_account, _) = find_listing_address(program_id, wrapper_account);
+    let (purchase_account, _) = find_purchase_address(program_id, wrapper_account, buyer);
+    
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(*wrapper_account, false),
+        AccountMeta::new_readonly(listing_account, false),
+        AccountMeta::new(purchase_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(freeze_accounts(program_id, wrapper_account));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: WrapperInstruction::PayToReveal { terms }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `SettleReveal` instruction
+pub fn settle_reveal(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    buyer: &Pubkey,
+    royalty_recipients: &[Pubkey],
+) -> Instruction {
+    let (purchase_account, _) = find_purchase_address(program_id, wrapper_account, buyer);
+    
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*wrapper_account, false),
+        AccountMeta::new(purchase_account, false),
+    ];
+    accounts.extend(royalty_recipients.iter().map(|recipient| AccountMeta::new(*recipient, false)));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: WrapperInstruction::SettleReveal.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `RefundReveal` instruction
+pub fn refund_reveal(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let (purchase_account, _) = find_purchase_address(program_id, wrapper_account, buyer);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new(purchase_account, false),
+        ],
+        data: WrapperInstruction::RefundReveal.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `ConfirmReveal` instruction
+pub fn confirm_reveal(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    wrapper_account: &Pubkey,
+) -> Instruction {
+    let (purchase_account, _) = find_purchase_address(program_id, wrapper_account, buyer);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*buyer, true),
+            AccountMeta::new(purchase_account, false),
+        ],
+        data: WrapperInstruction::ConfirmReveal.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `RequestAccess` instruction
+pub fn request_access(
+    program_id: &Pubkey,
+    requester: &Pubkey,
+    wrapper_account: &Pubkey,
+    level: u8,
+) -> Instruction {
+    let (request_account, _) = find_request_address(program_id, wrapper_account, requester);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*requester, true),
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new(request_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::RequestAccess { level }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create an `ApproveAccess` instruction
+pub fn approve_access(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    requester: &Pubkey,
+    level: u8,
+) -> Instruction {
+    let mut instruction = guarded_instruction(
+        program_id,
+        owner,
+        wrapper_account,
+        WrapperInstruction::ApproveAccess {
+            requester: *requester,
+            level,
+        },
+    );
+    instruction.accounts.extend(request_accounts(program_id, wrapper_account, requester));
+    instruction
+}
+
+/// Create a `DenyAccess` instruction
+pub fn deny_access(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    requester: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*wrapper_account, false),
+    ];
+    accounts.extend(request_accounts(program_id, wrapper_account, requester));
+    
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: WrapperInstruction::DenyAccess { requester: *requester }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `CheckAccess` instruction
+///
+/// Only counts grants on the wrapper; use `check_purchased_access` to also
+/// count a reveal purchase.
+pub fn check_access(
+    program_id: &Pubkey,
+    wrapper_account: &Pubkey,
+    viewer: &Pubkey,
+    required_level: u8,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*wrapper_account, false),
+            AccountMeta::new_readonly(*viewer, false),
+        ],
+        data: WrapperInstruction::CheckAccess { required_level }.try_to_vec().unwrap(),
+    }
+}
+
+/// Create a `CheckAccess` instruction that also counts the viewer's reveal purchase
+pub fn check_purchased_access(
+    program_id: &Pubkey,
+    wrapper_account: &Pubkey,
+    viewer: &Pubkey,
+    required_level: u8,
+) -> Instruction {
+    let (purchase_account, _) = find_purchase_address(program_id, wrapper_account, viewer);
+    let mut instruction = check_access(program_id, wrapper_account, viewer, required_level);
+    instruction.accounts.push(AccountMeta::new_readonly(purchase_account, false));
+    instruction
+}
+
+/// Create an `InitializeFreeze` instruction
+///
+/// `scope_authority` is the program's upgrade authority for a program-wide
+/// config, or the collection authority for a collection config.
+pub fn initialize_freeze(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    scope_authority: &Pubkey,
+    scope: FreezeScope,
+    authority: &Pubkey,
+) -> Instruction {
+    let scope_account = match scope {
+        FreezeScope::Program => bpf_loader_upgradeable::get_program_data_address(program_id),
+        FreezeScope::Collection(collection) => collection,
+    };
+    let (freeze_account, _) = find_freeze_address(program_id, &scope);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*scope_authority, true),
+            AccountMeta::new_readonly(scope_account, false),
+            AccountMeta::new(freeze_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: WrapperInstruction::InitializeFreeze {
+            scope,
+            authority: *authority,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Create a `SetFreeze` instruction
+pub fn set_freeze(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    scope: &FreezeScope,
+    frozen: bool,
+) -> Instruction {
+    freeze_authority_instruction(program_id, authority, scope, WrapperInstruction::SetFreeze { frozen })
+}
+
+/// Create a `SetFreezeAuthority` instruction
+pub fn set_freeze_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    scope: &FreezeScope,
+    new_authority: &Pubkey,
+) -> Instruction {
+    freeze_authority_instruction(
+        program_id,
+        authority,
+        scope,
+        WrapperInstruction::SetFreezeAuthority {
+            new_authority: *new_authority,
+        },
+    )
+}
+
+/// Create a `ResizeWrapper` instruction
+pub fn resize_wrapper(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    capacity: u16,
+) -> Instruction {
+    let mut instruction = owner_instruction(program_id, owner, wrapper_account, WrapperInstruction::ResizeWrapper { capacity });
+    instruction.accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    instruction
+}
+
+/// Point an instruction checking freeze configs at the freeze config of the wrapper's collection
+///
+/// `UpdatePrivacy`, `GrantAccess`, `ApproveAccess`, `JoinCollection`,
+/// `ListReveal` and `PayToReveal` builders assume the wrapper is in no
+/// collection and pass the program freeze config in the collection's slot;
+/// a member wrapper's instruction fails until that slot is filled in.
+pub fn with_collection_freeze(mut instruction: Instruction, program_id: &Pubkey, collection: &Pubkey) -> Instruction {
+    let (program_freeze, _) = find_freeze_address(program_id, &FreezeScope::Program);
+    let (collection_freeze, _) = find_freeze_address(program_id, &FreezeScope::Collection(*collection));
+    
+    // The collection's slot is the last one holding the program freeze config
+    if let Some(account) = instruction.accounts.iter_mut().rev().find(|account| account.pubkey == program_freeze) {
+        *account = AccountMeta::new_readonly(collection_freeze, false);
+    }
+    instruction
+}
+
+/// Build an instruction signed by a freeze authority against its freeze config
+fn freeze_authority_instruction(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    scope: &FreezeScope,
+    instruction: WrapperInstruction,
+) -> Instruction {
+    let (freeze_account, _) = find_freeze_address(program_id, scope);
+    
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(freeze_account, false),
+        ],
+        data: instruction.try_to_vec().unwrap(),
+    }
+}
+
+/// Build a settings instruction that is also paused by the program and collection freezes
+///
+/// Outside a collection the program freeze config stands in for the
+/// collection one, which the program only reads for members.
+fn guarded_instruction(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    instruction: WrapperInstruction,
+) -> Instruction {
+    let mut instruction = settings_instruction(program_id, owner, wrapper_account, instruction);
+    instruction.accounts.extend(freeze_accounts(program_id, wrapper_account));
+    instruction
+}
+
+/// Freeze accounts checked by instructions that widen access, assuming the wrapper is in no collection
+fn freeze_accounts(program_id: &Pubkey, wrapper_account: &Pubkey) -> [AccountMeta; 3] {
+    let (program_freeze, _) = find_freeze_address(program_id, &FreezeScope::Program);
+    let (membership_account, _) = find_membership_address(program_id, wrapper_account);
+    
+    [
+        AccountMeta::new_readonly(program_freeze, false),
+        AccountMeta::new_readonly(membership_account, false),
+        AccountMeta::new_readonly(program_freeze, false),
+    ]
+}
+
+/// Request PDA and rent refund recipient of an instruction answering an access request
+fn request_accounts(program_id: &Pubkey, wrapper_account: &Pubkey, requester: &Pubkey) -> [AccountMeta; 2] {
+    let (request_account, _) = find_request_address(program_id, wrapper_account, requester);
+    
+    [
+        AccountMeta::new(request_account, false),
+        AccountMeta::new(*requester, false),
+    ]
+}
+
+/// Build an owner instruction that changes settings guarded by the privacy lock
+fn settings_instruction(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    instruction: WrapperInstruction,
+) -> Instruction {
+    let (lock_account, _) = find_lock_address(program_id, wrapper_account);
+    
+    let mut instruction = owner_instruction(program_id, owner, wrapper_account, instruction);
+    instruction.accounts.push(AccountMeta::new_readonly(lock_account, false));
+    instruction
+}
+
+/// Build an instruction signed by the owner against the wrapper account
+fn owner_instruction(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    wrapper_account: &Pubkey,
+    instruction: WrapperInstruction,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*wrapper_account, false),
+        ],
+        data: instruction.try_to_vec().unwrap(),
+    }
 }
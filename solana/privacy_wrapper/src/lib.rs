@@ -1,21 +1,38 @@
 use solana_program::{
     account_info::AccountInfo,
-    entrypoint,
     entrypoint::ProgramResult,
     pubkey::Pubkey,
 };
 
 // Export modules
+pub mod collection;
 pub mod error;
+pub mod freeze;
 pub mod instruction;
+pub mod lock;
+pub mod marketplace;
 pub mod processor;
+pub mod recovery;
+pub mod registry;
+pub mod request;
+pub mod reveal;
 pub mod state;
+pub mod validation;
 
 // Program ID
 solana_program::declare_id!("GlchWrapperProgram111111111111111111111111111");
 
-// Program entrypoint
-entrypoint!(process_instruction);
+/// Anchor-compatible IDL of the instructions, accounts and errors
+///
+/// Instructions carry a one-byte Borsh discriminant rather than Anchor's
+/// eight-byte sighash, recorded as `discriminant` in the Shank style, and
+/// accounts have no discriminator. TypeScript clients can be generated from
+/// `idl/privacy_wrapper.json` with Solita or Kinobi.
+pub const IDL: &str = include_str!("../idl/privacy_wrapper.json");
+
+// Program entrypoint, left out when linked into another program
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 /// Process instruction
 pub fn process_instruction(
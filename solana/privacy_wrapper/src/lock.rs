@@ -0,0 +1,85 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for privacy lock PDAs
+pub const LOCK_SEED: &[u8] = b"lock";
+
+/// Freeze on a wrapper's privacy settings, stored in a PDA derived from the wrapper
+///
+/// While the lock is active the owner cannot update the privacy config or
+/// change who has access, so holders and viewers can rely on the settings
+/// they were shown.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PrivacyLock {
+    /// Whether the lock has been written
+    pub is_initialized: bool,
+    /// Wrapper the lock belongs to
+    pub wrapper: Pubkey,
+    /// When the lock was first set, as a Unix timestamp
+    pub locked_at: i64,
+    /// When the lock expires, or `None` if it never does
+    pub locked_until: Option<i64>,
+}
+
+impl PrivacyLock {
+    /// Size of a privacy lock account
+    pub const LEN: usize = 1 + 32 + 8 + (1 + 8);
+    
+    /// Deserialize a privacy lock from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let lock = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !lock.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(lock)
+    }
+    
+    /// Check if the lock is in force at a Unix timestamp
+    pub fn is_active(&self, now: i64) -> bool {
+        match self.locked_until {
+            Some(until) => now < until,
+            None => true,
+        }
+    }
+}
+
+/// Derive the privacy lock address for a wrapper
+pub fn find_lock_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOCK_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Check that a wrapper's privacy settings are not locked
+///
+/// The lock account must be the wrapper's lock PDA; a PDA that was never
+/// created means the wrapper has never been locked.
+pub fn check_unlocked(
+    program_id: &Pubkey,
+    wrapper: &Pubkey,
+    lock_account: &AccountInfo,
+    now: i64,
+) -> ProgramResult {
+    let (expected_lock, _) = find_lock_address(program_id, wrapper);
+    if *lock_account.key != expected_lock {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if lock_account.data_is_empty() || lock_account.owner != program_id {
+        return Ok(());
+    }
+    
+    if PrivacyLock::unpack(&lock_account.data.borrow())?.is_active(now) {
+        return Err(PrivacyWrapperError::PrivacySettingsLocked.into());
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for reveal listing PDAs
+pub const LISTING_SEED: &[u8] = b"listing";
+
+/// Seed prefix for reveal purchase PDAs
+pub const PURCHASE_SEED: &[u8] = b"purchase";
+
+/// Maximum number of royalty recipients on a listing
+pub const MAX_ROYALTY_SHARES: usize = 5;
+
+/// Basis points in a whole payment
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Tag logged ahead of a `RevealPurchased` event
+pub const REVEAL_PURCHASED_EVENT: &[u8] = b"reveal_purchased";
+
+/// Cut of each payment owed to a royalty recipient
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoyaltyShare {
+    /// Account receiving the cut
+    pub recipient: Pubkey,
+    /// Cut in basis points of the payment
+    pub basis_points: u16,
+}
+
+/// Price and access a wrapper owner offers viewers
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RevealTerms {
+    /// Lamports a viewer pays
+    pub price: u64,
+    /// Access level a payment buys
+    pub access_level: u8,
+    /// Seconds the access lasts
+    pub access_duration: i64,
+    /// Seconds the owner has to deliver the key before the buyer can reclaim the payment
+    pub settlement_window: i64,
+    /// Seconds after the settlement window in which only the buyer can act on
+    /// an unconfirmed payment, before the owner can settle it
+    pub dispute_window: i64,
+    /// Royalty cuts, paid out in order; the owner receives the rest
+    pub royalty_shares: Vec<RoyaltyShare>,
+}
+
+impl RevealTerms {
+    /// Check the terms are usable and the royalty cuts fit in one payment
+    pub fn validate(&self) -> ProgramResult {
+        let total_basis_points: u32 = self.royalty_shares.iter().map(|share| u32::from(share.basis_points)).sum();
+        
+        if self.price == 0
+            || self.access_level == 0
+            || self.access_duration <= 0
+            || self.settlement_window <= 0
+            || self.dispute_window <= 0
+            || self.royalty_shares.len() > MAX_ROYALTY_SHARES
+            || total_basis_points > u32::from(BASIS_POINTS)
+        {
+            return Err(PrivacyWrapperError::InvalidRevealTerms.into());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Reveal offer for a wrapper, stored in a PDA derived from the wrapper
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RevealListing {
+    /// Whether the listing has been written
+    pub is_initialized: bool,
+    /// Wrapper being offered
+    pub wrapper: Pubkey,
+    /// Terms viewers pay under
+    pub terms: RevealTerms,
+}
+
+impl RevealListing {
+    /// Size of a reveal listing account
+    pub const LEN: usize = 1 + 32 + 8 + 1 + 8 + 8 + 8 + (4 + (32 + 2) * MAX_ROYALTY_SHARES);
+    
+    /// Deserialize a reveal listing from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let listing = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !listing.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(listing)
+    }
+}
+
+/// Where a purchase's payment is
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurchaseStatus {
+    /// Held in the purchase account
+    Escrowed,
+    /// Paid out to the owner and royalty recipients
+    Settled,
+    /// Returned to the buyer, ending the access
+    Refunded,
+    /// Held in the purchase account after the buyer confirmed receiving the key
+    Confirmed,
+}
+
+/// Timed access a viewer paid for, stored in a PDA derived from the wrapper and buyer
+///
+/// The payment stays in this account until the owner settles it or the
+/// buyer reclaims it. The owner can settle once the buyer confirms receiving
+/// the key. Otherwise an unconfirmed buyer can reclaim the payment once the
+/// settlement window has passed, and has the dispute window after it to
+/// themselves; only then can the owner settle without a confirmation.
+/// Royalty cuts are fixed at purchase, so relisting can't change what
+/// recipients are owed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RevealPurchase {
+    /// Whether the purchase has been written
+    pub is_initialized: bool,
+    /// Wrapper paid for
+    pub wrapper: Pubkey,
+    /// Viewer who paid
+    pub buyer: Pubkey,
+    /// Lamports paid
+    pub amount: u64,
+    /// Access level bought
+    pub access_level: u8,
+    /// When the payment was made, as a Unix timestamp
+    pub paid_at: i64,
+    /// When the access ends, as a Unix timestamp
+    pub expires_at: i64,
+    /// When the buyer can reclaim an unsettled payment, as a Unix timestamp
+    pub refundable_at: i64,
+    /// When the owner can settle an unconfirmed payment, as a Unix timestamp
+    pub settleable_at: i64,
+    /// Royalty cuts owed from the payment
+    pub royalty_shares: Vec<RoyaltyShare>,
+    /// Where the payment is
+    pub status: PurchaseStatus,
+}
+
+impl RevealPurchase {
+    /// Size of a reveal purchase account
+    pub const LEN: usize = 1 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + (4 + (32 + 2) * MAX_ROYALTY_SHARES) + 1;
+    
+    /// Deserialize a reveal purchase from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let purchase = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !purchase.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(purchase)
+    }
+    
+    /// Split the payment into royalty cuts, in share order, and the owner's remainder
+    pub fn payouts(&self) -> (Vec<u64>, u64) {
+        let cuts: Vec<u64> = self.royalty_shares.iter()
+            .map(|share| (u128::from(self.amount) * u128::from(share.basis_points) / u128::from(BASIS_POINTS)) as u64)
+            .collect();
+        let remainder = self.amount - cuts.iter().sum::<u64>();
+        
+        (cuts, remainder)
+    }
+    
+    /// Whether the payment is still held in the purchase account
+    pub fn is_held(&self) -> bool {
+        matches!(self.status, PurchaseStatus::Escrowed | PurchaseStatus::Confirmed)
+    }
+    
+    /// Get the access level the purchase grants at a Unix timestamp
+    pub fn level_at(&self, now: i64) -> u8 {
+        if self.status == PurchaseStatus::Refunded || now >= self.expires_at {
+            0
+        } else {
+            self.access_level
+        }
+    }
+}
+
+/// Event logged when a viewer pays to reveal, consumed by the key-delivery service
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RevealPurchased {
+    /// Wrapper paid for
+    pub wrapper: Pubkey,
+    /// Viewer to deliver the key to
+    pub buyer: Pubkey,
+    /// Purchase account holding the payment
+    pub purchase: Pubkey,
+    /// Access level bought
+    pub access_level: u8,
+    /// When the access ends, as a Unix timestamp
+    pub expires_at: i64,
+    /// Lamports paid
+    pub amount: u64,
+}
+
+/// Derive the reveal listing address for a wrapper
+pub fn find_listing_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LISTING_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Derive the reveal purchase address for a wrapper and buyer
+pub fn find_purchase_address(program_id: &Pubkey, wrapper: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PURCHASE_SEED, wrapper.as_ref(), buyer.as_ref()], program_id)
+}
@@ -5,15 +5,43 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
     system_instruction,
-    program::{invoke, invoke_signed},
+    system_program,
+    program::{invoke, invoke_signed, set_return_data},
+    program_pack::Pack,
     sysvar::{rent::Rent, Sysvar},
     clock::Clock,
+    log::sol_log_data,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token::state::{Account as TokenAccount, Mint};
 
 use crate::{
+    collection::{
+        find_collection_address, find_membership_address, validate_collection, validate_collection_authority,
+        CollectionMembership, CollectionWrapper, COLLECTION_SEED, MEMBERSHIP_SEED,
+    },
+    freeze::{
+        check_collection_not_frozen, check_not_frozen, find_freeze_address, validate_upgrade_authority, FreezeConfig,
+        FreezeScope, FREEZE_SEED,
+    },
     instruction::WrapperInstruction,
-    state::PrivacyWrapper,
+    lock::{check_unlocked, find_lock_address, PrivacyLock, LOCK_SEED},
+    marketplace::{
+        find_listing_address, find_purchase_address, PurchaseStatus, RevealListing, RevealPurchase, RevealPurchased,
+        RevealTerms, LISTING_SEED, PURCHASE_SEED, REVEAL_PURCHASED_EVENT,
+    },
+    recovery::{find_recovery_address, validate_recovery, RecoveryConfig, RecoveryRequest, RecoveryVote, RECOVERY_SEED},
+    registry::{find_agent_address, validate_agent_name, validate_metadata_uri, AgentRecord, AGENT_SEED},
+    request::{find_request_address, AccessRequest, REQUEST_SEED},
+    reveal::{find_reveal_address, validate_reveal, RevealEscrow, RevealTime, REVEAL_SEED},
+    state::{
+        migrate_legacy, BorshWrapper, PrivacyWrapper, PrivacyWrapperView, PrivacyWrapperViewMut, WrapperHeader,
+        DEFAULT_ACCESS_CAPACITY, HEADER_LEN,
+    },
+    validation::{
+        check_derived, check_owner, check_payer, check_rent_sysvar, check_signer, check_system_program,
+        check_uninitialized, check_writable,
+    },
     error::PrivacyWrapperError,
 };
 
@@ -26,7 +54,7 @@ pub fn process_instruction(
     // Deserialize instruction
     let instruction = WrapperInstruction::try_from_slice(instruction_data)
         .map_err(|_| PrivacyWrapperError::InvalidInstruction)?;
-
+    
     // Route to the appropriate instruction handler
     match instruction {
         WrapperInstruction::CreateWrapper { privacy_config_hash } => {
@@ -35,11 +63,92 @@ pub fn process_instruction(
         WrapperInstruction::UpdatePrivacy { new_privacy_config_hash } => {
             update_privacy(program_id, accounts, new_privacy_config_hash)
         }
-        WrapperInstruction::GrantAccess { account, level } => {
-            grant_access(program_id, accounts, account, level)
+        WrapperInstruction::GrantAccess { grantee, level } => {
+            grant_access(program_id, accounts, grantee, level)
+        }
+        WrapperInstruction::RevokeAccess { grantee } => {
+            revoke_access(program_id, accounts, grantee)
+        }
+        WrapperInstruction::MigrateWrapper => {
+            migrate_wrapper(program_id, accounts)
+        }
+        WrapperInstruction::RegisterAgent { name, metadata_uri } => {
+            register_agent(program_id, accounts, name, metadata_uri)
+        }
+        WrapperInstruction::UpdateAgent { metadata_uri } => {
+            update_agent(program_id, accounts, metadata_uri)
+        }
+        WrapperInstruction::ScheduleReveal { label, reveal_time, key_commitment, sealed_key } => {
+            schedule_reveal(program_id, accounts, label, reveal_time, key_commitment, sealed_key)
+        }
+        WrapperInstruction::ClaimReveal { reveal_key } => {
+            claim_reveal(program_id, accounts, reveal_key)
+        }
+        WrapperInstruction::SetupRecovery { guardians, threshold, challenge_period, key_commitment, sealed_shares } => {
+            setup_recovery(program_id, accounts, guardians, threshold, challenge_period, key_commitment, sealed_shares)
+        }
+        WrapperInstruction::RequestRecovery { new_owner } => {
+            request_recovery(program_id, accounts, new_owner)
+        }
+        WrapperInstruction::VetoRecovery => {
+            veto_recovery(program_id, accounts)
+        }
+        WrapperInstruction::CompleteRecovery => {
+            complete_recovery(program_id, accounts)
+        }
+        WrapperInstruction::LockPrivacy { duration } => {
+            lock_privacy(program_id, accounts, duration)
+        }
+        WrapperInstruction::CreateCollection { default_policy_hash, trusted_agents } => {
+            create_collection(program_id, accounts, default_policy_hash, trusted_agents)
+        }
+        WrapperInstruction::UpdateCollection { default_policy_hash, trusted_agents } => {
+            update_collection(program_id, accounts, default_policy_hash, trusted_agents)
+        }
+        WrapperInstruction::JoinCollection { override_policy } => {
+            join_collection(program_id, accounts, override_policy)
         }
-        WrapperInstruction::RevokeAccess { account } => {
-            revoke_access(program_id, accounts, account)
+        WrapperInstruction::SetPolicyOverride { override_policy } => {
+            set_policy_override(program_id, accounts, override_policy)
+        }
+        WrapperInstruction::ListReveal { terms } => {
+            list_reveal(program_id, accounts, terms)
+        }
+        WrapperInstruction::PayToReveal { terms } => {
+            pay_to_reveal(program_id, accounts, terms)
+        }
+        WrapperInstruction::SettleReveal => {
+            settle_reveal(program_id, accounts)
+        }
+        WrapperInstruction::RefundReveal => {
+            refund_reveal(program_id, accounts)
+        }
+        WrapperInstruction::RequestAccess { level } => {
+            request_access(program_id, accounts, level)
+        }
+        WrapperInstruction::ApproveAccess { requester, level } => {
+            approve_access(program_id, accounts, requester, level)
+        }
+        WrapperInstruction::DenyAccess { requester } => {
+            deny_access(program_id, accounts, requester)
+        }
+        WrapperInstruction::CheckAccess { required_level } => {
+            check_access(program_id, accounts, required_level)
+        }
+        WrapperInstruction::InitializeFreeze { scope, authority } => {
+            initialize_freeze(program_id, accounts, scope, authority)
+        }
+        WrapperInstruction::SetFreeze { frozen } => {
+            set_freeze(program_id, accounts, frozen)
+        }
+        WrapperInstruction::SetFreezeAuthority { new_authority } => {
+            set_freeze_authority(program_id, accounts, new_authority)
+        }
+        WrapperInstruction::ResizeWrapper { capacity } => {
+            resize_wrapper(program_id, accounts, capacity)
+        }
+        WrapperInstruction::ConfirmReveal => {
+            confirm_reveal(program_id, accounts)
         }
     }
 }
@@ -58,14 +167,31 @@ pub fn create_wrapper(
     let wrapper_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
     
     // Verify the owner signed the transaction
     if !owner.is_signer {
         return Err(PrivacyWrapperError::NotNFTOwner.into());
     }
     
-    // Calculate space needed
-    let space = PrivacyWrapper::get_account_size(&privacy_config_hash);
+    // The owner pays for the wrapper account, which must sign for its creation and not exist yet
+    check_payer(owner)?;
+    check_signer(wrapper_account)?;
+    check_uninitialized(wrapper_account)?;
+    check_system_program(system_program)?;
+    check_rent_sysvar(rent_info)?;
+    
+    // Only a real SPL Token mint can be wrapped, and only by the account holding the NFT
+    check_owner(nft_mint, &spl_token::id())?;
+    Mint::unpack(&nft_mint.data.borrow())?;
+    check_owner(token_account, &spl_token::id())?;
+    let holding = TokenAccount::unpack(&token_account.data.borrow())?;
+    if holding.mint != *nft_mint.key || holding.owner != *owner.key || holding.amount != 1 {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Calculate space needed for the header and the initial access entries
+    let space = PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY);
     
     // Get rent
     let rent = &Rent::from_account_info(rent_info)?;
@@ -87,19 +213,19 @@ pub fn create_wrapper(
         ],
     )?;
     
-    // Create the wrapper data
-    let wrapper = PrivacyWrapper {
-        original_nft_mint: *nft_mint.key,
-        owner: *owner.key,
-        privacy_config_hash,
-        access_controls: std::collections::HashMap::new(),
-        last_updated: Clock::get()?.unix_timestamp as u64,
-    };
-    
-    // Serialize and store the wrapper
-    wrapper.serialize(&mut *wrapper_account.data.borrow_mut())?;
+    // Write the wrapper header in place
+    let now = Clock::get()?.unix_timestamp as u64;
+    PrivacyWrapperViewMut::initialize(
+        &mut wrapper_account.data.borrow_mut(),
+        nft_mint.key,
+        owner.key,
+        &privacy_config_hash,
+        now,
+    )?;
     
-    msg!("Privacy wrapper created for NFT: {}", nft_mint.key);
+    // Log without formatting; base58 encoding dominates handler compute units
+    msg!("Privacy wrapper created for NFT");
+    nft_mint.key.log();
     
     Ok(())
 }
@@ -115,6 +241,10 @@ pub fn update_privacy(
     // Parse accounts
     let owner = next_account_info(account_info_iter)?;
     let wrapper_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
     
     // Verify the owner signed the transaction
     if !owner.is_signer {
@@ -122,27 +252,32 @@ pub fn update_privacy(
     }
     
     // Verify account ownership
-    if wrapper_account.owner != program_id {
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
+    
+    // Settings are frozen while the wrapper is locked
+    let now = Clock::get()?.unix_timestamp;
+    check_unlocked(program_id, wrapper_account.key, lock_account, now)?;
+    
+    // ...and paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
     
-    // Deserialize the wrapper account
-    let mut wrapper = PrivacyWrapper::try_from_slice(&wrapper_account.data.borrow())
-        .map_err(|_| PrivacyWrapperError::InvalidAccountData)?;
+    // View the wrapper account in place
+    let mut data = wrapper_account.data.borrow_mut();
+    let mut wrapper = PrivacyWrapperViewMut::load_mut(&mut data)?;
     
     // Verify ownership
-    if wrapper.owner != *owner.key {
+    if !wrapper.header().is_owner(owner.key) {
         return Err(PrivacyWrapperError::NotNFTOwner.into());
     }
     
     // Update the privacy config hash
-    wrapper.privacy_config_hash = new_privacy_config_hash;
-    wrapper.last_updated = Clock::get()?.unix_timestamp as u64;
+    let header = wrapper.header_mut();
+    header.set_privacy_config_hash(&new_privacy_config_hash)?;
+    header.set_last_updated(now as u64);
     
-    // Save the updated wrapper
-    wrapper.serialize(&mut *wrapper_account.data.borrow_mut())?;
-    
-    msg!("Privacy settings updated for NFT: {}", wrapper.original_nft_mint);
+    msg!("Privacy settings updated for NFT");
+    header.original_nft_mint.log();
     
     Ok(())
 }
@@ -151,7 +286,7 @@ pub fn update_privacy(
 pub fn grant_access(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    account: String,
+    grantee: Pubkey,
     level: u8,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -159,6 +294,10 @@ pub fn grant_access(
     // Parse accounts
     let owner = next_account_info(account_info_iter)?;
     let wrapper_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
     
     // Verify the owner signed the transaction
     if !owner.is_signer {
@@ -166,27 +305,31 @@ pub fn grant_access(
     }
     
     // Verify account ownership
-    if wrapper_account.owner != program_id {
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
+    
+    // Settings are frozen while the wrapper is locked
+    let now = Clock::get()?.unix_timestamp;
+    check_unlocked(program_id, wrapper_account.key, lock_account, now)?;
+    
+    // ...and paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
     
-    // Deserialize the wrapper account
-    let mut wrapper = PrivacyWrapper::try_from_slice(&wrapper_account.data.borrow())
-        .map_err(|_| PrivacyWrapperError::InvalidAccountData)?;
+    // View the wrapper account in place
+    let mut data = wrapper_account.data.borrow_mut();
+    let mut wrapper = PrivacyWrapperViewMut::load_mut(&mut data)?;
     
     // Verify ownership
-    if wrapper.owner != *owner.key {
+    if !wrapper.header().is_owner(owner.key) {
         return Err(PrivacyWrapperError::NotNFTOwner.into());
     }
     
-    // Update access control
-    wrapper.access_controls.insert(account.clone(), level);
-    wrapper.last_updated = Clock::get()?.unix_timestamp as u64;
+    // Update access control; only the matching or next free entry is touched
+    wrapper.grant(&grantee, level)?;
+    wrapper.header_mut().set_last_updated(now as u64);
     
-    // Save the updated wrapper
-    wrapper.serialize(&mut *wrapper_account.data.borrow_mut())?;
-    
-    msg!("Access granted to {} with level {}", account, level);
+    msg!("Access granted");
+    grantee.log();
     
     Ok(())
 }
@@ -195,13 +338,14 @@ pub fn grant_access(
 pub fn revoke_access(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    account: String,
+    grantee: Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
     // Parse accounts
     let owner = next_account_info(account_info_iter)?;
     let wrapper_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
     
     // Verify the owner signed the transaction
     if !owner.is_signer {
@@ -209,27 +353,1692 @@ pub fn revoke_access(
     }
     
     // Verify account ownership
-    if wrapper_account.owner != program_id {
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
     
-    // Deserialize the wrapper account
-    let mut wrapper = PrivacyWrapper::try_from_slice(&wrapper_account.data.borrow())
-        .map_err(|_| PrivacyWrapperError::InvalidAccountData)?;
+    // Settings are frozen while the wrapper is locked
+    let now = Clock::get()?.unix_timestamp;
+    check_unlocked(program_id, wrapper_account.key, lock_account, now)?;
+    
+    // View the wrapper account in place
+    let mut data = wrapper_account.data.borrow_mut();
+    let mut wrapper = PrivacyWrapperViewMut::load_mut(&mut data)?;
     
     // Verify ownership
-    if wrapper.owner != *owner.key {
+    if !wrapper.header().is_owner(owner.key) {
         return Err(PrivacyWrapperError::NotNFTOwner.into());
     }
     
     // Remove access
-    wrapper.access_controls.remove(&account);
-    wrapper.last_updated = Clock::get()?.unix_timestamp as u64;
+    wrapper.revoke(&grantee);
+    wrapper.header_mut().set_last_updated(now as u64);
+    
+    msg!("Access revoked");
+    grantee.log();
+    
+    Ok(())
+}
+
+/// Migrate a wrapper from an earlier layout to the current layout
+pub fn migrate_wrapper(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
+    
+    // Accounts from the original program are Borsh-encoded, with the owner after the mint
+    let borsh_wrapper = BorshWrapper::parse(&wrapper_account.data.borrow());
+    let wrapper_owner = match &borsh_wrapper {
+        Some(wrapper) => wrapper.owner,
+        None => {
+            // The header layout is shared across versions, so ownership can be checked first
+            let data = wrapper_account.data.borrow();
+            let header: &WrapperHeader = bytemuck::from_bytes(
+                data.get(..HEADER_LEN).ok_or(PrivacyWrapperError::InvalidAccountData)?,
+            );
+            header.owner
+        }
+    };
+    if wrapper_owner != *owner.key {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Borsh accounts were sized for their encoding and grow to hold every entry, at the owner's expense
+    if let Some(wrapper) = &borsh_wrapper {
+        check_payer(owner)?;
+        check_system_program(system_program)?;
+        resize_account(wrapper_account, owner, system_program, wrapper.migrated_size())?;
+    }
+    
+    // Rewrite legacy entries in place
+    migrate_legacy(&mut wrapper_account.data.borrow_mut())?;
+    
+    msg!("Privacy wrapper migrated");
+    
+    Ok(())
+}
+
+/// Register an agent name in the agent registry
+pub fn register_agent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    metadata_uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let agent = next_account_info(account_info_iter)?;
+    let agent_record = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // The authority pays and the agent proves control of its key
+    if !authority.is_signer || !agent.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    check_payer(authority)?;
+    check_uninitialized(agent_record)?;
+    check_system_program(system_program)?;
+    
+    validate_agent_name(&name)?;
+    validate_metadata_uri(&metadata_uri)?;
+    
+    // Verify the record address matches the name
+    let (expected_record, bump) = find_agent_address(program_id, &name);
+    if *agent_record.key != expected_record {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Create the record account
+    let name_hash = solana_program::hash::hashv(&[name.as_bytes()]);
+    
+    create_pda_account(
+        authority,
+        agent_record,
+        system_program,
+        program_id,
+        AgentRecord::LEN,
+        0,
+        &[AGENT_SEED, name_hash.as_ref(), &[bump]],
+    )?;
+    
+    let record = AgentRecord {
+        is_initialized: true,
+        name,
+        agent: *agent.key,
+        authority: *authority.key,
+        metadata_uri,
+        registered_at: Clock::get()?.unix_timestamp as u64,
+    };
+    record.serialize(&mut *agent_record.data.borrow_mut())?;
+    
+    msg!("Agent registered");
+    agent.key.log();
+    
+    Ok(())
+}
+
+/// Update a registered agent's key and metadata
+pub fn update_agent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata_uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let new_agent = next_account_info(account_info_iter)?;
+    let agent_record = next_account_info(account_info_iter)?;
+    
+    // The new agent key must sign so a record can't point at a key nobody controls
+    if !authority.is_signer || !new_agent.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(agent_record, program_id)?;
+    check_writable(agent_record)?;
+    
+    validate_metadata_uri(&metadata_uri)?;
+    
+    let mut record = AgentRecord::unpack(&agent_record.data.borrow())?;
+    check_derived(agent_record, find_agent_address(program_id, &record.name))?;
+    
+    // Verify authority
+    if record.authority != *authority.key {
+        return Err(PrivacyWrapperError::NotAgentAuthority.into());
+    }
+    
+    record.agent = *new_agent.key;
+    record.metadata_uri = metadata_uri;
+    record.serialize(&mut *agent_record.data.borrow_mut())?;
+    
+    msg!("Agent updated");
+    new_agent.key.log();
+    
+    Ok(())
+}
+
+/// Escrow a sealed data key that is revealed after a slot or timestamp
+pub fn schedule_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    label: String,
+    reveal_time: RevealTime,
+    key_commitment: [u8; 32],
+    sealed_key: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
     
-    // Save the updated wrapper
-    wrapper.serialize(&mut *wrapper_account.data.borrow_mut())?;
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let reveal_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    // Only the wrapper owner schedules reveals for it
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    check_payer(owner)?;
+    check_uninitialized(reveal_account)?;
+    check_system_program(system_program)?;
+    
+    validate_reveal(&label, &sealed_key)?;
+    
+    // Verify the escrow address matches the wrapper and label
+    let (expected_reveal, bump) = find_reveal_address(program_id, wrapper_account.key, &label);
+    if *reveal_account.key != expected_reveal {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Create the escrow account
+    let label_hash = solana_program::hash::hashv(&[label.as_bytes()]);
+    
+    create_pda_account(
+        owner,
+        reveal_account,
+        system_program,
+        program_id,
+        RevealEscrow::LEN,
+        0,
+        &[REVEAL_SEED, wrapper_account.key.as_ref(), label_hash.as_ref(), &[bump]],
+    )?;
+    
+    let escrow = RevealEscrow {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        label,
+        reveal_time,
+        key_commitment,
+        sealed_key,
+        reveal_key: None,
+    };
+    escrow.serialize(&mut *reveal_account.data.borrow_mut())?;
+    
+    msg!("Reveal scheduled");
+    reveal_account.key.log();
+    
+    Ok(())
+}
+
+/// Publish the reveal key of an unlocked reveal
+pub fn claim_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reveal_key: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let claimer = next_account_info(account_info_iter)?;
+    let reveal_account = next_account_info(account_info_iter)?;
+    
+    if !claimer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(reveal_account, program_id)?;
+    check_writable(reveal_account)?;
+    
+    let mut escrow = RevealEscrow::unpack(&reveal_account.data.borrow())?;
+    check_derived(reveal_account, find_reveal_address(program_id, &escrow.wrapper, &escrow.label))?;
+    
+    if escrow.reveal_key.is_some() {
+        return Err(PrivacyWrapperError::AlreadyRevealed.into());
+    }
+    
+    if !escrow.reveal_time.has_passed(&Clock::get()?) {
+        return Err(PrivacyWrapperError::RevealLocked.into());
+    }
+    
+    if !escrow.matches(&reveal_key) {
+        return Err(PrivacyWrapperError::InvalidRevealKey.into());
+    }
+    
+    escrow.reveal_key = Some(reveal_key);
+    escrow.serialize(&mut *reveal_account.data.borrow_mut())?;
+    
+    msg!("Reveal claimed");
+    reveal_account.key.log();
+    
+    Ok(())
+}
+
+/// Set the guardians holding shares of the content key
+pub fn setup_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    challenge_period: i64,
+    key_commitment: [u8; 32],
+    sealed_shares: Vec<Vec<u8>>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    // Only the wrapper owner picks its guardians
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    check_payer(owner)?;
+    check_writable(recovery_account)?;
+    check_system_program(system_program)?;
+    
+    validate_recovery(&guardians, threshold, challenge_period, &sealed_shares)?;
+    
+    // Verify the recovery address matches the wrapper
+    let (expected_recovery, bump) = find_recovery_address(program_id, wrapper_account.key);
+    if *recovery_account.key != expected_recovery {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if recovery_account.data_is_empty() {
+        // First setup creates the account
+        create_pda_account(
+            owner,
+            recovery_account,
+            system_program,
+            program_id,
+            RecoveryConfig::LEN,
+            0,
+            &[RECOVERY_SEED, wrapper_account.key.as_ref(), &[bump]],
+        )?;
+    } else {
+        check_owner(recovery_account, program_id)?;
+        
+        // Guardians can't be swapped out from under a pending request; veto it first
+        if RecoveryConfig::unpack(&recovery_account.data.borrow())?.pending.is_some() {
+            return Err(PrivacyWrapperError::RecoveryPending.into());
+        }
+    }
+    
+    let config = RecoveryConfig {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        guardians,
+        threshold,
+        challenge_period,
+        key_commitment,
+        sealed_shares,
+        pending: None,
+        rival_votes: Vec::new(),
+    };
+    config.serialize(&mut *recovery_account.data.borrow_mut())?;
+    
+    msg!("Recovery guardians set");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Request or approve moving the wrapper to a new owner
+pub fn request_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_owner: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let guardian = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    
+    if !guardian.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(recovery_account, program_id)?;
+    check_writable(recovery_account)?;
+    
+    let mut config = RecoveryConfig::unpack(&recovery_account.data.borrow())?;
+    check_derived(recovery_account, find_recovery_address(program_id, &config.wrapper))?;
+    
+    if !config.is_guardian(guardian.key) {
+        return Err(PrivacyWrapperError::NotGuardian.into());
+    }
+    
+    // Each guardian backs one new owner; a new request withdraws their old one
+    config.rival_votes.retain(|vote| vote.guardian != *guardian.key);
+    if let Some(request) = config.pending.as_mut() {
+        request.approvals.retain(|approver| approver != guardian.key);
+    }
+    config.rival_votes.push(RecoveryVote { guardian: *guardian.key, new_owner });
+    
+    let backers: Vec<Pubkey> = config.rival_votes.iter()
+        .filter(|vote| vote.new_owner == new_owner)
+        .map(|vote| vote.guardian)
+        .collect();
+    let (approves, contested) = match &config.pending {
+        Some(request) => (request.new_owner == new_owner, !request.approvals.is_empty()),
+        None => (false, false),
+    };
+    
+    // The first request starts the challenge period and later ones approve it.
+    // A request another guardian still backs only gives way to a threshold.
+    if approves || !contested || backers.len() >= config.threshold as usize {
+        config.rival_votes.retain(|vote| vote.new_owner != new_owner);
+        match &mut config.pending {
+            Some(request) if approves => request.approvals.extend(backers),
+            pending => {
+                *pending = Some(RecoveryRequest {
+                    new_owner,
+                    requested_at: Clock::get()?.unix_timestamp,
+                    approvals: backers,
+                });
+            }
+        }
+    }
+    config.serialize(&mut *recovery_account.data.borrow_mut())?;
+    
+    msg!("Recovery requested");
+    new_owner.log();
+    
+    Ok(())
+}
+
+/// Cancel a pending recovery
+pub fn veto_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(recovery_account, program_id)?;
+    check_writable(recovery_account)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
     
-    msg!("Access revoked from {}", account);
+    check_derived(recovery_account, find_recovery_address(program_id, wrapper_account.key))?;
+    let mut config = RecoveryConfig::unpack(&recovery_account.data.borrow())?;
+    
+    if config.pending.take().is_none() {
+        return Err(PrivacyWrapperError::NoRecoveryPending.into());
+    }
+    config.rival_votes.clear();
+    config.serialize(&mut *recovery_account.data.borrow_mut())?;
+    
+    msg!("Recovery vetoed");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Move the wrapper to the new owner once the challenge period has passed
+pub fn complete_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let payer = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let recovery_account = next_account_info(account_info_iter)?;
+    
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(recovery_account, program_id)?;
+    check_writable(wrapper_account)?;
+    check_writable(recovery_account)?;
+    
+    check_derived(recovery_account, find_recovery_address(program_id, wrapper_account.key))?;
+    let mut config = RecoveryConfig::unpack(&recovery_account.data.borrow())?;
+    
+    let request = config.pending.take().ok_or(PrivacyWrapperError::NoRecoveryPending)?;
+    config.rival_votes.clear();
+    
+    if request.approvals.len() < config.threshold as usize {
+        return Err(PrivacyWrapperError::InsufficientApprovals.into());
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    if now < request.requested_at.saturating_add(config.challenge_period) {
+        return Err(PrivacyWrapperError::ChallengePeriodActive.into());
+    }
+    
+    // Hand the wrapper to the new owner
+    let mut data = wrapper_account.data.borrow_mut();
+    let mut wrapper = PrivacyWrapperViewMut::load_mut(&mut data)?;
+    let header = wrapper.header_mut();
+    header.owner = request.new_owner;
+    header.set_last_updated(now as u64);
+    
+    config.serialize(&mut *recovery_account.data.borrow_mut())?;
+    
+    msg!("Recovery completed");
+    request.new_owner.log();
+    
+    Ok(())
+}
+
+/// Freeze privacy settings and access grants, optionally for a duration
+pub fn lock_privacy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    duration: Option<i64>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    check_payer(owner)?;
+    check_writable(lock_account)?;
+    check_system_program(system_program)?;
+    
+    if matches!(duration, Some(duration) if duration <= 0) {
+        return Err(PrivacyWrapperError::InvalidLockDuration.into());
+    }
+    
+    // Verify the lock address matches the wrapper
+    let (expected_lock, bump) = find_lock_address(program_id, wrapper_account.key);
+    if *lock_account.key != expected_lock {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let now = Clock::get()?.unix_timestamp;
+    let locked_until = duration.map(|duration| now.saturating_add(duration));
+    
+    let locked_at = if lock_account.data_is_empty() {
+        // First lock creates the account
+        create_pda_account(
+            owner,
+            lock_account,
+            system_program,
+            program_id,
+            PrivacyLock::LEN,
+            0,
+            &[LOCK_SEED, wrapper_account.key.as_ref(), &[bump]],
+        )?;
+        now
+    } else {
+        check_owner(lock_account, program_id)?;
+        let existing = PrivacyLock::unpack(&lock_account.data.borrow())?;
+        if !existing.is_active(now) {
+            // An expired lock is replaced by a fresh one
+            now
+        } else {
+            // An active lock can be extended but never shortened
+            let shortens = match (existing.locked_until, locked_until) {
+                (None, Some(_)) => true,
+                (Some(current), Some(requested)) => requested < current,
+                (_, None) => false,
+            };
+            if shortens {
+                return Err(PrivacyWrapperError::InvalidLockDuration.into());
+            }
+            existing.locked_at
+        }
+    };
+    
+    let lock = PrivacyLock {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        locked_at,
+        locked_until,
+    };
+    lock.serialize(&mut *lock_account.data.borrow_mut())?;
+    
+    msg!("Privacy settings locked");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Create a collection wrapper holding the policy its members inherit
+pub fn create_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    default_policy_hash: String,
+    trusted_agents: Vec<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let collection_mint = next_account_info(account_info_iter)?;
+    let metadata_account = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    check_payer(authority)?;
+    check_uninitialized(collection_account)?;
+    check_system_program(system_program)?;
+    
+    // Only the collection's update authority can set its policy
+    validate_collection_authority(collection_mint.key, metadata_account, authority.key)?;
+    validate_collection(&default_policy_hash, &trusted_agents)?;
+    
+    // Verify the collection address matches the mint
+    let (expected_collection, bump) = find_collection_address(program_id, collection_mint.key);
+    if *collection_account.key != expected_collection {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Create the collection account
+    create_pda_account(
+        authority,
+        collection_account,
+        system_program,
+        program_id,
+        CollectionWrapper::LEN,
+        0,
+        &[COLLECTION_SEED, collection_mint.key.as_ref(), &[bump]],
+    )?;
+    
+    let collection = CollectionWrapper {
+        is_initialized: true,
+        collection_mint: *collection_mint.key,
+        authority: *authority.key,
+        default_policy_hash,
+        trusted_agents,
+        last_updated: Clock::get()?.unix_timestamp as u64,
+    };
+    collection.serialize(&mut *collection_account.data.borrow_mut())?;
+    
+    msg!("Collection wrapper created");
+    collection_mint.key.log();
+    
+    Ok(())
+}
+
+/// Update a collection's default policy and trusted agents
+pub fn update_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    default_policy_hash: String,
+    trusted_agents: Vec<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(collection_account, program_id)?;
+    check_writable(collection_account)?;
+    
+    validate_collection(&default_policy_hash, &trusted_agents)?;
+    
+    let mut collection = CollectionWrapper::unpack(&collection_account.data.borrow())?;
+    check_derived(collection_account, find_collection_address(program_id, &collection.collection_mint))?;
+    if collection.authority != *authority.key {
+        return Err(PrivacyWrapperError::NotCollectionAuthority.into());
+    }
+    
+    collection.default_policy_hash = default_policy_hash;
+    collection.trusted_agents = trusted_agents;
+    collection.last_updated = Clock::get()?.unix_timestamp as u64;
+    collection.serialize(&mut *collection_account.data.borrow_mut())?;
+    
+    msg!("Collection wrapper updated");
+    collection.collection_mint.log();
+    
+    Ok(())
+}
+
+/// Add a wrapper to a collection, approved by the collection authority
+pub fn join_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    override_policy: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let collection_account = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
+    let joined_freeze = next_account_info(account_info_iter)?;
+    
+    // Both the wrapper owner and the collection authority agree to the membership
+    if !owner.is_signer || !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(collection_account, program_id)?;
+    check_payer(owner)?;
+    check_writable(membership_account)?;
+    check_system_program(system_program)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    let collection = CollectionWrapper::unpack(&collection_account.data.borrow())?;
+    check_derived(collection_account, find_collection_address(program_id, &collection.collection_mint))?;
+    if collection.authority != *authority.key {
+        return Err(PrivacyWrapperError::NotCollectionAuthority.into());
+    }
+    
+    // Joining changes the wrapper's effective policy, so it is frozen by the lock
+    check_unlocked(program_id, wrapper_account.key, lock_account, Clock::get()?.unix_timestamp)?;
+    
+    // ...and paused while the program, the collection the wrapper leaves, or the one it joins is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
+    check_collection_not_frozen(program_id, collection_account.key, joined_freeze)?;
+    
+    // Verify the membership address matches the wrapper
+    let (expected_membership, bump) = find_membership_address(program_id, wrapper_account.key);
+    if *membership_account.key != expected_membership {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if membership_account.data_is_empty() {
+        // First join creates the account; later joins move the wrapper
+        create_pda_account(
+            owner,
+            membership_account,
+            system_program,
+            program_id,
+            CollectionMembership::LEN,
+            0,
+            &[MEMBERSHIP_SEED, wrapper_account.key.as_ref(), &[bump]],
+        )?;
+    } else {
+        check_owner(membership_account, program_id)?;
+    }
+    
+    let membership = CollectionMembership {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        collection: *collection_account.key,
+        override_policy,
+    };
+    membership.serialize(&mut *membership_account.data.borrow_mut())?;
+    
+    msg!("Wrapper joined collection");
+    collection_account.key.log();
+    
+    Ok(())
+}
+
+/// Choose between the wrapper's own policy and the collection default
+pub fn set_policy_override(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    override_policy: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(membership_account, program_id)?;
+    check_writable(membership_account)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Switching policies is a settings change, so it is frozen by the lock
+    check_unlocked(program_id, wrapper_account.key, lock_account, Clock::get()?.unix_timestamp)?;
+    
+    // ...and paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
+    
+    check_derived(membership_account, find_membership_address(program_id, wrapper_account.key))?;
+    let mut membership = CollectionMembership::unpack(&membership_account.data.borrow())?;
+    
+    membership.override_policy = override_policy;
+    membership.serialize(&mut *membership_account.data.borrow_mut())?;
+    
+    msg!("Collection policy override set");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Ask the owner for access to a wrapper
+pub fn request_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let requester = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let request_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_payer(requester)?;
+    check_writable(request_account)?;
+    check_system_program(system_program)?;
+    
+    // Only initialized wrappers take requests
+    PrivacyWrapperView::load(&wrapper_account.data.borrow())?;
+    
+    // Verify the request address matches the wrapper and requester
+    let (expected_request, bump) = find_request_address(program_id, wrapper_account.key, requester.key);
+    if *request_account.key != expected_request {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Each requester pays for their own request, so nobody can fill up a shared queue. A
+    // pending request can't be rewritten, so the level the owner reviews is the level approved
+    if !request_account.data_is_empty() {
+        return Err(PrivacyWrapperError::AccessRequestPending.into());
+    }
+    create_pda_account(
+        requester,
+        request_account,
+        system_program,
+        program_id,
+        AccessRequest::LEN,
+        0,
+        &[REQUEST_SEED, wrapper_account.key.as_ref(), requester.key.as_ref(), &[bump]],
+    )?;
+    
+    let request = AccessRequest {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        requester: *requester.key,
+        level,
+        requested_at: Clock::get()?.unix_timestamp,
+    };
+    request.serialize(&mut *request_account.data.borrow_mut())?;
+    
+    msg!("Access requested");
+    requester.key.log();
+    
+    Ok(())
+}
+
+/// Grant a pending request at the level it asked for
+pub fn approve_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    requester: Pubkey,
+    level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let lock_account = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
+    let request_account = next_account_info(account_info_iter)?;
+    let requester_account = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
+    
+    // Approving is a grant, so it is frozen by the lock
+    let now = Clock::get()?.unix_timestamp;
+    check_unlocked(program_id, wrapper_account.key, lock_account, now)?;
+    
+    // ...and paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
+    
+    // View the wrapper account in place
+    let mut data = wrapper_account.data.borrow_mut();
+    let mut wrapper = PrivacyWrapperViewMut::load_mut(&mut data)?;
+    
+    // Verify ownership
+    if !wrapper.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // The owner approves the level they reviewed, never one the requester swapped in since
+    if take_request(program_id, wrapper_account, request_account, requester_account, &requester)? != level {
+        return Err(PrivacyWrapperError::AccessRequestMismatch.into());
+    }
+    wrapper.grant(&requester, level)?;
+    wrapper.header_mut().set_last_updated(now as u64);
+    
+    msg!("Access request approved");
+    requester.log();
+    
+    Ok(())
+}
+
+/// Drop a pending request without granting it
+pub fn deny_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    requester: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let request_account = next_account_info(account_info_iter)?;
+    let requester_account = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    // Verify ownership
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    take_request(program_id, wrapper_account, request_account, requester_account, &requester)?;
+    
+    msg!("Access request denied");
+    requester.log();
+    
+    Ok(())
+}
+
+/// Close a pending access request, refunding its rent to the requester, and return the level it asked for
+fn take_request(
+    program_id: &Pubkey,
+    wrapper_account: &AccountInfo,
+    request_account: &AccountInfo,
+    requester_account: &AccountInfo,
+    requester: &Pubkey,
+) -> Result<u8, ProgramError> {
+    check_derived(request_account, find_request_address(program_id, wrapper_account.key, requester))?;
+    if request_account.data_is_empty() || request_account.owner != program_id {
+        return Err(PrivacyWrapperError::NoAccessRequest.into());
+    }
+    if requester_account.key != requester {
+        return Err(PrivacyWrapperError::NoAccessRequest.into());
+    }
+    check_writable(request_account)?;
+    check_writable(requester_account)?;
+    
+    let level = AccessRequest::unpack(&request_account.data.borrow())?.level;
+    close_account(request_account, requester_account)?;
+    
+    Ok(level)
+}
+
+/// Fail unless an account holds at least a required access level
+pub fn check_access(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    required_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let viewer = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter).ok();
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    let data = wrapper_account.data.borrow();
+    let mut level = PrivacyWrapperView::load(&data)?.get_access_level(viewer.key);
+    
+    // Timed access bought through PayToReveal counts until it expires or is
+    // refunded; a purchase PDA that was never created grants nothing
+    if let Some(purchase_account) = purchase_account {
+        check_derived(purchase_account, find_purchase_address(program_id, wrapper_account.key, viewer.key))?;
+        
+        if !purchase_account.data_is_empty() {
+            check_owner(purchase_account, program_id)?;
+            let purchase = RevealPurchase::unpack(&purchase_account.data.borrow())?;
+            level = level.max(purchase.level_at(Clock::get()?.unix_timestamp));
+        }
+    }
+    
+    if level < required_level {
+        return Err(PrivacyWrapperError::InsufficientAccess.into());
+    }
+    
+    // Calling programs read the level back with get_return_data
+    set_return_data(&[level]);
+    
+    Ok(())
+}
+
+/// Offer viewers timed access to a wrapper for a price
+pub fn list_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    terms: RevealTerms,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    check_payer(owner)?;
+    check_writable(listing_account)?;
+    check_system_program(system_program)?;
+    
+    terms.validate()?;
+    
+    // Selling access widens it, so listing is paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
+    
+    // Verify the listing address matches the wrapper
+    let (expected_listing, bump) = find_listing_address(program_id, wrapper_account.key);
+    if *listing_account.key != expected_listing {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if listing_account.data_is_empty() {
+        // First listing creates the account; later ones replace the terms
+        create_pda_account(
+            owner,
+            listing_account,
+            system_program,
+            program_id,
+            RevealListing::LEN,
+            0,
+            &[LISTING_SEED, wrapper_account.key.as_ref(), &[bump]],
+        )?;
+    } else {
+        check_owner(listing_account, program_id)?;
+    }
+    
+    let listing = RevealListing {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        terms,
+    };
+    listing.serialize(&mut *listing_account.data.borrow_mut())?;
+    
+    msg!("Reveal listed");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Pay into escrow for timed access under a wrapper's listing
+pub fn pay_to_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expected_terms: RevealTerms,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let buyer = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let listing_account = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let program_freeze = next_account_info(account_info_iter)?;
+    let membership_account = next_account_info(account_info_iter)?;
+    let collection_freeze = next_account_info(account_info_iter)?;
+    
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(listing_account, program_id)?;
+    check_payer(buyer)?;
+    check_writable(purchase_account)?;
+    check_system_program(system_program)?;
+    
+    check_derived(listing_account, find_listing_address(program_id, wrapper_account.key))?;
+    // The buyer pays only under the terms they agreed to, so an owner can't
+    // relist between the buyer reading the listing and the payment landing
+    let terms = RevealListing::unpack(&listing_account.data.borrow())?.terms;
+    if terms != expected_terms {
+        return Err(PrivacyWrapperError::RevealTermsChanged.into());
+    }
+    
+    // A purchase grants access, so it is paused while the program or the wrapper's collection is frozen
+    check_not_frozen(program_id, wrapper_account.key, program_freeze, membership_account, collection_freeze)?;
+    
+    // Verify the purchase address matches the wrapper and buyer
+    let (expected_purchase, bump) = find_purchase_address(program_id, wrapper_account.key, buyer.key);
+    if *purchase_account.key != expected_purchase {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Escrow the payment in the purchase account
+    let now = Clock::get()?.unix_timestamp;
+    if purchase_account.data_is_empty() {
+        create_pda_account(
+            buyer,
+            purchase_account,
+            system_program,
+            program_id,
+            RevealPurchase::LEN,
+            terms.price,
+            &[PURCHASE_SEED, wrapper_account.key.as_ref(), buyer.key.as_ref(), &[bump]],
+        )?;
+    } else {
+        check_owner(purchase_account, program_id)?;
+        
+        // A buyer renews only once the last payment has left escrow and the
+        // access it bought has ended, so a retried payment can't land twice
+        let previous = RevealPurchase::unpack(&purchase_account.data.borrow())?;
+        if previous.is_held() {
+            return Err(PrivacyWrapperError::PurchaseEscrowed.into());
+        }
+        if previous.level_at(now) > 0 {
+            return Err(PrivacyWrapperError::PurchaseStillActive.into());
+        }
+        
+        invoke(
+            &system_instruction::transfer(buyer.key, purchase_account.key, terms.price),
+            &[
+                buyer.clone(),
+                purchase_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    
+    let purchase = RevealPurchase {
+        is_initialized: true,
+        wrapper: *wrapper_account.key,
+        buyer: *buyer.key,
+        amount: terms.price,
+        access_level: terms.access_level,
+        paid_at: now,
+        expires_at: now.saturating_add(terms.access_duration),
+        refundable_at: now.saturating_add(terms.settlement_window),
+        settleable_at: now.saturating_add(terms.settlement_window).saturating_add(terms.dispute_window),
+        royalty_shares: terms.royalty_shares,
+        status: PurchaseStatus::Escrowed,
+    };
+    purchase.serialize(&mut *purchase_account.data.borrow_mut())?;
+    
+    // Emit the event the key-delivery service watches for
+    let event = RevealPurchased {
+        wrapper: purchase.wrapper,
+        buyer: purchase.buyer,
+        purchase: *purchase_account.key,
+        access_level: purchase.access_level,
+        expires_at: purchase.expires_at,
+        amount: purchase.amount,
+    };
+    sol_log_data(&[REVEAL_PURCHASED_EVENT, &event.try_to_vec()?]);
+    
+    Ok(())
+}
+
+/// Pay an escrowed purchase out to royalty recipients and the owner
+pub fn settle_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_owner(purchase_account, program_id)?;
+    check_writable(owner)?;
+    check_writable(purchase_account)?;
+    
+    if !PrivacyWrapperView::load(&wrapper_account.data.borrow())?.header().is_owner(owner.key) {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    let mut purchase = RevealPurchase::unpack(&purchase_account.data.borrow())?;
+    check_derived(purchase_account, find_purchase_address(program_id, wrapper_account.key, &purchase.buyer))?;
+    if !purchase.is_held() {
+        return Err(PrivacyWrapperError::PurchaseNotEscrowed.into());
+    }
+    
+    // Until the buyer confirms the key, the payment only pays out once the
+    // dispute window has passed without the buyer reclaiming it
+    if purchase.status == PurchaseStatus::Escrowed && Clock::get()?.unix_timestamp < purchase.settleable_at {
+        return Err(PrivacyWrapperError::RevealNotConfirmed.into());
+    }
+    
+    // Pay each royalty recipient its cut, then the owner the rest
+    let (cuts, remainder) = purchase.payouts();
+    for (share, cut) in purchase.royalty_shares.iter().zip(cuts) {
+        let recipient = next_account_info(account_info_iter)?;
+        if *recipient.key != share.recipient {
+            return Err(PrivacyWrapperError::InvalidRoyaltyRecipient.into());
+        }
+        check_writable(recipient)?;
+        move_lamports(purchase_account, recipient, cut)?;
+    }
+    move_lamports(purchase_account, owner, remainder)?;
+    
+    purchase.status = PurchaseStatus::Settled;
+    purchase.serialize(&mut *purchase_account.data.borrow_mut())?;
+    
+    msg!("Reveal settled");
+    purchase.buyer.log();
+    
+    Ok(())
+}
+
+/// Confirm receiving the key for an escrowed purchase, letting the owner settle it
+pub fn confirm_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let buyer = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter)?;
+    
+    check_signer(buyer)?;
+    
+    // Verify account ownership
+    check_owner(purchase_account, program_id)?;
+    check_writable(purchase_account)?;
+    
+    let mut purchase = RevealPurchase::unpack(&purchase_account.data.borrow())?;
+    check_derived(purchase_account, find_purchase_address(program_id, &purchase.wrapper, buyer.key))?;
+    if purchase.status != PurchaseStatus::Escrowed {
+        return Err(PrivacyWrapperError::PurchaseNotEscrowed.into());
+    }
+    
+    // The buyer gives up the refund in exchange for the key
+    purchase.status = PurchaseStatus::Confirmed;
+    purchase.serialize(&mut *purchase_account.data.borrow_mut())?;
+    
+    msg!("Reveal confirmed");
+    buyer.key.log();
+    
+    Ok(())
+}
+
+/// Reclaim an unsettled payment once the settlement window has passed
+pub fn refund_reveal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let buyer = next_account_info(account_info_iter)?;
+    let purchase_account = next_account_info(account_info_iter)?;
+    
+    if !buyer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    // Verify account ownership
+    check_owner(purchase_account, program_id)?;
+    check_writable(buyer)?;
+    check_writable(purchase_account)?;
+    
+    let mut purchase = RevealPurchase::unpack(&purchase_account.data.borrow())?;
+    check_derived(purchase_account, find_purchase_address(program_id, &purchase.wrapper, buyer.key))?;
+    if purchase.status != PurchaseStatus::Escrowed {
+        return Err(PrivacyWrapperError::PurchaseNotEscrowed.into());
+    }
+    if Clock::get()?.unix_timestamp < purchase.refundable_at {
+        return Err(PrivacyWrapperError::SettlementWindowActive.into());
+    }
+    
+    // Return the payment; the access ends with it
+    move_lamports(purchase_account, buyer, purchase.amount)?;
+    purchase.status = PurchaseStatus::Refunded;
+    purchase.serialize(&mut *purchase_account.data.borrow_mut())?;
+    
+    msg!("Reveal refunded");
+    buyer.key.log();
+    
+    Ok(())
+}
+
+/// Create a freeze config for the program or a collection
+pub fn initialize_freeze(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    scope: FreezeScope,
+    authority: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let payer = next_account_info(account_info_iter)?;
+    let scope_authority = next_account_info(account_info_iter)?;
+    let scope_account = next_account_info(account_info_iter)?;
+    let freeze_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    check_payer(payer)?;
+    check_signer(scope_authority)?;
+    check_uninitialized(freeze_account)?;
+    check_system_program(system_program)?;
+    
+    // The program's upgrade authority sets up the program-wide config, the
+    // collection authority a collection's
+    match scope {
+        FreezeScope::Program => validate_upgrade_authority(program_id, scope_account, scope_authority.key)?,
+        FreezeScope::Collection(collection) => {
+            check_owner(scope_account, program_id)?;
+            if *scope_account.key != collection {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            
+            let wrapper = CollectionWrapper::unpack(&scope_account.data.borrow())?;
+            check_derived(scope_account, find_collection_address(program_id, &wrapper.collection_mint))?;
+            if wrapper.authority != *scope_authority.key {
+                return Err(PrivacyWrapperError::NotCollectionAuthority.into());
+            }
+        }
+    }
+    
+    // Verify the config address matches the scope
+    let (expected_freeze, bump) = find_freeze_address(program_id, &scope);
+    if *freeze_account.key != expected_freeze {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let bump_seed = [bump];
+    let mut seeds = vec![FREEZE_SEED];
+    if let FreezeScope::Collection(collection) = &scope {
+        seeds.push(collection.as_ref());
+    }
+    seeds.push(&bump_seed);
+    
+    create_pda_account(
+        payer,
+        freeze_account,
+        system_program,
+        program_id,
+        FreezeConfig::LEN,
+        0,
+        &seeds,
+    )?;
+    
+    let config = FreezeConfig {
+        is_initialized: true,
+        scope,
+        authority,
+        frozen: false,
+        updated_at: Clock::get()?.unix_timestamp,
+    };
+    config.serialize(&mut *freeze_account.data.borrow_mut())?;
+    
+    msg!("Freeze config created");
+    authority.log();
+    
+    Ok(())
+}
+
+/// Pause or resume settings changes under a freeze config
+pub fn set_freeze(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    frozen: bool,
+) -> ProgramResult {
+    let (freeze_account, mut config) = load_freeze_config(program_id, accounts)?;
+    
+    config.frozen = frozen;
+    config.updated_at = Clock::get()?.unix_timestamp;
+    config.serialize(&mut *freeze_account.data.borrow_mut())?;
+    
+    if frozen {
+        msg!("Settings changes frozen");
+    } else {
+        msg!("Settings changes unfrozen");
+    }
+    freeze_account.key.log();
+    
+    Ok(())
+}
+
+/// Hand a freeze config to a new authority
+pub fn set_freeze_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let (freeze_account, mut config) = load_freeze_config(program_id, accounts)?;
+    
+    config.authority = new_authority;
+    config.serialize(&mut *freeze_account.data.borrow_mut())?;
+    
+    msg!("Freeze authority changed");
+    new_authority.log();
+    
+    Ok(())
+}
+
+/// Resize a wrapper to hold a number of access entries
+pub fn resize_wrapper(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    capacity: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let owner = next_account_info(account_info_iter)?;
+    let wrapper_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify the owner signed the transaction
+    if !owner.is_signer {
+        return Err(PrivacyWrapperError::NotNFTOwner.into());
+    }
+    
+    // Verify account ownership
+    check_owner(wrapper_account, program_id)?;
+    check_writable(wrapper_account)?;
+    check_payer(owner)?;
+    check_system_program(system_program)?;
+    
+    {
+        let data = wrapper_account.data.borrow();
+        let wrapper = PrivacyWrapperView::load(&data)?;
+        
+        if !wrapper.header().is_owner(owner.key) {
+            return Err(PrivacyWrapperError::NotNFTOwner.into());
+        }
+        
+        // Every grant must still fit
+        if (capacity as usize) < wrapper.entries().len() {
+            return Err(PrivacyWrapperError::AccessListFull.into());
+        }
+    }
+    
+    resize_account(wrapper_account, owner, system_program, PrivacyWrapper::get_account_size(capacity as usize))?;
+    
+    msg!("Privacy wrapper resized");
+    wrapper_account.key.log();
+    
+    Ok(())
+}
+
+/// Load the freeze config a freeze authority instruction acts on, checking its signer
+///
+/// The authority only signs and pays nothing, so a governance program or
+/// multisig can execute the instruction through CPI.
+fn load_freeze_config<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(&'a AccountInfo<'b>, FreezeConfig), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Parse accounts
+    let authority = next_account_info(account_info_iter)?;
+    let freeze_account = next_account_info(account_info_iter)?;
+    
+    check_signer(authority)?;
+    check_owner(freeze_account, program_id)?;
+    check_writable(freeze_account)?;
+    
+    let config = FreezeConfig::unpack(&freeze_account.data.borrow())?;
+    check_derived(freeze_account, find_freeze_address(program_id, &config.scope))?;
+    if config.authority != *authority.key {
+        return Err(PrivacyWrapperError::NotFreezeAuthority.into());
+    }
+    
+    Ok((freeze_account, config))
+}
+
+/// Create a program-owned PDA, funding its rent plus `deposit` from the payer
+///
+/// Anyone can send lamports to a PDA before it exists, and `CreateAccount`
+/// rejects an address that already holds some, so a pre-funded PDA is topped
+/// up, allocated and assigned instead of being created.
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    space: usize,
+    deposit: u64,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let balance = account.lamports();
+    
+    if balance == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent_lamports.saturating_add(deposit),
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+            &[seeds],
+        );
+    }
+    
+    // The payer still pays the whole deposit; only the rent is credited
+    let shortfall = rent_lamports.saturating_sub(balance).saturating_add(deposit);
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Resize a program-owned account, topping up its rent from the payer or refunding the excess
+fn resize_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+) -> ProgramResult {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let balance = account.lamports();
+    
+    if rent_lamports > balance {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, rent_lamports - balance),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    } else {
+        move_lamports(account, payer, balance - rent_lamports)?;
+    }
+    
+    account.realloc(space, true)
+}
+
+/// Move lamports out of a program-owned account
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let from_lamports = from.lamports().checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to.lamports().checked_add(amount).ok_or(ProgramError::InvalidArgument)?;
+    
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    
+    Ok(())
+}
+
+/// Close a program-owned account, sending all its lamports to a recipient
+///
+/// The account is emptied and handed back to the system program, so it can
+/// be created again, even later in the same transaction.
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    move_lamports(account, recipient, account.lamports())?;
+    account.realloc(0, false)?;
+    account.assign(&system_program::id());
     
     Ok(())
 }
@@ -0,0 +1,120 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for recovery PDAs
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+/// Maximum number of guardians per wrapper
+pub const MAX_GUARDIANS: usize = 8;
+
+/// Maximum length of a key share sealed to a guardian in bytes
+pub const MAX_SEALED_SHARE_LEN: usize = 128;
+
+/// Pending request to move a wrapper and its content key to a new owner
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RecoveryRequest {
+    /// Account the wrapper moves to once recovery completes
+    pub new_owner: Pubkey,
+    /// When the first guardian requested recovery
+    pub requested_at: i64,
+    /// Guardians that have approved the request
+    pub approvals: Vec<Pubkey>,
+}
+
+/// Guardian asking for a different new owner than the pending request's
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RecoveryVote {
+    /// Guardian casting the vote
+    pub guardian: Pubkey,
+    /// Account the guardian wants the wrapper moved to
+    pub new_owner: Pubkey,
+}
+
+/// Guardian recovery settings, stored in a PDA derived from the wrapper
+///
+/// The content key is split into shares sealed to each guardian. A threshold
+/// of guardians can move the wrapper to a new owner once the challenge period
+/// passes without the current owner vetoing the request. Each guardian backs
+/// one new owner at a time, and a threshold backing another one replaces the
+/// pending request, so a single guardian can't hold recovery hostage.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RecoveryConfig {
+    /// Whether the config has been written
+    pub is_initialized: bool,
+    /// Wrapper the config protects
+    pub wrapper: Pubkey,
+    /// Guardians holding key shares
+    pub guardians: Vec<Pubkey>,
+    /// Guardian approvals needed to recover
+    pub threshold: u8,
+    /// Seconds the owner has to veto a request
+    pub challenge_period: i64,
+    /// SHA-256 of the content key, to check recombined shares
+    pub key_commitment: [u8; 32],
+    /// Key shares sealed to each guardian, in guardian order
+    pub sealed_shares: Vec<Vec<u8>>,
+    /// Request awaiting the challenge period, if any
+    pub pending: Option<RecoveryRequest>,
+    /// Guardians backing a different new owner than the pending request's
+    pub rival_votes: Vec<RecoveryVote>,
+}
+
+impl RecoveryConfig {
+    /// Size of a recovery account
+    pub const LEN: usize = 1 + 32 + (4 + 32 * MAX_GUARDIANS) + 1 + 8 + 32
+        + (4 + (4 + MAX_SEALED_SHARE_LEN) * MAX_GUARDIANS)
+        + (1 + 32 + 8 + (4 + 32 * MAX_GUARDIANS))
+        + (4 + (32 + 32) * MAX_GUARDIANS);
+    
+    /// Deserialize a recovery config from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let config = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !config.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(config)
+    }
+    
+    /// Check if an account is one of the guardians
+    pub fn is_guardian(&self, account: &Pubkey) -> bool {
+        self.guardians.contains(account)
+    }
+}
+
+/// Derive the recovery address for a wrapper
+pub fn find_recovery_address(program_id: &Pubkey, wrapper: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECOVERY_SEED, wrapper.as_ref()], program_id)
+}
+
+/// Check that guardians, threshold, and shares describe a usable recovery
+pub fn validate_recovery(
+    guardians: &[Pubkey],
+    threshold: u8,
+    challenge_period: i64,
+    sealed_shares: &[Vec<u8>],
+) -> ProgramResult {
+    if guardians.len() > MAX_GUARDIANS || sealed_shares.iter().any(|share| share.len() > MAX_SEALED_SHARE_LEN) {
+        return Err(PrivacyWrapperError::ValueTooLong.into());
+    }
+    
+    let distinct = guardians.iter().enumerate().all(|(i, guardian)| !guardians[..i].contains(guardian));
+    if threshold == 0
+        || threshold as usize > guardians.len()
+        || !distinct
+        || challenge_period < 0
+        || sealed_shares.len() != guardians.len()
+    {
+        return Err(PrivacyWrapperError::InvalidRecoveryConfig.into());
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for agent record PDAs
+pub const AGENT_SEED: &[u8] = b"agent";
+
+/// Maximum length of an agent name in bytes
+pub const MAX_AGENT_NAME_LEN: usize = 64;
+
+/// Maximum length of an agent metadata URI in bytes
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Registered agent identity, stored in a PDA derived from the agent name
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AgentRecord {
+    /// Whether the record has been written
+    pub is_initialized: bool,
+    /// Agent name, e.g. `agent1.glitch.gang`
+    pub name: String,
+    /// Key the agent signs and is granted access with
+    pub agent: Pubkey,
+    /// Account allowed to update the record
+    pub authority: Pubkey,
+    /// URI of off-chain agent metadata
+    pub metadata_uri: String,
+    /// Registration timestamp
+    pub registered_at: u64,
+}
+
+impl AgentRecord {
+    /// Size of an agent record account
+    pub const LEN: usize = 1 + (4 + MAX_AGENT_NAME_LEN) + 32 + 32 + (4 + MAX_METADATA_URI_LEN) + 8;
+    
+    /// Deserialize an agent record from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let record = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !record.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(record)
+    }
+}
+
+/// Derive the agent record address for a name
+///
+/// Names can exceed the 32-byte seed limit, so the seed is the SHA-256 of the name.
+pub fn find_agent_address(program_id: &Pubkey, name: &str) -> (Pubkey, u8) {
+    let name_hash = hashv(&[name.as_bytes()]);
+    Pubkey::find_program_address(&[AGENT_SEED, name_hash.as_ref()], program_id)
+}
+
+/// Check that an agent name is non-empty, fits its slot, and uses
+/// lowercase ASCII letters, digits, `.`, `-`, or `_`
+pub fn validate_agent_name(name: &str) -> ProgramResult {
+    if name.is_empty() || name.len() > MAX_AGENT_NAME_LEN {
+        return Err(PrivacyWrapperError::InvalidAgentName.into());
+    }
+    
+    let valid = name.bytes().all(|b| {
+        b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'_')
+    });
+    
+    if !valid {
+        return Err(PrivacyWrapperError::InvalidAgentName.into());
+    }
+    
+    Ok(())
+}
+
+/// Check that a metadata URI fits its slot
+pub fn validate_metadata_uri(metadata_uri: &str) -> ProgramResult {
+    if metadata_uri.len() > MAX_METADATA_URI_LEN {
+        return Err(PrivacyWrapperError::ValueTooLong.into());
+    }
+    
+    Ok(())
+}
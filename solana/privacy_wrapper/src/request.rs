@@ -0,0 +1,54 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for access request PDAs
+pub const REQUEST_SEED: &[u8] = b"request";
+
+/// Access a viewer asked a wrapper's owner for, stored in a PDA derived from the wrapper and requester
+///
+/// The requester pays the account's rent and gets it back when the owner
+/// approves or denies the request, so asking costs nothing but can't crowd
+/// out anyone else's request.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AccessRequest {
+    /// Whether the request has been written
+    pub is_initialized: bool,
+    /// Wrapper access is asked for
+    pub wrapper: Pubkey,
+    /// Account asking for access
+    pub requester: Pubkey,
+    /// Access level asked for
+    pub level: u8,
+    /// When the request was last made, as a Unix timestamp
+    pub requested_at: i64,
+}
+
+impl AccessRequest {
+    /// Size of an access request account
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 8;
+    
+    /// Offset of the wrapper address, for filtering a wrapper's requests
+    pub const WRAPPER_OFFSET: usize = 1;
+    
+    /// Deserialize an access request from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let request = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !request.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(request)
+    }
+}
+
+/// Derive the access request address for a wrapper and requester
+pub fn find_request_address(program_id: &Pubkey, wrapper: &Pubkey, requester: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REQUEST_SEED, wrapper.as_ref(), requester.as_ref()], program_id)
+}
@@ -0,0 +1,99 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    clock::Clock,
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Seed prefix for reveal escrow PDAs
+pub const REVEAL_SEED: &[u8] = b"reveal";
+
+/// Maximum length of a reveal label in bytes
+pub const MAX_REVEAL_LABEL_LEN: usize = 32;
+
+/// Maximum length of a sealed data key in bytes
+pub const MAX_SEALED_KEY_LEN: usize = 128;
+
+/// When a scheduled reveal unlocks
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealTime {
+    /// Unix timestamp, as reported by the clock sysvar
+    Timestamp(i64),
+    /// Slot number
+    Slot(u64),
+}
+
+impl RevealTime {
+    /// Check if the reveal time has passed
+    pub fn has_passed(&self, clock: &Clock) -> bool {
+        match self {
+            RevealTime::Timestamp(timestamp) => clock.unix_timestamp >= *timestamp,
+            RevealTime::Slot(slot) => clock.slot >= *slot,
+        }
+    }
+}
+
+/// Time-locked data key, stored in a PDA derived from the wrapper and a label
+///
+/// Account data is public, so the data key is only stored sealed with a
+/// reveal key. The program accepts the reveal key only once the reveal time
+/// has passed and it matches the commitment, so nobody can publish it early
+/// through the program.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RevealEscrow {
+    /// Whether the escrow has been written
+    pub is_initialized: bool,
+    /// Wrapper the reveal belongs to
+    pub wrapper: Pubkey,
+    /// Label naming the reveal, e.g. `mission`
+    pub label: String,
+    /// When the reveal unlocks
+    pub reveal_time: RevealTime,
+    /// SHA-256 of the reveal key
+    pub key_commitment: [u8; 32],
+    /// Data key sealed with the reveal key
+    pub sealed_key: Vec<u8>,
+    /// Reveal key, published once the reveal unlocks
+    pub reveal_key: Option<[u8; 32]>,
+}
+
+impl RevealEscrow {
+    /// Size of a reveal escrow account
+    pub const LEN: usize = 1 + 32 + (4 + MAX_REVEAL_LABEL_LEN) + (1 + 8) + 32 + (4 + MAX_SEALED_KEY_LEN) + (1 + 32);
+    
+    /// Deserialize a reveal escrow from account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let escrow = Self::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::from(PrivacyWrapperError::InvalidAccountData))?;
+        
+        if !escrow.is_initialized {
+            return Err(PrivacyWrapperError::AccountNotInitialized.into());
+        }
+        
+        Ok(escrow)
+    }
+    
+    /// Check a reveal key against the commitment
+    pub fn matches(&self, reveal_key: &[u8; 32]) -> bool {
+        hashv(&[reveal_key]).to_bytes() == self.key_commitment
+    }
+}
+
+/// Derive the reveal escrow address for a wrapper and label
+pub fn find_reveal_address(program_id: &Pubkey, wrapper: &Pubkey, label: &str) -> (Pubkey, u8) {
+    let label_hash = hashv(&[label.as_bytes()]);
+    Pubkey::find_program_address(&[REVEAL_SEED, wrapper.as_ref(), label_hash.as_ref()], program_id)
+}
+
+/// Check that a reveal label and sealed key fit their slots
+pub fn validate_reveal(label: &str, sealed_key: &[u8]) -> ProgramResult {
+    if label.is_empty() || label.len() > MAX_REVEAL_LABEL_LEN || sealed_key.len() > MAX_SEALED_KEY_LEN {
+        return Err(PrivacyWrapperError::ValueTooLong.into());
+    }
+    
+    Ok(())
+}
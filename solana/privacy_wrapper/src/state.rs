@@ -1,12 +1,401 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
 use solana_program::{
+    entrypoint::ProgramResult,
+    hash::hashv,
     pubkey::Pubkey,
     program_error::ProgramError,
 };
 use std::collections::HashMap;
+use std::mem::size_of;
+use std::str::FromStr;
 
-/// Privacy wrapper state structure
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+use crate::error::PrivacyWrapperError;
+
+/// Current wrapper account layout version
+pub const WRAPPER_VERSION: u8 = 2;
+
+/// Seed for deriving grantee keys from legacy string agent IDs
+pub const LEGACY_AGENT_SEED: &[u8] = b"privacy-wrapper/legacy-agent";
+
+/// Maximum length of the privacy config hash in bytes
+pub const MAX_CONFIG_HASH_LEN: usize = 96;
+
+/// Number of access entries allocated when a wrapper is created
+pub const DEFAULT_ACCESS_CAPACITY: usize = 16;
+
+/// Space the original Borsh layout reserved for access controls
+pub const BORSH_ACCESS_CONTROLS_LEN: usize = 100;
+
+/// Size of the fixed wrapper header
+pub const HEADER_LEN: usize = size_of::<WrapperHeader>();
+
+/// Size of a single access entry
+pub const ENTRY_LEN: usize = size_of::<AccessEntry>();
+
+/// Map a legacy string agent ID to a grantee key
+///
+/// IDs that are already base58 public keys map to themselves; any other ID
+/// maps to the SHA-256 of `LEGACY_AGENT_SEED || id`.
+pub fn legacy_grantee(agent_id: &str) -> Pubkey {
+    Pubkey::from_str(agent_id)
+        .unwrap_or_else(|_| Pubkey::new_from_array(hashv(&[LEGACY_AGENT_SEED, agent_id.as_bytes()]).to_bytes()))
+}
+
+/// Fixed-layout header at the start of every wrapper account
+///
+/// All fields are byte-aligned so the header can be viewed in place
+/// regardless of where the runtime places the account data.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct WrapperHeader {
+    /// Layout version, zero while the account is uninitialized
+    pub version: u8,
+    /// Length of the privacy config hash
+    config_hash_len: u8,
+    /// Number of access entries in use (little-endian u16)
+    entry_count: [u8; 2],
+    /// Original NFT mint address
+    pub original_nft_mint: Pubkey,
+    /// Owner of the NFT
+    pub owner: Pubkey,
+    /// Last update timestamp (little-endian u64)
+    last_updated: [u8; 8],
+    /// Privacy config hash (points to off-chain privacy settings)
+    config_hash: [u8; MAX_CONFIG_HASH_LEN],
+}
+
+impl WrapperHeader {
+    /// Check if the header has been written
+    pub fn is_initialized(&self) -> bool {
+        self.version != 0
+    }
+    
+    /// Get the number of access entries in use
+    pub fn entry_count(&self) -> usize {
+        u16::from_le_bytes(self.entry_count) as usize
+    }
+    
+    fn set_entry_count(&mut self, count: usize) {
+        self.entry_count = (count as u16).to_le_bytes();
+    }
+    
+    /// Get the last update timestamp
+    pub fn last_updated(&self) -> u64 {
+        u64::from_le_bytes(self.last_updated)
+    }
+    
+    /// Set the last update timestamp
+    pub fn set_last_updated(&mut self, timestamp: u64) {
+        self.last_updated = timestamp.to_le_bytes();
+    }
+    
+    /// Get the privacy config hash
+    pub fn privacy_config_hash(&self) -> Result<&str, ProgramError> {
+        let len = (self.config_hash_len as usize).min(MAX_CONFIG_HASH_LEN);
+        std::str::from_utf8(&self.config_hash[..len])
+            .map_err(|_| PrivacyWrapperError::InvalidAccountData.into())
+    }
+    
+    /// Set the privacy config hash
+    pub fn set_privacy_config_hash(&mut self, hash: &str) -> ProgramResult {
+        if hash.len() > MAX_CONFIG_HASH_LEN {
+            return Err(PrivacyWrapperError::ValueTooLong.into());
+        }
+        
+        self.config_hash = [0; MAX_CONFIG_HASH_LEN];
+        self.config_hash[..hash.len()].copy_from_slice(hash.as_bytes());
+        self.config_hash_len = hash.len() as u8;
+        Ok(())
+    }
+    
+    /// Check if the account is the owner
+    pub fn is_owner(&self, account: &Pubkey) -> bool {
+        self.owner == *account
+    }
+}
+
+/// Fixed-size access control entry in the entries region
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct AccessEntry {
+    /// Account granted access
+    pub grantee: Pubkey,
+    /// Access level granted to the account
+    pub level: u8,
+}
+
+/// Wrapper state from the original Borsh layout, which had no version byte
+///
+/// The original program serialized this struct into an account sized by
+/// `get_account_size` and left the bytes after it zeroed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct BorshWrapper {
+    /// Original NFT mint address
+    pub original_nft_mint: Pubkey,
+    /// Owner of the NFT
+    pub owner: Pubkey,
+    /// Privacy config hash (points to off-chain privacy settings)
+    pub privacy_config_hash: String,
+    /// Mapping of access levels per string agent ID
+    pub access_controls: HashMap<String, u8>,
+    /// Last update timestamp
+    pub last_updated: u64,
+}
+
+impl BorshWrapper {
+    /// Get the size of the account the original program allocated
+    pub fn get_account_size(privacy_config_hash: &str) -> usize {
+        (32 * 2) + (4 + privacy_config_hash.len()) + BORSH_ACCESS_CONTROLS_LEN + 8
+    }
+    
+    /// Decode account data written by the original program, if that's what it holds
+    ///
+    /// Its first byte is part of the mint, so it can't be told apart from a
+    /// version byte. Instead the state must decode, the account must be at
+    /// least the size the original program allocated, and every byte after
+    /// the state must be zero; versioned accounts never pass all three.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut rest = data;
+        let wrapper = Self::deserialize(&mut rest).ok()?;
+        
+        if data.len() < Self::get_account_size(&wrapper.privacy_config_hash) || rest.iter().any(|&byte| byte != 0) {
+            return None;
+        }
+        
+        Some(wrapper)
+    }
+    
+    /// Get the size the account needs on the current layout
+    pub fn migrated_size(&self) -> usize {
+        PrivacyWrapper::get_account_size(self.access_controls.len().max(DEFAULT_ACCESS_CAPACITY))
+    }
+    
+    /// Rewrite account data of at least `migrated_size` bytes on the current layout
+    fn migrate(&self, data: &mut [u8]) -> ProgramResult {
+        if data.len() < self.migrated_size() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        
+        data.fill(0);
+        let mut view = PrivacyWrapperViewMut::initialize(
+            data,
+            &self.original_nft_mint,
+            &self.owner,
+            &self.privacy_config_hash,
+            self.last_updated,
+        )?;
+        
+        for (agent_id, level) in &self.access_controls {
+            view.grant(&legacy_grantee(agent_id), *level)?;
+        }
+        
+        Ok(())
+    }
+}
+
+/// Migrate account data from an earlier layout to the current layout in place
+///
+/// Borsh accounts from the original program are decoded and rewritten, with
+/// legacy string keys mapped by `legacy_grantee`, and must first be grown to
+/// `BorshWrapper::migrated_size`. Accounts already on the current layout are
+/// left untouched; anything else, such as the program's other Borsh accounts,
+/// is rejected.
+pub fn migrate_legacy(data: &mut [u8]) -> ProgramResult {
+    if let Some(wrapper) = BorshWrapper::parse(data) {
+        return wrapper.migrate(data);
+    }
+    
+    match data.first() {
+        Some(&WRAPPER_VERSION) if data.len() >= HEADER_LEN => Ok(()),
+        Some(0) => Err(PrivacyWrapperError::AccountNotInitialized.into()),
+        _ => Err(PrivacyWrapperError::InvalidAccountData.into()),
+    }
+}
+
+/// Check that the header is initialized with the current layout
+fn check_version(header: &WrapperHeader) -> ProgramResult {
+    match header.version {
+        WRAPPER_VERSION => Ok(()),
+        0 => Err(PrivacyWrapperError::AccountNotInitialized.into()),
+        _ => Err(PrivacyWrapperError::OutdatedAccountVersion.into()),
+    }
+}
+
+/// Split account data into the header and the entries region
+fn split(data: &[u8]) -> Result<(&WrapperHeader, &[u8]), ProgramError> {
+    if data.len() < HEADER_LEN {
+        return Err(PrivacyWrapperError::InvalidAccountData.into());
+    }
+    
+    let (header, entries) = data.split_at(HEADER_LEN);
+    let header: &WrapperHeader = bytemuck::from_bytes(header);
+    Ok((header, entries))
+}
+
+/// Split mutable account data into the header and the full-capacity entries region
+fn split_mut(data: &mut [u8]) -> Result<(&mut WrapperHeader, &mut [AccessEntry]), ProgramError> {
+    if data.len() < HEADER_LEN {
+        return Err(PrivacyWrapperError::InvalidAccountData.into());
+    }
+    
+    let (header, entries) = data.split_at_mut(HEADER_LEN);
+    let capacity = entries.len() / ENTRY_LEN;
+    let header: &mut WrapperHeader = bytemuck::from_bytes_mut(header);
+    let entries: &mut [AccessEntry] = bytemuck::cast_slice_mut(&mut entries[..capacity * ENTRY_LEN]);
+    
+    if header.entry_count() > capacity {
+        return Err(PrivacyWrapperError::InvalidAccountData.into());
+    }
+    
+    Ok((header, entries))
+}
+
+/// Read-only zero-copy view over wrapper account data
+pub struct PrivacyWrapperView<'a> {
+    /// Wrapper header
+    header: &'a WrapperHeader,
+    /// Access entries in use
+    entries: &'a [AccessEntry],
+}
+
+impl<'a> PrivacyWrapperView<'a> {
+    /// View initialized wrapper account data in place
+    pub fn load(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let (header, entries) = split(data)?;
+        check_version(header)?;
+        
+        let capacity = entries.len() / ENTRY_LEN;
+        if header.entry_count() > capacity {
+            return Err(PrivacyWrapperError::InvalidAccountData.into());
+        }
+        
+        Ok(Self {
+            header,
+            entries: bytemuck::cast_slice(&entries[..header.entry_count() * ENTRY_LEN]),
+        })
+    }
+    
+    /// Get the wrapper header
+    pub fn header(&self) -> &'a WrapperHeader {
+        self.header
+    }
+    
+    /// Get the access entries in use
+    pub fn entries(&self) -> &'a [AccessEntry] {
+        self.entries
+    }
+    
+    /// Get access level for an account
+    pub fn get_access_level(&self, grantee: &Pubkey) -> u8 {
+        self.entries.iter()
+            .find(|entry| entry.grantee == *grantee)
+            .map_or(0, |entry| entry.level)
+    }
+}
+
+/// Mutable zero-copy view over wrapper account data
+pub struct PrivacyWrapperViewMut<'a> {
+    /// Wrapper header
+    header: &'a mut WrapperHeader,
+    /// Full-capacity entries region; only the first `entry_count` are in use
+    entries: &'a mut [AccessEntry],
+}
+
+impl<'a> PrivacyWrapperViewMut<'a> {
+    /// Write a fresh header into zeroed account data
+    pub fn initialize(
+        data: &'a mut [u8],
+        original_nft_mint: &Pubkey,
+        owner: &Pubkey,
+        privacy_config_hash: &str,
+        last_updated: u64,
+    ) -> Result<Self, ProgramError> {
+        let (header, entries) = split_mut(data)?;
+        
+        if header.is_initialized() {
+            return Err(PrivacyWrapperError::InvalidAccountData.into());
+        }
+        
+        header.version = WRAPPER_VERSION;
+        header.original_nft_mint = *original_nft_mint;
+        header.owner = *owner;
+        header.set_privacy_config_hash(privacy_config_hash)?;
+        header.set_last_updated(last_updated);
+        header.set_entry_count(0);
+        
+        Ok(Self { header, entries })
+    }
+    
+    /// View initialized wrapper account data in place for modification
+    pub fn load_mut(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        let (header, entries) = split_mut(data)?;
+        check_version(header)?;
+        
+        Ok(Self { header, entries })
+    }
+    
+    /// Get the wrapper header
+    pub fn header(&self) -> &WrapperHeader {
+        self.header
+    }
+    
+    /// Get the wrapper header for modification
+    pub fn header_mut(&mut self) -> &mut WrapperHeader {
+        self.header
+    }
+    
+    /// Get the maximum number of access entries the account can hold
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Get the access entries in use
+    pub fn entries(&self) -> &[AccessEntry] {
+        &self.entries[..self.header.entry_count()]
+    }
+    
+    /// Grant or update access for an account
+    ///
+    /// A grantee holds at most one entry, so granting again only changes its
+    /// level.
+    pub fn grant(&mut self, grantee: &Pubkey, level: u8) -> ProgramResult {
+        let count = self.header.entry_count();
+        
+        if let Some(entry) = self.entries[..count].iter_mut().find(|entry| entry.grantee == *grantee) {
+            entry.level = level;
+            return Ok(());
+        }
+        
+        if count >= self.entries.len() {
+            return Err(PrivacyWrapperError::AccessListFull.into());
+        }
+        
+        self.entries[count] = AccessEntry { grantee: *grantee, level };
+        self.header.set_entry_count(count + 1);
+        Ok(())
+    }
+    
+    /// Revoke access for an account, returning whether an entry was removed
+    ///
+    /// The last entry is moved into the freed slot, so entry order is not stable.
+    pub fn revoke(&mut self, grantee: &Pubkey) -> bool {
+        let count = self.header.entry_count();
+        
+        let index = match self.entries[..count].iter().position(|entry| entry.grantee == *grantee) {
+            Some(index) => index,
+            None => return false,
+        };
+        
+        self.entries.swap(index, count - 1);
+        self.entries[count - 1] = AccessEntry::zeroed();
+        self.header.set_entry_count(count - 1);
+        true
+    }
+}
+
+/// Owned privacy wrapper state, decoded from account data for off-chain use
+#[derive(Debug, Clone, PartialEq)]
 pub struct PrivacyWrapper {
     /// Original NFT mint address
     pub original_nft_mint: Pubkey,
@@ -15,25 +404,53 @@ pub struct PrivacyWrapper {
     /// Privacy config hash (points to off-chain privacy settings)
     pub privacy_config_hash: String,
     /// Mapping of access levels per account
-    pub access_controls: HashMap<String, u8>,
+    pub access_controls: HashMap<Pubkey, u8>,
     /// Last update timestamp
     pub last_updated: u64,
 }
 
 impl PrivacyWrapper {
-    /// Get the size of the wrapper account
-    pub fn get_account_size(privacy_config_hash: &str) -> usize {
-        // Calculate size based on struct fields:
-        // - Pubkey size (32 bytes) * 2 (original_nft_mint + owner)
-        // - String length (4 bytes) + privacy_config_hash bytes
-        // - HashMap size (estimated as 4 bytes for len + potential entries)
-        // - Timestamp (8 bytes)
-        let estimated_access_controls_size = 100; // Allow space for some access entries
+    /// Decode wrapper state from account data
+    ///
+    /// Accounts are allocated with spare capacity for access entries, so
+    /// unused entry slots after the in-use region are ignored.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let view = PrivacyWrapperView::load(data)?;
+        let header = view.header();
+        
+        let access_controls = view.entries().iter()
+            .map(|entry| (entry.grantee, entry.level))
+            .collect();
+        
+        Ok(Self {
+            original_nft_mint: header.original_nft_mint,
+            owner: header.owner,
+            privacy_config_hash: header.privacy_config_hash()?.to_string(),
+            access_controls,
+            last_updated: header.last_updated(),
+        })
+    }
+    
+    /// Encode wrapper state into zeroed account data
+    pub fn pack(&self, data: &mut [u8]) -> ProgramResult {
+        let mut view = PrivacyWrapperViewMut::initialize(
+            data,
+            &self.original_nft_mint,
+            &self.owner,
+            &self.privacy_config_hash,
+            self.last_updated,
+        )?;
+        
+        for (grantee, level) in &self.access_controls {
+            view.grant(grantee, *level)?;
+        }
         
-        (32 * 2) + // Pubkeys
-        (4 + privacy_config_hash.len()) + // String length prefix + content
-        estimated_access_controls_size +
-        8 // Timestamp
+        Ok(())
+    }
+    
+    /// Get the size of a wrapper account holding up to `capacity` access entries
+    pub fn get_account_size(capacity: usize) -> usize {
+        HEADER_LEN + capacity * ENTRY_LEN
     }
     
     /// Check if the account is the owner
@@ -42,13 +459,13 @@ impl PrivacyWrapper {
     }
     
     /// Get access level for an account
-    pub fn get_access_level(&self, account: &str) -> u8 {
-        *self.access_controls.get(account).unwrap_or(&0)
+    pub fn get_access_level(&self, grantee: &Pubkey) -> u8 {
+        *self.access_controls.get(grantee).unwrap_or(&0)
     }
     
     /// Check if an account has required access level
-    pub fn has_access(&self, account: &str, required_level: u8) -> bool {
-        let account_level = self.get_access_level(account);
+    pub fn has_access(&self, grantee: &Pubkey, required_level: u8) -> bool {
+        let account_level = self.get_access_level(grantee);
         account_level >= required_level
     }
 }
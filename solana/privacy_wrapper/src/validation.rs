@@ -0,0 +1,97 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+
+use crate::error::PrivacyWrapperError;
+
+/// Check that an account signed the transaction
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    
+    Ok(())
+}
+
+/// Check that an account the handler writes to was passed writable
+///
+/// The runtime would reject the write after the handler ran; checking up
+/// front fails before any CPI or state change is attempted.
+pub fn check_writable(account: &AccountInfo) -> ProgramResult {
+    if !account.is_writable {
+        return Err(PrivacyWrapperError::AccountNotWritable.into());
+    }
+    
+    Ok(())
+}
+
+/// Check that an account is a data account owned by `owner`, not a program
+pub fn check_owner(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner != owner || account.executable {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    Ok(())
+}
+
+/// Check that a signer paying for an account creation or transfer can pay
+pub fn check_payer(account: &AccountInfo) -> ProgramResult {
+    check_signer(account)?;
+    check_writable(account)?;
+    
+    if account.executable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    Ok(())
+}
+
+/// Check that an account is the system program
+pub fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(account.key) || !account.executable {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    Ok(())
+}
+
+/// Check that an account is the rent sysvar
+pub fn check_rent_sysvar(account: &AccountInfo) -> ProgramResult {
+    if !sysvar::rent::check_id(account.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    Ok(())
+}
+
+/// Check that a state account sits at the address derived for what it holds
+///
+/// Every state account other than the wrapper is a PDA under a seed prefix
+/// of its own, so the address is its discriminator: an account of another
+/// type is refused even if its data happens to deserialize as this one.
+pub fn check_derived(account: &AccountInfo, (expected, _bump): (Pubkey, u8)) -> ProgramResult {
+    if *account.key != expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    Ok(())
+}
+
+/// Check that an account about to be created is writable and still empty
+///
+/// Only an unallocated account owned by the system program can be created;
+/// anything else was already initialized by someone.
+pub fn check_uninitialized(account: &AccountInfo) -> ProgramResult {
+    check_writable(account)?;
+    
+    if !account.data_is_empty() || !system_program::check_id(account.owner) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! CPI helpers for checking privacy wrapper access from other programs
+//!
+//! Games, marketplaces and other programs gate their own instructions on a
+//! wrapper's access list by invoking the wrapper program's `CheckAccess`
+//! instruction. Every helper takes the wrapper program ID the caller trusts
+//! and rejects any other program passed in its place, since the program
+//! account comes from the transaction.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use privacy_wrapper::{
+    instruction,
+    marketplace::{find_purchase_address, RevealPurchase},
+    state::PrivacyWrapperView,
+};
+
+pub use privacy_wrapper::{error::PrivacyWrapperError, id, ID};
+
+/// Accounts of a `CheckAccess` call
+pub struct CheckAccess<'a, 'info> {
+    /// The privacy wrapper program
+    pub wrapper_program: &'a AccountInfo<'info>,
+    /// The wrapper account
+    pub wrapper: &'a AccountInfo<'info>,
+    /// The account whose access is checked
+    pub viewer: &'a AccountInfo<'info>,
+    /// The viewer's purchase PDA, to count timed access bought through
+    /// `PayToReveal`; it may be passed before any purchase exists
+    pub purchase: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> CheckAccess<'a, 'info> {
+    /// Build the `CheckAccess` instruction for these accounts
+    pub fn instruction(&self, required_level: u8) -> Instruction {
+        let mut instruction =
+            instruction::check_access(self.wrapper_program.key, self.wrapper.key, self.viewer.key, required_level);
+        if let Some(purchase) = self.purchase {
+            instruction.accounts.push(AccountMeta::new_readonly(*purchase.key, false));
+        }
+        instruction
+    }
+    
+    /// Get the account infos the instruction is invoked with
+    pub fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut infos = vec![self.wrapper.clone(), self.viewer.clone()];
+        infos.extend(self.purchase.cloned());
+        infos.push(self.wrapper_program.clone());
+        infos
+    }
+    
+    /// Verify the program account is the expected wrapper program
+    fn verify_program(&self, wrapper_program_id: &Pubkey) -> ProgramResult {
+        if self.wrapper_program.key != wrapper_program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(())
+    }
+}
+
+/// Fail unless the viewer holds at least `required_level` on the wrapper
+///
+/// A shortfall surfaces as `PrivacyWrapperError::InsufficientAccess`.
+pub fn check_access(wrapper_program_id: &Pubkey, accounts: &CheckAccess, required_level: u8) -> ProgramResult {
+    accounts.verify_program(wrapper_program_id)?;
+    invoke(&accounts.instruction(required_level), &accounts.account_infos())
+}
+
+/// Get the viewer's access level through the wrapper program
+pub fn access_level(wrapper_program_id: &Pubkey, accounts: &CheckAccess) -> Result<u8, ProgramError> {
+    check_access(wrapper_program_id, accounts, 0)?;
+    
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *wrapper_program_id && data.len() == 1 => Ok(data[0]),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Read the viewer's access level straight from the wrapper account
+///
+/// Costs less compute than a CPI but depends on the account layout of the
+/// wrapper program version this crate was built against. Pass the viewer's
+/// purchase PDA to also count an active reveal purchase.
+pub fn read_access_level(
+    wrapper_program_id: &Pubkey,
+    wrapper: &AccountInfo,
+    viewer: &Pubkey,
+    purchase: Option<&AccountInfo>,
+) -> Result<u8, ProgramError> {
+    if wrapper.owner != wrapper_program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    let data = wrapper.data.borrow();
+    let mut level = PrivacyWrapperView::load(&data)?.get_access_level(viewer);
+    
+    if let Some(purchase) = purchase {
+        if *purchase.key != find_purchase_address(wrapper_program_id, wrapper.key, viewer).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        
+        if !purchase.data_is_empty() {
+            if purchase.owner != wrapper_program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let purchase = RevealPurchase::unpack(&purchase.data.borrow())?;
+            level = level.max(purchase.level_at(Clock::get()?.unix_timestamp));
+        }
+    }
+    
+    Ok(level)
+}
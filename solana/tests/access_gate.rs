@@ -0,0 +1,274 @@
+use access_gate::{
+    error::AccessGateError,
+    instruction,
+    state::{find_gate_address, find_viewer_key_address, AccessGate, ViewerKey},
+};
+use privacy_wrapper::{error::PrivacyWrapperError, marketplace::RevealTerms};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+const CONFIG_HASH: &str = "initial-privacy-config-hash";
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "access_gate",
+        access_gate::id(),
+        processor!(access_gate::processor::process_instruction),
+    );
+    program_test.add_program(
+        "privacy_wrapper",
+        privacy_wrapper::id(),
+        processor!(privacy_wrapper::processor::process_instruction),
+    );
+    program_test
+}
+
+fn transaction(context: &ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    
+    Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    )
+}
+
+/// Send instructions signed by the payer plus any extra signers
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let transaction = transaction(context, instructions, signers);
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Write an NFT mint and `holder`'s associated token account holding it
+fn add_nft(context: &mut ProgramTestContext, nft_mint: &Pubkey, holder: &Pubkey) {
+    let mut mint = vec![0; Mint::LEN];
+    Mint::pack(Mint { supply: 1, is_initialized: true, ..Mint::default() }, &mut mint).unwrap();
+    
+    let token_account = get_associated_token_address(holder, nft_mint);
+    let mut holding = vec![0; TokenAccount::LEN];
+    let state = AccountState::Initialized;
+    TokenAccount::pack(TokenAccount { mint: *nft_mint, owner: *holder, amount: 1, state, ..TokenAccount::default() }, &mut holding).unwrap();
+    
+    for (address, data) in [(*nft_mint, mint), (token_account, holding)] {
+        let account = Account { lamports: 1_000_000_000, data, owner: spl_token::id(), ..Account::default() };
+        context.set_account(&address, &AccountSharedData::from(account));
+    }
+}
+
+/// Create a wrapper owned by the context payer, gated at `min_level`
+async fn gated_wrapper(context: &mut ProgramTestContext, min_level: u8) -> Pubkey {
+    let wrapper_program = privacy_wrapper::id();
+    let owner = context.payer.pubkey();
+    let wrapper = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+    add_nft(context, &nft_mint, &owner);
+    
+    let instructions = [
+        privacy_wrapper::instruction::create_wrapper(
+            &wrapper_program,
+            &owner,
+            &nft_mint,
+            &wrapper.pubkey(),
+            CONFIG_HASH,
+        ),
+        instruction::create_gate(&access_gate::id(), &owner, &wrapper.pubkey(), min_level),
+    ];
+    send(context, &instructions, &[&wrapper]).await.unwrap();
+    wrapper.pubkey()
+}
+
+async fn grant(context: &mut ProgramTestContext, wrapper: &Pubkey, viewer: &Pubkey, level: u8) {
+    let ix = privacy_wrapper::instruction::grant_access(
+        &privacy_wrapper::id(),
+        &context.payer.pubkey(),
+        wrapper,
+        viewer,
+        level,
+    );
+    send(context, &[ix], &[]).await.unwrap();
+}
+
+/// Release a viewer's key, returning the key from the return data
+async fn release(context: &mut ProgramTestContext, wrapper: &Pubkey, viewer: &Keypair) -> Result<Vec<u8>, TransactionError> {
+    let ix = instruction::release_key(&access_gate::id(), &viewer.pubkey(), &privacy_wrapper::id(), wrapper);
+    let transaction = transaction(context, &[ix], &[viewer]);
+    
+    let simulation = context.banks_client.simulate_transaction(transaction.clone()).await.unwrap();
+    simulation.result.unwrap()?;
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, access_gate::id());
+    
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    Ok(return_data.data)
+}
+
+async fn load_viewer_key(context: &mut ProgramTestContext, wrapper: &Pubkey, viewer: &Pubkey) -> Option<ViewerKey> {
+    let (gate, _) = find_gate_address(&access_gate::id(), wrapper);
+    let (address, _) = find_viewer_key_address(&access_gate::id(), &gate, viewer);
+    let account = context.banks_client.get_account(address).await.unwrap()?;
+    Some(ViewerKey::unpack(&account.data).unwrap())
+}
+
+fn custom_error(err: TransactionError, code: u32) {
+    assert_eq!(err, TransactionError::InstructionError(0, InstructionError::Custom(code)));
+}
+
+#[tokio::test]
+async fn test_key_released_only_at_gate_level() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = gated_wrapper(&mut context, 2).await;
+    let owner = context.payer.pubkey();
+    let viewer = Keypair::new();
+    
+    let (gate_address, _) = find_gate_address(&access_gate::id(), &wrapper);
+    let account = context.banks_client.get_account(gate_address).await.unwrap().unwrap();
+    let gate = AccessGate::unpack(&account.data).unwrap();
+    assert_eq!(gate.wrapper, wrapper);
+    assert_eq!(gate.authority, owner);
+    assert_eq!(gate.min_level, 2);
+    
+    let wrapped_key = vec![0x42; 48];
+    let ix = instruction::store_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey(), wrapped_key.clone());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // Below the gate's level the wrapper program's check fails
+    grant(&mut context, &wrapper, &viewer.pubkey(), 1).await;
+    let err = release(&mut context, &wrapper, &viewer).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientAccess as u32);
+    assert_eq!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.unwrap().released_at, None);
+    
+    grant(&mut context, &wrapper, &viewer.pubkey(), 2).await;
+    assert_eq!(release(&mut context, &wrapper, &viewer).await.unwrap(), wrapped_key);
+    assert!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.unwrap().released_at.is_some());
+    
+    // Revoking access on the wrapper stops further releases
+    let ix = privacy_wrapper::instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer.pubkey());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let err = release(&mut context, &wrapper, &viewer).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientAccess as u32);
+    
+    // The wrapped key stays readable in account data until it's removed
+    assert_eq!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.unwrap().wrapped_key, wrapped_key);
+    let ix = instruction::remove_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.is_none());
+    
+    // The account is closed outright, so a key can be removed and stored again in one transaction
+    let instructions = [
+        instruction::store_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey(), vec![0x43; 48]),
+        instruction::remove_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey()),
+        instruction::store_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey(), vec![0x44; 48]),
+    ];
+    send(&mut context, &instructions, &[]).await.unwrap();
+    assert_eq!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.unwrap().wrapped_key, vec![0x44; 48]);
+}
+
+#[tokio::test]
+async fn test_only_owner_gates_and_only_authority_stores_keys() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = gated_wrapper(&mut context, 1).await;
+    let stranger = Keypair::new();
+    let viewer = Pubkey::new_unique();
+    
+    let ix = instruction::store_key(&access_gate::id(), &stranger.pubkey(), &wrapper, &viewer, vec![1; 32]);
+    let err = send(&mut context, &[ix], &[&stranger]).await.unwrap_err();
+    custom_error(err.unwrap(), AccessGateError::NotGateAuthority as u32);
+    
+    let ix = instruction::store_key(&access_gate::id(), &context.payer.pubkey(), &wrapper, &viewer, vec![]);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err.unwrap(), AccessGateError::InvalidWrappedKey as u32);
+    
+    // A wrapper owned by someone else can't be gated by the payer
+    let other_owner = Keypair::new();
+    let other_wrapper = Keypair::new();
+    let other_mint = Pubkey::new_unique();
+    add_nft(&mut context, &other_mint, &other_owner.pubkey());
+    let ix = privacy_wrapper::instruction::create_wrapper(
+        &privacy_wrapper::id(),
+        &other_owner.pubkey(),
+        &other_mint,
+        &other_wrapper.pubkey(),
+        CONFIG_HASH,
+    );
+    let fund = solana_program::system_instruction::transfer(&context.payer.pubkey(), &other_owner.pubkey(), 1_000_000_000);
+    send(&mut context, &[fund, ix], &[&other_owner, &other_wrapper]).await.unwrap();
+    
+    let ix = instruction::create_gate(&access_gate::id(), &context.payer.pubkey(), &other_wrapper.pubkey(), 1);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err.unwrap(), AccessGateError::NotWrapperOwner as u32);
+}
+
+#[tokio::test]
+async fn test_removed_key_closes_account() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = gated_wrapper(&mut context, 1).await;
+    let owner = context.payer.pubkey();
+    let viewer = Keypair::new();
+    grant(&mut context, &wrapper, &viewer.pubkey(), 1).await;
+    
+    let ix = instruction::store_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey(), vec![7; 32]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // Storing again replaces the key and clears the release record
+    assert_eq!(release(&mut context, &wrapper, &viewer).await.unwrap(), vec![7; 32]);
+    let ix = instruction::store_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey(), vec![8; 16]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let viewer_key = load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.unwrap();
+    assert_eq!(viewer_key.wrapped_key, vec![8; 16]);
+    assert_eq!(viewer_key.released_at, None);
+    
+    let ix = instruction::remove_key(&access_gate::id(), &owner, &wrapper, &viewer.pubkey());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert!(load_viewer_key(&mut context, &wrapper, &viewer.pubkey()).await.is_none());
+}
+
+#[tokio::test]
+async fn test_key_released_to_reveal_buyers() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = gated_wrapper(&mut context, 2).await;
+    let owner = context.payer.pubkey();
+    let buyer = Keypair::new();
+    
+    let wrapped_key = vec![0x17; 48];
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 2,
+        access_duration: 3_600,
+        settlement_window: 600,
+        dispute_window: 600,
+        royalty_shares: Vec::new(),
+    };
+    let instructions = [
+        instruction::store_key(&access_gate::id(), &owner, &wrapper, &buyer.pubkey(), wrapped_key.clone()),
+        privacy_wrapper::instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone()),
+        system_instruction::transfer(&owner, &buyer.pubkey(), 100_000_000),
+    ];
+    send(&mut context, &instructions, &[]).await.unwrap();
+    
+    // Without a grant or a purchase there is nothing to release
+    let err = release(&mut context, &wrapper, &buyer).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientAccess as u32);
+    
+    // A purchase at the gate's level releases the key while it lasts
+    let ix = privacy_wrapper::instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms);
+    send(&mut context, &[ix], &[&buyer]).await.unwrap();
+    assert_eq!(release(&mut context, &wrapper, &buyer).await.unwrap(), wrapped_key);
+}
@@ -0,0 +1,130 @@
+//! Compute-unit budgets for the wrapper instruction handlers
+//!
+//! These tests load the compiled BPF program so the compute meter reflects
+//! real on-chain cost. Run them with:
+//!
+//! ```bash
+//! cargo test-sbf --features test-bpf --test compute_units
+//! ```
+
+use privacy_wrapper::{instruction, state::DEFAULT_ACCESS_CAPACITY};
+use solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+/// Budget for CreateWrapper, including the system program CPI and the NFT holding checks
+const CREATE_WRAPPER_BUDGET: u64 = 20_000;
+
+/// Budget for UpdatePrivacy, including the freeze and membership PDA derivations
+const UPDATE_PRIVACY_BUDGET: u64 = 10_000;
+
+/// Budget for GrantAccess against a full access list, including the freeze checks
+const GRANT_ACCESS_BUDGET: u64 = 10_000;
+
+/// Budget for RevokeAccess against a full access list
+const REVOKE_ACCESS_BUDGET: u64 = 5_000;
+
+const CONFIG_HASH: &str = "initial-privacy-config-hash";
+
+fn program_test() -> ProgramTest {
+    // No native processor, so the BPF build of the program is loaded
+    let mut program_test = ProgramTest::new("privacy_wrapper", privacy_wrapper::id(), None);
+    program_test.prefer_bpf(true);
+    program_test
+}
+
+/// Simulate an instruction to read its compute units, then execute it
+async fn measure(
+    context: &mut ProgramTestContext,
+    ix: Instruction,
+    signers: &[&Keypair],
+) -> u64 {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    
+    let simulation = context.banks_client
+        .simulate_transaction(transaction.clone())
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    let units = simulation.simulation_details.unwrap().units_consumed;
+    
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    units
+}
+
+/// Write an NFT mint and `holder`'s associated token account holding it
+fn add_nft(context: &mut ProgramTestContext, nft_mint: &Pubkey, holder: &Pubkey) {
+    let mut mint = vec![0; Mint::LEN];
+    Mint::pack(Mint { supply: 1, is_initialized: true, ..Mint::default() }, &mut mint).unwrap();
+    
+    let token_account = get_associated_token_address(holder, nft_mint);
+    let mut holding = vec![0; TokenAccount::LEN];
+    let state = AccountState::Initialized;
+    TokenAccount::pack(TokenAccount { mint: *nft_mint, owner: *holder, amount: 1, state, ..TokenAccount::default() }, &mut holding).unwrap();
+    
+    for (address, data) in [(*nft_mint, mint), (token_account, holding)] {
+        let account = Account { lamports: 1_000_000_000, data, owner: spl_token::id(), ..Account::default() };
+        context.set_account(&address, &AccountSharedData::from(account));
+    }
+}
+
+/// Assert that measured units stay within a budget
+fn assert_within_budget(name: &str, units: u64, budget: u64) {
+    println!("{}: {} CU (budget {})", name, units, budget);
+    assert!(units <= budget, "{} used {} CU, over its budget of {}", name, units, budget);
+}
+
+#[tokio::test]
+async fn test_handler_compute_budgets() {
+    let mut context = program_test().start_with_context().await;
+    let program_id = privacy_wrapper::id();
+    let owner = context.payer.pubkey();
+    let wrapper = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+    add_nft(&mut context, &nft_mint, &owner);
+    
+    let ix = instruction::create_wrapper(&program_id, &owner, &nft_mint, &wrapper.pubkey(), CONFIG_HASH);
+    let units = measure(&mut context, ix, &[&wrapper]).await;
+    assert_within_budget("CreateWrapper", units, CREATE_WRAPPER_BUDGET);
+    
+    let ix = instruction::update_privacy(&program_id, &owner, &wrapper.pubkey(), "updated-privacy-config-hash");
+    let units = measure(&mut context, ix, &[]).await;
+    assert_within_budget("UpdatePrivacy", units, UPDATE_PRIVACY_BUDGET);
+    
+    // Worst case: every slot in use, so lookups scan the whole entries region
+    let grantees: Vec<Pubkey> = (0..DEFAULT_ACCESS_CAPACITY).map(|_| Pubkey::new_unique()).collect();
+    let mut grant_units = 0;
+    for grantee in &grantees {
+        let ix = instruction::grant_access(&program_id, &owner, &wrapper.pubkey(), grantee, 1);
+        grant_units = grant_units.max(measure(&mut context, ix, &[]).await);
+    }
+    assert_within_budget("GrantAccess (append)", grant_units, GRANT_ACCESS_BUDGET);
+    
+    let last = grantees[DEFAULT_ACCESS_CAPACITY - 1];
+    let ix = instruction::grant_access(&program_id, &owner, &wrapper.pubkey(), &last, 4);
+    let units = measure(&mut context, ix, &[]).await;
+    assert_within_budget("GrantAccess (update last)", units, GRANT_ACCESS_BUDGET);
+    
+    let ix = instruction::revoke_access(&program_id, &owner, &wrapper.pubkey(), &last);
+    let units = measure(&mut context, ix, &[]).await;
+    assert_within_budget("RevokeAccess (last)", units, REVOKE_ACCESS_BUDGET);
+    
+    let ix = instruction::revoke_access(&program_id, &owner, &wrapper.pubkey(), &grantees[0]);
+    let units = measure(&mut context, ix, &[]).await;
+    assert_within_budget("RevokeAccess (first)", units, REVOKE_ACCESS_BUDGET);
+}
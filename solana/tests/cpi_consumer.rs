@@ -0,0 +1,245 @@
+//! Example consumer program gating its own instruction on wrapper access
+//!
+//! The arena program admits a player only if they hold the required level
+//! on a wrapper, then reports the player's level as its return data.
+
+use privacy_wrapper::{
+    error::PrivacyWrapperError,
+    instruction,
+    marketplace::{find_purchase_address, RevealTerms},
+    processor::process_instruction,
+};
+use privacy_wrapper_cpi::{access_level, check_access, read_access_level, CheckAccess};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+const CONFIG_HASH: &str = "initial-privacy-config-hash";
+
+/// Arena program ID
+fn arena_id() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+/// Admit a player holding the required level on a wrapper
+///
+/// Accounts expected:
+/// 0. `[signer]` The player
+/// 1. `[]` The privacy wrapper program
+/// 2. `[]` The wrapper account
+/// 3. `[]` The player's reveal purchase PDA
+fn process_arena(_program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    let player = next_account_info(account_info_iter)?;
+    let wrapper_program = next_account_info(account_info_iter)?;
+    let wrapper = next_account_info(account_info_iter)?;
+    let purchase = next_account_info(account_info_iter)?;
+    
+    if !player.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let required_level = *instruction_data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    
+    let accounts = CheckAccess {
+        wrapper_program,
+        wrapper,
+        viewer: player,
+        purchase: Some(purchase),
+    };
+    check_access(&privacy_wrapper::id(), &accounts, required_level)?;
+    
+    // The CPI and the direct read agree on the level
+    let level = access_level(&privacy_wrapper::id(), &accounts)?;
+    if level != read_access_level(&privacy_wrapper::id(), wrapper, player.key, Some(purchase))? {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    msg!("Player admitted to the arena");
+    set_return_data(&[level]);
+    
+    Ok(())
+}
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "privacy_wrapper",
+        privacy_wrapper::id(),
+        processor!(process_instruction),
+    );
+    program_test.add_program("arena", arena_id(), processor!(process_arena));
+    program_test
+}
+
+fn enter_arena(player: &Pubkey, wrapper_program: &Pubkey, wrapper: &Pubkey, required_level: u8) -> Instruction {
+    let (purchase, _) = find_purchase_address(&privacy_wrapper::id(), wrapper, player);
+    
+    Instruction {
+        program_id: arena_id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(*wrapper_program, false),
+            AccountMeta::new_readonly(*wrapper, false),
+            AccountMeta::new_readonly(purchase, false),
+        ],
+        data: vec![required_level],
+    }
+}
+
+fn transaction(context: &ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    
+    Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    )
+}
+
+/// Write an NFT mint and `holder`'s associated token account holding it
+fn add_nft(context: &mut ProgramTestContext, nft_mint: &Pubkey, holder: &Pubkey) {
+    let mut mint = vec![0; Mint::LEN];
+    Mint::pack(Mint { supply: 1, is_initialized: true, ..Mint::default() }, &mut mint).unwrap();
+    
+    let token_account = get_associated_token_address(holder, nft_mint);
+    let mut holding = vec![0; TokenAccount::LEN];
+    let state = AccountState::Initialized;
+    TokenAccount::pack(TokenAccount { mint: *nft_mint, owner: *holder, amount: 1, state, ..TokenAccount::default() }, &mut holding).unwrap();
+    
+    for (address, data) in [(*nft_mint, mint), (token_account, holding)] {
+        let account = Account { lamports: 1_000_000_000, data, owner: spl_token::id(), ..Account::default() };
+        context.set_account(&address, &AccountSharedData::from(account));
+    }
+}
+
+/// Create a wrapper granting the player a level
+async fn setup(context: &mut ProgramTestContext, player: &Pubkey, level: u8) -> Pubkey {
+    let program_id = privacy_wrapper::id();
+    let owner = context.payer.pubkey();
+    let wrapper = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+    add_nft(context, &nft_mint, &owner);
+    
+    let instructions = [
+        instruction::create_wrapper(&program_id, &owner, &nft_mint, &wrapper.pubkey(), CONFIG_HASH),
+        instruction::grant_access(&program_id, &owner, &wrapper.pubkey(), player, level),
+    ];
+    let transaction = transaction(context, &instructions, &[&wrapper]);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    wrapper.pubkey()
+}
+
+fn instruction_error(err: BanksClientError) -> InstructionError {
+    match err.unwrap() {
+        TransactionError::InstructionError(0, err) => err,
+        err => panic!("Unexpected transaction error {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_consumer_admits_players_with_enough_access() {
+    let mut context = program_test().start_with_context().await;
+    let player = Keypair::new();
+    let wrapper = setup(&mut context, &player.pubkey(), 2).await;
+    
+    let ix = enter_arena(&player.pubkey(), &privacy_wrapper::id(), &wrapper, 2);
+    let simulation = context.banks_client
+        .simulate_transaction(transaction(&context, &[ix], &[&player]))
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    
+    // The arena's return data carries the level the wrapper reported
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, arena_id());
+    assert_eq!(return_data.data, vec![2]);
+}
+
+#[tokio::test]
+async fn test_consumer_rejects_insufficient_access() {
+    let mut context = program_test().start_with_context().await;
+    let player = Keypair::new();
+    let wrapper = setup(&mut context, &player.pubkey(), 2).await;
+    
+    let ix = enter_arena(&player.pubkey(), &privacy_wrapper::id(), &wrapper, 3);
+    let err = context.banks_client
+        .process_transaction(transaction(&context, &[ix], &[&player]))
+        .await
+        .unwrap_err();
+    assert_eq!(instruction_error(err), InstructionError::Custom(PrivacyWrapperError::InsufficientAccess as u32));
+    
+    // Accounts missing from the access list hold level 0
+    let stranger = Keypair::new();
+    let ix = enter_arena(&stranger.pubkey(), &privacy_wrapper::id(), &wrapper, 1);
+    let err = context.banks_client
+        .process_transaction(transaction(&context, &[ix], &[&stranger]))
+        .await
+        .unwrap_err();
+    assert_eq!(instruction_error(err), InstructionError::Custom(PrivacyWrapperError::InsufficientAccess as u32));
+}
+
+#[tokio::test]
+async fn test_consumer_admits_players_who_bought_access() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let player = Keypair::new();
+    let wrapper = setup(&mut context, &Pubkey::new_unique(), 1).await;
+    
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 2,
+        access_duration: 3_600,
+        settlement_window: 600,
+        dispute_window: 600,
+        royalty_shares: Vec::new(),
+    };
+    let instructions = [
+        instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone()),
+        system_instruction::transfer(&owner, &player.pubkey(), 100_000_000),
+        instruction::pay_to_reveal(&privacy_wrapper::id(), &player.pubkey(), &wrapper, terms),
+    ];
+    let transaction = transaction(&context, &instructions, &[&player]);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    
+    // The purchase admits the player without a grant on the wrapper
+    let ix = enter_arena(&player.pubkey(), &privacy_wrapper::id(), &wrapper, 2);
+    let simulation = context.banks_client
+        .simulate_transaction(transaction(&context, &[ix], &[&player]))
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    assert_eq!(simulation.simulation_details.unwrap().return_data.unwrap().data, vec![2]);
+}
+
+#[tokio::test]
+async fn test_consumer_rejects_substituted_program() {
+    let mut context = program_test().start_with_context().await;
+    let player = Keypair::new();
+    let wrapper = setup(&mut context, &player.pubkey(), 2).await;
+    
+    // A program that always succeeds can't stand in for the wrapper program
+    let ix = enter_arena(&player.pubkey(), &solana_program::system_program::id(), &wrapper, 1);
+    let err = context.banks_client
+        .process_transaction(transaction(&context, &[ix], &[&player]))
+        .await
+        .unwrap_err();
+    assert_eq!(instruction_error(err), InstructionError::IncorrectProgramId);
+}
@@ -1 +1,2180 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use privacy_wrapper::{
+    collection::{
+        find_collection_address, find_membership_address, find_metadata_address, CollectionMembership,
+        CollectionWrapper, TOKEN_METADATA_PROGRAM_ID,
+    },
+    error::PrivacyWrapperError,
+    freeze::{find_freeze_address, FreezeConfig, FreezeScope},
+    instruction::{self, WrapperInstruction},
+    lock::{find_lock_address, PrivacyLock},
+    marketplace::{find_purchase_address, PurchaseStatus, RevealPurchase, RevealTerms, RoyaltyShare},
+    processor::process_instruction,
+    recovery::{find_recovery_address, RecoveryConfig, RecoveryVote},
+    registry::{find_agent_address, AgentRecord},
+    request::{find_request_address, AccessRequest},
+    reveal::{find_reveal_address, RevealEscrow, RevealTime},
+    state::{
+        legacy_grantee, BorshWrapper, PrivacyWrapper, PrivacyWrapperView, DEFAULT_ACCESS_CAPACITY, HEADER_LEN,
+        MAX_CONFIG_HASH_LEN,
+    },
+};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, InstructionError},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_program::program_pack::Pack;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Clock,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
 
+const CONFIG_HASH: &str = "initial-privacy-config-hash";
+
+fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "privacy_wrapper",
+        privacy_wrapper::id(),
+        processor!(process_instruction),
+    )
+}
+
+/// Send instructions signed by the payer plus any extra signers
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[solana_program::instruction::Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Write an NFT mint and `holder`'s associated token account holding `amount` of it
+fn add_nft(context: &mut ProgramTestContext, nft_mint: &Pubkey, holder: &Pubkey, amount: u64) -> Pubkey {
+    let mut mint = vec![0; Mint::LEN];
+    Mint::pack(Mint { supply: 1, is_initialized: true, ..Mint::default() }, &mut mint).unwrap();
+    
+    let token_account = get_associated_token_address(holder, nft_mint);
+    let mut holding = vec![0; TokenAccount::LEN];
+    let state = AccountState::Initialized;
+    TokenAccount::pack(TokenAccount { mint: *nft_mint, owner: *holder, amount, state, ..TokenAccount::default() }, &mut holding).unwrap();
+    
+    for (address, data) in [(*nft_mint, mint), (token_account, holding)] {
+        let account = Account { lamports: 1_000_000_000, data, owner: spl_token::id(), ..Account::default() };
+        context.set_account(&address, &AccountSharedData::from(account));
+    }
+    token_account
+}
+
+/// Create a wrapper owned by the context payer, who holds the NFT
+async fn create_wrapper(context: &mut ProgramTestContext, nft_mint: &Pubkey) -> Keypair {
+    let payer = context.payer.pubkey();
+    add_nft(context, nft_mint, &payer, 1);
+    let wrapper = Keypair::new();
+    let ix = instruction::create_wrapper(
+        &privacy_wrapper::id(),
+        &context.payer.pubkey(),
+        nft_mint,
+        &wrapper.pubkey(),
+        CONFIG_HASH,
+    );
+    
+    send(context, &[ix], &[&wrapper]).await.unwrap();
+    wrapper
+}
+
+async fn load_wrapper(context: &mut ProgramTestContext, wrapper: &Pubkey) -> PrivacyWrapper {
+    let account = context.banks_client.get_account(*wrapper).await.unwrap().unwrap();
+    PrivacyWrapper::unpack(&account.data).unwrap()
+}
+
+fn custom_error(err: BanksClientError, expected: PrivacyWrapperError) {
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_create_wrapper_initializes_state() {
+    let mut context = program_test().start_with_context().await;
+    let nft_mint = Pubkey::new_unique();
+    
+    let wrapper = create_wrapper(&mut context, &nft_mint).await;
+    
+    let account = context.banks_client.get_account(wrapper.pubkey()).await.unwrap().unwrap();
+    assert_eq!(account.owner, privacy_wrapper::id());
+    assert_eq!(account.data.len(), PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY));
+    
+    let state = PrivacyWrapper::unpack(&account.data).unwrap();
+    assert_eq!(state.original_nft_mint, nft_mint);
+    assert_eq!(state.owner, context.payer.pubkey());
+    assert_eq!(state.privacy_config_hash, CONFIG_HASH);
+    assert!(state.access_controls.is_empty());
+}
+
+#[tokio::test]
+async fn test_update_privacy() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    
+    // The hash slot is fixed-size, so a longer hash fits without reallocating
+    let new_hash = "updated-privacy-config-hash-with-a-longer-value";
+    let ix = instruction::update_privacy(
+        &privacy_wrapper::id(),
+        &context.payer.pubkey(),
+        &wrapper.pubkey(),
+        new_hash,
+    );
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &wrapper.pubkey()).await;
+    assert_eq!(state.privacy_config_hash, new_hash);
+}
+
+#[tokio::test]
+async fn test_grant_and_revoke_access() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let owner = context.payer.pubkey();
+    let viewer = Pubkey::new_unique();
+    
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &viewer, 3);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &wrapper.pubkey()).await;
+    assert_eq!(state.get_access_level(&viewer), 3);
+    assert!(state.has_access(&viewer, 2));
+    assert!(!state.has_access(&viewer, 4));
+    
+    let ix = instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &viewer);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &wrapper.pubkey()).await;
+    assert_eq!(state.get_access_level(&viewer), 0);
+}
+
+#[tokio::test]
+async fn test_owner_must_sign() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    
+    // Strip the owner's signer flag
+    let mut ix = instruction::update_privacy(
+        &privacy_wrapper::id(),
+        &Pubkey::new_unique(),
+        &wrapper.pubkey(),
+        CONFIG_HASH,
+    );
+    ix.accounts[0].is_signer = false;
+    
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+}
+
+#[tokio::test]
+async fn test_non_owner_cannot_modify_wrapper() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let intruder = Keypair::new();
+    
+    let instructions = [
+        instruction::update_privacy(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper.pubkey(), CONFIG_HASH),
+        instruction::grant_access(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper.pubkey(), &intruder.pubkey(), 255),
+        instruction::revoke_access(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper.pubkey(), &intruder.pubkey()),
+        instruction::migrate_wrapper(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper.pubkey()),
+    ];
+    
+    for ix in instructions {
+        let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::NotNFTOwner);
+    }
+}
+
+#[tokio::test]
+async fn test_wrapper_owned_by_other_program_is_rejected() {
+    let mut program_test = program_test();
+    let fake_wrapper = Pubkey::new_unique();
+    program_test.add_account(fake_wrapper, Account {
+        lamports: 1_000_000_000,
+        data: vec![0; PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY)],
+        owner: solana_program::system_program::id(),
+        ..Account::default()
+    });
+    let mut context = program_test.start_with_context().await;
+    
+    let ix = instruction::grant_access(
+        &privacy_wrapper::id(),
+        &context.payer.pubkey(),
+        &fake_wrapper,
+        &Pubkey::new_unique(),
+        1,
+    );
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId),
+    );
+}
+
+#[tokio::test]
+async fn test_access_list_full_and_oversized_hash_fail() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let owner = context.payer.pubkey();
+    let grantees: Vec<Pubkey> = (0..DEFAULT_ACCESS_CAPACITY).map(|_| Pubkey::new_unique()).collect();
+    
+    // Fill every allocated entry slot
+    for grantee in &grantees {
+        let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), grantee, 1);
+        send(&mut context, &[ix], &[]).await.unwrap();
+    }
+    
+    // Updating an existing grant still works when the list is full
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &grantees[0], 4);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let one_too_many = Pubkey::new_unique();
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &one_too_many, 1);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AccessListFull);
+    
+    // Revoking frees a slot for a new grant
+    let ix = instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &grantees[3]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &one_too_many, 1);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // Hashes longer than their slot are rejected
+    let long_hash = "x".repeat(MAX_CONFIG_HASH_LEN + 1);
+    let ix = instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper.pubkey(), &long_hash);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::ValueTooLong);
+    
+    let state = load_wrapper(&mut context, &wrapper.pubkey()).await;
+    assert_eq!(state.access_controls.len(), DEFAULT_ACCESS_CAPACITY);
+    assert_eq!(state.get_access_level(&grantees[0]), 4);
+    assert_eq!(state.get_access_level(&grantees[3]), 0);
+    assert_eq!(state.get_access_level(&one_too_many), 1);
+    assert_eq!(state.privacy_config_hash, CONFIG_HASH);
+}
+
+#[tokio::test]
+async fn test_resize_wrapper_changes_capacity() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let owner = context.payer.pubkey();
+    let capacity = DEFAULT_ACCESS_CAPACITY + 8;
+    
+    let ix = instruction::resize_wrapper(&privacy_wrapper::id(), &owner, &wrapper, capacity as u16);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let account = context.banks_client.get_account(wrapper).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), PrivacyWrapper::get_account_size(capacity));
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+    
+    // The extra slots take grants beyond the default capacity
+    let grantees: Vec<Pubkey> = (0..capacity).map(|_| Pubkey::new_unique()).collect();
+    for grantee in &grantees {
+        let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, grantee, 1);
+        send(&mut context, &[ix], &[]).await.unwrap();
+    }
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AccessListFull);
+    
+    // Shrinking can't drop grants
+    let ix = instruction::resize_wrapper(&privacy_wrapper::id(), &owner, &wrapper, (capacity - 1) as u16);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AccessListFull);
+    
+    // Once they're gone it refunds the rent to the owner
+    for grantee in &grantees[DEFAULT_ACCESS_CAPACITY + 1..] {
+        let ix = instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, grantee);
+        send(&mut context, &[ix], &[]).await.unwrap();
+    }
+    let ix = instruction::resize_wrapper(&privacy_wrapper::id(), &owner, &wrapper, (DEFAULT_ACCESS_CAPACITY + 1) as u16);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let account = context.banks_client.get_account(wrapper).await.unwrap().unwrap();
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+    let state = PrivacyWrapper::unpack(&account.data).unwrap();
+    assert_eq!(state.access_controls.len(), DEFAULT_ACCESS_CAPACITY + 1);
+    
+    // Only the owner resizes
+    let intruder = Keypair::new();
+    let ix = instruction::resize_wrapper(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper, capacity as u16);
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+}
+
+#[tokio::test]
+async fn test_migrate_rejects_other_program_accounts() {
+    let mut program_test = program_test();
+    // A 31-byte name puts the agent key where a wrapper header keeps its owner
+    let agent = Keypair::new();
+    let name = "a".repeat(31);
+    let (record_address, _) = find_agent_address(&privacy_wrapper::id(), &name);
+    let record = AgentRecord {
+        is_initialized: true,
+        name,
+        agent: agent.pubkey(),
+        authority: agent.pubkey(),
+        metadata_uri: String::new(),
+        registered_at: 1_700_000_000,
+    };
+    let mut data = record.try_to_vec().unwrap();
+    data.resize(AgentRecord::LEN, 0);
+    assert_eq!(&data[36..68], agent.pubkey().as_ref());
+    program_test.add_account(record_address, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data: data.clone(),
+        owner: privacy_wrapper::id(),
+        ..Account::default()
+    });
+    let mut context = program_test.start_with_context().await;
+    
+    let ix = instruction::migrate_wrapper(&privacy_wrapper::id(), &agent.pubkey(), &record_address);
+    let err = send(&mut context, &[ix], &[&agent]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidAccountData);
+    
+    let account = context.banks_client.get_account(record_address).await.unwrap().unwrap();
+    assert_eq!(account.data, data);
+}
+
+/// Write account data the way the original program did, Borsh-encoded into its fixed allocation
+fn borsh_account_data(mint: &Pubkey, owner: &Pubkey, keys: &[(&str, u8)]) -> Vec<u8> {
+    let wrapper = BorshWrapper {
+        original_nft_mint: *mint,
+        owner: *owner,
+        privacy_config_hash: CONFIG_HASH.to_string(),
+        access_controls: keys.iter().map(|(key, level)| (key.to_string(), *level)).collect(),
+        last_updated: 1_700_000_000,
+    };
+    
+    let mut data = wrapper.try_to_vec().unwrap();
+    data.resize(BorshWrapper::get_account_size(CONFIG_HASH), 0);
+    data
+}
+
+#[tokio::test]
+async fn test_migrate_borsh_wrapper() {
+    let mut program_test = program_test();
+    let borsh_wrapper = Pubkey::new_unique();
+    // The mint's first byte reads as an unknown version until the account is migrated
+    let mint = Pubkey::new_from_array([7; 32]);
+    let pubkey_grantee = Pubkey::new_unique().to_string();
+    let owner = Keypair::new();
+    let data = borsh_account_data(&mint, &owner.pubkey(), &[("agent1.glitch.gang", 2), (pubkey_grantee.as_str(), 3)]);
+    program_test.add_account(borsh_wrapper, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: privacy_wrapper::id(),
+        ..Account::default()
+    });
+    program_test.add_account(owner.pubkey(), Account {
+        lamports: 1_000_000_000,
+        ..Account::default()
+    });
+    let mut context = program_test.start_with_context().await;
+    
+    let viewer = Pubkey::new_unique();
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner.pubkey(), &borsh_wrapper, &viewer, 1);
+    let err = send(&mut context, &[ix], &[&owner]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::OutdatedAccountVersion);
+    
+    // Only the owner recorded in the Borsh state can migrate it
+    let intruder = Keypair::new();
+    let ix = instruction::migrate_wrapper(&privacy_wrapper::id(), &intruder.pubkey(), &borsh_wrapper);
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+    
+    // Migration grows the account to the default capacity, with the owner paying the rent
+    let ix = instruction::migrate_wrapper(&privacy_wrapper::id(), &owner.pubkey(), &borsh_wrapper);
+    send(&mut context, &[ix], &[&owner]).await.unwrap();
+    
+    let account = context.banks_client.get_account(borsh_wrapper).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY));
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+    
+    let state = load_wrapper(&mut context, &borsh_wrapper).await;
+    assert_eq!(state.original_nft_mint, mint);
+    assert_eq!(state.owner, owner.pubkey());
+    assert_eq!(state.privacy_config_hash, CONFIG_HASH);
+    assert_eq!(state.last_updated, 1_700_000_000);
+    assert_eq!(state.access_controls.len(), 2);
+    assert_eq!(state.get_access_level(&legacy_grantee("agent1.glitch.gang")), 2);
+    assert_eq!(state.get_access_level(&pubkey_grantee.parse().unwrap()), 3);
+    
+    // Migrating again is a no-op, and the wrapper is usable
+    let ix = instruction::migrate_wrapper(&privacy_wrapper::id(), &owner.pubkey(), &borsh_wrapper);
+    send(&mut context, &[ix], &[&owner]).await.unwrap();
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner.pubkey(), &borsh_wrapper, &viewer, 1);
+    send(&mut context, &[ix], &[&owner]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &borsh_wrapper).await;
+    assert_eq!(state.access_controls.len(), 3);
+}
+
+#[test]
+fn test_versioned_wrappers_are_not_borsh() {
+    let wrapper = PrivacyWrapper {
+        original_nft_mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        privacy_config_hash: CONFIG_HASH.to_string(),
+        access_controls: [(Pubkey::new_unique(), 1)].into_iter().collect(),
+        last_updated: 1_700_000_000,
+    };
+    let mut data = vec![0u8; PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY)];
+    wrapper.pack(&mut data).unwrap();
+    assert!(BorshWrapper::parse(&data).is_none());
+    
+    // Borsh state is only recognised with zeroes after it
+    let mut borsh = borsh_account_data(&Pubkey::new_unique(), &Pubkey::new_unique(), &[("agent1.glitch.gang", 2)]);
+    assert!(BorshWrapper::parse(&borsh).is_some());
+    *borsh.last_mut().unwrap() = 1;
+    assert!(BorshWrapper::parse(&borsh).is_none());
+}
+
+#[test]
+fn test_legacy_grantee_matches_client_shim() {
+    use project_89::models::legacy_agent_pubkey;
+    
+    let pubkey = Pubkey::new_unique();
+    let pubkey_id = pubkey.to_string();
+    for agent_id in ["agent1.glitch.gang", "", pubkey_id.as_str()] {
+        assert_eq!(legacy_grantee(agent_id), legacy_agent_pubkey(agent_id));
+    }
+    assert_eq!(legacy_grantee(&pubkey_id), pubkey);
+}
+
+async fn load_agent(context: &mut ProgramTestContext, name: &str) -> AgentRecord {
+    let (address, _) = find_agent_address(&privacy_wrapper::id(), name);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    AgentRecord::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_register_and_update_agent() {
+    let mut context = program_test().start_with_context().await;
+    let authority = context.payer.pubkey();
+    let agent = Keypair::new();
+    let name = "agent1.glitch.gang";
+    
+    let ix = instruction::register_agent(&privacy_wrapper::id(), &authority, &agent.pubkey(), name, "ipfs://agent1");
+    send(&mut context, &[ix], &[&agent]).await.unwrap();
+    
+    let record = load_agent(&mut context, name).await;
+    assert_eq!(record.name, name);
+    assert_eq!(record.agent, agent.pubkey());
+    assert_eq!(record.authority, authority);
+    assert_eq!(record.metadata_uri, "ipfs://agent1");
+    
+    // A name can only be registered once
+    let squatter = Keypair::new();
+    let ix = instruction::register_agent(&privacy_wrapper::id(), &authority, &squatter.pubkey(), name, "");
+    assert!(send(&mut context, &[ix], &[&squatter]).await.is_err());
+    
+    // The authority can rotate the agent key
+    let rotated = Keypair::new();
+    let ix = instruction::update_agent(&privacy_wrapper::id(), &authority, &rotated.pubkey(), name, "ipfs://agent1-v2");
+    send(&mut context, &[ix], &[&rotated]).await.unwrap();
+    
+    let record = load_agent(&mut context, name).await;
+    assert_eq!(record.agent, rotated.pubkey());
+    assert_eq!(record.metadata_uri, "ipfs://agent1-v2");
+    
+    // Nobody else can
+    let intruder = Keypair::new();
+    let ix = instruction::update_agent(&privacy_wrapper::id(), &intruder.pubkey(), &intruder.pubkey(), name, "");
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotAgentAuthority);
+}
+
+#[tokio::test]
+async fn test_register_agent_rejects_bad_names_and_addresses() {
+    let mut context = program_test().start_with_context().await;
+    let authority = context.payer.pubkey();
+    let agent = Keypair::new();
+    
+    let long_name = "a".repeat(65);
+    for name in ["", "Agent1.Glitch.Gang", "agent one", long_name.as_str()] {
+        let ix = instruction::register_agent(&privacy_wrapper::id(), &authority, &agent.pubkey(), name, "");
+        let err = send(&mut context, &[ix], &[&agent]).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::InvalidAgentName);
+    }
+    
+    // The record must live at the PDA derived from the name
+    let mut ix = instruction::register_agent(&privacy_wrapper::id(), &authority, &agent.pubkey(), "agent1.glitch.gang", "");
+    ix.accounts[2].pubkey = find_agent_address(&privacy_wrapper::id(), "agent2.glitch.gang").0;
+    let err = send(&mut context, &[ix], &[&agent]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds),
+    );
+}
+
+async fn load_reveal(context: &mut ProgramTestContext, address: &Pubkey) -> RevealEscrow {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    RevealEscrow::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_schedule_and_claim_reveal() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let reveal_key = [9u8; 32];
+    let commitment = solana_program::hash::hashv(&[&reveal_key]).to_bytes();
+    let reveal_slot = context.banks_client.get_root_slot().await.unwrap() + 100;
+    
+    let ix = instruction::schedule_reveal(
+        &privacy_wrapper::id(),
+        &owner,
+        &wrapper.pubkey(),
+        "mission",
+        RevealTime::Slot(reveal_slot),
+        commitment,
+        vec![1, 2, 3],
+    );
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let (reveal_account, _) = find_reveal_address(&privacy_wrapper::id(), &wrapper.pubkey(), "mission");
+    let escrow = load_reveal(&mut context, &reveal_account).await;
+    assert_eq!((escrow.label.as_str(), escrow.sealed_key.as_slice()), ("mission", &[1u8, 2, 3][..]));
+    assert_eq!(escrow.reveal_key, None);
+    
+    // Locked until the reveal slot, even with the right key
+    let claim = |key| instruction::claim_reveal(&privacy_wrapper::id(), &owner, &reveal_account, key);
+    let err = send(&mut context, &[claim(reveal_key)], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::RevealLocked);
+    
+    context.warp_to_slot(reveal_slot).unwrap();
+    
+    let err = send(&mut context, &[claim([8u8; 32])], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidRevealKey);
+    
+    // Anyone holding the key can claim once unlocked, but only once
+    let keeper = Keypair::new();
+    let ix = instruction::claim_reveal(&privacy_wrapper::id(), &keeper.pubkey(), &reveal_account, reveal_key);
+    send(&mut context, &[ix], &[&keeper]).await.unwrap();
+    assert_eq!(load_reveal(&mut context, &reveal_account).await.reveal_key, Some(reveal_key));
+    
+    let err = send(&mut context, &[claim(reveal_key)], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AlreadyRevealed);
+}
+
+#[tokio::test]
+async fn test_only_owner_schedules_reveals() {
+    let mut context = program_test().start_with_context().await;
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let intruder = Keypair::new();
+    
+    let ix = instruction::schedule_reveal(
+        &privacy_wrapper::id(),
+        &intruder.pubkey(),
+        &wrapper.pubkey(),
+        "mission",
+        RevealTime::Timestamp(0),
+        [0u8; 32],
+        Vec::new(),
+    );
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+}
+
+async fn load_recovery(context: &mut ProgramTestContext, wrapper: &Pubkey) -> RecoveryConfig {
+    let (address, _) = find_recovery_address(&privacy_wrapper::id(), wrapper);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    RecoveryConfig::unpack(&account.data).unwrap()
+}
+
+/// Complete a recovery signed by a fresh account, so repeated attempts are distinct transactions
+async fn complete_recovery(context: &mut ProgramTestContext, wrapper: &Pubkey) -> Result<(), BanksClientError> {
+    let anyone = Keypair::new();
+    let ix = instruction::complete_recovery(&privacy_wrapper::id(), &anyone.pubkey(), wrapper);
+    send(context, &[ix], &[&anyone]).await
+}
+
+#[tokio::test]
+async fn test_guardian_recovery_with_veto_and_challenge_period() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let new_owner = Pubkey::new_unique();
+    
+    let ix = instruction::setup_recovery(
+        &privacy_wrapper::id(),
+        &owner,
+        &wrapper,
+        guardians.iter().map(|g| (g.pubkey(), vec![1u8; 93])).collect(),
+        2,
+        3_600,
+        [4u8; 32],
+    );
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(load_recovery(&mut context, &wrapper).await.threshold, 2);
+    
+    let request = |guardian: &Keypair, new_owner: &Pubkey| {
+        instruction::request_recovery(&privacy_wrapper::id(), &guardian.pubkey(), &wrapper, new_owner)
+    };
+    
+    // Only guardians can request
+    let intruder = Keypair::new();
+    let err = send(&mut context, &[request(&intruder, &intruder.pubkey())], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotGuardian);
+    
+    // One approval is below the threshold
+    send(&mut context, &[request(&guardians[0], &new_owner)], &[&guardians[0]]).await.unwrap();
+    let err = complete_recovery(&mut context, &wrapper).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientApprovals);
+    
+    // Two approvals still wait out the challenge period
+    send(&mut context, &[request(&guardians[1], &new_owner)], &[&guardians[1]]).await.unwrap();
+    let err = complete_recovery(&mut context, &wrapper).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::ChallengePeriodActive);
+    
+    // A lone competing request only records a vote
+    send(&mut context, &[request(&guardians[2], &intruder.pubkey())], &[&guardians[2]]).await.unwrap();
+    let config = load_recovery(&mut context, &wrapper).await;
+    assert_eq!(config.pending.unwrap().new_owner, new_owner);
+    assert_eq!(config.rival_votes, vec![RecoveryVote { guardian: guardians[2].pubkey(), new_owner: intruder.pubkey() }]);
+    
+    // The owner vetoes
+    let veto = instruction::veto_recovery(&privacy_wrapper::id(), &owner, &wrapper);
+    send(&mut context, &[veto], &[]).await.unwrap();
+    let config = load_recovery(&mut context, &wrapper).await;
+    assert_eq!((config.pending, config.rival_votes), (None, vec![]));
+    let err = complete_recovery(&mut context, &wrapper).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NoRecoveryPending);
+    
+    // A rogue guardian taking the pending slot is outvoted by a threshold
+    send(&mut context, &[request(&guardians[0], &guardians[0].pubkey())], &[&guardians[0]]).await.unwrap();
+    send(&mut context, &[request(&guardians[1], &new_owner)], &[&guardians[1]]).await.unwrap();
+    assert_eq!(load_recovery(&mut context, &wrapper).await.pending.unwrap().new_owner, guardians[0].pubkey());
+    send(&mut context, &[request(&guardians[2], &new_owner)], &[&guardians[2]]).await.unwrap();
+    let pending = load_recovery(&mut context, &wrapper).await.pending.unwrap();
+    assert_eq!(pending.new_owner, new_owner);
+    assert_eq!(pending.approvals, vec![guardians[1].pubkey(), guardians[2].pubkey()]);
+    
+    // Unvetoed requests complete once the challenge period has passed
+    
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += 3_600;
+    context.set_sysvar(&clock);
+    
+    complete_recovery(&mut context, &wrapper).await.unwrap();
+    assert_eq!(load_wrapper(&mut context, &wrapper).await.owner, new_owner);
+    assert_eq!(load_recovery(&mut context, &wrapper).await.pending, None);
+}
+
+async fn load_lock(context: &mut ProgramTestContext, wrapper: &Pubkey) -> PrivacyLock {
+    let (address, _) = find_lock_address(&privacy_wrapper::id(), wrapper);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    PrivacyLock::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_lock_privacy_freezes_settings_until_expiry() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let viewer = Pubkey::new_unique();
+    
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer, 3);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // Only the owner can lock, and only for a positive duration
+    let intruder = Keypair::new();
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &intruder.pubkey(), &wrapper, Some(3_600));
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+    
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(0));
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidLockDuration);
+    
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(3_600));
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let lock = load_lock(&mut context, &wrapper).await;
+    assert_eq!(lock.locked_until, Some(lock.locked_at + 3_600));
+    
+    // Settings and grants are frozen, including revocations
+    let frozen = [
+        instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper, "new-privacy-config-hash"),
+        instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1),
+        instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer),
+    ];
+    for ix in frozen {
+        let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::PrivacySettingsLocked);
+    }
+    
+    // The lock can be extended but not shortened
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(60));
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidLockDuration);
+    
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(7_200));
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let extended = load_lock(&mut context, &wrapper).await;
+    assert_eq!(extended.locked_at, lock.locked_at);
+    assert!(extended.locked_until > lock.locked_until);
+    
+    // Once the lock expires the owner can change settings again
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = extended.locked_until.unwrap();
+    context.set_sysvar(&clock);
+    
+    // (A different hash than the frozen attempt, so it's a distinct transaction)
+    let ix = instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper, "unlocked-privacy-config-hash");
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(load_wrapper(&mut context, &wrapper).await.privacy_config_hash, "unlocked-privacy-config-hash");
+    
+    // An indefinite lock never expires
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, None);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    clock.unix_timestamp += 10 * 365 * 86_400;
+    context.set_sysvar(&clock);
+    
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer, 2);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PrivacySettingsLocked);
+}
+
+#[tokio::test]
+async fn test_settings_changes_require_the_lock_account() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    
+    // Passing another account in place of the lock PDA can't dodge the lock
+    let mut ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1);
+    ix.accounts[2].pubkey = Pubkey::new_unique();
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds),
+    );
+}
+
+#[tokio::test]
+async fn test_create_wrapper_validates_its_accounts() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let existing = create_wrapper(&mut context, &Pubkey::new_unique()).await;
+    let fresh = Keypair::new();
+    let nft_mint = Pubkey::new_unique();
+    let token_account = add_nft(&mut context, &nft_mint, &owner, 1);
+    let create = |wrapper: &Pubkey, hash: &str| {
+        instruction::create_wrapper(&privacy_wrapper::id(), &owner, &nft_mint, wrapper, hash)
+    };
+    
+    // Replaying over an existing wrapper, even one signed for, is refused
+    let err = send(&mut context, &[create(&existing.pubkey(), "replayed")], &[&existing]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized));
+    
+    // The wrapper must sign and be writable, and the system program and rent sysvar must be the real ones
+    let mut unsigned = create(&fresh.pubkey(), "unsigned");
+    unsigned.accounts[2].is_signer = false;
+    let mut readonly = create(&fresh.pubkey(), "readonly");
+    readonly.accounts[2].is_writable = false;
+    let mut fake_system = create(&fresh.pubkey(), "fake-system");
+    fake_system.accounts[3].pubkey = privacy_wrapper::id();
+    let mut fake_rent = create(&fresh.pubkey(), "fake-rent");
+    fake_rent.accounts[4].pubkey = solana_program::sysvar::clock::id();
+    
+    // The mint must be a real SPL Token mint
+    let mut unknown_mint = create(&fresh.pubkey(), "unknown-mint");
+    unknown_mint.accounts[1].pubkey = Pubkey::new_unique();
+    let mut token_as_mint = create(&fresh.pubkey(), "token-as-mint");
+    token_as_mint.accounts[1].pubkey = token_account;
+    
+    // And the owner must hold it: not sold on, not in someone else's account, not another mint's
+    let (sold_mint, held_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    add_nft(&mut context, &sold_mint, &owner, 0);
+    let holder_account = add_nft(&mut context, &held_mint, &Pubkey::new_unique(), 1);
+    let sold = instruction::create_wrapper(&privacy_wrapper::id(), &owner, &sold_mint, &fresh.pubkey(), "sold");
+    let mut held_elsewhere = instruction::create_wrapper(&privacy_wrapper::id(), &owner, &held_mint, &fresh.pubkey(), "held");
+    held_elsewhere.accounts[5].pubkey = holder_account;
+    let mut other_mint = create(&fresh.pubkey(), "other-mint");
+    other_mint.accounts[5].pubkey = get_associated_token_address(&owner, &sold_mint);
+    let not_owner = InstructionError::Custom(PrivacyWrapperError::NotNFTOwner as u32);
+    
+    let cases = [
+        (unsigned, vec![], InstructionError::MissingRequiredSignature),
+        (readonly, vec![&fresh], InstructionError::Custom(PrivacyWrapperError::AccountNotWritable as u32)),
+        (fake_system, vec![&fresh], InstructionError::IncorrectProgramId),
+        (fake_rent, vec![&fresh], InstructionError::InvalidArgument),
+        (unknown_mint, vec![&fresh], InstructionError::IncorrectProgramId),
+        (token_as_mint, vec![&fresh], InstructionError::InvalidAccountData),
+        (sold, vec![&fresh], not_owner.clone()),
+        (held_elsewhere, vec![&fresh], not_owner.clone()),
+        (other_mint, vec![&fresh], not_owner),
+    ];
+    for (ix, signers, expected) in cases {
+        let err = send(&mut context, &[ix], &signers).await.unwrap_err();
+        assert_eq!(err.unwrap(), TransactionError::InstructionError(0, expected));
+    }
+    assert!(context.banks_client.get_account(fresh.pubkey()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_handlers_reject_readonly_and_impostor_accounts() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let not_writable = InstructionError::Custom(PrivacyWrapperError::AccountNotWritable as u32);
+    
+    // Accounts a handler writes to must be passed writable
+    let mut cases = Vec::new();
+    for (mut ix, index) in [
+        (instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper, "readonly"), 1),
+        (instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1), 1),
+        (instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique()), 1),
+        (instruction::migrate_wrapper(&privacy_wrapper::id(), &owner, &wrapper), 1),
+        (instruction::resize_wrapper(&privacy_wrapper::id(), &owner, &wrapper, 32), 1),
+        (instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(60)), 2),
+    ] {
+        ix.accounts[index].is_writable = false;
+        cases.push((ix, not_writable.clone()));
+    }
+    
+    // Programs passed as the system program, or as the wrapper, are refused
+    let mut fake_system = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(60));
+    fake_system.accounts[3].pubkey = Pubkey::new_unique();
+    cases.push((fake_system, InstructionError::IncorrectProgramId));
+    let mut program_as_wrapper = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &owner, 1);
+    program_as_wrapper.accounts[1].pubkey = privacy_wrapper::id();
+    cases.push((program_as_wrapper, InstructionError::IncorrectProgramId));
+    
+    for (ix, expected) in cases {
+        let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+        assert_eq!(err.unwrap(), TransactionError::InstructionError(0, expected));
+    }
+    assert!(load_wrapper(&mut context, &wrapper).await.access_controls.is_empty());
+}
+
+/// Send instructions again in a fresh transaction, as a client retrying would
+async fn resend(
+    context: &mut ProgramTestContext,
+    instructions: &[solana_program::instruction::Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    context.get_new_latest_blockhash().await.unwrap();
+    send(context, instructions, signers).await
+}
+
+#[tokio::test]
+async fn test_retried_instructions_apply_once() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let viewer = Keypair::new();
+    
+    // Granting again updates the grantee's entry
+    let grant = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer.pubkey(), 1);
+    send(&mut context, &[grant.clone()], &[]).await.unwrap();
+    resend(&mut context, &[grant], &[]).await.unwrap();
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer.pubkey(), 2);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &wrapper).await;
+    assert_eq!(state.access_controls.len(), 1);
+    assert_eq!(state.get_access_level(&viewer.pubkey()), 2);
+    
+    // Revoking again is a no-op
+    let revoke = instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer.pubkey());
+    send(&mut context, &[revoke.clone()], &[]).await.unwrap();
+    resend(&mut context, &[revoke], &[]).await.unwrap();
+    assert!(load_wrapper(&mut context, &wrapper).await.access_controls.is_empty());
+    
+    // Accounts created once refuse a replay instead of being overwritten
+    let agent = Keypair::new();
+    let register = instruction::register_agent(&privacy_wrapper::id(), &owner, &agent.pubkey(), "agent1.glitch.gang", "");
+    send(&mut context, &[register.clone()], &[&agent]).await.unwrap();
+    let err = resend(&mut context, &[register], &[&agent]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized));
+    
+    // A payment retried after it settled can't buy the same access twice
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 1,
+        access_duration: 86_400,
+        settlement_window: 3_600,
+        dispute_window: 3_600,
+        royalty_shares: vec![],
+    };
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let buyer = funded_buyer(&mut context).await;
+    let pay = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms.clone());
+    send(&mut context, &[pay.clone()], &[&buyer]).await.unwrap();
+    let ix = instruction::confirm_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper);
+    send(&mut context, &[ix], &[&buyer]).await.unwrap();
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &buyer.pubkey(), &[]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let paid_balance = balance(&mut context, &buyer.pubkey()).await;
+    let err = resend(&mut context, &[pay.clone()], &[&buyer]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PurchaseStillActive);
+    assert_eq!(balance(&mut context, &buyer.pubkey()).await, paid_balance);
+    
+    // Once the access ends the buyer can renew
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += terms.access_duration;
+    context.set_sysvar(&clock);
+    resend(&mut context, &[pay], &[&buyer]).await.unwrap();
+    assert_eq!(load_purchase(&mut context, &wrapper, &buyer.pubkey()).await.status, PurchaseStatus::Escrowed);
+}
+
+#[tokio::test]
+async fn test_state_accounts_are_checked_against_their_address() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    
+    // A program-owned account of another type can't stand in for the recovery config
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, Some(60));
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let mut ix = instruction::veto_recovery(&privacy_wrapper::id(), &owner, &wrapper);
+    ix.accounts[2].pubkey = find_lock_address(&privacy_wrapper::id(), &wrapper).0;
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::InvalidSeeds));
+}
+
+/// Add a collection mint whose Metaplex metadata names `authority` as update authority
+fn add_collection_mint(program_test: &mut ProgramTest, authority: &Pubkey) -> Pubkey {
+    let collection_mint = Pubkey::new_unique();
+    
+    // Key::MetadataV1, then the update authority and mint the program reads
+    let mut data = vec![4u8];
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(collection_mint.as_ref());
+    data.resize(679, 0);
+    
+    program_test.add_account(find_metadata_address(&collection_mint).0, Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_METADATA_PROGRAM_ID,
+        ..Account::default()
+    });
+    program_test.add_account(*authority, Account {
+        lamports: 1_000_000_000,
+        owner: solana_program::system_program::id(),
+        ..Account::default()
+    });
+    
+    collection_mint
+}
+
+async fn load_collection(context: &mut ProgramTestContext, collection_mint: &Pubkey) -> CollectionWrapper {
+    let (address, _) = find_collection_address(&privacy_wrapper::id(), collection_mint);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    CollectionWrapper::unpack(&account.data).unwrap()
+}
+
+async fn load_membership(context: &mut ProgramTestContext, wrapper: &Pubkey) -> CollectionMembership {
+    let (address, _) = find_membership_address(&privacy_wrapper::id(), wrapper);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    CollectionMembership::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_collection_wrapper_and_membership() {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let collection_mint = add_collection_mint(&mut program_test, &authority.pubkey());
+    let mut context = program_test.start_with_context().await;
+    let owner = context.payer.pubkey();
+    let agent = Pubkey::new_unique();
+    
+    // Only the metadata update authority can wrap the collection
+    let intruder = Keypair::new();
+    let ix = instruction::create_collection(&privacy_wrapper::id(), &intruder.pubkey(), &collection_mint, CONFIG_HASH, vec![]);
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotCollectionAuthority);
+    
+    let ix = instruction::create_collection(&privacy_wrapper::id(), &authority.pubkey(), &collection_mint, CONFIG_HASH, vec![agent]);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    let collection = load_collection(&mut context, &collection_mint).await;
+    assert_eq!(collection.authority, authority.pubkey());
+    assert_eq!(collection.trusted_agents, vec![agent]);
+    
+    // Joining needs the collection authority's approval
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let ix = instruction::join_collection(&privacy_wrapper::id(), &owner, &intruder.pubkey(), &wrapper, &collection_mint, false);
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotCollectionAuthority);
+    
+    let ix = instruction::join_collection(&privacy_wrapper::id(), &owner, &authority.pubkey(), &wrapper, &collection_mint, false);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    let membership = load_membership(&mut context, &wrapper).await;
+    assert_eq!(membership.collection, find_collection_address(&privacy_wrapper::id(), &collection_mint).0);
+    assert!(!membership.override_policy);
+    
+    // The authority updates the policy members inherit
+    let ix = instruction::update_collection(&privacy_wrapper::id(), &authority.pubkey(), &collection_mint, "collection-policy-v2", vec![]);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    assert_eq!(load_collection(&mut context, &collection_mint).await.default_policy_hash, "collection-policy-v2");
+    
+    let ix = instruction::update_collection(&privacy_wrapper::id(), &intruder.pubkey(), &collection_mint, CONFIG_HASH, vec![]);
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotCollectionAuthority);
+    
+    // The owner overrides, then can't switch back while the wrapper is locked
+    let (collection_account, _) = find_collection_address(&privacy_wrapper::id(), &collection_mint);
+    let ix = instruction::set_policy_override(&privacy_wrapper::id(), &owner, &wrapper, &collection_account, true);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert!(load_membership(&mut context, &wrapper).await.override_policy);
+    
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, None);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::set_policy_override(&privacy_wrapper::id(), &owner, &wrapper, &collection_account, false);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PrivacySettingsLocked);
+}
+
+/// Add a `ProgramData` account naming `authority` as the program's upgrade authority
+fn add_upgrade_authority(program_test: &mut ProgramTest, authority: &Pubkey) {
+    // ProgramData tag, deployment slot, then the optional upgrade authority
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    
+    program_test.add_account(bpf_loader_upgradeable::get_program_data_address(&privacy_wrapper::id()), Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        ..Account::default()
+    });
+}
+
+async fn load_freeze(context: &mut ProgramTestContext, scope: &FreezeScope) -> FreezeConfig {
+    let (address, _) = find_freeze_address(&privacy_wrapper::id(), scope);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    FreezeConfig::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_program_freeze_pauses_settings_changes() {
+    let mut program_test = program_test();
+    let upgrade_authority = Keypair::new();
+    add_upgrade_authority(&mut program_test, &upgrade_authority.pubkey());
+    let mut context = program_test.start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let viewer = Pubkey::new_unique();
+    let governance = Keypair::new();
+    
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer, 3);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // Only the upgrade authority sets up the program-wide config
+    let intruder = Keypair::new();
+    let ix = instruction::initialize_freeze(&privacy_wrapper::id(), &owner, &intruder.pubkey(), FreezeScope::Program, &intruder.pubkey());
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotFreezeAuthority);
+    
+    let ix = instruction::initialize_freeze(&privacy_wrapper::id(), &owner, &upgrade_authority.pubkey(), FreezeScope::Program, &governance.pubkey());
+    send(&mut context, &[ix], &[&upgrade_authority]).await.unwrap();
+    let config = load_freeze(&mut context, &FreezeScope::Program).await;
+    assert_eq!(config.authority, governance.pubkey());
+    assert!(!config.frozen);
+    
+    // A listing made before the freeze can't be bought from during it
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 1,
+        access_duration: 86_400,
+        settlement_window: 3_600,
+        dispute_window: 3_600,
+        royalty_shares: vec![],
+    };
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let buyer = funded_buyer(&mut context).await;
+    
+    // Only the config's authority freezes
+    let ix = instruction::set_freeze(&privacy_wrapper::id(), &upgrade_authority.pubkey(), &FreezeScope::Program, true);
+    let err = send(&mut context, &[ix], &[&upgrade_authority]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotFreezeAuthority);
+    
+    let ix = instruction::set_freeze(&privacy_wrapper::id(), &governance.pubkey(), &FreezeScope::Program, true);
+    send(&mut context, &[ix], &[&governance]).await.unwrap();
+    assert!(load_freeze(&mut context, &FreezeScope::Program).await.frozen);
+    
+    // Settings changes and sales that widen access are paused, revocations still go through
+    let relisted = RevealTerms { price: 2_000_000, ..terms.clone() };
+    let frozen = [
+        (instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper, "new-privacy-config-hash"), vec![]),
+        (instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1), vec![]),
+        (instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, relisted), vec![]),
+        (instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms.clone()), vec![&buyer]),
+    ];
+    for (ix, signers) in frozen {
+        let err = send(&mut context, &[ix], &signers).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::ProgramFrozen);
+    }
+    
+    let ix = instruction::revoke_access(&privacy_wrapper::id(), &owner, &wrapper, &viewer);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // The authority hands over the config, and the new one unfreezes
+    let multisig = Keypair::new();
+    let ix = instruction::set_freeze_authority(&privacy_wrapper::id(), &governance.pubkey(), &FreezeScope::Program, &multisig.pubkey());
+    send(&mut context, &[ix], &[&governance]).await.unwrap();
+    
+    let ix = instruction::set_freeze(&privacy_wrapper::id(), &governance.pubkey(), &FreezeScope::Program, false);
+    let err = send(&mut context, &[ix], &[&governance]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotFreezeAuthority);
+    
+    let ix = instruction::set_freeze(&privacy_wrapper::id(), &multisig.pubkey(), &FreezeScope::Program, false);
+    send(&mut context, &[ix], &[&multisig]).await.unwrap();
+    
+    // (A different hash than the frozen attempt, so it's a distinct transaction)
+    let ix = instruction::update_privacy(&privacy_wrapper::id(), &owner, &wrapper, "unfrozen-privacy-config-hash");
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(load_wrapper(&mut context, &wrapper).await.privacy_config_hash, "unfrozen-privacy-config-hash");
+    
+    // Passing another account in place of the freeze PDA can't dodge a freeze
+    let mut ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &wrapper, &Pubkey::new_unique(), 1);
+    ix.accounts[3].pubkey = Pubkey::new_unique();
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds),
+    );
+}
+
+#[tokio::test]
+async fn test_collection_freeze_pauses_its_members() {
+    let mut program_test = program_test();
+    let authority = Keypair::new();
+    let collection_mint = add_collection_mint(&mut program_test, &authority.pubkey());
+    let mut context = program_test.start_with_context().await;
+    let owner = context.payer.pubkey();
+    let (collection, _) = find_collection_address(&privacy_wrapper::id(), &collection_mint);
+    let scope = FreezeScope::Collection(collection);
+    
+    let ix = instruction::create_collection(&privacy_wrapper::id(), &authority.pubkey(), &collection_mint, CONFIG_HASH, vec![]);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    let member = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let ix = instruction::join_collection(&privacy_wrapper::id(), &owner, &authority.pubkey(), &member, &collection_mint, false);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    let outsider = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    
+    // A member's listing made before the freeze can't be bought from during it
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 1,
+        access_duration: 86_400,
+        settlement_window: 3_600,
+        dispute_window: 3_600,
+        royalty_shares: vec![],
+    };
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &member, terms.clone());
+    let ix = instruction::with_collection_freeze(ix, &privacy_wrapper::id(), &collection);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let buyer = funded_buyer(&mut context).await;
+    
+    // Only the collection authority sets up the collection's config
+    let intruder = Keypair::new();
+    let ix = instruction::initialize_freeze(&privacy_wrapper::id(), &owner, &intruder.pubkey(), scope, &intruder.pubkey());
+    let err = send(&mut context, &[ix], &[&intruder]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotCollectionAuthority);
+    
+    let ix = instruction::initialize_freeze(&privacy_wrapper::id(), &owner, &authority.pubkey(), scope, &authority.pubkey());
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    let ix = instruction::set_freeze(&privacy_wrapper::id(), &authority.pubkey(), &scope, true);
+    send(&mut context, &[ix], &[&authority]).await.unwrap();
+    assert_eq!(load_freeze(&mut context, &scope).await.scope, scope);
+    
+    // Members are paused, and leaving out the collection config doesn't help
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &member, &Pubkey::new_unique(), 1);
+    let err = send(&mut context, &[ix.clone()], &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds),
+    );
+    
+    let ix = instruction::with_collection_freeze(ix, &privacy_wrapper::id(), &collection);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::CollectionFrozen);
+    
+    // So are policy switches and sales, and nothing new joins the collection
+    let relisted = RevealTerms { price: 2_000_000, ..terms.clone() };
+    let list = instruction::list_reveal(&privacy_wrapper::id(), &owner, &member, relisted);
+    let pay = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &member, terms.clone());
+    let frozen = [
+        (instruction::set_policy_override(&privacy_wrapper::id(), &owner, &member, &collection, true), vec![]),
+        (instruction::with_collection_freeze(list, &privacy_wrapper::id(), &collection), vec![]),
+        (instruction::with_collection_freeze(pay, &privacy_wrapper::id(), &collection), vec![&buyer]),
+    ];
+    for (ix, signers) in frozen {
+        let err = send(&mut context, &[ix], &signers).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::CollectionFrozen);
+    }
+    
+    let ix = instruction::join_collection(&privacy_wrapper::id(), &owner, &authority.pubkey(), &outsider, &collection_mint, false);
+    let err = send(&mut context, &[ix], &[&authority]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::CollectionFrozen);
+    
+    // Wrappers outside the collection aren't affected
+    let ix = instruction::grant_access(&privacy_wrapper::id(), &owner, &outsider, &Pubkey::new_unique(), 1);
+    send(&mut context, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pre_funded_pdas_are_still_created() {
+    let mut program_test = program_test();
+    let upgrade_authority = Keypair::new();
+    add_upgrade_authority(&mut program_test, &upgrade_authority.pubkey());
+    let mut context = program_test.start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let buyer = funded_buyer(&mut context).await;
+    
+    // Anyone can fund an address before the program creates its account there
+    let (freeze, _) = find_freeze_address(&privacy_wrapper::id(), &FreezeScope::Program);
+    let (lock, _) = find_lock_address(&privacy_wrapper::id(), &wrapper);
+    let (purchase, _) = find_purchase_address(&privacy_wrapper::id(), &wrapper, &buyer.pubkey());
+    let grief = Rent::default().minimum_balance(0);
+    let ixs: Vec<_> = [freeze, lock, purchase]
+        .iter()
+        .map(|address| system_instruction::transfer(&owner, address, grief))
+        .collect();
+    send(&mut context, &ixs, &[]).await.unwrap();
+    
+    let ix = instruction::initialize_freeze(&privacy_wrapper::id(), &owner, &upgrade_authority.pubkey(), FreezeScope::Program, &upgrade_authority.pubkey());
+    send(&mut context, &[ix], &[&upgrade_authority]).await.unwrap();
+    assert!(!load_freeze(&mut context, &FreezeScope::Program).await.frozen);
+    
+    // The buyer still escrows the whole price; only the rent is covered
+    let terms = RevealTerms {
+        price: 1_000_000,
+        access_level: 1,
+        access_duration: 86_400,
+        settlement_window: 3_600,
+        dispute_window: 3_600,
+        royalty_shares: vec![],
+    };
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms.clone());
+    send(&mut context, &[ix], &[&buyer]).await.unwrap();
+    assert_eq!(load_purchase(&mut context, &wrapper, &buyer.pubkey()).await.amount, terms.price);
+    assert_eq!(
+        balance(&mut context, &purchase).await,
+        Rent::default().minimum_balance(RevealPurchase::LEN) + terms.price,
+    );
+    
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, None);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(load_lock(&mut context, &wrapper).await.wrapper, wrapper);
+}
+
+async fn load_purchase(context: &mut ProgramTestContext, wrapper: &Pubkey, buyer: &Pubkey) -> RevealPurchase {
+    let (address, _) = find_purchase_address(&privacy_wrapper::id(), wrapper, buyer);
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    RevealPurchase::unpack(&account.data).unwrap()
+}
+
+async fn balance(context: &mut ProgramTestContext, account: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*account).await.unwrap()
+}
+
+/// Create a buyer funded by the context payer
+async fn funded_buyer(context: &mut ProgramTestContext) -> Keypair {
+    let buyer = Keypair::new();
+    let ix = system_instruction::transfer(&context.payer.pubkey(), &buyer.pubkey(), 10_000_000_000);
+    send(context, &[ix], &[]).await.unwrap();
+    buyer
+}
+
+#[tokio::test]
+async fn test_pay_to_reveal_escrows_and_splits_royalties() {
+    use project_89::models::RevealPurchased;
+    
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let (artist, platform) = (Pubkey::new_unique(), Pubkey::new_unique());
+    
+    let mut terms = RevealTerms {
+        price: 1_000_000_000,
+        access_level: 3,
+        access_duration: 86_400,
+        settlement_window: 3_600,
+        dispute_window: 3_600,
+        royalty_shares: vec![
+            RoyaltyShare { recipient: artist, basis_points: 1_000 },
+            RoyaltyShare { recipient: platform, basis_points: 9_001 },
+        ],
+    };
+    
+    // Royalties can't add up to more than the payment
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone());
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidRevealTerms);
+    
+    terms.royalty_shares[1].basis_points = 250;
+    let ix = instruction::list_reveal(&privacy_wrapper::id(), &owner, &wrapper, terms.clone());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    // The buyer pays only under the exact terms they agreed to, not just the price
+    let buyer = funded_buyer(&mut context).await;
+    let shortened = RevealTerms { access_duration: 60, ..terms.clone() };
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, shortened);
+    let err = send(&mut context, &[ix], &[&buyer]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::RevealTermsChanged);
+    
+    // Paying escrows the price and logs an event for key delivery
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms.clone());
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[ix], Some(&owner), &[&context.payer, &buyer], blockhash);
+    let result = context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    assert!(result.result.is_ok());
+    
+    let events = RevealPurchased::from_logs(&result.metadata.unwrap().log_messages);
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].wrapper, events[0].buyer, events[0].access_level), (wrapper, buyer.pubkey(), 3));
+    assert_eq!(events[0].amount, terms.price);
+    
+    let purchase = load_purchase(&mut context, &wrapper, &buyer.pubkey()).await;
+    assert_eq!(purchase.status, PurchaseStatus::Escrowed);
+    assert_eq!(purchase.expires_at, events[0].expires_at);
+    assert_eq!(purchase.level_at(purchase.paid_at), 3);
+    assert_eq!(purchase.level_at(purchase.expires_at), 0);
+    
+    // CheckAccess counts the purchase when the viewer's purchase PDA is passed
+    let ix = instruction::check_purchased_access(&privacy_wrapper::id(), &wrapper, &buyer.pubkey(), 3);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::check_access(&privacy_wrapper::id(), &wrapper, &buyer.pubkey(), 3);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientAccess);
+    
+    // Another viewer can't borrow the buyer's purchase
+    let mut ix = instruction::check_access(&privacy_wrapper::id(), &wrapper, &owner, 1);
+    let (purchase_account, _) = find_purchase_address(&privacy_wrapper::id(), &wrapper, &buyer.pubkey());
+    ix.accounts.push(AccountMeta::new_readonly(purchase_account, false));
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::InvalidSeeds));
+    
+    // One payment in escrow at a time, and no refunds inside the settlement window
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper, terms.clone());
+    let err = send(&mut context, &[ix], &[&buyer]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PurchaseEscrowed);
+    
+    let ix = instruction::refund_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper);
+    let err = send(&mut context, &[ix], &[&buyer]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::SettlementWindowActive);
+    
+    // The owner can't settle before the buyer confirms the key
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &buyer.pubkey(), &[artist, platform]);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::RevealNotConfirmed);
+    
+    let ix = instruction::confirm_reveal(&privacy_wrapper::id(), &buyer.pubkey(), &wrapper);
+    send(&mut context, &[ix], &[&buyer]).await.unwrap();
+    let confirmed = load_purchase(&mut context, &wrapper, &buyer.pubkey()).await;
+    assert_eq!(confirmed.status, PurchaseStatus::Confirmed);
+    assert_eq!(confirmed.level_at(confirmed.paid_at), 3);
+    
+    // Recipients must be passed in share order
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &buyer.pubkey(), &[platform, artist]);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InvalidRoyaltyRecipient);
+    
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &buyer.pubkey(), &[artist, platform]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(balance(&mut context, &artist).await, 100_000_000);
+    assert_eq!(balance(&mut context, &platform).await, 25_000_000);
+    assert_eq!(load_purchase(&mut context, &wrapper, &buyer.pubkey()).await.status, PurchaseStatus::Settled);
+    
+    // An unsettled payment is returned once the window passes, ending the access
+    let other_buyer = funded_buyer(&mut context).await;
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &other_buyer.pubkey(), &wrapper, terms.clone());
+    send(&mut context, &[ix], &[&other_buyer]).await.unwrap();
+    let paid_balance = balance(&mut context, &other_buyer.pubkey()).await;
+    
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += terms.settlement_window;
+    context.set_sysvar(&clock);
+    
+    let ix = instruction::refund_reveal(&privacy_wrapper::id(), &other_buyer.pubkey(), &wrapper);
+    send(&mut context, &[ix], &[&other_buyer]).await.unwrap();
+    assert_eq!(balance(&mut context, &other_buyer.pubkey()).await, paid_balance + terms.price);
+    
+    let refunded = load_purchase(&mut context, &wrapper, &other_buyer.pubkey()).await;
+    assert_eq!(refunded.status, PurchaseStatus::Refunded);
+    assert_eq!(refunded.level_at(refunded.paid_at), 0);
+    let ix = instruction::check_purchased_access(&privacy_wrapper::id(), &wrapper, &other_buyer.pubkey(), 1);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::InsufficientAccess);
+    
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &other_buyer.pubkey(), &[artist, platform]);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PurchaseNotEscrowed);
+    
+    // A buyer who neither confirms nor reclaims keeps the refund to themselves
+    // through the dispute window, and can be settled without confirming after it
+    let late_buyer = funded_buyer(&mut context).await;
+    let ix = instruction::pay_to_reveal(&privacy_wrapper::id(), &late_buyer.pubkey(), &wrapper, terms.clone());
+    send(&mut context, &[ix], &[&late_buyer]).await.unwrap();
+    
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += terms.settlement_window;
+    context.set_sysvar(&clock);
+    
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &late_buyer.pubkey(), &[artist, platform]);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::RevealNotConfirmed);
+    
+    clock.unix_timestamp += terms.dispute_window;
+    context.set_sysvar(&clock);
+    
+    let ix = instruction::settle_reveal(&privacy_wrapper::id(), &owner, &wrapper, &late_buyer.pubkey(), &[artist, platform]);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    assert_eq!(load_purchase(&mut context, &wrapper, &late_buyer.pubkey()).await.status, PurchaseStatus::Settled);
+    
+    // Confirming only applies to a payment still in escrow
+    let ix = instruction::confirm_reveal(&privacy_wrapper::id(), &late_buyer.pubkey(), &wrapper);
+    let err = send(&mut context, &[ix], &[&late_buyer]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PurchaseNotEscrowed);
+}
+
+async fn load_request(context: &mut ProgramTestContext, wrapper: &Pubkey, requester: &Pubkey) -> Option<AccessRequest> {
+    let (address, _) = find_request_address(&privacy_wrapper::id(), wrapper, requester);
+    let account = context.banks_client.get_account(address).await.unwrap()?;
+    Some(AccessRequest::unpack(&account.data).unwrap())
+}
+
+#[tokio::test]
+async fn test_access_requests_are_approved_or_denied() {
+    use project_89::models::AccessRequest as ClientRequest;
+    
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let alice = funded_buyer(&mut context).await;
+    let bob = funded_buyer(&mut context).await;
+    let alice_balance = balance(&mut context, &alice.pubkey()).await;
+    
+    // Anyone can ask, paying for their own request, but a pending request can't be changed
+    let ix = instruction::request_access(&privacy_wrapper::id(), &alice.pubkey(), &wrapper, 2);
+    send(&mut context, &[ix], &[&alice]).await.unwrap();
+    let ix = instruction::request_access(&privacy_wrapper::id(), &bob.pubkey(), &wrapper, 1);
+    send(&mut context, &[ix], &[&bob]).await.unwrap();
+    let ix = instruction::request_access(&privacy_wrapper::id(), &alice.pubkey(), &wrapper, 3);
+    let err = send(&mut context, &[ix], &[&alice]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AccessRequestPending);
+    
+    let request = load_request(&mut context, &wrapper, &alice.pubkey()).await.unwrap();
+    assert_eq!((request.wrapper, request.requester, request.level), (wrapper, alice.pubkey(), 2));
+    assert_eq!(
+        balance(&mut context, &alice.pubkey()).await,
+        alice_balance - Rent::default().minimum_balance(AccessRequest::LEN),
+    );
+    assert!(load_wrapper(&mut context, &wrapper).await.access_controls.is_empty());
+    
+    // The client reads the same request from the account
+    let (address, _) = find_request_address(&privacy_wrapper::id(), &wrapper, &alice.pubkey());
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(ClientRequest::unpack(&account.data).unwrap().level, 2);
+    
+    // Requests need a wrapper and the requester's own request PDA
+    let ix = instruction::request_access(&privacy_wrapper::id(), &alice.pubkey(), &Pubkey::new_unique(), 1);
+    let err = send(&mut context, &[ix], &[&alice]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::IncorrectProgramId));
+    let mut ix = instruction::request_access(&privacy_wrapper::id(), &alice.pubkey(), &wrapper, 1);
+    ix.accounts[2].pubkey = find_request_address(&privacy_wrapper::id(), &wrapper, &bob.pubkey()).0;
+    let err = send(&mut context, &[ix], &[&alice]).await.unwrap_err();
+    assert_eq!(err.unwrap(), TransactionError::InstructionError(0, InstructionError::InvalidSeeds));
+    
+    // Only the owner decides, and only on the level they reviewed
+    let ix = instruction::approve_access(&privacy_wrapper::id(), &alice.pubkey(), &wrapper, &alice.pubkey(), 2);
+    let err = send(&mut context, &[ix], &[&alice]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NotNFTOwner);
+    let ix = instruction::approve_access(&privacy_wrapper::id(), &owner, &wrapper, &alice.pubkey(), 3);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::AccessRequestMismatch);
+    assert!(load_request(&mut context, &wrapper, &alice.pubkey()).await.is_some());
+    
+    // Answering a request closes it and refunds the requester
+    let ix = instruction::approve_access(&privacy_wrapper::id(), &owner, &wrapper, &alice.pubkey(), 2);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::deny_access(&privacy_wrapper::id(), &owner, &wrapper, &bob.pubkey());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    let state = load_wrapper(&mut context, &wrapper).await;
+    assert_eq!(state.get_access_level(&alice.pubkey()), 2);
+    assert_eq!(state.get_access_level(&bob.pubkey()), 0);
+    assert!(load_request(&mut context, &wrapper, &alice.pubkey()).await.is_none());
+    assert!(load_request(&mut context, &wrapper, &bob.pubkey()).await.is_none());
+    assert_eq!(balance(&mut context, &alice.pubkey()).await, alice_balance);
+    
+    // Decided requests are gone, and the requester can ask again
+    let ix = instruction::deny_access(&privacy_wrapper::id(), &owner, &wrapper, &alice.pubkey());
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::NoAccessRequest);
+    let ix = instruction::request_access(&privacy_wrapper::id(), &bob.pubkey(), &wrapper, 3);
+    send(&mut context, &[ix], &[&bob]).await.unwrap();
+    assert_eq!(load_request(&mut context, &wrapper, &bob.pubkey()).await.unwrap().level, 3);
+    
+    // Requests take no room in the wrapper, so any number can wait at once
+    let requesters: Vec<Keypair> = (0..DEFAULT_ACCESS_CAPACITY + 1).map(|_| Keypair::new()).collect();
+    for requester in &requesters {
+        let fund = system_instruction::transfer(&owner, &requester.pubkey(), 10_000_000);
+        let ix = instruction::request_access(&privacy_wrapper::id(), &requester.pubkey(), &wrapper, 1);
+        send(&mut context, &[fund, ix], &[requester]).await.unwrap();
+    }
+    let account = context.banks_client.get_account(wrapper).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY));
+    
+    // A privacy lock freezes approvals, but requests can still be turned down
+    let ix = instruction::lock_privacy(&privacy_wrapper::id(), &owner, &wrapper, None);
+    send(&mut context, &[ix], &[]).await.unwrap();
+    let ix = instruction::approve_access(&privacy_wrapper::id(), &owner, &wrapper, &requesters[0].pubkey(), 1);
+    let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+    custom_error(err, PrivacyWrapperError::PrivacySettingsLocked);
+    let ix = instruction::deny_access(&privacy_wrapper::id(), &owner, &wrapper, &requesters[0].pubkey());
+    send(&mut context, &[ix], &[]).await.unwrap();
+    
+    assert!(load_request(&mut context, &wrapper, &requesters[0].pubkey()).await.is_none());
+    assert!(load_request(&mut context, &wrapper, &requesters[1].pubkey()).await.is_some());
+    assert_eq!(load_wrapper(&mut context, &wrapper).await.access_controls.len(), 1);
+}
+
+#[tokio::test]
+async fn test_setup_recovery_rejects_bad_configs() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let wrapper = create_wrapper(&mut context, &Pubkey::new_unique()).await.pubkey();
+    let guardian = Pubkey::new_unique();
+    
+    for (guardians, threshold, challenge_period) in [
+        (vec![guardian], 0, 60),
+        (vec![guardian], 2, 60),
+        (vec![guardian, guardian], 1, 60),
+        (vec![guardian], 1, -1),
+    ] {
+        let ix = instruction::setup_recovery(
+            &privacy_wrapper::id(),
+            &owner,
+            &wrapper,
+            guardians.into_iter().map(|g| (g, Vec::new())).collect(),
+            threshold,
+            challenge_period,
+            [0u8; 32],
+        );
+        let err = send(&mut context, &[ix], &[]).await.unwrap_err();
+        custom_error(err, PrivacyWrapperError::InvalidRecoveryConfig);
+    }
+}
+
+#[test]
+fn test_recovery_config_layout_matches_client() {
+    use project_89::models::{find_recovery_address as client_find_recovery_address, RecoveryConfig as ClientConfig};
+    
+    let guardians: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    let config = RecoveryConfig {
+        is_initialized: true,
+        wrapper: Pubkey::new_unique(),
+        guardians: guardians.clone(),
+        threshold: 5,
+        challenge_period: 86_400,
+        key_commitment: [1u8; 32],
+        sealed_shares: vec![vec![2u8; 128]; 8],
+        pending: Some(privacy_wrapper::recovery::RecoveryRequest {
+            new_owner: Pubkey::new_unique(),
+            requested_at: 1_700_000_000,
+            approvals: guardians.clone(),
+        }),
+        rival_votes: guardians.iter()
+            .map(|guardian| RecoveryVote { guardian: *guardian, new_owner: Pubkey::new_unique() })
+            .collect(),
+    };
+    let mut data = config.try_to_vec().unwrap();
+    assert_eq!(data.len(), RecoveryConfig::LEN);
+    data.resize(RecoveryConfig::LEN, 0);
+    
+    let client_config = ClientConfig::unpack(&data).unwrap();
+    assert_eq!(client_config.guardians, config.guardians);
+    assert_eq!(client_config.pending.unwrap().approvals.len(), 8);
+    assert_eq!(client_config.rival_votes.len(), 8);
+    assert_eq!(
+        client_find_recovery_address(&privacy_wrapper::id(), &config.wrapper),
+        find_recovery_address(&privacy_wrapper::id(), &config.wrapper),
+    );
+}
+
+#[test]
+fn test_agent_record_layout_matches_client() {
+    use project_89::models::{find_agent_address as client_find_agent_address, AgentRecord as ClientRecord};
+    
+    let record = AgentRecord {
+        is_initialized: true,
+        name: "agent1.glitch.gang".to_string(),
+        agent: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        metadata_uri: "ipfs://agent1".to_string(),
+        registered_at: 1_700_000_000,
+    };
+    let client_record = ClientRecord::try_from_slice(&record.try_to_vec().unwrap()).unwrap();
+    
+    assert_eq!(client_record.agent, record.agent);
+    assert_eq!(client_record.name, record.name);
+    assert_eq!(
+        client_find_agent_address(&privacy_wrapper::id(), &record.name),
+        find_agent_address(&privacy_wrapper::id(), &record.name),
+    );
+}
+
+#[test]
+fn test_reveal_escrow_layout_matches_client() {
+    use project_89::models::{
+        find_reveal_address as client_find_reveal_address, RevealEscrow as ClientEscrow, RevealTime as ClientTime,
+    };
+    
+    let escrow = RevealEscrow {
+        is_initialized: true,
+        wrapper: Pubkey::new_unique(),
+        label: "mission".to_string(),
+        reveal_time: RevealTime::Timestamp(1_700_000_000),
+        key_commitment: [1u8; 32],
+        sealed_key: vec![2u8; 60],
+        reveal_key: Some([3u8; 32]),
+    };
+    let mut data = escrow.try_to_vec().unwrap();
+    assert!(data.len() <= RevealEscrow::LEN);
+    data.resize(RevealEscrow::LEN, 0);
+    
+    let client_escrow = ClientEscrow::unpack(&data).unwrap();
+    assert_eq!(client_escrow.reveal_time, ClientTime::Timestamp(1_700_000_000));
+    assert_eq!(client_escrow.reveal_key, escrow.reveal_key);
+    assert_eq!(
+        client_find_reveal_address(&privacy_wrapper::id(), &escrow.wrapper, &escrow.label),
+        find_reveal_address(&privacy_wrapper::id(), &escrow.wrapper, &escrow.label),
+    );
+}
+
+#[test]
+fn test_privacy_lock_layout_matches_client() {
+    use project_89::models::{find_lock_address as client_find_lock_address, PrivacyLock as ClientLock};
+    
+    let lock = PrivacyLock {
+        is_initialized: true,
+        wrapper: Pubkey::new_unique(),
+        locked_at: 1_700_000_000,
+        locked_until: Some(1_700_086_400),
+    };
+    let data = lock.try_to_vec().unwrap();
+    assert_eq!(data.len(), PrivacyLock::LEN);
+    
+    let client_lock = ClientLock::unpack(&data).unwrap();
+    assert_eq!(client_lock.locked_until, lock.locked_until);
+    assert!(client_lock.is_active(1_700_000_000) && !client_lock.is_active(1_700_086_400));
+    assert_eq!(
+        client_find_lock_address(&privacy_wrapper::id(), &lock.wrapper),
+        find_lock_address(&privacy_wrapper::id(), &lock.wrapper),
+    );
+}
+
+#[test]
+fn test_freeze_config_layout_matches_client() {
+    use project_89::models::{find_freeze_address as client_find_freeze_address, FreezeConfig as ClientConfig, FreezeScope as ClientScope};
+    
+    let collection = Pubkey::new_unique();
+    let config = FreezeConfig {
+        is_initialized: true,
+        scope: FreezeScope::Collection(collection),
+        authority: Pubkey::new_unique(),
+        frozen: true,
+        updated_at: 1_700_000_000,
+    };
+    let data = config.try_to_vec().unwrap();
+    assert_eq!(data.len(), FreezeConfig::LEN);
+    
+    let client_config = ClientConfig::unpack(&data).unwrap();
+    assert_eq!(client_config.scope, ClientScope::Collection(collection));
+    assert_eq!(client_config.authority, config.authority);
+    assert!(client_config.frozen);
+    assert_eq!(
+        client_find_freeze_address(&privacy_wrapper::id(), &ClientScope::Program),
+        find_freeze_address(&privacy_wrapper::id(), &FreezeScope::Program),
+    );
+    assert_eq!(
+        client_find_freeze_address(&privacy_wrapper::id(), &ClientScope::Collection(collection)),
+        find_freeze_address(&privacy_wrapper::id(), &config.scope),
+    );
+}
+
+#[test]
+fn test_collection_layout_matches_client() {
+    use project_89::models::{
+        find_collection_address as client_find_collection_address, find_membership_address as client_find_membership_address,
+        CollectionMembership as ClientMembership, CollectionWrapper as ClientCollection, EffectivePolicy, WrapperState,
+    };
+    
+    let collection = CollectionWrapper {
+        is_initialized: true,
+        collection_mint: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        default_policy_hash: "collection-policy-hash".to_string(),
+        trusted_agents: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        last_updated: 1_700_000_000,
+    };
+    let mut data = collection.try_to_vec().unwrap();
+    assert!(data.len() <= CollectionWrapper::LEN);
+    data.resize(CollectionWrapper::LEN, 0);
+    let client_collection = ClientCollection::unpack(&data).unwrap();
+    assert_eq!(client_collection.trusted_agents, collection.trusted_agents);
+    
+    let mut membership = CollectionMembership {
+        is_initialized: true,
+        wrapper: Pubkey::new_unique(),
+        collection: find_collection_address(&privacy_wrapper::id(), &collection.collection_mint).0,
+        override_policy: false,
+    };
+    let data = membership.try_to_vec().unwrap();
+    assert_eq!(data.len(), CollectionMembership::LEN);
+    let client_membership = ClientMembership::unpack(&data).unwrap();
+    assert_eq!(client_membership.collection, membership.collection);
+    
+    assert_eq!(
+        client_find_collection_address(&privacy_wrapper::id(), &collection.collection_mint),
+        find_collection_address(&privacy_wrapper::id(), &collection.collection_mint),
+    );
+    assert_eq!(
+        client_find_membership_address(&privacy_wrapper::id(), &membership.wrapper),
+        find_membership_address(&privacy_wrapper::id(), &membership.wrapper),
+    );
+    
+    // Members inherit the collection policy until they override it
+    let state = WrapperState {
+        original_nft_mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        privacy_config_hash: CONFIG_HASH.to_string(),
+        access_controls: Default::default(),
+        last_updated: 0,
+    };
+    let inherited = EffectivePolicy::resolve(&state, Some(&client_membership), Some(&client_collection));
+    assert_eq!(inherited.policy_hash, collection.default_policy_hash);
+    assert_eq!(inherited.trusted_agents, collection.trusted_agents);
+    assert_eq!(inherited.inherited_from, Some(collection.collection_mint));
+    
+    membership.override_policy = true;
+    let client_membership = ClientMembership::unpack(&membership.try_to_vec().unwrap()).unwrap();
+    let overridden = EffectivePolicy::resolve(&state, Some(&client_membership), Some(&client_collection));
+    assert_eq!(overridden.policy_hash, CONFIG_HASH);
+    assert!(overridden.trusted_agents.is_empty() && overridden.inherited_from.is_none());
+    assert_eq!(EffectivePolicy::resolve(&state, None, None), overridden);
+}
+
+#[test]
+fn test_instruction_round_trip_matches_client() {
+    use project_89::models::{
+        FreezeScope as ClientScope, RevealTerms as ClientTerms, RevealTime as ClientTime, RoyaltyShare as ClientShare,
+        WrapperInstruction as ClientInstruction,
+    };
+    
+    let grantee = Pubkey::new_unique();
+    let cases = vec![
+        (
+            WrapperInstruction::CreateWrapper { privacy_config_hash: CONFIG_HASH.to_string() },
+            ClientInstruction::CreateWrapper { privacy_config_hash: CONFIG_HASH.to_string() },
+        ),
+        (
+            WrapperInstruction::UpdatePrivacy { new_privacy_config_hash: CONFIG_HASH.to_string() },
+            ClientInstruction::UpdatePrivacy { new_privacy_config_hash: CONFIG_HASH.to_string() },
+        ),
+        (
+            WrapperInstruction::GrantAccess { grantee, level: 255 },
+            ClientInstruction::GrantAccess { grantee, level: 255 },
+        ),
+        (
+            WrapperInstruction::RevokeAccess { grantee },
+            ClientInstruction::RevokeAccess { grantee },
+        ),
+        (
+            WrapperInstruction::MigrateWrapper,
+            ClientInstruction::MigrateWrapper,
+        ),
+        (
+            WrapperInstruction::RegisterAgent {
+                name: "agent1.glitch.gang".to_string(),
+                metadata_uri: "https://glitch.gang/agents/1.json".to_string(),
+            },
+            ClientInstruction::RegisterAgent {
+                name: "agent1.glitch.gang".to_string(),
+                metadata_uri: "https://glitch.gang/agents/1.json".to_string(),
+            },
+        ),
+        (
+            WrapperInstruction::UpdateAgent { metadata_uri: String::new() },
+            ClientInstruction::UpdateAgent { metadata_uri: String::new() },
+        ),
+        (
+            WrapperInstruction::ScheduleReveal {
+                label: "mission".to_string(),
+                reveal_time: RevealTime::Slot(42),
+                key_commitment: [7u8; 32],
+                sealed_key: vec![1, 2, 3],
+            },
+            ClientInstruction::ScheduleReveal {
+                label: "mission".to_string(),
+                reveal_time: ClientTime::Slot(42),
+                key_commitment: [7u8; 32],
+                sealed_key: vec![1, 2, 3],
+            },
+        ),
+        (
+            WrapperInstruction::ClaimReveal { reveal_key: [5u8; 32] },
+            ClientInstruction::ClaimReveal { reveal_key: [5u8; 32] },
+        ),
+        (
+            WrapperInstruction::SetupRecovery {
+                guardians: vec![grantee],
+                threshold: 1,
+                challenge_period: 86_400,
+                key_commitment: [6u8; 32],
+                sealed_shares: vec![vec![1, 2, 3]],
+            },
+            ClientInstruction::SetupRecovery {
+                guardians: vec![grantee],
+                threshold: 1,
+                challenge_period: 86_400,
+                key_commitment: [6u8; 32],
+                sealed_shares: vec![vec![1, 2, 3]],
+            },
+        ),
+        (
+            WrapperInstruction::RequestRecovery { new_owner: grantee },
+            ClientInstruction::RequestRecovery { new_owner: grantee },
+        ),
+        (
+            WrapperInstruction::VetoRecovery,
+            ClientInstruction::VetoRecovery,
+        ),
+        (
+            WrapperInstruction::CompleteRecovery,
+            ClientInstruction::CompleteRecovery,
+        ),
+        (
+            WrapperInstruction::LockPrivacy { duration: Some(86_400) },
+            ClientInstruction::LockPrivacy { duration: Some(86_400) },
+        ),
+        (
+            WrapperInstruction::LockPrivacy { duration: None },
+            ClientInstruction::LockPrivacy { duration: None },
+        ),
+        (
+            WrapperInstruction::CreateCollection {
+                default_policy_hash: CONFIG_HASH.to_string(),
+                trusted_agents: vec![grantee],
+            },
+            ClientInstruction::CreateCollection {
+                default_policy_hash: CONFIG_HASH.to_string(),
+                trusted_agents: vec![grantee],
+            },
+        ),
+        (
+            WrapperInstruction::UpdateCollection { default_policy_hash: String::new(), trusted_agents: Vec::new() },
+            ClientInstruction::UpdateCollection { default_policy_hash: String::new(), trusted_agents: Vec::new() },
+        ),
+        (
+            WrapperInstruction::JoinCollection { override_policy: false },
+            ClientInstruction::JoinCollection { override_policy: false },
+        ),
+        (
+            WrapperInstruction::SetPolicyOverride { override_policy: true },
+            ClientInstruction::SetPolicyOverride { override_policy: true },
+        ),
+        (
+            WrapperInstruction::ListReveal {
+                terms: RevealTerms {
+                    price: 1_000,
+                    access_level: 3,
+                    access_duration: 86_400,
+                    settlement_window: 3_600,
+                    dispute_window: 3_600,
+                    royalty_shares: vec![RoyaltyShare { recipient: grantee, basis_points: 500 }],
+                },
+            },
+            ClientInstruction::ListReveal {
+                terms: ClientTerms {
+                    price: 1_000,
+                    access_level: 3,
+                    access_duration: 86_400,
+                    settlement_window: 3_600,
+                    dispute_window: 3_600,
+                    royalty_shares: vec![ClientShare { recipient: grantee, basis_points: 500 }],
+                },
+            },
+        ),
+        (
+            WrapperInstruction::PayToReveal {
+                terms: RevealTerms {
+                    price: 1_000,
+                    access_level: 3,
+                    access_duration: 86_400,
+                    settlement_window: 3_600,
+                    dispute_window: 3_600,
+                    royalty_shares: vec![],
+                },
+            },
+            ClientInstruction::PayToReveal {
+                terms: ClientTerms {
+                    price: 1_000,
+                    access_level: 3,
+                    access_duration: 86_400,
+                    settlement_window: 3_600,
+                    dispute_window: 3_600,
+                    royalty_shares: vec![],
+                },
+            },
+        ),
+        (
+            WrapperInstruction::SettleReveal,
+            ClientInstruction::SettleReveal,
+        ),
+        (
+            WrapperInstruction::RefundReveal,
+            ClientInstruction::RefundReveal,
+        ),
+        (
+            WrapperInstruction::RequestAccess { level: 2 },
+            ClientInstruction::RequestAccess { level: 2 },
+        ),
+        (
+            WrapperInstruction::ApproveAccess { requester: grantee, level: 2 },
+            ClientInstruction::ApproveAccess { requester: grantee, level: 2 },
+        ),
+        (
+            WrapperInstruction::DenyAccess { requester: grantee },
+            ClientInstruction::DenyAccess { requester: grantee },
+        ),
+        (
+            WrapperInstruction::CheckAccess { required_level: 3 },
+            ClientInstruction::CheckAccess { required_level: 3 },
+        ),
+        (
+            WrapperInstruction::InitializeFreeze { scope: FreezeScope::Collection(grantee), authority: grantee },
+            ClientInstruction::InitializeFreeze { scope: ClientScope::Collection(grantee), authority: grantee },
+        ),
+        (
+            WrapperInstruction::SetFreeze { frozen: true },
+            ClientInstruction::SetFreeze { frozen: true },
+        ),
+        (
+            WrapperInstruction::SetFreezeAuthority { new_authority: grantee },
+            ClientInstruction::SetFreezeAuthority { new_authority: grantee },
+        ),
+        (
+            WrapperInstruction::ResizeWrapper { capacity: 300 },
+            ClientInstruction::ResizeWrapper { capacity: 300 },
+        ),
+        (
+            WrapperInstruction::ConfirmReveal,
+            ClientInstruction::ConfirmReveal,
+        ),
+    ];
+    
+    for (program_ix, client_ix) in cases {
+        let program_bytes = program_ix.try_to_vec().unwrap();
+        let client_bytes = client_ix.try_to_vec().unwrap();
+        
+        assert_eq!(program_bytes, client_bytes);
+        assert_eq!(WrapperInstruction::try_from_slice(&client_bytes).unwrap(), program_ix);
+    }
+}
+
+#[test]
+fn test_state_round_trip_with_spare_capacity() {
+    let mut wrapper = PrivacyWrapper {
+        original_nft_mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        privacy_config_hash: CONFIG_HASH.to_string(),
+        access_controls: Default::default(),
+        last_updated: 1_700_000_000,
+    };
+    let grantee = Pubkey::new_unique();
+    wrapper.access_controls.insert(grantee, 2);
+    
+    let mut data = vec![0u8; PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY)];
+    wrapper.pack(&mut data).unwrap();
+    
+    let decoded = PrivacyWrapper::unpack(&data).unwrap();
+    assert_eq!(decoded, wrapper);
+    
+    // The zero-copy view reads the same state without decoding
+    let view = PrivacyWrapperView::load(&data).unwrap();
+    assert_eq!(view.header().owner, wrapper.owner);
+    assert_eq!(view.entries().len(), 1);
+    assert_eq!(view.get_access_level(&grantee), 2);
+}
+
+#[test]
+fn test_uninitialized_account_is_rejected() {
+    let data = vec![0u8; PrivacyWrapper::get_account_size(DEFAULT_ACCESS_CAPACITY)];
+    
+    assert_eq!(
+        PrivacyWrapper::unpack(&data).unwrap_err(),
+        ProgramError::from(PrivacyWrapperError::AccountNotInitialized),
+    );
+}
+
+#[test]
+fn test_idl_matches_program_layout() {
+    let idl: serde_json::Value = serde_json::from_str(privacy_wrapper::IDL).unwrap();
+    assert_eq!(idl["metadata"]["address"], privacy_wrapper::id().to_string());
+    
+    // Every instruction's discriminant is the tag Borsh writes for its variant
+    let key = Pubkey::new_unique();
+    let terms = RevealTerms {
+        price: 1,
+        access_level: 1,
+        access_duration: 60,
+        settlement_window: 60,
+        dispute_window: 60,
+        royalty_shares: vec![],
+    };
+    let variants = [
+        ("createWrapper", WrapperInstruction::CreateWrapper { privacy_config_hash: String::new() }),
+        ("updatePrivacy", WrapperInstruction::UpdatePrivacy { new_privacy_config_hash: String::new() }),
+        ("grantAccess", WrapperInstruction::GrantAccess { grantee: key, level: 1 }),
+        ("revokeAccess", WrapperInstruction::RevokeAccess { grantee: key }),
+        ("migrateWrapper", WrapperInstruction::MigrateWrapper),
+        ("registerAgent", WrapperInstruction::RegisterAgent { name: String::new(), metadata_uri: String::new() }),
+        ("updateAgent", WrapperInstruction::UpdateAgent { metadata_uri: String::new() }),
+        ("scheduleReveal", WrapperInstruction::ScheduleReveal {
+            label: String::new(),
+            reveal_time: RevealTime::Slot(1),
+            key_commitment: [0; 32],
+            sealed_key: vec![],
+        }),
+        ("claimReveal", WrapperInstruction::ClaimReveal { reveal_key: [0; 32] }),
+        ("setupRecovery", WrapperInstruction::SetupRecovery {
+            guardians: vec![],
+            threshold: 1,
+            challenge_period: 0,
+            key_commitment: [0; 32],
+            sealed_shares: vec![],
+        }),
+        ("requestRecovery", WrapperInstruction::RequestRecovery { new_owner: key }),
+        ("vetoRecovery", WrapperInstruction::VetoRecovery),
+        ("completeRecovery", WrapperInstruction::CompleteRecovery),
+        ("lockPrivacy", WrapperInstruction::LockPrivacy { duration: None }),
+        ("createCollection", WrapperInstruction::CreateCollection { default_policy_hash: String::new(), trusted_agents: vec![] }),
+        ("updateCollection", WrapperInstruction::UpdateCollection { default_policy_hash: String::new(), trusted_agents: vec![] }),
+        ("joinCollection", WrapperInstruction::JoinCollection { override_policy: false }),
+        ("setPolicyOverride", WrapperInstruction::SetPolicyOverride { override_policy: false }),
+        ("listReveal", WrapperInstruction::ListReveal { terms: terms.clone() }),
+        ("payToReveal", WrapperInstruction::PayToReveal { terms }),
+        ("settleReveal", WrapperInstruction::SettleReveal),
+        ("refundReveal", WrapperInstruction::RefundReveal),
+        ("requestAccess", WrapperInstruction::RequestAccess { level: 1 }),
+        ("approveAccess", WrapperInstruction::ApproveAccess { requester: key, level: 1 }),
+        ("denyAccess", WrapperInstruction::DenyAccess { requester: key }),
+        ("checkAccess", WrapperInstruction::CheckAccess { required_level: 1 }),
+        ("initializeFreeze", WrapperInstruction::InitializeFreeze { scope: FreezeScope::Program, authority: key }),
+        ("setFreeze", WrapperInstruction::SetFreeze { frozen: true }),
+        ("setFreezeAuthority", WrapperInstruction::SetFreezeAuthority { new_authority: key }),
+        ("resizeWrapper", WrapperInstruction::ResizeWrapper { capacity: 1 }),
+        ("confirmReveal", WrapperInstruction::ConfirmReveal),
+    ];
+    let instructions = idl["instructions"].as_array().unwrap();
+    assert_eq!(instructions.len(), variants.len());
+    for (instruction, (name, variant)) in instructions.iter().zip(variants) {
+        assert_eq!(instruction["name"], name);
+        assert_eq!(instruction["discriminant"]["value"], variant.try_to_vec().unwrap()[0]);
+    }
+    
+    // The wrapper header fields add up to the zero-copy header
+    let header = idl["accounts"].as_array().unwrap().iter().find(|account| account["name"] == "WrapperHeader").unwrap();
+    let header_len: u64 = header["type"]["fields"].as_array().unwrap().iter()
+        .map(|field| match &field["type"] {
+            serde_json::Value::String(ty) => match ty.as_str() {
+                "u8" => 1,
+                "u16" => 2,
+                "u64" => 8,
+                "publicKey" => 32,
+                ty => panic!("Unexpected header field type {}", ty),
+            },
+            ty => ty["array"][1].as_u64().unwrap(),
+        })
+        .sum();
+    assert_eq!(header_len as usize, HEADER_LEN);
+    
+    // Error codes and messages follow the error enum
+    let errors = [
+        PrivacyWrapperError::InvalidInstruction,
+        PrivacyWrapperError::NotNFTOwner,
+        PrivacyWrapperError::InvalidAccountData,
+        PrivacyWrapperError::AccountNotInitialized,
+        PrivacyWrapperError::ValueTooLong,
+        PrivacyWrapperError::AccessListFull,
+        PrivacyWrapperError::OutdatedAccountVersion,
+        PrivacyWrapperError::InvalidAgentName,
+        PrivacyWrapperError::NotAgentAuthority,
+        PrivacyWrapperError::RevealLocked,
+        PrivacyWrapperError::InvalidRevealKey,
+        PrivacyWrapperError::AlreadyRevealed,
+        PrivacyWrapperError::InvalidRecoveryConfig,
+        PrivacyWrapperError::NotGuardian,
+        PrivacyWrapperError::RecoveryPending,
+        PrivacyWrapperError::NoRecoveryPending,
+        PrivacyWrapperError::ChallengePeriodActive,
+        PrivacyWrapperError::InsufficientApprovals,
+        PrivacyWrapperError::PrivacySettingsLocked,
+        PrivacyWrapperError::InvalidLockDuration,
+        PrivacyWrapperError::NotCollectionAuthority,
+        PrivacyWrapperError::InvalidRevealTerms,
+        PrivacyWrapperError::RevealTermsChanged,
+        PrivacyWrapperError::PurchaseEscrowed,
+        PrivacyWrapperError::PurchaseNotEscrowed,
+        PrivacyWrapperError::SettlementWindowActive,
+        PrivacyWrapperError::InvalidRoyaltyRecipient,
+        PrivacyWrapperError::RequestQueueFull,
+        PrivacyWrapperError::NoAccessRequest,
+        PrivacyWrapperError::InsufficientAccess,
+        PrivacyWrapperError::AccountNotWritable,
+        PrivacyWrapperError::PurchaseStillActive,
+        PrivacyWrapperError::ProgramFrozen,
+        PrivacyWrapperError::CollectionFrozen,
+        PrivacyWrapperError::NotFreezeAuthority,
+        PrivacyWrapperError::RevealNotConfirmed,
+        PrivacyWrapperError::AccessRequestPending,
+        PrivacyWrapperError::AccessRequestMismatch,
+    ];
+    let idl_errors = idl["errors"].as_array().unwrap();
+    assert_eq!(idl_errors.len(), errors.len());
+    for (idl_error, error) in idl_errors.iter().zip(errors) {
+        assert_eq!(idl_error["code"], error as u32);
+        assert_eq!(idl_error["name"], format!("{:?}", error));
+        assert_eq!(idl_error["msg"], error.to_string());
+    }
+}
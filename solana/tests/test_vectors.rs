@@ -0,0 +1,332 @@
+//! Borsh compatibility vectors for clients in other languages
+//!
+//! Serializes every `WrapperInstruction` variant, a set of wrapper account
+//! states and an access request to `fixtures/wrapper_vectors.json`, with each value's
+//! bytes in hex next to its decoded form, so the JS client can assert
+//! byte-for-byte compatibility. Names follow the IDL; 64-bit integers are
+//! decimal strings and byte arrays are hex.
+//!
+//! The test fails when the layouts drift from the committed fixtures.
+//! Regenerate them after an intended layout change with:
+//!
+//! ```bash
+//! UPDATE_TEST_VECTORS=1 cargo test --test test_vectors
+//! ```
+
+use borsh::BorshSerialize;
+use privacy_wrapper::{
+    freeze::FreezeScope,
+    instruction::WrapperInstruction,
+    marketplace::{RevealTerms, RoyaltyShare},
+    request::AccessRequest,
+    reveal::RevealTime,
+    state::{PrivacyWrapper, PrivacyWrapperView, PrivacyWrapperViewMut, WRAPPER_VERSION},
+};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+
+/// Committed fixture file, relative to the program crate
+const FIXTURES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/fixtures/wrapper_vectors.json");
+
+/// Access entry capacity of the wrapper vectors
+const VECTOR_CAPACITY: usize = 4;
+
+/// Deterministic key for the vectors
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn reveal_time_json(reveal_time: &RevealTime) -> Value {
+    match reveal_time {
+        RevealTime::Timestamp(timestamp) => json!({ "kind": "Timestamp", "value": timestamp.to_string() }),
+        RevealTime::Slot(slot) => json!({ "kind": "Slot", "value": slot.to_string() }),
+    }
+}
+
+fn terms_json(terms: &RevealTerms) -> Value {
+    json!({
+        "price": terms.price.to_string(),
+        "accessLevel": terms.access_level,
+        "accessDuration": terms.access_duration.to_string(),
+        "settlementWindow": terms.settlement_window.to_string(),
+        "disputeWindow": terms.dispute_window.to_string(),
+        "royaltyShares": terms.royalty_shares.iter()
+            .map(|share| json!({ "recipient": share.recipient.to_string(), "basisPoints": share.basis_points }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn freeze_scope_json(scope: &FreezeScope) -> Value {
+    match scope {
+        FreezeScope::Program => json!({ "kind": "Program" }),
+        FreezeScope::Collection(collection) => json!({ "kind": "Collection", "value": collection.to_string() }),
+    }
+}
+
+fn keys_json(keys: &[Pubkey]) -> Vec<String> {
+    keys.iter().map(Pubkey::to_string).collect()
+}
+
+/// Decode an instruction into its IDL name and arguments
+fn instruction_json(instruction: &WrapperInstruction) -> Value {
+    let (name, args) = match instruction {
+        WrapperInstruction::CreateWrapper { privacy_config_hash } => {
+            ("createWrapper", json!({ "privacyConfigHash": privacy_config_hash }))
+        },
+        WrapperInstruction::UpdatePrivacy { new_privacy_config_hash } => {
+            ("updatePrivacy", json!({ "newPrivacyConfigHash": new_privacy_config_hash }))
+        },
+        WrapperInstruction::GrantAccess { grantee, level } => {
+            ("grantAccess", json!({ "grantee": grantee.to_string(), "level": level }))
+        },
+        WrapperInstruction::RevokeAccess { grantee } => ("revokeAccess", json!({ "grantee": grantee.to_string() })),
+        WrapperInstruction::MigrateWrapper => ("migrateWrapper", json!({})),
+        WrapperInstruction::RegisterAgent { name, metadata_uri } => {
+            ("registerAgent", json!({ "name": name, "metadataUri": metadata_uri }))
+        },
+        WrapperInstruction::UpdateAgent { metadata_uri } => ("updateAgent", json!({ "metadataUri": metadata_uri })),
+        WrapperInstruction::ScheduleReveal { label, reveal_time, key_commitment, sealed_key } => ("scheduleReveal", json!({
+            "label": label,
+            "revealTime": reveal_time_json(reveal_time),
+            "keyCommitment": hex(key_commitment),
+            "sealedKey": hex(sealed_key),
+        })),
+        WrapperInstruction::ClaimReveal { reveal_key } => ("claimReveal", json!({ "revealKey": hex(reveal_key) })),
+        WrapperInstruction::SetupRecovery { guardians, threshold, challenge_period, key_commitment, sealed_shares } => ("setupRecovery", json!({
+            "guardians": keys_json(guardians),
+            "threshold": threshold,
+            "challengePeriod": challenge_period.to_string(),
+            "keyCommitment": hex(key_commitment),
+            "sealedShares": sealed_shares.iter().map(|share| hex(share)).collect::<Vec<_>>(),
+        })),
+        WrapperInstruction::RequestRecovery { new_owner } => ("requestRecovery", json!({ "newOwner": new_owner.to_string() })),
+        WrapperInstruction::VetoRecovery => ("vetoRecovery", json!({})),
+        WrapperInstruction::CompleteRecovery => ("completeRecovery", json!({})),
+        WrapperInstruction::LockPrivacy { duration } => {
+            ("lockPrivacy", json!({ "duration": duration.map(|duration| duration.to_string()) }))
+        },
+        WrapperInstruction::CreateCollection { default_policy_hash, trusted_agents } => ("createCollection", json!({
+            "defaultPolicyHash": default_policy_hash,
+            "trustedAgents": keys_json(trusted_agents),
+        })),
+        WrapperInstruction::UpdateCollection { default_policy_hash, trusted_agents } => ("updateCollection", json!({
+            "defaultPolicyHash": default_policy_hash,
+            "trustedAgents": keys_json(trusted_agents),
+        })),
+        WrapperInstruction::JoinCollection { override_policy } => {
+            ("joinCollection", json!({ "overridePolicy": override_policy }))
+        },
+        WrapperInstruction::SetPolicyOverride { override_policy } => {
+            ("setPolicyOverride", json!({ "overridePolicy": override_policy }))
+        },
+        WrapperInstruction::ListReveal { terms } => ("listReveal", json!({ "terms": terms_json(terms) })),
+        WrapperInstruction::PayToReveal { terms } => ("payToReveal", json!({ "terms": terms_json(terms) })),
+        WrapperInstruction::SettleReveal => ("settleReveal", json!({})),
+        WrapperInstruction::RefundReveal => ("refundReveal", json!({})),
+        WrapperInstruction::RequestAccess { level } => ("requestAccess", json!({ "level": level })),
+        WrapperInstruction::ApproveAccess { requester, level } => {
+            ("approveAccess", json!({ "requester": requester.to_string(), "level": level }))
+        },
+        WrapperInstruction::DenyAccess { requester } => ("denyAccess", json!({ "requester": requester.to_string() })),
+        WrapperInstruction::CheckAccess { required_level } => ("checkAccess", json!({ "requiredLevel": required_level })),
+        WrapperInstruction::InitializeFreeze { scope, authority } => ("initializeFreeze", json!({
+            "scope": freeze_scope_json(scope),
+            "authority": authority.to_string(),
+        })),
+        WrapperInstruction::SetFreeze { frozen } => ("setFreeze", json!({ "frozen": frozen })),
+        WrapperInstruction::SetFreezeAuthority { new_authority } => {
+            ("setFreezeAuthority", json!({ "newAuthority": new_authority.to_string() }))
+        },
+        WrapperInstruction::ResizeWrapper { capacity } => ("resizeWrapper", json!({ "capacity": capacity })),
+        WrapperInstruction::ConfirmReveal => ("confirmReveal", json!({})),
+    };
+    
+    json!({ "name": name, "args": args })
+}
+
+/// Every instruction variant, with edge values where the layout has choices
+fn instructions() -> Vec<WrapperInstruction> {
+    vec![
+        WrapperInstruction::CreateWrapper { privacy_config_hash: "initial-privacy-config-hash".to_string() },
+        WrapperInstruction::CreateWrapper { privacy_config_hash: String::new() },
+        WrapperInstruction::UpdatePrivacy { new_privacy_config_hash: "updated-privacy-config-hash".to_string() },
+        WrapperInstruction::GrantAccess { grantee: key(3), level: 2 },
+        WrapperInstruction::GrantAccess { grantee: key(4), level: 255 },
+        WrapperInstruction::RevokeAccess { grantee: key(3) },
+        WrapperInstruction::MigrateWrapper,
+        WrapperInstruction::RegisterAgent {
+            name: "agent1.glitch.gang".to_string(),
+            metadata_uri: "https://agents.glitch.gang/agent1.json".to_string(),
+        },
+        WrapperInstruction::UpdateAgent { metadata_uri: "ipfs://agent1".to_string() },
+        WrapperInstruction::ScheduleReveal {
+            label: "mission".to_string(),
+            reveal_time: RevealTime::Timestamp(1_700_000_000),
+            key_commitment: [0xab; 32],
+            sealed_key: vec![1, 2, 3, 4],
+        },
+        WrapperInstruction::ScheduleReveal {
+            label: "lore".to_string(),
+            reveal_time: RevealTime::Slot(u64::MAX),
+            key_commitment: [0; 32],
+            sealed_key: vec![],
+        },
+        WrapperInstruction::ClaimReveal { reveal_key: [0x5a; 32] },
+        WrapperInstruction::SetupRecovery {
+            guardians: vec![key(10), key(11), key(12)],
+            threshold: 2,
+            challenge_period: 259_200,
+            key_commitment: [0xcd; 32],
+            sealed_shares: vec![vec![1; 3], vec![2; 3], vec![3; 3]],
+        },
+        WrapperInstruction::RequestRecovery { new_owner: key(13) },
+        WrapperInstruction::VetoRecovery,
+        WrapperInstruction::CompleteRecovery,
+        WrapperInstruction::LockPrivacy { duration: Some(86_400) },
+        WrapperInstruction::LockPrivacy { duration: None },
+        WrapperInstruction::CreateCollection {
+            default_policy_hash: "collection-policy-hash".to_string(),
+            trusted_agents: vec![key(20), key(21)],
+        },
+        WrapperInstruction::UpdateCollection { default_policy_hash: "collection-policy-hash-2".to_string(), trusted_agents: vec![] },
+        WrapperInstruction::JoinCollection { override_policy: false },
+        WrapperInstruction::SetPolicyOverride { override_policy: true },
+        WrapperInstruction::ListReveal {
+            terms: RevealTerms {
+                price: 250_000_000,
+                access_level: 2,
+                access_duration: 604_800,
+                settlement_window: -1,
+                dispute_window: 86_400,
+                royalty_shares: vec![
+                    RoyaltyShare { recipient: key(30), basis_points: 500 },
+                    RoyaltyShare { recipient: key(31), basis_points: 10_000 },
+                ],
+            },
+        },
+        WrapperInstruction::PayToReveal {
+            terms: RevealTerms {
+                price: 300_000_000,
+                access_level: 1,
+                access_duration: 3_600,
+                settlement_window: 600,
+                dispute_window: 600,
+                royalty_shares: vec![RoyaltyShare { recipient: key(32), basis_points: 250 }],
+            },
+        },
+        WrapperInstruction::SettleReveal,
+        WrapperInstruction::RefundReveal,
+        WrapperInstruction::RequestAccess { level: 1 },
+        WrapperInstruction::ApproveAccess { requester: key(5), level: 2 },
+        WrapperInstruction::DenyAccess { requester: key(6) },
+        WrapperInstruction::CheckAccess { required_level: 2 },
+        WrapperInstruction::InitializeFreeze { scope: FreezeScope::Program, authority: key(40) },
+        WrapperInstruction::InitializeFreeze { scope: FreezeScope::Collection(key(41)), authority: key(42) },
+        WrapperInstruction::SetFreeze { frozen: true },
+        WrapperInstruction::SetFreezeAuthority { new_authority: key(43) },
+        WrapperInstruction::ResizeWrapper { capacity: 300 },
+        WrapperInstruction::ConfirmReveal,
+    ]
+}
+
+/// Operation building up wrapper account state
+enum Step {
+    Grant(u8, u8),
+    Revoke(u8),
+}
+
+/// Wrapper account states covering grants and slot reuse
+fn wrapper_cases() -> Vec<(&'static str, String, Vec<Step>)> {
+    use Step::*;
+    
+    vec![
+        ("empty", "hash-empty".to_string(), vec![]),
+        ("grants", "hash-grants".to_string(), vec![Grant(3, 1), Grant(4, 255)]),
+        ("full", "f".repeat(96), vec![Grant(3, 1), Grant(4, 2), Grant(5, 2), Grant(6, 1)]),
+        ("revoked", "hash-revoked".to_string(), vec![Grant(3, 1), Grant(4, 2), Grant(7, 3), Revoke(3)]),
+    ]
+}
+
+/// Encode a wrapper account state and decode it back through the program's view
+fn wrapper_vector(case: &str, config_hash: &str, steps: &[Step]) -> Value {
+    let mut data = vec![0u8; PrivacyWrapper::get_account_size(VECTOR_CAPACITY)];
+    let mut view = PrivacyWrapperViewMut::initialize(&mut data, &key(1), &key(2), config_hash, 1_700_000_000).unwrap();
+    for step in steps {
+        match step {
+            Step::Grant(grantee, level) => view.grant(&key(*grantee), *level).unwrap(),
+            Step::Revoke(grantee) => assert!(view.revoke(&key(*grantee))),
+        }
+    }
+    
+    let view = PrivacyWrapperView::load(&data).unwrap();
+    let header = view.header();
+    json!({
+        "case": case,
+        "capacity": VECTOR_CAPACITY,
+        "state": {
+            "version": WRAPPER_VERSION,
+            "originalNftMint": header.original_nft_mint.to_string(),
+            "owner": header.owner.to_string(),
+            "privacyConfigHash": header.privacy_config_hash().unwrap(),
+            "lastUpdated": header.last_updated().to_string(),
+            "accessControls": view.entries().iter()
+                .map(|entry| json!({ "grantee": entry.grantee.to_string(), "level": entry.level }))
+                .collect::<Vec<_>>(),
+        },
+        "bytes": hex(&data),
+    })
+}
+
+/// Encode an access request account
+fn request_vector() -> Value {
+    let request = AccessRequest {
+        is_initialized: true,
+        wrapper: key(9),
+        requester: key(5),
+        level: 2,
+        requested_at: 1_700_000_000,
+    };
+    
+    json!({
+        "state": {
+            "wrapper": request.wrapper.to_string(),
+            "requester": request.requester.to_string(),
+            "level": request.level,
+            "requestedAt": request.requested_at.to_string(),
+        },
+        "bytes": hex(&request.try_to_vec().unwrap()),
+    })
+}
+
+fn vectors() -> Value {
+    json!({
+        "instructions": instructions().iter()
+            .map(|instruction| {
+                let mut vector = instruction_json(instruction);
+                vector["bytes"] = json!(hex(&instruction.try_to_vec().unwrap()));
+                vector
+            })
+            .collect::<Vec<_>>(),
+        "wrappers": wrapper_cases().iter()
+            .map(|(case, config_hash, steps)| wrapper_vector(case, config_hash, steps))
+            .collect::<Vec<_>>(),
+        "requests": [request_vector()],
+    })
+}
+
+#[test]
+fn test_vectors_match_fixtures() {
+    let vectors = vectors();
+    
+    if std::env::var("UPDATE_TEST_VECTORS").is_ok() {
+        std::fs::write(FIXTURES_PATH, serde_json::to_string_pretty(&vectors).unwrap() + "\n").unwrap();
+        return;
+    }
+    
+    let fixtures: Value = serde_json::from_str(&std::fs::read_to_string(FIXTURES_PATH).unwrap()).unwrap();
+    assert_eq!(vectors, fixtures, "Layouts changed; regenerate with UPDATE_TEST_VECTORS=1 if intended");
+}
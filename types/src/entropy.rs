@@ -0,0 +1,14 @@
+use serde::{Serialize, Deserialize};
+
+/// Entropy sources for quantum-grade key generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EntropySource {
+    /// Recent Solana blockchain hash
+    BlockchainHash,
+    /// System time-based entropy
+    TimeEntropy,
+    /// Cosmic background radiation simulation
+    CosmicNoise,
+    /// VRM agent interaction data
+    AgentBehavior,
+}
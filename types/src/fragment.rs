@@ -1,7 +1,33 @@
 use serde::{Serialize, Deserialize};
 
-use super::timeline::TimelineType;
-use super::storage::StorageLocation;
+use crate::timeline::TimelineType;
+
+/// Storage location for metadata fragments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StorageLocation {
+    /// On-chain Solana storage
+    Onchain {
+        /// Program ID
+        program_id: String,
+        /// Account address
+        account: String,
+    },
+    /// Arweave permanent storage
+    Arweave {
+        /// Transaction ID
+        transaction_id: String,
+    },
+    /// IPFS decentralized storage
+    Ipfs {
+        /// IPFS CID
+        cid: String,
+    },
+    /// Shadow realm (custom encrypted storage)
+    ShadowRealm {
+        /// Encrypted access info
+        access_path: String,
+    },
+}
 
 /// Metadata fragment with timeline association
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +80,8 @@ impl MetadataFragment {
     }
     
     /// Calculate storage cost (theoretical, based on storage type and size)
+    ///
+    /// Uses fixed rates; see the timeline shifter's `CostEstimator` for live pricing.
     pub fn storage_cost(&self) -> f64 {
         let bytes = self.data.len() as f64;
         
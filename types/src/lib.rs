@@ -0,0 +1,14 @@
+//! Model types shared by the Quantum Veil core and client
+//!
+//! The core engine and the client SDK both use these definitions, so values
+//! pass between them without conversion and the two can't drift apart.
+
+pub mod entropy;
+pub mod fragment;
+pub mod privacy_level;
+pub mod timeline;
+
+pub use entropy::EntropySource;
+pub use fragment::{MetadataFragment, StorageLocation};
+pub use privacy_level::PrivacyLevel;
+pub use timeline::TimelineType;
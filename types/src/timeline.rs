@@ -31,6 +31,18 @@ impl TimelineType {
         }
     }
     
+    /// Get a stable label for this timeline, e.g. `social` or `custom:<name>`
+    pub fn label(&self) -> String {
+        match self {
+            TimelineType::Primary => "primary".to_string(),
+            TimelineType::Identity => "identity".to_string(),
+            TimelineType::Activity => "activity".to_string(),
+            TimelineType::Social => "social".to_string(),
+            TimelineType::Financial => "financial".to_string(),
+            TimelineType::Custom(name) => format!("custom:{}", name),
+        }
+    }
+    
     /// Get recommended storage type for this timeline
     pub fn recommended_storage(&self) -> &'static str {
         match self {